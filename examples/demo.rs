@@ -0,0 +1,27 @@
+//! The demo timeline in a native window.
+//!
+//! Run with `cargo run --example demo --features examples-support`.
+
+use egui_keyframe::examples_support::DemoApp;
+
+struct App {
+    demo: DemoApp,
+}
+
+impl eframe::App for App {
+    fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        egui::CentralPanel::default().show(ctx, |ui| self.demo.show(ui));
+    }
+}
+
+fn main() -> eframe::Result {
+    eframe::run_native(
+        "egui-keyframe demo",
+        eframe::NativeOptions::default(),
+        Box::new(|_cc| {
+            Ok(Box::new(App {
+                demo: DemoApp::new(),
+            }))
+        }),
+    )
+}
@@ -0,0 +1,416 @@
+//! Import of BVH motion capture files.
+//!
+//! [`load_bvh`] reads the skeleton of a BVH file and turns every channel of
+//! every joint, e.g. `Hips.Xposition` or `Chest.Zrotation`, into a
+//! `Track<f32>` with one linear keyframe per frame. Mocap data has a key on
+//! every frame; set [`BvhOptions::simplify`] to reduce them with
+//! [`Track::simplify`] on import.
+//!
+//! Rotations stay in degrees as stored in the file. Channel order, joint
+//! offsets and end sites are kept on the [`BvhJoint`]s for hosts that rebuild
+//! the skeleton.
+
+use crate::core::keyframe::{Keyframe, KeyframeType};
+use crate::core::track::{Track, TrackId};
+use crate::model::SimpleAnimationModel;
+use crate::traits::PropertyRow;
+
+/// Options for [`load_bvh`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct BvhOptions {
+    /// Reduce the per-frame keys of each channel with [`Track::simplify`] at
+    /// this tolerance, in the channel's unit; `None` keeps every frame.
+    pub simplify: Option<f64>,
+}
+
+impl BvhOptions {
+    /// Reduce keys at `tolerance` on import.
+    pub fn simplify(mut self, tolerance: f64) -> Self {
+        self.simplify = Some(tolerance);
+        self
+    }
+}
+
+/// An animated channel of a joint.
+#[derive(Debug, Clone)]
+pub struct BvhChannel {
+    /// Channel name as in the file, e.g. `Xposition` or `Zrotation`.
+    pub name: String,
+    /// The animation, in seconds.
+    pub track: Track<f32>,
+}
+
+/// A joint of the skeleton with its channels.
+#[derive(Debug, Clone)]
+pub struct BvhJoint {
+    /// Joint name.
+    pub name: String,
+    /// Index of the parent joint in [`BvhImport::joints`]; `None` for roots.
+    pub parent: Option<usize>,
+    /// Offset from the parent joint.
+    pub offset: [f32; 3],
+    /// Offset of the end site, for joints ending a chain.
+    pub end_site: Option<[f32; 3]>,
+    /// Channels in file order.
+    pub channels: Vec<BvhChannel>,
+}
+
+/// Skeleton and channel tracks read by [`load_bvh`].
+#[derive(Debug, Clone)]
+pub struct BvhImport {
+    /// Joints in file order, parents before children.
+    pub joints: Vec<BvhJoint>,
+    /// Duration of a frame in seconds.
+    pub frame_time: f64,
+    /// Number of frames.
+    pub frame_count: usize,
+    /// One collapsible row per joint, nested like the skeleton, with its
+    /// channel rows before its child joints.
+    pub rows: Vec<PropertyRow>,
+}
+
+impl BvhImport {
+    /// Frame rate of the motion.
+    pub fn fps(&self) -> f64 {
+        if self.frame_time > 0.0 {
+            1.0 / self.frame_time
+        } else {
+            0.0
+        }
+    }
+
+    /// Get a channel by its track ID.
+    pub fn channel(&self, id: TrackId) -> Option<(&BvhJoint, &BvhChannel)> {
+        self.joints.iter().find_map(|joint| {
+            joint
+                .channels
+                .iter()
+                .find(|channel| channel.track.id == id)
+                .map(|channel| (joint, channel))
+        })
+    }
+
+    /// Move the channels into a [`SimpleAnimationModel`] with one group per
+    /// animated joint.
+    ///
+    /// The model has only two levels, so use [`Self::rows`] with a custom
+    /// provider to keep the skeleton hierarchy.
+    pub fn into_model(self) -> SimpleAnimationModel {
+        let end = self.frame_time * self.frame_count.saturating_sub(1) as f64;
+        let mut model = SimpleAnimationModel::new().with_time_range(0.0, end);
+        for joint in self.joints.into_iter().filter(|j| !j.channels.is_empty()) {
+            let group = model.add_group(joint.name);
+            for channel in joint.channels {
+                model.add_track(&group, channel.name, channel.track);
+            }
+        }
+        model
+    }
+}
+
+/// Error reading a BVH file.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BvhError {
+    /// The file ended before the skeleton or motion was complete.
+    UnexpectedEnd,
+    /// A token on `line` (1-based) is not what the format allows.
+    Syntax { line: usize, message: String },
+}
+
+impl std::fmt::Display for BvhError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnexpectedEnd => write!(f, "unexpected end of BVH file"),
+            Self::Syntax { line, message } => write!(f, "BVH line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for BvhError {}
+
+/// Parse a BVH file into joints with one track per channel.
+pub fn load_bvh(text: &str, options: BvhOptions) -> Result<BvhImport, BvhError> {
+    let mut tokens = Tokens::new(text);
+    tokens.expect("HIERARCHY")?;
+
+    let mut joints = Vec::new();
+    let mut channel_names: Vec<Vec<String>> = Vec::new();
+    let mut depths = Vec::new();
+    while tokens.peek() != Some("MOTION") {
+        let keyword = tokens.next_token()?;
+        if keyword != "ROOT" {
+            return Err(tokens.error(format!("expected `ROOT` or `MOTION`, found `{keyword}`")));
+        }
+        parse_joint(
+            &mut tokens,
+            None,
+            0,
+            &mut joints,
+            &mut channel_names,
+            &mut depths,
+        )?;
+    }
+    tokens.expect("MOTION")?;
+    tokens.expect("Frames:")?;
+    let frame_count: usize = tokens.parse()?;
+    tokens.expect("Frame")?;
+    tokens.expect("Time:")?;
+    let frame_time: f64 = tokens.parse()?;
+
+    let channel_count: usize = channel_names.iter().map(Vec::len).sum();
+    let mut tracks: Vec<Track<f32>> = (0..channel_count).map(|_| Track::new()).collect();
+    for frame in 0..frame_count {
+        let time = frame as f64 * frame_time;
+        for track in &mut tracks {
+            let value: f32 = tokens.parse()?;
+            track.add_keyframe(Keyframe::new(time, value).with_type(KeyframeType::Linear));
+        }
+    }
+    if let Some(tolerance) = options.simplify {
+        for track in &mut tracks {
+            track.simplify(tolerance);
+        }
+    }
+
+    let mut tracks = tracks.into_iter();
+    for (joint, names) in joints.iter_mut().zip(channel_names) {
+        joint.channels = names
+            .into_iter()
+            .zip(tracks.by_ref())
+            .map(|(name, track)| BvhChannel { name, track })
+            .collect();
+    }
+
+    let rows = skeleton_rows(&joints, &depths);
+    Ok(BvhImport {
+        joints,
+        frame_time,
+        frame_count,
+        rows,
+    })
+}
+
+/// Parse a joint's block after its `ROOT` or `JOINT` keyword, appending it
+/// and its descendants in pre-order.
+fn parse_joint(
+    tokens: &mut Tokens,
+    parent: Option<usize>,
+    depth: usize,
+    joints: &mut Vec<BvhJoint>,
+    channel_names: &mut Vec<Vec<String>>,
+    depths: &mut Vec<usize>,
+) -> Result<(), BvhError> {
+    let name = tokens.next_token()?.to_string();
+    tokens.expect("{")?;
+    let index = joints.len();
+    joints.push(BvhJoint {
+        name,
+        parent,
+        offset: [0.0; 3],
+        end_site: None,
+        channels: Vec::new(),
+    });
+    channel_names.push(Vec::new());
+    depths.push(depth);
+
+    loop {
+        match tokens.next_token()? {
+            "OFFSET" => joints[index].offset = tokens.parse_vec3()?,
+            "CHANNELS" => {
+                let count: usize = tokens.parse()?;
+                channel_names[index] = (0..count)
+                    .map(|_| tokens.next_token().map(str::to_string))
+                    .collect::<Result<_, _>>()?;
+            }
+            "JOINT" => parse_joint(
+                tokens,
+                Some(index),
+                depth + 1,
+                joints,
+                channel_names,
+                depths,
+            )?,
+            "End" => {
+                tokens.expect("Site")?;
+                tokens.expect("{")?;
+                tokens.expect("OFFSET")?;
+                joints[index].end_site = Some(tokens.parse_vec3()?);
+                tokens.expect("}")?;
+            }
+            "}" => return Ok(()),
+            other => return Err(tokens.error(format!("unexpected `{other}` in joint"))),
+        }
+    }
+}
+
+/// Joint rows nested by `depths`, each followed by its channel rows.
+fn skeleton_rows(joints: &[BvhJoint], depths: &[usize]) -> Vec<PropertyRow> {
+    let mut rows = Vec::new();
+    let mut path: Vec<&str> = Vec::new();
+    for (index, (joint, &depth)) in joints.iter().zip(depths).enumerate() {
+        path.truncate(depth);
+        path.push(&joint.name);
+        let id = path.join("/");
+        let has_children =
+            !joint.channels.is_empty() || joints.iter().any(|other| other.parent == Some(index));
+        rows.push(PropertyRow {
+            id: id.clone(),
+            label: joint.name.clone(),
+            depth,
+            can_collapse: has_children,
+            ..Default::default()
+        });
+        rows.extend(joint.channels.iter().map(|channel| PropertyRow {
+            id: format!("{id}/{}", channel.name),
+            label: channel.name.clone(),
+            depth: depth + 1,
+            track_id: Some(channel.track.id),
+            ..Default::default()
+        }));
+    }
+    rows
+}
+
+/// Whitespace separated tokens with their 1-based line numbers.
+struct Tokens<'a> {
+    tokens: Vec<(usize, &'a str)>,
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn new(text: &'a str) -> Self {
+        let tokens = text
+            .lines()
+            .enumerate()
+            .flat_map(|(line, content)| {
+                content
+                    .split_whitespace()
+                    .map(move |token| (line + 1, token))
+            })
+            .collect();
+        Self {
+            tokens,
+            position: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).map(|(_, token)| *token)
+    }
+
+    fn next_token(&mut self) -> Result<&'a str, BvhError> {
+        let token = self.peek().ok_or(BvhError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), BvhError> {
+        let token = self.next_token()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{expected}`, found `{token}`")))
+        }
+    }
+
+    fn parse<T: std::str::FromStr>(&mut self) -> Result<T, BvhError> {
+        let token = self.next_token()?;
+        token
+            .parse()
+            .map_err(|_| self.error(format!("expected a number, found `{token}`")))
+    }
+
+    fn parse_vec3(&mut self) -> Result<[f32; 3], BvhError> {
+        Ok([self.parse()?, self.parse()?, self.parse()?])
+    }
+
+    /// A syntax error at the last read token.
+    fn error(&self, message: String) -> BvhError {
+        let line = self
+            .tokens
+            .get(self.position.saturating_sub(1))
+            .map_or(0, |(line, _)| *line);
+        BvhError::Syntax { line, message }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::AnimationDataProvider;
+
+    const WALK: &str = "HIERARCHY
+ROOT Hips
+{
+  OFFSET 0.0 0.0 0.0
+  CHANNELS 6 Xposition Yposition Zposition Zrotation Xrotation Yrotation
+  JOINT Chest
+  {
+    OFFSET 0.0 5.2 0.0
+    CHANNELS 3 Zrotation Xrotation Yrotation
+    End Site
+    {
+      OFFSET 0.0 4.0 0.0
+    }
+  }
+}
+MOTION
+Frames: 5
+Frame Time: 0.04
+0 90 0 0 0 0 0 0 0
+1 90 0 0 0 0 10 0 0
+2 90 0 0 0 0 20 0 0
+3 90 0 0 0 0 30 0 0
+4 90 0 0 0 0 40 0 0
+";
+
+    #[test]
+    fn imports_skeleton_channels_and_rows() {
+        let import = load_bvh(WALK, BvhOptions::default()).unwrap();
+        assert_eq!(import.fps(), 25.0);
+        assert_eq!(import.joints.len(), 2);
+        let chest = &import.joints[1];
+        assert_eq!(chest.parent, Some(0));
+        assert_eq!(chest.end_site, Some([0.0, 4.0, 0.0]));
+        assert_eq!(chest.channels[0].name, "Zrotation");
+        assert_eq!(chest.channels[0].track.evaluate(0.08), Some(20.0));
+        assert_eq!(import.joints[0].channels[0].track.len(), 5);
+
+        let rows: Vec<_> = import
+            .rows
+            .iter()
+            .map(|row| (row.id.as_str(), row.depth))
+            .collect();
+        assert_eq!(rows[0], ("Hips", 0));
+        assert_eq!(rows[1], ("Hips/Xposition", 1));
+        assert_eq!(rows[7], ("Hips/Chest", 1));
+        assert_eq!(rows[8], ("Hips/Chest/Zrotation", 2));
+
+        let simplified = load_bvh(WALK, BvhOptions::default().simplify(0.01)).unwrap();
+        let x = &simplified.joints[0].channels[0].track;
+        assert_eq!(x.len(), 2);
+        assert!((x.evaluate(0.06).unwrap() - 1.5).abs() < 1e-4);
+
+        let model = import.into_model();
+        assert_eq!(model.property_rows().len(), 11);
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert_eq!(
+            load_bvh(
+                "HIERARCHY\nROOT Hips\n{\n  OFFSET 0 zero 0\n",
+                BvhOptions::default()
+            )
+            .unwrap_err(),
+            BvhError::Syntax {
+                line: 4,
+                message: "expected a number, found `zero`".to_string()
+            }
+        );
+        assert_eq!(
+            load_bvh("HIERARCHY\nROOT Hips\n{\n", BvhOptions::default()).unwrap_err(),
+            BvhError::UnexpectedEnd
+        );
+    }
+}
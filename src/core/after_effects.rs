@@ -0,0 +1,529 @@
+//! After Effects keyframe clipboard text.
+//!
+//! After Effects copies keyframes to the system clipboard as tab-separated
+//! text that other tools can read and paste back, shown here with tabs as
+//! spaces:
+//!
+//! ```text
+//! Adobe After Effects 8.0 Keyframe Data
+//!
+//!     Units Per Second    24
+//!     Source Width    1920
+//!     Source Height    1080
+//!     Source Pixel Aspect Ratio    1
+//!     Comp Pixel Aspect Ratio    1
+//!
+//! Transform    Position
+//!     Frame    X pixels    Y pixels    Z pixels
+//!     0    960    540    0
+//!     12    1200    540    0
+//!
+//! End of Keyframe Data
+//! ```
+//!
+//! [`AeKeyframeData::parse`] turns each property into one [`Clipboard`] per
+//! dimension that pastes into a DopeSheet row with [`AeChannel::paste`] or
+//! [`Clipboard::paste`]; [`AeKeyframeData::to_text`] writes copied keyframes
+//! back out. Frames are converted with the text's frame rate and pixel and
+//! percent values can be mapped to fractions via [`AeUnits`].
+//!
+//! The format carries no easing, so imported keyframes are linear.
+
+use super::clipboard::{Clipboard, ClipboardKeyframe, PasteOptions};
+use super::history::KeyframeSnapshot;
+use super::keyframe::{BezierHandles, KeyframeId, KeyframeType, Spring};
+use super::time::TimeTick;
+use super::track::TrackId;
+use crate::traits::{AnimationCommand, AnimationDataProvider};
+
+/// First line of the text, with any version number.
+const HEADER_PREFIX: &str = "Adobe After Effects";
+const HEADER_SUFFIX: &str = "Keyframe Data";
+/// Version written by [`AeKeyframeData::to_text`].
+const HEADER: &str = "Adobe After Effects 8.0 Keyframe Data";
+const FOOTER: &str = "End of Keyframe Data";
+
+/// Frame rate and composition size used to convert After Effects units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AeUnits {
+    /// Frames per second.
+    pub fps: f64,
+    /// Composition width in pixels.
+    pub width: f64,
+    /// Composition height in pixels.
+    pub height: f64,
+    /// Map `X pixels` and `Y pixels` to fractions of the composition size
+    /// and `percent` to fractions of one.
+    pub normalize: bool,
+}
+
+impl Default for AeUnits {
+    fn default() -> Self {
+        Self {
+            fps: 24.0,
+            width: 1920.0,
+            height: 1080.0,
+            normalize: false,
+        }
+    }
+}
+
+impl AeUnits {
+    /// Units at `fps` with the default composition size.
+    pub fn new(fps: f64) -> Self {
+        Self {
+            fps,
+            ..Self::default()
+        }
+    }
+
+    /// Set the composition size in pixels.
+    pub fn composition_size(mut self, width: f64, height: f64) -> Self {
+        self.width = width;
+        self.height = height;
+        self
+    }
+
+    /// Set whether pixel and percent values are mapped to fractions.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Factor from track values to values in `unit`.
+    fn scale(&self, unit: &str) -> f64 {
+        if !self.normalize {
+            return 1.0;
+        }
+        match unit {
+            "X pixels" => self.width,
+            "Y pixels" => self.height,
+            "percent" => 100.0,
+            _ => 1.0,
+        }
+    }
+}
+
+/// One dimension of an [`AeProperty`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct AeChannel {
+    /// Unit column name, e.g. `X pixels`, `percent` or empty.
+    pub unit: String,
+    /// Time of the first keyframe.
+    pub start: TimeTick,
+    /// The keyframes, relative to `start`.
+    pub keyframes: Clipboard,
+}
+
+impl AeChannel {
+    /// Create a channel.
+    pub fn new(unit: impl Into<String>, start: TimeTick, keyframes: Clipboard) -> Self {
+        Self {
+            unit: unit.into(),
+            start,
+            keyframes,
+        }
+    }
+
+    /// Copy keyframes from a provider, starting at the earliest of them.
+    ///
+    /// Keyframes that do not exist are skipped.
+    pub fn copy<P: AnimationDataProvider + ?Sized>(
+        provider: &P,
+        unit: impl Into<String>,
+        keyframe_ids: impl IntoIterator<Item = KeyframeId>,
+    ) -> Self {
+        let snapshots: Vec<KeyframeSnapshot> = keyframe_ids
+            .into_iter()
+            .filter_map(|id| KeyframeSnapshot::capture(provider, id))
+            .collect();
+        let start = snapshots
+            .iter()
+            .map(|snapshot| snapshot.position)
+            .reduce(TimeTick::min)
+            .unwrap_or(TimeTick::zero());
+        let keyframes =
+            Clipboard::from_keyframes(snapshots.iter().map(|snapshot| ClipboardKeyframe {
+                channel: 0,
+                offset: snapshot.position - start,
+                value: snapshot.value,
+                handles: snapshot.handles,
+                keyframe_type: snapshot.keyframe_type,
+                spring: snapshot.spring,
+            }));
+        Self::new(unit, start, keyframes)
+    }
+
+    /// Command pasting the keyframes into `track_id` at their original
+    /// times.
+    pub fn paste(&self, track_id: TrackId) -> AnimationCommand {
+        self.keyframes
+            .paste(track_id, self.start, PasteOptions::default())
+    }
+
+    /// Absolute time and value of each keyframe.
+    fn points(&self) -> impl Iterator<Item = (f64, f64)> + '_ {
+        self.keyframes
+            .keyframes()
+            .iter()
+            .map(|kf| ((self.start + kf.offset).value(), kf.value))
+    }
+
+    /// Linearly interpolated value at `time`, held before the first and
+    /// after the last keyframe.
+    fn value_at(&self, time: f64) -> f64 {
+        let mut previous: Option<(f64, f64)> = None;
+        for (t, value) in self.points() {
+            if t >= time {
+                return match previous {
+                    Some((t0, v0)) if t > t0 => v0 + (value - v0) * (time - t0) / (t - t0),
+                    _ => value,
+                };
+            }
+            previous = Some((t, value));
+        }
+        previous.map_or(0.0, |(_, value)| value)
+    }
+}
+
+/// A keyframed After Effects property such as `Transform / Position`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AeProperty {
+    /// Property path, e.g. `["Transform", "Position"]` or
+    /// `["Effects", "Gaussian Blur #1", "Blurriness"]`.
+    pub path: Vec<String>,
+    /// One channel per dimension.
+    pub channels: Vec<AeChannel>,
+}
+
+impl AeProperty {
+    /// Create a property without channels.
+    pub fn new(path: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        Self {
+            path: path.into_iter().map(Into::into).collect(),
+            channels: Vec::new(),
+        }
+    }
+
+    /// Add a channel.
+    pub fn with_channel(mut self, channel: AeChannel) -> Self {
+        self.channels.push(channel);
+        self
+    }
+
+    /// Last segment of the path, e.g. `Position`.
+    pub fn name(&self) -> &str {
+        self.path.last().map_or("", String::as_str)
+    }
+}
+
+/// Error parsing After Effects keyframe text.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AeError {
+    /// The text does not start with the After Effects keyframe data header.
+    NotKeyframeData,
+    /// `line` (1-based) is not what the format allows.
+    Syntax { line: usize, message: String },
+}
+
+impl std::fmt::Display for AeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotKeyframeData => write!(f, "not After Effects keyframe data"),
+            Self::Syntax { line, message } => {
+                write!(f, "After Effects keyframe data line {line}: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for AeError {}
+
+/// Keyframes copied from or to After Effects.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct AeKeyframeData {
+    /// Frame rate and composition size of the text.
+    pub units: AeUnits,
+    /// Keyframed properties in text order.
+    pub properties: Vec<AeProperty>,
+}
+
+impl AeKeyframeData {
+    /// Create empty data written with `units`.
+    pub fn new(units: AeUnits) -> Self {
+        Self {
+            units,
+            properties: Vec::new(),
+        }
+    }
+
+    /// Add a property.
+    pub fn with_property(mut self, property: AeProperty) -> Self {
+        self.properties.push(property);
+        self
+    }
+
+    /// The first property named `name`, see [`AeProperty::name`].
+    pub fn property(&self, name: &str) -> Option<&AeProperty> {
+        self.properties
+            .iter()
+            .find(|property| property.name() == name)
+    }
+
+    /// Parse clipboard text copied from After Effects.
+    ///
+    /// The frame rate and composition size in the text replace those of
+    /// `units`; `units` supplies the ones the text leaves out and whether
+    /// values are normalized.
+    pub fn parse(text: &str, units: AeUnits) -> Result<Self, AeError> {
+        let mut lines = text
+            .lines()
+            .map(|line| line.trim_end_matches('\r'))
+            .enumerate()
+            .skip_while(|(_, line)| line.trim().is_empty());
+        match lines.next() {
+            Some((_, line))
+                if line.trim().starts_with(HEADER_PREFIX)
+                    && line.trim().ends_with(HEADER_SUFFIX) => {}
+            _ => return Err(AeError::NotKeyframeData),
+        }
+
+        let mut units = units;
+        let mut properties: Vec<(Vec<String>, Vec<RawChannel>)> = Vec::new();
+        for (index, line) in lines {
+            let error = |message: String| AeError::Syntax {
+                line: index + 1,
+                message,
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.trim() == FOOTER {
+                break;
+            }
+            if !line.starts_with('\t') {
+                properties.push((line.split('\t').map(str::to_string).collect(), Vec::new()));
+                continue;
+            }
+
+            let fields = fields(line);
+            let Some((_, channels)) = properties.last_mut() else {
+                let [key, value] = fields[..] else {
+                    return Err(error(format!("expected `key<TAB>value`, found `{line}`")));
+                };
+                let number = || {
+                    value
+                        .trim()
+                        .parse::<f64>()
+                        .map_err(|_| error(format!("`{key}` is not a number: `{value}`")))
+                };
+                match key {
+                    "Units Per Second" => units.fps = number()?,
+                    "Source Width" => units.width = number()?,
+                    "Source Height" => units.height = number()?,
+                    _ => {}
+                }
+                continue;
+            };
+
+            if fields[0] == "Frame" {
+                *channels = fields[1..]
+                    .iter()
+                    .map(|unit| (unit.to_string(), Vec::new()))
+                    .collect();
+                if channels.is_empty() {
+                    channels.push((String::new(), Vec::new()));
+                }
+                continue;
+            }
+            if channels.is_empty() {
+                return Err(error("keyframe before the `Frame` header".to_string()));
+            }
+            if fields.len() != channels.len() + 1 {
+                return Err(error(format!(
+                    "expected a frame and {} values, found {} fields",
+                    channels.len(),
+                    fields.len()
+                )));
+            }
+            let numbers = fields
+                .iter()
+                .map(|field| field.trim().parse::<f64>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| error(format!("expected numbers, found `{}`", line.trim())))?;
+            for ((_, keys), value) in channels.iter_mut().zip(&numbers[1..]) {
+                keys.push((numbers[0], *value));
+            }
+        }
+
+        if units.fps <= 0.0 {
+            return Err(AeError::Syntax {
+                line: 1,
+                message: "`Units Per Second` is not positive".to_string(),
+            });
+        }
+        let properties = properties
+            .into_iter()
+            .filter(|(_, channels)| !channels.is_empty())
+            .map(|(path, channels)| AeProperty {
+                path,
+                channels: channels
+                    .into_iter()
+                    .map(|(unit, keys)| {
+                        let scale = units.scale(&unit);
+                        let keyframes: Vec<ClipboardKeyframe> = keys
+                            .iter()
+                            .map(|&(frame, value)| ClipboardKeyframe {
+                                channel: 0,
+                                offset: TimeTick::from_frames(frame, units.fps),
+                                value: value / scale,
+                                handles: BezierHandles::linear(),
+                                keyframe_type: KeyframeType::Linear,
+                                spring: Spring::default(),
+                            })
+                            .collect();
+                        let start = keyframes
+                            .iter()
+                            .map(|kf| kf.offset)
+                            .reduce(TimeTick::min)
+                            .unwrap_or(TimeTick::zero());
+                        AeChannel::new(unit, start, Clipboard::from_keyframes(keyframes))
+                    })
+                    .collect(),
+            })
+            .collect();
+        Ok(Self { units, properties })
+    }
+
+    /// Encode as text After Effects pastes.
+    ///
+    /// Channels of a property with keyframes at different times get a row
+    /// for each time, with linearly interpolated values where a channel has
+    /// no keyframe.
+    pub fn to_text(&self) -> String {
+        let units = &self.units;
+        let mut text = format!(
+            "{HEADER}\n\n\tUnits Per Second\t{}\n\tSource Width\t{}\n\tSource Height\t{}\n\
+             \tSource Pixel Aspect Ratio\t1\n\tComp Pixel Aspect Ratio\t1\n",
+            number(units.fps),
+            number(units.width),
+            number(units.height),
+        );
+        for property in &self.properties {
+            if property.channels.is_empty() {
+                continue;
+            }
+            text.push('\n');
+            text.push_str(&property.path.join("\t"));
+            text.push_str("\n\tFrame");
+            for channel in &property.channels {
+                text.push('\t');
+                text.push_str(&channel.unit);
+            }
+            text.push_str("\t\n");
+
+            let mut times: Vec<f64> = property
+                .channels
+                .iter()
+                .flat_map(|channel| channel.points().map(|(time, _)| time))
+                .collect();
+            times.sort_by(f64::total_cmp);
+            times.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+            for time in times {
+                text.push('\t');
+                text.push_str(&number(time * units.fps));
+                for channel in &property.channels {
+                    let value = channel.value_at(time) * units.scale(&channel.unit);
+                    text.push('\t');
+                    text.push_str(&number(value));
+                }
+                text.push_str("\t\n");
+            }
+        }
+        text.push('\n');
+        text.push_str(FOOTER);
+        text.push('\n');
+        text
+    }
+}
+
+/// Unit and absolute `(frame, value)` pairs of a channel being parsed.
+type RawChannel = (String, Vec<(f64, f64)>);
+
+/// Tab-separated fields of an indented line, without the leading and
+/// trailing tab.
+fn fields(line: &str) -> Vec<&str> {
+    let line = line.strip_prefix('\t').unwrap_or(line);
+    let line = line.strip_suffix('\t').unwrap_or(line);
+    line.split('\t').collect()
+}
+
+/// `value` rounded to six decimals, without a trailing `.0`.
+fn number(value: f64) -> String {
+    let rounded = (value * 1e6).round() / 1e6;
+    // Avoid `-0`.
+    format!("{}", rounded + 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COPIED: &str = "Adobe After Effects 8.0 Keyframe Data\r\n\
+        \r\n\
+        \tUnits Per Second\t25\r\n\
+        \tSource Width\t1000\r\n\
+        \tSource Height\t500\r\n\
+        \tSource Pixel Aspect Ratio\t1\r\n\
+        \tComp Pixel Aspect Ratio\t1\r\n\
+        \r\n\
+        Transform\tPosition\r\n\
+        \tFrame\tX pixels\tY pixels\tZ pixels\t\r\n\
+        \t0\t500\t250\t0\t\r\n\
+        \t25\t1000\t0\t0\t\r\n\
+        \r\n\
+        Effects\tSlider Control\tSlider\r\n\
+        \tFrame\t\t\r\n\
+        \t50\t7\t\r\n\
+        \r\n\
+        End of Keyframe Data\r\n";
+
+    #[test]
+    fn parses_and_writes_after_effects_text() {
+        let data = AeKeyframeData::parse(COPIED, AeUnits::new(30.0).normalize(true)).unwrap();
+        assert_eq!(data.units.fps, 25.0);
+        assert_eq!((data.units.width, data.units.height), (1000.0, 500.0));
+
+        let position = data.property("Position").unwrap();
+        assert_eq!(position.path, ["Transform", "Position"]);
+        let units: Vec<_> = position.channels.iter().map(|c| c.unit.as_str()).collect();
+        assert_eq!(units, ["X pixels", "Y pixels", "Z pixels"]);
+        let x: Vec<_> = position.channels[0].points().collect();
+        assert_eq!(x, [(0.0, 0.5), (1.0, 1.0)]);
+
+        let slider = data.property("Slider").unwrap();
+        assert_eq!(slider.channels[0].unit, "");
+        assert_eq!(slider.channels[0].start, TimeTick::new(2.0));
+        let track_id = TrackId::new();
+        let AnimationCommand::PasteKeyframes { keyframes } = slider.channels[0].paste(track_id)
+        else {
+            panic!("expected PasteKeyframes");
+        };
+        assert_eq!(keyframes[0].position, TimeTick::new(2.0));
+        assert_eq!(keyframes[0].value, 7.0);
+
+        let text = data.to_text();
+        assert!(text.contains("\tFrame\tX pixels\tY pixels\tZ pixels\t\n\t0\t500\t250\t0\t\n"));
+        assert_eq!(AeKeyframeData::parse(&text, data.units), Ok(data));
+
+        assert_eq!(
+            AeKeyframeData::parse("hello", AeUnits::default()),
+            Err(AeError::NotKeyframeData)
+        );
+        let broken = COPIED.replace("\t25\t1000\t0\t0\t", "\t25\t1000\t0\t");
+        assert!(matches!(
+            AeKeyframeData::parse(&broken, AeUnits::default()),
+            Err(AeError::Syntax { line: 12, .. })
+        ));
+    }
+}
@@ -0,0 +1,210 @@
+//! Packed keyframe storage for many read-mostly tracks.
+//!
+//! A [`Track`] keeps its keyframes in an `IndexMap` so they can be edited by
+//! ID, which costs a hash table and a duplicate ID per key and one set of
+//! allocations per track. For thousands of mocap channels that are played
+//! back more than edited, [`TrackArena`] moves the keyframes of all tracks
+//! into one time-sorted buffer instead:
+//!
+//! ```ignore
+//! let mut arena = TrackArena::new();
+//! let hips_x = arena.insert(track);
+//! let value = arena.evaluate(hips_x, 1.5);
+//! ```
+//!
+//! Take a track back out with [`TrackArena::to_track`] to edit it.
+
+use super::extrapolation::Extrapolation;
+use super::interpolation::{Lerp, interpolate_with_extrapolation};
+use super::keyframe::Keyframe;
+use super::time::TimeTick;
+use super::track::{MemoryUsage, Track, TrackId, retime_to_local};
+
+/// Index of a track in a [`TrackArena`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ArenaTrack(usize);
+
+/// Attributes and keyframe range of a packed track.
+#[derive(Debug, Clone)]
+struct PackedTrack {
+    id: TrackId,
+    start: usize,
+    len: usize,
+    extrapolation_pre: Extrapolation,
+    extrapolation_post: Extrapolation,
+    time_offset: TimeTick,
+    time_scale: f64,
+}
+
+/// Keyframes of many tracks in one buffer, each track's sorted by time.
+#[derive(Debug, Clone)]
+pub struct TrackArena<T> {
+    keyframes: Vec<Keyframe<T>>,
+    tracks: Vec<PackedTrack>,
+}
+
+impl<T: Clone> Default for TrackArena<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> TrackArena<T> {
+    /// Create an empty arena.
+    pub fn new() -> Self {
+        Self {
+            keyframes: Vec::new(),
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Create an empty arena with room for `tracks` tracks holding
+    /// `keyframes` keyframes in total.
+    pub fn with_capacity(tracks: usize, keyframes: usize) -> Self {
+        Self {
+            keyframes: Vec::with_capacity(keyframes),
+            tracks: Vec::with_capacity(tracks),
+        }
+    }
+
+    /// Move the keyframes of `track` into the arena.
+    pub fn insert(&mut self, track: Track<T>) -> ArenaTrack {
+        let start = self.keyframes.len();
+        let mut keyframes: Vec<Keyframe<T>> = track.iter().cloned().collect();
+        keyframes.sort_by(|a, b| {
+            a.position
+                .partial_cmp(&b.position)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        self.keyframes.extend(keyframes);
+        self.tracks.push(PackedTrack {
+            id: track.id,
+            start,
+            len: self.keyframes.len() - start,
+            extrapolation_pre: track.extrapolation_pre,
+            extrapolation_post: track.extrapolation_post,
+            time_offset: track.time_offset,
+            time_scale: track.time_scale,
+        });
+        ArenaTrack(self.tracks.len() - 1)
+    }
+
+    /// Number of tracks.
+    pub fn len(&self) -> usize {
+        self.tracks.len()
+    }
+
+    /// Check if the arena has no tracks.
+    pub fn is_empty(&self) -> bool {
+        self.tracks.is_empty()
+    }
+
+    /// Find a track by its ID.
+    pub fn find(&self, id: TrackId) -> Option<ArenaTrack> {
+        self.tracks
+            .iter()
+            .position(|track| track.id == id)
+            .map(ArenaTrack)
+    }
+
+    /// ID of a track.
+    pub fn track_id(&self, track: ArenaTrack) -> Option<TrackId> {
+        self.tracks.get(track.0).map(|packed| packed.id)
+    }
+
+    /// Keyframes of a track, sorted by time.
+    pub fn keyframes(&self, track: ArenaTrack) -> &[Keyframe<T>] {
+        self.tracks.get(track.0).map_or(&[], |packed| {
+            &self.keyframes[packed.start..packed.start + packed.len]
+        })
+    }
+
+    /// Evaluate a track at a global position like [`Track::evaluate`].
+    ///
+    /// Returns `None` if the track does not exist or has no keyframes.
+    pub fn evaluate(&self, track: ArenaTrack, position: impl Into<TimeTick>) -> Option<T>
+    where
+        T: Lerp,
+    {
+        let packed = self.tracks.get(track.0)?;
+        let keyframes: Vec<&Keyframe<T>> = self.keyframes(track).iter().collect();
+        interpolate_with_extrapolation(
+            &keyframes,
+            retime_to_local(position.into(), packed.time_offset, packed.time_scale),
+            packed.extrapolation_pre,
+            packed.extrapolation_post,
+        )
+        .map(|triple| triple.lerp())
+    }
+
+    /// Copy a track out of the arena for editing.
+    pub fn to_track(&self, track: ArenaTrack) -> Option<Track<T>> {
+        let packed = self.tracks.get(track.0)?;
+        let mut result = Track::with_id(packed.id)
+            .with_extrapolation(packed.extrapolation_pre, packed.extrapolation_post)
+            .with_retime(packed.time_offset, packed.time_scale);
+        for keyframe in self.keyframes(track) {
+            result.add_keyframe(keyframe.clone());
+        }
+        Some(result)
+    }
+
+    /// Release spare capacity.
+    pub fn shrink_to_fit(&mut self) {
+        self.keyframes.shrink_to_fit();
+        self.tracks.shrink_to_fit();
+    }
+
+    /// Approximate heap memory held by the arena.
+    ///
+    /// Like [`Track::memory_usage`], heap memory owned by the values
+    /// themselves is not counted.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let keyframe = size_of::<Keyframe<T>>();
+        let track = size_of::<PackedTrack>();
+        MemoryUsage {
+            keyframes: self.keyframes.len(),
+            used_bytes: self.keyframes.len() * keyframe + self.tracks.len() * track,
+            allocated_bytes: self.keyframes.capacity() * keyframe + self.tracks.capacity() * track,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packs_and_evaluates_tracks() {
+        let mut tracks = Vec::new();
+        for channel in 0..10 {
+            let mut track = Track::<f32>::new().with_retime(1.0, 1.0);
+            for frame in (0..50).rev() {
+                track.add_keyframe(Keyframe::new(frame as f64, (channel * frame) as f32));
+            }
+            tracks.push(track);
+        }
+        let separate: MemoryUsage = tracks.iter().map(Track::memory_usage).sum();
+        let expected = tracks[3].evaluate(10.5);
+        let id = tracks[3].id;
+
+        let mut arena = TrackArena::new();
+        for track in tracks {
+            arena.insert(track);
+        }
+        arena.shrink_to_fit();
+
+        let third = arena.find(id).unwrap();
+        assert_eq!(arena.len(), 10);
+        assert_eq!(arena.evaluate(third, 10.5), expected);
+        assert_eq!(arena.keyframes(third)[1].position, TimeTick::new(1.0));
+
+        let usage = arena.memory_usage();
+        assert_eq!(usage.keyframes, separate.keyframes);
+        assert!(usage.used_bytes < separate.used_bytes);
+
+        let track = arena.to_track(third).unwrap();
+        assert_eq!(track.id, id);
+        assert_eq!(track.evaluate(10.5), expected);
+    }
+}
@@ -0,0 +1,210 @@
+//! Keyframes from audio amplitude.
+//!
+//! [`amplitude_track`] turns audio samples into a `Track<f32>` that follows
+//! their loudness, e.g. to drive a scale or brightness from music:
+//!
+//! ```ignore
+//! let options = AmplitudeOptions::default().release(0.2).simplify(0.02);
+//! let track = amplitude_track(&mix_to_mono(&samples, 2), 44_100.0, options);
+//! ```
+//!
+//! The loudness is the RMS of a window of samples around each keyframe,
+//! smoothed with separate attack and release times like a VU meter.
+//! Decoding audio files is left to the host.
+
+use super::keyframe::{Keyframe, KeyframeType};
+use super::track::Track;
+
+/// Options for [`amplitude_track`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AmplitudeOptions {
+    /// Keyframes per second.
+    pub rate: f64,
+    /// Length in seconds of the RMS window centered on each keyframe.
+    pub window: f64,
+    /// Time in seconds the envelope takes to rise about 63% of the way to a
+    /// louder level; `0` follows instantly.
+    pub attack: f64,
+    /// Time in seconds the envelope takes to fall about 63% of the way to a
+    /// quieter level; `0` follows instantly.
+    pub release: f64,
+    /// Scale values so the loudest keyframe is `1`.
+    pub normalize: bool,
+    /// Reduce keys with [`Track::simplify`] at this tolerance; `None` keeps
+    /// one key per `1 / rate` seconds.
+    pub simplify: Option<f64>,
+}
+
+impl Default for AmplitudeOptions {
+    fn default() -> Self {
+        Self {
+            rate: 30.0,
+            window: 0.05,
+            attack: 0.0,
+            release: 0.15,
+            normalize: true,
+            simplify: None,
+        }
+    }
+}
+
+impl AmplitudeOptions {
+    /// Set the keyframes per second.
+    pub fn rate(mut self, rate: f64) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    /// Set the RMS window length in seconds.
+    pub fn window(mut self, window: f64) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Set the attack time in seconds.
+    pub fn attack(mut self, attack: f64) -> Self {
+        self.attack = attack;
+        self
+    }
+
+    /// Set the release time in seconds.
+    pub fn release(mut self, release: f64) -> Self {
+        self.release = release;
+        self
+    }
+
+    /// Set whether values are normalized to a peak of `1`.
+    pub fn normalize(mut self, normalize: bool) -> Self {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Reduce keys at `tolerance`.
+    pub fn simplify(mut self, tolerance: f64) -> Self {
+        self.simplify = Some(tolerance);
+        self
+    }
+}
+
+/// Average interleaved samples of `channels` channels into one channel.
+pub fn mix_to_mono(samples: &[f32], channels: usize) -> Vec<f32> {
+    if channels <= 1 {
+        return samples.to_vec();
+    }
+    samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect()
+}
+
+/// RMS of mono `samples` in a window of `window` seconds around every
+/// `1 / rate` seconds, starting at time zero.
+///
+/// Returns an empty list if `sample_rate` or `rate` is not positive.
+pub fn rms_envelope(samples: &[f32], sample_rate: f64, rate: f64, window: f64) -> Vec<f32> {
+    if samples.is_empty() || sample_rate <= 0.0 || rate <= 0.0 {
+        return Vec::new();
+    }
+    // Prefix sums of squares make every window O(1).
+    let mut squares = Vec::with_capacity(samples.len() + 1);
+    squares.push(0.0f64);
+    let mut sum = 0.0;
+    for sample in samples {
+        sum += (*sample as f64).powi(2);
+        squares.push(sum);
+    }
+
+    let duration = samples.len() as f64 / sample_rate;
+    let half = (window.max(0.0) * sample_rate / 2.0).max(0.5);
+    (0..=(duration * rate).floor() as usize)
+        .map(|index| {
+            let center = index as f64 / rate * sample_rate;
+            let start = (center - half).round().clamp(0.0, samples.len() as f64) as usize;
+            let end = (center + half).round().clamp(0.0, samples.len() as f64) as usize;
+            if end <= start {
+                return 0.0;
+            }
+            ((squares[end] - squares[start]) / (end - start) as f64).sqrt() as f32
+        })
+        .collect()
+}
+
+/// A track following the loudness of mono `samples` at `sample_rate`.
+///
+/// Keys are linear, at multiples of `1 / options.rate` seconds.
+pub fn amplitude_track(samples: &[f32], sample_rate: f64, options: AmplitudeOptions) -> Track<f32> {
+    let mut envelope = rms_envelope(samples, sample_rate, options.rate, options.window);
+
+    let step = 1.0 / options.rate;
+    let coefficient = |time: f64| {
+        if time > 0.0 {
+            (-step / time).exp() as f32
+        } else {
+            0.0
+        }
+    };
+    let (attack, release) = (coefficient(options.attack), coefficient(options.release));
+    let mut level = envelope.first().copied().unwrap_or_default();
+    for value in &mut envelope {
+        let k = if *value > level { attack } else { release };
+        level = *value + k * (level - *value);
+        *value = level;
+    }
+
+    if options.normalize {
+        let peak = envelope.iter().copied().fold(0.0f32, f32::max);
+        if peak > 0.0 {
+            envelope.iter_mut().for_each(|value| *value /= peak);
+        }
+    }
+
+    let mut track = Track::new();
+    for (index, value) in envelope.into_iter().enumerate() {
+        track.add_keyframe(
+            Keyframe::new(index as f64 * step, value).with_type(KeyframeType::Linear),
+        );
+    }
+    if let Some(tolerance) = options.simplify {
+        track.simplify(tolerance);
+    }
+    track
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_loudness_with_release() {
+        let sample_rate = 1000.0;
+        // One second of a loud 50 Hz sine, then one second at half amplitude.
+        let samples: Vec<f32> = (0..2000)
+            .map(|i| {
+                let amplitude = if i < 1000 { 1.0 } else { 0.5 };
+                amplitude * (i as f32 / sample_rate as f32 * 50.0 * std::f32::consts::TAU).sin()
+            })
+            .collect();
+
+        let envelope = rms_envelope(&samples, sample_rate, 10.0, 0.1);
+        assert_eq!(envelope.len(), 21);
+        assert!((envelope[5] - std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+        assert!((envelope[15] - 0.5 * std::f32::consts::FRAC_1_SQRT_2).abs() < 1e-3);
+
+        let options = AmplitudeOptions::default()
+            .rate(10.0)
+            .window(0.1)
+            .release(0.2);
+        let track = amplitude_track(&samples, sample_rate, options);
+        assert_eq!(track.len(), 21);
+        assert!((track.evaluate(0.5).unwrap() - 1.0).abs() < 1e-3);
+        // The release smooths the drop instead of jumping to half.
+        let after = track.evaluate(1.1).unwrap();
+        assert!(after > 0.55 && after < 0.95);
+        assert!((track.evaluate(1.9).unwrap() - 0.5).abs() < 0.05);
+
+        let simplified = amplitude_track(&samples, sample_rate, options.simplify(0.01));
+        assert!(simplified.len() < track.len());
+
+        assert_eq!(mix_to_mono(&[1.0, 0.0, 0.5, 0.5], 2), [0.5, 0.5]);
+    }
+}
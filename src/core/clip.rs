@@ -0,0 +1,402 @@
+//! Clips for non-linear animation.
+//!
+//! A [`Clip`] bundles the tracks of a reusable motion, e.g. a walk cycle, in
+//! its own local time. A [`ClipInstance`] places a clip on the timeline,
+//! playing part of it, faster or slower, any number of times in a row.
+//! [`ClipSequence`] holds both and evaluates the instances at a global time:
+//!
+//! ```ignore
+//! let mut sequence = ClipSequence::new();
+//! let walk = sequence.add_clip(Clip::new("Walk").with_track("x", walk_x));
+//! let first = sequence.add_instance(walk, 0.0)?;
+//! sequence.set_loop_count(first, 3);
+//! let x = sequence.evaluate("x", 2.5);
+//! ```
+//!
+//! The [`ClipLane`](crate::widgets::ClipLane) moves, trims and splits
+//! instances, standalone or in the DopeSheet.
+
+use super::interpolation::Lerp;
+use super::time::TimeTick;
+use super::track::Track;
+use egui::Color32;
+use uuid::Uuid;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a clip.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipId(pub Uuid);
+
+impl ClipId {
+    /// Create a new random clip ID.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for ClipId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Unique identifier for a clip instance.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipInstanceId(pub Uuid);
+
+impl ClipInstanceId {
+    /// Create a new random clip instance ID.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for ClipInstanceId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A reusable bundle of tracks in local time.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clip<T> {
+    /// Unique identifier.
+    pub id: ClipId,
+    /// Display name, given to new instances.
+    pub name: String,
+    /// Display color, given to new instances.
+    pub color: Color32,
+    /// Tracks by the name of the property they animate.
+    pub tracks: Vec<(String, Track<T>)>,
+}
+
+impl<T: Clone> Clip<T> {
+    /// Create a clip without tracks.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: ClipId::new(),
+            name: name.into(),
+            color: Color32::from_rgb(120, 100, 170),
+            tracks: Vec::new(),
+        }
+    }
+
+    /// Add a track animating `property`.
+    pub fn with_track(mut self, property: impl Into<String>, track: Track<T>) -> Self {
+        self.tracks.push((property.into(), track));
+        self
+    }
+
+    /// Set the color.
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// The track animating `property`.
+    pub fn track(&self, property: &str) -> Option<&Track<T>> {
+        self.tracks
+            .iter()
+            .find(|(name, _)| name == property)
+            .map(|(_, track)| track)
+    }
+
+    /// Local time range covered by the keyframes of all tracks.
+    pub fn range(&self) -> Option<(TimeTick, TimeTick)> {
+        self.tracks
+            .iter()
+            .filter_map(|(_, track)| {
+                let (start, end) = track.time_range()?;
+                Some((track.to_global_time(start), track.to_global_time(end)))
+            })
+            .reduce(|(start, end), (other_start, other_end)| {
+                (start.min(other_start), end.max(other_end))
+            })
+    }
+}
+
+/// Which end of a [`ClipInstance`] is trimmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClipEdge {
+    Start,
+    End,
+}
+
+/// A [`Clip`] placed on the timeline.
+///
+/// Each loop plays the clip from `offset` for `length` of local time,
+/// `scale` times as fast as authored.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipInstance {
+    /// Unique identifier.
+    pub id: ClipInstanceId,
+    /// The clip played.
+    pub clip: ClipId,
+    /// Display name.
+    pub name: String,
+    /// Display color.
+    pub color: Color32,
+    /// Global time the instance starts at.
+    pub start: TimeTick,
+    /// Clip-local time played at `start`.
+    pub offset: TimeTick,
+    /// Clip-local time played per loop.
+    pub length: TimeTick,
+    /// Playback speed, above `0`; `2.0` plays the clip in half the time.
+    pub scale: f64,
+    /// Number of times the clip range plays back to back, at least `1`.
+    pub loop_count: u32,
+}
+
+impl ClipInstance {
+    /// Global duration of one loop.
+    pub fn loop_duration(&self) -> TimeTick {
+        self.length / self.scale
+    }
+
+    /// Global time the instance ends at.
+    pub fn end(&self) -> TimeTick {
+        self.start + self.loop_duration() * self.loop_count as f64
+    }
+
+    /// Whether `time` lies within `[start, end)`.
+    pub fn contains(&self, time: TimeTick) -> bool {
+        self.start <= time && time < self.end()
+    }
+
+    /// Clip-local time played at global `time`, or `None` outside the
+    /// instance.
+    pub fn local_time(&self, time: impl Into<TimeTick>) -> Option<TimeTick> {
+        let time = time.into();
+        if !self.contains(time) || self.length.value() <= 0.0 {
+            return None;
+        }
+        let played = (time - self.start).value() * self.scale;
+        Some(self.offset + TimeTick::new(played.rem_euclid(self.length.value())))
+    }
+}
+
+/// Clips and their instances on the timeline.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipSequence<T> {
+    /// Clips that instances refer to.
+    pub clips: Vec<Clip<T>>,
+    /// Instances from bottom to top; later instances win where they overlap.
+    pub instances: Vec<ClipInstance>,
+}
+
+impl<T: Clone> Default for ClipSequence<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone> ClipSequence<T> {
+    /// Minimum clip-local length an instance is trimmed or split to.
+    pub const MIN_LENGTH: f64 = 1e-3;
+
+    /// Create an empty sequence.
+    pub fn new() -> Self {
+        Self {
+            clips: Vec::new(),
+            instances: Vec::new(),
+        }
+    }
+
+    /// Add a clip.
+    pub fn add_clip(&mut self, clip: Clip<T>) -> ClipId {
+        let id = clip.id;
+        self.clips.push(clip);
+        id
+    }
+
+    /// Get a clip by ID.
+    pub fn clip(&self, id: ClipId) -> Option<&Clip<T>> {
+        self.clips.iter().find(|clip| clip.id == id)
+    }
+
+    /// Place the whole range of clip `id` once at global `start`, on top.
+    ///
+    /// Returns `None` if the clip does not exist or has no keyframes.
+    pub fn add_instance(
+        &mut self,
+        id: ClipId,
+        start: impl Into<TimeTick>,
+    ) -> Option<ClipInstanceId> {
+        let clip = self.clip(id)?;
+        let (from, to) = clip.range()?;
+        let instance = ClipInstance {
+            id: ClipInstanceId::new(),
+            clip: id,
+            name: clip.name.clone(),
+            color: clip.color,
+            start: start.into(),
+            offset: from,
+            length: (to - from).max(TimeTick::new(Self::MIN_LENGTH)),
+            scale: 1.0,
+            loop_count: 1,
+        };
+        let instance_id = instance.id;
+        self.instances.push(instance);
+        Some(instance_id)
+    }
+
+    /// Get an instance by ID.
+    pub fn instance(&self, id: ClipInstanceId) -> Option<&ClipInstance> {
+        self.instances.iter().find(|instance| instance.id == id)
+    }
+
+    /// Remove an instance.
+    pub fn remove_instance(&mut self, id: ClipInstanceId) -> Option<ClipInstance> {
+        let index = self
+            .instances
+            .iter()
+            .position(|instance| instance.id == id)?;
+        Some(self.instances.remove(index))
+    }
+
+    /// Move an instance to start at global `start`. Returns `false` if it
+    /// does not exist.
+    pub fn move_instance(&mut self, id: ClipInstanceId, start: impl Into<TimeTick>) -> bool {
+        self.instance_mut(id)
+            .map(|instance| instance.start = start.into())
+            .is_some()
+    }
+
+    /// Set how often an instance loops, at least once. Returns `false` if it
+    /// does not exist.
+    pub fn set_loop_count(&mut self, id: ClipInstanceId, loop_count: u32) -> bool {
+        self.instance_mut(id)
+            .map(|instance| instance.loop_count = loop_count.max(1))
+            .is_some()
+    }
+
+    /// Move an edge of an instance to global `time`, changing the clip range
+    /// every loop plays.
+    ///
+    /// Trimming the start keeps the clip content in place on the timeline.
+    /// The range never shrinks below [`MIN_LENGTH`](Self::MIN_LENGTH).
+    /// Returns the new `(start, end)`.
+    pub fn trim(
+        &mut self,
+        id: ClipInstanceId,
+        edge: ClipEdge,
+        time: impl Into<TimeTick>,
+    ) -> Option<(TimeTick, TimeTick)> {
+        let time = time.into();
+        let instance = self.instance_mut(id)?;
+        let loops = instance.loop_count as f64;
+        match edge {
+            ClipEdge::Start => {
+                let trimmed = ((time - instance.start).value() * instance.scale)
+                    .min(instance.length.value() - Self::MIN_LENGTH);
+                instance.start += TimeTick::new(trimmed / instance.scale);
+                instance.offset += TimeTick::new(trimmed);
+                instance.length -= TimeTick::new(trimmed);
+            }
+            ClipEdge::End => {
+                let length = (time - instance.start).value() * instance.scale / loops;
+                instance.length = TimeTick::new(length.max(Self::MIN_LENGTH));
+            }
+        }
+        Some((instance.start, instance.end()))
+    }
+
+    /// Split an instance at global `time` into two that play the same.
+    ///
+    /// Returns the ID of the new, later instance, or `None` if `time` is not
+    /// inside the instance or it loops more than once.
+    pub fn split(
+        &mut self,
+        id: ClipInstanceId,
+        time: impl Into<TimeTick>,
+    ) -> Option<ClipInstanceId> {
+        let time = time.into();
+        let index = self
+            .instances
+            .iter()
+            .position(|instance| instance.id == id)?;
+        let instance = &mut self.instances[index];
+        let played = (time - instance.start).value() * instance.scale;
+        if instance.loop_count != 1
+            || played < Self::MIN_LENGTH
+            || played > instance.length.value() - Self::MIN_LENGTH
+        {
+            return None;
+        }
+
+        let mut later = instance.clone();
+        instance.length = TimeTick::new(played);
+        later.id = ClipInstanceId::new();
+        later.start = time;
+        later.offset += instance.length;
+        later.length -= instance.length;
+        let later_id = later.id;
+        self.instances.insert(index + 1, later);
+        Some(later_id)
+    }
+
+    /// Evaluate `property` at global `time` from the topmost instance that
+    /// covers `time` and whose clip animates it.
+    pub fn evaluate(&self, property: &str, time: impl Into<TimeTick>) -> Option<T>
+    where
+        T: Lerp,
+    {
+        let time = time.into();
+        self.instances.iter().rev().find_map(|instance| {
+            let local = instance.local_time(time)?;
+            self.clip(instance.clip)?.track(property)?.evaluate(local)
+        })
+    }
+
+    fn instance_mut(&mut self, id: ClipInstanceId) -> Option<&mut ClipInstance> {
+        self.instances.iter_mut().find(|instance| instance.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::{Keyframe, KeyframeType};
+
+    #[test]
+    fn places_trims_and_splits_instances() {
+        let mut ramp = Track::<f32>::new();
+        ramp.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        ramp.add_keyframe(Keyframe::new(2.0, 20.0).with_type(KeyframeType::Linear));
+
+        let mut sequence = ClipSequence::new();
+        let clip = sequence.add_clip(Clip::new("Ramp").with_track("x", ramp));
+        let id = sequence.add_instance(clip, 10.0).unwrap();
+        assert_eq!(sequence.evaluate("x", 11.0), Some(10.0));
+        assert_eq!(sequence.evaluate("x", 9.0), None);
+        assert_eq!(sequence.evaluate("y", 11.0), None);
+
+        // Twice as fast, three times: [10, 13) plays the ramp every second.
+        sequence.instances[0].scale = 2.0;
+        sequence.set_loop_count(id, 3);
+        assert_eq!(sequence.instance(id).unwrap().end(), TimeTick::new(13.0));
+        assert_eq!(sequence.evaluate("x", 12.25), Some(5.0));
+
+        // Trimming the start keeps the content in place.
+        sequence.set_loop_count(id, 1);
+        assert_eq!(
+            sequence.trim(id, ClipEdge::Start, 10.5),
+            Some((TimeTick::new(10.5), TimeTick::new(11.0)))
+        );
+        assert_eq!(sequence.evaluate("x", 10.75), Some(15.0));
+
+        let later = sequence.split(id, 10.75).unwrap();
+        assert_eq!(sequence.instance(id).unwrap().end(), TimeTick::new(10.75));
+        assert_eq!(sequence.instance(later).unwrap().offset, TimeTick::new(1.5));
+        assert_eq!(sequence.evaluate("x", 10.875), Some(17.5));
+        assert!(sequence.split(later, 12.0).is_none());
+    }
+}
@@ -0,0 +1,620 @@
+//! Copy and paste of keyframes.
+//!
+//! [`Clipboard`] stores keyframes relative to the earliest copied one so they
+//! can be pasted at any time into any track. [`PasteMode`] names the common
+//! ways to paste, at the playhead, in place, mirrored or onto other tracks,
+//! so every host resolves them the same. [`Clipboard::to_text`] and
+//! [`Clipboard::from_text`] round-trip it through the system clipboard, e.g.
+//! via `egui::Context::copy_text`, so keyframes survive across sessions.
+
+use super::history::KeyframeSnapshot;
+use super::keyframe::{BezierHandles, KeyframeId, KeyframeType, Spring};
+use super::time::TimeTick;
+use super::track::TrackId;
+use crate::HashSet;
+use crate::traits::{AnimationCommand, AnimationDataProvider};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// First line of the text form of a [`Clipboard`].
+const TEXT_HEADER: &str = "egui-keyframe/keyframes 2";
+
+/// First line of the text form before copied tracks were recorded.
+const TEXT_HEADER_V1: &str = "egui-keyframe/keyframes 1";
+
+/// A copied keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ClipboardKeyframe {
+    /// Index of the track the keyframe was copied from in
+    /// [`Clipboard::tracks`].
+    pub channel: usize,
+    /// Time relative to the earliest copied keyframe.
+    pub offset: TimeTick,
+    /// Value.
+    pub value: f64,
+    /// Bezier handles.
+    pub handles: BezierHandles,
+    /// Interpolation type.
+    pub keyframe_type: KeyframeType,
+    /// Spring parameters, used by [`KeyframeType::Spring`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spring: Spring,
+}
+
+/// Options for [`Clipboard::paste`].
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PasteOptions {
+    /// Added to the paste time.
+    pub offset: TimeTick,
+    /// Paste reversed in time so the copied motion plays backwards.
+    pub flipped: bool,
+}
+
+/// How [`Clipboard::paste_with`] places the copied keyframes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PasteMode {
+    /// Into the copied tracks with the earliest keyframe at `time`, e.g. the
+    /// playhead.
+    AtPlayhead(TimeTick),
+    /// Into the copied tracks at the times they were copied from.
+    InPlace,
+    /// Into the copied tracks reversed in time, see [`Clipboard::flipped`],
+    /// starting at `time`.
+    Mirrored(TimeTick),
+    /// Onto other tracks with the earliest keyframe at `time`.
+    ///
+    /// The n-th copied track goes to the n-th of `tracks`; copied tracks
+    /// without a target are skipped.
+    ToTracks {
+        time: TimeTick,
+        tracks: Vec<TrackId>,
+    },
+}
+
+impl PasteMode {
+    /// Paste onto the tracks of the selected rows, in row order.
+    pub fn to_selected_rows<P: AnimationDataProvider + ?Sized>(
+        provider: &P,
+        selected_rows: &HashSet<String>,
+        time: TimeTick,
+    ) -> Self {
+        let tracks = provider
+            .property_rows()
+            .into_iter()
+            .filter(|row| selected_rows.contains(&row.id))
+            .filter_map(|row| row.track_id)
+            .collect();
+        Self::ToTracks { time, tracks }
+    }
+}
+
+/// Copied keyframes, sorted by time.
+#[derive(Debug, Clone, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Clipboard {
+    start: TimeTick,
+    tracks: Vec<TrackId>,
+    keyframes: Vec<ClipboardKeyframe>,
+}
+
+impl Clipboard {
+    /// Copy keyframes from a provider.
+    ///
+    /// Keyframes that do not exist are skipped. The copied tracks are
+    /// ordered like the provider's rows.
+    pub fn copy<P: AnimationDataProvider + ?Sized>(
+        provider: &P,
+        keyframe_ids: impl IntoIterator<Item = KeyframeId>,
+    ) -> Self {
+        let snapshots: Vec<KeyframeSnapshot> = keyframe_ids
+            .into_iter()
+            .filter_map(|id| KeyframeSnapshot::capture(provider, id))
+            .collect();
+        let Some(start) = snapshots
+            .iter()
+            .map(|snapshot| snapshot.position)
+            .reduce(TimeTick::min)
+        else {
+            return Self::default();
+        };
+
+        let mut tracks: Vec<TrackId> = provider
+            .property_rows()
+            .into_iter()
+            .filter_map(|row| row.track_id)
+            .filter(|id| snapshots.iter().any(|snapshot| snapshot.track_id == *id))
+            .collect();
+        for snapshot in &snapshots {
+            if !tracks.contains(&snapshot.track_id) {
+                tracks.push(snapshot.track_id);
+            }
+        }
+
+        let mut keyframes: Vec<ClipboardKeyframe> = snapshots
+            .iter()
+            .map(|snapshot| ClipboardKeyframe {
+                channel: tracks
+                    .iter()
+                    .position(|id| *id == snapshot.track_id)
+                    .unwrap_or_default(),
+                offset: snapshot.position - start,
+                value: snapshot.value,
+                handles: snapshot.handles,
+                keyframe_type: snapshot.keyframe_type,
+                spring: snapshot.spring,
+            })
+            .collect();
+        keyframes.sort_by(|a, b| {
+            a.offset
+                .partial_cmp(&b.offset)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        Self {
+            start,
+            tracks,
+            keyframes,
+        }
+    }
+
+    /// Create a clipboard from keyframes in any order.
+    ///
+    /// Offsets are shifted so the earliest keyframe is at zero; its original
+    /// offset becomes the [`start`](Self::start). No tracks are recorded, so
+    /// paste with [`PasteMode::ToTracks`] or [`paste`](Self::paste).
+    pub fn from_keyframes(keyframes: impl IntoIterator<Item = ClipboardKeyframe>) -> Self {
+        let mut keyframes: Vec<ClipboardKeyframe> = keyframes.into_iter().collect();
+        keyframes.sort_by(|a, b| {
+            a.offset
+                .partial_cmp(&b.offset)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        let start = keyframes.first().map_or(TimeTick::zero(), |kf| kf.offset);
+        for kf in &mut keyframes {
+            kf.offset -= start;
+        }
+        Self {
+            start,
+            tracks: Vec::new(),
+            keyframes,
+        }
+    }
+
+    /// Time the earliest keyframe was copied from.
+    pub fn start(&self) -> TimeTick {
+        self.start
+    }
+
+    /// Tracks the keyframes were copied from, indexed by
+    /// [`ClipboardKeyframe::channel`].
+    pub fn tracks(&self) -> &[TrackId] {
+        &self.tracks
+    }
+
+    /// Copied keyframes, sorted by offset.
+    pub fn keyframes(&self) -> &[ClipboardKeyframe] {
+        &self.keyframes
+    }
+
+    /// Number of copied keyframes.
+    pub fn len(&self) -> usize {
+        self.keyframes.len()
+    }
+
+    /// Check if nothing was copied.
+    pub fn is_empty(&self) -> bool {
+        self.keyframes.is_empty()
+    }
+
+    /// Time span from the first to the last copied keyframe.
+    pub fn duration(&self) -> TimeTick {
+        self.keyframes
+            .last()
+            .map_or(TimeTick::zero(), |kf| kf.offset)
+    }
+
+    /// The keyframes reversed in time.
+    ///
+    /// Handles are mirrored and interpolation types shifted so each segment
+    /// keeps its shape, played backwards.
+    pub fn flipped(&self) -> Self {
+        let duration = self.duration();
+        let count = self.keyframes.len();
+        let keyframes = (0..count)
+            .map(|j| {
+                let index = count - 1 - j;
+                let source = &self.keyframes[index];
+                // The reversed segment starting here was the original segment
+                // ending here, governed by the type and spring of the previous
+                // keyframe of the same track.
+                let (keyframe_type, spring) = self.keyframes[..index]
+                    .iter()
+                    .rfind(|previous| previous.channel == source.channel)
+                    .map_or((source.keyframe_type, source.spring), |previous| {
+                        (previous.keyframe_type, previous.spring)
+                    });
+                ClipboardKeyframe {
+                    channel: source.channel,
+                    offset: duration - source.offset,
+                    value: source.value,
+                    handles: BezierHandles {
+                        left_x: 1.0 - source.handles.right_x,
+                        left_y: 1.0 - source.handles.right_y,
+                        right_x: 1.0 - source.handles.left_x,
+                        right_y: 1.0 - source.handles.left_y,
+                    },
+                    keyframe_type,
+                    spring,
+                }
+            })
+            .collect();
+        Self {
+            start: self.start,
+            tracks: self.tracks.clone(),
+            keyframes,
+        }
+    }
+
+    /// Command pasting the keyframes of all copied tracks into `track_id`
+    /// with the earliest one at `time`.
+    ///
+    /// Pasted keyframes get new IDs.
+    pub fn paste(
+        &self,
+        track_id: TrackId,
+        time: TimeTick,
+        options: PasteOptions,
+    ) -> AnimationCommand {
+        let flipped;
+        let clipboard = if options.flipped {
+            flipped = self.flipped();
+            &flipped
+        } else {
+            self
+        };
+        clipboard.paste_into(time + options.offset, |_| Some(track_id))
+    }
+
+    /// Command pasting the keyframes as `mode` describes.
+    ///
+    /// Pasted keyframes get new IDs.
+    pub fn paste_with(&self, mode: PasteMode) -> AnimationCommand {
+        let source = |kf: &ClipboardKeyframe| self.tracks.get(kf.channel).copied();
+        match mode {
+            PasteMode::AtPlayhead(time) => self.paste_into(time, source),
+            PasteMode::InPlace => self.paste_into(self.start, source),
+            PasteMode::Mirrored(time) => {
+                let flipped = self.flipped();
+                flipped.paste_into(time, |kf| flipped.tracks.get(kf.channel).copied())
+            }
+            PasteMode::ToTracks { time, tracks } => {
+                self.paste_into(time, |kf| tracks.get(kf.channel).copied())
+            }
+        }
+    }
+
+    /// Paste with the earliest keyframe at `start` into the track `target`
+    /// returns for each keyframe, skipping those without one.
+    fn paste_into(
+        &self,
+        start: TimeTick,
+        target: impl Fn(&ClipboardKeyframe) -> Option<TrackId>,
+    ) -> AnimationCommand {
+        AnimationCommand::PasteKeyframes {
+            keyframes: self
+                .keyframes
+                .iter()
+                .filter_map(|kf| {
+                    Some(KeyframeSnapshot {
+                        track_id: target(kf)?,
+                        keyframe_id: KeyframeId::new(),
+                        position: start + kf.offset,
+                        value: kf.value,
+                        handles: kf.handles,
+                        keyframe_type: kf.keyframe_type,
+                        spring: kf.spring,
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Encode as plain text: the start time, one line per copied track and
+    /// one line per keyframe. Spring keyframes end with their frequency,
+    /// damping and response.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{TEXT_HEADER}\nstart {}", self.start.value());
+        for track_id in &self.tracks {
+            text.push_str(&format!("\ntrack {}", track_id.0));
+        }
+        for kf in &self.keyframes {
+            let [left_x, left_y, right_x, right_y] = kf.handles.to_array();
+            text.push_str(&format!(
+                "\n{} {} {} {} {left_x} {left_y} {right_x} {right_y}",
+                kf.channel,
+                kf.offset.value(),
+                kf.value,
+                type_name(kf.keyframe_type),
+            ));
+            if kf.keyframe_type == KeyframeType::Spring {
+                let Spring {
+                    frequency,
+                    damping,
+                    response,
+                } = kf.spring;
+                text.push_str(&format!(" {frequency} {damping} {response}"));
+            }
+        }
+        text
+    }
+
+    /// Decode text produced by [`to_text`](Self::to_text), or by earlier
+    /// versions without copied tracks.
+    ///
+    /// Returns `None` if the text is not a keyframe clipboard.
+    pub fn from_text(text: &str) -> Option<Self> {
+        let mut lines = text.lines().filter(|line| !line.trim().is_empty());
+        let versioned = match lines.next()?.trim() {
+            TEXT_HEADER => true,
+            TEXT_HEADER_V1 => false,
+            _ => return None,
+        };
+
+        let mut clipboard = Self::default();
+        for line in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            match (versioned, fields.as_slice()) {
+                (true, ["start", start]) => {
+                    clipboard.start = TimeTick::new(start.parse::<f64>().ok()?);
+                }
+                (true, ["track", id]) => {
+                    clipboard.tracks.push(TrackId(id.parse().ok()?));
+                }
+                (true, [channel, rest @ ..]) => {
+                    clipboard
+                        .keyframes
+                        .push(parse_keyframe(channel.parse().ok()?, rest)?);
+                }
+                (false, fields) => clipboard.keyframes.push(parse_keyframe(0, fields)?),
+                _ => return None,
+            }
+        }
+        Some(clipboard)
+    }
+}
+
+/// Parse the offset, value, type, handles and optional spring of a keyframe
+/// line.
+fn parse_keyframe(channel: usize, fields: &[&str]) -> Option<ClipboardKeyframe> {
+    let (
+        [
+            offset,
+            value,
+            keyframe_type,
+            left_x,
+            left_y,
+            right_x,
+            right_y,
+        ],
+        spring,
+    ) = (fields.get(..7)?, fields.get(7..)?)
+    else {
+        return None;
+    };
+    let spring = match spring {
+        [] => Spring::default(),
+        [frequency, damping, response] => Spring {
+            frequency: frequency.parse().ok()?,
+            damping: damping.parse().ok()?,
+            response: response.parse().ok()?,
+        },
+        _ => return None,
+    };
+    Some(ClipboardKeyframe {
+        channel,
+        offset: TimeTick::new(offset.parse::<f64>().ok()?),
+        value: value.parse().ok()?,
+        keyframe_type: type_from_name(keyframe_type)?,
+        handles: BezierHandles::from_array([
+            left_x.parse().ok()?,
+            left_y.parse().ok()?,
+            right_x.parse().ok()?,
+            right_y.parse().ok()?,
+        ]),
+        spring,
+    })
+}
+
+fn type_name(keyframe_type: KeyframeType) -> &'static str {
+    match keyframe_type {
+        KeyframeType::Bezier => "bezier",
+        KeyframeType::Hold => "hold",
+        KeyframeType::Linear => "linear",
+        KeyframeType::Auto => "auto",
+        KeyframeType::Spring => "spring",
+    }
+}
+
+fn type_from_name(name: &str) -> Option<KeyframeType> {
+    match name {
+        "bezier" => Some(KeyframeType::Bezier),
+        "hold" => Some(KeyframeType::Hold),
+        "linear" => Some(KeyframeType::Linear),
+        "auto" => Some(KeyframeType::Auto),
+        "spring" => Some(KeyframeType::Spring),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn clipboard() -> Clipboard {
+        Clipboard {
+            start: TimeTick::new(3.0),
+            tracks: vec![TrackId::new()],
+            keyframes: vec![
+                ClipboardKeyframe {
+                    channel: 0,
+                    offset: TimeTick::new(0.0),
+                    value: 1.0,
+                    handles: BezierHandles::ease_in(),
+                    keyframe_type: KeyframeType::Hold,
+                    spring: Spring::default(),
+                },
+                ClipboardKeyframe {
+                    channel: 0,
+                    offset: TimeTick::new(2.0),
+                    value: -0.5,
+                    handles: BezierHandles::ease_out(),
+                    keyframe_type: KeyframeType::Bezier,
+                    spring: Spring::default(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn text_round_trip() {
+        let mut clipboard = clipboard();
+        assert_eq!(
+            Clipboard::from_text(&clipboard.to_text()),
+            Some(clipboard.clone())
+        );
+        clipboard.keyframes[0].keyframe_type = KeyframeType::Spring;
+        clipboard.keyframes[0].spring = Spring {
+            frequency: 3.5,
+            damping: 0.25,
+            response: -1.0,
+        };
+        assert_eq!(Clipboard::from_text(&clipboard.to_text()), Some(clipboard));
+        assert_eq!(Clipboard::from_text("not keyframes"), None);
+
+        let v1 = "egui-keyframe/keyframes 1\n0 1 linear 0 0 1 1\n";
+        let old = Clipboard::from_text(v1).unwrap();
+        assert_eq!(old.len(), 1);
+        assert!(old.tracks().is_empty());
+    }
+
+    #[test]
+    fn paste_flipped_reverses_time() {
+        let track_id = TrackId::new();
+        let options = PasteOptions {
+            offset: TimeTick::new(1.0),
+            flipped: true,
+        };
+        let AnimationCommand::PasteKeyframes { keyframes } =
+            clipboard().paste(track_id, TimeTick::new(10.0), options)
+        else {
+            panic!("expected PasteKeyframes");
+        };
+
+        let pasted: Vec<_> = keyframes
+            .iter()
+            .map(|kf| (kf.position, kf.value, kf.keyframe_type))
+            .collect();
+        assert_eq!(
+            pasted,
+            [
+                (TimeTick::new(11.0), -0.5, KeyframeType::Hold),
+                (TimeTick::new(13.0), 1.0, KeyframeType::Hold),
+            ]
+        );
+        // The ease-out arrival becomes an ease-in departure.
+        assert_eq!(keyframes[0].handles.right_x, 1.0 - 0.58);
+        assert!(keyframes.iter().all(|kf| kf.track_id == track_id));
+    }
+
+    #[test]
+    fn flipped_shifts_types_per_track() {
+        let key = |channel, offset, keyframe_type| ClipboardKeyframe {
+            channel,
+            offset: TimeTick::new(offset),
+            value: 0.0,
+            handles: BezierHandles::default(),
+            keyframe_type,
+            spring: Spring::default(),
+        };
+        let clipboard = Clipboard {
+            start: TimeTick::new(0.0),
+            tracks: vec![TrackId::new(), TrackId::new()],
+            keyframes: vec![
+                key(0, 0.0, KeyframeType::Hold),
+                key(1, 1.0, KeyframeType::Linear),
+                key(0, 2.0, KeyframeType::Bezier),
+                key(1, 3.0, KeyframeType::Auto),
+            ],
+        };
+
+        let types: Vec<_> = clipboard
+            .flipped()
+            .keyframes
+            .iter()
+            .map(|kf| (kf.channel, kf.keyframe_type))
+            .collect();
+        assert_eq!(
+            types,
+            [
+                (1, KeyframeType::Linear),
+                (0, KeyframeType::Hold),
+                (1, KeyframeType::Linear),
+                (0, KeyframeType::Hold),
+            ]
+        );
+    }
+
+    #[test]
+    fn paste_modes_map_times_and_tracks() {
+        use crate::core::keyframe::Keyframe;
+        use crate::core::track::Track;
+        use crate::model::SimpleAnimationModel;
+
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Ball");
+        let mut x = Track::new();
+        let x_key = x.add_keyframe(Keyframe::new(2.0, 1.0));
+        let mut y = Track::new();
+        let y_key = y.add_keyframe(Keyframe::new(3.0, 5.0));
+        let x_id = model.add_track(&group, "x", x).unwrap();
+        let y_id = model.add_track(&group, "y", y).unwrap();
+        let other = model.add_track(&group, "z", Track::new()).unwrap();
+
+        // Tracks follow row order, not copy order.
+        let clipboard = Clipboard::copy(&model, [y_key, x_key]);
+        assert_eq!(clipboard.tracks(), [x_id, y_id]);
+        assert_eq!(clipboard.start(), TimeTick::new(2.0));
+        assert_eq!(
+            Clipboard::from_text(&clipboard.to_text()),
+            Some(clipboard.clone())
+        );
+
+        let placed = |mode| {
+            let AnimationCommand::PasteKeyframes { keyframes } = clipboard.paste_with(mode) else {
+                panic!("expected PasteKeyframes");
+            };
+            keyframes
+                .iter()
+                .map(|kf| (kf.track_id, kf.position.value(), kf.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            placed(PasteMode::InPlace),
+            [(x_id, 2.0, 1.0), (y_id, 3.0, 5.0)]
+        );
+        assert_eq!(
+            placed(PasteMode::AtPlayhead(TimeTick::new(10.0))),
+            [(x_id, 10.0, 1.0), (y_id, 11.0, 5.0)]
+        );
+        assert_eq!(
+            placed(PasteMode::Mirrored(TimeTick::new(10.0))),
+            [(y_id, 10.0, 5.0), (x_id, 11.0, 1.0)]
+        );
+
+        // Only one target row: the second copied track is skipped.
+        let selected: HashSet<String> = [other.0.to_string()].into_iter().collect();
+        let mode = PasteMode::to_selected_rows(&model, &selected, TimeTick::new(0.0));
+        assert_eq!(placed(mode), [(other, 0.0, 1.0)]);
+    }
+}
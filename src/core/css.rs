@@ -0,0 +1,348 @@
+//! CSS `@keyframes` export and import for `Track<f32>`.
+//!
+//! [`Track::to_css_keyframes`] writes one keyframe selector per keyframe at
+//! its percentage of the animation duration, with the segment's easing as an
+//! `animation-timing-function`:
+//!
+//! ```css
+//! @keyframes opacity {
+//!   0% { opacity: 0; animation-timing-function: cubic-bezier(0.42, 0, 0.58, 1); }
+//!   50% { opacity: 1; animation-timing-function: steps(1, end); }
+//!   100% { opacity: 0.5; }
+//! }
+//! ```
+//!
+//! [`Track::from_css_keyframes`] reads such a block back.
+
+use super::interpolation::segment_control_points;
+use super::keyframe::{BezierHandles, Keyframe, KeyframeType};
+use super::time::TimeTick;
+use super::track::Track;
+
+/// CSS's initial `animation-timing-function`, `ease`.
+const EASE: [f32; 4] = [0.25, 0.1, 0.25, 1.0];
+
+/// Error parsing a CSS `@keyframes` block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CssError {
+    /// There is no complete `@keyframes name { … }` block.
+    MissingKeyframes,
+    /// A keyframe selector is not `from`, `to` or a percentage.
+    InvalidSelector(String),
+    /// A keyframe has no numeric property value.
+    MissingValue(String),
+    /// An `animation-timing-function` is not understood.
+    InvalidTimingFunction(String),
+}
+
+impl std::fmt::Display for CssError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingKeyframes => write!(f, "no @keyframes block found"),
+            Self::InvalidSelector(selector) => write!(f, "invalid keyframe selector `{selector}`"),
+            Self::MissingValue(selector) => {
+                write!(f, "keyframe `{selector}` has no numeric property value")
+            }
+            Self::InvalidTimingFunction(function) => {
+                write!(f, "unsupported timing function `{function}`")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CssError {}
+
+/// Easing of the segment after a keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Timing {
+    Bezier([f32; 4]),
+    Linear,
+    Hold,
+}
+
+impl Track<f32> {
+    /// A CSS `@keyframes` block animating the property `name`, also used as
+    /// the animation name, over `duration`.
+    ///
+    /// Keyframes are placed at their global time as a percentage of
+    /// `duration`; if the curve does not start at `0` or end at `duration`,
+    /// the first and last values are held to fill the range, like
+    /// [`Extrapolation::Constant`](super::extrapolation::Extrapolation::Constant).
+    /// Keyframes outside the range are kept with offsets below `0%` or above
+    /// `100%`, which browsers ignore. Bezier and auto segments become
+    /// `cubic-bezier()`, hold and disconnected segments `steps(1, end)`.
+    /// Spring segments, which CSS cannot express, fall back to `linear`.
+    pub fn to_css_keyframes(&self, name: &str, duration: impl Into<TimeTick>) -> String {
+        let duration = duration.into().value();
+        let keyframes = self.keyframes_sorted();
+        let percent = |time: f64| {
+            if duration > 0.0 {
+                time / duration * 100.0
+            } else {
+                0.0
+            }
+        };
+
+        let mut css = format!("@keyframes {name} {{\n");
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            css.push_str("}\n");
+            return css;
+        };
+
+        let first_time = self.to_global_time(first.position).value();
+        if percent(first_time) > 0.0 {
+            css.push_str(&format!(
+                "  0% {{ {name}: {}; animation-timing-function: steps(1, end); }}\n",
+                number(first.value as f64)
+            ));
+        }
+        for (index, kf) in keyframes.iter().enumerate() {
+            let time = self.to_global_time(kf.position).value();
+            let timing = if index + 1 == keyframes.len() {
+                String::new()
+            } else if kf.keyframe_type == KeyframeType::Hold || !kf.connected_right {
+                " animation-timing-function: steps(1, end);".to_string()
+            } else if matches!(
+                kf.keyframe_type,
+                KeyframeType::Linear | KeyframeType::Spring
+            ) {
+                " animation-timing-function: linear;".to_string()
+            } else {
+                let [x1, y1, x2, y2] = segment_control_points(&keyframes, index);
+                format!(
+                    " animation-timing-function: cubic-bezier({}, {}, {}, {});",
+                    number(x1 as f64),
+                    number(y1 as f64),
+                    number(x2 as f64),
+                    number(y2 as f64)
+                )
+            };
+            css.push_str(&format!(
+                "  {}% {{ {name}: {};{timing} }}\n",
+                number(percent(time)),
+                number(kf.value as f64)
+            ));
+        }
+        let last_time = self.to_global_time(last.position).value();
+        if percent(last_time) < 100.0 {
+            css.push_str(&format!(
+                "  100% {{ {name}: {}; }}\n",
+                number(last.value as f64)
+            ));
+        }
+        css.push_str("}\n");
+        css
+    }
+
+    /// Parse the first `@keyframes` block in `css`, placing its percentages
+    /// over `duration`. Returns the animation name and the track.
+    ///
+    /// Each keyframe takes the first property with a numeric value; units
+    /// such as `px` or `deg` are dropped. Keyframes without an
+    /// `animation-timing-function` use CSS's default `ease`. Step functions
+    /// become hold keyframes.
+    pub fn from_css_keyframes(
+        css: &str,
+        duration: impl Into<TimeTick>,
+    ) -> Result<(String, Self), CssError> {
+        let duration = duration.into().value();
+        let css = strip_comments(css);
+        let start = css.find("@keyframes").ok_or(CssError::MissingKeyframes)?;
+        let rest = &css[start + "@keyframes".len()..];
+        let open = rest.find('{').ok_or(CssError::MissingKeyframes)?;
+        let name = rest[..open].trim().trim_matches('"').to_string();
+
+        // Keyframe rules up to the block's closing brace.
+        let mut entries: Vec<(f64, f32, Timing)> = Vec::new();
+        let mut body = &rest[open + 1..];
+        loop {
+            if body.trim_start().starts_with('}') {
+                break;
+            }
+            let open = body.find('{').ok_or(CssError::MissingKeyframes)?;
+            let close = body.find('}').ok_or(CssError::MissingKeyframes)?;
+            if close < open {
+                return Err(CssError::MissingKeyframes);
+            }
+            let selector = body[..open].trim();
+            let (value, timing) = parse_declarations(selector, &body[open + 1..close])?;
+            for offset in selector.split(',') {
+                entries.push((parse_offset(offset.trim())?, value, timing));
+            }
+            body = &body[close + 1..];
+        }
+        entries.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut track = Track::new();
+        let mut left = (0.0, 0.0);
+        for (offset, value, timing) in entries {
+            let (keyframe_type, [x1, y1, x2, y2]) = match timing {
+                Timing::Bezier(points) => (KeyframeType::Bezier, points),
+                Timing::Linear => (KeyframeType::Linear, [0.0, 0.0, 1.0, 1.0]),
+                Timing::Hold => (KeyframeType::Hold, [0.0, 0.0, 1.0, 1.0]),
+            };
+            let handles = BezierHandles {
+                left_x: left.0,
+                left_y: left.1,
+                right_x: x1,
+                right_y: y1,
+            };
+            track.add_keyframe(
+                Keyframe::new(offset / 100.0 * duration, value)
+                    .with_handles(handles)
+                    .with_type(keyframe_type),
+            );
+            left = (x2, y2);
+        }
+        Ok((name, track))
+    }
+}
+
+/// The first numeric property value and the timing function of a keyframe
+/// rule.
+fn parse_declarations(selector: &str, declarations: &str) -> Result<(f32, Timing), CssError> {
+    let mut value = None;
+    let mut timing = Timing::Bezier(EASE);
+    for declaration in declarations.split(';') {
+        let Some((property, text)) = declaration.split_once(':') else {
+            continue;
+        };
+        let (property, text) = (property.trim(), text.trim());
+        if property == "animation-timing-function" {
+            timing = parse_timing(text)?;
+        } else if value.is_none() {
+            value = parse_number(text);
+        }
+    }
+    value
+        .map(|value| (value, timing))
+        .ok_or_else(|| CssError::MissingValue(selector.to_string()))
+}
+
+/// Offset in percent of a `from`, `to` or `N%` selector.
+fn parse_offset(selector: &str) -> Result<f64, CssError> {
+    match selector {
+        "from" => Ok(0.0),
+        "to" => Ok(100.0),
+        _ => selector
+            .strip_suffix('%')
+            .and_then(|number| number.trim().parse().ok())
+            .ok_or_else(|| CssError::InvalidSelector(selector.to_string())),
+    }
+}
+
+fn parse_timing(text: &str) -> Result<Timing, CssError> {
+    let invalid = || CssError::InvalidTimingFunction(text.to_string());
+    match text {
+        "linear" => Ok(Timing::Linear),
+        "ease" => Ok(Timing::Bezier(EASE)),
+        "ease-in" => Ok(Timing::Bezier([0.42, 0.0, 1.0, 1.0])),
+        "ease-out" => Ok(Timing::Bezier([0.0, 0.0, 0.58, 1.0])),
+        "ease-in-out" => Ok(Timing::Bezier([0.42, 0.0, 0.58, 1.0])),
+        "step-start" | "step-end" => Ok(Timing::Hold),
+        _ if text.starts_with("steps(") => Ok(Timing::Hold),
+        _ => {
+            let arguments = text
+                .strip_prefix("cubic-bezier(")
+                .and_then(|rest| rest.strip_suffix(')'))
+                .ok_or_else(invalid)?;
+            let points: Vec<f32> = arguments
+                .split(',')
+                .map(|number| number.trim().parse().map_err(|_| invalid()))
+                .collect::<Result<_, _>>()?;
+            let points: [f32; 4] = points.try_into().map_err(|_| invalid())?;
+            Ok(Timing::Bezier(points))
+        }
+    }
+}
+
+/// The leading number of a CSS value such as `12.5px`.
+fn parse_number(text: &str) -> Option<f32> {
+    let end = text
+        .char_indices()
+        .find(|&(index, c)| {
+            !(c.is_ascii_digit() || c == '.' || ((c == '-' || c == '+') && index == 0))
+        })
+        .map_or(text.len(), |(index, _)| index);
+    text[..end].parse().ok()
+}
+
+fn strip_comments(css: &str) -> String {
+    let mut result = String::with_capacity(css.len());
+    let mut rest = css;
+    while let Some(start) = rest.find("/*") {
+        result.push_str(&rest[..start]);
+        rest = rest[start..]
+            .find("*/")
+            .map_or("", |end| &rest[start + end + 2..]);
+    }
+    result.push_str(rest);
+    result
+}
+
+/// `value` with at most four decimals and no trailing zeros.
+fn number(value: f64) -> String {
+    let text = format!("{value:.4}");
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn css_round_trip() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_handles(BezierHandles::ease_in_out()));
+        track.add_keyframe(Keyframe::new(1.0, 1.0).with_type(KeyframeType::Hold));
+        track.add_keyframe(Keyframe::new(1.5, 0.5).with_type(KeyframeType::Linear));
+
+        let css = track.to_css_keyframes("opacity", 2.0);
+        assert!(css.starts_with("@keyframes opacity {\n"));
+        assert!(css.contains("  50% { opacity: 1; animation-timing-function: steps(1, end); }\n"));
+        assert!(css.contains("  100% { opacity: 0.5; }\n"));
+
+        let (name, copy) = Track::from_css_keyframes(&css, 2.0).unwrap();
+        assert_eq!(name, "opacity");
+        for step in 0..=20 {
+            let time = step as f64 * 0.1;
+            let (a, b) = (copy.evaluate(time).unwrap(), track.evaluate(time).unwrap());
+            assert!((a - b).abs() < 1e-3, "{time}: {a} != {b}");
+        }
+
+        // Springs fall back to linear.
+        let mut spring = Track::<f32>::new();
+        spring.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Spring));
+        spring.add_keyframe(Keyframe::new(1.0, 1.0));
+        assert!(
+            spring
+                .to_css_keyframes("opacity", 1.0)
+                .contains("  0% { opacity: 0; animation-timing-function: linear; }\n")
+        );
+    }
+
+    #[test]
+    fn parses_browser_css() {
+        let css = "/* fade */ @keyframes slide { from { transform: none; left: -10px } \
+                   50%, 60% { left: 20px; animation-timing-function: ease-out } to { left: 0px } }";
+        let (name, track) = Track::from_css_keyframes(css, 1.0).unwrap();
+        assert_eq!(name, "slide");
+        assert_eq!(track.len(), 4);
+        assert_eq!(track.evaluate(0.0), Some(-10.0));
+        assert_eq!(track.evaluate(0.55), Some(20.0));
+
+        assert_eq!(
+            Track::from_css_keyframes("@keyframes x { half { left: 1px } }", 1.0).unwrap_err(),
+            CssError::InvalidSelector("half".to_string())
+        );
+        assert_eq!(
+            Track::from_css_keyframes("a { left: 1px }", 1.0).unwrap_err(),
+            CssError::MissingKeyframes
+        );
+    }
+}
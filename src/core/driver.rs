@@ -0,0 +1,211 @@
+//! Links that make one track follow another.
+//!
+//! A [`Driver`] maps the value of a source track from an input range onto an
+//! output range and uses the result as the value of its target track, so a
+//! parameter follows another without duplicating keyframes:
+//!
+//! ```ignore
+//! let mut drivers = Drivers::new();
+//! drivers.add(
+//!     Driver::new(slider, door)
+//!         .input(0.0, 1.0)
+//!         .output(0.0, 90.0)
+//!         .with_easing(EasingPreset::EaseInOut),
+//! );
+//! let angle = drivers.evaluate(door, time, &|track, time| value_of(track, time));
+//! ```
+//!
+//! Drivers are evaluated at query time; the target's own keyframes are
+//! ignored while it is driven. The DopeSheet marks driven rows with a link
+//! icon, see [`PropertyRow::driven`](crate::PropertyRow::driven).
+
+use super::easing::EasingPreset;
+use super::interpolation::CubicBezier;
+use super::keyframe::BezierHandles;
+use super::time::TimeTick;
+use super::track::TrackId;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Drivers nested deeper than this are treated as a cycle.
+const MAX_CHAIN: usize = 32;
+
+/// A link setting a target track's value from a source track's value.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Driver {
+    /// Track whose value is read.
+    pub source: TrackId,
+    /// Track whose value is set.
+    pub target: TrackId,
+    /// Source values mapped onto `output`; values outside are clamped.
+    pub input: (f64, f64),
+    /// Target values `input` is mapped onto.
+    pub output: (f64, f64),
+    /// Easing of the mapping, linear if `None`.
+    pub easing: Option<BezierHandles>,
+}
+
+impl Driver {
+    /// Create a driver copying the value of `source` to `target`.
+    pub fn new(source: TrackId, target: TrackId) -> Self {
+        Self {
+            source,
+            target,
+            input: (0.0, 1.0),
+            output: (0.0, 1.0),
+            easing: None,
+        }
+    }
+
+    /// Set the range of source values.
+    pub fn input(mut self, min: f64, max: f64) -> Self {
+        self.input = (min, max);
+        self
+    }
+
+    /// Set the range of target values.
+    pub fn output(mut self, min: f64, max: f64) -> Self {
+        self.output = (min, max);
+        self
+    }
+
+    /// Ease the mapping with a preset.
+    pub fn with_easing(mut self, preset: EasingPreset) -> Self {
+        self.easing = Some(preset.handles());
+        self
+    }
+
+    /// Target value for a source `value`.
+    pub fn map(&self, value: f64) -> f64 {
+        let (from, to) = self.input;
+        let t = if to == from {
+            if value < from { 0.0 } else { 1.0 }
+        } else {
+            ((value - from) / (to - from)).clamp(0.0, 1.0)
+        };
+        let t = match self.easing {
+            Some(handles) => CubicBezier::new(
+                handles.right_x,
+                handles.right_y,
+                1.0 - handles.left_x,
+                1.0 - handles.left_y,
+            )
+            .solve(t as f32) as f64,
+            None => t,
+        };
+        self.output.0 + (self.output.1 - self.output.0) * t
+    }
+}
+
+/// Collection of [`Driver`]s, at most one per target track.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Drivers {
+    drivers: Vec<Driver>,
+}
+
+impl Drivers {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a driver, replacing the one of the same target.
+    ///
+    /// Returns `false`, and adds nothing, if the driver would close a cycle.
+    pub fn add(&mut self, driver: Driver) -> bool {
+        let mut source = Some(driver.source);
+        while let Some(track) = source {
+            if track == driver.target {
+                return false;
+            }
+            source = self.get(track).map(|upstream| upstream.source);
+        }
+        self.remove(driver.target);
+        self.drivers.push(driver);
+        true
+    }
+
+    /// Remove the driver of `target`.
+    pub fn remove(&mut self, target: TrackId) -> Option<Driver> {
+        let index = self
+            .drivers
+            .iter()
+            .position(|driver| driver.target == target)?;
+        Some(self.drivers.remove(index))
+    }
+
+    /// Get the driver of `target`.
+    pub fn get(&self, target: TrackId) -> Option<&Driver> {
+        self.drivers.iter().find(|driver| driver.target == target)
+    }
+
+    /// Check if `track` is driven.
+    pub fn is_driven(&self, track: TrackId) -> bool {
+        self.get(track).is_some()
+    }
+
+    /// Iterate over all drivers.
+    pub fn iter(&self) -> impl Iterator<Item = &Driver> {
+        self.drivers.iter()
+    }
+
+    /// Check if there are no drivers.
+    pub fn is_empty(&self) -> bool {
+        self.drivers.is_empty()
+    }
+
+    /// Value of `track` at `time`, following drivers upstream; `value`
+    /// evaluates the keyframes of an undriven track.
+    pub fn evaluate(
+        &self,
+        track: TrackId,
+        time: TimeTick,
+        value: &dyn Fn(TrackId, TimeTick) -> Option<f64>,
+    ) -> Option<f64> {
+        let mut chain = Vec::new();
+        let mut source = track;
+        while let Some(driver) = self.get(source) {
+            if chain.len() == MAX_CHAIN {
+                return None;
+            }
+            chain.push(driver);
+            source = driver.source;
+        }
+        let value = value(source, time)?;
+        Some(
+            chain
+                .iter()
+                .rev()
+                .fold(value, |value, driver| driver.map(value)),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_source_values_through_chains() {
+        let (slider, door, lamp) = (TrackId::new(), TrackId::new(), TrackId::new());
+        let mut drivers = Drivers::new();
+        assert!(drivers.add(Driver::new(slider, door).input(0.0, 1.0).output(0.0, 90.0)));
+        assert!(drivers.add(Driver::new(door, lamp).input(0.0, 90.0).output(1.0, 0.0)));
+        assert!(!drivers.add(Driver::new(lamp, slider)));
+        assert!(drivers.is_driven(door) && !drivers.is_driven(slider));
+
+        let value = |track: TrackId, time: TimeTick| (track == slider).then_some(time.value());
+        let at = |track, time: f64| drivers.evaluate(track, TimeTick::new(time), &value);
+        assert_eq!(at(door, 0.5), Some(45.0));
+        assert_eq!(at(door, 2.0), Some(90.0));
+        assert_eq!(at(lamp, 0.25), Some(0.75));
+        assert_eq!(at(slider, 0.25), Some(0.25));
+
+        let eased = Driver::new(slider, door).with_easing(EasingPreset::EaseInOut);
+        assert!(eased.map(0.25) < 0.25);
+        assert!((eased.map(0.5) - 0.5).abs() < 1e-3);
+    }
+}
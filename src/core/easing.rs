@@ -1,8 +1,18 @@
 //! Easing presets for animation curves.
 //!
 //! This module provides common easing functions as bezier control points.
+//! Bounces and elastic wobbles need more than one segment; a
+//! [`MultiEasing`] is expanded into generated keyframes between two keys by
+//! [`apply_preset_multi`].
 
-use super::keyframe::BezierHandles;
+use crate::traits::AnimationCommand;
+
+use super::interpolation::{fit_control_points, resolved_handles, segment_control_points};
+use super::keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType};
+use super::track::TrackId;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 /// Named easing preset.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -182,3 +192,445 @@ pub fn match_preset(handles: &BezierHandles, tolerance: f32) -> Option<EasingPre
     }
     None
 }
+
+/// A built-in or user-defined easing.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Easing {
+    /// A built-in preset.
+    Preset(EasingPreset),
+    /// A preset of an [`EasingLibrary`], by name.
+    Custom(String),
+}
+
+impl Easing {
+    /// Get the display name.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Preset(preset) => preset.name(),
+            Self::Custom(name) => name,
+        }
+    }
+}
+
+/// Named user-defined easing presets, kept in the order they were added.
+///
+/// Handles follow the convention of [`EasingPreset::handles`]. The library
+/// serializes with serde, so hosts can save it with their settings.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EasingLibrary {
+    presets: Vec<(String, BezierHandles)>,
+}
+
+impl EasingLibrary {
+    /// Create an empty library.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a preset, replacing the one with the same name.
+    pub fn add(&mut self, name: impl Into<String>, handles: BezierHandles) {
+        let name = name.into();
+        match self.presets.iter_mut().find(|(other, _)| *other == name) {
+            Some((_, existing)) => *existing = handles,
+            None => self.presets.push((name, handles)),
+        }
+    }
+
+    /// Remove a preset.
+    pub fn remove(&mut self, name: &str) -> Option<BezierHandles> {
+        let index = self.presets.iter().position(|(other, _)| other == name)?;
+        Some(self.presets.remove(index).1)
+    }
+
+    /// Get the handles of a preset.
+    pub fn get(&self, name: &str) -> Option<BezierHandles> {
+        self.presets
+            .iter()
+            .find(|(other, _)| other == name)
+            .map(|(_, handles)| *handles)
+    }
+
+    /// Iterate over the presets as `(name, handles)`.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, BezierHandles)> {
+        self.presets
+            .iter()
+            .map(|(name, handles)| (name.as_str(), *handles))
+    }
+
+    /// Check if the library has no presets.
+    pub fn is_empty(&self) -> bool {
+        self.presets.is_empty()
+    }
+
+    /// Try to match handles to a built-in preset, then to one of the
+    /// library.
+    pub fn match_preset(&self, handles: &BezierHandles, tolerance: f32) -> Option<Easing> {
+        match_preset(handles, tolerance)
+            .map(Easing::Preset)
+            .or_else(|| {
+                self.presets
+                    .iter()
+                    .find(|(_, preset)| handles_similar(handles, preset, tolerance))
+                    .map(|(name, _)| Easing::Custom(name.clone()))
+            })
+    }
+}
+
+/// Easing handles of the segment from `keyframes[index]` to the next
+/// keyframe, in the convention of [`EasingPreset::handles`], for
+/// [`match_preset`] and [`EasingLibrary::match_preset`].
+///
+/// `keyframes` must be sorted by position and `index + 1` in bounds.
+pub fn segment_easing(keyframes: &[&Keyframe<f32>], index: usize) -> BezierHandles {
+    let [x1, y1, x2, y2] = segment_control_points(keyframes, index);
+    BezierHandles::from_css(x1, y1, x2, y2)
+}
+
+/// Commands that give the segment from `keyframes[index]` to the next
+/// keyframe the easing `handles`, in the convention of
+/// [`EasingPreset::handles`].
+///
+/// Emits, per end key, an [`AnimationCommand::SetKeyframeType`] if it has
+/// to become a bezier key, an [`AnimationCommand::SetKeyframeWeightedHandles`]
+/// clearing weighted handles, and an [`AnimationCommand::SetKeyframeHandles`]
+/// that keeps its handle on the other side.
+///
+/// `keyframes` must be sorted by position and `index + 1` in bounds.
+pub fn ease_segment_commands(
+    keyframes: &[&Keyframe<f32>],
+    index: usize,
+    handles: BezierHandles,
+) -> Vec<AnimationCommand> {
+    let (from, to) = (keyframes[index], keyframes[index + 1]);
+    let mut from_handles = resolved_handles(keyframes, index);
+    [from_handles.right_x, from_handles.right_y] = [handles.right_x, handles.right_y];
+    let mut to_handles = resolved_handles(keyframes, index + 1);
+    [to_handles.left_x, to_handles.left_y] = [1.0 - handles.left_x, 1.0 - handles.left_y];
+
+    let mut commands = Vec::new();
+    for (keyframe, handles, retype) in [
+        (
+            from,
+            from_handles,
+            from.keyframe_type != KeyframeType::Bezier,
+        ),
+        (to, to_handles, to.keyframe_type == KeyframeType::Auto),
+    ] {
+        if retype {
+            commands.push(AnimationCommand::SetKeyframeType {
+                keyframe_id: keyframe.id,
+                keyframe_type: KeyframeType::Bezier,
+            });
+        }
+        commands.extend(keyframe_commands(keyframe, handles));
+    }
+    commands
+}
+
+/// Easing that no single bezier segment can express.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MultiEasing {
+    BounceIn,
+    BounceOut,
+    BounceInOut,
+    ElasticIn,
+    ElasticOut,
+    ElasticInOut,
+}
+
+impl MultiEasing {
+    /// Get the display name for this preset.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BounceIn => "Bounce In",
+            Self::BounceOut => "Bounce Out",
+            Self::BounceInOut => "Bounce In Out",
+            Self::ElasticIn => "Elastic In",
+            Self::ElasticOut => "Elastic Out",
+            Self::ElasticInOut => "Elastic In Out",
+        }
+    }
+
+    /// Get all presets.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::BounceIn,
+            Self::BounceOut,
+            Self::BounceInOut,
+            Self::ElasticIn,
+            Self::ElasticOut,
+            Self::ElasticInOut,
+        ]
+    }
+
+    /// Eased progress at `t` in `0..=1`, after easings.net.
+    pub fn evaluate(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::BounceIn => 1.0 - bounce_out(1.0 - t),
+            Self::BounceOut => bounce_out(t),
+            Self::BounceInOut if t < 0.5 => (1.0 - bounce_out(1.0 - 2.0 * t)) / 2.0,
+            Self::BounceInOut => (1.0 + bounce_out(2.0 * t - 1.0)) / 2.0,
+            Self::ElasticIn => 1.0 - elastic_out(1.0 - t),
+            Self::ElasticOut => elastic_out(t),
+            Self::ElasticInOut if t == 0.0 || t == 1.0 => t,
+            Self::ElasticInOut => {
+                let wave = ((20.0 * t - 11.125) * std::f64::consts::TAU / 4.5).sin();
+                if t < 0.5 {
+                    -(20.0 * t - 10.0).exp2() * wave / 2.0
+                } else {
+                    (-20.0 * t + 10.0).exp2() * wave / 2.0 + 1.0
+                }
+            }
+        }
+    }
+
+    /// Times in `0..=1` of the generated keys, both ends included: every
+    /// turning point of the curve, plus the middle of in-out presets.
+    fn breakpoints(&self) -> Vec<f64> {
+        const SAMPLES: usize = 2048;
+        let step = 1.0 / SAMPLES as f64;
+        let values: Vec<f64> = (0..=SAMPLES)
+            .map(|i| self.evaluate(i as f64 * step))
+            .collect();
+        let mut times = vec![0.0];
+        for i in 1..SAMPLES {
+            let (rise, fall) = (values[i] - values[i - 1], values[i + 1] - values[i]);
+            if rise * fall < 0.0 {
+                times.push(self.turning_point(
+                    (i - 1) as f64 * step,
+                    (i + 1) as f64 * step,
+                    rise > 0.0,
+                ));
+            }
+        }
+        if matches!(self, Self::BounceInOut | Self::ElasticInOut) {
+            times.push(0.5);
+            times.sort_by(f64::total_cmp);
+        }
+        times.push(1.0);
+        times
+    }
+
+    /// Refine the maximum, or minimum, of the curve within `from..to` by
+    /// golden-section search.
+    fn turning_point(&self, mut from: f64, mut to: f64, maximum: bool) -> f64 {
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let better = |a: f64, b: f64| {
+            let (a, b) = (self.evaluate(a), self.evaluate(b));
+            if maximum { a > b } else { a < b }
+        };
+        for _ in 0..40 {
+            let a = to - (to - from) * ratio;
+            let b = from + (to - from) * ratio;
+            if better(a, b) {
+                to = b;
+            } else {
+                from = a;
+            }
+        }
+        (from + to) / 2.0
+    }
+}
+
+fn bounce_out(t: f64) -> f64 {
+    const N1: f64 = 7.5625;
+    const D1: f64 = 2.75;
+    if t < 1.0 / D1 {
+        N1 * t * t
+    } else if t < 2.0 / D1 {
+        let t = t - 1.5 / D1;
+        N1 * t * t + 0.75
+    } else if t < 2.5 / D1 {
+        let t = t - 2.25 / D1;
+        N1 * t * t + 0.9375
+    } else {
+        let t = t - 2.625 / D1;
+        N1 * t * t + 0.984375
+    }
+}
+
+fn elastic_out(t: f64) -> f64 {
+    if t == 0.0 || t == 1.0 {
+        return t;
+    }
+    (-10.0 * t).exp2() * ((10.0 * t - 0.75) * std::f64::consts::TAU / 3.0).sin() + 1.0
+}
+
+/// Commands that shape the segment from `from` to `to` of track `track_id`
+/// like `preset` by inserting bezier keyframes at its turning points.
+///
+/// Emits, in order, the [`AnimationCommand::SetKeyframeType`],
+/// [`AnimationCommand::SetKeyframeWeightedHandles`] and
+/// [`AnimationCommand::SetKeyframeHandles`] of `from`, an
+/// [`AnimationCommand::InsertKeyframe`] per generated key, and the weighted
+/// and plain handles of `to`. Keys already between the two are left alone;
+/// remove them first. Returns no commands if the keys have the same time or
+/// value.
+pub fn apply_preset_multi(
+    track_id: TrackId,
+    from: &Keyframe<f32>,
+    to: &Keyframe<f32>,
+    preset: MultiEasing,
+) -> Vec<AnimationCommand> {
+    let duration = to.position - from.position;
+    let change = to.value - from.value;
+    if duration.value() <= 0.0 || change == 0.0 {
+        return Vec::new();
+    }
+
+    let times = preset.breakpoints();
+    let mut handles: Vec<BezierHandles> = times.iter().map(|_| BezierHandles::default()).collect();
+    handles[0] = from.handles;
+    handles[times.len() - 1] = to.handles;
+    for (index, pair) in times.windows(2).enumerate() {
+        let [x1, y1, x2, y2] = fit_segment(preset, pair[0], pair[1]);
+        [handles[index].right_x, handles[index].right_y] = [x1, y1];
+        [handles[index + 1].left_x, handles[index + 1].left_y] = [x2, y2];
+    }
+
+    let mut commands = vec![AnimationCommand::SetKeyframeType {
+        keyframe_id: from.id,
+        keyframe_type: KeyframeType::Bezier,
+    }];
+    commands.extend(keyframe_commands(from, handles[0]));
+    for (time, handles) in times.iter().zip(&handles).skip(1).take(times.len() - 2) {
+        commands.push(AnimationCommand::InsertKeyframe {
+            track_id,
+            keyframe_id: KeyframeId::new(),
+            position: from.position + duration * *time,
+            value: (from.value + change * preset.evaluate(*time) as f32) as f64,
+            handles: *handles,
+            keyframe_type: KeyframeType::Bezier,
+        });
+    }
+    commands.extend(keyframe_commands(to, handles[times.len() - 1]));
+    commands
+}
+
+/// Commands replacing the handles of `keyframe`, clearing weighted ones.
+fn keyframe_commands(
+    keyframe: &Keyframe<f32>,
+    handles: BezierHandles,
+) -> impl Iterator<Item = AnimationCommand> {
+    keyframe
+        .weighted
+        .map(|_| AnimationCommand::SetKeyframeWeightedHandles {
+            keyframe_id: keyframe.id,
+            handles: None,
+        })
+        .into_iter()
+        .chain(std::iter::once(AnimationCommand::SetKeyframeHandles {
+            keyframe_id: keyframe.id,
+            handles,
+        }))
+}
+
+/// Normalized control points of the bezier closest to `preset` from time
+/// `from` to `to`.
+fn fit_segment(preset: MultiEasing, from: f64, to: f64) -> [f32; 4] {
+    const SAMPLES: usize = 16;
+    let (start, end) = (preset.evaluate(from), preset.evaluate(to));
+    let (duration, change) = (to - from, end - start);
+    if change.abs() < 1e-9 {
+        return [1.0 / 3.0, 1.0 / 3.0, 2.0 / 3.0, 2.0 / 3.0];
+    }
+    // One-sided slopes, as bounces have corners at their keys.
+    let step = duration * 1e-4;
+    let normalize = |slope: f64| (slope * duration / change) as f32;
+    let start_slope = (preset.evaluate(from + step) - start) / step;
+    let end_slope = (end - preset.evaluate(to - step)) / step;
+    let samples: Vec<(f32, f32)> = (1..SAMPLES)
+        .map(|i| {
+            let x = i as f64 / SAMPLES as f64;
+            let value = preset.evaluate(from + duration * x);
+            (x as f32, ((value - start) / change) as f32)
+        })
+        .collect();
+    fit_control_points(&samples, normalize(start_slope), normalize(end_slope))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleAnimationModel;
+    use crate::core::track::Track;
+    use crate::traits::AnimationDataMutator;
+
+    #[test]
+    fn multi_segment_presets_follow_their_easing() {
+        for &preset in MultiEasing::all() {
+            let mut track = Track::new();
+            let from = Keyframe::new(1.0, 10.0_f32);
+            let to = Keyframe::new(3.0, -10.0_f32);
+            track.add_keyframe(from.clone());
+            track.add_keyframe(to.clone());
+            let mut model = SimpleAnimationModel::new();
+            let group = model.add_group("Ball");
+            let track_id = model.add_track(&group, "Y", track).unwrap();
+
+            for command in apply_preset_multi(track_id, &from, &to, preset) {
+                model.execute(command);
+            }
+            let track = model.track(track_id).unwrap();
+            assert!(track.len() > 4, "{}", preset.name());
+            for i in 0..=100 {
+                let t = i as f64 / 100.0;
+                let expected = 10.0 - 20.0 * preset.evaluate(t) as f32;
+                let value = track.evaluate(1.0 + 2.0 * t).unwrap();
+                assert!((value - expected).abs() < 0.1, "{} at {t}", preset.name());
+            }
+        }
+    }
+
+    #[test]
+    fn custom_presets_match_and_apply() {
+        let snappy = BezierHandles::from_css(0.1, 0.9, 0.2, 1.0);
+        let mut library = EasingLibrary::new();
+        library.add("Snappy", BezierHandles::linear());
+        library.add("Snappy", snappy);
+        assert_eq!(library.iter().count(), 1);
+        assert_eq!(
+            library.match_preset(&snappy, 0.01),
+            Some(Easing::Custom("Snappy".into()))
+        );
+        assert_eq!(
+            library.match_preset(&EasingPreset::EaseIn.handles(), 0.01),
+            Some(Easing::Preset(EasingPreset::EaseIn))
+        );
+
+        let mut track = Track::new();
+        let from = track.add_keyframe(Keyframe::new(0.0, 0.0_f32).with_type(KeyframeType::Auto));
+        track.add_keyframe(Keyframe::new(1.0, 5.0_f32).with_type(KeyframeType::Auto));
+        track.add_keyframe(Keyframe::new(2.0, 0.0_f32));
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Ball");
+        let track_id = model.add_track(&group, "Y", track).unwrap();
+
+        let commands = ease_segment_commands(
+            &model.track(track_id).unwrap().keyframes_sorted(),
+            0,
+            snappy,
+        );
+        for command in commands {
+            model.execute(command);
+        }
+        let keyframes = model.track(track_id).unwrap().keyframes_sorted();
+        assert_eq!(keyframes[0].id, from);
+        assert_eq!(
+            library.match_preset(&segment_easing(&keyframes, 0), 0.01),
+            Some(Easing::Custom("Snappy".into()))
+        );
+        // The second key keeps its auto tangent towards the third.
+        assert_eq!(keyframes[1].keyframe_type, KeyframeType::Bezier);
+        assert!((keyframes[1].handles.right_y).abs() < 1e-6);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&library).unwrap();
+            let loaded: EasingLibrary = serde_json::from_str(&json).unwrap();
+            assert_eq!(loaded, library);
+        }
+    }
+}
@@ -0,0 +1,215 @@
+//! Euler angle cleanup for imported rotations.
+//!
+//! Rotations baked by other tools often jump by 360° where an angle wraps
+//! around, or flip to the equivalent `(x + 180°, 180° - y, z + 180°)` near
+//! gimbal lock. Both look right on a single frame but spin the object when
+//! interpolated. [`euler_filter`] picks, for every key, the equivalent angles
+//! closest to the previous key:
+//!
+//! ```ignore
+//! let changed = euler_filter([&mut rotate_x, &mut rotate_y, &mut rotate_z]);
+//! ```
+//!
+//! [`euler_filter_commands`] plans the same edits as [`AnimationCommand`]s
+//! for undoable use through a provider. Angles are in degrees and the tracks
+//! are passed in rotation order, so the middle one is the axis that flips.
+
+use crate::traits::AnimationCommand;
+
+use super::keyframe::{KeyframeId, WeightedHandles};
+use super::time::TimeTick;
+use super::track::Track;
+
+/// A key value replaced by [`plan`].
+struct EulerEdit {
+    axis: usize,
+    keyframe_id: KeyframeId,
+    value: f32,
+    /// Whether the value was mirrored, which mirrors its weighted handles.
+    mirrored: bool,
+}
+
+/// Remove 360° wraps and gimbal flips from three rotation tracks in degrees,
+/// given in rotation order.
+///
+/// Keys present on all three tracks at the same time may flip to the
+/// equivalent orientation; other keys are only unwrapped. Returns the number
+/// of keys changed.
+pub fn euler_filter(tracks: [&mut Track<f32>; 3]) -> usize {
+    let edits = plan([&*tracks[0], &*tracks[1], &*tracks[2]]);
+    for edit in &edits {
+        if let Some(keyframe) = tracks[edit.axis].get_keyframe_mut(edit.keyframe_id) {
+            keyframe.value = edit.value;
+            if edit.mirrored {
+                keyframe.weighted = keyframe.weighted.map(mirror);
+            }
+        }
+    }
+    edits.len()
+}
+
+/// Commands that apply [`euler_filter`] to three tracks.
+///
+/// Emits a [`AnimationCommand::SetKeyframeValue`] per changed key, followed
+/// by an [`AnimationCommand::SetKeyframeWeightedHandles`] for flipped keys
+/// with weighted handles, in time order.
+pub fn euler_filter_commands(tracks: [&Track<f32>; 3]) -> Vec<AnimationCommand> {
+    let mut commands = Vec::new();
+    for edit in plan(tracks) {
+        commands.push(AnimationCommand::SetKeyframeValue {
+            keyframe_id: edit.keyframe_id,
+            value: edit.value as f64,
+        });
+        let weighted = tracks[edit.axis]
+            .get_keyframe(edit.keyframe_id)
+            .and_then(|keyframe| keyframe.weighted);
+        if let Some(handles) = weighted.filter(|_| edit.mirrored) {
+            commands.push(AnimationCommand::SetKeyframeWeightedHandles {
+                keyframe_id: edit.keyframe_id,
+                handles: Some(mirror(handles)),
+            });
+        }
+    }
+    commands
+}
+
+/// `angles` unwrapped to be closest to `previous`, or their flipped
+/// equivalent if that is closer.
+///
+/// Axes without a previous angle are left as they are.
+pub fn closest_euler(previous: [Option<f32>; 3], angles: [f32; 3]) -> [f32; 3] {
+    closest(previous, angles).0
+}
+
+/// [`closest_euler`] and whether the angles were flipped.
+fn closest(previous: [Option<f32>; 3], angles: [f32; 3]) -> ([f32; 3], bool) {
+    let flipped = [angles[0] + 180.0, 180.0 - angles[1], angles[2] + 180.0];
+    let direct = unwrap(previous, angles);
+    let flipped = unwrap(previous, flipped);
+    // Prefer the unflipped angles unless flipping is clearly closer.
+    if distance(previous, flipped) + 1e-3 < distance(previous, direct) {
+        (flipped, true)
+    } else {
+        (direct, false)
+    }
+}
+
+/// Edits that filter `tracks`, in time order.
+fn plan(tracks: [&Track<f32>; 3]) -> Vec<EulerEdit> {
+    let mut times: Vec<TimeTick> = tracks
+        .iter()
+        .flat_map(|track| track.iter().map(|keyframe| keyframe.position))
+        .collect();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    times.dedup();
+
+    let sorted = tracks.map(Track::keyframes_sorted);
+    let mut next = [0usize; 3];
+    let mut previous = [None; 3];
+    let mut edits = Vec::new();
+    for time in times {
+        let keys: [_; 3] = std::array::from_fn(|axis| {
+            let keyframe = sorted[axis]
+                .get(next[axis])
+                .filter(|keyframe| keyframe.position == time)?;
+            next[axis] += 1;
+            Some(*keyframe)
+        });
+
+        let (filtered, flipped) = if keys.iter().all(Option::is_some) {
+            closest(previous, keys.map(|key| key.map_or(0.0, |key| key.value)))
+        } else {
+            // Flipping one axis alone would change the orientation.
+            let unwrapped = std::array::from_fn(|axis| {
+                keys[axis].map_or(0.0, |key| unwrap_angle(previous[axis], key.value))
+            });
+            (unwrapped, false)
+        };
+
+        for (axis, key) in keys.iter().enumerate() {
+            let Some(key) = key else { continue };
+            previous[axis] = Some(filtered[axis]);
+            if (filtered[axis] - key.value).abs() > 1e-4 {
+                edits.push(EulerEdit {
+                    axis,
+                    keyframe_id: key.id,
+                    value: filtered[axis],
+                    // Only the middle angle changes sign when flipping.
+                    mirrored: flipped && axis == 1,
+                });
+            }
+        }
+    }
+    edits
+}
+
+fn unwrap(previous: [Option<f32>; 3], angles: [f32; 3]) -> [f32; 3] {
+    std::array::from_fn(|axis| unwrap_angle(previous[axis], angles[axis]))
+}
+
+/// `angle` plus the multiple of 360° that brings it closest to `previous`.
+fn unwrap_angle(previous: Option<f32>, angle: f32) -> f32 {
+    previous.map_or(angle, |previous| {
+        angle + 360.0 * ((previous - angle) / 360.0).round()
+    })
+}
+
+fn distance(previous: [Option<f32>; 3], angles: [f32; 3]) -> f32 {
+    previous
+        .iter()
+        .zip(angles)
+        .filter_map(|(previous, angle)| previous.map(|previous| (angle - previous).abs()))
+        .sum()
+}
+
+fn mirror(handles: WeightedHandles) -> WeightedHandles {
+    WeightedHandles {
+        left_value: -handles.left_value,
+        right_value: -handles.right_value,
+        ..handles
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::Keyframe;
+
+    #[test]
+    fn removes_wraps_and_flips() {
+        let mut x = Track::<f32>::new();
+        let mut y = Track::<f32>::new();
+        let mut z = Track::<f32>::new();
+        // Frame 2 wraps past 180°; frame 3 is the gimbal-flipped twin of
+        // `(200, 80, 0)`.
+        for (time, [a, b, c]) in [
+            (0.0, [170.0, 70.0, 0.0]),
+            (1.0, [185.0, 75.0, 0.0]),
+            (2.0, [-165.0, 78.0, 0.0]),
+            (3.0, [20.0, 100.0, 180.0]),
+        ] {
+            x.add_keyframe(Keyframe::new(time, a));
+            y.add_keyframe(Keyframe::new(time, b));
+            z.add_keyframe(Keyframe::new(time, c));
+        }
+        // A key only on X is unwrapped but never flipped.
+        x.add_keyframe(Keyframe::new(4.0, -150.0));
+
+        let commands = euler_filter_commands([&x, &y, &z]);
+        assert!(matches!(
+            commands.first(),
+            Some(AnimationCommand::SetKeyframeValue { value, .. }) if *value == 195.0
+        ));
+
+        assert_eq!(euler_filter([&mut x, &mut y, &mut z]), commands.len());
+        let values = |time: f64| {
+            [&x, &y, &z].map(|track| track.keyframe_at_position(time, 1e-6).unwrap().value)
+        };
+        assert_eq!(values(2.0), [195.0, 78.0, 0.0]);
+        assert_eq!(values(3.0), [200.0, 80.0, 0.0]);
+        assert_eq!(x.keyframe_at_position(4.0, 1e-6).unwrap().value, 210.0);
+
+        // Filtering again changes nothing.
+        assert_eq!(euler_filter([&mut x, &mut y, &mut z]), 0);
+    }
+}
@@ -0,0 +1,541 @@
+//! Expression-driven values.
+//!
+//! An [`Expression`] computes a value from the time `t`, named parameters and
+//! other tracks instead of keyframes:
+//!
+//! ```ignore
+//! let wobble = Expression::parse("sin(t * 2) * amplitude + base(t - 0.1)")?;
+//! let variables = ExpressionVariables::new()
+//!     .constant("amplitude", 5.0)
+//!     .track("base", &base_track);
+//! let value = wobble.evaluate(1.5, &variables)?;
+//! ```
+//!
+//! A bare name is a parameter or another track at `t`; calling a track like
+//! a function evaluates it at another time. Besides `+ - * / % ^` and
+//! parentheses, expressions know the constants `pi`, `tau` and `e` and the
+//! functions `sin`, `cos`, `tan`, `asin`, `acos`, `atan`, `atan2`, `sqrt`,
+//! `abs`, `sign`, `floor`, `ceil`, `round`, `fract`, `exp`, `ln`, `log10`,
+//! `pow`, `min`, `max`, `clamp`, `lerp` and `noise`.
+//!
+//! Draw an expression with
+//! [`CurveEditor::read_only_curve`](crate::CurveEditor::read_only_curve) and
+//! turn it into keyframes with [`Expression::bake_commands`].
+
+use crate::HashMap;
+use crate::traits::AnimationCommand;
+
+use super::keyframe::{BezierHandles, KeyframeId, KeyframeType};
+use super::modifier::perlin;
+use super::time::TimeTick;
+use super::track::{Track, TrackId};
+
+/// Error parsing or evaluating an [`Expression`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionError {
+    /// The text is not a valid expression at byte `position`.
+    Syntax { position: usize, message: String },
+    /// A name is neither built in nor known to the context.
+    UnknownVariable(String),
+    /// A function is called with the wrong number of arguments.
+    Arguments { function: String, count: usize },
+}
+
+impl std::fmt::Display for ExpressionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax { position, message } => {
+                write!(f, "expression syntax error at {position}: {message}")
+            }
+            Self::UnknownVariable(name) => write!(f, "unknown variable `{name}`"),
+            Self::Arguments { function, count } => {
+                write!(f, "`{function}` does not take {count} arguments")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpressionError {}
+
+/// Values an [`Expression`] looks up by name.
+pub trait ExpressionContext {
+    /// Value of `name` at `time`, e.g. a parameter or another track's
+    /// evaluated value; `None` if the name is unknown.
+    fn variable(&self, name: &str, time: TimeTick) -> Option<f64>;
+}
+
+/// No variables besides the built-ins.
+impl ExpressionContext for () {
+    fn variable(&self, _name: &str, _time: TimeTick) -> Option<f64> {
+        None
+    }
+}
+
+/// Named constants and tracks for evaluating an [`Expression`].
+#[derive(Debug, Clone, Default)]
+pub struct ExpressionVariables<'a> {
+    constants: HashMap<String, f64>,
+    tracks: HashMap<String, &'a Track<f32>>,
+}
+
+impl<'a> ExpressionVariables<'a> {
+    /// Create an empty set of variables.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a constant parameter.
+    pub fn constant(mut self, name: impl Into<String>, value: f64) -> Self {
+        self.constants.insert(name.into(), value);
+        self
+    }
+
+    /// Add a track, evaluated at the time it is looked up at.
+    pub fn track(mut self, name: impl Into<String>, track: &'a Track<f32>) -> Self {
+        self.tracks.insert(name.into(), track);
+        self
+    }
+}
+
+impl ExpressionContext for ExpressionVariables<'_> {
+    fn variable(&self, name: &str, time: TimeTick) -> Option<f64> {
+        self.constants.get(name).copied().or_else(|| {
+            self.tracks
+                .get(name)?
+                .evaluate(time)
+                .map(|value| value as f64)
+        })
+    }
+}
+
+/// A parsed expression of time, see the [module docs](self).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression {
+    source: String,
+    root: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Number(f64),
+    Variable(String),
+    Negate(Box<Node>),
+    Binary(char, Box<Node>, Box<Node>),
+    Call(String, Vec<Node>),
+}
+
+impl Expression {
+    /// Parse `source`.
+    pub fn parse(source: &str) -> Result<Self, ExpressionError> {
+        let mut parser = Parser {
+            source: source.as_bytes(),
+            position: 0,
+        };
+        let root = parser.sum()?;
+        parser.skip_whitespace();
+        if parser.position < source.len() {
+            return Err(parser.error("unexpected input"));
+        }
+        Ok(Self {
+            source: source.to_owned(),
+            root,
+        })
+    }
+
+    /// The text the expression was parsed from.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Value at `time`.
+    pub fn evaluate(
+        &self,
+        time: impl Into<TimeTick>,
+        context: &dyn ExpressionContext,
+    ) -> Result<f64, ExpressionError> {
+        evaluate(&self.root, time.into(), context)
+    }
+
+    /// Sample the expression at every frame of `fps` within the global time
+    /// `range`, both ends inclusive, like [`Track::bake`].
+    pub fn bake(
+        &self,
+        fps: f64,
+        range: (TimeTick, TimeTick),
+        context: &dyn ExpressionContext,
+    ) -> Result<Vec<(TimeTick, f64)>, ExpressionError> {
+        if fps <= 0.0 || !fps.is_finite() {
+            return Ok(Vec::new());
+        }
+        let first = (range.0.to_frames(fps) - 1e-6).ceil() as i64;
+        let last = (range.1.to_frames(fps) + 1e-6).floor() as i64;
+        (first..=last)
+            .map(|frame| {
+                let time = TimeTick::from_frames(frame as f64, fps);
+                Ok((time, self.evaluate(time, context)?))
+            })
+            .collect()
+    }
+
+    /// Commands that replace the keyframes `replaced` of track `track_id`
+    /// with linear keyframes baked from the expression, see [`Self::bake`].
+    ///
+    /// Emits one [`AnimationCommand::RemoveKeyframes`], if `replaced` is not
+    /// empty, followed by an [`AnimationCommand::InsertKeyframe`] per frame.
+    pub fn bake_commands(
+        &self,
+        track_id: TrackId,
+        replaced: Vec<KeyframeId>,
+        fps: f64,
+        range: (TimeTick, TimeTick),
+        context: &dyn ExpressionContext,
+    ) -> Result<Vec<AnimationCommand>, ExpressionError> {
+        let samples = self.bake(fps, range, context)?;
+        let mut commands = Vec::with_capacity(samples.len() + 1);
+        if !replaced.is_empty() {
+            commands.push(AnimationCommand::RemoveKeyframes {
+                keyframe_ids: replaced,
+            });
+        }
+        commands.extend(samples.into_iter().map(|(position, value)| {
+            AnimationCommand::InsertKeyframe {
+                track_id,
+                keyframe_id: KeyframeId::new(),
+                position,
+                value,
+                handles: BezierHandles::default(),
+                keyframe_type: KeyframeType::Linear,
+            }
+        }));
+        Ok(commands)
+    }
+}
+
+impl std::str::FromStr for Expression {
+    type Err = ExpressionError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+fn evaluate(
+    node: &Node,
+    time: TimeTick,
+    context: &dyn ExpressionContext,
+) -> Result<f64, ExpressionError> {
+    Ok(match node {
+        Node::Number(value) => *value,
+        Node::Variable(name) => match name.as_str() {
+            "t" | "time" => time.value(),
+            "pi" => std::f64::consts::PI,
+            "tau" => std::f64::consts::TAU,
+            "e" => std::f64::consts::E,
+            _ => context
+                .variable(name, time)
+                .ok_or_else(|| ExpressionError::UnknownVariable(name.clone()))?,
+        },
+        Node::Negate(operand) => -evaluate(operand, time, context)?,
+        Node::Binary(operator, left, right) => {
+            let (left, right) = (
+                evaluate(left, time, context)?,
+                evaluate(right, time, context)?,
+            );
+            match operator {
+                '+' => left + right,
+                '-' => left - right,
+                '*' => left * right,
+                '/' => left / right,
+                '%' => left.rem_euclid(right),
+                _ => left.powf(right),
+            }
+        }
+        Node::Call(name, arguments) => {
+            let arguments = arguments
+                .iter()
+                .map(|argument| evaluate(argument, time, context))
+                .collect::<Result<Vec<f64>, _>>()?;
+            call(name, &arguments, context)?
+        }
+    })
+}
+
+fn call(
+    name: &str,
+    arguments: &[f64],
+    context: &dyn ExpressionContext,
+) -> Result<f64, ExpressionError> {
+    let unary = |function: fn(f64) -> f64| match arguments {
+        [x] => Some(function(*x)),
+        _ => None,
+    };
+    let value = match name {
+        "sin" => unary(f64::sin),
+        "cos" => unary(f64::cos),
+        "tan" => unary(f64::tan),
+        "asin" => unary(f64::asin),
+        "acos" => unary(f64::acos),
+        "atan" => unary(f64::atan),
+        "sqrt" => unary(f64::sqrt),
+        "abs" => unary(f64::abs),
+        "sign" => unary(f64::signum),
+        "floor" => unary(f64::floor),
+        "ceil" => unary(f64::ceil),
+        "round" => unary(f64::round),
+        "fract" => unary(|x| x - x.floor()),
+        "exp" => unary(f64::exp),
+        "ln" => unary(f64::ln),
+        "log10" => unary(f64::log10),
+        "noise" => unary(|x| perlin(x, 0)),
+        "atan2" | "pow" | "min" | "max" => match arguments {
+            [a, b] => Some(match name {
+                "atan2" => a.atan2(*b),
+                "pow" => a.powf(*b),
+                "min" => a.min(*b),
+                _ => a.max(*b),
+            }),
+            _ => None,
+        },
+        "clamp" => match arguments {
+            [x, min, max] => Some(x.max(*min).min(*max)),
+            _ => None,
+        },
+        "lerp" => match arguments {
+            [a, b, t] => Some(a + (b - a) * t),
+            _ => None,
+        },
+        // Any other name is a variable evaluated at another time.
+        _ => match arguments {
+            [time] => Some(
+                context
+                    .variable(name, TimeTick::new(*time))
+                    .ok_or_else(|| ExpressionError::UnknownVariable(name.to_owned()))?,
+            ),
+            _ => None,
+        },
+    };
+    value.ok_or_else(|| ExpressionError::Arguments {
+        function: name.to_owned(),
+        count: arguments.len(),
+    })
+}
+
+/// Recursive descent parser over the bytes of an expression.
+struct Parser<'a> {
+    source: &'a [u8],
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ExpressionError {
+        ExpressionError::Syntax {
+            position: self.position,
+            message: message.to_owned(),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self
+            .source
+            .get(self.position)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.position += 1;
+        }
+    }
+
+    /// Next non-whitespace byte, without consuming it.
+    fn peek(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.source.get(self.position).copied()
+    }
+
+    /// Consume `byte` if it comes next.
+    fn eat(&mut self, byte: u8) -> bool {
+        let found = self.peek() == Some(byte);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    /// `product (('+' | '-') product)*`
+    fn sum(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.product()?;
+        while let Some(operator @ (b'+' | b'-')) = self.peek() {
+            self.position += 1;
+            node = Node::Binary(operator as char, Box::new(node), Box::new(self.product()?));
+        }
+        Ok(node)
+    }
+
+    /// `unary (('*' | '/' | '%') unary)*`
+    fn product(&mut self) -> Result<Node, ExpressionError> {
+        let mut node = self.unary()?;
+        while let Some(operator @ (b'*' | b'/' | b'%')) = self.peek() {
+            self.position += 1;
+            node = Node::Binary(operator as char, Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+
+    /// `('-' | '+') unary | power`
+    fn unary(&mut self) -> Result<Node, ExpressionError> {
+        if self.eat(b'-') {
+            Ok(Node::Negate(Box::new(self.unary()?)))
+        } else if self.eat(b'+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    /// `atom ('^' unary)?`, so `-2^2` is `-4` and `2^-1` is `0.5`.
+    fn power(&mut self) -> Result<Node, ExpressionError> {
+        let base = self.atom()?;
+        if self.eat(b'^') {
+            Ok(Node::Binary('^', Box::new(base), Box::new(self.unary()?)))
+        } else {
+            Ok(base)
+        }
+    }
+
+    /// A number, a name, a call or a parenthesized expression.
+    fn atom(&mut self) -> Result<Node, ExpressionError> {
+        match self.peek() {
+            Some(b'(') => {
+                self.position += 1;
+                let node = self.sum()?;
+                if !self.eat(b')') {
+                    return Err(self.error("expected `)`"));
+                }
+                Ok(node)
+            }
+            Some(byte) if byte.is_ascii_digit() || byte == b'.' => self.number(),
+            Some(byte) if byte.is_ascii_alphabetic() || byte == b'_' => {
+                let name = self.name();
+                if !self.eat(b'(') {
+                    return Ok(Node::Variable(name));
+                }
+                let mut arguments = Vec::new();
+                if !self.eat(b')') {
+                    loop {
+                        arguments.push(self.sum()?);
+                        if self.eat(b')') {
+                            break;
+                        }
+                        if !self.eat(b',') {
+                            return Err(self.error("expected `,` or `)`"));
+                        }
+                    }
+                }
+                Ok(Node::Call(name, arguments))
+            }
+            Some(_) => Err(self.error("expected a number, name or `(`")),
+            None => Err(self.error("unexpected end")),
+        }
+    }
+
+    fn number(&mut self) -> Result<Node, ExpressionError> {
+        let start = self.position;
+        let digits = |parser: &mut Self| {
+            while parser
+                .source
+                .get(parser.position)
+                .is_some_and(|byte| byte.is_ascii_digit() || *byte == b'.')
+            {
+                parser.position += 1;
+            }
+        };
+        digits(self);
+        // An exponent, unless the `e` starts a name.
+        if matches!(self.source.get(self.position), Some(b'e' | b'E')) {
+            let sign = matches!(self.source.get(self.position + 1), Some(b'+' | b'-'));
+            let exponent = self.position + 1 + sign as usize;
+            if self.source.get(exponent).is_some_and(u8::is_ascii_digit) {
+                self.position = exponent;
+                digits(self);
+            }
+        }
+        std::str::from_utf8(&self.source[start..self.position])
+            .ok()
+            .and_then(|text| text.parse().ok())
+            .map(Node::Number)
+            .ok_or_else(|| ExpressionError::Syntax {
+                position: start,
+                message: "invalid number".to_owned(),
+            })
+    }
+
+    /// A name of letters, digits, `_` and `.`, e.g. `arm.rotate_x`.
+    fn name(&mut self) -> String {
+        let start = self.position;
+        while self
+            .source
+            .get(self.position)
+            .is_some_and(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'.'))
+        {
+            self.position += 1;
+        }
+        String::from_utf8_lossy(&self.source[start..self.position]).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::Keyframe;
+
+    #[test]
+    fn evaluates_and_bakes_expressions() {
+        let mut base = Track::<f32>::new();
+        base.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        base.add_keyframe(Keyframe::new(1.0, 10.0));
+        let variables = ExpressionVariables::new()
+            .constant("amplitude", 2.0)
+            .track("arm.x", &base);
+
+        let value = |source: &str, time: f64| {
+            Expression::parse(source).and_then(|expression| expression.evaluate(time, &variables))
+        };
+        assert_eq!(value("1 + 2 * 3 - -2^2 / 4", 0.0), Ok(8.0));
+        assert_eq!(value("2^-1 + 1.5e1 + 7 % 4", 0.0), Ok(18.5));
+        assert_eq!(value("sin(t * pi / 2) * amplitude", 1.0), Ok(2.0));
+        assert_eq!(value("arm.x + arm.x(t - 0.5)", 1.0), Ok(15.0));
+        assert_eq!(value("clamp(t, 0, 1) + max(1, 2)", 3.0), Ok(3.0));
+
+        assert_eq!(
+            value("speed * t", 0.0),
+            Err(ExpressionError::UnknownVariable("speed".to_owned()))
+        );
+        assert!(matches!(
+            value("min(1)", 0.0),
+            Err(ExpressionError::Arguments { count: 1, .. })
+        ));
+        assert!(matches!(
+            Expression::parse("(1 + 2"),
+            Err(ExpressionError::Syntax { position: 6, .. })
+        ));
+
+        let expression: Expression = "t * 10".parse().unwrap();
+        let replaced = vec![KeyframeId::new()];
+        let commands = expression
+            .bake_commands(
+                TrackId::new(),
+                replaced,
+                2.0,
+                (TimeTick::new(0.0), TimeTick::new(1.0)),
+                &(),
+            )
+            .unwrap();
+        assert_eq!(commands.len(), 4);
+        assert!(matches!(
+            commands[0],
+            AnimationCommand::RemoveKeyframes { .. }
+        ));
+        assert!(matches!(
+            commands[2],
+            AnimationCommand::InsertKeyframe { value, .. } if value == 5.0
+        ));
+    }
+}
@@ -0,0 +1,110 @@
+//! Extrapolation modes for the regions before the first and after the last
+//! keyframe of a track.
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How a track is evaluated outside its keyframe range.
+///
+/// Pre-extrapolation applies before the first keyframe, post-extrapolation
+/// after the last one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Extrapolation {
+    /// Hold the value of the boundary keyframe.
+    #[default]
+    Constant,
+    /// Continue along the tangent of the boundary keyframe.
+    Linear,
+    /// Repeat the keyframed range.
+    Cycle,
+    /// Repeat the keyframed range, offsetting each repetition by the
+    /// difference between the last and first value.
+    CycleWithOffset,
+    /// Repeat the keyframed range, alternating forward and backward.
+    PingPong,
+}
+
+impl Extrapolation {
+    /// Get the display name for this mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Constant => "Constant",
+            Self::Linear => "Linear",
+            Self::Cycle => "Cycle",
+            Self::CycleWithOffset => "Cycle with Offset",
+            Self::PingPong => "Ping-Pong",
+        }
+    }
+
+    /// Get all modes.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::Constant,
+            Self::Linear,
+            Self::Cycle,
+            Self::CycleWithOffset,
+            Self::PingPong,
+        ]
+    }
+
+    /// Whether this mode repeats the keyframed range.
+    pub fn is_cyclic(&self) -> bool {
+        matches!(self, Self::Cycle | Self::CycleWithOffset | Self::PingPong)
+    }
+
+    /// Map a time offset from the start of the keyframed range into the range.
+    ///
+    /// `offset` is `time - start` and `period` is `end - start`. Returns the
+    /// mapped offset in `[0, period]` and the signed cycle index. Non-cyclic
+    /// modes return the offset unchanged with cycle index `0`.
+    pub fn map_offset(&self, offset: f64, period: f64) -> (f64, i64) {
+        if !self.is_cyclic() || period <= 0.0 {
+            return (offset, 0);
+        }
+
+        let cycle = (offset / period).floor();
+        let local = offset - cycle * period;
+        let cycle = cycle as i64;
+
+        let local = if *self == Self::PingPong && cycle.rem_euclid(2) == 1 {
+            period - local
+        } else {
+            local
+        };
+
+        (local, cycle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_offset_cycle() {
+        let (local, cycle) = Extrapolation::Cycle.map_offset(2.5, 1.0);
+        assert!((local - 0.5).abs() < 1e-10);
+        assert_eq!(cycle, 2);
+
+        let (local, cycle) = Extrapolation::Cycle.map_offset(-0.25, 1.0);
+        assert!((local - 0.75).abs() < 1e-10);
+        assert_eq!(cycle, -1);
+    }
+
+    #[test]
+    fn map_offset_ping_pong() {
+        let (local, cycle) = Extrapolation::PingPong.map_offset(1.25, 1.0);
+        assert!((local - 0.75).abs() < 1e-10);
+        assert_eq!(cycle, 1);
+
+        let (local, _) = Extrapolation::PingPong.map_offset(2.25, 1.0);
+        assert!((local - 0.25).abs() < 1e-10);
+    }
+
+    #[test]
+    fn map_offset_non_cyclic() {
+        assert_eq!(Extrapolation::Linear.map_offset(3.0, 1.0), (3.0, 0));
+        assert_eq!(Extrapolation::Cycle.map_offset(3.0, 0.0), (3.0, 0));
+    }
+}
@@ -0,0 +1,280 @@
+//! Parametric curve generators ("LFOs").
+//!
+//! A [`Generator`] describes a periodic [`Waveform`] by frequency,
+//! amplitude, phase and offset and turns it into an editable `Track<f32>`:
+//!
+//! ```ignore
+//! let wobble = Generator::new(Waveform::Sine).frequency(2.0).amplitude(10.0);
+//! let track = wobble.track(0.0, 4.0);
+//! ```
+//!
+//! Tracks get as few keyframes as reproduce the waveform: corners of
+//! triangles and sawtooths, steps of squares and smooth
+//! [`Auto`](KeyframeType::Auto) keys along sines and noise. The
+//! [`GeneratorDialog`](crate::widgets::GeneratorDialog) edits a generator
+//! with a preview.
+
+use super::keyframe::{Keyframe, KeyframeType};
+use super::modifier::perlin;
+use super::time::TimeTick;
+use super::track::Track;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Offset in cycles of the key just before a sawtooth drops.
+const DROP_WIDTH: f64 = 1e-4;
+
+/// Shape of one cycle of a [`Generator`], in `-1..=1`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Waveform {
+    /// Sine wave starting at zero, rising.
+    #[default]
+    Sine,
+    /// High for the first half of each cycle, low for the second.
+    Square,
+    /// Straight ramps through the peaks of the sine.
+    Triangle,
+    /// Ramp from low to high, then drop.
+    Sawtooth,
+    /// Smooth Perlin noise, zero at every whole cycle.
+    Noise,
+}
+
+impl Waveform {
+    /// All waveforms, in menu order.
+    pub const ALL: [Self; 5] = [
+        Self::Sine,
+        Self::Square,
+        Self::Triangle,
+        Self::Sawtooth,
+        Self::Noise,
+    ];
+
+    /// Name shown to users.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Sine => "Sine",
+            Self::Square => "Square",
+            Self::Triangle => "Triangle",
+            Self::Sawtooth => "Sawtooth",
+            Self::Noise => "Noise",
+        }
+    }
+
+    /// Key spacing in cycles, offset of the first key, and key type.
+    fn keys(self) -> (f64, f64, KeyframeType) {
+        match self {
+            Self::Sine => (0.125, 0.0, KeyframeType::Auto),
+            Self::Square => (0.5, 0.0, KeyframeType::Hold),
+            Self::Triangle => (0.5, 0.25, KeyframeType::Linear),
+            Self::Sawtooth => (1.0, 0.0, KeyframeType::Linear),
+            Self::Noise => (0.25, 0.0, KeyframeType::Auto),
+        }
+    }
+}
+
+/// A periodic source of values.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Generator {
+    /// Shape of each cycle.
+    pub waveform: Waveform,
+    /// Cycles per time unit.
+    pub frequency: f64,
+    /// Largest deviation from `offset`.
+    pub amplitude: f32,
+    /// Shift in cycles, e.g. `0.25` starts a sine at its peak.
+    pub phase: f64,
+    /// Value the waveform oscillates around.
+    pub offset: f32,
+    /// Picks one of many noise patterns for [`Waveform::Noise`].
+    pub seed: u32,
+}
+
+impl Default for Generator {
+    fn default() -> Self {
+        Self::new(Waveform::default())
+    }
+}
+
+impl Generator {
+    /// Create a generator of one cycle per time unit between `-1` and `1`.
+    pub fn new(waveform: Waveform) -> Self {
+        Self {
+            waveform,
+            frequency: 1.0,
+            amplitude: 1.0,
+            phase: 0.0,
+            offset: 0.0,
+            seed: 0,
+        }
+    }
+
+    /// Set the cycles per time unit.
+    pub fn frequency(mut self, frequency: f64) -> Self {
+        self.frequency = frequency;
+        self
+    }
+
+    /// Set the largest deviation from the offset.
+    pub fn amplitude(mut self, amplitude: f32) -> Self {
+        self.amplitude = amplitude;
+        self
+    }
+
+    /// Set the shift in cycles.
+    pub fn phase(mut self, phase: f64) -> Self {
+        self.phase = phase;
+        self
+    }
+
+    /// Set the value the waveform oscillates around.
+    pub fn offset(mut self, offset: f32) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    /// Set the noise pattern.
+    pub fn seed(mut self, seed: u32) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// Value at `time`.
+    pub fn value(&self, time: impl Into<TimeTick>) -> f32 {
+        self.value_at_cycle(self.cycles(time.into()))
+    }
+
+    /// A track reproducing the generator from `start` to `end`, with keys at
+    /// both ends.
+    pub fn track(&self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Track<f32> {
+        let (start, end) = (start.into(), end.into());
+        let (step, shift, key_type) = self.waveform.keys();
+        let mut track = Track::new();
+        let mut add = |time: TimeTick, value: f32, key_type: KeyframeType| {
+            track.add_keyframe(Keyframe::new(time, value).with_type(key_type));
+        };
+
+        add(start, self.value(start), key_type);
+        if end <= start {
+            return track;
+        }
+        if self.frequency > 0.0 && self.frequency.is_finite() {
+            let (from, to) = (self.cycles(start), self.cycles(end));
+            let first = ((from - shift) / step).floor() as i64 + 1;
+            let last = ((to - shift) / step).ceil() as i64 - 1;
+            for index in first..=last {
+                let cycle = shift + index as f64 * step;
+                match self.waveform {
+                    Waveform::Square => {
+                        // Take the value after the step.
+                        add(
+                            self.time_at(cycle),
+                            self.value_at_cycle(cycle + DROP_WIDTH),
+                            key_type,
+                        );
+                    }
+                    Waveform::Sawtooth => {
+                        if cycle - DROP_WIDTH > from {
+                            add(
+                                self.time_at(cycle - DROP_WIDTH),
+                                self.value_at_cycle(cycle - DROP_WIDTH),
+                                KeyframeType::Hold,
+                            );
+                        }
+                        add(
+                            self.time_at(cycle),
+                            self.value_at_cycle(cycle + DROP_WIDTH * 1e-3),
+                            key_type,
+                        );
+                    }
+                    _ => add(self.time_at(cycle), self.value_at_cycle(cycle), key_type),
+                }
+            }
+        }
+        // Hold the value from before a step that falls on the end.
+        let end_value = match self.waveform {
+            Waveform::Square | Waveform::Sawtooth => {
+                self.value_at_cycle(self.cycles(end) - DROP_WIDTH * 1e-3)
+            }
+            _ => self.value(end),
+        };
+        add(end, end_value, key_type);
+        track
+    }
+
+    /// Cycles elapsed at `time`, including the phase.
+    fn cycles(&self, time: TimeTick) -> f64 {
+        time.value() * self.frequency + self.phase
+    }
+
+    /// Time at which `cycles` have elapsed.
+    fn time_at(&self, cycles: f64) -> TimeTick {
+        TimeTick::new((cycles - self.phase) / self.frequency)
+    }
+
+    fn value_at_cycle(&self, cycle: f64) -> f32 {
+        let fraction = cycle - cycle.floor();
+        let wave = match self.waveform {
+            Waveform::Sine => (cycle * std::f64::consts::TAU).sin(),
+            Waveform::Square => {
+                if fraction < 0.5 {
+                    1.0
+                } else {
+                    -1.0
+                }
+            }
+            Waveform::Triangle => {
+                let shifted = cycle - 0.25;
+                4.0 * (shifted - shifted.floor() - 0.5).abs() - 1.0
+            }
+            Waveform::Sawtooth => 2.0 * fraction - 1.0,
+            Waveform::Noise => perlin(cycle, self.seed),
+        };
+        self.offset + self.amplitude * wave as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracks_follow_their_waveform() {
+        let close = |a: f32, b: f32, tolerance: f32| (a - b).abs() <= tolerance;
+
+        let triangle = Generator::new(Waveform::Triangle)
+            .frequency(0.5)
+            .amplitude(2.0)
+            .offset(1.0);
+        let track = triangle.track(0.0, 4.0);
+        // Ends plus the four corners.
+        assert_eq!(track.len(), 6);
+        for i in 0..=40 {
+            let time = i as f64 / 10.0;
+            let value = track.evaluate(time).unwrap();
+            assert!(close(value, triangle.value(time), 1e-4), "{time}");
+        }
+
+        let square = Generator::new(Waveform::Square).frequency(2.0);
+        let track = square.track(0.0, 1.0);
+        assert_eq!(track.evaluate(0.1), Some(1.0));
+        assert_eq!(track.evaluate(0.3), Some(-1.0));
+        assert_eq!(track.evaluate(0.6), Some(1.0));
+
+        let saw = Generator::new(Waveform::Sawtooth);
+        let track = saw.track(0.0, 2.0);
+        assert!(close(track.evaluate(0.5).unwrap(), 0.0, 1e-3));
+        assert!(close(track.evaluate(0.999).unwrap(), 0.998, 1e-3));
+        assert!(close(track.evaluate(1.25).unwrap(), -0.5, 1e-3));
+
+        let sine = Generator::new(Waveform::Sine).phase(0.25);
+        let track = sine.track(0.0, 1.0);
+        for i in 0..=20 {
+            let time = i as f64 / 20.0;
+            assert!(close(track.evaluate(time).unwrap(), sine.value(time), 0.05));
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! Named, colored sets of tracks ("track groups").
+//!
+//! A [`TrackGroup`] bundles the tracks of e.g. one object so they can be
+//! shown or hidden with a single toggle. A track may belong to several
+//! groups; it is hidden as soon as one of them is.
+
+use super::track::TrackId;
+use crate::traits::PropertyRow;
+use egui::Color32;
+use uuid::Uuid;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a track group.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrackGroupId(pub Uuid);
+
+impl TrackGroupId {
+    /// Create a new random track group ID.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for TrackGroupId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A named, colored set of tracks with a shared visibility flag.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrackGroup {
+    /// Unique identifier.
+    pub id: TrackGroupId,
+    /// Display name.
+    pub name: String,
+    /// Color for member tracks that have none of their own.
+    pub color: Option<Color32>,
+    /// Member tracks.
+    pub tracks: Vec<TrackId>,
+    /// Whether the member tracks are shown.
+    pub visible: bool,
+}
+
+impl TrackGroup {
+    /// Create a new, visible group without tracks.
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            id: TrackGroupId::new(),
+            name: name.into(),
+            color: None,
+            tracks: Vec::new(),
+            visible: true,
+        }
+    }
+
+    /// Set the color.
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = Some(color);
+        self
+    }
+
+    /// Set the member tracks.
+    pub fn with_tracks(mut self, tracks: impl IntoIterator<Item = TrackId>) -> Self {
+        self.tracks = tracks.into_iter().collect();
+        self
+    }
+
+    /// Check if `track_id` is a member.
+    pub fn contains(&self, track_id: TrackId) -> bool {
+        self.tracks.contains(&track_id)
+    }
+}
+
+/// Collection of [`TrackGroup`]s in insertion order.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TrackGroups {
+    groups: Vec<TrackGroup>,
+}
+
+impl TrackGroups {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a group.
+    pub fn add(&mut self, group: TrackGroup) -> TrackGroupId {
+        let id = group.id;
+        self.groups.push(group);
+        id
+    }
+
+    /// Remove a group.
+    pub fn remove(&mut self, id: TrackGroupId) -> Option<TrackGroup> {
+        let index = self.groups.iter().position(|group| group.id == id)?;
+        Some(self.groups.remove(index))
+    }
+
+    /// Get a group by ID.
+    pub fn get(&self, id: TrackGroupId) -> Option<&TrackGroup> {
+        self.groups.iter().find(|group| group.id == id)
+    }
+
+    /// Show or hide all tracks of a group. Returns `false` if it does not
+    /// exist.
+    pub fn set_visible(&mut self, id: TrackGroupId, visible: bool) -> bool {
+        self.get_mut(id)
+            .map(|group| group.visible = visible)
+            .is_some()
+    }
+
+    /// Flip the visibility of a group and return the new state, or `None`
+    /// if it does not exist.
+    pub fn toggle_visible(&mut self, id: TrackGroupId) -> Option<bool> {
+        let group = self.get_mut(id)?;
+        group.visible = !group.visible;
+        Some(group.visible)
+    }
+
+    /// Add `track_id` to a group. Returns `false` if it does not exist.
+    pub fn add_track(&mut self, id: TrackGroupId, track_id: TrackId) -> bool {
+        self.get_mut(id)
+            .map(|group| {
+                if !group.contains(track_id) {
+                    group.tracks.push(track_id);
+                }
+            })
+            .is_some()
+    }
+
+    /// Check if a track is shown, i.e. no group containing it is hidden.
+    pub fn is_track_visible(&self, track_id: TrackId) -> bool {
+        self.groups
+            .iter()
+            .all(|group| group.visible || !group.contains(track_id))
+    }
+
+    /// Color of the first group containing `track_id` that has one.
+    pub fn track_color(&self, track_id: TrackId) -> Option<Color32> {
+        self.groups
+            .iter()
+            .filter(|group| group.contains(track_id))
+            .find_map(|group| group.color)
+    }
+
+    /// Remove the rows of hidden tracks and give the remaining track rows
+    /// without a color of their own their group's color.
+    pub fn apply_to_rows(&self, rows: &mut Vec<PropertyRow>) {
+        if self.groups.is_empty() {
+            return;
+        }
+        rows.retain(|row| row.track_id.is_none_or(|id| self.is_track_visible(id)));
+        for row in rows.iter_mut() {
+            if row.color.is_none()
+                && let Some(track_id) = row.track_id
+            {
+                row.color = self.track_color(track_id);
+            }
+        }
+    }
+
+    /// Iterate over groups in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = &TrackGroup> {
+        self.groups.iter()
+    }
+
+    /// Number of groups.
+    pub fn len(&self) -> usize {
+        self.groups.len()
+    }
+
+    /// Check if there are no groups.
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    fn get_mut(&mut self, id: TrackGroupId) -> Option<&mut TrackGroup> {
+        self.groups.iter_mut().find(|group| group.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hidden_group_hides_member_rows() {
+        let (a, b) = (TrackId::new(), TrackId::new());
+        let mut groups = TrackGroups::new();
+        let object = groups.add(
+            TrackGroup::new("Object B")
+                .with_color(Color32::RED)
+                .with_tracks([a]),
+        );
+
+        let row = |track_id| PropertyRow {
+            id: format!("{track_id:?}"),
+            label: String::new(),
+            depth: 1,
+            track_id: Some(track_id),
+            ..Default::default()
+        };
+
+        let mut rows = vec![row(a), row(b)];
+        groups.apply_to_rows(&mut rows);
+        assert_eq!(rows[0].color, Some(Color32::RED));
+        assert_eq!(rows[1].color, None);
+
+        assert_eq!(groups.toggle_visible(object), Some(false));
+        assert!(!groups.is_track_visible(a));
+        assert!(groups.is_track_visible(b));
+        let mut rows = vec![row(a), row(b)];
+        groups.apply_to_rows(&mut rows);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].track_id, Some(b));
+    }
+}
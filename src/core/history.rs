@@ -0,0 +1,1111 @@
+//! Undo/redo history for [`AnimationCommand`]s.
+//!
+//! [`CommandHistory`] executes commands on a model implementing both
+//! [`AnimationDataProvider`] and [`AnimationDataMutator`]. The inverse of each
+//! command is computed from the model state right before it is executed.
+
+use crate::HashSet;
+use crate::core::keyframe::{BezierHandles, KeyframeId, KeyframeType, Spring};
+use crate::core::time::TimeTick;
+use crate::core::track::TrackId;
+use crate::traits::{
+    AnimationCommand, AnimationDataMutator, AnimationDataProvider, PropertyRow, RowTarget,
+};
+use crate::transport::subtree_track_ids;
+
+/// Default maximum number of undo steps kept by [`CommandHistory`].
+pub const DEFAULT_HISTORY_LIMIT: usize = 100;
+
+/// Full state of a single keyframe, sufficient to restore it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyframeSnapshot {
+    /// Track the keyframe belongs to.
+    pub track_id: TrackId,
+    /// Keyframe ID.
+    pub keyframe_id: KeyframeId,
+    /// Time position.
+    pub position: TimeTick,
+    /// Value.
+    pub value: f64,
+    /// Bezier handles.
+    pub handles: BezierHandles,
+    /// Interpolation type.
+    pub keyframe_type: KeyframeType,
+    /// Spring parameters, used by [`KeyframeType::Spring`].
+    pub spring: Spring,
+}
+
+impl KeyframeSnapshot {
+    /// Capture the state of a keyframe from a provider.
+    ///
+    /// Returns `None` if the keyframe does not exist.
+    pub fn capture<P: AnimationDataProvider + ?Sized>(
+        provider: &P,
+        keyframe_id: KeyframeId,
+    ) -> Option<Self> {
+        let (track_id, position) = provider.find_keyframe(keyframe_id)?;
+        Some(Self {
+            track_id,
+            keyframe_id,
+            position,
+            value: provider.keyframe_value(track_id, keyframe_id)?,
+            handles: provider
+                .keyframe_handles(track_id, keyframe_id)
+                .unwrap_or_default(),
+            keyframe_type: provider
+                .keyframe_type(track_id, keyframe_id)
+                .unwrap_or_default(),
+            spring: provider
+                .keyframe_spring(track_id, keyframe_id)
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Command that re-creates this keyframe.
+    pub fn insert_command(&self) -> AnimationCommand {
+        AnimationCommand::InsertKeyframe {
+            track_id: self.track_id,
+            keyframe_id: self.keyframe_id,
+            position: self.position,
+            value: self.value,
+            handles: self.handles,
+            keyframe_type: self.keyframe_type,
+        }
+    }
+
+    /// Commands that restore the position and value of this keyframe.
+    pub fn restore_commands(&self) -> [AnimationCommand; 2] {
+        [
+            AnimationCommand::MoveKeyframe {
+                keyframe_id: self.keyframe_id,
+                new_position: self.position,
+            },
+            AnimationCommand::SetKeyframeValue {
+                keyframe_id: self.keyframe_id,
+                value: self.value,
+            },
+        ]
+    }
+}
+
+/// Commands restoring the handles and types of the keyframes of `track_id`
+/// right before and after `position`.
+fn neighbor_handle_commands<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    track_id: TrackId,
+    position: TimeTick,
+) -> Option<Vec<AnimationCommand>> {
+    let positions = provider.keyframe_positions(track_id)?;
+    let by_position = |a: &&(KeyframeId, TimeTick), b: &&(KeyframeId, TimeTick)| {
+        a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal)
+    };
+    let prev = positions
+        .iter()
+        .filter(|(_, p)| *p < position)
+        .max_by(by_position);
+    let next = positions
+        .iter()
+        .filter(|(_, p)| *p > position)
+        .min_by(by_position);
+
+    let mut commands = Vec::new();
+    for &(id, _) in prev.into_iter().chain(next) {
+        commands.extend([
+            AnimationCommand::SetKeyframeHandles {
+                keyframe_id: id,
+                handles: provider.keyframe_handles(track_id, id)?,
+            },
+            AnimationCommand::SetKeyframeWeightedHandles {
+                keyframe_id: id,
+                handles: provider.keyframe_weighted_handles(track_id, id),
+            },
+            AnimationCommand::SetKeyframeType {
+                keyframe_id: id,
+                keyframe_type: provider.keyframe_type(track_id, id)?,
+            },
+        ]);
+    }
+    Some(commands)
+}
+
+/// Command restoring the spring parameters of the removed keyframe
+/// `snapshot`, if it is a [`KeyframeType::Spring`] keyframe.
+fn spring_command<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    snapshot: &KeyframeSnapshot,
+) -> Option<AnimationCommand> {
+    if snapshot.keyframe_type != KeyframeType::Spring {
+        return None;
+    }
+    Some(AnimationCommand::SetKeyframeSpring {
+        keyframe_id: snapshot.keyframe_id,
+        spring: provider.keyframe_spring(snapshot.track_id, snapshot.keyframe_id)?,
+    })
+}
+
+/// Command restoring the weighted handles of the removed keyframe
+/// `snapshot`, if it has any.
+fn weighted_command<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    snapshot: &KeyframeSnapshot,
+) -> Option<AnimationCommand> {
+    Some(AnimationCommand::SetKeyframeWeightedHandles {
+        keyframe_id: snapshot.keyframe_id,
+        handles: Some(provider.keyframe_weighted_handles(snapshot.track_id, snapshot.keyframe_id)?),
+    })
+}
+
+/// Commands restoring the pin, lock, breakdown mark, label and tag of the
+/// removed keyframe `snapshot`, where set.
+fn marking_commands<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    snapshot: &KeyframeSnapshot,
+) -> Vec<AnimationCommand> {
+    let (track_id, keyframe_id) = (snapshot.track_id, snapshot.keyframe_id);
+    let pinned = provider.keyframe_pinned(track_id, keyframe_id);
+    let locked = provider.keyframe_locked(track_id, keyframe_id);
+    let breakdown = provider.keyframe_breakdown(track_id, keyframe_id);
+    let label = provider.keyframe_label(track_id, keyframe_id);
+    let tag = provider.keyframe_tag(track_id, keyframe_id);
+    [
+        pinned.then_some(AnimationCommand::SetKeyframePinned {
+            keyframe_id,
+            pinned,
+        }),
+        locked.then(|| AnimationCommand::SetKeyframesLocked {
+            keyframe_ids: vec![keyframe_id],
+            locked,
+        }),
+        breakdown.then_some(AnimationCommand::SetKeyframeBreakdown {
+            keyframe_id,
+            breakdown,
+        }),
+        label
+            .is_some()
+            .then_some(AnimationCommand::SetKeyframeLabel { keyframe_id, label }),
+        tag.is_some()
+            .then_some(AnimationCommand::SetKeyframeTag { keyframe_id, tag }),
+    ]
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Compute the commands that revert `command`.
+///
+/// `provider` must reflect the state *before* `command` is executed.
+/// Returns `None` for commands that are not undoable (view state such as
+/// [`AnimationCommand::SetCurrentTime`]) or when required data is missing.
+///
+/// [`AnimationCommand::AddKeyframe`] and [`AnimationCommand::BakeTrack`] also
+/// return `None`: the IDs of the new keyframes are only known after
+/// execution. [`CommandHistory`] handles them.
+/// [`AnimationCommand::AddTrack`] and [`AnimationCommand::AddGroup`] are not
+/// undoable for the same reason. [`AnimationCommand::RemoveTrack`] is not
+/// undoable because the removed rows can only be restored by the host.
+/// Undoing [`AnimationCommand::ResetRow`] re-inserts the removed keyframes
+/// with their weighted handles, spring parameters and markings.
+pub fn inverse_command<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    command: &AnimationCommand,
+) -> Option<Vec<AnimationCommand>> {
+    match command {
+        AnimationCommand::AddKeyframe { .. }
+        | AnimationCommand::AddTrack { .. }
+        | AnimationCommand::AddGroup { .. }
+        | AnimationCommand::RemoveTrack { .. }
+        | AnimationCommand::BakeTrack { .. }
+        | AnimationCommand::SetCurrentTime(_)
+        | AnimationCommand::SetWorkRange { .. }
+        | AnimationCommand::ToggleRowCollapse(_)
+        | AnimationCommand::IsolateRow(_)
+        | AnimationCommand::SetTrackGroupVisible { .. }
+        | AnimationCommand::SetTrackMuted { .. }
+        | AnimationCommand::SetTrackSolo { .. } => None,
+        AnimationCommand::AddMarker(marker) => Some(vec![AnimationCommand::RemoveMarker {
+            marker_id: marker.id,
+        }]),
+        AnimationCommand::MoveMarker { marker_id, .. } => {
+            Some(vec![AnimationCommand::MoveMarker {
+                marker_id: *marker_id,
+                time: provider.marker(*marker_id)?.time,
+            }])
+        }
+        AnimationCommand::RemoveMarker { marker_id } => Some(vec![AnimationCommand::AddMarker(
+            provider.marker(*marker_id)?,
+        )]),
+        AnimationCommand::InsertKeyframe { keyframe_id, .. } => {
+            Some(vec![AnimationCommand::RemoveKeyframes {
+                keyframe_ids: vec![*keyframe_id],
+            }])
+        }
+        AnimationCommand::InsertKeyframePreserving {
+            track_id,
+            keyframe_id,
+            position,
+        } => {
+            // Restore the handles of the neighbors the split adjusts.
+            let mut commands = vec![AnimationCommand::RemoveKeyframes {
+                keyframe_ids: vec![*keyframe_id],
+            }];
+            commands.extend(neighbor_handle_commands(provider, *track_id, *position)?);
+            Some(commands)
+        }
+        AnimationCommand::InsertBreakdown {
+            from,
+            to,
+            keyframe_id,
+            fraction,
+        } => {
+            let (track_id, from) = provider.find_keyframe(*from)?;
+            let (_, to) = provider.find_keyframe(*to)?;
+            let mut commands = vec![AnimationCommand::RemoveKeyframes {
+                keyframe_ids: vec![*keyframe_id],
+            }];
+            commands.extend(neighbor_handle_commands(
+                provider,
+                track_id,
+                from.lerp(to, *fraction),
+            )?);
+            Some(commands)
+        }
+        AnimationCommand::RemoveKeyframePreserving { keyframe_id } => {
+            // Re-insert the keyframe, then restore its neighbors' handles.
+            let snapshot = KeyframeSnapshot::capture(provider, *keyframe_id)?;
+            let mut commands = vec![
+                snapshot.insert_command(),
+                AnimationCommand::SetKeyframeWeightedHandles {
+                    keyframe_id: *keyframe_id,
+                    handles: provider.keyframe_weighted_handles(snapshot.track_id, *keyframe_id),
+                },
+            ];
+            commands.extend(marking_commands(provider, &snapshot));
+            commands.extend(spring_command(provider, &snapshot));
+            commands.extend(neighbor_handle_commands(
+                provider,
+                snapshot.track_id,
+                snapshot.position,
+            )?);
+            Some(commands)
+        }
+        AnimationCommand::PasteKeyframes { keyframes } => {
+            Some(vec![AnimationCommand::RemoveKeyframes {
+                keyframe_ids: keyframes.iter().map(|kf| kf.keyframe_id).collect(),
+            }])
+        }
+        AnimationCommand::RemoveKeyframes { keyframe_ids } => Some(
+            keyframe_ids
+                .iter()
+                .filter_map(|id| KeyframeSnapshot::capture(provider, *id))
+                .flat_map(|snapshot| {
+                    std::iter::once(snapshot.insert_command())
+                        .chain(marking_commands(provider, &snapshot))
+                        .chain(spring_command(provider, &snapshot))
+                })
+                .collect(),
+        ),
+        AnimationCommand::MoveKeyframe { keyframe_id, .. } => {
+            let (_, position) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::MoveKeyframe {
+                keyframe_id: *keyframe_id,
+                new_position: position,
+            }])
+        }
+        AnimationCommand::SetKeyframeValue { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeValue {
+                keyframe_id: *keyframe_id,
+                value: provider.keyframe_value(track_id, *keyframe_id)?,
+            }])
+        }
+        AnimationCommand::SetKeyframeHandles { keyframe_id, .. } => {
+            // Hosts may drop weighted handles when the normalized ones change.
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![
+                AnimationCommand::SetKeyframeHandles {
+                    keyframe_id: *keyframe_id,
+                    handles: provider.keyframe_handles(track_id, *keyframe_id)?,
+                },
+                AnimationCommand::SetKeyframeWeightedHandles {
+                    keyframe_id: *keyframe_id,
+                    handles: provider.keyframe_weighted_handles(track_id, *keyframe_id),
+                },
+            ])
+        }
+        AnimationCommand::SetKeyframeWeightedHandles { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeWeightedHandles {
+                keyframe_id: *keyframe_id,
+                handles: provider.keyframe_weighted_handles(track_id, *keyframe_id),
+            }])
+        }
+        AnimationCommand::SetKeyframeSpring { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeSpring {
+                keyframe_id: *keyframe_id,
+                spring: provider.keyframe_spring(track_id, *keyframe_id)?,
+            }])
+        }
+        AnimationCommand::SetKeyframePinned { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframePinned {
+                keyframe_id: *keyframe_id,
+                pinned: provider.keyframe_pinned(track_id, *keyframe_id),
+            }])
+        }
+        AnimationCommand::SetKeyframesLocked { keyframe_ids, .. } => {
+            // Restore each keyframe's own state, grouped by it.
+            let (locked, unlocked): (Vec<KeyframeId>, Vec<KeyframeId>) =
+                keyframe_ids.iter().partition(|id| {
+                    provider
+                        .find_keyframe(**id)
+                        .is_some_and(|(track_id, _)| provider.keyframe_locked(track_id, **id))
+                });
+            Some(
+                [(locked, true), (unlocked, false)]
+                    .into_iter()
+                    .filter(|(keyframe_ids, _)| !keyframe_ids.is_empty())
+                    .map(
+                        |(keyframe_ids, locked)| AnimationCommand::SetKeyframesLocked {
+                            keyframe_ids,
+                            locked,
+                        },
+                    )
+                    .collect(),
+            )
+        }
+        AnimationCommand::SetKeyframeBreakdown { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeBreakdown {
+                keyframe_id: *keyframe_id,
+                breakdown: provider.keyframe_breakdown(track_id, *keyframe_id),
+            }])
+        }
+        AnimationCommand::SetKeyframeLabel { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeLabel {
+                keyframe_id: *keyframe_id,
+                label: provider.keyframe_label(track_id, *keyframe_id),
+            }])
+        }
+        AnimationCommand::SetKeyframeTag { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeTag {
+                keyframe_id: *keyframe_id,
+                tag: provider.keyframe_tag(track_id, *keyframe_id),
+            }])
+        }
+        AnimationCommand::SetKeyframeType { keyframe_id, .. } => {
+            let (track_id, _) = provider.find_keyframe(*keyframe_id)?;
+            Some(vec![AnimationCommand::SetKeyframeType {
+                keyframe_id: *keyframe_id,
+                keyframe_type: provider.keyframe_type(track_id, *keyframe_id)?,
+            }])
+        }
+        AnimationCommand::OffsetKeyframes { keyframe_ids, .. }
+        | AnimationCommand::ScaleKeyframes { keyframe_ids, .. } => Some(
+            keyframe_ids
+                .iter()
+                .filter_map(|id| KeyframeSnapshot::capture(provider, *id))
+                .flat_map(|snapshot| snapshot.restore_commands())
+                .collect(),
+        ),
+        // Flipping twice around the same anchor restores the keyframes, and
+        // reversing keeps the span.
+        AnimationCommand::FlipKeyframesTime { .. }
+        | AnimationCommand::FlipKeyframesValue { .. }
+        | AnimationCommand::ReverseKeyframes { .. } => Some(vec![command.clone()]),
+        AnimationCommand::RenameRow { row_id, .. } => {
+            let rows = provider.property_rows();
+            let row = rows.iter().find(|row| row.id == *row_id)?;
+            Some(vec![AnimationCommand::RenameRow {
+                row_id: row_id.clone(),
+                label: row.label.clone(),
+            }])
+        }
+        AnimationCommand::SetRowColor { row_id, .. } => {
+            let rows = provider.property_rows();
+            let row = rows.iter().find(|row| row.id == *row_id)?;
+            Some(vec![AnimationCommand::SetRowColor {
+                row_id: row_id.clone(),
+                color: row.color,
+            }])
+        }
+        AnimationCommand::SetRowLocked { row_id, .. } => {
+            let rows = provider.property_rows();
+            let row = rows.iter().find(|row| row.id == *row_id)?;
+            Some(vec![AnimationCommand::SetRowLocked {
+                row_id: row_id.clone(),
+                locked: row.locked,
+            }])
+        }
+        AnimationCommand::ResetRow { row_id } => {
+            let rows = provider.property_rows();
+            let row_ids = HashSet::from_iter([row_id.clone()]);
+            Some(
+                subtree_track_ids(&rows, &row_ids)
+                    .into_iter()
+                    .flat_map(|track_id| provider.keyframe_positions(track_id).unwrap_or_default())
+                    .filter_map(|(id, _)| KeyframeSnapshot::capture(provider, id))
+                    .flat_map(|snapshot| {
+                        std::iter::once(snapshot.insert_command())
+                            .chain(weighted_command(provider, &snapshot))
+                            .chain(spring_command(provider, &snapshot))
+                            .chain(marking_commands(provider, &snapshot))
+                    })
+                    .collect(),
+            )
+        }
+        AnimationCommand::MoveRow { row_id, .. } => {
+            let (parent, index) = row_location(&provider.property_rows(), row_id)?;
+            Some(vec![AnimationCommand::MoveRow {
+                row_id: row_id.clone(),
+                new_parent: parent,
+                index,
+            }])
+        }
+        AnimationCommand::ReorderRow { row, .. } => {
+            let (parent, index) = row_location(&provider.property_rows(), row)?;
+            Some(vec![AnimationCommand::ReorderRow {
+                row: row.clone(),
+                target: RowTarget { parent, index },
+            }])
+        }
+        AnimationCommand::SetTrackRetime { track_id, .. } => {
+            Some(vec![AnimationCommand::SetTrackRetime {
+                track_id: *track_id,
+                time_offset: provider.track_time_offset(*track_id),
+                time_scale: provider.track_time_scale(*track_id),
+            }])
+        }
+    }
+}
+
+/// Parent row ID and index among the parent's children of a row.
+///
+/// `rows` must be in tree order (parents directly followed by children).
+/// Returns `None` if the row does not exist.
+pub fn row_location(rows: &[PropertyRow], row_id: &str) -> Option<(Option<String>, usize)> {
+    let position = rows.iter().position(|row| row.id == row_id)?;
+    let depth = rows[position].depth;
+
+    let parent = rows[..position]
+        .iter()
+        .rposition(|row| row.depth < depth)
+        .filter(|_| depth > 0);
+    let siblings_start = parent.map_or(0, |parent| parent + 1);
+    let index = rows[siblings_start..position]
+        .iter()
+        .filter(|row| row.depth == depth)
+        .count();
+
+    Some((parent.map(|parent| rows[parent].id.clone()), index))
+}
+
+/// A recorded undo step.
+#[derive(Debug, Clone, Default)]
+struct HistoryEntry {
+    /// Commands re-applied on redo, in execution order.
+    redo: Vec<AnimationCommand>,
+    /// Commands applied on undo, in application order.
+    undo: Vec<AnimationCommand>,
+}
+
+impl HistoryEntry {
+    fn push(&mut self, forward: Vec<AnimationCommand>, inverse: Vec<AnimationCommand>) {
+        self.redo.extend(forward);
+        // Later commands must be reverted first.
+        self.undo.splice(0..0, inverse);
+    }
+}
+
+/// Undo/redo stack for [`AnimationCommand`]s.
+///
+/// Route all edits through [`execute`](Self::execute) instead of calling
+/// [`AnimationDataMutator::execute`] directly. Commands executed between
+/// [`begin_group`](Self::begin_group) and [`end_group`](Self::end_group) form
+/// a single undo step, mirroring [`AnimationDataMutator::begin_scrub`] and
+/// [`AnimationDataMutator::end_scrub`].
+#[derive(Debug, Clone)]
+pub struct CommandHistory {
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    group: Option<HistoryEntry>,
+    /// Lengths of the open group's redo and undo commands when each nested
+    /// group began, innermost last.
+    group_starts: Vec<(usize, usize)>,
+    limit: usize,
+}
+
+impl Default for CommandHistory {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CommandHistory {
+    /// Create an empty history with [`DEFAULT_HISTORY_LIMIT`] undo steps.
+    pub fn new() -> Self {
+        Self::with_limit(DEFAULT_HISTORY_LIMIT)
+    }
+
+    /// Create an empty history keeping at most `limit` undo steps.
+    pub fn with_limit(limit: usize) -> Self {
+        Self {
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            group: None,
+            group_starts: Vec::new(),
+            limit,
+        }
+    }
+
+    /// Execute a command on the model and record it for undo.
+    ///
+    /// Non-undoable commands are executed without being recorded.
+    pub fn execute<M>(&mut self, model: &mut M, command: AnimationCommand)
+    where
+        M: AnimationDataProvider + AnimationDataMutator,
+    {
+        if let Some((forward, inverse)) = Self::execute_recorded(model, command) {
+            self.redo_stack.clear();
+            match &mut self.group {
+                Some(group) => group.push(forward, inverse),
+                None => {
+                    let mut entry = HistoryEntry::default();
+                    entry.push(forward, inverse);
+                    self.push_undo(entry);
+                }
+            }
+        }
+    }
+
+    /// Execute all commands in order as a single undo step.
+    pub fn execute_all<M>(
+        &mut self,
+        model: &mut M,
+        commands: impl IntoIterator<Item = AnimationCommand>,
+    ) where
+        M: AnimationDataProvider + AnimationDataMutator,
+    {
+        self.begin_group();
+        commands
+            .into_iter()
+            .for_each(|command| self.execute(model, command));
+        self.end_group(model, true);
+    }
+
+    /// Begin grouping commands into a single undo step.
+    ///
+    /// Groups nest; only the outermost [`end_group`](Self::end_group) closes
+    /// the step.
+    pub fn begin_group(&mut self) {
+        let group = self.group.get_or_insert_with(HistoryEntry::default);
+        self.group_starts.push((group.redo.len(), group.undo.len()));
+    }
+
+    /// End a group started with [`begin_group`](Self::begin_group).
+    ///
+    /// If `commit` is false, the commands of the group are reverted on the
+    /// model and discarded; for a nested group only those executed since its
+    /// `begin_group`.
+    pub fn end_group<M: AnimationDataMutator>(&mut self, model: &mut M, commit: bool) {
+        let Some((redo_len, undo_len)) = self.group_starts.pop() else {
+            return;
+        };
+
+        if !commit && let Some(group) = &mut self.group {
+            // Inverses of later commands come first.
+            let added = group.undo.len() - undo_len;
+            group
+                .undo
+                .drain(..added)
+                .for_each(|command| model.execute(command));
+            group.redo.truncate(redo_len);
+        }
+        if self.group_starts.is_empty()
+            && let Some(group) = self.group.take()
+            && !group.redo.is_empty()
+        {
+            self.push_undo(group);
+        }
+    }
+
+    /// Whether a group is currently open.
+    pub fn is_grouping(&self) -> bool {
+        self.group.is_some()
+    }
+
+    /// Undo the last step. Returns `false` if there was nothing to undo.
+    ///
+    /// An open group is committed first.
+    pub fn undo<M: AnimationDataMutator>(&mut self, model: &mut M) -> bool {
+        self.commit_open_group();
+        match self.undo_stack.pop() {
+            Some(entry) => {
+                entry
+                    .undo
+                    .iter()
+                    .cloned()
+                    .for_each(|command| model.execute(command));
+                self.redo_stack.push(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Redo the last undone step. Returns `false` if there was nothing to redo.
+    pub fn redo<M: AnimationDataMutator>(&mut self, model: &mut M) -> bool {
+        self.commit_open_group();
+        match self.redo_stack.pop() {
+            Some(entry) => {
+                entry
+                    .redo
+                    .iter()
+                    .cloned()
+                    .for_each(|command| model.execute(command));
+                self.undo_stack.push(entry);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Whether there is a step to undo.
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty() || self.group.as_ref().is_some_and(|g| !g.redo.is_empty())
+    }
+
+    /// Whether there is a step to redo.
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Number of undo steps.
+    pub fn undo_len(&self) -> usize {
+        self.undo_stack.len()
+    }
+
+    /// Number of redo steps.
+    pub fn redo_len(&self) -> usize {
+        self.redo_stack.len()
+    }
+
+    /// Drop all recorded steps.
+    pub fn clear(&mut self) {
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.group = None;
+        self.group_starts.clear();
+    }
+
+    /// Execute a command, returning the forward commands to replay on redo
+    /// and their inverse.
+    fn execute_recorded<M>(
+        model: &mut M,
+        command: AnimationCommand,
+    ) -> Option<(Vec<AnimationCommand>, Vec<AnimationCommand>)>
+    where
+        M: AnimationDataProvider + AnimationDataMutator,
+    {
+        if let AnimationCommand::AddKeyframe { track_id, .. } = command {
+            // The new ID is only known after execution. Replay as an insert
+            // so redo re-creates the keyframe with the same ID.
+            let before = Self::keyframe_ids(model, track_id);
+            model.execute(command);
+            let keyframe_id = Self::keyframe_ids(model, track_id)
+                .into_iter()
+                .find(|id| !before.contains(id))?;
+            let snapshot = KeyframeSnapshot::capture(model, keyframe_id)?;
+            return Some((
+                vec![snapshot.insert_command()],
+                vec![AnimationCommand::RemoveKeyframes {
+                    keyframe_ids: vec![keyframe_id],
+                }],
+            ));
+        }
+
+        if let AnimationCommand::BakeTrack { track_id, .. } = command {
+            // Replay as a swap of snapshots so redo re-creates the baked
+            // keyframes with the same IDs.
+            let capture = |model: &M| {
+                let ids: Vec<KeyframeId> = model
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .collect();
+                let snapshots: Vec<AnimationCommand> = ids
+                    .iter()
+                    .filter_map(|id| KeyframeSnapshot::capture(model, *id))
+                    .map(|snapshot| snapshot.insert_command())
+                    .collect();
+                (ids, snapshots)
+            };
+            let (before, restore) = capture(model);
+            model.execute(command);
+            let (after, baked) = capture(model);
+            let swap = |remove: Vec<KeyframeId>, insert: Vec<AnimationCommand>| {
+                let mut commands = vec![AnimationCommand::RemoveKeyframes {
+                    keyframe_ids: remove,
+                }];
+                commands.extend(insert);
+                commands
+            };
+            return Some((swap(before, baked), swap(after, restore)));
+        }
+
+        let inverse = inverse_command(model, &command);
+        model.execute(command.clone());
+        inverse.map(|inverse| (vec![command], inverse))
+    }
+
+    fn keyframe_ids<P: AnimationDataProvider>(
+        provider: &P,
+        track_id: TrackId,
+    ) -> HashSet<KeyframeId> {
+        provider
+            .keyframe_positions(track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    fn push_undo(&mut self, entry: HistoryEntry) {
+        self.undo_stack.push(entry);
+        if self.undo_stack.len() > self.limit {
+            self.undo_stack.remove(0);
+        }
+    }
+
+    fn commit_open_group(&mut self) {
+        if let Some(group) = self.group.take() {
+            self.group_starts.clear();
+            if !group.redo.is_empty() {
+                self.push_undo(group);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::Keyframe;
+    use crate::core::track::Track;
+    use crate::traits::PropertyRow;
+
+    /// Minimal model with one `f32` track.
+    struct Model {
+        track: Track<f32>,
+    }
+
+    impl AnimationDataProvider for Model {
+        fn property_rows(&self) -> Vec<PropertyRow> {
+            vec![PropertyRow {
+                id: "value".into(),
+                label: "Value".into(),
+                depth: 0,
+                track_id: Some(self.track.id),
+                ..Default::default()
+            }]
+        }
+
+        fn keyframe_positions(&self, track_id: TrackId) -> Option<Vec<(KeyframeId, TimeTick)>> {
+            (track_id == self.track.id).then(|| self.track.positions())
+        }
+
+        fn keyframe_value(&self, _: TrackId, keyframe_id: KeyframeId) -> Option<f64> {
+            self.track
+                .get_keyframe(keyframe_id)
+                .map(|kf| kf.value as f64)
+        }
+
+        fn keyframe_handles(&self, _: TrackId, keyframe_id: KeyframeId) -> Option<BezierHandles> {
+            self.track.get_keyframe(keyframe_id).map(|kf| kf.handles)
+        }
+
+        fn keyframe_type(&self, _: TrackId, keyframe_id: KeyframeId) -> Option<KeyframeType> {
+            self.track
+                .get_keyframe(keyframe_id)
+                .map(|kf| kf.keyframe_type)
+        }
+
+        fn current_time(&self) -> TimeTick {
+            TimeTick::default()
+        }
+
+        fn time_range(&self) -> (TimeTick, TimeTick) {
+            (TimeTick::new(0.0), TimeTick::new(10.0))
+        }
+
+        fn value_range(&self, _: TrackId) -> Option<(f32, f32)> {
+            self.track.value_range()
+        }
+    }
+
+    impl AnimationDataMutator for Model {
+        fn execute(&mut self, command: AnimationCommand) {
+            match command {
+                AnimationCommand::AddKeyframe {
+                    position, value, ..
+                } => {
+                    self.track
+                        .add_keyframe(Keyframe::new(position, value as f32));
+                }
+                AnimationCommand::InsertKeyframe {
+                    keyframe_id,
+                    position,
+                    value,
+                    handles,
+                    keyframe_type,
+                    ..
+                } => {
+                    self.track.add_keyframe(
+                        Keyframe::with_id(keyframe_id, position, value as f32)
+                            .with_handles(handles)
+                            .with_type(keyframe_type),
+                    );
+                }
+                AnimationCommand::RemoveKeyframes { keyframe_ids } => {
+                    keyframe_ids.into_iter().for_each(|id| {
+                        self.track.remove_keyframe(id);
+                    });
+                }
+                AnimationCommand::MoveKeyframe {
+                    keyframe_id,
+                    new_position,
+                } => {
+                    if let Some(kf) = self.track.get_keyframe_mut(keyframe_id) {
+                        kf.position = new_position;
+                    }
+                }
+                AnimationCommand::SetKeyframeValue { keyframe_id, value } => {
+                    if let Some(kf) = self.track.get_keyframe_mut(keyframe_id) {
+                        kf.value = value as f32;
+                    }
+                }
+                AnimationCommand::OffsetKeyframes {
+                    keyframe_ids,
+                    delta_time,
+                    delta_value,
+                } => {
+                    for id in keyframe_ids {
+                        if let Some(kf) = self.track.get_keyframe_mut(id) {
+                            kf.position += delta_time;
+                            kf.value += delta_value as f32;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fn begin_scrub(&mut self) {}
+
+        fn end_scrub(&mut self, _commit: bool) {}
+    }
+
+    fn model() -> (Model, KeyframeId) {
+        let mut track = Track::new();
+        let id = track.add_keyframe(Keyframe::new(1.0, 10.0));
+        (Model { track }, id)
+    }
+
+    #[test]
+    fn undo_redo_move() {
+        let (mut model, id) = model();
+        let mut history = CommandHistory::new();
+
+        history.execute(
+            &mut model,
+            AnimationCommand::MoveKeyframe {
+                keyframe_id: id,
+                new_position: TimeTick::new(3.0),
+            },
+        );
+        assert_eq!(
+            model.track.get_keyframe(id).unwrap().position,
+            TimeTick::new(3.0)
+        );
+
+        assert!(history.undo(&mut model));
+        assert_eq!(
+            model.track.get_keyframe(id).unwrap().position,
+            TimeTick::new(1.0)
+        );
+        assert!(!history.undo(&mut model));
+
+        assert!(history.redo(&mut model));
+        assert_eq!(
+            model.track.get_keyframe(id).unwrap().position,
+            TimeTick::new(3.0)
+        );
+    }
+
+    #[test]
+    fn undo_remove_keeps_id() {
+        let (mut model, id) = model();
+        let mut history = CommandHistory::new();
+
+        history.execute(
+            &mut model,
+            AnimationCommand::RemoveKeyframes {
+                keyframe_ids: vec![id],
+            },
+        );
+        assert!(model.track.is_empty());
+
+        history.undo(&mut model);
+        assert_eq!(model.track.get_keyframe(id).unwrap().value, 10.0);
+    }
+
+    #[test]
+    fn add_keyframe_redo_keeps_id() {
+        let (mut model, _) = model();
+        let track_id = model.track.id;
+        let mut history = CommandHistory::new();
+
+        history.execute(
+            &mut model,
+            AnimationCommand::AddKeyframe {
+                track_id,
+                position: TimeTick::new(2.0),
+                value: 20.0,
+            },
+        );
+        let added = model
+            .track
+            .keyframe_at_position(2.0, 1e-6)
+            .map(|kf| kf.id)
+            .unwrap();
+
+        history.undo(&mut model);
+        assert_eq!(model.track.len(), 1);
+
+        history.redo(&mut model);
+        assert!(model.track.get_keyframe(added).is_some());
+    }
+
+    #[test]
+    fn group_is_single_step() {
+        let (mut model, id) = model();
+        let mut history = CommandHistory::new();
+
+        history.begin_group();
+        for _ in 0..3 {
+            history.execute(
+                &mut model,
+                AnimationCommand::OffsetKeyframes {
+                    keyframe_ids: vec![id],
+                    delta_time: TimeTick::new(1.0),
+                    delta_value: 1.0,
+                },
+            );
+        }
+        history.end_group(&mut model, true);
+
+        assert_eq!(history.undo_len(), 1);
+        history.undo(&mut model);
+        let kf = model.track.get_keyframe(id).unwrap();
+        assert_eq!(kf.position, TimeTick::new(1.0));
+        assert_eq!(kf.value, 10.0);
+    }
+
+    #[test]
+    fn cancelled_group_reverts() {
+        let (mut model, id) = model();
+        let mut history = CommandHistory::new();
+
+        history.begin_group();
+        history.execute(
+            &mut model,
+            AnimationCommand::SetKeyframeValue {
+                keyframe_id: id,
+                value: 99.0,
+            },
+        );
+        history.end_group(&mut model, false);
+
+        assert_eq!(model.track.get_keyframe(id).unwrap().value, 10.0);
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn cancelled_nested_group_reverts_only_its_commands() {
+        let (mut model, id) = model();
+        let mut history = CommandHistory::new();
+        let set = |value| AnimationCommand::SetKeyframeValue {
+            keyframe_id: id,
+            value,
+        };
+
+        history.begin_group();
+        history.execute(&mut model, set(20.0));
+        history.begin_group();
+        history.execute(&mut model, set(30.0));
+        history.execute(&mut model, set(40.0));
+        history.end_group(&mut model, false);
+        assert_eq!(model.track.get_keyframe(id).unwrap().value, 20.0);
+        history.end_group(&mut model, true);
+
+        assert_eq!(history.undo_len(), 1);
+        history.undo(&mut model);
+        assert_eq!(model.track.get_keyframe(id).unwrap().value, 10.0);
+        history.redo(&mut model);
+        assert_eq!(model.track.get_keyframe(id).unwrap().value, 20.0);
+    }
+
+    #[test]
+    fn row_location_and_rename_inverse() {
+        let row = |id: &str, depth: usize| PropertyRow {
+            id: id.into(),
+            label: id.into(),
+            depth,
+            ..Default::default()
+        };
+        let rows = [
+            row("a", 0),
+            row("a.x", 1),
+            row("a.y", 1),
+            row("a.y.z", 2),
+            row("a.w", 1),
+            row("b", 0),
+        ];
+        assert_eq!(row_location(&rows, "a"), Some((None, 0)));
+        assert_eq!(row_location(&rows, "a.w"), Some((Some("a".into()), 2)));
+        assert_eq!(row_location(&rows, "a.y.z"), Some((Some("a.y".into()), 0)));
+        assert_eq!(row_location(&rows, "b"), Some((None, 1)));
+        assert_eq!(row_location(&rows, "c"), None);
+
+        let (model, _) = model();
+        let inverse = inverse_command(
+            &model,
+            &AnimationCommand::RenameRow {
+                row_id: "value".into(),
+                label: "Opacity".into(),
+            },
+        );
+        assert!(matches!(
+            inverse.as_deref(),
+            Some([AnimationCommand::RenameRow { label, .. }]) if label == "Value"
+        ));
+    }
+
+    #[test]
+    fn reset_row_inverse_restores_keyframes() {
+        let (model, id) = model();
+        let inverse = inverse_command(
+            &model,
+            &AnimationCommand::ResetRow {
+                row_id: "value".into(),
+            },
+        );
+        assert!(matches!(
+            inverse.as_deref(),
+            Some([AnimationCommand::InsertKeyframe { keyframe_id, .. }]) if *keyframe_id == id
+        ));
+    }
+}
@@ -2,7 +2,8 @@
 //!
 //! This module provides cubic bezier solving for smooth animation curves.
 
-use super::keyframe::{Keyframe, KeyframeType};
+use super::extrapolation::Extrapolation;
+use super::keyframe::{BezierHandles, Keyframe, KeyframeType};
 use super::time::TimeTick;
 
 /// Cubic bezier curve solver.
@@ -59,6 +60,15 @@ impl CubicBezier {
         Self::new(left_right_x, left_right_y, right_left_x, right_left_y)
     }
 
+    /// Create the bezier of the segment from `keyframes[index]` to the next
+    /// keyframe.
+    ///
+    /// Honors weighted and auto handles; see [`segment_control_points`].
+    pub fn from_keyframes<T: Lerp>(keyframes: &[&Keyframe<T>], index: usize) -> Self {
+        let [x1, y1, x2, y2] = segment_control_points(keyframes, index);
+        Self::new(x1, y1, x2, y2)
+    }
+
     /// Linear bezier (straight line).
     pub fn linear() -> Self {
         Self::new(0.0, 0.0, 1.0, 1.0)
@@ -178,18 +188,67 @@ impl<T: Clone> InterpolationTriple<T> {
 pub trait Lerp {
     /// Linearly interpolate between self and other.
     fn lerp(&self, other: &Self, t: f32) -> Self;
+
+    /// Offset self by `count` times the difference `to - from`.
+    ///
+    /// Used by [`Extrapolation::CycleWithOffset`]. The default implementation
+    /// returns `self` unchanged.
+    fn offset_by(&self, _from: &Self, _to: &Self, _count: f32) -> Self
+    where
+        Self: Clone,
+    {
+        self.clone()
+    }
+
+    /// The value as a scalar, if it is one.
+    ///
+    /// [Weighted handles](Keyframe::weighted) only apply to scalar values.
+    /// The default returns `None`.
+    fn scalar(&self) -> Option<f64> {
+        None
+    }
 }
 
 impl Lerp for f32 {
     fn lerp(&self, other: &Self, t: f32) -> Self {
         self + (other - self) * t
     }
+
+    fn offset_by(&self, from: &Self, to: &Self, count: f32) -> Self {
+        self + (to - from) * count
+    }
+
+    fn scalar(&self) -> Option<f64> {
+        Some(*self as f64)
+    }
 }
 
 impl Lerp for f64 {
     fn lerp(&self, other: &Self, t: f32) -> Self {
         self + (other - self) * t as f64
     }
+
+    fn offset_by(&self, from: &Self, to: &Self, count: f32) -> Self {
+        self + (to - from) * count as f64
+    }
+
+    fn scalar(&self) -> Option<f64> {
+        Some(*self)
+    }
+}
+
+/// Stepped: holds `self` until the next keyframe, e.g. for visibility.
+impl Lerp for bool {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        if t < 1.0 { *self } else { *other }
+    }
+}
+
+/// Stepped: holds `self` until the next keyframe, e.g. for enum states.
+impl Lerp for u32 {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        if t < 1.0 { *self } else { *other }
+    }
 }
 
 impl<const N: usize> Lerp for [f32; N] {
@@ -200,6 +259,258 @@ impl<const N: usize> Lerp for [f32; N] {
         }
         result
     }
+
+    fn offset_by(&self, from: &Self, to: &Self, count: f32) -> Self {
+        let mut result = *self;
+        for i in 0..N {
+            result[i] = self[i] + (to[i] - from[i]) * count;
+        }
+        result
+    }
+}
+
+/// Normalized handles of `keyframes[index]` relative to its neighbors.
+///
+/// For scalar values, [`KeyframeType::Auto`] keyframes get handles computed
+/// from their neighbors and [weighted handles](Keyframe::weighted) are
+/// normalized to the neighboring segments. Otherwise the stored handles are
+/// returned.
+///
+/// `keyframes` must be sorted by position.
+pub fn resolved_handles<T: Lerp>(keyframes: &[&Keyframe<T>], index: usize) -> BezierHandles {
+    let kf = keyframes[index];
+    if kf.value.scalar().is_none() {
+        return kf.handles;
+    }
+    let span = |from: &Keyframe<T>, to: &Keyframe<T>| {
+        Some((
+            (to.position - from.position).value(),
+            to.value.scalar()? - from.value.scalar()?,
+        ))
+    };
+    let prev = index.checked_sub(1).map(|i| keyframes[i]);
+    let next = keyframes.get(index + 1).copied();
+    let left_span = prev.and_then(|prev| span(prev, kf));
+    let right_span = next.and_then(|next| span(kf, next));
+
+    if kf.keyframe_type == KeyframeType::Auto {
+        return auto_handles(left_span, right_span);
+    }
+    match kf.weighted {
+        Some(weighted) => weighted.to_normalized(left_span, right_span),
+        None => kf.handles,
+    }
+}
+
+/// Auto-clamped handles of a keyframe: tangent parallel to the line through
+/// its neighbors, flat at extremes and ends, one third of each segment long.
+///
+/// Handle heights are clamped to their segment so the curve does not
+/// overshoot the neighboring values.
+fn auto_handles(left_span: Option<(f64, f64)>, right_span: Option<(f64, f64)>) -> BezierHandles {
+    let slope = match (left_span, right_span) {
+        (Some((left_dt, left_dv)), Some((right_dt, right_dv)))
+            if left_dv * right_dv > 0.0 && left_dt + right_dt > 0.0 =>
+        {
+            (left_dv + right_dv) / (left_dt + right_dt)
+        }
+        _ => 0.0,
+    };
+    // Height of a third-length handle, normalized to a segment.
+    let height = |(dt, dv): (f64, f64)| {
+        if dv.abs() > f64::EPSILON {
+            (slope * dt / 3.0 / dv).clamp(0.0, 1.0) as f32
+        } else {
+            0.0
+        }
+    };
+
+    let linear = BezierHandles::linear();
+    BezierHandles {
+        left_x: if left_span.is_some() {
+            2.0 / 3.0
+        } else {
+            linear.left_x
+        },
+        left_y: left_span.map_or(linear.left_y, |span| 1.0 - height(span)),
+        right_x: if right_span.is_some() {
+            1.0 / 3.0
+        } else {
+            linear.right_x
+        },
+        right_y: right_span.map_or(linear.right_y, height),
+    }
+}
+
+/// Normalized control points `[x1, y1, x2, y2]` of the bezier segment from
+/// `keyframes[index]` to the next keyframe.
+///
+/// Handles are [resolved](resolved_handles) first. Control points reaching
+/// past the other keyframe in time are pulled back along their tangent so the
+/// curve stays a function of time.
+///
+/// `keyframes` must be sorted by position and `index + 1` in bounds.
+pub fn segment_control_points<T: Lerp>(keyframes: &[&Keyframe<T>], index: usize) -> [f32; 4] {
+    let out_handle = resolved_handles(keyframes, index);
+    let in_handle = resolved_handles(keyframes, index + 1);
+
+    // Scale each handle about its keyframe so its X stays within `[0, 1]`.
+    let (x1, y1) = if out_handle.right_x > 1.0 {
+        (1.0, out_handle.right_y / out_handle.right_x)
+    } else {
+        (out_handle.right_x.max(0.0), out_handle.right_y)
+    };
+    let (x2, y2) = if in_handle.left_x < 0.0 {
+        let length = 1.0 - in_handle.left_x;
+        (0.0, 1.0 - (1.0 - in_handle.left_y) / length)
+    } else {
+        (in_handle.left_x.min(1.0), in_handle.left_y)
+    };
+    [x1, y1, x2, y2]
+}
+
+/// Split the normalized segment bezier `[x1, y1, x2, y2]` (see
+/// [`segment_control_points`]) at the time fraction `x` with de Casteljau's
+/// algorithm.
+///
+/// Returns the control points of the part before and after the split, each
+/// normalized to its own part, and the progression at the split. A part
+/// without change in value gets flat control points.
+pub fn split_control_points(points: [f32; 4], x: f32) -> ([f32; 4], f32, [f32; 4]) {
+    let [x1, y1, x2, y2] = points;
+    let t = CubicBezier::new(x1, y1, x2, y2).solve_curve_x(x.clamp(0.0, 1.0));
+    let lerp =
+        |(ax, ay): (f32, f32), (bx, by): (f32, f32)| (ax + (bx - ax) * t, ay + (by - ay) * t);
+
+    let (p0, p1, p2, p3) = ((0.0, 0.0), (x1, y1), (x2, y2), (1.0, 1.0));
+    let (q0, q1, q2) = (lerp(p0, p1), lerp(p1, p2), lerp(p2, p3));
+    let (r0, r1) = (lerp(q0, q1), lerp(q1, q2));
+    let (mx, my) = lerp(r0, r1);
+
+    // Normalize a point of the part spanning `origin + (0..=span)`.
+    let normalize = |value: f32, origin: f32, span: f32| {
+        if span.abs() > f32::EPSILON {
+            (value - origin) / span
+        } else {
+            0.0
+        }
+    };
+    let before = [
+        normalize(q0.0, 0.0, mx),
+        normalize(q0.1, 0.0, my),
+        normalize(r0.0, 0.0, mx),
+        normalize(r0.1, 0.0, my),
+    ];
+    let after = [
+        normalize(r1.0, mx, 1.0 - mx),
+        normalize(r1.1, my, 1.0 - my),
+        normalize(q2.0, mx, 1.0 - mx),
+        normalize(q2.1, my, 1.0 - my),
+    ];
+    (before, my, after)
+}
+
+/// Slopes (dy/dx) of the normalized curve of the segment from
+/// `keyframes[index]` to the next keyframe at its start and end.
+///
+/// Hold and disconnected segments are flat, linear ones have slope one.
+///
+/// `keyframes` must be sorted by position and `index + 1` in bounds.
+pub fn segment_end_slopes<T: Lerp>(keyframes: &[&Keyframe<T>], index: usize) -> (f32, f32) {
+    let kf = keyframes[index];
+    if !kf.connected_right {
+        return (0.0, 0.0);
+    }
+    match kf.keyframe_type {
+        KeyframeType::Hold => (0.0, 0.0),
+        KeyframeType::Linear => (1.0, 1.0),
+        KeyframeType::Spring => {
+            const STEP: f64 = 1e-6;
+            let duration = (keyframes[index + 1].position - kf.position).value();
+            let progress = |x: f64| kf.spring.progress(x * duration);
+            (
+                (progress(STEP) / STEP) as f32,
+                ((progress(1.0) - progress(1.0 - STEP)) / STEP) as f32,
+            )
+        }
+        KeyframeType::Bezier | KeyframeType::Auto => {
+            let [x1, y1, x2, y2] = segment_control_points(keyframes, index);
+            let start = if x1 > 1e-6 { y1 / x1 } else { 0.0 };
+            let end = if x2 < 1.0 - 1e-6 {
+                (1.0 - y2) / (1.0 - x2)
+            } else {
+                0.0
+            };
+            (start, end)
+        }
+    }
+}
+
+/// Fit normalized segment control points `[x1, y1, x2, y2]` to `samples` of
+/// a curve from `(0, 0)` to `(1, 1)`, given as `(x, y)` pairs.
+///
+/// The handles keep the tangent slopes `start_slope` and `end_slope` (dy/dx)
+/// at the ends; their lengths are fitted to minimize the squared value error
+/// at the samples with a few Gauss-Newton steps.
+pub fn fit_control_points(samples: &[(f32, f32)], start_slope: f32, end_slope: f32) -> [f32; 4] {
+    let unit = |(x, y): (f32, f32)| {
+        let length = (x * x + y * y).sqrt();
+        (x / length, y / length)
+    };
+    let (t1, t2) = (unit((1.0, start_slope)), unit((-1.0, -end_slope)));
+    let points = |(a1, a2): (f32, f32)| [t1.0 * a1, t1.1 * a1, 1.0 + t2.0 * a2, 1.0 + t2.1 * a2];
+    let residuals = |lengths: (f32, f32)| -> Vec<f32> {
+        let [x1, y1, x2, y2] = points(lengths);
+        let bezier = CubicBezier::new(x1, y1, x2, y2);
+        samples.iter().map(|(x, y)| bezier.solve(*x) - y).collect()
+    };
+    let cost = |r: &[f32]| r.iter().map(|r| r * r).sum::<f32>();
+
+    // Start from handles a third of the chord long, as default handles.
+    const MIN_LENGTH: f32 = 1e-3;
+    const STEP: f32 = 1e-3;
+    let mut lengths = (2f32.sqrt() / 3.0, 2f32.sqrt() / 3.0);
+    let mut r = residuals(lengths);
+    for _ in 0..20 {
+        let j1: Vec<f32> = residuals((lengths.0 + STEP, lengths.1))
+            .iter()
+            .zip(&r)
+            .map(|(moved, r)| (moved - r) / STEP)
+            .collect();
+        let j2: Vec<f32> = residuals((lengths.0, lengths.1 + STEP))
+            .iter()
+            .zip(&r)
+            .map(|(moved, r)| (moved - r) / STEP)
+            .collect();
+        let dot = |a: &[f32], b: &[f32]| a.iter().zip(b).map(|(a, b)| a * b).sum::<f32>();
+        let (c11, c12, c22) = (dot(&j1, &j1), dot(&j1, &j2), dot(&j2, &j2));
+        let (g1, g2) = (dot(&j1, &r), dot(&j2, &r));
+        let det = c11 * c22 - c12 * c12;
+        if det.abs() < 1e-12 {
+            break;
+        }
+        let mut delta = ((c12 * g2 - c22 * g1) / det, (c12 * g1 - c11 * g2) / det);
+
+        // Halve the step until it reduces the error.
+        let mut improved = false;
+        for _ in 0..8 {
+            let candidate = (
+                (lengths.0 + delta.0).max(MIN_LENGTH),
+                (lengths.1 + delta.1).max(MIN_LENGTH),
+            );
+            let candidate_r = residuals(candidate);
+            if cost(&candidate_r) < cost(&r) {
+                (lengths, r) = (candidate, candidate_r);
+                improved = true;
+                break;
+            }
+            delta = (delta.0 / 2.0, delta.1 / 2.0);
+        }
+        if !improved {
+            break;
+        }
+    }
+    points(lengths)
 }
 
 /// Compute the interpolation triple at a given position.
@@ -210,7 +521,7 @@ impl<const N: usize> Lerp for [f32; N] {
 ///
 /// # Returns
 /// `None` if there are no keyframes, otherwise the interpolation triple.
-pub fn interpolate_at_position<T: Clone>(
+pub fn interpolate_at_position<T: Lerp + Clone>(
     keyframes: &[&Keyframe<T>],
     position: impl Into<TimeTick>,
 ) -> Option<InterpolationTriple<T>> {
@@ -278,15 +589,13 @@ pub fn interpolate_at_position<T: Clone>(
             let value_progression = match left_kf.keyframe_type {
                 KeyframeType::Hold => 0.0,
                 KeyframeType::Linear => local_pos,
-                KeyframeType::Bezier => {
-                    let bezier = CubicBezier::from_handles(
-                        left_kf.handles.right_x,
-                        left_kf.handles.right_y,
-                        right_kf.handles.left_x,
-                        right_kf.handles.left_y,
-                    );
-                    bezier.solve(local_pos)
+                KeyframeType::Bezier | KeyframeType::Auto => {
+                    CubicBezier::from_keyframes(keyframes, l).solve(local_pos)
                 }
+                KeyframeType::Spring => left_kf
+                    .spring
+                    .progress((position - left_kf.position).value())
+                    as f32,
             };
 
             Some(InterpolationTriple {
@@ -301,6 +610,104 @@ pub fn interpolate_at_position<T: Clone>(
     }
 }
 
+/// Compute the interpolation triple at a given position, honoring
+/// extrapolation outside the keyframe range.
+///
+/// Inside the keyframe range this is identical to [`interpolate_at_position`],
+/// which corresponds to [`Extrapolation::Constant`] on both sides.
+///
+/// # Arguments
+/// * `keyframes` - Slice of keyframes, must be sorted by position
+/// * `position` - Time position to evaluate at
+/// * `pre` - Extrapolation before the first keyframe
+/// * `post` - Extrapolation after the last keyframe
+pub fn interpolate_with_extrapolation<T: Lerp + Clone>(
+    keyframes: &[&Keyframe<T>],
+    position: impl Into<TimeTick>,
+    pre: Extrapolation,
+    post: Extrapolation,
+) -> Option<InterpolationTriple<T>> {
+    let position = position.into();
+    let first = keyframes.first()?;
+    let last = keyframes.last()?;
+
+    let mode = if position < first.position {
+        pre
+    } else if position > last.position {
+        post
+    } else {
+        Extrapolation::Constant
+    };
+
+    match mode {
+        Extrapolation::Constant => interpolate_at_position(keyframes, position),
+        Extrapolation::Linear => Some(extrapolate_linear(keyframes, position)),
+        Extrapolation::Cycle | Extrapolation::CycleWithOffset | Extrapolation::PingPong => {
+            let period = (last.position - first.position).value();
+            let (local, cycle) = mode.map_offset((position - first.position).value(), period);
+            let triple = interpolate_at_position(keyframes, first.position + TimeTick::new(local))?;
+
+            if mode == Extrapolation::CycleWithOffset && cycle != 0 {
+                let count = cycle as f32;
+                Some(InterpolationTriple {
+                    left: triple.left.offset_by(&first.value, &last.value, count),
+                    right: triple
+                        .right
+                        .map(|right| right.offset_by(&first.value, &last.value, count)),
+                    progression: triple.progression,
+                })
+            } else {
+                Some(triple)
+            }
+        }
+    }
+}
+
+/// Extrapolate linearly along the tangent of the boundary keyframe.
+///
+/// The tangent is expressed as a progression slope between the boundary
+/// keyframe and its neighbor, so the result stays an [`InterpolationTriple`].
+fn extrapolate_linear<T: Lerp + Clone>(
+    keyframes: &[&Keyframe<T>],
+    position: TimeTick,
+) -> InterpolationTriple<T> {
+    let hold = |kf: &Keyframe<T>| InterpolationTriple {
+        left: kf.value.clone(),
+        right: None,
+        progression: 0.0,
+    };
+
+    if keyframes.len() < 2 {
+        return hold(keyframes[0]);
+    }
+
+    let before = position < keyframes[0].position;
+    let left_index = if before { 0 } else { keyframes.len() - 2 };
+    let (left_kf, right_kf) = (keyframes[left_index], keyframes[left_index + 1]);
+
+    let time_range = (right_kf.position - left_kf.position).value();
+    if time_range <= 0.0 {
+        return hold(if before { left_kf } else { right_kf });
+    }
+
+    // Slope of the normalized segment curve (dy/dx) at the boundary.
+    let (start_slope, end_slope) = segment_end_slopes(keyframes, left_index);
+    let slope = if before { start_slope } else { end_slope };
+
+    let (anchor, boundary) = if before {
+        (0.0, left_kf)
+    } else {
+        (1.0, right_kf)
+    };
+    let local = ((position - boundary.position).value() / time_range) as f32;
+
+    InterpolationTriple {
+        left: left_kf.value.clone(),
+        right: Some(right_kf.value.clone()),
+        progression: anchor + local * slope,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -382,4 +789,122 @@ mod tests {
         // Ease-in-out at midpoint should be close to 0.5 but eased
         assert!(result.progression >= 0.0 && result.progression <= 1.0);
     }
+
+    #[test]
+    fn weighted_handles_keep_length_in_time() {
+        use crate::core::keyframe::WeightedHandles;
+
+        // A weighted handle reaching past the next keyframe is shortened
+        // along its tangent.
+        let weighted = WeightedHandles {
+            right_time: 4.0,
+            right_value: 8.0,
+            ..Default::default()
+        };
+        let kf1 = Keyframe::new(0.0, 0.0_f32).with_weighted(Some(weighted));
+        let kf2 = Keyframe::new(2.0, 4.0_f32).with_handles(BezierHandles::ease_in_out());
+        assert_eq!(
+            segment_control_points(&[&kf1, &kf2], 0),
+            [1.0, 1.0, 0.42, 0.0]
+        );
+
+        // Non-scalar values ignore weighted handles.
+        let kf3 = Keyframe::new(0.0, [0.0_f32; 2]).with_weighted(Some(weighted));
+        let kf4 = Keyframe::new(2.0, [4.0_f32; 2]);
+        assert_eq!(
+            segment_control_points(&[&kf3, &kf4], 0),
+            [1.0, 1.0, 0.0, 0.0]
+        );
+    }
+
+    #[test]
+    fn auto_handles_follow_neighbors() {
+        let auto = |time: f64, value: f32| Keyframe::new(time, value).with_type(KeyframeType::Auto);
+        let (kf1, kf2, kf3) = (auto(0.0, 0.0), auto(1.0, 10.0), auto(2.0, 20.0));
+        let rising = [&kf1, &kf2, &kf3];
+
+        // The middle tangent follows its neighbors; the ends are flat.
+        let middle = resolved_handles(&rising, 1);
+        assert!((middle.right_x - 1.0 / 3.0).abs() < 1e-6);
+        assert!((middle.right_y - 1.0 / 3.0).abs() < 1e-6);
+        assert_eq!(resolved_handles(&rising, 0).right_y, 0.0);
+
+        // Moving a neighbor recomputes the tangent.
+        let kf3 = auto(2.0, 10.0);
+        let peak = [&kf1, &kf2, &kf3];
+        assert_eq!(resolved_handles(&peak, 1).right_y, 0.0);
+        assert_eq!(resolved_handles(&peak, 1).left_y, 1.0);
+    }
+
+    #[test]
+    fn extrapolate_linear_both_sides() {
+        let kf1 = Keyframe::new(0.0, 0.0_f32).with_type(KeyframeType::Linear);
+        let kf2 = Keyframe::new(1.0, 10.0_f32).with_type(KeyframeType::Linear);
+        let keyframes: Vec<&Keyframe<f32>> = vec![&kf1, &kf2];
+        let linear = Extrapolation::Linear;
+
+        let after = interpolate_with_extrapolation(&keyframes, 2.0, linear, linear).unwrap();
+        assert!((after.lerp() - 20.0).abs() < 1e-4);
+
+        let before = interpolate_with_extrapolation(&keyframes, -1.0, linear, linear).unwrap();
+        assert!((before.lerp() + 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn extrapolate_cycles() {
+        let kf1 = Keyframe::new(0.0, 0.0_f32).with_type(KeyframeType::Linear);
+        let kf2 = Keyframe::new(1.0, 10.0_f32);
+        let keyframes: Vec<&Keyframe<f32>> = vec![&kf1, &kf2];
+        let constant = Extrapolation::Constant;
+
+        let cycle =
+            interpolate_with_extrapolation(&keyframes, 1.25, constant, Extrapolation::Cycle);
+        assert!((cycle.unwrap().lerp() - 2.5).abs() < 1e-4);
+
+        let offset = interpolate_with_extrapolation(
+            &keyframes,
+            2.25,
+            constant,
+            Extrapolation::CycleWithOffset,
+        );
+        assert!((offset.unwrap().lerp() - 22.5).abs() < 1e-4);
+
+        let ping_pong =
+            interpolate_with_extrapolation(&keyframes, 1.25, constant, Extrapolation::PingPong);
+        assert!((ping_pong.unwrap().lerp() - 7.5).abs() < 1e-4);
+
+        // Constant holds the boundary value.
+        let held = interpolate_with_extrapolation(&keyframes, -3.0, constant, constant);
+        assert_eq!(held.unwrap().lerp(), 0.0);
+    }
+
+    #[test]
+    fn springs_overshoot_and_settle() {
+        use crate::core::keyframe::Spring;
+
+        let spring = |damping, response| Spring {
+            frequency: 2.0,
+            damping,
+            response,
+        };
+        let kf1 = Keyframe::new(0.0, 0.0_f32)
+            .with_type(KeyframeType::Spring)
+            .with_spring(spring(0.3, 0.0));
+        let kf2 = Keyframe::new(4.0, 10.0_f32);
+        let keyframes: Vec<&Keyframe<f32>> = vec![&kf1, &kf2];
+        let at = |time: f64| interpolate_at_position(&keyframes, time).unwrap().lerp();
+
+        assert_eq!(at(0.0), 0.0);
+        let peak = (1..40).map(|i| at(i as f64 / 40.0)).fold(0.0, f32::max);
+        assert!(peak > 10.5, "{peak}");
+        assert!((at(3.9) - 10.0).abs() < 1e-2);
+        assert!(segment_end_slopes(&keyframes, 0).0.abs() < 1e-2);
+
+        // Critical damping never overshoots; a response starts moving at once
+        // and a negative one anticipates.
+        let critical = spring(1.0, 0.0);
+        assert!((0..100).all(|i| critical.progress(i as f64 / 20.0) <= 1.0));
+        assert!(spring(1.0, 1.0).progress(0.05) > critical.progress(0.05));
+        assert!(spring(0.5, -1.0).progress(0.02) < 0.0);
+    }
 }
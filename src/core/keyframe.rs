@@ -4,6 +4,7 @@
 //! bezier handles for smooth interpolation.
 
 use super::time::TimeTick;
+use egui::Color32;
 use uuid::Uuid;
 
 #[cfg(feature = "serde")]
@@ -123,6 +124,108 @@ impl BezierHandles {
     }
 }
 
+/// Weighted bezier handles as absolute offsets from their keyframe.
+///
+/// Unlike [`BezierHandles`], which are normalized to the neighboring segments,
+/// these keep their length in time and value units when keyframes move, like
+/// Maya-style weighted tangents. A handle may reach past the neighboring
+/// keyframe in time; for evaluation it is then shortened along its tangent so
+/// the curve stays a function of time.
+///
+/// The left handle usually has a negative time offset, the right one a
+/// positive one.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct WeightedHandles {
+    /// Left handle time offset.
+    pub left_time: f64,
+    /// Left handle value offset.
+    pub left_value: f64,
+    /// Right handle time offset.
+    pub right_time: f64,
+    /// Right handle value offset.
+    pub right_value: f64,
+}
+
+impl WeightedHandles {
+    /// Convert normalized handles to weighted ones.
+    ///
+    /// `left_span` and `right_span` are the `(time, value)` deltas of the
+    /// segments before and after the keyframe. Handles without a segment get
+    /// zero offsets.
+    pub fn from_normalized(
+        handles: &BezierHandles,
+        left_span: Option<(f64, f64)>,
+        right_span: Option<(f64, f64)>,
+    ) -> Self {
+        let (left_time, left_value) = left_span.map_or((0.0, 0.0), |(dt, dv)| {
+            (
+                (handles.left_x as f64 - 1.0) * dt,
+                (handles.left_y as f64 - 1.0) * dv,
+            )
+        });
+        let (right_time, right_value) = right_span.map_or((0.0, 0.0), |(dt, dv)| {
+            (handles.right_x as f64 * dt, handles.right_y as f64 * dv)
+        });
+        Self {
+            left_time,
+            left_value,
+            right_time,
+            right_value,
+        }
+    }
+
+    /// Convert to normalized handles.
+    ///
+    /// `left_span` and `right_span` are the `(time, value)` deltas of the
+    /// segments before and after the keyframe. Handles without a segment keep
+    /// the linear default. On flat segments the value offset cannot be
+    /// normalized and the handle becomes flat.
+    ///
+    /// X may fall outside `[0, 1]` for handles reaching past the neighboring
+    /// keyframe.
+    pub fn to_normalized(
+        &self,
+        left_span: Option<(f64, f64)>,
+        right_span: Option<(f64, f64)>,
+    ) -> BezierHandles {
+        let linear = BezierHandles::linear();
+        let (left_x, left_y) = left_span.filter(|(dt, _)| *dt > 0.0).map_or(
+            (linear.left_x, linear.left_y),
+            |(dt, dv)| {
+                (
+                    (1.0 + self.left_time / dt) as f32,
+                    (1.0 + ratio(self.left_value, dv)) as f32,
+                )
+            },
+        );
+        let (right_x, right_y) = right_span.filter(|(dt, _)| *dt > 0.0).map_or(
+            (linear.right_x, linear.right_y),
+            |(dt, dv)| {
+                (
+                    (self.right_time / dt) as f32,
+                    ratio(self.right_value, dv) as f32,
+                )
+            },
+        );
+        BezierHandles {
+            left_x,
+            left_y,
+            right_x,
+            right_y,
+        }
+    }
+}
+
+/// `value / span`, or `0.0` if `span` is (nearly) zero.
+fn ratio(value: f64, span: f64) -> f64 {
+    if span.abs() > f64::EPSILON {
+        value / span
+    } else {
+        0.0
+    }
+}
+
 /// The interpolation type between keyframes.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -134,6 +237,121 @@ pub enum KeyframeType {
     Hold,
     /// Linear interpolation (ignore bezier handles).
     Linear,
+    /// Bezier interpolation with handles computed from the neighboring
+    /// keyframes (auto-clamped), so the curve stays smooth as they move.
+    ///
+    /// Only applies to scalar values; others use the stored handles.
+    Auto,
+    /// A damped spring pulled from this keyframe's value towards the next
+    /// one's, shaped by the keyframe's [`Spring`] parameters.
+    Spring,
+}
+
+/// Parameters of a [`KeyframeType::Spring`] segment, as second-order
+/// dynamics.
+///
+/// The spring may overshoot and settles on the next keyframe's value; if
+/// it has not settled when the next keyframe is reached, the curve jumps.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Spring {
+    /// Natural frequency in oscillations per time unit; higher settles
+    /// faster.
+    pub frequency: f32,
+    /// Damping ratio: `0` oscillates forever, below `1` overshoots and `1`
+    /// or more settles without overshooting.
+    pub damping: f32,
+    /// Initial response: `0` starts at rest, `1` starts moving at once,
+    /// above overshoots more and below `0` anticipates by moving away
+    /// first.
+    pub response: f32,
+}
+
+impl Default for Spring {
+    fn default() -> Self {
+        Self {
+            frequency: 2.0,
+            damping: 0.5,
+            response: 0.0,
+        }
+    }
+}
+
+impl Spring {
+    /// Progress from the start value (`0`) to the target (`1`) after
+    /// `elapsed` time units.
+    pub fn progress(&self, elapsed: f64) -> f64 {
+        if elapsed <= 0.0 {
+            return 0.0;
+        }
+        let omega = std::f64::consts::TAU * (self.frequency as f64).max(1e-3);
+        let zeta = (self.damping as f64).max(0.0);
+        // Offset from the target starts at -1 with the velocity of the
+        // response.
+        let (e0, v0) = (-1.0, self.response as f64 * zeta * omega);
+        let t = elapsed;
+        let offset = if (zeta - 1.0).abs() < 1e-6 {
+            (e0 + (v0 + omega * e0) * t) * (-omega * t).exp()
+        } else if zeta < 1.0 {
+            let omega_d = omega * (1.0 - zeta * zeta).sqrt();
+            let c2 = (v0 + zeta * omega * e0) / omega_d;
+            (-zeta * omega * t).exp() * (e0 * (omega_d * t).cos() + c2 * (omega_d * t).sin())
+        } else {
+            let root = (zeta * zeta - 1.0).sqrt();
+            let (s1, s2) = (-omega * (zeta - root), -omega * (zeta + root));
+            let a = (v0 - s2 * e0) / (s1 - s2);
+            a * (s1 * t).exp() + (e0 - a) * (s2 * t).exp()
+        };
+        1.0 + offset
+    }
+}
+
+/// Color tag of a keyframe, e.g. to tell extremes from breakdowns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KeyframeTag {
+    Red,
+    Orange,
+    Yellow,
+    Green,
+    Blue,
+    Purple,
+}
+
+impl KeyframeTag {
+    /// All tags, in menu order.
+    pub const ALL: [Self; 6] = [
+        Self::Red,
+        Self::Orange,
+        Self::Yellow,
+        Self::Green,
+        Self::Blue,
+        Self::Purple,
+    ];
+
+    /// Name shown to users.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Red => "Red",
+            Self::Orange => "Orange",
+            Self::Yellow => "Yellow",
+            Self::Green => "Green",
+            Self::Blue => "Blue",
+            Self::Purple => "Purple",
+        }
+    }
+
+    /// Color keyframe dots with this tag are drawn in.
+    pub fn color(self) -> Color32 {
+        match self {
+            Self::Red => Color32::from_rgb(235, 80, 80),
+            Self::Orange => Color32::from_rgb(240, 150, 60),
+            Self::Yellow => Color32::from_rgb(235, 215, 70),
+            Self::Green => Color32::from_rgb(90, 200, 100),
+            Self::Blue => Color32::from_rgb(80, 150, 240),
+            Self::Purple => Color32::from_rgb(180, 110, 230),
+        }
+    }
 }
 
 /// A keyframe storing a value at a specific time position.
@@ -156,6 +374,31 @@ pub struct Keyframe<T> {
     pub connected_right: bool,
     /// The interpolation type for the curve leaving this keyframe.
     pub keyframe_type: KeyframeType,
+    /// Weighted handles; when set they replace `handles` for scalar values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub weighted: Option<WeightedHandles>,
+    /// Whether the keyframe is excluded from batch edits such as offsetting,
+    /// scaling and pasting over it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub pinned: bool,
+    /// Whether the keyframe is locked against edits; the widgets ignore
+    /// drags and deletes targeting it.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub locked: bool,
+    /// Whether the keyframe is a breakdown, an in-between of the key poses
+    /// around it, see [`Track::insert_breakdown`](super::track::Track::insert_breakdown).
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub breakdown: bool,
+    /// Text shown when hovering the keyframe.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub label: Option<String>,
+    /// Color tag; tagged keyframes are drawn in the tag's color.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub tag: Option<KeyframeTag>,
+    /// Spring parameters of a [`KeyframeType::Spring`] segment leaving this
+    /// keyframe.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub spring: Spring,
 }
 
 impl<T: Default> Keyframe<T> {
@@ -168,6 +411,13 @@ impl<T: Default> Keyframe<T> {
             handles: BezierHandles::default(),
             connected_right: true,
             keyframe_type: KeyframeType::default(),
+            weighted: None,
+            pinned: false,
+            locked: false,
+            breakdown: false,
+            label: None,
+            tag: None,
+            spring: Spring::default(),
         }
     }
 }
@@ -182,6 +432,13 @@ impl<T> Keyframe<T> {
             handles: BezierHandles::default(),
             connected_right: true,
             keyframe_type: KeyframeType::default(),
+            weighted: None,
+            pinned: false,
+            locked: false,
+            breakdown: false,
+            label: None,
+            tag: None,
+            spring: Spring::default(),
         }
     }
 
@@ -197,11 +454,54 @@ impl<T> Keyframe<T> {
         self
     }
 
+    /// Set weighted handles, which replace the normalized ones for scalar
+    /// values.
+    pub fn with_weighted(mut self, weighted: Option<WeightedHandles>) -> Self {
+        self.weighted = weighted;
+        self
+    }
+
+    /// Set the spring parameters, used by [`KeyframeType::Spring`].
+    pub fn with_spring(mut self, spring: Spring) -> Self {
+        self.spring = spring;
+        self
+    }
+
     /// Set whether this keyframe is connected to the next.
     pub fn with_connected(mut self, connected: bool) -> Self {
         self.connected_right = connected;
         self
     }
+
+    /// Set whether this keyframe is pinned.
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Set whether this keyframe is locked against edits.
+    pub fn with_locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Set whether this keyframe is a breakdown.
+    pub fn with_breakdown(mut self, breakdown: bool) -> Self {
+        self.breakdown = breakdown;
+        self
+    }
+
+    /// Set the hover label.
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// Set the color tag.
+    pub fn with_tag(mut self, tag: Option<KeyframeTag>) -> Self {
+        self.tag = tag;
+        self
+    }
 }
 
 #[cfg(test)]
@@ -228,6 +528,24 @@ mod tests {
         assert_eq!(ease_in.right_y, 0.0);
     }
 
+    #[test]
+    fn weighted_handles_round_trip() {
+        let handles = BezierHandles::ease_in_out();
+        let (left_span, right_span) = (Some((2.0, 10.0)), Some((4.0, -5.0)));
+        let weighted = WeightedHandles::from_normalized(&handles, left_span, right_span);
+        assert!((weighted.left_time + 0.58 * 2.0).abs() < 1e-6);
+        assert!((weighted.right_time - 0.58 * 4.0).abs() < 1e-6);
+
+        let restored = weighted.to_normalized(left_span, right_span);
+        for (a, b) in restored.to_array().iter().zip(handles.to_array()) {
+            assert!((a - b).abs() < 1e-6);
+        }
+
+        // Keyframes moving apart keep the absolute handle length.
+        let stretched = weighted.to_normalized(left_span, Some((8.0, -5.0)));
+        assert!((stretched.right_x - 0.29).abs() < 1e-6);
+    }
+
     #[test]
     fn handles_array_conversion() {
         let handles = BezierHandles::ease_in_out();
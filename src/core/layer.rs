@@ -0,0 +1,270 @@
+//! Animation layers.
+//!
+//! A [`LayeredTrack`] animates one property with a base track and any number
+//! of [`AnimationLayer`]s stacked on top, e.g. a hand-keyed wobble added to
+//! a mocap curve. Each layer blends its value into the result below it by a
+//! [`BlendMode`] and a weight that can itself be animated:
+//!
+//! ```ignore
+//! let mut rotation = LayeredTrack::new(mocap);
+//! rotation.push(AnimationLayer::new("Wobble", wobble).blend(BlendMode::Additive));
+//! let value = rotation.evaluate(1.5);
+//! ```
+//!
+//! Hosts show the layers in the DopeSheet as child rows of the property and
+//! report each layer's mode through
+//! [`AnimationDataProvider::track_blend`](crate::AnimationDataProvider::track_blend).
+
+use super::color::Rgba;
+use super::interpolation::Lerp;
+use super::time::TimeTick;
+use super::track::Track;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// How an [`AnimationLayer`] combines with the layers below it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum BlendMode {
+    /// Replace the value below, crossfading by the weight.
+    #[default]
+    Override,
+    /// Add the weighted value to the value below.
+    Additive,
+    /// Scale the value below; a weight of `0` scales by `1`.
+    Multiply,
+}
+
+impl BlendMode {
+    /// Name shown in the DopeSheet.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Override => "Override",
+            Self::Additive => "Additive",
+            Self::Multiply => "Multiply",
+        }
+    }
+
+    /// Blend `layer` into `base` with `weight`.
+    ///
+    /// [`Override`](Self::Override) clamps the weight to `0..=1`; the other
+    /// modes use it as is.
+    pub fn apply<T: Blend>(self, base: &T, layer: &T, weight: f32) -> T {
+        match self {
+            Self::Override => base.lerp(layer, weight.clamp(0.0, 1.0)),
+            Self::Additive => base.add(layer, weight),
+            Self::Multiply => base.multiply(layer, weight),
+        }
+    }
+}
+
+/// Values that can be layered with every [`BlendMode`].
+pub trait Blend: Lerp + Clone {
+    /// `self + other * weight`.
+    fn add(&self, other: &Self, weight: f32) -> Self;
+
+    /// `self * other`, with `other` faded towards `1` as `weight` drops to
+    /// `0`.
+    fn multiply(&self, other: &Self, weight: f32) -> Self;
+}
+
+impl Blend for f32 {
+    fn add(&self, other: &Self, weight: f32) -> Self {
+        self + other * weight
+    }
+
+    fn multiply(&self, other: &Self, weight: f32) -> Self {
+        self * Lerp::lerp(&1.0, other, weight)
+    }
+}
+
+impl Blend for f64 {
+    fn add(&self, other: &Self, weight: f32) -> Self {
+        self + other * weight as f64
+    }
+
+    fn multiply(&self, other: &Self, weight: f32) -> Self {
+        self * Lerp::lerp(&1.0, other, weight)
+    }
+}
+
+impl<const N: usize> Blend for [f32; N] {
+    fn add(&self, other: &Self, weight: f32) -> Self {
+        std::array::from_fn(|i| self[i].add(&other[i], weight))
+    }
+
+    fn multiply(&self, other: &Self, weight: f32) -> Self {
+        std::array::from_fn(|i| self[i].multiply(&other[i], weight))
+    }
+}
+
+/// Blends in linear RGB, keeping the interpolation space of `self`.
+impl Blend for Rgba {
+    fn add(&self, other: &Self, weight: f32) -> Self {
+        Rgba::new(
+            self.r.add(&other.r, weight),
+            self.g.add(&other.g, weight),
+            self.b.add(&other.b, weight),
+            self.a.add(&other.a, weight),
+        )
+        .space(self.space)
+    }
+
+    fn multiply(&self, other: &Self, weight: f32) -> Self {
+        Rgba::new(
+            self.r.multiply(&other.r, weight),
+            self.g.multiply(&other.g, weight),
+            self.b.multiply(&other.b, weight),
+            self.a.multiply(&other.a, weight),
+        )
+        .space(self.space)
+    }
+}
+
+/// A track blended on top of the layers below it.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AnimationLayer<T> {
+    /// Name shown in the DopeSheet.
+    pub name: String,
+    /// Values of the layer.
+    pub track: Track<T>,
+    /// How the layer combines with the layers below.
+    pub blend: BlendMode,
+    /// Weight where [`weight_track`](Self::weight_track) has no keys.
+    pub weight: f32,
+    /// Animated weight, replacing [`weight`](Self::weight).
+    pub weight_track: Option<Track<f32>>,
+    /// Whether the layer is skipped.
+    pub muted: bool,
+}
+
+impl<T> AnimationLayer<T> {
+    /// Create an [`Override`](BlendMode::Override) layer at full weight.
+    pub fn new(name: impl Into<String>, track: Track<T>) -> Self {
+        Self {
+            name: name.into(),
+            track,
+            blend: BlendMode::default(),
+            weight: 1.0,
+            weight_track: None,
+            muted: false,
+        }
+    }
+
+    /// Set the blend mode.
+    pub fn blend(mut self, blend: BlendMode) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Set the constant weight.
+    pub fn weight(mut self, weight: f32) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    /// Animate the weight.
+    pub fn weight_track(mut self, weight_track: Track<f32>) -> Self {
+        self.weight_track = Some(weight_track);
+        self
+    }
+
+    /// Weight of the layer at global `position`.
+    pub fn weight_at(&self, position: impl Into<TimeTick>) -> f32 {
+        self.weight_track
+            .as_ref()
+            .and_then(|track| track.evaluate(position))
+            .unwrap_or(self.weight)
+    }
+}
+
+/// A property animated by a base track and [`AnimationLayer`]s on top.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct LayeredTrack<T> {
+    /// Track the layers blend into.
+    pub base: Track<T>,
+    /// Layers from bottom to top.
+    pub layers: Vec<AnimationLayer<T>>,
+}
+
+impl<T: Clone> LayeredTrack<T> {
+    /// Create a layered track without layers.
+    pub fn new(base: Track<T>) -> Self {
+        Self {
+            base,
+            layers: Vec::new(),
+        }
+    }
+
+    /// Add a layer on top.
+    pub fn push(&mut self, layer: AnimationLayer<T>) {
+        self.layers.push(layer);
+    }
+
+    /// Evaluate the blended value at a global position.
+    ///
+    /// Layers apply from bottom to top; muted layers and layers without
+    /// keyframes are skipped. Returns `None` if the base track has no
+    /// keyframes.
+    pub fn evaluate(&self, position: impl Into<TimeTick>) -> Option<T>
+    where
+        T: Blend,
+    {
+        let position = position.into();
+        let mut value = self.base.evaluate(position)?;
+        for layer in self.layers.iter().filter(|layer| !layer.muted) {
+            if let Some(layer_value) = layer.track.evaluate(position) {
+                value = layer
+                    .blend
+                    .apply(&value, &layer_value, layer.weight_at(position));
+            }
+        }
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::Keyframe;
+
+    fn constant(value: f32) -> Track<f32> {
+        let mut track = Track::new();
+        track.add_keyframe(Keyframe::new(0.0, value));
+        track
+    }
+
+    #[test]
+    fn blends_layers_bottom_to_top() {
+        let mut layered = LayeredTrack::new(constant(10.0));
+        assert_eq!(layered.evaluate(0.0), Some(10.0));
+
+        let mut fade = Track::new();
+        fade.add_keyframe(Keyframe::new(0.0, 0.0));
+        fade.add_keyframe(Keyframe::new(2.0, 1.0));
+        layered.push(AnimationLayer::new("Pose", constant(20.0)).weight_track(fade));
+        layered.push(
+            AnimationLayer::new("Offset", constant(5.0))
+                .blend(BlendMode::Additive)
+                .weight(2.0),
+        );
+        layered.push(
+            AnimationLayer::new("Scale", constant(3.0))
+                .blend(BlendMode::Multiply)
+                .weight(0.5),
+        );
+
+        // (lerp(10, 20, 0.5) + 5 * 2) * lerp(1, 3, 0.5).
+        assert_eq!(layered.evaluate(1.0), Some(50.0));
+
+        layered.layers[1].muted = true;
+        assert_eq!(layered.evaluate(2.0), Some(40.0));
+
+        layered.push(AnimationLayer::new("Empty", Track::new()));
+        assert_eq!(layered.evaluate(2.0), Some(40.0));
+        assert_eq!(LayeredTrack::<f32>::new(Track::new()).evaluate(0.0), None);
+    }
+}
@@ -0,0 +1,213 @@
+//! Named time annotations ("markers").
+//!
+//! [`Markers`] keeps labeled, colored points in time sorted. Widgets draw
+//! them as vertical flags and use them as snap targets.
+
+use super::time::TimeTick;
+use crate::traits::AnimationCommand;
+use egui::Color32;
+use uuid::Uuid;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a marker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct MarkerId(pub Uuid);
+
+impl MarkerId {
+    /// Create a new random marker ID.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for MarkerId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A labeled, colored point in time.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Marker {
+    /// Unique identifier.
+    pub id: MarkerId,
+    /// Time position.
+    pub time: TimeTick,
+    /// Display label.
+    pub label: String,
+    /// Display color.
+    pub color: Color32,
+}
+
+impl Marker {
+    /// Create a new marker.
+    pub fn new(time: impl Into<TimeTick>, label: impl Into<String>) -> Self {
+        Self {
+            id: MarkerId::new(),
+            time: time.into(),
+            label: label.into(),
+            color: Color32::from_rgb(240, 200, 90),
+        }
+    }
+
+    /// Set the color.
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+}
+
+/// Collection of [`Marker`]s sorted by time.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Markers {
+    markers: Vec<Marker>,
+}
+
+impl Markers {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a marker.
+    pub fn add(&mut self, marker: Marker) -> MarkerId {
+        let id = marker.id;
+        let index = self
+            .markers
+            .partition_point(|other| other.time <= marker.time);
+        self.markers.insert(index, marker);
+        id
+    }
+
+    /// Remove a marker.
+    pub fn remove(&mut self, id: MarkerId) -> Option<Marker> {
+        let index = self.markers.iter().position(|marker| marker.id == id)?;
+        Some(self.markers.remove(index))
+    }
+
+    /// Get a marker by ID.
+    pub fn get(&self, id: MarkerId) -> Option<&Marker> {
+        self.markers.iter().find(|marker| marker.id == id)
+    }
+
+    /// Move a marker to a new time. Returns `false` if it does not exist.
+    pub fn set_time(&mut self, id: MarkerId, time: impl Into<TimeTick>) -> bool {
+        match self.remove(id) {
+            Some(mut marker) => {
+                marker.time = time.into();
+                self.add(marker);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Change a marker's label. Returns `false` if it does not exist.
+    pub fn rename(&mut self, id: MarkerId, label: impl Into<String>) -> bool {
+        self.get_mut(id)
+            .map(|marker| marker.label = label.into())
+            .is_some()
+    }
+
+    /// Set a marker's color. Returns `false` if it does not exist.
+    pub fn set_color(&mut self, id: MarkerId, color: Color32) -> bool {
+        self.get_mut(id)
+            .map(|marker| marker.color = color)
+            .is_some()
+    }
+
+    /// Label `M<n>` for a new marker, numbered one past the highest `M<n>`
+    /// label in use.
+    pub fn next_label(&self) -> String {
+        let last = self
+            .markers
+            .iter()
+            .filter_map(|marker| marker.label.strip_prefix('M')?.parse::<usize>().ok())
+            .max()
+            .unwrap_or(0);
+        format!("M{}", last + 1)
+    }
+
+    /// Apply a marker command. Other commands are ignored.
+    pub fn apply(&mut self, command: &AnimationCommand) {
+        match command {
+            AnimationCommand::AddMarker(marker) => {
+                self.add(marker.clone());
+            }
+            AnimationCommand::MoveMarker { marker_id, time } => {
+                self.set_time(*marker_id, *time);
+            }
+            AnimationCommand::RemoveMarker { marker_id } => {
+                self.remove(*marker_id);
+            }
+            _ => {}
+        }
+    }
+
+    /// Times of all markers in ascending order, e.g. for snapping.
+    pub fn times(&self) -> Vec<TimeTick> {
+        self.markers.iter().map(|marker| marker.time).collect()
+    }
+
+    /// Iterate over markers in time order.
+    pub fn iter(&self) -> impl Iterator<Item = &Marker> {
+        self.markers.iter()
+    }
+
+    /// Number of markers.
+    pub fn len(&self) -> usize {
+        self.markers.len()
+    }
+
+    /// Check if there are no markers.
+    pub fn is_empty(&self) -> bool {
+        self.markers.is_empty()
+    }
+
+    fn get_mut(&mut self, id: MarkerId) -> Option<&mut Marker> {
+        self.markers.iter_mut().find(|marker| marker.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn markers_stay_sorted() {
+        let mut markers = Markers::new();
+        let b = markers.add(Marker::new(2.0, "B"));
+        markers.add(Marker::new(1.0, "A"));
+        markers.add(Marker::new(3.0, "C"));
+
+        assert!(markers.set_time(b, 4.0));
+        let labels: Vec<_> = markers.iter().map(|m| m.label.as_str()).collect();
+        assert_eq!(labels, ["A", "C", "B"]);
+        assert_eq!(markers.times()[2], TimeTick::new(4.0));
+
+        assert!(markers.remove(b).is_some());
+        assert!(!markers.set_time(b, 0.0));
+        assert_eq!(markers.len(), 2);
+    }
+
+    #[test]
+    fn next_label_skips_labels_in_use() {
+        let mut markers = Markers::new();
+        assert_eq!(markers.next_label(), "M1");
+
+        let first = markers.add(Marker::new(1.0, markers.next_label()));
+        markers.add(Marker::new(2.0, markers.next_label()));
+        markers.remove(first);
+        // With `M1` gone there is one marker, but `M2` is taken.
+        assert_eq!(markers.next_label(), "M3");
+
+        markers.add(Marker::new(3.0, "M10"));
+        markers.add(Marker::new(4.0, "Mid"));
+        assert_eq!(markers.next_label(), "M11");
+    }
+}
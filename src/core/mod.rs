@@ -1,7 +1,28 @@
 //! Core data structures for keyframe animation.
 
+pub mod after_effects;
+#[cfg(feature = "arena")]
+pub mod arena;
+pub mod audio;
+pub mod clip;
+pub mod clipboard;
+pub mod color;
+pub mod css;
+pub mod driver;
 pub mod easing;
+pub mod euler;
+#[cfg(feature = "expression")]
+pub mod expression;
+pub mod extrapolation;
+pub mod generators;
+pub mod group;
+pub mod history;
 pub mod interpolation;
 pub mod keyframe;
+pub mod layer;
+pub mod marker;
+pub mod modifier;
+pub mod section;
 pub mod time;
+pub mod time_warp;
 pub mod track;
@@ -0,0 +1,274 @@
+//! Procedural modifiers applied on top of keyframe interpolation.
+//!
+//! A [`ModifierStack`] reshapes a `Track<f32>` after its keys are
+//! interpolated: each [`TrackModifier`] sees the curve with the modifiers
+//! below it applied and may evaluate it at any time, so a [`Cycle`] can
+//! repeat the keys of a curve that [`Noise`] then jitters:
+//!
+//! ```ignore
+//! let mut stack = ModifierStack::new();
+//! stack.push(Cycle { offset: true });
+//! stack.push(Noise { amplitude: 0.1, frequency: 4.0, seed: 7 });
+//! let value = stack.evaluate(&track, 2.5);
+//! ```
+//!
+//! Hosts keep a stack per track; the
+//! [`CurveEditor`](crate::CurveEditor) draws the modified curve over the
+//! keyed one with [`CurveEditor::modifiers`](crate::CurveEditor::modifiers).
+
+use super::time::TimeTick;
+use super::track::Track;
+
+/// A procedural change to a curve, evaluated after keyframe interpolation.
+pub trait TrackModifier: std::fmt::Debug + Send + Sync {
+    /// Name shown to users.
+    fn name(&self) -> &str;
+
+    /// The modified value at `time`, given the curve below the modifier.
+    fn apply(&self, time: TimeTick, input: &ModifierInput<'_>) -> Option<f32>;
+}
+
+/// The curve a [`TrackModifier`] modifies: the keyed curve with the
+/// modifiers below it in the stack applied.
+pub struct ModifierInput<'a> {
+    base: &'a dyn Fn(TimeTick) -> Option<f32>,
+    range: Option<(TimeTick, TimeTick)>,
+    below: &'a [Box<dyn TrackModifier>],
+}
+
+impl ModifierInput<'_> {
+    /// Value of the curve at `time`.
+    pub fn evaluate(&self, time: TimeTick) -> Option<f32> {
+        match self.below.split_last() {
+            Some((modifier, below)) => modifier.apply(
+                time,
+                &ModifierInput {
+                    base: self.base,
+                    range: self.range,
+                    below,
+                },
+            ),
+            None => (self.base)(time),
+        }
+    }
+
+    /// Global time range of the keyframes.
+    pub fn range(&self) -> Option<(TimeTick, TimeTick)> {
+        self.range
+    }
+}
+
+/// Modifiers applied to a track from first to last.
+#[derive(Debug, Default)]
+pub struct ModifierStack {
+    /// Modifiers, the first applied first.
+    pub modifiers: Vec<Box<dyn TrackModifier>>,
+}
+
+impl ModifierStack {
+    /// Create an empty stack.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a modifier on top.
+    pub fn push(&mut self, modifier: impl TrackModifier + 'static) {
+        self.modifiers.push(Box::new(modifier));
+    }
+
+    /// Check if the stack has no modifiers.
+    pub fn is_empty(&self) -> bool {
+        self.modifiers.is_empty()
+    }
+
+    /// Evaluate `track` with the modifiers applied at a global position.
+    pub fn evaluate(&self, track: &Track<f32>, position: impl Into<TimeTick>) -> Option<f32> {
+        let range = track
+            .time_range()
+            .map(|(start, end)| (track.to_global_time(start), track.to_global_time(end)));
+        self.evaluate_with(range, &|time| track.evaluate(time), position.into())
+    }
+
+    /// Evaluate the modified curve of `base`, whose keyframes span `range`.
+    pub(crate) fn evaluate_with(
+        &self,
+        range: Option<(TimeTick, TimeTick)>,
+        base: &dyn Fn(TimeTick) -> Option<f32>,
+        time: TimeTick,
+    ) -> Option<f32> {
+        ModifierInput {
+            base,
+            range,
+            below: &self.modifiers,
+        }
+        .evaluate(time)
+    }
+}
+
+/// Smooth Perlin noise added to the curve, e.g. for camera shake.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Noise {
+    /// Largest deviation from the curve.
+    pub amplitude: f32,
+    /// Bumps per time unit.
+    pub frequency: f64,
+    /// Picks one of many different noise patterns.
+    pub seed: u32,
+}
+
+impl TrackModifier for Noise {
+    fn name(&self) -> &str {
+        "Noise"
+    }
+
+    fn apply(&self, time: TimeTick, input: &ModifierInput<'_>) -> Option<f32> {
+        let noise = perlin(time.value() * self.frequency, self.seed) as f32;
+        input
+            .evaluate(time)
+            .map(|value| value + self.amplitude * noise)
+    }
+}
+
+/// Scales the curve, then offsets it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffsetScale {
+    /// Added after scaling.
+    pub offset: f32,
+    /// Factor the values are multiplied by.
+    pub scale: f32,
+}
+
+impl TrackModifier for OffsetScale {
+    fn name(&self) -> &str {
+        "Offset/Scale"
+    }
+
+    fn apply(&self, time: TimeTick, input: &ModifierInput<'_>) -> Option<f32> {
+        input
+            .evaluate(time)
+            .map(|value| value * self.scale + self.offset)
+    }
+}
+
+/// Repeats the keyed range of the curve before and after it.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Cycle {
+    /// Shift each repetition by the difference between the last and first
+    /// value, so e.g. a walk cycle keeps moving forward.
+    pub offset: bool,
+}
+
+impl TrackModifier for Cycle {
+    fn name(&self) -> &str {
+        if self.offset {
+            "Cycle with offset"
+        } else {
+            "Cycle"
+        }
+    }
+
+    fn apply(&self, time: TimeTick, input: &ModifierInput<'_>) -> Option<f32> {
+        let Some((start, end)) = input.range() else {
+            return input.evaluate(time);
+        };
+        let length = (end - start).value();
+        if length <= 0.0 {
+            return input.evaluate(time);
+        }
+        let cycles = ((time - start).value() / length).floor();
+        let value = input.evaluate(time - TimeTick::new(cycles * length))?;
+        if self.offset && cycles != 0.0 {
+            let rise = input.evaluate(end)? - input.evaluate(start)?;
+            Some(value + rise * cycles as f32)
+        } else {
+            Some(value)
+        }
+    }
+}
+
+/// Keeps the curve within `min..=max`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clamp {
+    /// Lowest value.
+    pub min: f32,
+    /// Highest value.
+    pub max: f32,
+}
+
+impl TrackModifier for Clamp {
+    fn name(&self) -> &str {
+        "Clamp"
+    }
+
+    fn apply(&self, time: TimeTick, input: &ModifierInput<'_>) -> Option<f32> {
+        input
+            .evaluate(time)
+            .map(|value| value.max(self.min).min(self.max))
+    }
+}
+
+/// One-dimensional Perlin noise in `-1..=1`, zero at whole numbers.
+pub(crate) fn perlin(x: f64, seed: u32) -> f64 {
+    let cell = x.floor();
+    let t = x - cell;
+    let from = gradient(cell as i64, seed) * t;
+    let to = gradient(cell as i64 + 1, seed) * (t - 1.0);
+    let fade = t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    // Gradients in `-1..=1` reach at most half the range.
+    2.0 * (from + (to - from) * fade)
+}
+
+/// Pseudo-random slope in `-1..=1` at lattice point `cell`.
+fn gradient(cell: i64, seed: u32) -> f64 {
+    let mut hash = (cell as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+        ^ (seed as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    (hash >> 11) as f64 / (1u64 << 53) as f64 * 2.0 - 1.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::{Keyframe, KeyframeType};
+
+    #[test]
+    fn applies_modifiers_bottom_to_top() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(1.0, 10.0));
+
+        let mut stack = ModifierStack::new();
+        assert_eq!(stack.evaluate(&track, 0.5), Some(5.0));
+
+        stack.push(Cycle { offset: true });
+        assert_eq!(stack.evaluate(&track, 2.5), Some(25.0));
+        assert_eq!(stack.evaluate(&track, -0.5), Some(-5.0));
+
+        stack.push(OffsetScale {
+            offset: 1.0,
+            scale: 2.0,
+        });
+        stack.push(Clamp {
+            min: 0.0,
+            max: 40.0,
+        });
+        assert_eq!(stack.evaluate(&track, 1.5), Some(31.0));
+        assert_eq!(stack.evaluate(&track, 2.5), Some(40.0));
+
+        // Noise vanishes at whole times and stays within its amplitude.
+        let mut noisy = ModifierStack::new();
+        noisy.push(Noise {
+            amplitude: 0.5,
+            frequency: 1.0,
+            seed: 3,
+        });
+        assert_eq!(noisy.evaluate(&track, 0.0), Some(0.0));
+        let jitter: Vec<f32> = (1..10)
+            .map(|i| noisy.evaluate(&track, i as f64 / 10.0).unwrap() - i as f32)
+            .collect();
+        assert!(jitter.iter().all(|offset| offset.abs() <= 0.5));
+        assert!(jitter.iter().any(|offset| offset.abs() > 1e-3));
+    }
+}
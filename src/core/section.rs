@@ -0,0 +1,286 @@
+//! Named time ranges ("shots" or "sections") for structuring long timelines.
+//!
+//! [`Sections`] keeps labeled, colored ranges sorted and non-overlapping.
+
+use super::time::TimeTick;
+use egui::Color32;
+use uuid::Uuid;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unique identifier for a section.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SectionId(pub Uuid);
+
+impl SectionId {
+    /// Create a new random section ID.
+    pub fn new() -> Self {
+        Self(Uuid::new_v4())
+    }
+}
+
+impl Default for SectionId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A labeled, colored time range `[start, end)`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Section {
+    /// Unique identifier.
+    pub id: SectionId,
+    /// Display name.
+    pub name: String,
+    /// Start time (inclusive).
+    pub start: TimeTick,
+    /// End time (exclusive).
+    pub end: TimeTick,
+    /// Display color.
+    pub color: Color32,
+}
+
+impl Section {
+    /// Create a new section. `start` and `end` are swapped if reversed.
+    pub fn new(
+        name: impl Into<String>,
+        start: impl Into<TimeTick>,
+        end: impl Into<TimeTick>,
+    ) -> Self {
+        let (start, end) = (start.into(), end.into());
+        Self {
+            id: SectionId::new(),
+            name: name.into(),
+            start: start.min(end),
+            end: start.max(end),
+            color: Color32::from_rgb(90, 120, 170),
+        }
+    }
+
+    /// Set the color.
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Length of the section.
+    pub fn duration(&self) -> TimeTick {
+        self.end - self.start
+    }
+
+    /// Whether `time` lies within `[start, end)`.
+    pub fn contains(&self, time: TimeTick) -> bool {
+        self.start <= time && time < self.end
+    }
+
+    fn overlaps(&self, start: TimeTick, end: TimeTick) -> bool {
+        self.start < end && start < self.end
+    }
+}
+
+/// Sorted, non-overlapping collection of [`Section`]s.
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Sections {
+    sections: Vec<Section>,
+}
+
+impl Sections {
+    /// Create an empty collection.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a section.
+    ///
+    /// Returns `None` if it is empty or overlaps an existing section.
+    pub fn add(&mut self, section: Section) -> Option<SectionId> {
+        if section.start >= section.end
+            || self
+                .sections
+                .iter()
+                .any(|other| other.overlaps(section.start, section.end))
+        {
+            return None;
+        }
+
+        let id = section.id;
+        let index = self
+            .sections
+            .partition_point(|other| other.start < section.start);
+        self.sections.insert(index, section);
+        Some(id)
+    }
+
+    /// Remove a section.
+    pub fn remove(&mut self, id: SectionId) -> Option<Section> {
+        let index = self.index_of(id)?;
+        Some(self.sections.remove(index))
+    }
+
+    /// Get a section by ID.
+    pub fn get(&self, id: SectionId) -> Option<&Section> {
+        self.sections.iter().find(|section| section.id == id)
+    }
+
+    /// Index of a section in time order.
+    pub fn index_of(&self, id: SectionId) -> Option<usize> {
+        self.sections.iter().position(|section| section.id == id)
+    }
+
+    /// The section containing `time`, if any.
+    pub fn section_at(&self, time: impl Into<TimeTick>) -> Option<&Section> {
+        let time = time.into();
+        self.sections.iter().find(|section| section.contains(time))
+    }
+
+    /// Rename a section. Returns `false` if it does not exist.
+    pub fn rename(&mut self, id: SectionId, name: impl Into<String>) -> bool {
+        self.get_mut(id)
+            .map(|section| section.name = name.into())
+            .is_some()
+    }
+
+    /// Set a section's color. Returns `false` if it does not exist.
+    pub fn set_color(&mut self, id: SectionId, color: Color32) -> bool {
+        self.get_mut(id)
+            .map(|section| section.color = color)
+            .is_some()
+    }
+
+    /// Move a section's boundaries.
+    ///
+    /// The range is clamped so it neither overlaps its neighbors nor becomes
+    /// empty. Returns the applied `(start, end)`.
+    pub fn set_bounds(
+        &mut self,
+        id: SectionId,
+        start: impl Into<TimeTick>,
+        end: impl Into<TimeTick>,
+    ) -> Option<(TimeTick, TimeTick)> {
+        let index = self.index_of(id)?;
+        let (start, end) = (start.into(), end.into());
+        let (start, end) = (start.min(end), start.max(end));
+
+        let section = &self.sections[index];
+        let min_start = index.checked_sub(1).map(|prev| self.sections[prev].end);
+        let max_end = self.sections.get(index + 1).map(|next| next.start);
+
+        let start = min_start
+            .map_or(start, |min| start.max(min))
+            .min(section.end);
+        let end = max_end.map_or(end, |max| end.min(max)).max(start);
+        if start >= end {
+            return None;
+        }
+
+        let section = &mut self.sections[index];
+        section.start = start;
+        section.end = end;
+        Some((start, end))
+    }
+
+    /// Move a section to another position in the sequence.
+    ///
+    /// The sections between the old and new index are re-laid out in their
+    /// new order. Each keeps its duration and the gaps between the slots are
+    /// preserved, so the affected span starts where it did before. Returns
+    /// `false` if the section does not exist.
+    pub fn reorder(&mut self, id: SectionId, new_index: usize) -> bool {
+        let Some(old_index) = self.index_of(id) else {
+            return false;
+        };
+        let new_index = new_index.min(self.sections.len() - 1);
+        if old_index == new_index {
+            return true;
+        }
+
+        let (first, last) = (old_index.min(new_index), old_index.max(new_index));
+        let span_start = self.sections[first].start;
+        let gaps: Vec<TimeTick> = (first + 1..=last)
+            .map(|i| self.sections[i].start - self.sections[i - 1].end)
+            .collect();
+
+        let section = self.sections.remove(old_index);
+        self.sections.insert(new_index, section);
+
+        let mut cursor = span_start;
+        for (offset, section) in self.sections[first..=last].iter_mut().enumerate() {
+            if offset > 0 {
+                cursor += gaps[offset - 1];
+            }
+            let duration = section.duration();
+            section.start = cursor;
+            section.end = cursor + duration;
+            cursor = section.end;
+        }
+        true
+    }
+
+    /// Iterate over sections in time order.
+    pub fn iter(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter()
+    }
+
+    /// Number of sections.
+    pub fn len(&self) -> usize {
+        self.sections.len()
+    }
+
+    /// Check if there are no sections.
+    pub fn is_empty(&self) -> bool {
+        self.sections.is_empty()
+    }
+
+    fn get_mut(&mut self, id: SectionId) -> Option<&mut Section> {
+        self.sections.iter_mut().find(|section| section.id == id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_rejects_overlap() {
+        let mut sections = Sections::new();
+        let intro = sections.add(Section::new("Intro", 0.0, 2.0)).unwrap();
+        assert!(sections.add(Section::new("Overlap", 1.0, 3.0)).is_none());
+        let outro = sections.add(Section::new("Outro", 4.0, 5.0)).unwrap();
+
+        assert_eq!(sections.section_at(1.0).map(|s| s.id), Some(intro));
+        assert_eq!(sections.section_at(2.0), None);
+        assert_eq!(sections.section_at(4.5).map(|s| s.id), Some(outro));
+    }
+
+    #[test]
+    fn set_bounds_clamps_to_neighbors() {
+        let mut sections = Sections::new();
+        sections.add(Section::new("A", 0.0, 2.0));
+        let b = sections.add(Section::new("B", 3.0, 5.0)).unwrap();
+        sections.add(Section::new("C", 6.0, 8.0));
+
+        assert_eq!(
+            sections.set_bounds(b, 1.0, 7.0),
+            Some((TimeTick::new(2.0), TimeTick::new(6.0)))
+        );
+    }
+
+    #[test]
+    fn reorder_preserves_durations_and_gaps() {
+        let mut sections = Sections::new();
+        let a = sections.add(Section::new("A", 0.0, 1.0)).unwrap();
+        sections.add(Section::new("B", 2.0, 5.0));
+
+        assert!(sections.reorder(a, 1));
+        let names: Vec<_> = sections.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, ["B", "A"]);
+
+        let (b, a) = (&sections.sections[0], &sections.sections[1]);
+        assert_eq!((b.start, b.end), (TimeTick::new(0.0), TimeTick::new(3.0)));
+        assert_eq!((a.start, a.end), (TimeTick::new(4.0), TimeTick::new(5.0)));
+    }
+}
@@ -626,6 +626,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::explicit_auto_deref)]
     fn deref_access() {
         let t = TimeTick::new(1.5);
         // Access inner type via deref
@@ -0,0 +1,119 @@
+//! Time remapping.
+//!
+//! A [`TimeWarp`] is a curve from playback time to the time other tracks
+//! are evaluated at. Where the curve is flatter than the diagonal the
+//! animation plays in slow motion, where it is steeper it speeds up, and a
+//! flat stretch holds a frame:
+//!
+//! ```ignore
+//! let warp = TimeWarp::identity(0.0, 4.0);
+//! let value = warp.evaluate(&track, time);
+//! ```
+//!
+//! The warp curve is a plain `Track<f32>`, so it is edited in a
+//! [`CurveEditor`](crate::CurveEditor) with
+//! [`CurveEditorConfig::time_warp`](crate::widgets::CurveEditorConfig::time_warp)
+//! set, which draws the diagonal for reference.
+
+use super::clip::ClipSequence;
+use super::extrapolation::Extrapolation;
+use super::interpolation::Lerp;
+use super::keyframe::{Keyframe, KeyframeType};
+use super::time::TimeTick;
+use super::track::Track;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A curve mapping time in to time out.
+///
+/// Without keyframes, time passes through unchanged.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimeWarp {
+    /// Output time by input time.
+    pub track: Track<f32>,
+}
+
+impl TimeWarp {
+    /// Create a warp from a curve of output time by input time.
+    pub fn new(track: Track<f32>) -> Self {
+        Self { track }
+    }
+
+    /// Create a warp that maps `start..=end` onto itself with linear keys
+    /// and extends linearly beyond, ready to be reshaped.
+    pub fn identity(start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        let mut track =
+            Track::new().with_extrapolation(Extrapolation::Linear, Extrapolation::Linear);
+        for time in [start.into(), end.into()] {
+            track.add_keyframe(
+                Keyframe::new(time, time.value() as f32).with_type(KeyframeType::Linear),
+            );
+        }
+        Self { track }
+    }
+
+    /// Output time at input `time`.
+    pub fn map(&self, time: impl Into<TimeTick>) -> TimeTick {
+        let time = time.into();
+        self.track
+            .evaluate(time)
+            .map_or(time, |warped| TimeTick::new(warped as f64))
+    }
+
+    /// Playback speed at input `time`: `1` for real time, `0.5` for half
+    /// speed and negative while playing backwards.
+    pub fn speed(&self, time: impl Into<TimeTick>) -> f64 {
+        const STEP: f64 = 1e-3;
+        let time = time.into();
+        let before = self.map(time - TimeTick::new(STEP));
+        let after = self.map(time + TimeTick::new(STEP));
+        (after - before).value() / (2.0 * STEP)
+    }
+
+    /// Evaluate `track` at the output time of input `time`.
+    pub fn evaluate<T: Lerp + Clone>(
+        &self,
+        track: &Track<T>,
+        time: impl Into<TimeTick>,
+    ) -> Option<T> {
+        track.evaluate(self.map(time))
+    }
+
+    /// Evaluate `property` of a clip sequence at the output time of input
+    /// `time`, warping all its instances alike.
+    pub fn evaluate_clips<T: Lerp + Clone>(
+        &self,
+        sequence: &ClipSequence<T>,
+        property: &str,
+        time: impl Into<TimeTick>,
+    ) -> Option<T> {
+        sequence.evaluate(property, self.map(time))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ramps_speed_of_evaluated_tracks() {
+        let mut warp = TimeWarp::identity(0.0, 4.0);
+        assert_eq!(warp.map(6.0), TimeTick::new(6.0));
+        assert!((warp.speed(2.0) - 1.0).abs() < 1e-3);
+
+        // Half speed for the first two seconds, then catch up.
+        warp.track
+            .add_keyframe(Keyframe::new(2.0, 1.0).with_type(KeyframeType::Linear));
+        assert_eq!(warp.map(1.0), TimeTick::new(0.5));
+        assert!((warp.speed(1.0) - 0.5).abs() < 1e-3);
+        assert!((warp.speed(3.0) - 1.5).abs() < 1e-3);
+
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(4.0, 40.0));
+        assert_eq!(warp.evaluate(&track, 1.0), Some(5.0));
+        assert_eq!(TimeWarp::default().evaluate(&track, 1.0), Some(10.0));
+    }
+}
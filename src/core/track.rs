@@ -1,6 +1,12 @@
 //! Animation track containing a sequence of keyframes.
 
-use super::keyframe::{Keyframe, KeyframeId};
+use super::extrapolation::Extrapolation;
+use super::interpolation::{
+    CubicBezier, InterpolationTriple, Lerp, fit_control_points, interpolate_at_position,
+    interpolate_with_extrapolation, resolved_handles, segment_control_points, segment_end_slopes,
+    split_control_points,
+};
+use super::keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType, WeightedHandles};
 use super::time::TimeTick;
 use indexmap::IndexMap;
 use uuid::Uuid;
@@ -8,6 +14,10 @@ use uuid::Uuid;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
+/// Largest difference in seconds at which a time counts as that of a
+/// keyframe, absorbing the rounding of retimed and frame-snapped times.
+pub(crate) const KEY_TIME_TOLERANCE: f64 = 1e-6;
+
 /// Unique identifier for a track.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -36,6 +46,27 @@ pub struct Track<T> {
     pub id: TrackId,
     /// Keyframes indexed by their ID.
     keyframes: IndexMap<KeyframeId, Keyframe<T>>,
+    /// Extrapolation before the first keyframe.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extrapolation_pre: Extrapolation,
+    /// Extrapolation after the last keyframe.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub extrapolation_post: Extrapolation,
+    /// Global time at which the track's local time `0` plays.
+    #[cfg_attr(feature = "serde", serde(default = "TimeTick::zero"))]
+    pub time_offset: TimeTick,
+    /// Playback speed of the track's keyframes; `2.0` plays twice as fast.
+    #[cfg_attr(feature = "serde", serde(default = "default_time_scale"))]
+    pub time_scale: f64,
+    /// Whether the track is locked against edits; the widgets ignore drags
+    /// and deletes of its keyframes.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub locked: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_time_scale() -> f64 {
+    1.0
 }
 
 impl<T: Clone> Default for Track<T> {
@@ -47,10 +78,7 @@ impl<T: Clone> Default for Track<T> {
 impl<T: Clone> Track<T> {
     /// Create a new empty track.
     pub fn new() -> Self {
-        Self {
-            id: TrackId::new(),
-            keyframes: IndexMap::new(),
-        }
+        Self::with_id(TrackId::new())
     }
 
     /// Create a track with a specific ID.
@@ -58,9 +86,40 @@ impl<T: Clone> Track<T> {
         Self {
             id,
             keyframes: IndexMap::new(),
+            extrapolation_pre: Extrapolation::default(),
+            extrapolation_post: Extrapolation::default(),
+            time_offset: TimeTick::zero(),
+            time_scale: 1.0,
+            locked: false,
         }
     }
 
+    /// Set the extrapolation before the first and after the last keyframe.
+    pub fn with_extrapolation(mut self, pre: Extrapolation, post: Extrapolation) -> Self {
+        self.extrapolation_pre = pre;
+        self.extrapolation_post = post;
+        self
+    }
+
+    /// Set the time offset and speed applied when evaluating the track.
+    pub fn with_retime(mut self, time_offset: impl Into<TimeTick>, time_scale: f64) -> Self {
+        self.time_offset = time_offset.into();
+        self.time_scale = time_scale;
+        self
+    }
+
+    /// Map a global time to the track's local (keyframe) time.
+    ///
+    /// A zero `time_scale` freezes the track at local time `0`.
+    pub fn to_local_time(&self, time: impl Into<TimeTick>) -> TimeTick {
+        retime_to_local(time.into(), self.time_offset, self.time_scale)
+    }
+
+    /// Map a local (keyframe) time to global time.
+    pub fn to_global_time(&self, local: impl Into<TimeTick>) -> TimeTick {
+        retime_to_global(local.into(), self.time_offset, self.time_scale)
+    }
+
     /// Add a keyframe to the track.
     ///
     /// Returns the keyframe ID.
@@ -70,6 +129,545 @@ impl<T: Clone> Track<T> {
         id
     }
 
+    /// Insert a keyframe at the local time `position` without changing the
+    /// shape of the curve.
+    ///
+    /// The bezier segment around `position` is split in two and the handles
+    /// of the new keyframe and its neighbors are set so the curve stays
+    /// identical. Auto and weighted handles of the neighbors are converted
+    /// to normalized bezier handles. Hold, linear and disconnected segments
+    /// keep their interpolation.
+    ///
+    /// Returns the ID of the new keyframe, or `None` if `position` is not
+    /// strictly between two keyframes.
+    pub fn insert_keyframe_preserving(
+        &mut self,
+        position: impl Into<TimeTick>,
+    ) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        self.insert_keyframe_preserving_with_id(KeyframeId::new(), position)
+    }
+
+    /// Like [`Self::insert_keyframe_preserving`], giving the new keyframe the
+    /// ID `id`.
+    pub fn insert_keyframe_preserving_with_id(
+        &mut self,
+        id: KeyframeId,
+        position: impl Into<TimeTick>,
+    ) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        let position = position.into();
+        let sorted = self.keyframes_sorted();
+        let right = sorted.iter().position(|kf| kf.position > position)?;
+        let left = right.checked_sub(1)?;
+        let (prev, next) = (sorted[left], sorted[right]);
+        if prev.position >= position {
+            return None;
+        }
+
+        let mut keyframe = Keyframe::with_id(
+            id,
+            position,
+            interpolate_at_position(&sorted, position)?.lerp(),
+        )
+        .with_type(prev.keyframe_type)
+        .with_connected(prev.connected_right);
+
+        let bezier = matches!(
+            prev.keyframe_type,
+            KeyframeType::Bezier | KeyframeType::Auto
+        );
+        if !bezier || !prev.connected_right {
+            self.add_keyframe(keyframe);
+            return Some(id);
+        }
+
+        let x = ((position - prev.position) / (next.position - prev.position)) as f32;
+        let (before, progression, after) =
+            split_control_points(segment_control_points(&sorted, left), x);
+        let mut prev_handles = resolved_handles(&sorted, left);
+        let mut next_handles = resolved_handles(&sorted, right);
+        let (prev_id, next_id) = (prev.id, next.id);
+
+        keyframe.value = prev.value.lerp(&next.value, progression);
+        keyframe.keyframe_type = KeyframeType::Bezier;
+        keyframe.handles = BezierHandles::from_array([before[2], before[3], after[0], after[1]]);
+        [prev_handles.right_x, prev_handles.right_y] = [before[0], before[1]];
+        [next_handles.left_x, next_handles.left_y] = [after[2], after[3]];
+
+        self.set_bezier_handles(prev_id, prev_handles);
+        self.set_bezier_handles(next_id, next_handles);
+        self.add_keyframe(keyframe);
+        Some(id)
+    }
+
+    /// Insert a keyframe at the local time `position` with the value the
+    /// curve has there, e.g. to key the current value at the playhead.
+    ///
+    /// Between keyframes this is [`Self::insert_keyframe_preserving`].
+    /// Before the first or after the last keyframe, the new keyframe takes
+    /// the extrapolated value and the type of the nearest keyframe.
+    ///
+    /// Returns the ID of the new keyframe, or `None` if the track is empty
+    /// or already has a keyframe at `position`.
+    pub fn insert_keyframe_on_curve(&mut self, position: impl Into<TimeTick>) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        self.insert_keyframe_on_curve_with_id(KeyframeId::new(), position)
+    }
+
+    /// Like [`Self::insert_keyframe_on_curve`], giving the new keyframe the
+    /// ID `id`.
+    pub fn insert_keyframe_on_curve_with_id(
+        &mut self,
+        id: KeyframeId,
+        position: impl Into<TimeTick>,
+    ) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        let position = position.into();
+        let sorted = self.keyframes_sorted();
+        let (first, last) = (*sorted.first()?, *sorted.last()?);
+        let nearest = if position < first.position {
+            first
+        } else if position > last.position {
+            last
+        } else {
+            return self.insert_keyframe_preserving_with_id(id, position);
+        };
+
+        let value = interpolate_with_extrapolation(
+            &sorted,
+            position,
+            self.extrapolation_pre,
+            self.extrapolation_post,
+        )?
+        .lerp();
+        let keyframe = Keyframe::with_id(id, position, value).with_type(nearest.keyframe_type);
+        self.add_keyframe(keyframe);
+        Some(id)
+    }
+
+    /// Insert a breakdown keyframe at `fraction` of the way in time from
+    /// keyframe `from` to keyframe `to`, with the value the curve has there.
+    ///
+    /// The keyframe is inserted like with [`Self::insert_keyframe_preserving`]
+    /// and marked as [`Keyframe::breakdown`]. Returns its ID, or `None` if
+    /// either keyframe does not exist or there is no room between them.
+    pub fn insert_breakdown(
+        &mut self,
+        from: KeyframeId,
+        to: KeyframeId,
+        fraction: f64,
+    ) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        self.insert_breakdown_with_id(KeyframeId::new(), from, to, fraction)
+    }
+
+    /// Like [`Self::insert_breakdown`], giving the new keyframe the ID `id`.
+    pub fn insert_breakdown_with_id(
+        &mut self,
+        id: KeyframeId,
+        from: KeyframeId,
+        to: KeyframeId,
+        fraction: f64,
+    ) -> Option<KeyframeId>
+    where
+        T: Lerp,
+    {
+        let from = self.keyframes.get(&from)?.position;
+        let to = self.keyframes.get(&to)?.position;
+        self.insert_keyframe_preserving_with_id(id, from.lerp(to, fraction))?;
+        if let Some(keyframe) = self.keyframes.get_mut(&id) {
+            keyframe.breakdown = true;
+        }
+        Some(id)
+    }
+
+    /// Set the value at the local time `time`, e.g. when auto-keying an
+    /// edit: updates the keyframe at `time`, give or take rounding, or adds
+    /// one there.
+    ///
+    /// Returns the ID of the updated or added keyframe.
+    pub fn set_value_at(&mut self, time: impl Into<TimeTick>, value: T) -> KeyframeId {
+        let time = time.into();
+        match self
+            .keyframes
+            .values_mut()
+            .find(|kf| (kf.position - time).value().abs() < KEY_TIME_TOLERANCE)
+        {
+            Some(keyframe) => {
+                keyframe.value = value;
+                keyframe.id
+            }
+            None => self.add_keyframe(Keyframe::with_id(KeyframeId::new(), time, value)),
+        }
+    }
+
+    /// Remove a keyframe and refit the handles of its neighbors so the curve
+    /// keeps approximately the shape it had through the keyframe.
+    ///
+    /// The tangents at the neighbors are kept. Auto and weighted handles of
+    /// the neighbors are converted to normalized bezier handles. Only applies
+    /// to a scalar keyframe between two connected bezier segments whose
+    /// neighbors differ in value; otherwise this is [`Self::remove_keyframe`].
+    ///
+    /// Returns the removed keyframe if it existed.
+    pub fn remove_keyframe_preserving(&mut self, id: KeyframeId) -> Option<Keyframe<T>>
+    where
+        T: Lerp,
+    {
+        let refit = self.refit_neighbor_handles(id);
+        let removed = self.remove_keyframe(id)?;
+        for (neighbor, handles) in refit.into_iter().flatten() {
+            self.set_bezier_handles(neighbor, handles);
+        }
+        Some(removed)
+    }
+
+    /// Remove keyframes that are not needed to keep the curve within
+    /// `tolerance` of its current values, e.g. after importing baked or
+    /// motion capture data with a keyframe per frame.
+    ///
+    /// Runs of connected, non-hold segments of scalar values are refitted
+    /// with as few bezier segments as possible, splitting at the keyframe of
+    /// largest error like Ramer–Douglas–Peucker. The tangents of kept
+    /// keyframes are preserved; those next to removed keyframes get
+    /// normalized bezier handles. Hold and disconnected segments are kept.
+    ///
+    /// Returns the number of removed keyframes.
+    pub fn simplify(&mut self, tolerance: f64) -> usize
+    where
+        T: Lerp,
+    {
+        let sorted = self.keyframes_sorted();
+        let count = sorted.len();
+        let Some(values) = sorted
+            .iter()
+            .map(|kf| kf.value.scalar())
+            .collect::<Option<Vec<f64>>>()
+        else {
+            return 0;
+        };
+        if count < 3 {
+            return 0;
+        }
+        let times: Vec<f64> = sorted.iter().map(|kf| kf.position.value()).collect();
+        let smooth = |segment: usize| {
+            let kf = sorted[segment];
+            kf.connected_right
+                && kf.keyframe_type != KeyframeType::Hold
+                && times[segment + 1] > times[segment]
+        };
+        // Slopes in value per time leaving and entering each segment.
+        let slopes: Vec<(f64, f64)> = (0..count - 1)
+            .map(|segment| {
+                let (start, end) = segment_end_slopes(&sorted, segment);
+                let rate = (values[segment + 1] - values[segment])
+                    / (times[segment + 1] - times[segment]).max(f64::EPSILON);
+                (start as f64 * rate, end as f64 * rate)
+            })
+            .collect();
+        // The curve sampled at and between keyframes; keyframe `i` is sample
+        // `i * SUBSTEPS`.
+        const SUBSTEPS: usize = 4;
+        let samples: Vec<(f64, f64)> = (0..count - 1)
+            .flat_map(|segment| (0..SUBSTEPS).map(move |step| (segment, step)))
+            .map(|(segment, step)| {
+                let x = step as f32 / SUBSTEPS as f32;
+                let progression = match sorted[segment].keyframe_type {
+                    _ if step == 0 => 0.0,
+                    KeyframeType::Linear => x,
+                    _ => CubicBezier::from_keyframes(&sorted, segment).solve(x),
+                };
+                let (t0, t1) = (times[segment], times[segment + 1]);
+                let (v0, v1) = (values[segment], values[segment + 1]);
+                (
+                    t0 + (t1 - t0) * x as f64,
+                    v0 + (v1 - v0) * progression as f64,
+                )
+            })
+            .chain([(times[count - 1], values[count - 1])])
+            .collect();
+
+        let mut keep = vec![true; count];
+        let mut fitted = Vec::new();
+        let mut start = 0;
+        while start < count - 1 {
+            if !smooth(start) {
+                start += 1;
+                continue;
+            }
+            let mut end = start + 1;
+            while end < count - 1 && smooth(end) {
+                end += 1;
+            }
+            keep[start + 1..end].fill(false);
+
+            let mut spans = vec![(start, end)];
+            while let Some((a, b)) = spans.pop() {
+                if b == a + 1 {
+                    continue;
+                }
+                let interior = &samples[a * SUBSTEPS + 1..b * SUBSTEPS];
+                let fit = fit_span(
+                    (times[a], values[a]),
+                    (times[b], values[b]),
+                    (slopes[a].0, slopes[b - 1].1),
+                    interior,
+                );
+                let split = match &fit {
+                    Some((points, error)) if error.0 <= tolerance => {
+                        fitted.push((a, b, *points));
+                        continue;
+                    }
+                    Some((_, (_, sample))) => a + (sample + 1 + SUBSTEPS / 2) / SUBSTEPS,
+                    // The ends have the same value: split at the farthest key.
+                    None => (a + 1..b)
+                        .max_by(|i, j| {
+                            let deviation = |k: &usize| (values[*k] - values[a]).abs();
+                            deviation(i).total_cmp(&deviation(j))
+                        })
+                        .unwrap_or(a + 1),
+                };
+                let split = split.clamp(a + 1, b - 1);
+                keep[split] = true;
+                spans.extend([(a, split), (split, b)]);
+            }
+            start = end;
+        }
+
+        // New handles of the ends of fitted segments, by sorted index.
+        let mut handles: Vec<Option<BezierHandles>> = vec![None; count];
+        for &(a, b, [x1, y1, x2, y2]) in &fitted {
+            let start = handles[a].get_or_insert_with(|| resolved_handles(&sorted, a));
+            [start.right_x, start.right_y] = [x1, y1];
+            let end = handles[b].get_or_insert_with(|| resolved_handles(&sorted, b));
+            [end.left_x, end.left_y] = [x2, y2];
+        }
+        let handles: Vec<(KeyframeId, BezierHandles)> = sorted
+            .iter()
+            .zip(handles)
+            .filter_map(|(kf, handles)| Some((kf.id, handles?)))
+            .collect();
+        let fitted_starts: Vec<KeyframeId> = fitted.iter().map(|(a, _, _)| sorted[*a].id).collect();
+        let removed: Vec<KeyframeId> = sorted
+            .iter()
+            .zip(&keep)
+            .filter(|(_, keep)| !**keep)
+            .map(|(kf, _)| kf.id)
+            .collect();
+
+        for id in &removed {
+            self.keyframes.shift_remove(id);
+        }
+        for (id, handles) in handles {
+            self.set_bezier_handles(id, handles);
+        }
+        // Linear segments may have been replaced by fitted bezier ones.
+        for id in fitted_starts {
+            if let Some(kf) = self.keyframes.get_mut(&id) {
+                kf.keyframe_type = KeyframeType::Bezier;
+            }
+        }
+        removed.len()
+    }
+
+    /// Handles of the neighbors of keyframe `id` approximating the curve
+    /// through it by one segment, see [`Self::remove_keyframe_preserving`].
+    fn refit_neighbor_handles(&self, id: KeyframeId) -> Option<[(KeyframeId, BezierHandles); 2]>
+    where
+        T: Lerp,
+    {
+        let sorted = self.keyframes_sorted();
+        let index = sorted.iter().position(|kf| kf.id == id)?;
+        let (prev, kf, next) = (
+            sorted[index.checked_sub(1)?],
+            sorted[index],
+            *sorted.get(index + 1)?,
+        );
+        let bezier = |kf: &Keyframe<T>| {
+            kf.connected_right
+                && matches!(kf.keyframe_type, KeyframeType::Bezier | KeyframeType::Auto)
+        };
+        if !bezier(prev) || !bezier(kf) {
+            return None;
+        }
+
+        let [t0, t1, t2] = [prev, kf, next].map(|kf| kf.position.value());
+        let [v0, v1, v2] = [
+            prev.value.scalar()?,
+            kf.value.scalar()?,
+            next.value.scalar()?,
+        ];
+        let (duration, change) = (t2 - t0, v2 - v0);
+        if t1 <= t0 || t2 <= t1 || change.abs() < f64::EPSILON {
+            return None;
+        }
+
+        // End slopes in value per time, then normalized to the new segment.
+        let start = segment_end_slopes(&sorted, index - 1).0 as f64 * (v1 - v0) / (t1 - t0);
+        let end = segment_end_slopes(&sorted, index).1 as f64 * (v2 - v1) / (t2 - t1);
+        let normalize = |slope: f64| (slope * duration / change) as f32;
+
+        const SAMPLES: usize = 32;
+        let samples = (1..SAMPLES)
+            .map(|i| {
+                let x = i as f64 / SAMPLES as f64;
+                let value = interpolate_at_position(&sorted, t0 + duration * x)?
+                    .lerp()
+                    .scalar()?;
+                Some((x as f32, ((value - v0) / change) as f32))
+            })
+            .collect::<Option<Vec<_>>>()?;
+        let [a, b, c, d] = fit_control_points(&samples, normalize(start), normalize(end));
+
+        let mut prev_handles = resolved_handles(&sorted, index - 1);
+        let mut next_handles = resolved_handles(&sorted, index + 1);
+        [prev_handles.right_x, prev_handles.right_y] = [a, b];
+        [next_handles.left_x, next_handles.left_y] = [c, d];
+        Some([(prev.id, prev_handles), (next.id, next_handles)])
+    }
+
+    /// Set normalized `handles` on keyframe `id`, turning auto and weighted
+    /// handles into plain bezier ones.
+    fn set_bezier_handles(&mut self, id: KeyframeId, handles: BezierHandles) {
+        if let Some(kf) = self.keyframes.get_mut(&id) {
+            kf.handles = handles;
+            kf.weighted = None;
+            if kf.keyframe_type == KeyframeType::Auto {
+                kf.keyframe_type = KeyframeType::Bezier;
+            }
+        }
+    }
+
+    /// Sample the track at every frame of `fps` within the global time
+    /// `range`, both ends inclusive.
+    ///
+    /// Returns an empty list if the track has no keyframes or `fps` is not
+    /// positive.
+    pub fn bake(&self, fps: f64, range: (TimeTick, TimeTick)) -> Vec<(TimeTick, T)>
+    where
+        T: Lerp,
+    {
+        if self.is_empty() || fps <= 0.0 || !fps.is_finite() {
+            return Vec::new();
+        }
+        // Tolerate rounding of times that sit exactly on a frame.
+        let first = (range.0.to_frames(fps) - 1e-6).ceil() as i64;
+        let last = (range.1.to_frames(fps) + 1e-6).floor() as i64;
+        (first..=last)
+            .filter_map(|frame| {
+                let time = TimeTick::from_frames(frame as f64, fps);
+                self.evaluate(time).map(|value| (time, value))
+            })
+            .collect()
+    }
+
+    /// Replace all keyframes with linear keyframes at every frame of `fps`
+    /// within the global time `range`, for targets without bezier
+    /// interpolation.
+    ///
+    /// Returns the number of baked keyframes. The track is left unchanged if
+    /// there is nothing to sample.
+    pub fn bake_in_place(&mut self, fps: f64, range: (TimeTick, TimeTick)) -> usize
+    where
+        T: Lerp,
+    {
+        let samples = self.bake(fps, range);
+        if samples.is_empty() {
+            return 0;
+        }
+        self.keyframes.clear();
+        for (time, value) in samples {
+            self.add_keyframe(
+                Keyframe::with_id(KeyframeId::new(), self.to_local_time(time), value)
+                    .with_type(KeyframeType::Linear),
+            );
+        }
+        self.len()
+    }
+
+    /// Mirror the keyframes `ids` in time around the local time `anchor`,
+    /// so the motion through them plays backwards.
+    ///
+    /// Handles are mirrored and the interpolation type, connection and
+    /// spring of each segment between the keyframes move with it. Pinned
+    /// keyframes stay. Flipping twice around the same anchor restores the
+    /// keyframes. Returns the number of flipped keyframes.
+    pub fn flip_time(&mut self, ids: &[KeyframeId], anchor: TimeTick) -> usize {
+        let mut flipped: Vec<(KeyframeId, TimeTick)> = ids
+            .iter()
+            .filter_map(|id| self.keyframes.get(id))
+            .filter(|kf| !kf.pinned)
+            .map(|kf| (kf.id, kf.position))
+            .collect();
+        flipped.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+        flipped.dedup_by_key(|(id, _)| *id);
+
+        // The segment leaving each keyframe leaves the next one after the
+        // flip; the last keyframe's outer segment goes to the first.
+        let segments: Vec<_> = flipped
+            .iter()
+            .filter_map(|(id, _)| self.keyframes.get(id))
+            .map(|kf| (kf.keyframe_type, kf.connected_right, kf.spring))
+            .collect();
+        for (index, (id, _)) in flipped.iter().enumerate() {
+            let Some(kf) = self.keyframes.get_mut(id) else {
+                continue;
+            };
+            let from = (index + segments.len() - 1) % segments.len();
+            (kf.keyframe_type, kf.connected_right, kf.spring) = segments[from];
+            kf.position = anchor + anchor - kf.position;
+            let handles = kf.handles;
+            kf.handles = BezierHandles {
+                left_x: 1.0 - handles.right_x,
+                left_y: 1.0 - handles.right_y,
+                right_x: 1.0 - handles.left_x,
+                right_y: 1.0 - handles.left_y,
+            };
+            if let Some(weighted) = kf.weighted.as_mut() {
+                *weighted = WeightedHandles {
+                    left_time: -weighted.right_time,
+                    left_value: weighted.right_value,
+                    right_time: -weighted.left_time,
+                    right_value: weighted.left_value,
+                };
+            }
+        }
+        flipped.len()
+    }
+
+    /// Reverse the order of the keyframes `ids` within the time span they
+    /// cover, so the motion through them plays backwards in the same time.
+    ///
+    /// This is [`Self::flip_time`] around the middle of the span; pinned
+    /// keyframes stay and don't count towards it. Returns the number of
+    /// reversed keyframes.
+    pub fn reverse(&mut self, ids: &[KeyframeId]) -> usize {
+        let span = ids
+            .iter()
+            .filter_map(|id| self.keyframes.get(id))
+            .filter(|kf| !kf.pinned)
+            .map(|kf| kf.position)
+            .fold(None, |span, position| match span {
+                None => Some((position, position)),
+                Some((start, end)) => Some((position.min(start), position.max(end))),
+            });
+        match span {
+            Some((start, end)) => self.flip_time(ids, start.lerp(end, 0.5)),
+            None => 0,
+        }
+    }
+
     /// Remove a keyframe by ID.
     ///
     /// Returns the removed keyframe if it existed.
@@ -124,6 +722,37 @@ impl<T: Clone> Track<T> {
         (left, right)
     }
 
+    /// Compute the interpolation triple at a global position, honoring the
+    /// track's retime attributes and extrapolation modes.
+    ///
+    /// Returns `None` if the track has no keyframes.
+    pub fn interpolate(&self, position: impl Into<TimeTick>) -> Option<InterpolationTriple<T>>
+    where
+        T: Lerp,
+    {
+        interpolate_with_extrapolation(
+            &self.keyframes_sorted(),
+            self.to_local_time(position),
+            self.extrapolation_pre,
+            self.extrapolation_post,
+        )
+    }
+
+    /// Evaluate the track's value at a position.
+    ///
+    /// Handles keyframe ordering, hold/linear/bezier interpolation,
+    /// disconnected segments and the track's extrapolation modes. Returns
+    /// `None` if the track has no keyframes.
+    ///
+    /// All [`Animatable`](crate::Animatable) types provided by this crate
+    /// implement [`Lerp`].
+    pub fn evaluate(&self, position: impl Into<TimeTick>) -> Option<T>
+    where
+        T: Lerp,
+    {
+        self.interpolate(position).map(|triple| triple.lerp())
+    }
+
     /// Find the keyframe at the exact position, if any.
     pub fn keyframe_at_position(
         &self,
@@ -205,6 +834,123 @@ impl<T: Clone> Track<T> {
             .map(|(id, kf)| (*id, kf.position))
             .collect()
     }
+
+    /// Release spare keyframe capacity, e.g. after loading or simplifying.
+    pub fn shrink_to_fit(&mut self) {
+        self.keyframes.shrink_to_fit();
+    }
+
+    /// Approximate heap memory held by the keyframes.
+    ///
+    /// Counts the keyframes, their IDs and the lookup table, but not heap
+    /// memory owned by the values themselves.
+    pub fn memory_usage(&self) -> MemoryUsage {
+        // IndexMap stores a hash, the key and the value per entry, plus an
+        // index and a control byte per slot of its hash table.
+        let entry = size_of::<u64>() + size_of::<KeyframeId>() + size_of::<Keyframe<T>>();
+        let slot = size_of::<usize>() + 1;
+        MemoryUsage {
+            keyframes: self.keyframes.len(),
+            used_bytes: self.keyframes.len() * (entry + slot),
+            allocated_bytes: self.keyframes.capacity() * (entry + slot),
+        }
+    }
+}
+
+/// Approximate heap memory of keyframes, see [`Track::memory_usage`].
+///
+/// Usages add up, e.g. to report a whole model.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MemoryUsage {
+    /// Number of keyframes.
+    pub keyframes: usize,
+    /// Bytes in use by the keyframes.
+    pub used_bytes: usize,
+    /// Bytes allocated, including spare capacity.
+    pub allocated_bytes: usize,
+}
+
+impl std::ops::Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            keyframes: self.keyframes + other.keyframes,
+            used_bytes: self.used_bytes + other.used_bytes,
+            allocated_bytes: self.allocated_bytes + other.allocated_bytes,
+        }
+    }
+}
+
+impl std::ops::AddAssign for MemoryUsage {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::iter::Sum for MemoryUsage {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |total, usage| total + usage)
+    }
+}
+
+/// Fit one bezier segment from `start` to `end`, given as `(time, value)`,
+/// with the end slopes in value per time to the `samples` between them.
+///
+/// Returns the normalized control points together with the largest value
+/// error and the index of its sample, or `None` if the ends have the same
+/// value.
+fn fit_span(
+    start: (f64, f64),
+    end: (f64, f64),
+    (start_slope, end_slope): (f64, f64),
+    samples: &[(f64, f64)],
+) -> Option<([f32; 4], (f64, usize))> {
+    let (duration, change) = (end.0 - start.0, end.1 - start.1);
+    if change.abs() < f64::EPSILON {
+        return None;
+    }
+    let normalize = |slope: f64| (slope * duration / change) as f32;
+    let normalized: Vec<(f32, f32)> = samples
+        .iter()
+        .map(|(time, value)| {
+            (
+                ((time - start.0) / duration) as f32,
+                ((value - start.1) / change) as f32,
+            )
+        })
+        .collect();
+
+    let points = fit_control_points(&normalized, normalize(start_slope), normalize(end_slope));
+    let [x1, y1, x2, y2] = points;
+    let bezier = CubicBezier::new(x1, y1, x2, y2);
+    let error = samples
+        .iter()
+        .zip(&normalized)
+        .map(|((_, value), (x, _))| (start.1 + change * bezier.solve(*x) as f64 - value).abs())
+        .enumerate()
+        .map(|(index, error)| (error, index))
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((0.0, 0));
+    Some((points, error))
+}
+
+/// Map a global time to local time given a track's retime attributes.
+pub fn retime_to_local(time: TimeTick, time_offset: TimeTick, time_scale: f64) -> TimeTick {
+    if time_scale == 0.0 {
+        TimeTick::zero()
+    } else {
+        (time - time_offset) * time_scale
+    }
+}
+
+/// Map a local time to global time given a track's retime attributes.
+pub fn retime_to_global(local: TimeTick, time_offset: TimeTick, time_scale: f64) -> TimeTick {
+    if time_scale == 0.0 {
+        time_offset
+    } else {
+        time_offset + local / time_scale
+    }
 }
 
 #[cfg(test)]
@@ -225,6 +971,66 @@ mod tests {
         assert_eq!(track.get_keyframe(id2).unwrap().value, 20.0);
     }
 
+    #[test]
+    fn flip_time_reverses_the_motion() {
+        let mut track = Track::<f32>::new();
+        let ids = [
+            track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear)),
+            track.add_keyframe(
+                Keyframe::new(1.0, 10.0)
+                    .with_handles(BezierHandles::from_array([0.6, 0.8, 0.3, 0.1])),
+            ),
+            track.add_keyframe(Keyframe::new(3.0, 5.0).with_type(KeyframeType::Hold)),
+        ];
+        let original = track.clone();
+
+        assert_eq!(track.flip_time(&ids, TimeTick::new(1.5)), 3);
+        for i in 0..=30 {
+            let time = i as f64 / 10.0;
+            let mirrored = original.evaluate(3.0 - time).unwrap();
+            assert!(
+                (track.evaluate(time).unwrap() - mirrored).abs() < 1e-3,
+                "{time}"
+            );
+        }
+
+        // Flipping again restores the keyframes.
+        track.flip_time(&ids, TimeTick::new(1.5));
+        for id in ids {
+            let (flipped, kf) = (
+                track.get_keyframe(id).unwrap(),
+                original.get_keyframe(id).unwrap(),
+            );
+            assert_eq!(flipped.position, kf.position);
+            assert_eq!(flipped.keyframe_type, kf.keyframe_type);
+            let (a, b) = (flipped.handles.to_array(), kf.handles.to_array());
+            assert!(a.iter().zip(b).all(|(a, b)| (a - b).abs() < 1e-6));
+        }
+    }
+
+    #[test]
+    fn reverse_keeps_the_span() {
+        let mut track = Track::<f32>::new();
+        let first = track.add_keyframe(Keyframe::new(1.0, 0.0));
+        let last = track.add_keyframe(Keyframe::new(2.0, 4.0));
+        let outside = track.add_keyframe(Keyframe::new(5.0, 1.0));
+
+        assert_eq!(track.reverse(&[first, last]), 2);
+        assert_eq!(
+            track.get_keyframe(first).unwrap().position,
+            TimeTick::new(2.0)
+        );
+        assert_eq!(
+            track.get_keyframe(last).unwrap().position,
+            TimeTick::new(1.0)
+        );
+        assert_eq!(
+            track.get_keyframe(outside).unwrap().position,
+            TimeTick::new(5.0)
+        );
+        assert_eq!(track.evaluate(1.0), Some(4.0));
+    }
+
     #[test]
     fn track_sorted() {
         let mut track = Track::<f32>::new();
@@ -277,4 +1083,225 @@ mod tests {
         assert_eq!(min, 10.0);
         assert_eq!(max, 50.0);
     }
+
+    #[test]
+    fn track_extrapolation() {
+        let mut track =
+            Track::<f32>::new().with_extrapolation(Extrapolation::Constant, Extrapolation::Cycle);
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(crate::KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(2.0, 20.0));
+
+        assert_eq!(track.interpolate(-1.0).unwrap().lerp(), 0.0);
+        assert!((track.interpolate(3.0).unwrap().lerp() - 10.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn track_evaluate() {
+        let mut track = Track::<f32>::new();
+        assert!(track.evaluate(0.0).is_none());
+
+        track.add_keyframe(Keyframe::new(2.0, 20.0).with_type(crate::KeyframeType::Hold));
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(crate::KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(3.0, 30.0).with_connected(false));
+        track.add_keyframe(Keyframe::new(4.0, 40.0));
+
+        assert!((track.evaluate(1.0).unwrap() - 10.0).abs() < 1e-4);
+        assert_eq!(track.evaluate(2.5), Some(20.0));
+        assert_eq!(track.evaluate(3.5), Some(30.0));
+        assert_eq!(track.evaluate(-1.0), Some(0.0));
+        assert_eq!(track.evaluate(5.0), Some(40.0));
+    }
+
+    #[test]
+    fn reports_and_shrinks_memory() {
+        let mut track = Track::<f32>::new();
+        for frame in 0..100 {
+            track.add_keyframe(Keyframe::new(frame as f64, 0.0));
+        }
+        let loaded = track.memory_usage();
+        assert_eq!(loaded.keyframes, 100);
+        assert!(loaded.used_bytes >= 100 * size_of::<Keyframe<f32>>());
+
+        let ids: Vec<KeyframeId> = track.keyframe_ids().skip(2).collect();
+        for id in ids {
+            track.remove_keyframe(id);
+        }
+        track.shrink_to_fit();
+        let shrunk = track.memory_usage();
+        assert_eq!(shrunk.keyframes, 2);
+        assert!(shrunk.allocated_bytes < loaded.allocated_bytes / 10);
+        assert_eq!((loaded + shrunk).keyframes, 102);
+    }
+
+    #[test]
+    fn bool_and_enum_tracks_step() {
+        let mut visible = Track::<bool>::new();
+        visible.add_keyframe(Keyframe::new(0.0, false));
+        visible.add_keyframe(Keyframe::new(1.0, true));
+        assert_eq!(visible.evaluate(0.99), Some(false));
+        assert_eq!(visible.evaluate(1.0), Some(true));
+
+        let mut state = Track::<u32>::new();
+        state.add_keyframe(Keyframe::new(0.0, 2));
+        state.add_keyframe(Keyframe::new(2.0, 5));
+        assert_eq!(state.evaluate(1.5), Some(2));
+        assert_eq!(state.evaluate(3.0), Some(5));
+    }
+
+    #[test]
+    fn insert_preserving_keeps_curve() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_handles(BezierHandles::ease_in_out()));
+        track.add_keyframe(Keyframe::new(2.0, 10.0).with_type(KeyframeType::Auto));
+        track.add_keyframe(Keyframe::new(3.0, 30.0).with_handles(BezierHandles::ease_out()));
+        let samples: Vec<f32> = (0..=30)
+            .map(|i| track.evaluate(i as f64 / 10.0).unwrap())
+            .collect();
+
+        assert!(track.insert_keyframe_preserving(0.0).is_none());
+        assert!(track.insert_keyframe_preserving(3.5).is_none());
+        let id = track.insert_keyframe_preserving(0.7).unwrap();
+        track.insert_keyframe_preserving(2.4).unwrap();
+
+        assert_eq!(track.len(), 5);
+        assert_eq!(track.get_keyframe(id).unwrap().position, TimeTick::new(0.7));
+        for (i, expected) in samples.into_iter().enumerate() {
+            let value = track.evaluate(i as f64 / 10.0).unwrap();
+            assert!(
+                (value - expected).abs() < 1e-3,
+                "{i}: {value} != {expected}"
+            );
+        }
+    }
+
+    #[test]
+    fn insert_on_curve_keys_the_current_value() {
+        let mut track =
+            Track::<f32>::new().with_extrapolation(Extrapolation::Constant, Extrapolation::Linear);
+        track.add_keyframe(Keyframe::new(1.0, 0.0).with_type(KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(2.0, 10.0).with_type(KeyframeType::Linear));
+
+        assert!(track.insert_keyframe_on_curve(1.0).is_none());
+        let inside = track.insert_keyframe_on_curve(1.5).unwrap();
+        let before = track.insert_keyframe_on_curve(0.0).unwrap();
+        let after = track.insert_keyframe_on_curve(3.0).unwrap();
+
+        assert_eq!(track.len(), 5);
+        assert_eq!(track.get_keyframe(inside).unwrap().value, 5.0);
+        assert_eq!(track.get_keyframe(before).unwrap().value, 0.0);
+        let after = track.get_keyframe(after).unwrap();
+        assert_eq!(after.value, 20.0);
+        assert_eq!(after.keyframe_type, KeyframeType::Linear);
+        assert!(Track::<f32>::new().insert_keyframe_on_curve(0.0).is_none());
+    }
+
+    #[test]
+    fn set_value_at_updates_or_adds() {
+        let mut track = Track::<f32>::new();
+        let key = track.add_keyframe(Keyframe::new(1.0, 0.0));
+
+        assert_eq!(track.set_value_at(1.0, 5.0), key);
+        // Retimed times can be off by rounding.
+        assert_eq!(track.set_value_at(1.0 + 1e-12, 5.0), key);
+        let added = track.set_value_at(2.0, 7.0);
+        assert_ne!(added, key);
+        assert_eq!(track.len(), 2);
+        assert_eq!(track.evaluate(1.0), Some(5.0));
+        assert_eq!(track.evaluate(2.0), Some(7.0));
+    }
+
+    #[test]
+    fn remove_preserving_refits_neighbors() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_handles(BezierHandles::ease_in_out()));
+        track.add_keyframe(Keyframe::new(2.0, 10.0).with_handles(BezierHandles::ease_out()));
+        let samples: Vec<f32> = (0..=20)
+            .map(|i| track.evaluate(i as f64 / 10.0).unwrap())
+            .collect();
+        let max_error = |track: &Track<f32>| {
+            samples
+                .iter()
+                .enumerate()
+                .map(|(i, expected)| (track.evaluate(i as f64 / 10.0).unwrap() - expected).abs())
+                .fold(0.0, f32::max)
+        };
+
+        // Removing a keyframe inserted on the curve restores it.
+        let id = track.insert_keyframe_preserving(0.6).unwrap();
+        let mut plain = track.clone();
+        plain.remove_keyframe(id);
+        assert!(track.remove_keyframe_preserving(id).is_some());
+        assert_eq!(track.len(), 2);
+        assert!(max_error(&track) < 0.01, "{}", max_error(&track));
+        assert!(max_error(&track) < max_error(&plain));
+    }
+
+    #[test]
+    fn simplify_baked_keys() {
+        // A sine baked at 30 fps with linear keys, then a hold step.
+        let mut track = Track::<f32>::new();
+        for frame in 0..=60 {
+            let time = frame as f64 / 30.0;
+            track.add_keyframe(
+                Keyframe::new(time, (time * std::f64::consts::PI).sin() as f32).with_type(
+                    if frame < 60 {
+                        KeyframeType::Linear
+                    } else {
+                        KeyframeType::Hold
+                    },
+                ),
+            );
+        }
+        track.add_keyframe(Keyframe::new(2.5, 1.0));
+        let before = track.clone();
+
+        let removed = track.simplify(0.01);
+        assert_eq!(track.len() + removed, 62);
+        assert!(track.len() <= 8, "{} keyframes left", track.len());
+        // The hold step is kept.
+        assert!(track.keyframe_at_position(2.0, 1e-6).is_some());
+        assert!(track.keyframe_at_position(2.5, 1e-6).is_some());
+        for step in 0..=250 {
+            let time = step as f64 / 100.0;
+            let error = (track.evaluate(time).unwrap() - before.evaluate(time).unwrap()).abs();
+            assert!(error < 0.015, "error {error} at {time}");
+        }
+    }
+
+    #[test]
+    fn bake_samples_every_frame() {
+        let mut track = Track::<f32>::new().with_retime(1.0, 1.0);
+        track.add_keyframe(Keyframe::new(0.0, 0.0));
+        track.add_keyframe(Keyframe::new(1.0, 10.0));
+        let before = track.clone();
+
+        let range = (TimeTick::new(1.0), TimeTick::new(2.0));
+        let samples = track.bake(4.0, range);
+        assert_eq!(samples.len(), 5);
+        assert_eq!(samples[2].0, TimeTick::new(1.5));
+        assert_eq!(samples[2].1, before.evaluate(1.5).unwrap());
+
+        assert_eq!(track.bake_in_place(4.0, range), 5);
+        assert!(
+            track
+                .iter()
+                .all(|kf| kf.keyframe_type == KeyframeType::Linear)
+        );
+        for (time, value) in samples {
+            assert_eq!(track.evaluate(time), Some(value));
+        }
+        assert!(track.bake(0.0, range).is_empty());
+    }
+
+    #[test]
+    fn track_retime() {
+        let mut track = Track::<f32>::new().with_retime(10.0, 2.0);
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(crate::KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(2.0, 20.0));
+
+        assert_eq!(track.to_global_time(2.0), TimeTick::new(11.0));
+        assert_eq!(track.to_local_time(11.0), TimeTick::new(2.0));
+        assert_eq!(track.evaluate(9.0), Some(0.0));
+        assert!((track.evaluate(10.5).unwrap() - 10.0).abs() < 1e-4);
+    }
 }
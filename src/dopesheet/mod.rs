@@ -9,13 +9,25 @@ mod selection;
 mod track_area;
 
 use crate::HashSet;
+use crate::core::clip::ClipInstance;
 use crate::core::keyframe::KeyframeId;
-use crate::traits::{AnimationDataProvider, PropertyRow};
+use crate::core::marker::Markers;
+use crate::core::track::{TrackId, retime_to_local};
+use crate::key_bindings::KeyBindings;
+use crate::snap::SnapConfig;
+use crate::traits::{AnimationCommand, AnimationDataMutator, AnimationDataProvider, PropertyRow};
+use crate::transport::subtree_track_ids;
+use crate::widgets::bounding_box::AnchorMode;
+use crate::widgets::clip_lane::{ClipLane, ClipLaneConfig, ClipLaneResponse};
+use crate::widgets::interaction::InteractionSizes;
+use crate::widgets::keyframe_dot::{KeyframeDotTextures, KeyframeVisual};
+use crate::widgets::playhead::PlayheadStyle;
+use crate::widgets::time_ruler::ScrubCallback;
 use crate::{SpaceTransform, TimeTick};
-use egui::{Color32, Rect, Response, Sense, Ui, Vec2};
+use egui::{Color32, Pos2, Rect, Response, Sense, Ui, Vec2};
 
-pub use property_tree::PropertyTree;
-pub use selection::SelectionState;
+pub use property_tree::{GroupTransform, PropertyTree, ROW_COLOR_PALETTE};
+pub use selection::{SelectionMode, SelectionState};
 pub use track_area::TrackArea;
 
 /// Configuration for the DopeSheet.
@@ -35,10 +47,29 @@ pub struct DopeSheetConfig {
     pub alt_row_color: Color32,
     /// Color for row separator lines.
     pub separator_color: Color32,
-    /// Color for the playhead.
-    pub playhead_color: Color32,
+    /// Appearance and grab zone of the playhead.
+    pub playhead: PlayheadStyle,
     /// Whether to show aggregate keyframes for parent rows.
     pub show_aggregates: bool,
+    /// Whether to show the "+" row and "Add track…" action in the tree.
+    pub show_add_track: bool,
+    /// Whether to draw indent guide lines in the tree.
+    pub show_indent_guides: bool,
+    /// Whether to show a lock toggle on every row of the tree.
+    pub show_lock_toggles: bool,
+    /// Whether to show mute and solo toggles on the track rows of the tree.
+    pub show_mute_solo: bool,
+    /// Snapping of the playhead and dragged rows; `None` disables it.
+    pub snap: Option<SnapConfig>,
+    /// Images drawn instead of the keyframe diamonds.
+    pub keyframe_textures: Option<KeyframeDotTextures>,
+    /// Pointer hit sizes, resolved for UI zoom and touch input when shown.
+    pub interaction: InteractionSizes,
+    /// Shortcuts, and selection and retime modifiers.
+    pub key_bindings: KeyBindings,
+    /// Anchor the "Flip time" and "Flip value" entries of the track area's
+    /// context menu mirror the selected keyframes around.
+    pub anchor_mode: AnchorMode,
 }
 
 impl Default for DopeSheetConfig {
@@ -51,8 +82,21 @@ impl Default for DopeSheetConfig {
             track_background: Color32::from_gray(25),
             alt_row_color: Color32::from_gray(30),
             separator_color: Color32::from_gray(45),
-            playhead_color: Color32::from_rgb(255, 100, 100),
+            playhead: PlayheadStyle::default(),
             show_aggregates: true,
+            show_add_track: false,
+            show_indent_guides: true,
+            show_lock_toggles: true,
+            show_mute_solo: true,
+            snap: None,
+            keyframe_textures: None,
+            // Rows are denser than the curve editor.
+            interaction: InteractionSizes {
+                keyframe_hit: 10.0,
+                ..Default::default()
+            },
+            key_bindings: KeyBindings::default(),
+            anchor_mode: AnchorMode::default(),
         }
     }
 }
@@ -64,18 +108,102 @@ pub struct DopeSheetResponse {
     pub response: Option<Response>,
     /// Row that was clicked.
     pub clicked_row: Option<String>,
+    /// Rows picked in the tree by a click or the arrow keys, in tree order.
+    pub row_selection: Vec<String>,
+    /// How to combine `row_selection` with the current selection (see
+    /// [`SelectionState::apply_row_selection`]). `Some` when the row
+    /// selection changed this frame.
+    pub row_selection_mode: Option<SelectionMode>,
     /// Keyframe that was clicked.
     pub clicked_keyframe: Option<KeyframeId>,
-    /// Keyframes that were box-selected.
+    /// Edits on the clip lane, if shown with [`DopeSheet::clips`].
+    pub clips: Option<ClipLaneResponse>,
+    /// Keyframes that were box-selected, in row order and then time order.
     pub box_selected: Vec<KeyframeId>,
+    /// How to combine `box_selected` with the current selection (see
+    /// [`SelectionState::apply_box_selection`]). `Some` when a box selection
+    /// finished this frame, even if it enclosed nothing.
+    pub box_select_mode: Option<SelectionMode>,
     /// Row expansion toggle request.
     pub toggle_collapse: Option<String>,
     /// Time position clicked (for scrubbing or adding keyframes).
     pub clicked_time: Option<TimeTick>,
     /// Row that was double-clicked.
     pub double_clicked_row: Option<String>,
-    /// Animation commands to execute (from user interactions).
-    pub commands: Vec<crate::traits::AnimationCommand>,
+    /// Time the playhead was scrubbed to this frame.
+    pub scrubbed_time: Option<TimeTick>,
+    /// Transform applied to a group row's descendant keyframes.
+    ///
+    /// The corresponding commands are already in `commands`.
+    pub group_transform: Option<GroupTransform>,
+    /// Keyframes to select, replacing the selection, from "Select all keys"
+    /// in a row's context menu or "Select tagged" in the track area's.
+    pub select_keyframes: Option<Vec<KeyframeId>>,
+    /// Row and time a drag-and-drop payload is hovering over.
+    pub drop_hover: Option<DropTarget>,
+    /// Row and time a drag-and-drop payload was released over.
+    pub dropped: Option<DropTarget>,
+    /// Position and state of every visible keyframe dot of a track row.
+    pub keyframes: Vec<KeyframeVisual>,
+    /// Animation commands to execute (from user interactions), in the order
+    /// they must run: property tree edits (add track, context menu,
+    /// rename), the group transform (scale before offset), track area edits
+    /// and finally the scrubbed playhead time.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// What lies under a screen position in a [`DopeSheet`].
+///
+/// Returned by [`DopeSheet::hit_test`] so hosts can build custom gestures,
+/// e.g. dropping assets onto a row.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DopeSheetHit {
+    /// A row, either in the property tree (`time` is `None`) or on empty
+    /// space in the track area.
+    Row {
+        row_id: String,
+        time: Option<TimeTick>,
+    },
+    /// A keyframe dot on a track row.
+    Keyframe {
+        row_id: String,
+        keyframe_id: KeyframeId,
+        time: TimeTick,
+    },
+    /// An aggregate keyframe dot on a parent row.
+    Aggregate {
+        row_id: String,
+        keyframe_ids: Vec<KeyframeId>,
+        time: TimeTick,
+    },
+    /// The playhead.
+    Playhead { time: TimeTick },
+    /// Track area time below the last row.
+    RulerTime(TimeTick),
+}
+
+/// Where an egui drag-and-drop payload hovers or was dropped on a
+/// [`DopeSheet`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropTarget {
+    /// Row under the pointer.
+    pub row_id: String,
+    /// Track of that row (`None` for group rows).
+    pub track_id: Option<TrackId>,
+    /// Time under the pointer (`None` over the property tree).
+    pub time: Option<TimeTick>,
+}
+
+impl DropTarget {
+    /// Take the dropped payload from egui if it has type `T`.
+    ///
+    /// Call this for [`DopeSheetResponse::dropped`].
+    pub fn take_payload<T: Send + Sync + 'static>(
+        &self,
+        ctx: &egui::Context,
+    ) -> Option<std::sync::Arc<T>> {
+        egui::DragAndDrop::take_payload::<T>(ctx)
+    }
 }
 
 /// The main DopeSheet widget.
@@ -99,6 +227,12 @@ pub struct DopeSheet<'a, P: AnimationDataProvider> {
     selected_keyframes: &'a HashSet<KeyframeId>,
     selected_rows: &'a HashSet<String>,
     config: DopeSheetConfig,
+    markers: Option<&'a Markers>,
+    clips: Option<&'a [ClipInstance]>,
+    work_range: Option<(TimeTick, TimeTick)>,
+    id_source: Option<egui::Id>,
+    collapse_store: Option<egui::Context>,
+    on_scrub: Option<ScrubCallback<'a>>,
 }
 
 impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
@@ -115,6 +249,12 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
             selected_keyframes,
             selected_rows,
             config: DopeSheetConfig::default(),
+            markers: None,
+            clips: None,
+            work_range: None,
+            id_source: None,
+            collapse_store: None,
+            on_scrub: None,
         }
     }
 
@@ -142,12 +282,74 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
         self
     }
 
+    /// Show markers as vertical lines and use them as snap targets.
+    pub fn markers(mut self, markers: &'a Markers) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    /// Show a [`ClipLane`] of clip instances above the rows.
+    ///
+    /// Its edits are reported in [`DopeSheetResponse::clips`].
+    pub fn clips(mut self, instances: &'a [ClipInstance]) -> Self {
+        self.clips = Some(instances);
+        self
+    }
+
+    /// Shade times outside the `(start, end)` work range.
+    pub fn work_range(mut self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        self.work_range = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Set a custom ID source, to tell several DopeSheets apart.
+    pub fn id_source(mut self, id: impl std::hash::Hash) -> Self {
+        self.id_source = Some(egui::Id::new(id));
+        self
+    }
+
+    /// Keep the collapse state of rows in egui memory.
+    ///
+    /// The widget then toggles rows itself and overrides
+    /// [`PropertyRow::is_collapsed`], which only seeds the initial state. The
+    /// state is persisted with egui memory, keyed by row ID.
+    /// [`DopeSheetResponse::toggle_collapse`] is still reported.
+    pub fn remember_collapse(mut self, ctx: &egui::Context) -> Self {
+        self.collapse_store = Some(ctx.clone());
+        self
+    }
+
+    /// Set a callback invoked with `(old_time, new_time)` whenever the
+    /// playhead is scrubbed, e.g. for audio scrubbing.
+    pub fn on_scrub(mut self, callback: impl FnMut(TimeTick, TimeTick) + 'a) -> Self {
+        self.on_scrub = Some(Box::new(callback));
+        self
+    }
+
+    /// Rasterize the DopeSheet into an image of `size` points at
+    /// `pixels_per_point`, e.g. for timeline previews or clip thumbnails.
+    ///
+    /// The track area fills `size` right of the property tree and starts at
+    /// the scroll offset of the DopeSheet's [`SpaceTransform`] with its zoom.
+    pub fn into_image(self, size: Vec2, pixels_per_point: f32) -> egui::ColorImage {
+        let mut space = *self.space;
+        space.left_padding = self.config.tree_width;
+        space.visible_width = (size.x - self.config.tree_width).max(0.0);
+        let sheet = DopeSheet {
+            space: &space,
+            ..self
+        };
+        crate::widgets::offscreen::render_image(size, pixels_per_point, |ui| {
+            sheet.show(ui);
+        })
+    }
+
     /// Show the DopeSheet widget.
-    pub fn show(self, ui: &mut Ui) -> DopeSheetResponse {
+    pub fn show(mut self, ui: &mut Ui) -> DopeSheetResponse {
         let mut result = DopeSheetResponse::default();
 
         let available = ui.available_size();
-        let rows = self.provider.property_rows();
+        let rows = self.property_rows();
 
         // Filter visible rows (collapsed parents hide children)
         let visible_rows = self.filter_visible_rows(&rows);
@@ -165,18 +367,19 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
             return result;
         }
 
-        // Split into tree and track areas
-        let tree_rect = Rect::from_min_size(
-            total_rect.min,
-            Vec2::new(self.config.tree_width, total_rect.height()),
-        );
-        let track_rect = Rect::from_min_size(
-            tree_rect.right_top(),
-            Vec2::new(
-                total_rect.width() - self.config.tree_width,
-                total_rect.height(),
-            ),
-        );
+        let (clip_rect, total_rect) = self.split_clip_lane(total_rect);
+        if let Some(clip_rect) = clip_rect {
+            result.clips = self.show_clip_lane(ui, clip_rect);
+        }
+
+        let (tree_rect, track_rect) = self.split_rect(total_rect);
+
+        // Drag-and-drop target: any egui payload dragged over a row.
+        let drop_target = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|_| egui::DragAndDrop::has_any_payload(ui.ctx()))
+            .and_then(|pos| self.drop_target(&rows, total_rect, pos));
 
         // Render property tree
         let tree_response = PropertyTree::new(&visible_rows, self.selected_rows)
@@ -186,33 +389,54 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
                 self.config.row_height,
                 self.config.indent_per_level,
             )
+            .show_add_track(self.config.show_add_track)
+            .show_indent_guides(self.config.show_indent_guides)
+            .show_lock_toggles(self.config.show_lock_toggles)
+            .show_mute_solo(self.config.show_mute_solo)
+            .key_bindings(self.config.key_bindings)
             .show(ui, tree_rect);
 
         if let Some(row_id) = tree_response.clicked_row {
             result.clicked_row = Some(row_id);
         }
+        result.row_selection = tree_response.row_selection;
+        result.row_selection_mode = tree_response.row_selection_mode;
         if let Some(row_id) = tree_response.toggle_collapse {
+            self.store_collapse_toggle(&rows, &row_id);
             result.toggle_collapse = Some(row_id);
         }
         if let Some(row_id) = tree_response.double_clicked_row {
             result.double_clicked_row = Some(row_id);
         }
+        if let Some((parent_row, label)) = tree_response.add_track {
+            result
+                .commands
+                .push(AnimationCommand::AddTrack { parent_row, label });
+        }
+        if let Some(row_id) = tree_response.select_keys {
+            let row_ids = HashSet::from_iter([row_id]);
+            result.select_keyframes = Some(
+                subtree_track_ids(&rows, &row_ids)
+                    .into_iter()
+                    .filter_map(|track_id| self.provider.keyframe_positions(track_id))
+                    .flatten()
+                    .map(|(id, _)| id)
+                    .collect(),
+            );
+        }
+        result.commands.extend(tree_response.commands);
+        if let Some(transform) = tree_response.group_transform {
+            result
+                .commands
+                .extend(self.group_transform_commands(&rows, &transform));
+            result.group_transform = Some(transform);
+        }
 
         // Render track area
-        let track_response = TrackArea::new(
-            self.provider,
-            &visible_rows,
-            self.space,
-            self.selected_keyframes,
-        )
-        .config(
-            self.config.track_background,
-            self.config.alt_row_color,
-            self.config.row_height,
-            self.config.playhead_color,
-            self.config.show_aggregates,
-        )
-        .show(ui, track_rect);
+        let track_response = self
+            .track_area(&visible_rows)
+            .on_scrub(self.on_scrub.take())
+            .show(ui, track_rect);
 
         if let Some(kf_id) = track_response.clicked_keyframe {
             result.clicked_keyframe = Some(kf_id);
@@ -221,6 +445,43 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
             result.clicked_time = Some(time);
         }
         result.box_selected = track_response.box_selected;
+        result.box_select_mode = track_response.box_select_mode;
+        result.keyframes = track_response.keyframes;
+        if track_response.select_keyframes.is_some() {
+            result.select_keyframes = track_response.select_keyframes;
+        }
+        result.commands.extend(track_response.commands);
+        if let Some(time) = track_response.scrubbed_time {
+            result.scrubbed_time = Some(time);
+            result.commands.push(AnimationCommand::SetCurrentTime(time));
+        }
+
+        // Key the selected rows, or the row under the pointer, at the
+        // playhead.
+        if let Some(pos) = ui
+            .ctx()
+            .pointer_hover_pos()
+            .filter(|pos| total_rect.contains(*pos))
+            && ui
+                .input(|i| KeyBindings::pressed(i, self.config.key_bindings.insert_key_at_playhead))
+        {
+            let row_ids = if self.selected_rows.is_empty() {
+                self.hit_test_rows(total_rect, pos)
+                    .and_then(|hit| match hit {
+                        DopeSheetHit::Row { row_id, .. }
+                        | DopeSheetHit::Keyframe { row_id, .. }
+                        | DopeSheetHit::Aggregate { row_id, .. } => Some(row_id),
+                        DopeSheetHit::Playhead { .. } | DopeSheetHit::RulerTime(_) => None,
+                    })
+                    .into_iter()
+                    .collect()
+            } else {
+                self.selected_rows.clone()
+            };
+            result
+                .commands
+                .extend(self.insert_at_playhead_commands(&rows, &row_ids));
+        }
 
         // Draw separator between tree and tracks
         let painter = ui.painter_at(total_rect);
@@ -229,9 +490,314 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
             egui::Stroke::new(1.0, self.config.separator_color),
         );
 
+        if let Some(target) = drop_target {
+            self.paint_drop_target(&painter, &visible_rows, total_rect, &target);
+            if ui.input(|i| i.pointer.any_released()) {
+                result.dropped = Some(target.clone());
+            }
+            result.drop_hover = Some(target);
+        }
+
         result
     }
 
+    /// Hit-test a screen position against the layout the DopeSheet renders
+    /// into `rect` (the rect of [`DopeSheetResponse::response`]).
+    ///
+    /// The clip lane is not hit-tested; use [`ClipLaneResponse`] instead.
+    pub fn hit_test(&self, rect: Rect, pos: Pos2) -> Option<DopeSheetHit> {
+        let (_, rect) = self.split_clip_lane(rect);
+        self.hit_test_rows(rect, pos)
+    }
+
+    /// [`Self::hit_test`] below the clip lane.
+    fn hit_test_rows(&self, rect: Rect, pos: Pos2) -> Option<DopeSheetHit> {
+        if !rect.contains(pos) {
+            return None;
+        }
+
+        let rows = self.property_rows();
+        let visible_rows = self.filter_visible_rows(&rows);
+        let (tree_rect, track_rect) = self.split_rect(rect);
+
+        if tree_rect.contains(pos) {
+            let index = ((pos.y - tree_rect.top()) / self.config.row_height).floor() as usize;
+            return visible_rows.get(index).map(|row| DopeSheetHit::Row {
+                row_id: row.id.clone(),
+                time: None,
+            });
+        }
+
+        self.track_area(&visible_rows).hit_test(track_rect, pos)
+    }
+
+    /// Drop target under `pos`.
+    fn drop_target(&self, rows: &[PropertyRow], rect: Rect, pos: Pos2) -> Option<DropTarget> {
+        let (row_id, time) = match self.hit_test_rows(rect, pos)? {
+            DopeSheetHit::Row { row_id, time } => (row_id, time),
+            DopeSheetHit::Keyframe { row_id, time, .. }
+            | DopeSheetHit::Aggregate { row_id, time, .. } => (row_id, Some(time)),
+            DopeSheetHit::Playhead { .. } | DopeSheetHit::RulerTime(_) => {
+                // Resolve the row directly; the playhead spans all rows.
+                let visible_rows = self.filter_visible_rows(rows);
+                let index = ((pos.y - rect.top()) / self.config.row_height).floor() as usize;
+                let row = visible_rows.get(index)?;
+                (row.id.clone(), Some(self.space.clipped_to_unit(pos.x)))
+            }
+        };
+        let track_id = rows.iter().find(|row| row.id == row_id)?.track_id;
+        Some(DropTarget {
+            row_id,
+            track_id,
+            time,
+        })
+    }
+
+    /// Highlight the drop target row and time.
+    fn paint_drop_target(
+        &self,
+        painter: &egui::Painter,
+        visible_rows: &[PropertyRow],
+        rect: Rect,
+        target: &DropTarget,
+    ) {
+        let Some(index) = visible_rows.iter().position(|row| row.id == target.row_id) else {
+            return;
+        };
+        let color = painter.ctx().style().visuals.selection.stroke.color;
+        let row_rect = Rect::from_min_size(
+            Pos2::new(
+                rect.left(),
+                rect.top() + index as f32 * self.config.row_height,
+            ),
+            Vec2::new(rect.width(), self.config.row_height),
+        );
+        painter.rect_stroke(
+            row_rect,
+            0.0,
+            egui::Stroke::new(1.5, color),
+            egui::StrokeKind::Inside,
+        );
+        if let Some(time) = target.time {
+            let x = self.space.unit_to_clipped(time);
+            painter.line_segment(
+                [
+                    Pos2::new(x, row_rect.top()),
+                    Pos2::new(x, row_rect.bottom()),
+                ],
+                egui::Stroke::new(2.0, color),
+            );
+        }
+    }
+
+    /// Split the clip lane, if shown, off the top of the widget rect.
+    fn split_clip_lane(&self, rect: Rect) -> (Option<Rect>, Rect) {
+        if self.clips.is_none() {
+            return (None, rect);
+        }
+        let height = self.config.row_height.min(rect.height());
+        let (lane, rows) = rect.split_top_bottom_at_y(rect.top() + height);
+        (Some(lane), rows)
+    }
+
+    /// Show the clip lane with its label in the property tree column.
+    fn show_clip_lane(&self, ui: &mut Ui, rect: Rect) -> Option<ClipLaneResponse> {
+        let instances = self.clips?;
+        let (label_rect, lane_rect) = self.split_rect(rect);
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(label_rect, 0.0, self.config.tree_background);
+        painter.text(
+            Pos2::new(label_rect.left() + 8.0, label_rect.center().y),
+            egui::Align2::LEFT_CENTER,
+            "Clips",
+            egui::FontId::proportional(12.0),
+            ui.visuals().weak_text_color(),
+        );
+        let response = ClipLane::new(instances, self.space)
+            .config(ClipLaneConfig {
+                height: rect.height(),
+                background: self.config.track_background,
+                ..Default::default()
+            })
+            .show(ui, lane_rect);
+        painter.line_segment(
+            [rect.left_bottom(), rect.right_bottom()],
+            egui::Stroke::new(1.0, self.config.separator_color),
+        );
+        Some(response)
+    }
+
+    /// Split the widget rect into the property tree and track area rects.
+    fn split_rect(&self, rect: Rect) -> (Rect, Rect) {
+        let tree_rect =
+            Rect::from_min_size(rect.min, Vec2::new(self.config.tree_width, rect.height()));
+        let track_rect = Rect::from_min_size(
+            tree_rect.right_top(),
+            Vec2::new(rect.width() - self.config.tree_width, rect.height()),
+        );
+        (tree_rect, track_rect)
+    }
+
+    /// Track area for the given visible rows, configured from the DopeSheet.
+    fn track_area<'b>(&self, visible_rows: &'b [PropertyRow]) -> TrackArea<'b, P>
+    where
+        'a: 'b,
+    {
+        TrackArea::new(
+            self.provider,
+            visible_rows,
+            self.space,
+            self.selected_keyframes,
+        )
+        .config(
+            self.config.track_background,
+            self.config.alt_row_color,
+            self.config.row_height,
+            self.config.playhead.color,
+            self.config.show_aggregates,
+        )
+        .playhead(self.config.playhead)
+        .snap(self.config.snap.clone())
+        .keyframe_textures(self.config.keyframe_textures)
+        .interaction(self.config.interaction)
+        .key_bindings(self.config.key_bindings)
+        .anchor_mode(self.config.anchor_mode)
+        .markers(self.markers)
+        .work_range(self.work_range)
+    }
+
+    /// Commands inserting a keyframe at the playhead on every unlocked track
+    /// at or below `row_ids` that has no keyframe there yet.
+    fn insert_at_playhead_commands(
+        &self,
+        rows: &[PropertyRow],
+        row_ids: &HashSet<String>,
+    ) -> Vec<AnimationCommand> {
+        let playhead = self.provider.current_time();
+        subtree_track_ids(rows, row_ids)
+            .into_iter()
+            .filter(|track_id| {
+                !rows
+                    .iter()
+                    .any(|row| row.track_id == Some(*track_id) && row.locked)
+            })
+            .filter_map(|track_id| {
+                let position = retime_to_local(
+                    playhead,
+                    self.provider.track_time_offset(track_id),
+                    self.provider.track_time_scale(track_id),
+                );
+                let keyed = self
+                    .provider
+                    .keyframe_positions(track_id)?
+                    .iter()
+                    .any(|(_, time)| *time == position);
+                (!keyed).then(|| AnimationCommand::InsertKeyframePreserving {
+                    track_id,
+                    keyframe_id: KeyframeId::new(),
+                    position,
+                })
+            })
+            .collect()
+    }
+
+    /// Commands applying a [`GroupTransform`] to all keyframes below the
+    /// group row, as one batch.
+    fn group_transform_commands(
+        &self,
+        rows: &[PropertyRow],
+        transform: &GroupTransform,
+    ) -> Vec<AnimationCommand> {
+        let group: HashSet<String> = [transform.row_id.clone()].into_iter().collect();
+        let keyframes: Vec<(TrackId, KeyframeId, TimeTick)> = subtree_track_ids(rows, &group)
+            .into_iter()
+            .flat_map(|track_id| {
+                self.provider
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |(id, position)| (track_id, id, position))
+            })
+            .collect();
+        let Some(anchor_time) = keyframes
+            .iter()
+            .map(|(.., position)| *position)
+            .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+        else {
+            return Vec::new();
+        };
+        // Pinned and locked keyframes stay in place.
+        let locked_tracks: HashSet<TrackId> = rows
+            .iter()
+            .filter(|row| row.locked)
+            .filter_map(|row| row.track_id)
+            .collect();
+        let keyframe_ids: Vec<KeyframeId> = keyframes
+            .into_iter()
+            .filter(|(track_id, id, _)| {
+                !locked_tracks.contains(track_id)
+                    && !self.provider.keyframe_pinned(*track_id, *id)
+                    && !self.provider.keyframe_locked(*track_id, *id)
+            })
+            .map(|(_, id, _)| id)
+            .collect();
+
+        let mut commands = Vec::new();
+        if transform.time_scale != 1.0 {
+            commands.push(AnimationCommand::ScaleKeyframes {
+                keyframe_ids: keyframe_ids.clone(),
+                anchor_time,
+                anchor_value: 0.0,
+                time_scale: transform.time_scale,
+                value_scale: 1.0,
+            });
+        }
+        if transform.delta_time != TimeTick::default() {
+            commands.push(AnimationCommand::OffsetKeyframes {
+                keyframe_ids,
+                delta_time: transform.delta_time,
+                delta_value: 0.0,
+            });
+        }
+        commands
+    }
+
+    /// Property rows from the provider with the remembered collapse state
+    /// and the track groups applied.
+    fn property_rows(&self) -> Vec<PropertyRow> {
+        let mut rows = self.provider.property_rows();
+        self.provider.track_groups().apply_to_rows(&mut rows);
+        if let Some(ctx) = &self.collapse_store
+            && let Some(collapsed) =
+                ctx.data_mut(|data| data.get_persisted::<Vec<String>>(self.collapse_id()))
+        {
+            rows.iter_mut()
+                .filter(|row| row.can_collapse)
+                .for_each(|row| row.is_collapsed = collapsed.contains(&row.id));
+        }
+        rows
+    }
+
+    /// Toggle a row in the remembered collapse state.
+    fn store_collapse_toggle(&self, rows: &[PropertyRow], row_id: &str) {
+        let Some(ctx) = &self.collapse_store else {
+            return;
+        };
+        let collapsed: Vec<String> = rows
+            .iter()
+            .filter(|row| row.can_collapse && (row.is_collapsed != (row.id == row_id)))
+            .map(|row| row.id.clone())
+            .collect();
+        ctx.data_mut(|data| data.insert_persisted(self.collapse_id(), collapsed));
+    }
+
+    /// Memory ID of the remembered collapse state.
+    fn collapse_id(&self) -> egui::Id {
+        self.id_source
+            .unwrap_or_else(|| egui::Id::new("dopesheet"))
+            .with("collapsed_rows")
+    }
+
     fn filter_visible_rows(&self, rows: &[PropertyRow]) -> Vec<PropertyRow> {
         let mut visible = Vec::new();
         let mut collapsed_depth: Option<usize> = None;
@@ -257,3 +823,74 @@ impl<'a, P: AnimationDataProvider> DopeSheet<'a, P> {
         visible
     }
 }
+
+impl<P: AnimationDataProvider + AnimationDataMutator> DopeSheet<'_, P> {
+    /// Show a DopeSheet for `provider` that applies its own interactions.
+    ///
+    /// Clicks, box selection and "Select all keys" update `selection`,
+    /// collapse toggles and all emitted commands are executed on `provider`.
+    /// Selection changes apply first, then the collapse toggle, then the
+    /// commands in order. The returned response reports what happened, with
+    /// `commands` already drained.
+    pub fn show_stateful(
+        ui: &mut Ui,
+        space: &SpaceTransform,
+        selection: &mut SelectionState,
+        provider: &mut P,
+    ) -> DopeSheetResponse {
+        Self::show_stateful_with(ui, space, selection, provider, |dope_sheet| dope_sheet)
+    }
+
+    /// Like [`Self::show_stateful`], with `configure` applied to the
+    /// DopeSheet, e.g. to set its config, markers or ID.
+    pub fn show_stateful_with(
+        ui: &mut Ui,
+        space: &SpaceTransform,
+        selection: &mut SelectionState,
+        provider: &mut P,
+        configure: impl for<'d> FnOnce(DopeSheet<'d, P>) -> DopeSheet<'d, P>,
+    ) -> DopeSheetResponse {
+        let selected_keyframes = selection.keyframes.clone();
+        let selected_rows = selection.rows.clone();
+        let dope_sheet = configure(DopeSheet::new(
+            provider,
+            space,
+            &selected_keyframes,
+            &selected_rows,
+        ));
+        // With remembered collapse state the widget toggles rows itself.
+        let remembers_collapse = dope_sheet.collapse_store.is_some();
+        let key_bindings = dope_sheet.config.key_bindings;
+        let mut response = dope_sheet.show(ui);
+
+        let mode = ui.input(|i| key_bindings.selection_mode(&i.modifiers));
+        if let Some(mode) = response.row_selection_mode {
+            selection.apply_row_selection(response.row_selection.iter().cloned(), mode);
+        }
+        if let Some(id) = response.clicked_keyframe {
+            match mode {
+                SelectionMode::Toggle => selection.toggle_keyframe(id),
+                mode => selection.select_keyframe(id, mode == SelectionMode::Add),
+            }
+        } else if response.clicked_time.is_some() && mode == SelectionMode::Replace {
+            selection.clear_keyframes();
+        }
+        if let Some(mode) = response.box_select_mode {
+            selection.apply_box_selection(response.box_selected.iter().copied(), mode);
+        }
+        if let Some(ids) = &response.select_keyframes {
+            selection.select_keyframes(ids.iter().copied(), false);
+        }
+
+        if let Some(row_id) = &response.toggle_collapse
+            && !remembers_collapse
+        {
+            provider.execute(AnimationCommand::ToggleRowCollapse(row_id.clone()));
+        }
+        for command in response.commands.drain(..) {
+            provider.execute(command);
+        }
+
+        response
+    }
+}
@@ -1,20 +1,92 @@
 //! Property tree panel for the DopeSheet.
 
 use crate::HashSet;
-use crate::traits::PropertyRow;
-use egui::{Color32, CursorIcon, Pos2, Rect, Sense, Ui, Vec2};
+use crate::TimeTick;
+use crate::core::history::row_location;
+use crate::dopesheet::SelectionMode;
+use crate::key_bindings::KeyBindings;
+use crate::traits::{AnimationCommand, PropertyRow, RowTarget};
+use crate::widgets::keyframe_dot::paint_padlock;
+use egui::{Color32, CursorIcon, Key, Modifiers, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+
+/// A transform applied to all descendant keyframes of a group row.
+///
+/// Keyframes are scaled in time around the group's earliest keyframe, then
+/// offset by `delta_time`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupTransform {
+    /// ID of the group row.
+    pub row_id: String,
+    /// Time offset.
+    pub delta_time: TimeTick,
+    /// Time scale factor.
+    pub time_scale: f64,
+}
+
+impl GroupTransform {
+    /// Create an identity transform for a group row.
+    pub fn new(row_id: impl Into<String>) -> Self {
+        Self {
+            row_id: row_id.into(),
+            delta_time: TimeTick::default(),
+            time_scale: 1.0,
+        }
+    }
+
+    /// Whether this transform leaves keyframes unchanged.
+    pub fn is_identity(&self) -> bool {
+        self.delta_time == TimeTick::default() && self.time_scale == 1.0
+    }
+}
 
 /// Response from the property tree.
 #[derive(Default)]
 pub struct PropertyTreeResponse {
     /// Row that was clicked.
     pub clicked_row: Option<String>,
+    /// Rows picked by a click or the arrow keys, in tree order.
+    pub row_selection: Vec<String>,
+    /// How to combine `row_selection` with the current selection. `Some`
+    /// when the selection changed this frame.
+    pub row_selection_mode: Option<SelectionMode>,
     /// Row expansion toggle request.
     pub toggle_collapse: Option<String>,
     /// Row that was double-clicked.
     pub double_clicked_row: Option<String>,
+    /// Transform confirmed in the "Shift children…" dialog of a group row.
+    pub group_transform: Option<GroupTransform>,
+    /// Track to create as `(parent_row, label)`, confirmed in the "Add
+    /// track" dialog.
+    pub add_track: Option<(Option<String>, String)>,
+    /// Row whose keyframes (including those of descendant rows) the user
+    /// asked to select via "Select all keys".
+    pub select_keys: Option<String>,
+    /// Commands from the row context menu and inline rename.
+    pub commands: Vec<AnimationCommand>,
 }
 
+/// Draft of the "Add track" dialog: `(parent_row, label)`.
+type AddTrackDraft = (Option<String>, String);
+
+/// Keyboard cursor of the tree: `(cursor_row, anchor_row)`, the anchor being
+/// where range selections start.
+type RowCursor = (String, String);
+
+/// Default label of a new track.
+const NEW_TRACK_LABEL: &str = "New Track";
+
+/// Colors offered by the "Color" submenu of the row context menu.
+pub const ROW_COLOR_PALETTE: [Color32; 8] = [
+    Color32::from_rgb(230, 90, 90),
+    Color32::from_rgb(235, 150, 70),
+    Color32::from_rgb(230, 210, 80),
+    Color32::from_rgb(110, 200, 100),
+    Color32::from_rgb(80, 200, 200),
+    Color32::from_rgb(90, 140, 230),
+    Color32::from_rgb(170, 110, 230),
+    Color32::from_rgb(230, 110, 190),
+];
+
 /// Property tree panel widget.
 pub struct PropertyTree<'a> {
     rows: &'a [PropertyRow],
@@ -23,6 +95,11 @@ pub struct PropertyTree<'a> {
     alt_row_color: Color32,
     row_height: f32,
     indent_per_level: f32,
+    show_add_track: bool,
+    show_indent_guides: bool,
+    show_lock_toggles: bool,
+    show_mute_solo: bool,
+    key_bindings: KeyBindings,
 }
 
 impl<'a> PropertyTree<'a> {
@@ -35,6 +112,11 @@ impl<'a> PropertyTree<'a> {
             alt_row_color: Color32::from_gray(30),
             row_height: 24.0,
             indent_per_level: 16.0,
+            show_add_track: false,
+            show_indent_guides: true,
+            show_lock_toggles: true,
+            show_mute_solo: true,
+            key_bindings: KeyBindings::default(),
         }
     }
 
@@ -53,15 +135,77 @@ impl<'a> PropertyTree<'a> {
         self
     }
 
+    /// Show a "+" row below the last row and an "Add track…" context action
+    /// on group rows.
+    pub fn show_add_track(mut self, show: bool) -> Self {
+        self.show_add_track = show;
+        self
+    }
+
+    /// Draw a vertical guide line per ancestor depth level. Clicking a guide
+    /// collapses that ancestor.
+    pub fn show_indent_guides(mut self, show: bool) -> Self {
+        self.show_indent_guides = show;
+        self
+    }
+
+    /// Show a padlock left of the color indicator of every row; clicking it
+    /// locks or unlocks the row. Unlocked rows show it while hovered.
+    pub fn show_lock_toggles(mut self, show: bool) -> Self {
+        self.show_lock_toggles = show;
+        self
+    }
+
+    /// Show "M" and "S" toggles left of the padlock of every track row;
+    /// clicking them mutes or solos the track. Inactive toggles show while
+    /// the row is hovered.
+    pub fn show_mute_solo(mut self, show: bool) -> Self {
+        self.show_mute_solo = show;
+        self
+    }
+
+    /// Set the selection modifiers: clicks with
+    /// [`KeyBindings::toggle_selection`] toggle a row, clicks and arrow keys
+    /// with [`KeyBindings::extend_selection`] add the rows from the last
+    /// clicked one.
+    pub fn key_bindings(mut self, key_bindings: KeyBindings) -> Self {
+        self.key_bindings = key_bindings;
+        self
+    }
+
     /// Show the property tree.
+    ///
+    /// After a click the tree has keyboard focus: Up/Down move the
+    /// selection, Left/Right collapse and expand rows or move to the parent
+    /// and first child, and Enter toggles the row's collapse state.
     pub fn show(self, ui: &mut Ui, rect: Rect) -> PropertyTreeResponse {
         let mut result = PropertyTreeResponse::default();
 
         let painter = ui.painter_at(rect);
+        let dialog_id = ui.make_persistent_id("property_tree_group_transform");
+        let add_track_id = ui.make_persistent_id("property_tree_add_track");
+        let rename_id = ui.make_persistent_id("property_tree_rename");
+        let drag_id = ui.make_persistent_id("property_tree_row_drag");
+        let cursor_id = ui.make_persistent_id("property_tree_cursor");
+
+        // Takes keyboard focus when a row is clicked.
+        let focus_response = ui.interact(
+            rect,
+            ui.make_persistent_id("property_tree_focus"),
+            Sense::focusable_noninteractive(),
+        );
+        let cursor = focus_response
+            .has_focus()
+            .then(|| ui.data(|data| data.get_temp::<RowCursor>(cursor_id)))
+            .flatten()
+            .map(|(cursor, _)| cursor);
 
         // Background
         painter.rect_filled(rect, 0.0, self.background);
 
+        // Rows leading to the current row, indexed by depth.
+        let mut ancestors: Vec<&PropertyRow> = Vec::new();
+
         // Render rows
         for (i, row) in self.rows.iter().enumerate() {
             let row_rect = Rect::from_min_size(
@@ -69,6 +213,10 @@ impl<'a> PropertyTree<'a> {
                 Vec2::new(rect.width(), self.row_height),
             );
 
+            ancestors.truncate(row.depth);
+            let row_ancestors = ancestors.clone();
+            ancestors.push(row);
+
             if !ui.is_rect_visible(row_rect) {
                 continue;
             }
@@ -85,7 +233,10 @@ impl<'a> PropertyTree<'a> {
             }
 
             // Allocate interaction area
-            let response = ui.allocate_rect(row_rect, Sense::click());
+            let response = ui.allocate_rect(row_rect, Sense::click_and_drag());
+            if response.drag_started() {
+                ui.data_mut(|data| data.insert_temp(drag_id, row.id.clone()));
+            }
 
             if response.hovered() {
                 ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
@@ -98,14 +249,41 @@ impl<'a> PropertyTree<'a> {
                 }
             }
 
+            if cursor.as_ref() == Some(&row.id) {
+                painter.rect_stroke(
+                    row_rect.shrink(0.5),
+                    0.0,
+                    Stroke::new(1.0, ui.visuals().selection.stroke.color),
+                    egui::StrokeKind::Inside,
+                );
+            }
+
             if response.clicked() {
                 result.clicked_row = Some(row.id.clone());
+                let mode = ui.input(|i| self.key_bindings.selection_mode(&i.modifiers));
+                self.select_to(ui, cursor_id, i, mode, &mut result);
+                focus_response.request_focus();
             }
 
             if response.double_clicked() {
                 result.double_clicked_row = Some(row.id.clone());
             }
 
+            response.context_menu(|ui| {
+                self.row_context_menu(ui, row, &mut result, dialog_id, add_track_id, rename_id);
+            });
+
+            if self.show_indent_guides {
+                self.show_indent_guides_for(
+                    ui,
+                    &painter,
+                    rect,
+                    row_rect,
+                    &row_ancestors,
+                    &mut result,
+                );
+            }
+
             // Content
             let indent = row.depth as f32 * self.indent_per_level;
             let mut x = rect.left() + 4.0 + indent;
@@ -145,6 +323,8 @@ impl<'a> PropertyTree<'a> {
             // Label.
             let label_color = if is_selected {
                 ui.visuals().selection.stroke.color
+            } else if row.muted {
+                Color32::from_gray(120)
             } else if row.track_id.is_some() {
                 // Leaf nodes.
                 Color32::from_gray(200)
@@ -153,24 +333,941 @@ impl<'a> PropertyTree<'a> {
                 Color32::from_gray(180)
             };
 
-            painter.text(
-                Pos2::new(x, y_center),
-                egui::Align2::LEFT_CENTER,
-                &row.label,
-                egui::FontId::proportional(12.0),
-                label_color,
+            // Icons right of the label: link, mute and solo toggles, lock
+            // toggle, color indicator.
+            let toggles_left = if self.show_lock_toggles {
+                row_rect.right() - 30.0
+            } else {
+                row_rect.right() - 16.0
+            };
+            let icons_left = if self.show_mute_solo {
+                toggles_left - 28.0
+            } else {
+                toggles_left
+            };
+            let label_right = if row.driven {
+                icons_left - 14.0
+            } else {
+                icons_left
+            };
+            let label_rect = Rect::from_min_max(
+                Pos2::new(x, row_rect.top()),
+                Pos2::new(label_right, row_rect.bottom()),
             );
-
-            // Color indicator for tracks
-            if let Some(color) = row.color {
-                let indicator_rect = Rect::from_min_size(
-                    Pos2::new(row_rect.right() - 12.0, y_center - 3.0),
-                    Vec2::new(6.0, 6.0),
+            if !self.show_rename_edit(ui, row, label_rect, rename_id, &mut result) {
+                painter.text(
+                    Pos2::new(x, y_center),
+                    egui::Align2::LEFT_CENTER,
+                    &row.label,
+                    egui::FontId::proportional(12.0),
+                    label_color,
                 );
-                painter.rect_filled(indicator_rect, 2.0, color);
             }
+
+            if row.driven {
+                paint_link_icon(&painter, Pos2::new(icons_left - 6.0, y_center), label_color);
+            }
+
+            if self.show_mute_solo
+                && let Some(track_id) = row.track_id
+            {
+                let center = Pos2::new(toggles_left - 21.0, y_center);
+                let hovered = response.hovered();
+                if self.show_audition_toggle(ui, &painter, row, center, "M", row.muted, hovered) {
+                    result.commands.push(AnimationCommand::SetTrackMuted {
+                        track_id,
+                        muted: !row.muted,
+                    });
+                }
+                let center = Pos2::new(toggles_left - 7.0, y_center);
+                if self.show_audition_toggle(ui, &painter, row, center, "S", row.solo, hovered) {
+                    result.commands.push(AnimationCommand::SetTrackSolo {
+                        track_id,
+                        solo: !row.solo,
+                    });
+                }
+            }
+
+            if self.show_lock_toggles {
+                self.show_lock_toggle(ui, &painter, row, row_rect, response.hovered(), &mut result);
+            }
+
+            // Color indicator; click to pick a color.
+            self.show_color_indicator(ui, &painter, row, row_rect, response.hovered(), &mut result);
+        }
+
+        if self.show_add_track {
+            self.show_add_track_row(ui, rect, add_track_id);
+        }
+
+        self.show_row_drag(ui, &painter, rect, drag_id, &mut result);
+
+        if focus_response.has_focus() {
+            self.handle_keys(ui, focus_response.id, cursor_id, &mut result);
         }
 
+        result.group_transform = self.show_group_transform_dialog(ui, dialog_id);
+        result.add_track = self.show_add_track_dialog(ui, add_track_id);
+
         result
     }
+
+    /// Select row `index`, or with [`SelectionMode::Add`] the rows from the
+    /// anchor to it, and move the cursor there.
+    fn select_to(
+        &self,
+        ui: &Ui,
+        cursor_id: egui::Id,
+        index: usize,
+        mode: SelectionMode,
+        result: &mut PropertyTreeResponse,
+    ) {
+        let anchor = ui
+            .data(|data| data.get_temp::<RowCursor>(cursor_id))
+            .and_then(|(_, anchor)| self.rows.iter().position(|row| row.id == anchor))
+            .filter(|_| mode == SelectionMode::Add)
+            .unwrap_or(index);
+        result.row_selection = self.rows[anchor.min(index)..=anchor.max(index)]
+            .iter()
+            .map(|row| row.id.clone())
+            .collect();
+        result.row_selection_mode = Some(mode);
+        let cursor = (self.rows[index].id.clone(), self.rows[anchor].id.clone());
+        ui.data_mut(|data| data.insert_temp(cursor_id, cursor));
+    }
+
+    /// Move the cursor and collapse or expand rows with the arrow keys and
+    /// Enter.
+    fn handle_keys(
+        &self,
+        ui: &Ui,
+        focus_id: egui::Id,
+        cursor_id: egui::Id,
+        result: &mut PropertyTreeResponse,
+    ) {
+        ui.memory_mut(|memory| {
+            memory.set_focus_lock_filter(
+                focus_id,
+                egui::EventFilter {
+                    horizontal_arrows: true,
+                    vertical_arrows: true,
+                    ..Default::default()
+                },
+            );
+        });
+        // Extra Shift is ignored when matching, so this also catches
+        // extending moves.
+        let (up, down, left, right, enter, modifiers) = ui.input_mut(|i| {
+            (
+                i.consume_key(Modifiers::NONE, Key::ArrowUp),
+                i.consume_key(Modifiers::NONE, Key::ArrowDown),
+                i.consume_key(Modifiers::NONE, Key::ArrowLeft),
+                i.consume_key(Modifiers::NONE, Key::ArrowRight),
+                i.consume_key(Modifiers::NONE, Key::Enter),
+                i.modifiers,
+            )
+        });
+        let mode = if KeyBindings::held(&modifiers, self.key_bindings.extend_selection) {
+            SelectionMode::Add
+        } else {
+            SelectionMode::Replace
+        };
+
+        let cursor = ui
+            .data(|data| data.get_temp::<RowCursor>(cursor_id))
+            .and_then(|(cursor, _)| self.rows.iter().position(|row| row.id == cursor));
+        let Some(index) = cursor else {
+            if (up || down) && !self.rows.is_empty() {
+                self.select_to(ui, cursor_id, 0, SelectionMode::Replace, result);
+            }
+            return;
+        };
+        let row = &self.rows[index];
+        let has_children = self
+            .rows
+            .get(index + 1)
+            .is_some_and(|next| next.depth > row.depth);
+
+        if up && index > 0 {
+            self.select_to(ui, cursor_id, index - 1, mode, result);
+        } else if down && index + 1 < self.rows.len() {
+            self.select_to(ui, cursor_id, index + 1, mode, result);
+        } else if left {
+            if row.can_collapse && !row.is_collapsed {
+                result.toggle_collapse = Some(row.id.clone());
+            } else if let Some(parent) = self.rows[..index]
+                .iter()
+                .rposition(|parent| parent.depth < row.depth)
+            {
+                self.select_to(ui, cursor_id, parent, SelectionMode::Replace, result);
+            }
+        } else if right && row.can_collapse {
+            if row.is_collapsed {
+                result.toggle_collapse = Some(row.id.clone());
+            } else if has_children {
+                self.select_to(ui, cursor_id, index + 1, SelectionMode::Replace, result);
+            }
+        } else if enter && row.can_collapse {
+            result.toggle_collapse = Some(row.id.clone());
+        }
+    }
+
+    /// Draw the insertion line of a row being dragged and emit
+    /// [`AnimationCommand::ReorderRow`] when it is dropped.
+    fn show_row_drag(
+        &self,
+        ui: &mut Ui,
+        painter: &egui::Painter,
+        rect: Rect,
+        drag_id: egui::Id,
+        result: &mut PropertyTreeResponse,
+    ) {
+        let Some(dragged) = ui.data(|data| data.get_temp::<String>(drag_id)) else {
+            return;
+        };
+        let (pointer, released) = ui.input(|i| (i.pointer.latest_pos(), !i.pointer.any_down()));
+
+        let drop = pointer
+            .filter(|pointer| pointer.y >= rect.top() && !self.rows.is_empty())
+            .and_then(|pointer| {
+                let offset = (pointer.y - rect.top()) / self.row_height;
+                let hovered = offset as usize;
+                let (hovered, after) = if hovered < self.rows.len() {
+                    (hovered, offset.fract() >= 0.5)
+                } else {
+                    (self.rows.len() - 1, true)
+                };
+                row_drop(self.rows, &dragged, hovered, after)
+            });
+
+        if released {
+            ui.data_mut(|data| data.remove::<String>(drag_id));
+            if let Some((command, ..)) = drop {
+                result.commands.push(command);
+            }
+            return;
+        }
+
+        ui.ctx().set_cursor_icon(CursorIcon::Grabbing);
+        if let Some((_, boundary, depth)) = drop {
+            let y = rect.top() + boundary as f32 * self.row_height;
+            let left = rect.left() + 4.0 + depth as f32 * self.indent_per_level;
+            let stroke = Stroke::new(2.0, ui.visuals().selection.stroke.color);
+            painter.circle_stroke(Pos2::new(left + 3.0, y), 3.0, stroke);
+            painter.hline(left + 6.0..=rect.right() - 4.0, y, stroke);
+        }
+    }
+
+    /// Draw one guide line segment per ancestor across `row_rect`.
+    ///
+    /// Guides sit under the ancestor's collapse arrow. Clicking one collapses
+    /// that ancestor.
+    fn show_indent_guides_for(
+        &self,
+        ui: &mut Ui,
+        painter: &egui::Painter,
+        rect: Rect,
+        row_rect: Rect,
+        ancestors: &[&PropertyRow],
+        result: &mut PropertyTreeResponse,
+    ) {
+        for ancestor in ancestors {
+            let x = rect.left() + 4.0 + ancestor.depth as f32 * self.indent_per_level + 6.0;
+            let hit_rect = Rect::from_x_y_ranges(x - 3.0..=x + 3.0, row_rect.y_range());
+            let response = ui.interact(
+                hit_rect,
+                ui.make_persistent_id(("property_tree_guide", &ancestor.id, row_rect.top() as i32)),
+                Sense::click(),
+            );
+
+            let color = if response.hovered() {
+                ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+                Color32::from_gray(120)
+            } else {
+                Color32::from_gray(60)
+            };
+            painter.vline(x, row_rect.y_range(), Stroke::new(1.0, color));
+
+            if response.clicked() && ancestor.can_collapse {
+                result.toggle_collapse = Some(ancestor.id.clone());
+            }
+        }
+    }
+
+    /// Show the color square of a row and its color picker popup.
+    ///
+    /// Rows without a color show an empty square while hovered. The picked
+    /// color is previewed on the square and emitted as one
+    /// [`AnimationCommand::SetRowColor`] when the picker closes, so a drag
+    /// across the picker is a single undo step. "No color" clears it.
+    fn show_color_indicator(
+        &self,
+        ui: &mut Ui,
+        painter: &egui::Painter,
+        row: &PropertyRow,
+        row_rect: Rect,
+        row_hovered: bool,
+        result: &mut PropertyTreeResponse,
+    ) {
+        let indicator_rect = Rect::from_min_size(
+            Pos2::new(row_rect.right() - 12.0, row_rect.center().y - 3.0),
+            Vec2::new(6.0, 6.0),
+        );
+        let response = ui.interact(
+            indicator_rect.expand(3.0),
+            ui.make_persistent_id(("property_tree_color", &row.id)),
+            Sense::click(),
+        );
+        let popup = egui::Popup::from_toggle_button_response(&response)
+            .close_behavior(egui::PopupCloseBehavior::CloseOnClickOutside);
+        let is_open = popup.is_open();
+
+        // Color picked while the popup is open.
+        let draft_id = response.id.with("draft");
+        let draft = ui.data(|data| data.get_temp::<Color32>(draft_id));
+        if !is_open && let Some(color) = draft {
+            ui.data_mut(|data| data.remove::<Color32>(draft_id));
+            if row.color != Some(color) {
+                result.commands.push(AnimationCommand::SetRowColor {
+                    row_id: row.id.clone(),
+                    color: Some(color),
+                });
+            }
+        }
+
+        match draft.or(row.color) {
+            Some(color) => {
+                painter.rect_filled(indicator_rect, 2.0, color);
+            }
+            None if row_hovered || response.hovered() || is_open => {
+                painter.rect_stroke(
+                    indicator_rect,
+                    2.0,
+                    egui::Stroke::new(1.0, Color32::from_gray(130)),
+                    egui::StrokeKind::Inside,
+                );
+            }
+            None => {}
+        }
+        if response.hovered() {
+            ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+            painter.rect_stroke(
+                indicator_rect.expand(1.0),
+                2.0,
+                egui::Stroke::new(1.0, Color32::WHITE),
+                egui::StrokeKind::Outside,
+            );
+        }
+
+        popup.show(|ui| {
+            let mut color = draft.or(row.color).unwrap_or(Color32::WHITE);
+            if egui::color_picker::color_picker_color32(
+                ui,
+                &mut color,
+                egui::color_picker::Alpha::Opaque,
+            ) {
+                ui.data_mut(|data| data.insert_temp(draft_id, color));
+            }
+            if ui.button("No color").clicked() {
+                ui.data_mut(|data| data.remove::<Color32>(draft_id));
+                result.commands.push(AnimationCommand::SetRowColor {
+                    row_id: row.id.clone(),
+                    color: None,
+                });
+                ui.close();
+            }
+        });
+    }
+
+    /// Padlock toggling the lock of `row`, closed while the row is locked.
+    fn show_lock_toggle(
+        &self,
+        ui: &mut Ui,
+        painter: &egui::Painter,
+        row: &PropertyRow,
+        row_rect: Rect,
+        row_hovered: bool,
+        result: &mut PropertyTreeResponse,
+    ) {
+        let toggle_rect = Rect::from_center_size(
+            Pos2::new(row_rect.right() - 23.0, row_rect.center().y),
+            Vec2::splat(12.0),
+        );
+        let response = ui.interact(
+            toggle_rect,
+            ui.make_persistent_id(("property_tree_lock", &row.id)),
+            Sense::click(),
+        );
+        if response.clicked() {
+            result.commands.push(AnimationCommand::SetRowLocked {
+                row_id: row.id.clone(),
+                locked: !row.locked,
+            });
+        }
+
+        let color = if response.hovered() {
+            ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+            Color32::WHITE
+        } else if row.locked {
+            Color32::from_gray(200)
+        } else if row_hovered {
+            Color32::from_gray(110)
+        } else {
+            return;
+        };
+        let body = Rect::from_center_size(
+            toggle_rect.center() + Vec2::new(0.0, 2.0),
+            Vec2::new(7.0, 5.0),
+        );
+        paint_padlock(painter, body, color, row.locked);
+        response.on_hover_text(if row.locked { "Unlock" } else { "Lock" });
+    }
+
+    /// Lettered mute or solo toggle of `row` centered on `center`, filled
+    /// while `active`; returns whether it was clicked.
+    #[allow(clippy::too_many_arguments)]
+    fn show_audition_toggle(
+        &self,
+        ui: &mut Ui,
+        painter: &egui::Painter,
+        row: &PropertyRow,
+        center: Pos2,
+        letter: &str,
+        active: bool,
+        row_hovered: bool,
+    ) -> bool {
+        let toggle_rect = Rect::from_center_size(center, Vec2::splat(12.0));
+        let response = ui.interact(
+            toggle_rect,
+            ui.make_persistent_id(("property_tree_audition", letter, &row.id)),
+            Sense::click(),
+        );
+        let clicked = response.clicked();
+
+        let fill = match letter {
+            "M" => Color32::from_rgb(200, 110, 60),
+            _ => Color32::from_rgb(210, 180, 60),
+        };
+        let hovered = response.hovered();
+        if hovered {
+            ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+        }
+        if active {
+            painter.rect_filled(toggle_rect, 2.0, fill);
+        } else if hovered || row_hovered {
+            painter.rect_stroke(
+                toggle_rect,
+                2.0,
+                Stroke::new(1.0, Color32::from_gray(if hovered { 200 } else { 90 })),
+                egui::StrokeKind::Inside,
+            );
+        } else {
+            return clicked;
+        }
+        let text_color = if active {
+            Color32::from_gray(20)
+        } else if hovered {
+            Color32::WHITE
+        } else {
+            Color32::from_gray(130)
+        };
+        painter.text(
+            center,
+            egui::Align2::CENTER_CENTER,
+            letter,
+            egui::FontId::proportional(9.0),
+            text_color,
+        );
+        response.on_hover_text(match (letter, active) {
+            ("M", false) => "Mute",
+            ("M", true) => "Unmute",
+            (_, false) => "Solo",
+            (_, true) => "Unsolo",
+        });
+        clicked
+    }
+
+    /// Fill the context menu of a row.
+    fn row_context_menu(
+        &self,
+        ui: &mut Ui,
+        row: &PropertyRow,
+        result: &mut PropertyTreeResponse,
+        dialog_id: egui::Id,
+        add_track_id: egui::Id,
+        rename_id: egui::Id,
+    ) {
+        if ui.button("Rename").clicked() {
+            ui.data_mut(|data| data.insert_temp(rename_id, (row.id.clone(), row.label.clone())));
+            ui.close();
+        }
+        let delete_label = if row.track_id.is_some() {
+            "Delete track"
+        } else {
+            "Delete group"
+        };
+        if ui.button(delete_label).clicked() {
+            result.commands.push(AnimationCommand::RemoveTrack {
+                row_id: row.id.clone(),
+            });
+            ui.close();
+        }
+        ui.menu_button("Color", |ui| {
+            ui.horizontal(|ui| {
+                for color in ROW_COLOR_PALETTE {
+                    let (rect, response) =
+                        ui.allocate_exact_size(Vec2::splat(16.0), Sense::click());
+                    ui.painter().rect_filled(rect, 2.0, color);
+                    if row.color == Some(color) {
+                        ui.painter().rect_stroke(
+                            rect,
+                            2.0,
+                            egui::Stroke::new(1.5, Color32::WHITE),
+                            egui::StrokeKind::Outside,
+                        );
+                    }
+                    if response.clicked() {
+                        result.commands.push(AnimationCommand::SetRowColor {
+                            row_id: row.id.clone(),
+                            color: Some(color),
+                        });
+                        ui.close();
+                    }
+                }
+            });
+            if ui
+                .add_enabled(row.color.is_some(), egui::Button::new("None"))
+                .clicked()
+            {
+                result.commands.push(AnimationCommand::SetRowColor {
+                    row_id: row.id.clone(),
+                    color: None,
+                });
+                ui.close();
+            }
+        });
+
+        ui.separator();
+
+        let mut locked = row.locked;
+        if ui.checkbox(&mut locked, "Lock").clicked() {
+            result.commands.push(AnimationCommand::SetRowLocked {
+                row_id: row.id.clone(),
+                locked,
+            });
+            ui.close();
+        }
+        if ui.button("Isolate").clicked() {
+            result
+                .commands
+                .push(AnimationCommand::IsolateRow(Some(row.id.clone())));
+            ui.close();
+        }
+        if ui.button("Select all keys").clicked() {
+            result.select_keys = Some(row.id.clone());
+            ui.close();
+        }
+        if ui
+            .add_enabled(!row.locked, egui::Button::new("Reset to default"))
+            .clicked()
+        {
+            result.commands.push(AnimationCommand::ResetRow {
+                row_id: row.id.clone(),
+            });
+            ui.close();
+        }
+
+        if row.can_collapse {
+            ui.separator();
+            if ui.button("Shift children…").clicked() {
+                ui.data_mut(|data| {
+                    data.insert_temp(dialog_id, GroupTransform::new(row.id.clone()))
+                });
+                ui.close();
+            }
+            if self.show_add_track && ui.button("Add track…").clicked() {
+                let draft: AddTrackDraft = (Some(row.id.clone()), NEW_TRACK_LABEL.into());
+                ui.data_mut(|data| data.insert_temp(add_track_id, draft));
+                ui.close();
+            }
+        }
+    }
+
+    /// Show the inline rename field if `row` is being renamed.
+    ///
+    /// Returns `false` if the row is not being renamed.
+    fn show_rename_edit(
+        &self,
+        ui: &mut Ui,
+        row: &PropertyRow,
+        rect: Rect,
+        rename_id: egui::Id,
+        result: &mut PropertyTreeResponse,
+    ) -> bool {
+        let Some((row_id, mut label)) =
+            ui.data(|data| data.get_temp::<(String, String)>(rename_id))
+        else {
+            return false;
+        };
+        if row_id != row.id {
+            return false;
+        }
+
+        let edit = ui.put(
+            rect.shrink2(Vec2::new(0.0, 2.0)),
+            egui::TextEdit::singleline(&mut label).margin(Vec2::new(2.0, 0.0)),
+        );
+        edit.request_focus();
+        if edit.lost_focus() {
+            ui.data_mut(|data| data.remove::<(String, String)>(rename_id));
+            let label = label.trim();
+            if !ui.input(|i| i.key_pressed(egui::Key::Escape))
+                && !label.is_empty()
+                && label != row.label
+            {
+                result.commands.push(AnimationCommand::RenameRow {
+                    row_id,
+                    label: label.to_string(),
+                });
+            }
+        } else {
+            ui.data_mut(|data| data.insert_temp(rename_id, (row_id, label)));
+        }
+        true
+    }
+
+    /// Show the "Shift children…" dialog while one is open.
+    ///
+    /// Returns the transform once the user applies it.
+    fn show_group_transform_dialog(&self, ui: &Ui, id: egui::Id) -> Option<GroupTransform> {
+        let mut draft = ui.data(|data| data.get_temp::<GroupTransform>(id))?;
+        let label = self
+            .rows
+            .iter()
+            .find(|row| row.id == draft.row_id)
+            .map_or(draft.row_id.as_str(), |row| row.label.as_str());
+
+        let mut open = true;
+        let mut applied = false;
+        let mut cancelled = false;
+        egui::Window::new(format!("Shift children of {label}"))
+            .id(id)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                egui::Grid::new(id.with("grid")).show(ui, |ui| {
+                    ui.label("Offset");
+                    let mut delta = draft.delta_time.value();
+                    if ui
+                        .add(egui::DragValue::new(&mut delta).speed(0.01))
+                        .changed()
+                    {
+                        draft.delta_time = TimeTick::new(delta);
+                    }
+                    ui.end_row();
+
+                    ui.label("Scale");
+                    ui.add(
+                        egui::DragValue::new(&mut draft.time_scale)
+                            .speed(0.01)
+                            .range(0.01..=100.0),
+                    );
+                    ui.end_row();
+                });
+
+                ui.horizontal(|ui| {
+                    applied = ui.button("Apply").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if applied || cancelled || !open {
+            ui.data_mut(|data| data.remove::<GroupTransform>(id));
+        } else {
+            ui.data_mut(|data| data.insert_temp(id, draft.clone()));
+        }
+
+        (applied && !draft.is_identity()).then_some(draft)
+    }
+
+    /// Show the "+" row that opens the "Add track" dialog for a root track.
+    fn show_add_track_row(&self, ui: &mut Ui, rect: Rect, dialog_id: egui::Id) {
+        let row_rect = Rect::from_min_size(
+            Pos2::new(
+                rect.left(),
+                rect.top() + self.rows.len() as f32 * self.row_height,
+            ),
+            Vec2::new(rect.width(), self.row_height),
+        );
+        if !ui.is_rect_visible(row_rect) {
+            return;
+        }
+
+        let response = ui.allocate_rect(row_rect, Sense::click());
+        let color = if response.hovered() {
+            ui.ctx().set_cursor_icon(CursorIcon::PointingHand);
+            Color32::WHITE
+        } else {
+            Color32::from_gray(130)
+        };
+        ui.painter_at(rect).text(
+            Pos2::new(rect.left() + 8.0, row_rect.center().y),
+            egui::Align2::LEFT_CENTER,
+            "+ Add track",
+            egui::FontId::proportional(12.0),
+            color,
+        );
+
+        if response.clicked() {
+            let draft: AddTrackDraft = (None, NEW_TRACK_LABEL.into());
+            ui.data_mut(|data| data.insert_temp(dialog_id, draft));
+        }
+    }
+
+    /// Show the "Add track" dialog while one is open.
+    ///
+    /// Returns `(parent_row, label)` once the user confirms it.
+    fn show_add_track_dialog(&self, ui: &Ui, id: egui::Id) -> Option<AddTrackDraft> {
+        let (parent_row, mut label) = ui.data(|data| data.get_temp::<AddTrackDraft>(id))?;
+
+        let mut open = true;
+        let mut added = false;
+        let mut cancelled = false;
+        egui::Window::new("Add track")
+            .id(id)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ui.ctx(), |ui| {
+                let edit = ui.text_edit_singleline(&mut label);
+                if edit.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    added = true;
+                }
+
+                ui.horizontal(|ui| {
+                    added |= ui.button("Add").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if added || cancelled || !open {
+            ui.data_mut(|data| data.remove::<AddTrackDraft>(id));
+        } else {
+            ui.data_mut(|data| data.insert_temp(id, (parent_row.clone(), label.clone())));
+        }
+
+        let label = label.trim();
+        (added && !label.is_empty()).then(|| (parent_row, label.to_string()))
+    }
+}
+
+/// Paint two chain links centered on `center`, marking a driven row.
+fn paint_link_icon(painter: &egui::Painter, center: Pos2, color: Color32) {
+    let stroke = Stroke::new(1.2, color);
+    for dx in [-2.5, 2.5] {
+        painter.rect_stroke(
+            Rect::from_center_size(center + Vec2::new(dx, 0.0), Vec2::new(8.0, 5.0)),
+            2.5,
+            stroke,
+            egui::StrokeKind::Middle,
+        );
+    }
+}
+
+/// Where row `dragged` lands when dropped over row `hovered`, in its lower
+/// half if `after`.
+///
+/// Tracks dropped on a group row become its first child; other rows are
+/// placed before or after the sibling of the same depth containing the
+/// hovered row. Returns the [`AnimationCommand::ReorderRow`], the index of the
+/// row the insertion line is drawn above, and the line's depth. `None` if
+/// the drop would not change anything.
+fn row_drop(
+    rows: &[PropertyRow],
+    dragged: &str,
+    hovered: usize,
+    after: bool,
+) -> Option<(AnimationCommand, usize, usize)> {
+    let from = rows.iter().position(|row| row.id == dragged)?;
+    let target = rows.get(hovered)?;
+    let depth = rows[from].depth;
+    let location = row_location(rows, dragged)?;
+
+    let (parent, index, boundary, line_depth) =
+        if rows[from].track_id.is_some() && target.track_id.is_none() {
+            (Some(target.id.clone()), 0, hovered + 1, target.depth + 1)
+        } else {
+            let sibling = rows[..=hovered]
+                .iter()
+                .rposition(|row| row.depth <= depth)
+                .filter(|&sibling| rows[sibling].depth == depth && sibling != from)?;
+            let before = sibling == hovered && !after;
+            let boundary = if before {
+                sibling
+            } else {
+                sibling
+                    + 1
+                    + rows[sibling + 1..]
+                        .iter()
+                        .take_while(|row| row.depth > depth)
+                        .count()
+            };
+            let (parent, mut index) = row_location(rows, &rows[sibling].id)?;
+            // The dragged row no longer counts once it is taken out.
+            if location.0 == parent && from < sibling {
+                index -= 1;
+            }
+            (parent, index + usize::from(!before), boundary, depth)
+        };
+
+    (location != (parent.clone(), index)).then(|| {
+        (
+            AnimationCommand::ReorderRow {
+                row: dragged.to_string(),
+                target: RowTarget { parent, index },
+            },
+            boundary,
+            line_depth,
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TrackId;
+    use crate::widgets::offscreen::run_input_frame;
+    use egui::{Context, Event, PointerButton, RawInput};
+
+    fn row(id: &str, depth: usize, track: bool) -> PropertyRow {
+        PropertyRow {
+            id: id.into(),
+            label: id.into(),
+            depth,
+            can_collapse: !track,
+            track_id: track.then(TrackId::new),
+            ..Default::default()
+        }
+    }
+
+    fn rows() -> [PropertyRow; 5] {
+        [
+            row("a", 0, false),
+            row("a.x", 1, true),
+            row("a.y", 1, true),
+            row("b", 0, false),
+            row("b.z", 1, true),
+        ]
+    }
+
+    #[test]
+    fn clicks_and_arrow_keys_select_rows() {
+        let rows = rows();
+        let selected = HashSet::default();
+        let size = Vec2::new(200.0, 120.0);
+        let ctx = Context::default();
+        let frame = |events: Vec<Event>, modifiers: Modifiers| {
+            let input = RawInput {
+                events,
+                modifiers,
+                ..Default::default()
+            };
+            let mut changes = (Vec::new(), None, None);
+            run_input_frame(&ctx, size, 1.0, input, |ui| {
+                let response = PropertyTree::new(&rows, &selected)
+                    .show(ui, Rect::from_min_size(Pos2::ZERO, size));
+                changes = (
+                    response.row_selection,
+                    response.row_selection_mode,
+                    response.toggle_collapse,
+                );
+            });
+            changes
+        };
+        // Clicks the label of the row with index `row`.
+        let click = |row: usize, modifiers: Modifiers| {
+            let pos = Pos2::new(100.0, row as f32 * 24.0 + 12.0);
+            let button = |pressed| Event::PointerButton {
+                pos,
+                button: PointerButton::Primary,
+                pressed,
+                modifiers,
+            };
+            frame(vec![Event::PointerMoved(pos)], modifiers);
+            frame(vec![button(true)], modifiers);
+            frame(vec![button(false)], modifiers)
+        };
+        let key = |key: Key, modifiers: Modifiers| {
+            let event = Event::Key {
+                key,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers,
+            };
+            frame(vec![event], modifiers)
+        };
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        assert_eq!(
+            click(1, Modifiers::NONE),
+            (ids(&["a.x"]), Some(SelectionMode::Replace), None)
+        );
+        assert_eq!(
+            click(3, Modifiers::SHIFT),
+            (ids(&["a.x", "a.y", "b"]), Some(SelectionMode::Add), None)
+        );
+        assert_eq!(
+            click(2, Modifiers::COMMAND),
+            (ids(&["a.y"]), Some(SelectionMode::Toggle), None)
+        );
+        assert_eq!(
+            key(Key::ArrowDown, Modifiers::SHIFT),
+            (ids(&["a.y", "b"]), Some(SelectionMode::Add), None)
+        );
+        assert_eq!(
+            key(Key::ArrowDown, Modifiers::NONE),
+            (ids(&["b.z"]), Some(SelectionMode::Replace), None)
+        );
+        // Left moves to the parent, then collapses it.
+        assert_eq!(
+            key(Key::ArrowLeft, Modifiers::NONE),
+            (ids(&["b"]), Some(SelectionMode::Replace), None)
+        );
+        assert_eq!(
+            key(Key::ArrowLeft, Modifiers::NONE),
+            (Vec::new(), None, Some("b".into()))
+        );
+        assert_eq!(
+            key(Key::Enter, Modifiers::NONE),
+            (Vec::new(), None, Some("b".into()))
+        );
+    }
+
+    #[test]
+    fn dropped_rows_move_among_their_siblings() {
+        let rows = rows();
+        let target = |dragged: &str, hovered: usize, after: bool| {
+            row_drop(&rows, dragged, hovered, after).map(|(command, boundary, _)| match command {
+                AnimationCommand::ReorderRow { target, .. } => {
+                    (target.parent, target.index, boundary)
+                }
+                _ => unreachable!(),
+            })
+        };
+
+        // Below the next sibling.
+        assert_eq!(target("a.x", 2, true), Some((Some("a".into()), 1, 3)));
+        // Onto its own place.
+        assert_eq!(target("a.x", 1, false), None);
+        assert_eq!(target("a.x", 2, false), None);
+        // Into another group.
+        assert_eq!(target("a.y", 3, false), Some((Some("b".into()), 0, 4)));
+        assert_eq!(target("b.z", 1, false), Some((Some("a".into()), 0, 1)));
+        // Groups move past whole subtrees.
+        assert_eq!(target("a", 4, true), Some((None, 1, 5)));
+        assert_eq!(target("b", 0, false), Some((None, 0, 0)));
+        assert_eq!(target("b", 1, false), None);
+    }
 }
@@ -1,7 +1,34 @@
 //! Selection state management for the DopeSheet.
 
 use crate::HashSet;
-use crate::core::keyframe::KeyframeId;
+use crate::core::keyframe::{KeyframeId, KeyframeTag};
+use crate::traits::AnimationDataProvider;
+
+/// How a box selection combines with the existing selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SelectionMode {
+    /// Replace the selection.
+    #[default]
+    Replace,
+    /// Add to the selection (Shift).
+    Add,
+    /// Toggle the selection state of each item (Ctrl/Cmd).
+    Toggle,
+}
+
+impl SelectionMode {
+    /// Derive the mode from held modifiers with the default bindings, see
+    /// [`KeyBindings::selection_mode`](crate::KeyBindings::selection_mode).
+    pub fn from_modifiers(modifiers: &egui::Modifiers) -> Self {
+        if modifiers.shift {
+            Self::Add
+        } else if modifiers.command {
+            Self::Toggle
+        } else {
+            Self::Replace
+        }
+    }
+}
 
 /// Selection state for the DopeSheet.
 #[derive(Debug, Clone, Default)]
@@ -72,6 +99,27 @@ impl SelectionState {
         }
     }
 
+    /// Select multiple rows.
+    pub fn select_rows(&mut self, ids: impl IntoIterator<Item = String>, add_to_selection: bool) {
+        if !add_to_selection {
+            self.rows.clear();
+        }
+        self.rows.extend(ids);
+    }
+
+    /// Apply a row selection from the property tree with the given mode.
+    pub fn apply_row_selection(
+        &mut self,
+        ids: impl IntoIterator<Item = String>,
+        mode: SelectionMode,
+    ) {
+        match mode {
+            SelectionMode::Replace => self.select_rows(ids, false),
+            SelectionMode::Add => self.select_rows(ids, true),
+            SelectionMode::Toggle => ids.into_iter().for_each(|id| self.toggle_row(&id)),
+        }
+    }
+
     /// Select multiple keyframes.
     pub fn select_keyframes(
         &mut self,
@@ -84,6 +132,30 @@ impl SelectionState {
         self.keyframes.extend(ids);
     }
 
+    /// Select the keyframes of all tracks of `provider` tagged `tag`, e.g.
+    /// all red keys.
+    pub fn select_tagged<P: AnimationDataProvider + ?Sized>(
+        &mut self,
+        provider: &P,
+        tag: KeyframeTag,
+        add_to_selection: bool,
+    ) {
+        self.select_keyframes(tagged_keyframes(provider, tag), add_to_selection);
+    }
+
+    /// Apply a box selection result using the given mode.
+    pub fn apply_box_selection(
+        &mut self,
+        ids: impl IntoIterator<Item = KeyframeId>,
+        mode: SelectionMode,
+    ) {
+        match mode {
+            SelectionMode::Replace => self.select_keyframes(ids, false),
+            SelectionMode::Add => self.select_keyframes(ids, true),
+            SelectionMode::Toggle => ids.into_iter().for_each(|id| self.toggle_keyframe(id)),
+        }
+    }
+
     /// Check if a keyframe is selected.
     pub fn is_keyframe_selected(&self, id: &KeyframeId) -> bool {
         self.keyframes.contains(id)
@@ -116,3 +188,23 @@ impl SelectionState {
         self.box_start = None;
     }
 }
+
+/// The keyframes of all tracks of `provider` tagged `tag`, in row order.
+pub(crate) fn tagged_keyframes<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    tag: KeyframeTag,
+) -> Vec<KeyframeId> {
+    provider
+        .property_rows()
+        .into_iter()
+        .filter_map(|row| row.track_id)
+        .flat_map(|track_id| {
+            provider
+                .keyframe_positions(track_id)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(id, _)| id)
+                .filter(move |id| provider.keyframe_tag(track_id, *id) == Some(tag))
+        })
+        .collect()
+}
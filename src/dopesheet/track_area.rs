@@ -1,12 +1,29 @@
 //! Track area panel for the DopeSheet.
 
-use crate::core::keyframe::KeyframeId;
-use crate::traits::{AnimationDataProvider, PropertyRow};
-use crate::widgets::keyframe_dot::{AggregateKeyframeDot, KeyframeDot};
-use crate::widgets::time_ruler::draw_time_grid;
-use crate::{HashMap, HashSet};
+use super::DopeSheetHit;
+use super::selection::{SelectionMode, SelectionState, tagged_keyframes};
+use crate::HashSet;
+use crate::core::keyframe::{KeyframeId, KeyframeTag};
+use crate::core::layer::BlendMode;
+use crate::core::marker::Markers;
+use crate::core::track::{TrackId, retime_to_global, retime_to_local};
+use crate::key_bindings::KeyBindings;
+use crate::snap::SnapConfig;
+use crate::traits::{AnimationCommand, AnimationDataProvider, PropertyRow, TrackLane};
+use crate::transport::audible_track_ids;
+use crate::widgets::bounding_box::{AnchorMode, WorldBoundingBox};
+use crate::widgets::interaction::InteractionSizes;
+use crate::widgets::keyframe_dot::{
+    AggregateKeyframeDot, BREAKDOWN_TINT, KeyframeDot, KeyframeDotTextures, KeyframeVisual,
+    KeyframeVisualState, desaturated, keyframe_tooltip, muted,
+};
+use crate::widgets::playhead::PlayheadStyle;
+use crate::widgets::time_ruler::{
+    ScrubCallback, draw_markers, draw_time_grid, draw_work_range, notify_scrub,
+};
 use crate::{SpaceTransform, TimeTick};
-use egui::{Color32, Pos2, Rect, Sense, Stroke, Ui, Vec2};
+use egui::{Color32, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2};
+use std::collections::BTreeMap;
 
 /// Response from the track area.
 #[derive(Default)]
@@ -15,10 +32,34 @@ pub struct TrackAreaResponse {
     pub clicked_keyframe: Option<KeyframeId>,
     /// Time position clicked.
     pub clicked_time: Option<TimeTick>,
-    /// Keyframes selected via box selection.
+    /// Keyframes selected via box selection, in row order and then time
+    /// order.
     pub box_selected: Vec<KeyframeId>,
+    /// How to combine `box_selected` with the current selection. `Some` when
+    /// a box selection finished this frame, even if it enclosed nothing.
+    pub box_select_mode: Option<SelectionMode>,
+    /// Time the playhead was scrubbed to by dragging it.
+    pub scrubbed_time: Option<TimeTick>,
+    /// Position and state of every visible keyframe dot of a track row.
+    pub keyframes: Vec<KeyframeVisual>,
+    /// Keyframes to select, replacing the selection, from "Select tagged"
+    /// in the context menu.
+    pub select_keyframes: Option<Vec<KeyframeId>>,
+    /// Animation commands to execute (from user interactions).
+    pub commands: Vec<AnimationCommand>,
 }
 
+/// In-progress drag of a whole row's keyframe block.
+#[derive(Debug, Clone, Copy)]
+struct RetimeDrag {
+    track_id: TrackId,
+    start_time: TimeTick,
+    original_offset: TimeTick,
+}
+
+/// A selected keyframe as `(id, global time, value)`.
+type SelectedKey = (KeyframeId, TimeTick, f32);
+
 /// Track area panel widget.
 pub struct TrackArea<'a, P: AnimationDataProvider> {
     provider: &'a P,
@@ -28,8 +69,16 @@ pub struct TrackArea<'a, P: AnimationDataProvider> {
     background: Color32,
     alt_row_color: Color32,
     row_height: f32,
-    playhead_color: Color32,
+    playhead: PlayheadStyle,
     show_aggregates: bool,
+    snap: Option<SnapConfig>,
+    keyframe_textures: Option<KeyframeDotTextures>,
+    interaction: InteractionSizes,
+    key_bindings: KeyBindings,
+    anchor_mode: AnchorMode,
+    markers: Option<&'a Markers>,
+    work_range: Option<(TimeTick, TimeTick)>,
+    on_scrub: Option<ScrubCallback<'a>>,
 }
 
 impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
@@ -48,8 +97,16 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
             background: Color32::from_gray(25),
             alt_row_color: Color32::from_gray(30),
             row_height: 24.0,
-            playhead_color: Color32::from_rgb(255, 100, 100),
+            playhead: PlayheadStyle::default(),
             show_aggregates: true,
+            snap: None,
+            keyframe_textures: None,
+            interaction: InteractionSizes::default(),
+            key_bindings: KeyBindings::default(),
+            anchor_mode: AnchorMode::default(),
+            markers: None,
+            work_range: None,
+            on_scrub: None,
         }
     }
 
@@ -65,13 +122,70 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
         self.background = background;
         self.alt_row_color = alt_row_color;
         self.row_height = row_height;
-        self.playhead_color = playhead_color;
+        self.playhead.color = playhead_color;
         self.show_aggregates = show_aggregates;
         self
     }
 
+    /// Set the appearance and grab zone of the playhead.
+    pub fn playhead(mut self, playhead: PlayheadStyle) -> Self {
+        self.playhead = playhead;
+        self
+    }
+
+    /// Snap the playhead and dragged rows to frames, keyframes, markers or
+    /// the grid.
+    pub fn snap(mut self, snap: Option<SnapConfig>) -> Self {
+        self.snap = snap;
+        self
+    }
+
+    /// Draw images from a texture atlas instead of the keyframe diamonds.
+    pub fn keyframe_textures(mut self, textures: Option<KeyframeDotTextures>) -> Self {
+        self.keyframe_textures = textures;
+        self
+    }
+
+    /// Set the pointer hit sizes, resolved for UI zoom and touch input when
+    /// shown.
+    pub fn interaction(mut self, interaction: InteractionSizes) -> Self {
+        self.interaction = interaction;
+        self
+    }
+
+    /// Set the selection and retime modifiers.
+    pub fn key_bindings(mut self, key_bindings: KeyBindings) -> Self {
+        self.key_bindings = key_bindings;
+        self
+    }
+
+    /// Set the anchor the selection is flipped around.
+    pub fn anchor_mode(mut self, mode: AnchorMode) -> Self {
+        self.anchor_mode = mode;
+        self
+    }
+
+    /// Show markers as vertical lines and use them as snap targets.
+    pub fn markers(mut self, markers: Option<&'a Markers>) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Shade times outside the `(start, end)` work range.
+    pub fn work_range(mut self, range: Option<(TimeTick, TimeTick)>) -> Self {
+        self.work_range = range;
+        self
+    }
+
+    /// Set the scrub callback invoked with `(old_time, new_time)`.
+    pub fn on_scrub(mut self, callback: Option<ScrubCallback<'a>>) -> Self {
+        self.on_scrub = callback;
+        self
+    }
+
     /// Show the track area.
-    pub fn show(self, ui: &mut Ui, rect: Rect) -> TrackAreaResponse {
+    pub fn show(mut self, ui: &mut Ui, rect: Rect) -> TrackAreaResponse {
+        self.interaction = self.interaction.for_context(ui.ctx());
         let mut result = TrackAreaResponse::default();
 
         let painter = ui.painter_at(rect);
@@ -81,6 +195,29 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
 
         // Time grid.
         draw_time_grid(&painter, rect, self.space, Color32::from_gray(40), None);
+        if let Some(range) = self.work_range {
+            draw_work_range(&painter, rect, self.space, range);
+        }
+        if let Some(markers) = self.markers {
+            draw_markers(&painter, rect, self.space, markers, false);
+        }
+
+        // Row block drag in progress: preview the dragged track at its new offset.
+        let retime_id = ui.id().with("track_area_retime");
+        let retime_drag = ui.data(|data| data.get_temp::<RetimeDrag>(retime_id));
+        let retime_preview = retime_drag.and_then(|drag| {
+            let pointer = ui.input(|i| i.pointer.interact_pos())?;
+            let delta = self.space.clipped_to_unit(pointer.x) - drag.start_time;
+            let offset = self.snap_offset(ui, drag.track_id, drag.original_offset + delta);
+            Some((drag.track_id, offset))
+        });
+
+        let pointer = ui
+            .input(|i| i.pointer.hover_pos())
+            .filter(|pos| rect.contains(*pos));
+
+        // Tracks not silenced by a mute or solo, also counting collapsed rows.
+        let audible = audible_track_ids(&self.provider.property_rows());
 
         // Render rows.
         // Vec of (id, pos, row_index).
@@ -106,25 +243,83 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
             // Draw keyframes for this row
             if let Some(track_id) = row.track_id {
                 if let Some(positions) = self.provider.keyframe_positions(track_id) {
+                    let color = row.color.unwrap_or(Color32::from_rgb(100, 180, 255));
+                    let silenced = !audible.contains(&track_id);
+                    let lane_color = if silenced { muted(color) } else { color };
+                    let lane = self.provider.track_lane(track_id);
+                    if let Some(blend) = self.provider.track_blend(track_id) {
+                        paint_layer_lane(&painter, row_rect, blend, lane_color);
+                    }
+                    if lane == TrackLane::Color {
+                        let xs = positions.iter().map(|&(_, position)| {
+                            let position = self.display_time(track_id, position, retime_preview);
+                            self.space.unit_to_clipped(position)
+                        });
+                        let first = xs.clone().fold(f32::INFINITY, f32::min);
+                        let last = xs.fold(f32::NEG_INFINITY, f32::max);
+                        paint_color_lane(&painter, row_rect, first..=last, |x| {
+                            let time = self.space.clipped_to_unit(x);
+                            self.provider.track_color(track_id, time).map(Color32::from)
+                        });
+                    } else if lane != TrackLane::Keyframes {
+                        let mut steps: Vec<(f32, f64)> = positions
+                            .iter()
+                            .map(|&(kf_id, position)| {
+                                let position =
+                                    self.display_time(track_id, position, retime_preview);
+                                let value = self.provider.keyframe_value(track_id, kf_id);
+                                (
+                                    self.space.unit_to_clipped(position),
+                                    value.unwrap_or_default(),
+                                )
+                            })
+                            .collect();
+                        steps.sort_by(|a, b| a.0.total_cmp(&b.0));
+                        paint_stepped_lane(&painter, row_rect, &lane, lane_color, &steps);
+                    }
                     for (kf_id, position) in positions {
+                        let position = self.display_time(track_id, position, retime_preview);
                         let x = self.space.unit_to_clipped(position);
                         if x >= rect.left() && x <= rect.right() {
                             let pos = Pos2::new(x, y_center);
-                            let is_selected = self.selected_keyframes.contains(&kf_id);
+                            let state = KeyframeVisualState {
+                                selected: self.selected_keyframes.contains(&kf_id),
+                                hovered: pointer.is_some_and(|p| {
+                                    (p.x - x).abs() + (p.y - y_center).abs()
+                                        < self.interaction.keyframe_hit
+                                }),
+                                locked: row.locked
+                                    || self.provider.keyframe_locked(track_id, kf_id),
+                                muted: silenced,
+                                pinned: self.provider.keyframe_pinned(track_id, kf_id),
+                                breakdown: self.provider.keyframe_breakdown(track_id, kf_id),
+                                tag: self.provider.keyframe_tag(track_id, kf_id),
+                                dragged: retime_preview.is_some_and(|(id, _)| id == track_id),
+                            };
 
-                            KeyframeDot::new(pos)
-                                .color(row.color.unwrap_or(Color32::from_rgb(100, 180, 255)))
-                                .selected(is_selected)
-                                .size(4.0)
-                                .paint(&painter);
+                            if lane == TrackLane::Keyframes {
+                                KeyframeDot::new(pos)
+                                    .color(color)
+                                    .state(state)
+                                    .size(4.0)
+                                    .textures(self.keyframe_textures)
+                                    .paint(&painter);
+                            } else {
+                                paint_step_key(&painter, row_rect, x, color, state);
+                            }
 
                             keyframe_positions.push((kf_id, pos, i));
+                            result.keyframes.push(KeyframeVisual {
+                                keyframe_id: kf_id,
+                                pos,
+                                state,
+                            });
                         }
                     }
                 }
             } else if self.show_aggregates && row.can_collapse {
                 // Aggregate keyframes for parent rows
-                let aggregates = self.collect_aggregates(row, i);
+                let aggregates = self.collect_aggregates(row, i, retime_preview);
                 for (quantized_ms, kf_ids) in aggregates {
                     let position = TimeTick::new(quantized_ms as f64 / 1000.0);
                     let x = self.space.unit_to_clipped(position);
@@ -152,38 +347,193 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
         // Draw playhead
         let current_time = self.provider.current_time();
         let playhead_x = self.space.unit_to_clipped(current_time);
-        if playhead_x >= rect.left() && playhead_x <= rect.right() {
-            painter.line_segment(
-                [
-                    Pos2::new(playhead_x, rect.top()),
-                    Pos2::new(playhead_x, rect.bottom()),
-                ],
-                Stroke::new(2.0, self.playhead_color),
-            );
-
-            // Playhead head (small triangle at top)
-            let head_size = 6.0;
-            painter.add(egui::Shape::convex_polygon(
-                vec![
-                    Pos2::new(playhead_x, rect.top()),
-                    Pos2::new(playhead_x - head_size, rect.top() - head_size),
-                    Pos2::new(playhead_x + head_size, rect.top() - head_size),
-                ],
-                self.playhead_color,
-                Stroke::NONE,
-            ));
-        }
+        self.playhead
+            .paint(&painter, rect, playhead_x, current_time);
 
         // Handle interactions
         let response = ui.allocate_rect(rect, Sense::click_and_drag());
 
-        if let Some(pos) = response.interact_pointer_pos() {
+        // Label and tag of the hovered keyframe.
+        if !response.dragged()
+            && let Some(text) = result
+                .keyframes
+                .iter()
+                .find(|kf| kf.state.hovered)
+                .and_then(|kf| {
+                    let (track_id, _) = self.provider.find_keyframe(kf.keyframe_id)?;
+                    keyframe_tooltip(
+                        self.provider
+                            .keyframe_label(track_id, kf.keyframe_id)
+                            .as_deref(),
+                        kf.state.tag,
+                    )
+                })
+        {
+            response.clone().on_hover_text_at_pointer(text);
+        }
+
+        response.context_menu(|ui| {
+            if !self.selected_keyframes.is_empty() {
+                if ui.button("Flip time").clicked() {
+                    result.commands.extend(self.flip_commands(true));
+                    ui.close();
+                }
+                if ui.button("Flip value").clicked() {
+                    result.commands.extend(self.flip_commands(false));
+                    ui.close();
+                }
+                if ui.button("Reverse").clicked() {
+                    let keyframe_ids = self
+                        .selected_by_track()
+                        .into_iter()
+                        .flat_map(|(_, keyframes)| keyframes.into_iter().map(|(id, ..)| id))
+                        .collect();
+                    result
+                        .commands
+                        .push(AnimationCommand::ReverseKeyframes { keyframe_ids });
+                    ui.close();
+                }
+
+                // Breakdowns go between two selected keyframes of a track.
+                ui.separator();
+                let fraction_id = response.id.with("breakdown_fraction");
+                let mut percent = ui.data(|data| data.get_temp(fraction_id)).unwrap_or(50.0);
+                let breakdowns = self.breakdown_commands(percent / 100.0);
+                ui.horizontal(|ui| {
+                    let button = ui.add_enabled(
+                        !breakdowns.is_empty(),
+                        egui::Button::new("Insert breakdown"),
+                    );
+                    ui.add(
+                        egui::DragValue::new(&mut percent)
+                            .range(1.0..=99.0)
+                            .suffix("%"),
+                    );
+                    if button.clicked() {
+                        result.commands.extend(breakdowns);
+                        ui.close();
+                    }
+                });
+                ui.data_mut(|data| data.insert_temp(fraction_id, percent));
+
+                ui.separator();
+                for (label, locked) in [("Lock", true), ("Unlock", false)] {
+                    let command = self.lock_command(locked);
+                    if ui
+                        .add_enabled(command.is_some(), egui::Button::new(label))
+                        .clicked()
+                    {
+                        result.commands.extend(command);
+                        ui.close();
+                    }
+                }
+                ui.menu_button("Tag", |ui| {
+                    for tag in std::iter::once(None).chain(KeyframeTag::ALL.map(Some)) {
+                        if tag_button(ui, tag).clicked() {
+                            result.commands.extend(self.tag_commands(tag));
+                            ui.close();
+                        }
+                    }
+                });
+            }
+            ui.menu_button("Select tagged", |ui| {
+                for tag in KeyframeTag::ALL {
+                    if tag_button(ui, Some(tag)).clicked() {
+                        result.select_keyframes = Some(tagged_keyframes(self.provider, tag));
+                        ui.close();
+                    }
+                }
+            });
+        });
+
+        // Playhead scrubbing, a row block drag or a box selection; otherwise
+        // clicks.
+        let box_id = ui.id().with("track_area_box_select");
+        let scrub_id = response.id.with("scrub");
+        if response.drag_started()
+            && let Some(pos) = response.interact_pointer_pos()
+            && (pos.x - playhead_x).abs() <= self.playhead_grab()
+        {
+            ui.data_mut(|data| data.insert_temp(scrub_id.with("active"), true));
+        }
+        let scrubbing = ui.data(|data| data.get_temp::<bool>(scrub_id.with("active")));
+        if scrubbing == Some(true) {
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                let mut time = self.space.clipped_to_unit(pos.x);
+                if let Some(snap) = &self.snap {
+                    time = snap.apply(
+                        time,
+                        &ui.input(|i| i.modifiers),
+                        self.space.pixels_per_unit,
+                        &self.snap_targets(None),
+                        &self.marker_times(),
+                    );
+                }
+                notify_scrub(ui, scrub_id, current_time, time, true, &mut self.on_scrub);
+                result.scrubbed_time = Some(time);
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| {
+                    data.remove::<bool>(scrub_id.with("active"));
+                    data.remove::<TimeTick>(scrub_id);
+                });
+            }
+        } else if let Some(drag) = self.retime_drag(ui, &response, rect, retime_id) {
+            // Row block dragging (retime).
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<RetimeDrag>(retime_id));
+                if let Some((_, time_offset)) = retime_preview
+                    && time_offset != drag.original_offset
+                {
+                    result.commands.push(AnimationCommand::SetTrackRetime {
+                        track_id: drag.track_id,
+                        time_offset,
+                        time_scale: self.provider.track_time_scale(drag.track_id),
+                    });
+                }
+            }
+        } else if let Some((mut box_state, start)) = self.box_selection(ui, &response, rect, box_id)
+        {
+            // Box selection on empty space, spanning rows. Aggregate dots
+            // select all of their children. Shift adds, Ctrl/Cmd toggles.
+            if let Some(pos) = response.interact_pointer_pos() {
+                let selection_rect = Rect::from_two_pos(start, pos);
+                let color = ui.visuals().selection.bg_fill;
+                painter.rect_filled(selection_rect, 0.0, color.linear_multiply(0.2));
+                painter.rect_stroke(
+                    selection_rect,
+                    0.0,
+                    Stroke::new(1.0, color),
+                    egui::StrokeKind::Inside,
+                );
+
+                if response.drag_stopped() {
+                    let mut seen = HashSet::default();
+                    result.box_selected = keyframe_positions
+                        .iter()
+                        .filter(|(_, kf_pos, _)| selection_rect.contains(*kf_pos))
+                        .map(|(kf_id, _, _)| *kf_id)
+                        .filter(|kf_id| seen.insert(*kf_id))
+                        .collect();
+                    result.box_select_mode =
+                        Some(ui.input(|i| self.key_bindings.selection_mode(&i.modifiers)));
+                }
+            }
+            if response.drag_stopped() {
+                box_state.end_box_selection();
+                ui.data_mut(|data| data.remove::<SelectionState>(box_id));
+            } else {
+                ui.data_mut(|data| data.insert_temp(box_id, box_state));
+            }
+        } else if let Some(pos) = response.interact_pointer_pos() {
             // Check for keyframe clicks
             if response.clicked() {
                 for (kf_id, kf_pos, _) in &keyframe_positions {
                     let dx = (pos.x - kf_pos.x).abs();
                     let dy = (pos.y - kf_pos.y).abs();
-                    if dx + dy < 10.0 {
+                    if dx + dy < self.interaction.keyframe_hit {
                         result.clicked_keyframe = Some(*kf_id);
                         break;
                     }
@@ -199,14 +549,338 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
         result
     }
 
+    /// The row block drag (retime) in progress, starting one if `response`
+    /// starts a drag with the retime modifiers on an unlocked track row.
+    fn retime_drag(
+        &self,
+        ui: &Ui,
+        response: &Response,
+        rect: Rect,
+        retime_id: egui::Id,
+    ) -> Option<RetimeDrag> {
+        if response.drag_started()
+            && !self.key_bindings.retime.is_none()
+            && ui.input(|i| i.modifiers.matches_exact(self.key_bindings.retime))
+            && let Some(pos) = response.interact_pointer_pos()
+            && let Some(track_id) = self
+                .row_at(rect, pos.y)
+                .filter(|row| !row.locked)
+                .and_then(|row| row.track_id)
+        {
+            let drag = RetimeDrag {
+                track_id,
+                start_time: self.space.clipped_to_unit(pos.x),
+                original_offset: self.provider.track_time_offset(track_id),
+            };
+            ui.data_mut(|data| data.insert_temp(retime_id, drag));
+        }
+        ui.data(|data| data.get_temp::<RetimeDrag>(retime_id))
+    }
+
+    /// The box selection in progress and its start, starting one if
+    /// `response` starts a primary drag on a row or the ruler.
+    fn box_selection(
+        &self,
+        ui: &Ui,
+        response: &Response,
+        rect: Rect,
+        box_id: egui::Id,
+    ) -> Option<(SelectionState, Pos2)> {
+        let mut box_state: SelectionState =
+            ui.data(|data| data.get_temp(box_id)).unwrap_or_default();
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && let Some(pos) = response.interact_pointer_pos()
+            && matches!(
+                self.hit_test(rect, pos),
+                Some(DopeSheetHit::Row { .. } | DopeSheetHit::RulerTime(_))
+            )
+        {
+            box_state.start_box_selection(pos);
+        }
+        let start = box_state.box_start.filter(|_| box_state.box_selecting)?;
+        Some((box_state, start))
+    }
+
+    /// Commands flipping the selected keyframes of unlocked tracks in time
+    /// or value around the anchor, one per track in row order.
+    ///
+    /// Times are flipped around one global anchor; values around an anchor
+    /// per track, as tracks have different units.
+    fn flip_commands(&self, time: bool) -> Vec<AnimationCommand> {
+        let playhead = self.provider.current_time();
+        let tracks = self.selected_by_track();
+
+        let anchor_time =
+            WorldBoundingBox::from_points(tracks.iter().flat_map(|(_, keyframes)| {
+                keyframes.iter().map(|(_, time, value)| (*time, *value))
+            }))
+            .map(|bbox| bbox.anchor_mode(self.anchor_mode, playhead).anchor.0);
+        tracks
+            .into_iter()
+            .filter_map(|(track_id, keyframes)| {
+                let keyframe_ids = keyframes.iter().map(|(id, ..)| *id).collect();
+                if time {
+                    let anchor = retime_to_local(
+                        anchor_time?,
+                        self.provider.track_time_offset(track_id),
+                        self.provider.track_time_scale(track_id),
+                    );
+                    Some(AnimationCommand::FlipKeyframesTime {
+                        keyframe_ids,
+                        anchor_time: anchor,
+                    })
+                } else {
+                    let bbox = WorldBoundingBox::from_points(
+                        keyframes.iter().map(|(_, time, value)| (*time, *value)),
+                    )?;
+                    Some(AnimationCommand::FlipKeyframesValue {
+                        keyframe_ids,
+                        anchor_value: bbox.anchor_mode(self.anchor_mode, playhead).anchor.1 as f64,
+                    })
+                }
+            })
+            .collect()
+    }
+
+    /// Commands inserting a breakdown at `fraction` of the way between the
+    /// two selected keyframes of each unlocked track with exactly two.
+    fn breakdown_commands(&self, fraction: f64) -> Vec<AnimationCommand> {
+        self.selected_by_track()
+            .into_iter()
+            .filter_map(|(_, keyframes)| match keyframes[..] {
+                [(from, ..), (to, ..)] => Some(AnimationCommand::InsertBreakdown {
+                    from,
+                    to,
+                    keyframe_id: KeyframeId::new(),
+                    fraction,
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Command locking or unlocking the selected keyframes that are not
+    /// already, if any.
+    fn lock_command(&self, locked: bool) -> Option<AnimationCommand> {
+        let keyframe_ids: Vec<KeyframeId> = self
+            .provider
+            .property_rows()
+            .into_iter()
+            .filter_map(|row| row.track_id)
+            .flat_map(|track_id| {
+                self.provider
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(id, _)| id)
+                    .filter(move |id| {
+                        self.selected_keyframes.contains(id)
+                            && self.provider.keyframe_locked(track_id, *id) != locked
+                    })
+            })
+            .collect();
+        (!keyframe_ids.is_empty()).then_some(AnimationCommand::SetKeyframesLocked {
+            keyframe_ids,
+            locked,
+        })
+    }
+
+    /// Commands setting the tag of the selected keyframes of unlocked tracks.
+    fn tag_commands(&self, tag: Option<KeyframeTag>) -> Vec<AnimationCommand> {
+        self.selected_by_track()
+            .into_iter()
+            .flat_map(|(track_id, keyframes)| {
+                keyframes
+                    .into_iter()
+                    .map(|(id, ..)| id)
+                    .filter(move |id| self.provider.keyframe_tag(track_id, *id) != tag)
+            })
+            .map(|keyframe_id| AnimationCommand::SetKeyframeTag { keyframe_id, tag })
+            .collect()
+    }
+
+    /// The selected, unlocked keyframes of unlocked tracks, in row order and
+    /// then time order.
+    fn selected_by_track(&self) -> Vec<(TrackId, Vec<SelectedKey>)> {
+        self.provider
+            .property_rows()
+            .into_iter()
+            .filter(|row| !row.locked)
+            .filter_map(|row| row.track_id)
+            .map(|track_id| {
+                let offset = self.provider.track_time_offset(track_id);
+                let scale = self.provider.track_time_scale(track_id);
+                let mut keyframes: Vec<_> = self
+                    .provider
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|(id, _)| {
+                        self.selected_keyframes.contains(id)
+                            && !self.provider.keyframe_locked(track_id, *id)
+                    })
+                    .filter_map(|(id, position)| {
+                        let value = self.provider.keyframe_value(track_id, id)? as f32;
+                        Some((id, retime_to_global(position, offset, scale), value))
+                    })
+                    .collect();
+                keyframes
+                    .sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal));
+                (track_id, keyframes)
+            })
+            .filter(|(_, keyframes)| !keyframes.is_empty())
+            .collect()
+    }
+
+    /// Hit-test a screen position against the rows, keyframes and playhead
+    /// rendered into `rect`.
+    pub fn hit_test(&self, rect: Rect, pos: Pos2) -> Option<DopeSheetHit> {
+        if !rect.contains(pos) {
+            return None;
+        }
+
+        let time = self.space.clipped_to_unit(pos.x);
+        let current_time = self.provider.current_time();
+        if (self.space.unit_to_clipped(current_time) - pos.x).abs() <= self.playhead_grab() {
+            return Some(DopeSheetHit::Playhead { time: current_time });
+        }
+
+        let index = ((pos.y - rect.top()) / self.row_height).floor() as usize;
+        let Some(row) = self.rows.get(index) else {
+            return Some(DopeSheetHit::RulerTime(time));
+        };
+        let y_center = rect.top() + (index as f32 + 0.5) * self.row_height;
+        let hits =
+            |x: f32| (pos.x - x).abs() + (pos.y - y_center).abs() < self.interaction.keyframe_hit;
+
+        if let Some(track_id) = row.track_id {
+            let keyframe = self
+                .provider
+                .keyframe_positions(track_id)
+                .into_iter()
+                .flatten()
+                .map(|(kf_id, position)| (kf_id, self.display_time(track_id, position, None)))
+                .find(|(_, time)| hits(self.space.unit_to_clipped(*time)));
+            if let Some((keyframe_id, time)) = keyframe {
+                return Some(DopeSheetHit::Keyframe {
+                    row_id: row.id.clone(),
+                    keyframe_id,
+                    time,
+                });
+            }
+        } else if self.show_aggregates && row.can_collapse {
+            let aggregate = self
+                .collect_aggregates(row, index, None)
+                .into_iter()
+                .map(|(quantized_ms, kf_ids)| (TimeTick::new(quantized_ms as f64 / 1000.0), kf_ids))
+                .find(|(time, _)| hits(self.space.unit_to_clipped(*time)));
+            if let Some((time, keyframe_ids)) = aggregate {
+                return Some(DopeSheetHit::Aggregate {
+                    row_id: row.id.clone(),
+                    keyframe_ids,
+                    time,
+                });
+            }
+        }
+
+        Some(DopeSheetHit::Row {
+            row_id: row.id.clone(),
+            time: Some(time),
+        })
+    }
+
+    /// Row at a screen-space y coordinate.
+    fn row_at(&self, rect: Rect, y: f32) -> Option<&PropertyRow> {
+        let index = ((y - rect.top()) / self.row_height).floor();
+        (index >= 0.0)
+            .then(|| self.rows.get(index as usize))
+            .flatten()
+    }
+
+    /// Half width of the zone in which the pointer grabs the playhead.
+    fn playhead_grab(&self) -> f32 {
+        self.playhead.grab_radius_or(self.interaction.edge_grab)
+    }
+
+    /// Marker times to snap to.
+    fn marker_times(&self) -> Vec<TimeTick> {
+        self.markers.map(Markers::times).unwrap_or_default()
+    }
+
+    /// Displayed times of the keyframes on all rows except `exclude`.
+    fn snap_targets(&self, exclude: Option<TrackId>) -> Vec<TimeTick> {
+        self.rows
+            .iter()
+            .filter_map(|row| row.track_id)
+            .filter(|track_id| Some(*track_id) != exclude)
+            .flat_map(|track_id| {
+                self.provider
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(move |(_, position)| self.display_time(track_id, position, None))
+            })
+            .collect()
+    }
+
+    /// Adjust a dragged track offset so the track's first keyframe snaps.
+    fn snap_offset(&self, ui: &Ui, track_id: TrackId, offset: TimeTick) -> TimeTick {
+        let Some(snap) = &self.snap else {
+            return offset;
+        };
+        let Some(first) = self
+            .provider
+            .keyframe_positions(track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, position)| position)
+            .reduce(TimeTick::min)
+        else {
+            return offset;
+        };
+
+        let time = self.display_time(track_id, first, Some((track_id, offset)));
+        let snapped = snap.apply(
+            time,
+            &ui.input(|i| i.modifiers),
+            self.space.pixels_per_unit,
+            &self.snap_targets(Some(track_id)),
+            &self.marker_times(),
+        );
+        offset + (snapped - time)
+    }
+
+    /// Map a track-local keyframe position to displayed (global) time.
+    ///
+    /// `preview` overrides the time offset of one track during a row drag.
+    fn display_time(
+        &self,
+        track_id: TrackId,
+        position: TimeTick,
+        preview: Option<(TrackId, TimeTick)>,
+    ) -> TimeTick {
+        let time_offset = match preview {
+            Some((preview_track, offset)) if preview_track == track_id => offset,
+            _ => self.provider.track_time_offset(track_id),
+        };
+        retime_to_global(
+            position,
+            time_offset,
+            self.provider.track_time_scale(track_id),
+        )
+    }
+
     /// Collect aggregate keyframes for a parent row.
     /// Returns a map from quantized time (milliseconds as i64) to keyframe IDs.
     fn collect_aggregates(
         &self,
         parent_row: &PropertyRow,
         parent_index: usize,
-    ) -> HashMap<i64, Vec<KeyframeId>> {
-        let mut aggregates: HashMap<i64, Vec<KeyframeId>> = HashMap::new();
+        preview: Option<(TrackId, TimeTick)>,
+    ) -> BTreeMap<i64, Vec<KeyframeId>> {
+        // Sorted by time so drawing, hit testing and box selection do not
+        // depend on hash order.
+        let mut aggregates: BTreeMap<i64, Vec<KeyframeId>> = BTreeMap::new();
 
         // Find all child rows.
         let parent_depth = parent_row.depth;
@@ -220,6 +894,7 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
                 && let Some(positions) = self.provider.keyframe_positions(track_id)
             {
                 for (kf_id, position) in positions {
+                    let position = self.display_time(track_id, position, preview);
                     // Quantize to avoid floating point issues (millisecond precision)
                     let quantized = (position.value() * 1000.0).round() as i64;
                     aggregates.entry(quantized).or_default().push(kf_id);
@@ -230,3 +905,150 @@ impl<'a, P: AnimationDataProvider> TrackArea<'a, P> {
         aggregates
     }
 }
+
+/// Menu button for `tag`, in its color.
+fn tag_button(ui: &mut Ui, tag: Option<KeyframeTag>) -> egui::Response {
+    match tag {
+        Some(tag) => {
+            ui.button(egui::RichText::new(format!("● {}", tag.label())).color(tag.color()))
+        }
+        None => ui.button("None"),
+    }
+}
+
+/// Paint the values a stepped track holds between its keys, given as screen
+/// x and value sorted by x.
+fn paint_stepped_lane(
+    painter: &egui::Painter,
+    row_rect: Rect,
+    lane: &TrackLane,
+    color: Color32,
+    keys: &[(f32, f64)],
+) {
+    let bar = row_rect.shrink2(Vec2::new(0.0, row_rect.height() * 0.25));
+    for (index, &(x, value)) in keys.iter().enumerate() {
+        let end = keys.get(index + 1).map_or(row_rect.right(), |next| next.0);
+        let segment = Rect::from_x_y_ranges(x.max(row_rect.left())..=end, bar.y_range());
+        if segment.width() <= 0.0 {
+            continue;
+        }
+        match lane {
+            TrackLane::Keyframes | TrackLane::Color => {}
+            TrackLane::Toggle => {
+                if value != 0.0 {
+                    painter.rect_filled(segment, 2.0, color.gamma_multiply(0.6));
+                }
+            }
+            TrackLane::Enum { labels } => {
+                let shade = if index % 2 == 0 { 0.45 } else { 0.3 };
+                painter.rect_filled(segment, 2.0, color.gamma_multiply(shade));
+                let state = value.round().max(0.0) as usize;
+                let label = labels
+                    .get(state)
+                    .cloned()
+                    .unwrap_or_else(|| format!("{}", value.round()));
+                painter.with_clip_rect(segment.shrink(1.0)).text(
+                    Pos2::new(segment.left() + 4.0, segment.center().y),
+                    egui::Align2::LEFT_CENTER,
+                    label,
+                    egui::FontId::proportional(10.0),
+                    Color32::from_gray(230),
+                );
+            }
+        }
+    }
+}
+
+/// Paint the backdrop of an animation layer row: a band inset from the row
+/// so consecutive layers read as a stack, labeled with the blend mode.
+fn paint_layer_lane(painter: &egui::Painter, row_rect: Rect, blend: BlendMode, color: Color32) {
+    let band = row_rect.shrink2(Vec2::new(0.0, 1.0));
+    painter.rect_filled(band, 0.0, color.gamma_multiply(0.12));
+    painter.rect_filled(
+        Rect::from_min_size(band.min, Vec2::new(3.0, band.height())),
+        0.0,
+        color.gamma_multiply(0.6),
+    );
+    painter.text(
+        Pos2::new(band.right() - 4.0, band.center().y),
+        egui::Align2::RIGHT_CENTER,
+        blend.label(),
+        egui::FontId::proportional(10.0),
+        color.gamma_multiply(0.8),
+    );
+}
+
+/// Paint a gradient strip over `range` of screen x, sampling `color_at`
+/// every few pixels.
+fn paint_color_lane(
+    painter: &egui::Painter,
+    row_rect: Rect,
+    range: std::ops::RangeInclusive<f32>,
+    color_at: impl Fn(f32) -> Option<Color32>,
+) {
+    const STEP: f32 = 4.0;
+    let start = range.start().max(row_rect.left());
+    let end = range.end().min(row_rect.right());
+    if end <= start {
+        return;
+    }
+    let bar = row_rect.shrink2(Vec2::new(0.0, row_rect.height() * 0.25));
+    let mut mesh = egui::Mesh::default();
+    let columns = ((end - start) / STEP).ceil() as u32;
+    for column in 0..=columns {
+        let x = (start + column as f32 * STEP).min(end);
+        let color = color_at(x).unwrap_or(Color32::TRANSPARENT);
+        mesh.colored_vertex(Pos2::new(x, bar.top()), color);
+        mesh.colored_vertex(Pos2::new(x, bar.bottom()), color);
+        if column > 0 {
+            let i = column * 2;
+            mesh.add_triangle(i - 2, i - 1, i);
+            mesh.add_triangle(i - 1, i + 1, i);
+        }
+    }
+    painter.add(mesh);
+    painter.rect_stroke(
+        Rect::from_x_y_ranges(start..=end, bar.y_range()),
+        0.0,
+        Stroke::new(1.0, Color32::from_gray(20)),
+        egui::StrokeKind::Outside,
+    );
+}
+
+/// Paint the key of a stepped lane as a tick across the lane.
+fn paint_step_key(
+    painter: &egui::Painter,
+    row_rect: Rect,
+    x: f32,
+    color: Color32,
+    state: KeyframeVisualState,
+) {
+    let color = state.tag.map_or(color, KeyframeTag::color);
+    let mut color = if state.selected {
+        Color32::from_rgb(255, 200, 100)
+    } else if state.hovered {
+        Color32::WHITE
+    } else if state.breakdown {
+        color.lerp_to_gamma(BREAKDOWN_TINT, 0.6)
+    } else {
+        color
+    };
+    if state.locked {
+        color = desaturated(color);
+    }
+    if state.muted {
+        color = muted(color);
+    }
+    let width = if state.selected || state.hovered {
+        3.0
+    } else {
+        2.0
+    };
+    // Breakdowns get shorter ticks.
+    let height = if state.breakdown { 0.45 } else { 0.7 };
+    let tick = Rect::from_center_size(
+        Pos2::new(x, row_rect.center().y),
+        Vec2::new(width, row_rect.height() * height),
+    );
+    painter.rect_filled(tick, 1.0, color);
+}
@@ -0,0 +1,116 @@
+//! Settings shared by the editing widgets and the host.
+//!
+//! With [`EditorSettings::auto_key`] on, value edits are keyed at the
+//! playhead. The [`KeyframeInspector`](crate::KeyframeInspector) does this
+//! itself; hosts turn the edits of their own value sliders into commands:
+//!
+//! ```ignore
+//! if ui.add(egui::Slider::new(&mut opacity, 0.0..=1.0)).changed() {
+//!     for command in settings.value_edit_commands(&model, track_id, opacity) {
+//!         model.execute(command);
+//!     }
+//! }
+//! ```
+
+use crate::core::track::{KEY_TIME_TOLERANCE, TrackId, retime_to_local};
+use crate::traits::{AnimationCommand, AnimationDataProvider};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Editor-wide settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct EditorSettings {
+    /// Key value edits at the playhead.
+    pub auto_key: bool,
+}
+
+impl EditorSettings {
+    /// Set whether value edits are keyed at the playhead.
+    pub fn auto_key(mut self, auto_key: bool) -> Self {
+        self.auto_key = auto_key;
+        self
+    }
+
+    /// Commands keying `value` on `track_id` at the playhead: a
+    /// [`SetKeyframeValue`](AnimationCommand::SetKeyframeValue) if the track
+    /// has a keyframe there, an [`AddKeyframe`](AnimationCommand::AddKeyframe)
+    /// otherwise.
+    ///
+    /// Empty with auto-key off; the host then applies the value as it
+    /// would without animation.
+    pub fn value_edit_commands<P: AnimationDataProvider + ?Sized>(
+        &self,
+        provider: &P,
+        track_id: TrackId,
+        value: f64,
+    ) -> Vec<AnimationCommand> {
+        if !self.auto_key {
+            return Vec::new();
+        }
+        let position = retime_to_local(
+            provider.current_time(),
+            provider.track_time_offset(track_id),
+            provider.track_time_scale(track_id),
+        );
+        let keyed = provider
+            .keyframe_positions(track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .find(|(_, time)| (*time - position).value().abs() < KEY_TIME_TOLERANCE);
+        vec![match keyed {
+            Some((keyframe_id, _)) => AnimationCommand::SetKeyframeValue { keyframe_id, value },
+            None => AnimationCommand::AddKeyframe {
+                track_id,
+                position,
+                value,
+            },
+        }]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleAnimationModel;
+    use crate::core::keyframe::Keyframe;
+    use crate::core::time::TimeTick;
+    use crate::core::track::Track;
+    use crate::traits::AnimationDataMutator;
+
+    #[test]
+    fn auto_key_sets_or_adds_at_the_playhead() {
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Ball");
+        let mut track = Track::new();
+        let key = track.add_keyframe(Keyframe::new(0.0, 1.0_f32));
+        let track_id = model.add_track(&group, "Y", track).unwrap();
+
+        let settings = EditorSettings::default();
+        assert!(
+            settings
+                .value_edit_commands(&model, track_id, 2.0)
+                .is_empty()
+        );
+
+        let settings = settings.auto_key(true);
+        for command in settings.value_edit_commands(&model, track_id, 2.0) {
+            model.execute(command);
+        }
+        model.execute(AnimationCommand::SetCurrentTime(TimeTick::new(1.0)));
+        for command in settings.value_edit_commands(&model, track_id, 3.0) {
+            model.execute(command);
+        }
+        // Keyed again at a time that differs from the new key by rounding.
+        model.execute(AnimationCommand::SetCurrentTime(TimeTick::new(1.0 + 1e-12)));
+        for command in settings.value_edit_commands(&model, track_id, 4.0) {
+            model.execute(command);
+        }
+
+        let track = model.track(track_id).unwrap();
+        assert_eq!(track.len(), 2);
+        assert_eq!(track.get_keyframe(key).unwrap().value, 2.0);
+        assert_eq!(track.evaluate(1.0), Some(4.0));
+    }
+}
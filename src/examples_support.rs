@@ -0,0 +1,365 @@
+//! Demo data model and timeline window.
+//!
+//! [`DemoApp`] wires a [`SimpleAnimationModel`] filled by [`demo_model`] to a
+//! [`TransportBar`] and a [`TimelinePanel`], with selection, playback, undo
+//! and redo handled the way a host application would. It is the starting
+//! point the examples build on; copy it and replace the model with your own
+//! [`AnimationDataProvider`] and [`AnimationDataMutator`].
+//!
+//! ```ignore
+//! let mut demo = DemoApp::new();
+//! egui::CentralPanel::default().show(ctx, |ui| demo.show(ui));
+//! ```
+//!
+//! The sample tracks are built by plain functions so tests can check the
+//! curves the demo shows.
+
+use crate::HashSet;
+use crate::core::keyframe::{BezierHandles, Keyframe, KeyframeType};
+use crate::core::marker::{Marker, Markers};
+use crate::core::track::Track;
+use crate::dopesheet::{SelectionMode, SelectionState};
+use crate::model::SimpleAnimationModel;
+use crate::playback::PlaybackController;
+use crate::spaces::SpaceTransform;
+use crate::traits::{AnimationCommand, AnimationDataMutator, AnimationDataProvider};
+use crate::transport::Transport;
+use crate::widgets::{TimelinePanel, TimelinePanelResponse, TransportBar};
+use egui::{Context, Key, KeyboardShortcut, Modifiers, Ui};
+
+/// Frame rate of the demo.
+pub const DEMO_FPS: f64 = 24.0;
+
+/// Length of the demo animation in seconds.
+pub const DEMO_DURATION: f64 = 4.0;
+
+const UNDO: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::Z);
+const REDO: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::Z);
+
+/// Height of a ball bouncing three times with decaying peaks, from 1 to 0.
+pub fn bounce_track() -> Track<f32> {
+    let mut track = Track::new();
+    track.add_keyframe(Keyframe::new(0.0, 1.0).with_handles(BezierHandles::ease_in()));
+    let peaks = [0.5, 0.25];
+    let mut time = 0.6;
+    for peak in peaks {
+        track.add_keyframe(Keyframe::new(time, 0.0).with_handles(BezierHandles::ease_out()));
+        let hang = (peak as f64).sqrt() * 0.6;
+        track.add_keyframe(Keyframe::new(time + hang, peak).with_handles(BezierHandles::ease_in()));
+        time += 2.0 * hang;
+    }
+    track.add_keyframe(Keyframe::new(time, 0.0).with_type(KeyframeType::Linear));
+    track
+}
+
+/// Horizontal position easing in and out from 0 to 10 over the demo.
+pub fn slide_track() -> Track<f32> {
+    let mut track = Track::new();
+    track.add_keyframe(Keyframe::new(0.0, 0.0).with_handles(BezierHandles::ease_in_out()));
+    track.add_keyframe(
+        Keyframe::new(DEMO_DURATION, 10.0).with_handles(BezierHandles::ease_in_out()),
+    );
+    track
+}
+
+/// Rotation in degrees turning at constant speed, one turn over the demo.
+pub fn spin_track() -> Track<f32> {
+    let mut track = Track::new();
+    track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+    track.add_keyframe(Keyframe::new(DEMO_DURATION, 360.0).with_type(KeyframeType::Linear));
+    track
+}
+
+/// Opacity switching between 1 and 0 every second.
+pub fn blink_track() -> Track<f32> {
+    let mut track = Track::new();
+    for second in 0..=DEMO_DURATION as usize {
+        let value = if second % 2 == 0 { 1.0 } else { 0.0 };
+        track.add_keyframe(Keyframe::new(second as f64, value).with_type(KeyframeType::Hold));
+    }
+    track
+}
+
+/// A model with a `Ball` group holding [`bounce_track`] and
+/// [`slide_track`] and a `Box` group holding [`spin_track`] and
+/// [`blink_track`], limited to `0..=1`.
+pub fn demo_model() -> SimpleAnimationModel {
+    let mut model = SimpleAnimationModel::new().with_time_range(0.0, DEMO_DURATION);
+    let ball = model.add_group("Ball");
+    let height = model.add_track(&ball, "Height", bounce_track());
+    model.add_track(&ball, "Position X", slide_track());
+    let cube = model.add_group("Box");
+    model.add_track(&cube, "Rotation", spin_track());
+    if let Some(opacity) = model.add_track(&cube, "Opacity", blink_track()) {
+        model.set_value_limits(opacity, Some((0.0, 1.0)));
+    }
+    model.set_active_track(height);
+    *model.markers_mut() = demo_markers();
+    model
+}
+
+/// Markers at the bounces of [`bounce_track`].
+pub fn demo_markers() -> Markers {
+    let mut markers = Markers::new();
+    for (index, kf) in bounce_track()
+        .keyframes_sorted()
+        .into_iter()
+        .filter(|kf| kf.value == 0.0)
+        .enumerate()
+    {
+        markers.add(Marker::new(kf.position, format!("Bounce {}", index + 1)));
+    }
+    markers
+}
+
+/// A complete timeline over [`demo_model`]: transport bar on top, timeline
+/// panel below.
+///
+/// Space toggles playback, the transport shortcuts step through frames,
+/// keyframes and markers, and Cmd/Ctrl+Z and Cmd/Ctrl+Shift+Z undo and
+/// redo. Edits during one drag form one undo step.
+#[derive(Debug, Clone)]
+pub struct DemoApp {
+    /// The animation data.
+    pub model: SimpleAnimationModel,
+    /// Selected keyframes and rows.
+    pub selection: SelectionState,
+    /// Playback state.
+    pub playback: PlaybackController,
+    /// Time axis shared by all parts of the timeline.
+    pub space: SpaceTransform,
+    /// Frame rate for the ruler, snapping and frame stepping.
+    pub fps: f64,
+    dragging: bool,
+}
+
+impl Default for DemoApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoApp {
+    /// Create the demo over [`demo_model`].
+    pub fn new() -> Self {
+        Self::with_model(demo_model())
+    }
+
+    /// Create the demo over `model`, playing its whole time range.
+    pub fn with_model(model: SimpleAnimationModel) -> Self {
+        let (start, end) = model.time_range();
+        Self {
+            model,
+            selection: SelectionState::new(),
+            playback: PlaybackController::new(start, end),
+            space: SpaceTransform::default().fit_range(start, end, 0.05),
+            fps: DEMO_FPS,
+            dragging: false,
+        }
+    }
+
+    /// Show the demo in a window titled `Timeline`.
+    pub fn show_window(&mut self, ctx: &Context, open: &mut bool) {
+        egui::Window::new("Timeline")
+            .open(open)
+            .default_size([800.0, 500.0])
+            .show(ctx, |ui| self.show(ui));
+    }
+
+    /// Show the transport bar and timeline panel in the available space.
+    pub fn show(&mut self, ui: &mut Ui) {
+        let playback = self.playback.update(ui.ctx());
+        self.execute(playback.commands);
+        self.handle_shortcuts(ui.ctx());
+
+        ui.horizontal(|ui| {
+            let transport = TransportBar::new(&mut self.playback, self.fps).show(ui);
+            ui.separator();
+            let undo = ui
+                .add_enabled(self.model.history().can_undo(), egui::Button::new("Undo"))
+                .clicked();
+            let redo = ui
+                .add_enabled(self.model.history().can_redo(), egui::Button::new("Redo"))
+                .clicked();
+            self.execute(transport.commands);
+            if undo {
+                self.model.undo();
+            }
+            if redo {
+                self.model.redo();
+            }
+        });
+
+        let mut panel = TimelinePanel::new(
+            &self.model,
+            &self.space,
+            &self.selection.keyframes,
+            &self.selection.rows,
+        )
+        .fps(self.fps as f32)
+        .markers(self.model.markers())
+        .id_source("demo_timeline");
+        if let Some((start, end)) = self.model.work_range() {
+            panel = panel.work_range(start, end);
+        }
+        let response = panel.show(ui);
+        self.apply_response(ui.ctx(), response);
+    }
+
+    /// Execute commands on the model and keep playback in sync.
+    pub fn execute(&mut self, commands: impl IntoIterator<Item = AnimationCommand>) {
+        for command in commands {
+            self.playback.apply_command(&command);
+            self.model.execute(command);
+        }
+    }
+
+    fn handle_shortcuts(&mut self, ctx: &Context) {
+        if ctx.wants_keyboard_input() {
+            return;
+        }
+        // Check redo first; its shortcut includes undo's.
+        if ctx.input_mut(|i| i.consume_shortcut(&REDO)) {
+            self.model.redo();
+        } else if ctx.input_mut(|i| i.consume_shortcut(&UNDO)) {
+            self.model.undo();
+        }
+        if ctx.input(|i| i.key_pressed(Key::Space)) {
+            self.playback.toggle();
+        }
+
+        let marker_times = self.model.markers().times();
+        let time = Transport::new(&self.model, self.fps)
+            .selected_rows(&self.selection.rows)
+            .markers(&marker_times)
+            .handle_input(ctx);
+        if let Some(time) = time {
+            self.playback.pause();
+            self.execute([AnimationCommand::SetCurrentTime(time)]);
+        }
+    }
+
+    fn apply_response(&mut self, ctx: &Context, mut response: TimelinePanelResponse) {
+        if let Some(space) = response.space {
+            self.space = space;
+        }
+
+        let mode = ctx.input(|i| SelectionMode::from_modifiers(&i.modifiers));
+        let selection = &mut self.selection;
+        let dope_sheet = &response.dope_sheet;
+        if let Some(row_id) = &dope_sheet.clicked_row {
+            match mode {
+                SelectionMode::Toggle => selection.toggle_row(row_id),
+                mode => selection.select_row(row_id.clone(), mode == SelectionMode::Add),
+            }
+        }
+        let clicked_keyframe = dope_sheet.clicked_keyframe.or(response
+            .curve_editor
+            .as_ref()
+            .and_then(|curve| curve.clicked_keyframe));
+        if let Some(id) = clicked_keyframe {
+            match mode {
+                SelectionMode::Toggle => selection.toggle_keyframe(id),
+                mode => selection.select_keyframe(id, mode == SelectionMode::Add),
+            }
+        } else if dope_sheet.clicked_time.is_some() && mode == SelectionMode::Replace {
+            selection.clear_keyframes();
+        }
+        if let Some(mode) = dope_sheet.box_select_mode {
+            selection.apply_box_selection(dope_sheet.box_selected.iter().copied(), mode);
+        }
+        if let Some(ids) = &dope_sheet.select_keyframes {
+            selection.select_keyframes(ids.iter().copied(), false);
+        }
+        if let Some(curve) = &response.curve_editor {
+            if let Some((start, end)) = curve.clicked_segment {
+                selection.apply_box_selection([start, end], mode);
+            }
+            if let Some((ids, mode)) = &curve.box_selected {
+                selection.apply_box_selection(ids.iter().copied(), *mode);
+            }
+            if curve.deselect_all {
+                selection.clear_keyframes();
+            }
+            if curve.select_all
+                && let Some(track_id) = response.curve_track
+            {
+                let ids = self
+                    .model
+                    .keyframe_positions(track_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|(id, _)| id);
+                selection.select_keyframes(ids, false);
+            }
+        }
+        if let Some(row_id) = &dope_sheet.toggle_collapse {
+            self.model
+                .execute(AnimationCommand::ToggleRowCollapse(row_id.clone()));
+        }
+
+        // Group the edits of one drag into a single undo step.
+        let pointer_down = ctx.input(|i| i.pointer.primary_down());
+        let edits = response
+            .commands
+            .iter()
+            .any(|command| !matches!(command, AnimationCommand::SetCurrentTime(_)));
+        if pointer_down && edits && !self.dragging {
+            self.model.begin_scrub();
+            self.dragging = true;
+        }
+        self.execute(response.commands.drain(..));
+        if !pointer_down && self.dragging {
+            self.model.end_scrub(true);
+            self.dragging = false;
+        }
+
+        // Keyframes may have been deleted or undone away.
+        let existing: HashSet<_> = self
+            .model
+            .track_ids()
+            .into_iter()
+            .flat_map(|track_id| self.model.keyframe_positions(track_id).unwrap_or_default())
+            .map(|(id, _)| id)
+            .collect();
+        self.selection.keyframes.retain(|id| existing.contains(id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TimeTick;
+    use crate::widgets::offscreen::run_frame;
+
+    #[test]
+    fn demo_animates_and_shows() {
+        let bounce = bounce_track();
+        assert_eq!(bounce.evaluate(0.0), Some(1.0));
+        assert_eq!(bounce.evaluate(0.6), Some(0.0));
+        let last = *bounce.keyframes_sorted().last().unwrap();
+        assert_eq!(last.value, 0.0);
+        assert_eq!(slide_track().evaluate(DEMO_DURATION), Some(10.0));
+        assert_eq!(spin_track().evaluate(DEMO_DURATION / 2.0), Some(180.0));
+        assert_eq!(blink_track().evaluate(1.5), Some(0.0));
+        assert_eq!(demo_markers().len(), 3);
+
+        let mut demo = DemoApp::new();
+        assert_eq!(demo.model.property_rows().len(), 6);
+        let opacity = demo.model.property_rows()[5].track_id.unwrap();
+        let out_of_limits = AnimationCommand::AddKeyframe {
+            track_id: opacity,
+            position: TimeTick::new(0.5),
+            value: 2.0,
+        };
+        assert!(out_of_limits.validate(&demo.model).is_err());
+
+        demo.execute([AnimationCommand::SetCurrentTime(TimeTick::new(1.0))]);
+        assert_eq!(demo.playback.time(), TimeTick::new(1.0));
+        assert_eq!(demo.model.current_time(), TimeTick::new(1.0));
+
+        run_frame(egui::vec2(800.0, 400.0), 1.0, |ui| demo.show(ui));
+        assert_eq!(demo.model.current_time(), TimeTick::new(1.0));
+    }
+}
@@ -0,0 +1,248 @@
+//! "Nice" tick and grid intervals shared by rulers, grids and snapping.
+//!
+//! [`nice_intervals`] picks the smallest readable interval at least as large
+//! as an ideal one, e.g. the time covered by [`TIME_TICK_SPACING`] pixels.
+//! The [`TimeRuler`](crate::TimeRuler), the grids of the curve editors and
+//! dope sheet, the [`ValueRuler`](crate::ValueRuler) and grid snapping all use
+//! it, so hosts drawing their own ticks can match them exactly.
+
+/// Minimum distance in pixels between major time ticks and grid lines.
+pub const TIME_TICK_SPACING: f64 = 100.0;
+
+/// Minimum distance in pixels between major value ticks and grid lines.
+pub const VALUE_TICK_SPACING: f64 = 40.0;
+
+/// Intervals in seconds for [`IntervalContext::Seconds`]: decimal fractions
+/// of a second, then multiples that read well on a clock.
+pub const SECOND_INTERVALS: [f64; 20] = [
+    0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0, 30.0,
+    60.0, 120.0, 300.0, 600.0,
+];
+
+/// Intervals in frames below one second for [`IntervalContext::Frames`].
+pub const FRAME_INTERVALS: [f64; 4] = [1.0, 2.0, 5.0, 10.0];
+
+/// Mantissas of intervals for [`IntervalContext::Decimal`].
+pub const DECIMAL_STEPS: [f64; 3] = [1.0, 2.0, 5.0];
+
+/// Intervals in degrees and their minor step counts for
+/// [`IntervalContext::Degrees`].
+pub const DEGREE_INTERVALS: [(f64, usize); 7] = [
+    (1.0, 2),
+    (5.0, 5),
+    (15.0, 3),
+    (45.0, 3),
+    (90.0, 2),
+    (180.0, 2),
+    (360.0, 4),
+];
+
+/// Intervals in percent and their minor step counts for
+/// [`IntervalContext::Percent`].
+pub const PERCENT_INTERVALS: [(f64, usize); 6] = [
+    (1.0, 2),
+    (5.0, 5),
+    (10.0, 2),
+    (25.0, 5),
+    (50.0, 2),
+    (100.0, 4),
+];
+
+/// Intervals of a `0..=1` range and their minor step counts for
+/// [`IntervalContext::Normalized`].
+pub const NORMALIZED_INTERVALS: [(f64, usize); 5] =
+    [(0.05, 5), (0.1, 2), (0.25, 5), (0.5, 2), (1.0, 4)];
+
+/// Which intervals count as nice.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IntervalContext {
+    /// Time in seconds, see [`SECOND_INTERVALS`].
+    Seconds,
+    /// Time in seconds at a frame rate: whole [`FRAME_INTERVALS`] below one
+    /// second, [`SECOND_INTERVALS`] from one second on.
+    Frames(f64),
+    /// Plain values: 1, 2 or 5 times a power of ten.
+    Decimal,
+    /// Angles: [`DEGREE_INTERVALS`], decimal below one degree and multiples
+    /// of full turns above.
+    Degrees,
+    /// Percentages: [`PERCENT_INTERVALS`], decimal outside them.
+    Percent,
+    /// Fractions of one: [`NORMALIZED_INTERVALS`], decimal outside them.
+    Normalized,
+}
+
+impl IntervalContext {
+    /// [`Self::Frames`] at `fps` if given, [`Self::Seconds`] otherwise.
+    pub fn from_fps(fps: Option<f64>) -> Self {
+        fps.filter(|fps| *fps > 0.0)
+            .map_or(Self::Seconds, Self::Frames)
+    }
+}
+
+/// Major interval and its subdivision returned by [`nice_intervals`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct NiceIntervals {
+    /// Interval between major ticks or grid lines.
+    pub major: f64,
+    /// Number of minor steps per major interval, including the major tick.
+    pub minor_count: usize,
+}
+
+impl NiceIntervals {
+    /// Interval between minor ticks.
+    pub fn minor(&self) -> f64 {
+        self.major / self.minor_count.max(1) as f64
+    }
+}
+
+/// The smallest nice interval of `context` at least `ideal` long.
+///
+/// A non-positive or non-finite `ideal` gives a major interval of one.
+pub fn nice_intervals(ideal: f64, context: IntervalContext) -> NiceIntervals {
+    if !ideal.is_finite() || ideal <= 0.0 {
+        return NiceIntervals {
+            major: 1.0,
+            minor_count: seconds_minor_count(1.0),
+        };
+    }
+    match context {
+        IntervalContext::Seconds => {
+            let major = SECOND_INTERVALS
+                .into_iter()
+                .find(|interval| *interval >= ideal)
+                .unwrap_or_else(|| decimal_interval(ideal));
+            NiceIntervals {
+                major,
+                minor_count: seconds_minor_count(major),
+            }
+        }
+        IntervalContext::Frames(fps) => {
+            let frame = FRAME_INTERVALS
+                .into_iter()
+                .filter(|frames| *frames < fps)
+                .find(|frames| frames / fps >= ideal);
+            if let Some(frames) = frame {
+                NiceIntervals {
+                    major: frames / fps,
+                    // Every frame, or every other one for ten frames.
+                    minor_count: if frames > 5.0 { 5 } else { frames as usize },
+                }
+            } else {
+                let major = nice_intervals(ideal.max(1.0), IntervalContext::Seconds).major;
+                // Prefer minor ticks on whole frames.
+                let preferred = seconds_minor_count(major);
+                let minor_count = [preferred, 4, 5, 2]
+                    .into_iter()
+                    .find(|count| {
+                        let frames = major * fps / *count as f64;
+                        (frames - frames.round()).abs() < 1e-6
+                    })
+                    .unwrap_or(preferred);
+                NiceIntervals { major, minor_count }
+            }
+        }
+        IntervalContext::Decimal => NiceIntervals {
+            major: decimal_interval(ideal),
+            minor_count: 2,
+        },
+        IntervalContext::Degrees => {
+            if ideal > 360.0 {
+                NiceIntervals {
+                    major: 360.0 * decimal_interval(ideal / 360.0),
+                    minor_count: 2,
+                }
+            } else {
+                table_intervals(ideal, &DEGREE_INTERVALS)
+            }
+        }
+        IntervalContext::Percent => table_intervals(ideal, &PERCENT_INTERVALS),
+        IntervalContext::Normalized => table_intervals(ideal, &NORMALIZED_INTERVALS),
+    }
+}
+
+/// The smallest interval of `table` at least `ideal`, or a decimal one if
+/// `ideal` is below or above the whole table.
+fn table_intervals(ideal: f64, table: &[(f64, usize)]) -> NiceIntervals {
+    let smallest = table.first().map_or(0.0, |(major, _)| *major);
+    table
+        .iter()
+        .filter(|_| ideal > smallest / 2.0)
+        .find(|(major, _)| *major >= ideal)
+        .map_or_else(
+            || nice_intervals(ideal, IntervalContext::Decimal),
+            |&(major, minor_count)| NiceIntervals { major, minor_count },
+        )
+}
+
+/// The smallest 1, 2 or 5 times a power of ten at least `ideal`.
+fn decimal_interval(ideal: f64) -> f64 {
+    let magnitude = 10f64.powf(ideal.log10().floor());
+    DECIMAL_STEPS
+        .into_iter()
+        .map(|step| step * magnitude)
+        .find(|interval| *interval >= ideal)
+        .unwrap_or(10.0 * magnitude)
+}
+
+/// Minor steps per major interval of seconds.
+fn seconds_minor_count(major: f64) -> usize {
+    if major >= 1.0 && major != 5.0 && major != 10.0 {
+        4
+    } else {
+        5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn intervals_follow_context() {
+        let seconds = nice_intervals(0.3, IntervalContext::Seconds);
+        assert_eq!(
+            seconds,
+            NiceIntervals {
+                major: 0.5,
+                minor_count: 5
+            }
+        );
+        assert_eq!(
+            nice_intervals(1000.0, IntervalContext::Seconds).major,
+            1000.0
+        );
+
+        // 0.1 s at 24 fps is 2.4 frames, so 5 frames, minor ticks per frame.
+        let frames = nice_intervals(0.1, IntervalContext::Frames(24.0));
+        assert_eq!(frames.major, 5.0 / 24.0);
+        assert_eq!(frames.minor_count, 5);
+        // One second at 30 fps splits into 6 frame steps rather than 7.5.
+        let second = nice_intervals(0.8, IntervalContext::Frames(30.0));
+        assert_eq!(
+            second,
+            NiceIntervals {
+                major: 1.0,
+                minor_count: 5
+            }
+        );
+        assert_eq!(
+            nice_intervals(0.8, IntervalContext::Frames(24.0)).minor_count,
+            4
+        );
+
+        assert_eq!(nice_intervals(30.0, IntervalContext::Decimal).major, 50.0);
+        assert_eq!(nice_intervals(30.0, IntervalContext::Degrees).major, 45.0);
+        assert_eq!(nice_intervals(500.0, IntervalContext::Degrees).major, 720.0);
+        assert_eq!(nice_intervals(0.3, IntervalContext::Degrees).major, 0.5);
+        assert_eq!(
+            nice_intervals(12.0, IntervalContext::Percent),
+            NiceIntervals {
+                major: 25.0,
+                minor_count: 5
+            }
+        );
+        assert_eq!(nice_intervals(0.2, IntervalContext::Normalized).major, 0.25);
+        assert_eq!(nice_intervals(0.0, IntervalContext::Decimal).major, 1.0);
+    }
+}
@@ -0,0 +1,337 @@
+//! JSON files for persisting tracks.
+//!
+//! [`save_tracks`] and [`load_tracks`] read and write a versioned document
+//! that keeps track and keyframe IDs, so selections, undo history and host
+//! references survive a round trip. Times are in seconds.
+//!
+//! Version 1 of the schema:
+//!
+//! ```json
+//! {
+//!   "version": 1,
+//!   "tracks": [
+//!     {
+//!       "id": "5f0c…",
+//!       "name": "Cube.x",
+//!       "metadata": { "unit": "m" },
+//!       "time_offset": 0.0,
+//!       "time_scale": 1.0,
+//!       "locked": false,
+//!       "extrapolation_pre": "Constant",
+//!       "extrapolation_post": "Cycle",
+//!       "keyframes": [
+//!         {
+//!           "id": "9a31…",
+//!           "time": 1.0,
+//!           "value": 10.0,
+//!           "type": "Bezier",
+//!           "handles": { "left_x": 0.0, "left_y": 0.0, "right_x": 1.0, "right_y": 1.0 },
+//!           "weighted": null,
+//!           "spring": null,
+//!           "connected_right": true,
+//!           "pinned": false,
+//!           "locked": false,
+//!           "breakdown": false,
+//!           "label": "Contact",
+//!           "tag": "Red"
+//!         }
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+//!
+//! `metadata`, `time_offset`, `time_scale`, `locked`, the extrapolation
+//! modes, `weighted`, `spring`, `connected_right`, `pinned`, `breakdown`,
+//! `label` and `tag` are optional. `type` is one of `Bezier`, `Hold`,
+//! `Linear`, `Auto` and `Spring`; `spring` holds the `frequency`, `damping`
+//! and `response` of `Spring` keys; `tag` one of the [`KeyframeTag`] colors;
+//! the extrapolation modes are named as in [`Extrapolation`]. `value` is any
+//! value the track's type serializes to.
+
+use crate::core::extrapolation::Extrapolation;
+use crate::core::keyframe::{
+    BezierHandles, Keyframe, KeyframeId, KeyframeTag, KeyframeType, Spring, WeightedHandles,
+};
+use crate::core::time::TimeTick;
+use crate::core::track::{Track, TrackId};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Schema version written by [`save_tracks`].
+///
+/// [`load_tracks`] reads this and all earlier versions.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A track with the name and metadata stored next to it in a file.
+#[derive(Debug, Clone)]
+pub struct TrackRecord<T> {
+    /// Display name, e.g. the animated property's path.
+    pub name: String,
+    /// Free-form key/value pairs for the host.
+    pub metadata: BTreeMap<String, String>,
+    /// The track.
+    pub track: Track<T>,
+}
+
+impl<T> TrackRecord<T> {
+    /// Create a record without metadata.
+    pub fn new(name: impl Into<String>, track: Track<T>) -> Self {
+        Self {
+            name: name.into(),
+            metadata: BTreeMap::new(),
+            track,
+        }
+    }
+
+    /// Add a metadata entry.
+    pub fn with_metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+}
+
+/// Error reading or writing a track file.
+#[derive(Debug)]
+pub enum IoError {
+    /// The document is not valid JSON or does not match the schema.
+    Json(serde_json::Error),
+    /// The document was written by a newer version of the schema.
+    UnsupportedVersion(u32),
+}
+
+impl std::fmt::Display for IoError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "invalid track file: {error}"),
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "track file version {version} is newer than the supported version {FORMAT_VERSION}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(error) => Some(error),
+            Self::UnsupportedVersion(_) => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for IoError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// Serialize `tracks` to a pretty-printed JSON document.
+pub fn save_tracks<T: Serialize + Clone>(tracks: &[TrackRecord<T>]) -> Result<String, IoError> {
+    let document = Document {
+        version: FORMAT_VERSION,
+        tracks: tracks.iter().map(TrackDocument::from_record).collect(),
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+/// Parse tracks from a JSON document written by [`save_tracks`].
+pub fn load_tracks<T: DeserializeOwned + Clone>(
+    json: &str,
+) -> Result<Vec<TrackRecord<T>>, IoError> {
+    // Check the version first so newer files fail with a clear error rather
+    // than a schema mismatch.
+    let Version { version } = serde_json::from_str(json)?;
+    if version > FORMAT_VERSION {
+        Err(IoError::UnsupportedVersion(version))
+    } else {
+        let document: Document<T> = serde_json::from_str(json)?;
+        Ok(document
+            .tracks
+            .into_iter()
+            .map(TrackDocument::into_record)
+            .collect())
+    }
+}
+
+#[derive(Deserialize)]
+struct Version {
+    version: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Document<T> {
+    version: u32,
+    tracks: Vec<TrackDocument<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TrackDocument<T> {
+    id: TrackId,
+    name: String,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    metadata: BTreeMap<String, String>,
+    #[serde(default)]
+    time_offset: f64,
+    #[serde(default = "default_time_scale")]
+    time_scale: f64,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    extrapolation_pre: Extrapolation,
+    #[serde(default)]
+    extrapolation_post: Extrapolation,
+    keyframes: Vec<KeyframeDocument<T>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct KeyframeDocument<T> {
+    id: KeyframeId,
+    time: f64,
+    value: T,
+    #[serde(rename = "type")]
+    keyframe_type: KeyframeType,
+    handles: BezierHandles,
+    #[serde(default)]
+    weighted: Option<WeightedHandles>,
+    #[serde(default)]
+    spring: Option<Spring>,
+    #[serde(default = "default_connected")]
+    connected_right: bool,
+    #[serde(default)]
+    pinned: bool,
+    #[serde(default)]
+    locked: bool,
+    #[serde(default)]
+    breakdown: bool,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    tag: Option<KeyframeTag>,
+}
+
+fn default_time_scale() -> f64 {
+    1.0
+}
+
+fn default_connected() -> bool {
+    true
+}
+
+impl<T: Clone> TrackDocument<T> {
+    fn from_record(record: &TrackRecord<T>) -> Self {
+        let track = &record.track;
+        Self {
+            id: track.id,
+            name: record.name.clone(),
+            metadata: record.metadata.clone(),
+            time_offset: track.time_offset.value(),
+            time_scale: track.time_scale,
+            locked: track.locked,
+            extrapolation_pre: track.extrapolation_pre,
+            extrapolation_post: track.extrapolation_post,
+            keyframes: track
+                .keyframes_sorted()
+                .into_iter()
+                .map(|kf| KeyframeDocument {
+                    id: kf.id,
+                    time: kf.position.value(),
+                    value: kf.value.clone(),
+                    keyframe_type: kf.keyframe_type,
+                    handles: kf.handles,
+                    weighted: kf.weighted,
+                    spring: (kf.keyframe_type == KeyframeType::Spring).then_some(kf.spring),
+                    connected_right: kf.connected_right,
+                    pinned: kf.pinned,
+                    locked: kf.locked,
+                    breakdown: kf.breakdown,
+                    label: kf.label.clone(),
+                    tag: kf.tag,
+                })
+                .collect(),
+        }
+    }
+
+    fn into_record(self) -> TrackRecord<T> {
+        let mut track = Track::with_id(self.id)
+            .with_extrapolation(self.extrapolation_pre, self.extrapolation_post)
+            .with_retime(TimeTick::new(self.time_offset), self.time_scale);
+        track.locked = self.locked;
+        for kf in self.keyframes {
+            let mut keyframe = Keyframe::with_id(kf.id, TimeTick::new(kf.time), kf.value)
+                .with_handles(kf.handles)
+                .with_type(kf.keyframe_type);
+            keyframe.weighted = kf.weighted;
+            keyframe.spring = kf.spring.unwrap_or_default();
+            keyframe.connected_right = kf.connected_right;
+            keyframe.pinned = kf.pinned;
+            keyframe.locked = kf.locked;
+            keyframe.breakdown = kf.breakdown;
+            keyframe.label = kf.label;
+            keyframe.tag = kf.tag;
+            track.add_keyframe(keyframe);
+        }
+        TrackRecord {
+            name: self.name,
+            metadata: self.metadata,
+            track,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_ids_and_curves() {
+        let mut track = Track::<f32>::new()
+            .with_extrapolation(Extrapolation::Constant, Extrapolation::Cycle)
+            .with_retime(0.5, 2.0);
+        let first = track.add_keyframe(Keyframe::new(0.0, 1.0).with_type(KeyframeType::Hold));
+        track.add_keyframe(Keyframe::new(1.0, 5.0));
+        let spring = Spring {
+            frequency: 4.0,
+            damping: 0.2,
+            response: 0.5,
+        };
+        let springy = track.add_keyframe(
+            Keyframe::new(2.0, -3.0)
+                .with_type(KeyframeType::Spring)
+                .with_spring(spring),
+        );
+        track.add_keyframe(Keyframe::new(3.0, 2.0));
+        let records = [TrackRecord::new("Cube.x", track.clone()).with_metadata("unit", "m")];
+
+        let json = save_tracks(&records).unwrap();
+        let loaded = load_tracks::<f32>(&json).unwrap();
+
+        assert_eq!(loaded.len(), 1);
+        assert_eq!(loaded[0].name, "Cube.x");
+        assert_eq!(loaded[0].metadata["unit"], "m");
+        let copy = &loaded[0].track;
+        assert_eq!(copy.id, track.id);
+        assert_eq!(copy.positions(), track.positions());
+        assert_eq!(
+            copy.get_keyframe(first).unwrap().keyframe_type,
+            KeyframeType::Hold
+        );
+        assert_eq!(copy.get_keyframe(springy).unwrap().spring, spring);
+        for step in 0..40 {
+            let time = step as f64 * 0.1;
+            assert_eq!(copy.evaluate(time), track.evaluate(time));
+        }
+    }
+
+    #[test]
+    fn newer_version_is_rejected() {
+        let json = r#"{ "version": 99, "tracks": [] }"#;
+        assert!(matches!(
+            load_tracks::<f32>(json),
+            Err(IoError::UnsupportedVersion(99))
+        ));
+        assert!(matches!(load_tracks::<f32>("{}"), Err(IoError::Json(_))));
+    }
+}
@@ -0,0 +1,210 @@
+//! Keyboard shortcuts and modifier keys of the editing widgets.
+//!
+//! [`KeyBindings`] is part of the config of the
+//! [`CurveEditor`](crate::CurveEditor), which the
+//! [`MultiCurveEditor`](crate::MultiCurveEditor) shares, and of the
+//! [`DopeSheet`](crate::DopeSheet).
+//! Hosts remap a binding by replacing it and disable it with `None` or
+//! [`Modifiers::NONE`], e.g. to keep `F` for an app-level command:
+//!
+//! ```ignore
+//! let config = CurveEditorConfig {
+//!     key_bindings: KeyBindings {
+//!         fit_view: Some(KeyboardShortcut::new(Modifiers::SHIFT, Key::F)),
+//!         pan: Modifiers::NONE,
+//!         ..Default::default()
+//!     },
+//!     ..Default::default()
+//! };
+//! ```
+//!
+//! Shortcuts match only with exactly their modifiers held, so `Cmd+F` is
+//! left to the app while `F` fits the view.
+
+use crate::dopesheet::SelectionMode;
+use egui::{InputState, Key, KeyboardShortcut, Modifiers};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Shortcuts and modifier keys of the [`CurveEditor`](crate::CurveEditor)
+/// and [`DopeSheet`](crate::DopeSheet).
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct KeyBindings {
+    /// Select all keyframes.
+    pub select_all: Option<KeyboardShortcut>,
+    /// Clear the selection.
+    pub deselect_all: Option<KeyboardShortcut>,
+    /// Delete the selected keyframe.
+    pub delete: Option<KeyboardShortcut>,
+    /// Delete the selected keyframes, adjusting their neighbors to keep the
+    /// curve shape.
+    pub delete_preserving: Option<KeyboardShortcut>,
+    /// Fit the view to all keyframes.
+    pub fit_view: Option<KeyboardShortcut>,
+    /// Toggle the normalized view.
+    pub toggle_normalized: Option<KeyboardShortcut>,
+    /// Insert a keyframe with the current value at the playhead, keeping
+    /// the curve shape: on the curve editor's track, or on the selected
+    /// rows (or the row under the pointer) of the DopeSheet.
+    pub insert_key_at_playhead: Option<KeyboardShortcut>,
+    /// Held while clicking or box selecting to add to the selection.
+    pub extend_selection: Modifiers,
+    /// Held while clicking or box selecting to toggle items in the
+    /// selection.
+    pub toggle_selection: Modifiers,
+    /// Held while dragging keyframes to move them along the dominant axis
+    /// only.
+    pub constrain_axis: Modifiers,
+    /// Held while dragging a bezier handle to move it independently of the
+    /// opposite handle.
+    pub break_tangents: Modifiers,
+    /// Held while clicking the curve to insert a keyframe.
+    pub insert_keyframe: Modifiers,
+    /// Held while dragging with the primary button to pan; middle-button
+    /// drags always pan.
+    pub pan: Modifiers,
+    /// Held while scrolling to zoom instead of pan. `Modifiers::NONE` also
+    /// ignores pinch gestures.
+    ///
+    /// egui turns `Cmd`/`Ctrl`-scrolling into zoom itself, see
+    /// `egui::InputOptions::zoom_modifier`.
+    pub zoom: Modifiers,
+    /// Held while dragging a row in the DopeSheet to move its whole
+    /// keyframe block in time.
+    pub retime: Modifiers,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            select_all: Some(KeyboardShortcut::new(Modifiers::COMMAND, Key::A)),
+            deselect_all: Some(KeyboardShortcut::new(Modifiers::NONE, Key::Escape)),
+            delete: Some(KeyboardShortcut::new(Modifiers::NONE, Key::Delete)),
+            delete_preserving: Some(KeyboardShortcut::new(Modifiers::SHIFT, Key::Delete)),
+            fit_view: Some(KeyboardShortcut::new(Modifiers::NONE, Key::F)),
+            toggle_normalized: Some(KeyboardShortcut::new(Modifiers::NONE, Key::N)),
+            insert_key_at_playhead: Some(KeyboardShortcut::new(Modifiers::NONE, Key::K)),
+            extend_selection: Modifiers::SHIFT,
+            toggle_selection: Modifiers::COMMAND,
+            constrain_axis: Modifiers::SHIFT,
+            break_tangents: Modifiers::SHIFT,
+            insert_keyframe: Modifiers::COMMAND,
+            pan: Modifiers::ALT,
+            zoom: Modifiers::COMMAND,
+            retime: Modifiers::ALT,
+        }
+    }
+}
+
+impl KeyBindings {
+    /// Bindings with every shortcut and modifier disabled.
+    pub fn none() -> Self {
+        Self {
+            select_all: None,
+            deselect_all: None,
+            delete: None,
+            delete_preserving: None,
+            fit_view: None,
+            toggle_normalized: None,
+            insert_key_at_playhead: None,
+            extend_selection: Modifiers::NONE,
+            toggle_selection: Modifiers::NONE,
+            constrain_axis: Modifiers::NONE,
+            break_tangents: Modifiers::NONE,
+            insert_keyframe: Modifiers::NONE,
+            pan: Modifiers::NONE,
+            zoom: Modifiers::NONE,
+            retime: Modifiers::NONE,
+        }
+    }
+
+    /// Check if `shortcut` was pressed this frame with exactly its
+    /// modifiers held.
+    pub fn pressed(input: &InputState, shortcut: Option<KeyboardShortcut>) -> bool {
+        shortcut.is_some_and(|shortcut| {
+            input.modifiers.matches_exact(shortcut.modifiers)
+                && input.key_pressed(shortcut.logical_key)
+        })
+    }
+
+    /// Check if the modifier `binding` is held; a disabled binding never is.
+    pub fn held(modifiers: &Modifiers, binding: Modifiers) -> bool {
+        !binding.is_none() && modifiers.contains(binding)
+    }
+
+    /// How a click or box selection combines with the selection, given the
+    /// held modifiers.
+    pub fn selection_mode(&self, modifiers: &Modifiers) -> SelectionMode {
+        if Self::held(modifiers, self.extend_selection) {
+            SelectionMode::Add
+        } else if Self::held(modifiers, self.toggle_selection) {
+            SelectionMode::Toggle
+        } else {
+            SelectionMode::Replace
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(modifiers: Modifiers, key: Key) -> egui::RawInput {
+        egui::RawInput {
+            modifiers,
+            events: vec![egui::Event::Key {
+                key,
+                physical_key: None,
+                pressed: true,
+                repeat: false,
+                modifiers,
+            }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn shortcuts_match_exactly_and_can_be_remapped() {
+        let ctx = egui::Context::default();
+        let pressed = |raw: egui::RawInput, bindings: KeyBindings| {
+            let mut fit = false;
+            let _ = ctx.run(raw, |ctx| {
+                fit = ctx.input(|i| KeyBindings::pressed(i, bindings.fit_view));
+            });
+            fit
+        };
+
+        let defaults = KeyBindings::default();
+        assert!(pressed(press(Modifiers::NONE, Key::F), defaults));
+        // `Cmd+F` is left to the app.
+        assert!(!pressed(press(Modifiers::COMMAND, Key::F), defaults));
+
+        let remapped = KeyBindings {
+            fit_view: Some(KeyboardShortcut::new(Modifiers::SHIFT, Key::Home)),
+            ..defaults
+        };
+        assert!(!pressed(press(Modifiers::NONE, Key::F), remapped));
+        assert!(pressed(press(Modifiers::SHIFT, Key::Home), remapped));
+        assert!(!pressed(
+            press(Modifiers::NONE, Key::F),
+            KeyBindings::none()
+        ));
+
+        assert_eq!(
+            defaults.selection_mode(&Modifiers::SHIFT),
+            SelectionMode::Add
+        );
+        assert_eq!(
+            defaults.selection_mode(&Modifiers::COMMAND),
+            SelectionMode::Toggle
+        );
+        assert_eq!(
+            KeyBindings::none().selection_mode(&Modifiers::SHIFT),
+            SelectionMode::Replace
+        );
+        assert!(KeyBindings::held(&Modifiers::ALT, defaults.pan));
+        assert!(!KeyBindings::held(&Modifiers::ALT, Modifiers::NONE));
+    }
+}
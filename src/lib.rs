@@ -27,29 +27,83 @@
 pub type HashSet<T> = ahash::AHashSet<T>;
 pub type HashMap<K, V> = ahash::AHashMap<K, V>;
 
+#[cfg(feature = "bvh")]
+pub mod bvh;
 pub mod core;
 pub mod dopesheet;
+pub mod editor_settings;
+#[cfg(feature = "examples-support")]
+pub mod examples_support;
+pub mod intervals;
+#[cfg(feature = "serde")]
+pub mod io;
+pub mod key_bindings;
+#[cfg(feature = "lottie")]
+pub mod lottie;
+pub mod model;
+pub mod playback;
+pub mod snap;
 pub mod spaces;
+#[cfg(feature = "serde")]
+pub mod theatre;
 pub mod traits;
+pub mod transport;
+pub mod validation;
 pub mod widgets;
 
 // Re-exports for convenience
+#[cfg(feature = "expression")]
+pub use core::expression::{Expression, ExpressionContext, ExpressionError, ExpressionVariables};
 pub use core::{
+    after_effects::{AeError, AeKeyframeData, AeUnits},
+    clip::{Clip, ClipEdge, ClipId, ClipInstance, ClipInstanceId, ClipSequence},
+    clipboard::{Clipboard, PasteMode, PasteOptions},
+    color::{ColorSpace, Rgba},
+    css::CssError,
+    driver::{Driver, Drivers},
     easing,
-    interpolation::{CubicBezier, InterpolationTriple, interpolate_at_position},
-    keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType},
+    euler::{euler_filter, euler_filter_commands},
+    extrapolation::Extrapolation,
+    generators::{Generator, Waveform},
+    group::{TrackGroup, TrackGroupId, TrackGroups},
+    history::CommandHistory,
+    interpolation::{
+        CubicBezier, InterpolationTriple, interpolate_at_position, interpolate_with_extrapolation,
+        resolved_handles, segment_control_points,
+    },
+    keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType, Spring, WeightedHandles},
+    layer::{AnimationLayer, Blend, BlendMode, LayeredTrack},
+    marker::{Marker, MarkerId, Markers},
+    modifier::{Clamp, Cycle, ModifierInput, ModifierStack, Noise, OffsetScale, TrackModifier},
+    section::{Section, SectionId, Sections},
     time::TimeTick,
-    track::{Track, TrackId},
+    time_warp::TimeWarp,
+    track::{MemoryUsage, Track, TrackId},
 };
 pub use dopesheet::DopeSheet;
+pub use editor_settings::EditorSettings;
+pub use intervals::{IntervalContext, NiceIntervals, nice_intervals};
+pub use key_bindings::KeyBindings;
+pub use model::SimpleAnimationModel;
+pub use playback::{
+    LoopMode, PlaybackController, PlaybackDirection, PlaybackEvent, PlaybackResponse,
+};
+pub use snap::{SnapConfig, SnapTarget};
 pub use spaces::SpaceTransform;
 pub use traits::{
     Animatable, AnimationCommand, AnimationDataMutator, AnimationDataProvider, KeyframeSource,
-    KeyframeView, PropertyRow,
+    KeyframeView, PropertyRow, ProviderTrack, RowTarget, TrackLane, ValueUnit,
+};
+
+pub use transport::{
+    Transport, TransportAction, audible_track_ids, next_keyframe_after, prev_keyframe_before,
 };
 
 // Re-export uuid for KeyframeId construction in downstream crates
 pub use uuid;
 pub use widgets::{
-    AnchorMode, BoundingBox, BoundingBoxConfig, BoundingBoxHandle, CurveEditor, HandleSide,
+    AnchorMode, BoundingBox, BoundingBoxConfig, BoundingBoxHandle, ClipLane, CurveEditor,
+    CurveEditorState, CurveEntry, CurveLayer, GeneratorDialog, HandleSide, InteractionSizes,
+    KeyframeInspector, KeyframeVisual, KeyframeVisualState, MotionPathEditor, MultiCurveEditor,
+    TimelinePanel, ValueNormalization, WorldBoundingBox, reduced_motion, set_reduced_motion,
 };
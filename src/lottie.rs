@@ -0,0 +1,352 @@
+//! Import and export of Lottie (bodymovin) animated properties.
+//!
+//! A Lottie property stores keyframe times in frames and the easing of each
+//! segment as an out tangent `o` on its first keyframe and an in tangent `i`
+//! for its second, both normalized to the segment like [`BezierHandles`]:
+//!
+//! ```json
+//! {
+//!   "a": 1,
+//!   "k": [
+//!     { "t": 0, "s": [0, 10], "o": { "x": [0.33, 0.33], "y": [0, 0] },
+//!       "i": { "x": [0.67, 0.67], "y": [1, 1] } },
+//!     { "t": 24, "s": [100, 10], "h": 1 },
+//!     { "t": 48, "s": [50, 20] }
+//!   ]
+//! }
+//! ```
+//!
+//! A property with `n` dimensions maps to `n` tracks with keyframes at the
+//! same times. [`from_lottie`] also reads static properties and the older
+//! format that stores the end value of each segment in `e`.
+//!
+//! [`BezierHandles`]: crate::BezierHandles
+
+use crate::core::interpolation::segment_control_points;
+use crate::core::keyframe::{BezierHandles, Keyframe, KeyframeType};
+use crate::core::track::Track;
+use serde::{Deserialize, Serialize};
+
+/// Times of keyframes in different tracks closer than this, in seconds, are
+/// the same Lottie keyframe.
+const TIME_TOLERANCE: f64 = 1e-6;
+
+/// Error converting a Lottie property.
+#[derive(Debug)]
+pub enum LottieError {
+    /// The property does not match the Lottie schema.
+    Json(serde_json::Error),
+    /// The tracks of a multi-dimensional property have keyframes at
+    /// different times.
+    MismatchedKeyframes,
+}
+
+impl std::fmt::Display for LottieError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Json(error) => write!(f, "invalid Lottie property: {error}"),
+            Self::MismatchedKeyframes => {
+                write!(
+                    f,
+                    "tracks of a Lottie property need keyframes at the same times"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for LottieError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Json(error) => Some(error),
+            Self::MismatchedKeyframes => None,
+        }
+    }
+}
+
+impl From<serde_json::Error> for LottieError {
+    fn from(error: serde_json::Error) -> Self {
+        Self::Json(error)
+    }
+}
+
+/// Convert a Lottie property, e.g. `layers[0].ks.p` of a Lottie file, into
+/// one track per dimension.
+///
+/// Keyframe times are divided by `fps`, the file's `fr`.
+pub fn from_lottie(property: &serde_json::Value, fps: f64) -> Result<Vec<Track<f32>>, LottieError> {
+    let property = Property::deserialize(property)?;
+    let keyframes = match property.k {
+        Content::Animated(keyframes) => keyframes,
+        Content::Static(value) => {
+            return Ok(value
+                .values()
+                .into_iter()
+                .map(|value| {
+                    let mut track = Track::new();
+                    track.add_keyframe(Keyframe::new(0.0, value));
+                    track
+                })
+                .collect());
+        }
+    };
+
+    // Values at each keyframe; the old format only has the end value of the
+    // previous segment on the last keyframe.
+    let mut values: Vec<Vec<f32>> = Vec::with_capacity(keyframes.len());
+    for (index, kf) in keyframes.iter().enumerate() {
+        let previous_end = index
+            .checked_sub(1)
+            .and_then(|i| keyframes[i].e.as_ref())
+            .map(Scalar::values);
+        values.push(
+            kf.s.as_ref()
+                .map(Scalar::values)
+                .or(previous_end)
+                .or_else(|| values.last().cloned())
+                .unwrap_or_default(),
+        );
+    }
+
+    let dimensions = values.iter().map(Vec::len).max().unwrap_or(0);
+    let tracks = (0..dimensions)
+        .map(|dim| {
+            let mut track = Track::new();
+            let mut in_handle = (0.0, 0.0);
+            for (index, kf) in keyframes.iter().enumerate() {
+                let value = values[index]
+                    .get(dim)
+                    .or(values[index].last())
+                    .copied()
+                    .unwrap_or_default();
+                let is_last = index + 1 == keyframes.len();
+                let out_handle = kf.o.as_ref().map(|o| o.at(dim));
+                let keyframe_type = if kf.h == Some(1) {
+                    KeyframeType::Hold
+                } else if out_handle.is_some() || is_last {
+                    KeyframeType::Bezier
+                } else {
+                    KeyframeType::Linear
+                };
+                let (right_x, right_y) = out_handle.unwrap_or((1.0, 1.0));
+                let handles = BezierHandles {
+                    left_x: in_handle.0,
+                    left_y: in_handle.1,
+                    right_x,
+                    right_y,
+                };
+                track.add_keyframe(
+                    Keyframe::new(kf.t / fps, value)
+                        .with_handles(handles)
+                        .with_type(keyframe_type),
+                );
+                in_handle = kf.i.as_ref().map_or((0.0, 0.0), |i| i.at(dim));
+            }
+            track
+        })
+        .collect();
+    Ok(tracks)
+}
+
+/// Convert tracks, one per dimension, into an animated Lottie property.
+///
+/// Keyframe times are multiplied by `fps`, the file's `fr`. Auto and
+/// weighted handles are written as the plain tangents they resolve to, and
+/// disconnected segments as holds.
+pub fn to_lottie(tracks: &[&Track<f32>], fps: f64) -> Result<serde_json::Value, LottieError> {
+    let sorted: Vec<Vec<&Keyframe<f32>>> = tracks
+        .iter()
+        .map(|track| track.keyframes_sorted())
+        .collect();
+    let times: Vec<Vec<f64>> = tracks
+        .iter()
+        .zip(&sorted)
+        .map(|(track, keyframes)| {
+            keyframes
+                .iter()
+                .map(|kf| track.to_global_time(kf.position).value())
+                .collect()
+        })
+        .collect();
+    let first_times = times.first().cloned().unwrap_or_default();
+    let aligned = times.iter().all(|track_times| {
+        track_times.len() == first_times.len()
+            && track_times
+                .iter()
+                .zip(&first_times)
+                .all(|(a, b)| (a - b).abs() < TIME_TOLERANCE)
+    });
+    if !aligned {
+        return Err(LottieError::MismatchedKeyframes);
+    }
+
+    let keyframes = first_times
+        .iter()
+        .enumerate()
+        .map(|(index, time)| {
+            let is_last = index + 1 == first_times.len();
+            let hold = sorted.iter().any(|keyframes| {
+                let kf = keyframes[index];
+                kf.keyframe_type == KeyframeType::Hold || !kf.connected_right
+            });
+            // Control points `[x1, y1, x2, y2]` of the segment per dimension.
+            let points: Vec<[f32; 4]> = sorted
+                .iter()
+                .map(|keyframes| match keyframes[index].keyframe_type {
+                    _ if is_last => [0.0, 0.0, 1.0, 1.0],
+                    KeyframeType::Linear => [0.0, 0.0, 1.0, 1.0],
+                    _ => segment_control_points(keyframes, index),
+                })
+                .collect();
+            let tangent = |x: usize, y: usize| Tangent {
+                x: Scalar::Many(points.iter().map(|p| p[x]).collect()),
+                y: Scalar::Many(points.iter().map(|p| p[y]).collect()),
+            };
+            LottieKeyframe {
+                t: time * fps,
+                s: Some(Scalar::Many(
+                    sorted
+                        .iter()
+                        .map(|keyframes| keyframes[index].value)
+                        .collect(),
+                )),
+                e: None,
+                o: (!is_last).then(|| tangent(0, 1)),
+                i: (!is_last).then(|| tangent(2, 3)),
+                h: hold.then_some(1),
+            }
+        })
+        .collect();
+
+    Ok(serde_json::to_value(Property {
+        a: 1,
+        k: Content::Animated(keyframes),
+    })?)
+}
+
+#[derive(Serialize, Deserialize)]
+struct Property {
+    #[serde(default)]
+    a: u8,
+    k: Content,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Content {
+    Animated(Vec<LottieKeyframe>),
+    Static(Scalar),
+}
+
+#[derive(Serialize, Deserialize)]
+struct LottieKeyframe {
+    t: f64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    s: Option<Scalar>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    e: Option<Scalar>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    o: Option<Tangent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    i: Option<Tangent>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    h: Option<u8>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Tangent {
+    x: Scalar,
+    y: Scalar,
+}
+
+impl Tangent {
+    /// `(x, y)` for dimension `dim`; single values apply to all dimensions.
+    fn at(&self, dim: usize) -> (f32, f32) {
+        (self.x.at(dim), self.y.at(dim))
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Scalar {
+    One(f32),
+    Many(Vec<f32>),
+}
+
+impl Scalar {
+    fn values(&self) -> Vec<f32> {
+        match self {
+            Self::One(value) => vec![*value],
+            Self::Many(values) => values.clone(),
+        }
+    }
+
+    fn at(&self, dim: usize) -> f32 {
+        match self {
+            Self::One(value) => *value,
+            Self::Many(values) => values
+                .get(dim)
+                .or(values.last())
+                .copied()
+                .unwrap_or_default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::time::TimeTick;
+
+    #[test]
+    fn imports_tangents_holds_and_dimensions() {
+        let property = serde_json::json!({
+            "a": 1,
+            "k": [
+                { "t": 0, "s": [0, 10], "o": { "x": 0.33, "y": 0 }, "i": { "x": [0.67, 0.5], "y": [1, 1] } },
+                { "t": 24, "s": [100, 10], "h": 1 },
+                { "t": 48, "s": [50, 20] }
+            ]
+        });
+        let tracks = from_lottie(&property, 24.0).unwrap();
+        assert_eq!(tracks.len(), 2);
+
+        let x = tracks[0].keyframes_sorted();
+        assert_eq!(x.len(), 3);
+        assert_eq!(x[1].position, TimeTick::new(1.0));
+        assert_eq!(x[0].handles.right_x, 0.33);
+        assert_eq!(x[1].handles.left_x, 0.67);
+        assert_eq!(tracks[1].keyframes_sorted()[1].handles.left_x, 0.5);
+        assert_eq!(x[1].keyframe_type, KeyframeType::Hold);
+        assert_eq!(tracks[0].evaluate(1.5), Some(100.0));
+
+        let static_value = serde_json::json!({ "a": 0, "k": 42 });
+        let tracks = from_lottie(&static_value, 24.0).unwrap();
+        assert_eq!(tracks[0].evaluate(3.0), Some(42.0));
+    }
+
+    #[test]
+    fn round_trip_keeps_curve() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_handles(BezierHandles::ease_in_out()));
+        track.add_keyframe(Keyframe::new(1.0, 10.0).with_type(KeyframeType::Auto));
+        track.add_keyframe(Keyframe::new(2.0, 4.0).with_type(KeyframeType::Hold));
+        track.add_keyframe(Keyframe::new(3.0, 8.0));
+
+        let property = to_lottie(&[&track], 30.0).unwrap();
+        assert_eq!(property["k"][1]["t"], 30.0);
+        let copy = &from_lottie(&property, 30.0).unwrap()[0];
+        for step in 0..=30 {
+            let time = step as f64 * 0.1;
+            let (a, b) = (copy.evaluate(time).unwrap(), track.evaluate(time).unwrap());
+            assert!((a - b).abs() < 1e-4, "{time}: {a} != {b}");
+        }
+
+        let mut other = Track::<f32>::new();
+        other.add_keyframe(Keyframe::new(0.5, 1.0));
+        assert!(matches!(
+            to_lottie(&[&track, &other], 30.0),
+            Err(LottieError::MismatchedKeyframes)
+        ));
+    }
+}
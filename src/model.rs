@@ -0,0 +1,1682 @@
+//! Ready-made animation model for the widgets.
+//!
+//! [`SimpleAnimationModel`] holds named groups of `Track<f32>` and implements
+//! [`AnimationDataProvider`], [`AnimationDataMutator`] and [`KeyframeSource`],
+//! so a working DopeSheet and CurveEditor need no trait glue:
+//!
+//! ```ignore
+//! let mut model = SimpleAnimationModel::new();
+//! let group = model.add_group("Cube");
+//! let x = model.add_track(&group, "X", Track::new());
+//!
+//! DopeSheet::show_stateful(ui, &space, &mut selection, &mut model);
+//! ```
+
+use crate::HashSet;
+use crate::core::driver::{Driver, Drivers};
+use crate::core::extrapolation::Extrapolation;
+use crate::core::group::{TrackGroup, TrackGroupId, TrackGroups};
+use crate::core::history::{CommandHistory, KeyframeSnapshot};
+use crate::core::keyframe::{
+    BezierHandles, Keyframe, KeyframeId, KeyframeTag, KeyframeType, Spring, WeightedHandles,
+};
+use crate::core::layer::BlendMode;
+use crate::core::marker::{Marker, MarkerId, Markers};
+use crate::core::time::TimeTick;
+use crate::core::track::{MemoryUsage, Track, TrackId};
+use crate::traits::{
+    AnimationCommand, AnimationDataMutator, AnimationDataProvider, KeyframeSource, KeyframeView,
+    PropertyRow, TrackLane, ValueUnit,
+};
+use crate::transport::subtree_track_ids;
+use egui::Color32;
+
+/// A named track in a [`SimpleAnimationModel`].
+#[derive(Debug, Clone)]
+struct ModelTrack {
+    label: String,
+    color: Option<Color32>,
+    limits: Option<(f64, f64)>,
+    lane: TrackLane,
+    unit: ValueUnit,
+    blend: Option<BlendMode>,
+    muted: bool,
+    solo: bool,
+    track: Track<f32>,
+}
+
+/// A named, collapsible group of tracks in a [`SimpleAnimationModel`].
+#[derive(Debug, Clone)]
+struct ModelGroup {
+    id: String,
+    label: String,
+    color: Option<Color32>,
+    locked: bool,
+    tracks: Vec<ModelTrack>,
+}
+
+/// The animation data of a [`SimpleAnimationModel`], without its history.
+#[derive(Debug, Clone)]
+struct ModelData {
+    groups: Vec<ModelGroup>,
+    collapsed: HashSet<String>,
+    isolated: Option<String>,
+    current_time: TimeTick,
+    time_range: (TimeTick, TimeTick),
+    work_range: Option<(TimeTick, TimeTick)>,
+    track_groups: TrackGroups,
+    drivers: Drivers,
+    markers: Markers,
+    next_group: usize,
+}
+
+/// Animation model holding named groups of `Track<f32>`.
+///
+/// Each group is a collapsible row in the DopeSheet with its tracks as child
+/// rows. All edits executed through [`AnimationDataMutator::execute`] are
+/// recorded for [`undo`](Self::undo) and [`redo`](Self::redo); the commands
+/// between [`begin_scrub`](AnimationDataMutator::begin_scrub) and
+/// [`end_scrub`](AnimationDataMutator::end_scrub) form one undo step.
+///
+/// As a [`KeyframeSource`] the model shows its
+/// [active track](Self::set_active_track) in a CurveEditor.
+#[derive(Debug, Clone)]
+pub struct SimpleAnimationModel {
+    data: ModelData,
+    history: CommandHistory,
+    active_track: Option<TrackId>,
+}
+
+impl Default for SimpleAnimationModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SimpleAnimationModel {
+    /// Create an empty model with a time range of `0..10`.
+    pub fn new() -> Self {
+        Self {
+            data: ModelData {
+                groups: Vec::new(),
+                collapsed: HashSet::default(),
+                isolated: None,
+                current_time: TimeTick::zero(),
+                time_range: (TimeTick::zero(), TimeTick::new(10.0)),
+                work_range: None,
+                track_groups: TrackGroups::new(),
+                drivers: Drivers::new(),
+                markers: Markers::new(),
+                next_group: 0,
+            },
+            history: CommandHistory::new(),
+            active_track: None,
+        }
+    }
+
+    /// Set the animation time range.
+    pub fn with_time_range(mut self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        self.data.time_range = (start.into(), end.into());
+        self
+    }
+
+    /// Add an empty group labeled `label` and return its row ID.
+    pub fn add_group(&mut self, label: impl Into<String>) -> String {
+        self.data.add_group(label.into())
+    }
+
+    /// Add `track` labeled `label` to the group with row ID `group`.
+    ///
+    /// Returns the track's ID, or `None` if the group does not exist.
+    pub fn add_track(
+        &mut self,
+        group: &str,
+        label: impl Into<String>,
+        track: Track<f32>,
+    ) -> Option<TrackId> {
+        let id = track.id;
+        self.data
+            .groups
+            .iter_mut()
+            .find(|g| g.id == group)?
+            .tracks
+            .push(ModelTrack {
+                label: label.into(),
+                color: None,
+                limits: None,
+                lane: TrackLane::default(),
+                unit: ValueUnit::default(),
+                blend: None,
+                muted: false,
+                solo: false,
+                track,
+            });
+        Some(id)
+    }
+
+    /// Add a [`TrackGroup`] whose tracks can be hidden together with
+    /// [`AnimationCommand::SetTrackGroupVisible`].
+    pub fn add_track_group(&mut self, group: TrackGroup) -> TrackGroupId {
+        self.data.track_groups.add(group)
+    }
+
+    /// The track with `id`.
+    pub fn track(&self, id: TrackId) -> Option<&Track<f32>> {
+        self.data.track(id)
+    }
+
+    /// The track with `id`, for edits that bypass the undo history.
+    pub fn track_mut(&mut self, id: TrackId) -> Option<&mut Track<f32>> {
+        self.data.track_mut(id)
+    }
+
+    /// Set the range the values of track `id` must stay within, see
+    /// [`AnimationCommand::validate`]; `None` removes the limits.
+    ///
+    /// Returns `false` if the track does not exist.
+    pub fn set_value_limits(&mut self, id: TrackId, limits: Option<(f64, f64)>) -> bool {
+        let track = self
+            .data
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find(|t| t.track.id == id);
+        track.map(|t| t.limits = limits).is_some()
+    }
+
+    /// Set how the DopeSheet draws track `id`, e.g. [`TrackLane::Toggle`]
+    /// for a visibility switch stored as `0`/`1`.
+    ///
+    /// Returns `false` if the track does not exist.
+    pub fn set_track_lane(&mut self, id: TrackId, lane: TrackLane) -> bool {
+        let track = self
+            .data
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find(|t| t.track.id == id);
+        track.map(|t| t.lane = lane).is_some()
+    }
+
+    /// Set the unit of the values of track `id`, e.g. [`ValueUnit::Degrees`]
+    /// for a rotation.
+    ///
+    /// Returns `false` if the track does not exist.
+    pub fn set_track_unit(&mut self, id: TrackId, unit: ValueUnit) -> bool {
+        let track = self
+            .data
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find(|t| t.track.id == id);
+        track.map(|t| t.unit = unit).is_some()
+    }
+
+    /// Mark track `id` as an [`AnimationLayer`](crate::AnimationLayer)
+    /// blended with `blend`, or as a plain track with `None`.
+    ///
+    /// Returns `false` if the track does not exist.
+    pub fn set_track_blend(&mut self, id: TrackId, blend: Option<BlendMode>) -> bool {
+        let track = self
+            .data
+            .groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find(|t| t.track.id == id);
+        track.map(|t| t.blend = blend).is_some()
+    }
+
+    /// Make the target track of `driver` follow its source track.
+    ///
+    /// Returns `false` if the driver would close a cycle, see
+    /// [`Drivers::add`].
+    pub fn add_driver(&mut self, driver: Driver) -> bool {
+        self.data.drivers.add(driver)
+    }
+
+    /// Remove the driver of track `target`, which plays its own keyframes
+    /// again.
+    pub fn remove_driver(&mut self, target: TrackId) -> Option<Driver> {
+        self.data.drivers.remove(target)
+    }
+
+    /// All drivers between tracks.
+    pub fn drivers(&self) -> &Drivers {
+        &self.data.drivers
+    }
+
+    /// Timeline markers, edited with [`AnimationCommand::AddMarker`],
+    /// [`AnimationCommand::MoveMarker`] and [`AnimationCommand::RemoveMarker`].
+    pub fn markers(&self) -> &Markers {
+        &self.data.markers
+    }
+
+    /// Mutable access to the markers, bypassing the undo history.
+    pub fn markers_mut(&mut self) -> &mut Markers {
+        &mut self.data.markers
+    }
+
+    /// Value of track `id` at global `time`, following its driver if it
+    /// has one.
+    pub fn evaluate(&self, id: TrackId, time: impl Into<TimeTick>) -> Option<f32> {
+        self.data
+            .drivers
+            .evaluate(id, time.into(), &|id, time| {
+                self.track(id)?.evaluate(time).map(f64::from)
+            })
+            .map(|value| value as f32)
+    }
+
+    /// Approximate heap memory held by the keyframes of all tracks, see
+    /// [`Track::memory_usage`].
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.data.tracks().map(|t| t.track.memory_usage()).sum()
+    }
+
+    /// Release spare keyframe capacity of all tracks.
+    pub fn shrink_to_fit(&mut self) {
+        for group in &mut self.data.groups {
+            for t in &mut group.tracks {
+                t.track.shrink_to_fit();
+            }
+        }
+    }
+
+    /// IDs of all tracks in row order.
+    pub fn track_ids(&self) -> Vec<TrackId> {
+        self.data
+            .groups
+            .iter()
+            .flat_map(|group| group.tracks.iter().map(|t| t.track.id))
+            .collect()
+    }
+
+    /// The work range set by [`AnimationCommand::SetWorkRange`].
+    pub fn work_range(&self) -> Option<(TimeTick, TimeTick)> {
+        self.data.work_range
+    }
+
+    /// Set the track shown when the model is used as a [`KeyframeSource`].
+    pub fn set_active_track(&mut self, id: Option<TrackId>) {
+        self.active_track = id;
+    }
+
+    /// The track shown when the model is used as a [`KeyframeSource`].
+    pub fn active_track(&self) -> Option<TrackId> {
+        self.active_track
+    }
+
+    /// Undo the last edit. Returns `false` if there was nothing to undo.
+    pub fn undo(&mut self) -> bool {
+        self.history.undo(&mut self.data)
+    }
+
+    /// Redo the last undone edit. Returns `false` if there was nothing to
+    /// redo.
+    pub fn redo(&mut self) -> bool {
+        self.history.redo(&mut self.data)
+    }
+
+    /// The undo history.
+    pub fn history(&self) -> &CommandHistory {
+        &self.history
+    }
+}
+
+impl AnimationDataProvider for SimpleAnimationModel {
+    fn property_rows(&self) -> Vec<PropertyRow> {
+        self.data.property_rows()
+    }
+
+    fn keyframe_positions(&self, track_id: TrackId) -> Option<Vec<(KeyframeId, TimeTick)>> {
+        self.data.keyframe_positions(track_id)
+    }
+
+    fn keyframe_value(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<f64> {
+        self.data.keyframe_value(track_id, keyframe_id)
+    }
+
+    fn keyframe_handles(
+        &self,
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+    ) -> Option<BezierHandles> {
+        self.data.keyframe_handles(track_id, keyframe_id)
+    }
+
+    fn keyframe_weighted_handles(
+        &self,
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+    ) -> Option<WeightedHandles> {
+        self.data.keyframe_weighted_handles(track_id, keyframe_id)
+    }
+
+    fn keyframe_type(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<KeyframeType> {
+        self.data.keyframe_type(track_id, keyframe_id)
+    }
+
+    fn keyframe_spring(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<Spring> {
+        self.data.keyframe_spring(track_id, keyframe_id)
+    }
+
+    fn keyframe_pinned(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.data.keyframe_pinned(track_id, keyframe_id)
+    }
+
+    fn keyframe_locked(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.data.keyframe_locked(track_id, keyframe_id)
+    }
+
+    fn keyframe_breakdown(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.data.keyframe_breakdown(track_id, keyframe_id)
+    }
+
+    fn keyframe_label(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<String> {
+        self.data.keyframe_label(track_id, keyframe_id)
+    }
+
+    fn keyframe_tag(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<KeyframeTag> {
+        self.data.keyframe_tag(track_id, keyframe_id)
+    }
+
+    fn track_time_offset(&self, track_id: TrackId) -> TimeTick {
+        self.data.track_time_offset(track_id)
+    }
+
+    fn track_time_scale(&self, track_id: TrackId) -> f64 {
+        self.data.track_time_scale(track_id)
+    }
+
+    fn track_value_limits(&self, track_id: TrackId) -> Option<(f64, f64)> {
+        self.data.track_value_limits(track_id)
+    }
+
+    fn track_lane(&self, track_id: TrackId) -> TrackLane {
+        self.data.track_lane(track_id)
+    }
+
+    fn track_unit(&self, track_id: TrackId) -> ValueUnit {
+        self.data.track_unit(track_id)
+    }
+
+    fn track_blend(&self, track_id: TrackId) -> Option<BlendMode> {
+        self.data.track_blend(track_id)
+    }
+
+    fn find_keyframe(&self, keyframe_id: KeyframeId) -> Option<(TrackId, TimeTick)> {
+        self.data.find_keyframe(keyframe_id)
+    }
+
+    fn current_time(&self) -> TimeTick {
+        self.data.current_time
+    }
+
+    fn time_range(&self) -> (TimeTick, TimeTick) {
+        self.data.time_range
+    }
+
+    fn value_range(&self, track_id: TrackId) -> Option<(f32, f32)> {
+        self.data.value_range(track_id)
+    }
+
+    fn track_groups(&self) -> TrackGroups {
+        self.data.track_groups()
+    }
+
+    fn marker(&self, marker_id: MarkerId) -> Option<Marker> {
+        self.data.marker(marker_id)
+    }
+}
+
+impl AnimationDataMutator for SimpleAnimationModel {
+    fn execute(&mut self, command: AnimationCommand) {
+        self.history.execute(&mut self.data, command);
+    }
+
+    fn begin_scrub(&mut self) {
+        self.history.begin_group();
+    }
+
+    fn end_scrub(&mut self, commit: bool) {
+        self.history.end_group(&mut self.data, commit);
+    }
+}
+
+impl KeyframeSource for SimpleAnimationModel {
+    fn keyframes_sorted(&self) -> Vec<KeyframeView> {
+        let Some(track) = self.active_track.and_then(|id| self.track(id)) else {
+            return Vec::new();
+        };
+        let locked = KeyframeSource::is_locked(self);
+        let mut keyframes = KeyframeSource::keyframes_sorted(track);
+        for kf in &mut keyframes {
+            kf.locked |= locked;
+        }
+        keyframes
+    }
+
+    fn is_locked(&self) -> bool {
+        // Keyframes of locked groups are locked too.
+        self.active_track.is_some_and(|id| {
+            self.data.groups.iter().any(|group| {
+                group
+                    .tracks
+                    .iter()
+                    .any(|t| t.track.id == id && (t.track.locked || group.locked))
+            })
+        })
+    }
+
+    fn value_range(&self) -> Option<(f32, f32)> {
+        self.track(self.active_track?)?.value_range()
+    }
+
+    fn len(&self) -> usize {
+        self.active_track
+            .and_then(|id| self.track(id))
+            .map_or(0, Track::len)
+    }
+
+    fn extrapolation(&self) -> (Extrapolation, Extrapolation) {
+        self.active_track
+            .and_then(|id| self.track(id))
+            .map_or((Extrapolation::Constant, Extrapolation::Constant), |t| {
+                (t.extrapolation_pre, t.extrapolation_post)
+            })
+    }
+}
+
+impl ModelData {
+    fn add_group(&mut self, label: String) -> String {
+        self.next_group += 1;
+        let id = format!("group-{}", self.next_group);
+        self.groups.push(ModelGroup {
+            id: id.clone(),
+            label,
+            color: None,
+            locked: false,
+            tracks: Vec::new(),
+        });
+        id
+    }
+
+    fn tracks(&self) -> impl Iterator<Item = &ModelTrack> {
+        self.groups.iter().flat_map(|group| group.tracks.iter())
+    }
+
+    fn track(&self, id: TrackId) -> Option<&Track<f32>> {
+        self.tracks().map(|t| &t.track).find(|track| track.id == id)
+    }
+
+    fn track_mut(&mut self, id: TrackId) -> Option<&mut Track<f32>> {
+        self.groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .map(|t| &mut t.track)
+            .find(|track| track.id == id)
+    }
+
+    fn model_track_mut(&mut self, id: TrackId) -> Option<&mut ModelTrack> {
+        self.groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find(|t| t.track.id == id)
+    }
+
+    fn keyframe_mut(&mut self, id: KeyframeId) -> Option<&mut Keyframe<f32>> {
+        self.groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .find_map(|t| t.track.get_keyframe_mut(id))
+    }
+
+    /// Keyframe `id` for batch edits, `None` if it is pinned, locked or on a
+    /// locked track.
+    fn editable_keyframe_mut(&mut self, id: KeyframeId) -> Option<&mut Keyframe<f32>> {
+        self.groups
+            .iter_mut()
+            .flat_map(|group| group.tracks.iter_mut())
+            .filter(|t| !t.track.locked)
+            .find_map(|t| t.track.get_keyframe_mut(id))
+            .filter(|kf| !kf.pinned && !kf.locked)
+    }
+
+    fn keyframe(&self, track_id: TrackId, id: KeyframeId) -> Option<&Keyframe<f32>> {
+        self.track(track_id)?.get_keyframe(id)
+    }
+
+    /// Whether `track_id` is locked or a pinned or locked keyframe of it
+    /// sits at `position`.
+    fn protected_at(&self, track_id: TrackId, position: TimeTick) -> bool {
+        self.track(track_id).is_some_and(|track| {
+            track.locked
+                || track.iter().any(|kf| {
+                    (kf.pinned || kf.locked) && (kf.position - position).value().abs() < 1e-9
+                })
+        })
+    }
+
+    fn insert(&mut self, snapshot: &KeyframeSnapshot) {
+        if let Some(track) = self.track_mut(snapshot.track_id) {
+            track.add_keyframe(
+                Keyframe::with_id(
+                    snapshot.keyframe_id,
+                    snapshot.position,
+                    snapshot.value as f32,
+                )
+                .with_handles(snapshot.handles)
+                .with_type(snapshot.keyframe_type)
+                .with_spring(snapshot.spring),
+            );
+        }
+    }
+
+    /// Group and track index of the track row `row_id`.
+    fn track_row(&self, row_id: &str) -> Option<(usize, usize)> {
+        self.groups.iter().enumerate().find_map(|(g, group)| {
+            group
+                .tracks
+                .iter()
+                .position(|t| t.track.id.0.to_string() == row_id)
+                .map(|t| (g, t))
+        })
+    }
+
+    fn group_index(&self, row_id: &str) -> Option<usize> {
+        self.groups.iter().position(|group| group.id == row_id)
+    }
+
+    /// Apply `edit` to the label, color and locked flag of a row.
+    fn edit_row(
+        &mut self,
+        row_id: &str,
+        edit: impl FnOnce(&mut String, &mut Option<Color32>, &mut bool),
+    ) {
+        if let Some(g) = self.group_index(row_id) {
+            let group = &mut self.groups[g];
+            edit(&mut group.label, &mut group.color, &mut group.locked);
+        } else if let Some((g, t)) = self.track_row(row_id) {
+            let track = &mut self.groups[g].tracks[t];
+            edit(&mut track.label, &mut track.color, &mut track.track.locked);
+        }
+    }
+
+    fn move_row(&mut self, row_id: &str, new_parent: Option<String>, index: usize) {
+        if let Some(g) = self.group_index(row_id) {
+            // Groups only live at the root level.
+            if new_parent.is_none() {
+                let group = self.groups.remove(g);
+                let index = index.min(self.groups.len());
+                self.groups.insert(index, group);
+            }
+        } else if let Some((g, t)) = self.track_row(row_id)
+            && let Some(parent) = new_parent.and_then(|parent| self.group_index(&parent))
+        {
+            let track = self.groups[g].tracks.remove(t);
+            let tracks = &mut self.groups[parent].tracks;
+            tracks.insert(index.min(tracks.len()), track);
+        }
+    }
+}
+
+impl AnimationDataProvider for ModelData {
+    fn property_rows(&self) -> Vec<PropertyRow> {
+        let mut rows = Vec::new();
+        for group in &self.groups {
+            let group_isolated = self.isolated.as_ref().is_none_or(|id| *id == group.id);
+            let tracks: Vec<&ModelTrack> = group
+                .tracks
+                .iter()
+                .filter(|t| {
+                    group_isolated
+                        || self
+                            .isolated
+                            .as_ref()
+                            .is_some_and(|id| *id == t.track.id.0.to_string())
+                })
+                .collect();
+            if !group_isolated && tracks.is_empty() {
+                continue;
+            }
+
+            rows.push(PropertyRow {
+                id: group.id.clone(),
+                label: group.label.clone(),
+                depth: 0,
+                can_collapse: !group.tracks.is_empty(),
+                is_collapsed: self.collapsed.contains(&group.id),
+                track_id: None,
+                color: group.color,
+                locked: group.locked,
+                driven: false,
+                muted: false,
+                solo: false,
+            });
+            rows.extend(tracks.into_iter().map(|t| PropertyRow {
+                id: t.track.id.0.to_string(),
+                label: t.label.clone(),
+                depth: 1,
+                can_collapse: false,
+                is_collapsed: false,
+                track_id: Some(t.track.id),
+                color: t.color.or(group.color),
+                locked: t.track.locked || group.locked,
+                driven: self.drivers.is_driven(t.track.id),
+                muted: t.muted,
+                solo: t.solo,
+            }));
+        }
+        rows
+    }
+
+    fn keyframe_positions(&self, track_id: TrackId) -> Option<Vec<(KeyframeId, TimeTick)>> {
+        self.track(track_id).map(Track::positions)
+    }
+
+    fn keyframe_value(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<f64> {
+        self.keyframe(track_id, keyframe_id)
+            .map(|kf| kf.value as f64)
+    }
+
+    fn keyframe_handles(
+        &self,
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+    ) -> Option<BezierHandles> {
+        self.keyframe(track_id, keyframe_id).map(|kf| kf.handles)
+    }
+
+    fn keyframe_weighted_handles(
+        &self,
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+    ) -> Option<WeightedHandles> {
+        self.keyframe(track_id, keyframe_id)?.weighted
+    }
+
+    fn keyframe_type(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<KeyframeType> {
+        self.keyframe(track_id, keyframe_id)
+            .map(|kf| kf.keyframe_type)
+    }
+
+    fn keyframe_spring(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<Spring> {
+        self.keyframe(track_id, keyframe_id).map(|kf| kf.spring)
+    }
+
+    fn keyframe_pinned(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.keyframe(track_id, keyframe_id)
+            .is_some_and(|kf| kf.pinned)
+    }
+
+    fn keyframe_locked(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.keyframe(track_id, keyframe_id)
+            .is_some_and(|kf| kf.locked)
+    }
+
+    fn keyframe_breakdown(&self, track_id: TrackId, keyframe_id: KeyframeId) -> bool {
+        self.keyframe(track_id, keyframe_id)
+            .is_some_and(|kf| kf.breakdown)
+    }
+
+    fn keyframe_label(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<String> {
+        self.keyframe(track_id, keyframe_id)?.label.clone()
+    }
+
+    fn keyframe_tag(&self, track_id: TrackId, keyframe_id: KeyframeId) -> Option<KeyframeTag> {
+        self.keyframe(track_id, keyframe_id)?.tag
+    }
+
+    fn track_time_offset(&self, track_id: TrackId) -> TimeTick {
+        self.track(track_id)
+            .map_or(TimeTick::zero(), |track| track.time_offset)
+    }
+
+    fn track_time_scale(&self, track_id: TrackId) -> f64 {
+        self.track(track_id).map_or(1.0, |track| track.time_scale)
+    }
+
+    fn track_value_limits(&self, track_id: TrackId) -> Option<(f64, f64)> {
+        self.tracks().find(|t| t.track.id == track_id)?.limits
+    }
+
+    fn track_lane(&self, track_id: TrackId) -> TrackLane {
+        self.tracks()
+            .find(|t| t.track.id == track_id)
+            .map(|t| t.lane.clone())
+            .unwrap_or_default()
+    }
+
+    fn track_unit(&self, track_id: TrackId) -> ValueUnit {
+        self.tracks()
+            .find(|t| t.track.id == track_id)
+            .map_or(ValueUnit::Generic, |t| t.unit)
+    }
+
+    fn track_blend(&self, track_id: TrackId) -> Option<BlendMode> {
+        self.tracks().find(|t| t.track.id == track_id)?.blend
+    }
+
+    fn find_keyframe(&self, keyframe_id: KeyframeId) -> Option<(TrackId, TimeTick)> {
+        self.tracks().find_map(|t| {
+            t.track
+                .get_keyframe(keyframe_id)
+                .map(|kf| (t.track.id, kf.position))
+        })
+    }
+
+    fn current_time(&self) -> TimeTick {
+        self.current_time
+    }
+
+    fn time_range(&self) -> (TimeTick, TimeTick) {
+        self.time_range
+    }
+
+    fn value_range(&self, track_id: TrackId) -> Option<(f32, f32)> {
+        self.track(track_id)?.value_range()
+    }
+
+    fn track_groups(&self) -> TrackGroups {
+        self.track_groups.clone()
+    }
+
+    fn marker(&self, marker_id: MarkerId) -> Option<Marker> {
+        self.markers.get(marker_id).cloned()
+    }
+}
+
+impl AnimationDataMutator for ModelData {
+    fn execute(&mut self, command: AnimationCommand) {
+        match command {
+            AnimationCommand::AddKeyframe {
+                track_id,
+                position,
+                value,
+            } => {
+                if let Some(track) = self.track_mut(track_id) {
+                    track.add_keyframe(Keyframe::new(position, value as f32));
+                }
+            }
+            AnimationCommand::InsertKeyframe {
+                track_id,
+                keyframe_id,
+                position,
+                value,
+                handles,
+                keyframe_type,
+            } => self.insert(&KeyframeSnapshot {
+                track_id,
+                keyframe_id,
+                position,
+                value,
+                handles,
+                keyframe_type,
+                spring: Spring::default(),
+            }),
+            AnimationCommand::InsertKeyframePreserving {
+                track_id,
+                keyframe_id,
+                position,
+            } => {
+                if let Some(track) = self.track_mut(track_id) {
+                    track.insert_keyframe_on_curve_with_id(keyframe_id, position);
+                }
+            }
+            AnimationCommand::InsertBreakdown {
+                from,
+                to,
+                keyframe_id,
+                fraction,
+            } => {
+                if let Some((track_id, _)) = self.find_keyframe(from)
+                    && let Some(track) = self.track_mut(track_id)
+                {
+                    track.insert_breakdown_with_id(keyframe_id, from, to, fraction);
+                }
+            }
+            AnimationCommand::PasteKeyframes { keyframes } => {
+                // Pinned and locked keyframes and locked tracks are not
+                // pasted over.
+                for snapshot in &keyframes {
+                    if !self.protected_at(snapshot.track_id, snapshot.position) {
+                        self.insert(snapshot);
+                    }
+                }
+            }
+            AnimationCommand::RemoveKeyframes { keyframe_ids } => {
+                for id in keyframe_ids {
+                    if let Some((track_id, _)) = self.find_keyframe(id)
+                        && let Some(track) = self.track_mut(track_id)
+                    {
+                        track.remove_keyframe(id);
+                    }
+                }
+            }
+            AnimationCommand::RemoveKeyframePreserving { keyframe_id } => {
+                if let Some((track_id, _)) = self.find_keyframe(keyframe_id)
+                    && let Some(track) = self.track_mut(track_id)
+                {
+                    track.remove_keyframe_preserving(keyframe_id);
+                }
+            }
+            AnimationCommand::MoveKeyframe {
+                keyframe_id,
+                new_position,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.position = new_position;
+                }
+            }
+            AnimationCommand::SetKeyframeValue { keyframe_id, value } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.value = value as f32;
+                }
+            }
+            AnimationCommand::SetKeyframeHandles {
+                keyframe_id,
+                handles,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.handles = handles;
+                }
+            }
+            AnimationCommand::SetKeyframeWeightedHandles {
+                keyframe_id,
+                handles,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.weighted = handles;
+                }
+            }
+            AnimationCommand::SetKeyframeSpring {
+                keyframe_id,
+                spring,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.spring = spring;
+                }
+            }
+            AnimationCommand::SetKeyframeType {
+                keyframe_id,
+                keyframe_type,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.keyframe_type = keyframe_type;
+                }
+            }
+            AnimationCommand::SetKeyframePinned {
+                keyframe_id,
+                pinned,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.pinned = pinned;
+                }
+            }
+            AnimationCommand::SetKeyframesLocked {
+                keyframe_ids,
+                locked,
+            } => {
+                for id in keyframe_ids {
+                    if let Some(kf) = self.keyframe_mut(id) {
+                        kf.locked = locked;
+                    }
+                }
+            }
+            AnimationCommand::SetKeyframeBreakdown {
+                keyframe_id,
+                breakdown,
+            } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.breakdown = breakdown;
+                }
+            }
+            AnimationCommand::SetKeyframeLabel { keyframe_id, label } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.label = label;
+                }
+            }
+            AnimationCommand::SetKeyframeTag { keyframe_id, tag } => {
+                if let Some(kf) = self.keyframe_mut(keyframe_id) {
+                    kf.tag = tag;
+                }
+            }
+            AnimationCommand::SetCurrentTime(time) => self.current_time = time,
+            AnimationCommand::SetWorkRange { start, end } => self.work_range = Some((start, end)),
+            command @ (AnimationCommand::AddMarker(_)
+            | AnimationCommand::MoveMarker { .. }
+            | AnimationCommand::RemoveMarker { .. }) => self.markers.apply(&command),
+            AnimationCommand::ToggleRowCollapse(row_id) => {
+                if !self.collapsed.remove(&row_id) {
+                    self.collapsed.insert(row_id);
+                }
+            }
+            AnimationCommand::OffsetKeyframes {
+                keyframe_ids,
+                delta_time,
+                delta_value,
+            } => {
+                for id in keyframe_ids {
+                    if let Some(kf) = self.editable_keyframe_mut(id) {
+                        kf.position += delta_time;
+                        kf.value += delta_value as f32;
+                    }
+                }
+            }
+            AnimationCommand::ScaleKeyframes {
+                keyframe_ids,
+                anchor_time,
+                anchor_value,
+                time_scale,
+                value_scale,
+            } => {
+                for id in keyframe_ids {
+                    if let Some(kf) = self.editable_keyframe_mut(id) {
+                        kf.position = anchor_time + (kf.position - anchor_time) * time_scale;
+                        kf.value =
+                            (anchor_value + (kf.value as f64 - anchor_value) * value_scale) as f32;
+                    }
+                }
+            }
+            AnimationCommand::FlipKeyframesTime {
+                keyframe_ids,
+                anchor_time,
+            } => {
+                for group in &mut self.groups {
+                    for track in &mut group.tracks {
+                        track.track.flip_time(&keyframe_ids, anchor_time);
+                    }
+                }
+            }
+            AnimationCommand::ReverseKeyframes { keyframe_ids } => {
+                for group in &mut self.groups {
+                    for track in &mut group.tracks {
+                        track.track.reverse(&keyframe_ids);
+                    }
+                }
+            }
+            AnimationCommand::FlipKeyframesValue {
+                keyframe_ids,
+                anchor_value,
+            } => {
+                // Normalized handles are relative to the value change of
+                // their segment and stay as they are.
+                for id in keyframe_ids {
+                    if let Some(kf) = self.editable_keyframe_mut(id) {
+                        kf.value = (2.0 * anchor_value - kf.value as f64) as f32;
+                        if let Some(weighted) = kf.weighted.as_mut() {
+                            weighted.left_value = -weighted.left_value;
+                            weighted.right_value = -weighted.right_value;
+                        }
+                    }
+                }
+            }
+            AnimationCommand::AddTrack { parent_row, label } => {
+                // Tracks live in groups: without a parent, use the last group.
+                let group = match parent_row.and_then(|id| self.group_index(&id)) {
+                    Some(group) => group,
+                    None if !self.groups.is_empty() => self.groups.len() - 1,
+                    None => {
+                        self.add_group("Group".to_owned());
+                        0
+                    }
+                };
+                self.groups[group].tracks.push(ModelTrack {
+                    label,
+                    color: None,
+                    limits: None,
+                    lane: TrackLane::default(),
+                    unit: ValueUnit::default(),
+                    blend: None,
+                    muted: false,
+                    solo: false,
+                    track: Track::new(),
+                });
+            }
+            AnimationCommand::AddGroup { label, .. } => {
+                // Groups do not nest.
+                self.add_group(label);
+            }
+            AnimationCommand::RemoveTrack { row_id } => {
+                if let Some(g) = self.group_index(&row_id) {
+                    self.groups.remove(g);
+                } else if let Some((g, t)) = self.track_row(&row_id) {
+                    self.groups[g].tracks.remove(t);
+                }
+            }
+            AnimationCommand::RenameRow { row_id, label } => {
+                self.edit_row(&row_id, |old, _, _| *old = label);
+            }
+            AnimationCommand::SetRowColor { row_id, color } => {
+                self.edit_row(&row_id, |_, old, _| *old = color);
+            }
+            AnimationCommand::SetRowLocked { row_id, locked } => {
+                self.edit_row(&row_id, |_, _, old| *old = locked);
+            }
+            AnimationCommand::SetTrackMuted { track_id, muted } => {
+                if let Some(track) = self.model_track_mut(track_id) {
+                    track.muted = muted;
+                }
+            }
+            AnimationCommand::SetTrackSolo { track_id, solo } => {
+                if let Some(track) = self.model_track_mut(track_id) {
+                    track.solo = solo;
+                }
+            }
+            AnimationCommand::MoveRow {
+                row_id,
+                new_parent,
+                index,
+            } => self.move_row(&row_id, new_parent, index),
+            AnimationCommand::ReorderRow { row, target } => {
+                self.move_row(&row, target.parent, target.index);
+            }
+            AnimationCommand::IsolateRow(row_id) => self.isolated = row_id,
+            AnimationCommand::SetTrackGroupVisible { group_id, visible } => {
+                self.track_groups.set_visible(group_id, visible);
+            }
+            AnimationCommand::ResetRow { row_id } => {
+                let row_ids = HashSet::from_iter([row_id]);
+                for track_id in subtree_track_ids(&self.property_rows(), &row_ids) {
+                    if let Some(track) = self.track_mut(track_id) {
+                        let ids: Vec<KeyframeId> = track.keyframe_ids().collect();
+                        ids.into_iter().for_each(|id| {
+                            track.remove_keyframe(id);
+                        });
+                    }
+                }
+            }
+            AnimationCommand::SetTrackRetime {
+                track_id,
+                time_offset,
+                time_scale,
+            } => {
+                if let Some(track) = self.track_mut(track_id) {
+                    track.time_offset = time_offset;
+                    track.time_scale = time_scale;
+                }
+            }
+            AnimationCommand::BakeTrack {
+                track_id,
+                fps,
+                range,
+            } => {
+                if let Some(track) = self.track_mut(track_id) {
+                    track.bake_in_place(fps, range);
+                }
+            }
+        }
+    }
+
+    fn begin_scrub(&mut self) {}
+
+    fn end_scrub(&mut self, _commit: bool) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::history::inverse_command;
+    use crate::dopesheet::SelectionState;
+    use crate::traits::RowTarget;
+
+    fn model() -> (SimpleAnimationModel, TrackId, KeyframeId) {
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Cube");
+        let mut track = Track::new();
+        let id = track.add_keyframe(Keyframe::new(1.0, 10.0));
+        let track_id = model.add_track(&group, "X", track).unwrap();
+        (model, track_id, id)
+    }
+
+    #[test]
+    fn rows_follow_groups_and_collapse() {
+        let (mut model, track_id, _) = model();
+        let rows = model.property_rows();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].label, "Cube");
+        assert_eq!(rows[1].track_id, Some(track_id));
+
+        model.execute(AnimationCommand::ToggleRowCollapse(rows[0].id.clone()));
+        assert!(model.property_rows()[0].is_collapsed);
+    }
+
+    #[test]
+    fn scrub_is_one_undo_step() {
+        let (mut model, track_id, id) = model();
+
+        model.begin_scrub();
+        for time in [2.0, 3.0] {
+            model.execute(AnimationCommand::MoveKeyframe {
+                keyframe_id: id,
+                new_position: TimeTick::new(time),
+            });
+        }
+        model.end_scrub(true);
+        assert_eq!(
+            model.find_keyframe(id),
+            Some((track_id, TimeTick::new(3.0)))
+        );
+
+        assert!(model.undo());
+        assert_eq!(
+            model.find_keyframe(id),
+            Some((track_id, TimeTick::new(1.0)))
+        );
+        assert!(!model.undo());
+        assert!(model.redo());
+        assert_eq!(
+            model.find_keyframe(id),
+            Some((track_id, TimeTick::new(3.0)))
+        );
+    }
+
+    #[test]
+    fn preserving_insert_and_remove_are_undoable() {
+        let (mut model, track_id, id) = model();
+        let track = model.track_mut(track_id).unwrap();
+        track.get_keyframe_mut(id).unwrap().keyframe_type = KeyframeType::Auto;
+        track.add_keyframe(Keyframe::new(3.0, 20.0));
+        let before = model.track(track_id).unwrap().evaluate(1.5);
+
+        let inserted = KeyframeId::new();
+        model.execute(AnimationCommand::InsertKeyframePreserving {
+            track_id,
+            keyframe_id: inserted,
+            position: TimeTick::new(2.0),
+        });
+        let track = model.track(track_id).unwrap();
+        assert_eq!(track.len(), 3);
+        assert_eq!(
+            track.get_keyframe(id).unwrap().keyframe_type,
+            KeyframeType::Bezier
+        );
+        assert!((track.evaluate(1.5).unwrap() - before.unwrap()).abs() < 1e-3);
+
+        model.execute(AnimationCommand::RemoveKeyframePreserving {
+            keyframe_id: inserted,
+        });
+        assert!(
+            model
+                .track(track_id)
+                .unwrap()
+                .get_keyframe(inserted)
+                .is_none()
+        );
+        assert!(model.undo());
+        assert!(
+            model
+                .track(track_id)
+                .unwrap()
+                .get_keyframe(inserted)
+                .is_some()
+        );
+
+        assert!(model.undo());
+        let track = model.track(track_id).unwrap();
+        assert!(track.get_keyframe(inserted).is_none());
+        assert_eq!(
+            track.get_keyframe(id).unwrap().keyframe_type,
+            KeyframeType::Auto
+        );
+    }
+
+    #[test]
+    fn bake_is_undoable() {
+        let (mut model, track_id, id) = model();
+        model.execute(AnimationCommand::BakeTrack {
+            track_id,
+            fps: 10.0,
+            range: (TimeTick::zero(), TimeTick::new(1.0)),
+        });
+        let baked = model.keyframe_positions(track_id).unwrap();
+        assert_eq!(baked.len(), 11);
+        assert!(model.track(track_id).unwrap().get_keyframe(id).is_none());
+
+        assert!(model.undo());
+        assert_eq!(model.keyframe_positions(track_id).unwrap().len(), 1);
+        assert!(model.track(track_id).unwrap().get_keyframe(id).is_some());
+
+        // Redo re-creates the baked keyframes with the same IDs.
+        assert!(model.redo());
+        assert_eq!(model.keyframe_positions(track_id).unwrap(), baked);
+    }
+
+    #[test]
+    fn pinned_keyframes_resist_batch_edits() {
+        let (mut model, track_id, id) = model();
+        model.execute(AnimationCommand::SetKeyframePinned {
+            keyframe_id: id,
+            pinned: true,
+        });
+        assert!(model.keyframe_pinned(track_id, id));
+
+        model.execute(AnimationCommand::OffsetKeyframes {
+            keyframe_ids: vec![id],
+            delta_time: TimeTick::new(1.0),
+            delta_value: 1.0,
+        });
+        assert_eq!(
+            model.find_keyframe(id),
+            Some((track_id, TimeTick::new(1.0)))
+        );
+
+        // Pasting over the pinned keyframe is skipped.
+        let clipboard = crate::core::clipboard::Clipboard::copy(&model, [id]);
+        model.execute(clipboard.paste(track_id, TimeTick::new(1.0), Default::default()));
+        assert_eq!(model.track(track_id).unwrap().len(), 1);
+
+        // Undoing a removal restores the pin.
+        model.execute(AnimationCommand::RemoveKeyframes {
+            keyframe_ids: vec![id],
+        });
+        assert!(model.undo());
+        assert!(model.keyframe_pinned(track_id, id));
+    }
+
+    #[test]
+    fn locked_keyframes_and_tracks_resist_batch_edits() {
+        let (mut model, track_id, id) = model();
+        let other = model
+            .track_mut(track_id)
+            .unwrap()
+            .add_keyframe(Keyframe::new(2.0, 20.0));
+        model.execute(AnimationCommand::SetKeyframesLocked {
+            keyframe_ids: vec![id],
+            locked: true,
+        });
+
+        let offset = AnimationCommand::OffsetKeyframes {
+            keyframe_ids: vec![id, other],
+            delta_time: TimeTick::new(1.0),
+            delta_value: 1.0,
+        };
+        model.execute(offset.clone());
+        assert_eq!(model.keyframe_value(track_id, id), Some(10.0));
+        assert_eq!(model.keyframe_value(track_id, other), Some(21.0));
+
+        model.execute(AnimationCommand::FlipKeyframesValue {
+            keyframe_ids: vec![id],
+            anchor_value: 0.0,
+        });
+        assert_eq!(model.keyframe_value(track_id, id), Some(10.0));
+
+        // Pasting over the locked keyframe is skipped.
+        let clipboard = crate::core::clipboard::Clipboard::copy(&model, [other]);
+        model.execute(clipboard.paste(track_id, TimeTick::new(1.0), Default::default()));
+        assert_eq!(model.keyframe_value(track_id, id), Some(10.0));
+        assert_eq!(model.track(track_id).unwrap().len(), 2);
+
+        // A locked track keeps all of its keys.
+        model.execute(AnimationCommand::SetRowLocked {
+            row_id: track_id.0.to_string(),
+            locked: true,
+        });
+        model.execute(offset);
+        assert_eq!(model.keyframe_value(track_id, other), Some(21.0));
+        model.execute(clipboard.paste(track_id, TimeTick::new(5.0), Default::default()));
+        assert_eq!(model.track(track_id).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn flips_are_undoable() {
+        let (mut model, track_id, first) = model();
+        let second = model
+            .track_mut(track_id)
+            .unwrap()
+            .add_keyframe(Keyframe::new(3.0, 20.0));
+        let keyframe_ids = vec![first, second];
+
+        model.execute(AnimationCommand::FlipKeyframesValue {
+            keyframe_ids: keyframe_ids.clone(),
+            anchor_value: 15.0,
+        });
+        assert_eq!(model.keyframe_value(track_id, first), Some(20.0));
+        assert_eq!(model.keyframe_value(track_id, second), Some(10.0));
+
+        model.execute(AnimationCommand::FlipKeyframesTime {
+            keyframe_ids,
+            anchor_time: TimeTick::new(2.5),
+        });
+        assert_eq!(
+            model.find_keyframe(first),
+            Some((track_id, TimeTick::new(4.0)))
+        );
+        assert!(model.undo());
+        assert!(model.undo());
+        assert_eq!(model.keyframe_value(track_id, first), Some(10.0));
+        assert_eq!(
+            model.find_keyframe(second),
+            Some((track_id, TimeTick::new(3.0)))
+        );
+    }
+
+    #[test]
+    fn breakdowns_keep_the_curve_and_are_undoable() {
+        let (mut model, track_id, first) = model();
+        let second = model
+            .track_mut(track_id)
+            .unwrap()
+            .add_keyframe(Keyframe::new(3.0, 20.0));
+        let value = model.track(track_id).unwrap().evaluate(1.5).unwrap();
+
+        let breakdown = KeyframeId::new();
+        model.execute(AnimationCommand::InsertBreakdown {
+            from: first,
+            to: second,
+            keyframe_id: breakdown,
+            fraction: 0.25,
+        });
+        assert_eq!(
+            model.find_keyframe(breakdown),
+            Some((track_id, TimeTick::new(1.5)))
+        );
+        assert!(model.keyframe_breakdown(track_id, breakdown));
+        assert!((model.keyframe_value(track_id, breakdown).unwrap() - value as f64).abs() < 1e-4);
+
+        // Removing and restoring keeps the breakdown mark.
+        model.execute(AnimationCommand::RemoveKeyframes {
+            keyframe_ids: vec![breakdown],
+        });
+        assert!(model.undo());
+        assert!(model.keyframe_breakdown(track_id, breakdown));
+        assert!(model.undo());
+        assert_eq!(model.find_keyframe(breakdown), None);
+    }
+
+    #[test]
+    fn labels_and_tags_survive_removal_and_select_keys() {
+        let (mut model, track_id, first) = model();
+        let second = model
+            .track_mut(track_id)
+            .unwrap()
+            .add_keyframe(Keyframe::new(3.0, 20.0));
+
+        model.execute(AnimationCommand::SetKeyframeLabel {
+            keyframe_id: first,
+            label: Some("Contact".into()),
+        });
+        model.execute(AnimationCommand::SetKeyframeTag {
+            keyframe_id: first,
+            tag: Some(KeyframeTag::Red),
+        });
+        assert_eq!(
+            model.keyframe_label(track_id, first).as_deref(),
+            Some("Contact")
+        );
+
+        let mut selection = SelectionState::default();
+        selection.select_tagged(&model, KeyframeTag::Red, false);
+        assert!(selection.is_keyframe_selected(&first));
+        assert!(!selection.is_keyframe_selected(&second));
+
+        // Removing and restoring keeps the label and tag.
+        model.execute(AnimationCommand::RemoveKeyframes {
+            keyframe_ids: vec![first],
+        });
+        assert!(model.undo());
+        assert_eq!(model.keyframe_tag(track_id, first), Some(KeyframeTag::Red));
+        assert_eq!(
+            model.keyframe_label(track_id, first).as_deref(),
+            Some("Contact")
+        );
+        assert!(model.undo());
+        assert_eq!(model.keyframe_tag(track_id, first), None);
+    }
+
+    #[test]
+    fn keyframe_and_track_locks_are_undoable() {
+        let (mut model, track_id, first) = model();
+        let second = model
+            .track_mut(track_id)
+            .unwrap()
+            .add_keyframe(Keyframe::new(3.0, 20.0));
+        model.execute(AnimationCommand::SetKeyframesLocked {
+            keyframe_ids: vec![first],
+            locked: true,
+        });
+        model.execute(AnimationCommand::SetKeyframesLocked {
+            keyframe_ids: vec![first, second],
+            locked: true,
+        });
+        assert!(model.keyframe_locked(track_id, second));
+        assert!(model.undo());
+        assert!(model.keyframe_locked(track_id, first));
+        assert!(!model.keyframe_locked(track_id, second));
+
+        // A locked track locks the keyframes it shows.
+        model.set_active_track(Some(track_id));
+        model.execute(AnimationCommand::SetRowLocked {
+            row_id: track_id.0.to_string(),
+            locked: true,
+        });
+        assert!(model.track(track_id).unwrap().locked);
+        assert!(KeyframeSource::is_locked(&model));
+        assert!(
+            KeyframeSource::keyframes_sorted(&model)
+                .iter()
+                .all(|kf| kf.locked)
+        );
+        assert!(model.undo());
+        assert!(!model.track(track_id).unwrap().locked);
+        assert!(!KeyframeSource::is_locked(&model));
+    }
+
+    #[test]
+    fn reordered_rows_are_undoable() {
+        let (mut model, track_id, _) = model();
+        let sphere = model.add_group("Sphere");
+        let labels = |model: &SimpleAnimationModel| {
+            model
+                .property_rows()
+                .into_iter()
+                .map(|row| row.label)
+                .collect::<Vec<_>>()
+        };
+
+        model.execute(AnimationCommand::ReorderRow {
+            row: track_id.0.to_string(),
+            target: RowTarget {
+                parent: Some(sphere.clone()),
+                index: 0,
+            },
+        });
+        model.execute(AnimationCommand::ReorderRow {
+            row: sphere,
+            target: RowTarget {
+                parent: None,
+                index: 0,
+            },
+        });
+        assert_eq!(labels(&model), ["Sphere", "X", "Cube"]);
+
+        assert!(model.undo());
+        assert_eq!(labels(&model), ["Cube", "Sphere", "X"]);
+        assert!(model.undo());
+        assert_eq!(labels(&model), ["Cube", "X", "Sphere"]);
+        assert!(model.redo());
+        assert_eq!(labels(&model), ["Cube", "Sphere", "X"]);
+    }
+
+    #[test]
+    fn reset_row_undo_restores_keyframe_state() {
+        let (mut model, track_id, id) = model();
+        let weighted = WeightedHandles {
+            left_time: -0.5,
+            left_value: 1.0,
+            right_time: 0.5,
+            right_value: -1.0,
+        };
+        let spring = Spring {
+            frequency: 3.0,
+            damping: 0.4,
+            response: 0.0,
+        };
+        model.execute(AnimationCommand::SetKeyframeType {
+            keyframe_id: id,
+            keyframe_type: KeyframeType::Spring,
+        });
+        model.execute(AnimationCommand::SetKeyframeWeightedHandles {
+            keyframe_id: id,
+            handles: Some(weighted),
+        });
+        model.execute(AnimationCommand::SetKeyframeSpring {
+            keyframe_id: id,
+            spring,
+        });
+        model.execute(AnimationCommand::SetKeyframeLabel {
+            keyframe_id: id,
+            label: Some("Contact".into()),
+        });
+        model.execute(AnimationCommand::SetKeyframeTag {
+            keyframe_id: id,
+            tag: Some(KeyframeTag::Green),
+        });
+        model.execute(AnimationCommand::SetKeyframeBreakdown {
+            keyframe_id: id,
+            breakdown: true,
+        });
+        model.execute(AnimationCommand::SetKeyframesLocked {
+            keyframe_ids: vec![id],
+            locked: true,
+        });
+        let state = |model: &SimpleAnimationModel| {
+            (
+                KeyframeSnapshot::capture(model, id),
+                model.keyframe_weighted_handles(track_id, id),
+                model.keyframe_label(track_id, id),
+                model.keyframe_tag(track_id, id),
+                model.keyframe_breakdown(track_id, id),
+                model.keyframe_locked(track_id, id),
+            )
+        };
+        let before = state(&model);
+        assert_eq!(before.0.map(|snapshot| snapshot.spring), Some(spring));
+
+        model.execute(AnimationCommand::ResetRow {
+            row_id: track_id.0.to_string(),
+        });
+        assert_eq!(model.find_keyframe(id), None);
+
+        assert!(model.undo());
+        assert_eq!(state(&model), before);
+        assert!(model.redo());
+        assert_eq!(model.find_keyframe(id), None);
+    }
+
+    #[test]
+    fn handle_edits_undo_to_weighted_handles() {
+        let (mut model, track_id, id) = model();
+        let weighted = Some(WeightedHandles {
+            left_time: -0.25,
+            left_value: 2.0,
+            right_time: 0.25,
+            right_value: 2.0,
+        });
+        let handles = BezierHandles {
+            right_y: 0.8,
+            ..Default::default()
+        };
+        model.execute(AnimationCommand::SetKeyframeWeightedHandles {
+            keyframe_id: id,
+            handles: weighted,
+        });
+
+        let edit = AnimationCommand::SetKeyframeHandles {
+            keyframe_id: id,
+            handles,
+        };
+        assert!(matches!(
+            inverse_command(&model, &edit).as_deref(),
+            Some([
+                AnimationCommand::SetKeyframeHandles { .. },
+                AnimationCommand::SetKeyframeWeightedHandles { handles, .. },
+            ]) if *handles == weighted
+        ));
+
+        model.execute(edit);
+        assert!(model.undo());
+        assert_eq!(
+            model.keyframe_handles(track_id, id),
+            Some(BezierHandles::default())
+        );
+        assert_eq!(model.keyframe_weighted_handles(track_id, id), weighted);
+
+        assert!(model.redo());
+        assert_eq!(model.keyframe_handles(track_id, id), Some(handles));
+        assert_eq!(model.keyframe_weighted_handles(track_id, id), weighted);
+    }
+
+    #[test]
+    fn marker_edits_are_undoable() {
+        let (mut model, _, _) = model();
+        let marker = Marker::new(1.0, model.markers().next_label());
+        let id = marker.id;
+        let time = |model: &SimpleAnimationModel| model.markers().get(id).map(|m| m.time);
+
+        model.execute(AnimationCommand::AddMarker(marker));
+        model.execute(AnimationCommand::MoveMarker {
+            marker_id: id,
+            time: TimeTick::new(2.0),
+        });
+        assert_eq!(time(&model), Some(TimeTick::new(2.0)));
+        model.execute(AnimationCommand::RemoveMarker { marker_id: id });
+        assert!(model.markers().is_empty());
+
+        assert!(model.undo());
+        assert_eq!(model.markers().get(id).unwrap().label, "M1");
+        assert!(model.undo());
+        assert_eq!(time(&model), Some(TimeTick::new(1.0)));
+        assert!(model.undo());
+        assert_eq!(time(&model), None);
+        assert!(model.redo());
+        assert_eq!(time(&model), Some(TimeTick::new(1.0)));
+
+        let missing = AnimationCommand::RemoveMarker {
+            marker_id: MarkerId::new(),
+        };
+        assert!(missing.validate(&model).is_err());
+    }
+
+    #[test]
+    fn mute_and_solo_pick_the_audible_tracks() {
+        let (mut model, first, _) = model();
+        let group = model.add_group("Sphere");
+        let second = model.add_track(&group, "Y", Track::new()).unwrap();
+        let audible =
+            |model: &SimpleAnimationModel| crate::audible_track_ids(&model.property_rows());
+        assert_eq!(audible(&model).len(), 2);
+
+        model.execute(AnimationCommand::SetTrackSolo {
+            track_id: first,
+            solo: true,
+        });
+        assert_eq!(audible(&model), HashSet::from_iter([first]));
+        model.execute(AnimationCommand::SetTrackMuted {
+            track_id: first,
+            muted: true,
+        });
+        assert!(audible(&model).is_empty());
+        let rows = model.property_rows();
+        let row = rows.iter().find(|row| row.track_id == Some(first)).unwrap();
+        assert!(row.muted && row.solo);
+
+        model.execute(AnimationCommand::SetTrackSolo {
+            track_id: first,
+            solo: false,
+        });
+        assert_eq!(audible(&model), HashSet::from_iter([second]));
+        // Auditioning is not undoable.
+        assert!(!model.undo());
+    }
+
+    #[test]
+    fn active_track_is_keyframe_source() {
+        let (mut model, track_id, _) = model();
+        assert!(KeyframeSource::is_empty(&model));
+
+        model.set_active_track(Some(track_id));
+        assert_eq!(KeyframeSource::len(&model), 1);
+        assert_eq!(model.keyframes_sorted()[0].value, 10.0);
+    }
+}
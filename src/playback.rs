@@ -0,0 +1,406 @@
+//! Playback of animation time within a work area.
+//!
+//! [`PlaybackController`] advances the current time and reports
+//! [`PlaybackEvent`]s when playback wraps, bounces or finishes at the range
+//! boundaries, so hosts can reset particle systems or audio.
+//! [`PlaybackController::update`] drives it from egui's frame time and emits
+//! [`AnimationCommand::SetCurrentTime`].
+
+use crate::TimeTick;
+use crate::traits::AnimationCommand;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// What happens when playback reaches a boundary of the work area.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum LoopMode {
+    /// Play once and stop at the boundary.
+    Once,
+    /// Jump back to the opposite boundary and continue.
+    #[default]
+    Loop,
+    /// Reverse direction at each boundary.
+    PingPong,
+}
+
+impl LoopMode {
+    /// Get the display name for this mode.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Once => "Once",
+            Self::Loop => "Loop",
+            Self::PingPong => "Ping-Pong",
+        }
+    }
+
+    /// Get all modes.
+    pub fn all() -> &'static [Self] {
+        &[Self::Once, Self::Loop, Self::PingPong]
+    }
+}
+
+/// Playback direction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PlaybackDirection {
+    /// Time increases.
+    #[default]
+    Forward,
+    /// Time decreases.
+    Backward,
+}
+
+impl PlaybackDirection {
+    /// The opposite direction.
+    pub fn reversed(self) -> Self {
+        match self {
+            Self::Forward => Self::Backward,
+            Self::Backward => Self::Forward,
+        }
+    }
+
+    /// `1.0` for forward, `-1.0` for backward.
+    pub fn sign(self) -> f64 {
+        match self {
+            Self::Forward => 1.0,
+            Self::Backward => -1.0,
+        }
+    }
+}
+
+/// Event emitted while advancing playback.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlaybackEvent {
+    /// Playback left the range at `from` and re-entered at `to`.
+    Wrapped {
+        /// Boundary playback left the range at.
+        from: TimeTick,
+        /// Boundary playback re-entered the range at.
+        to: TimeTick,
+    },
+    /// Playback reversed direction at a boundary.
+    Bounced {
+        /// Boundary where the direction changed.
+        at: TimeTick,
+    },
+    /// Playback reached a boundary and stopped.
+    Finished {
+        /// Boundary where playback stopped.
+        at: TimeTick,
+    },
+}
+
+/// Result of [`PlaybackController::update`].
+#[derive(Debug, Clone, Default)]
+pub struct PlaybackResponse {
+    /// Boundary events that occurred this frame, in order.
+    pub events: Vec<PlaybackEvent>,
+    /// Animation commands to execute (a [`AnimationCommand::SetCurrentTime`]
+    /// while playing).
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Advances animation time within a work area.
+///
+/// Time units are seconds when driven by [`update`](Self::update).
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlaybackController {
+    /// In/out range `(start, end)` playback is confined to.
+    pub range: (TimeTick, TimeTick),
+    /// Behavior at the range boundaries.
+    pub loop_mode: LoopMode,
+    /// Playback speed multiplier; `1.0` is real time.
+    pub speed: f64,
+    time: TimeTick,
+    direction: PlaybackDirection,
+    playing: bool,
+}
+
+impl PlaybackController {
+    /// Create a stopped controller at the start of `range`.
+    pub fn new(start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        let start = start.into();
+        Self {
+            range: (start, end.into()),
+            loop_mode: LoopMode::default(),
+            speed: 1.0,
+            time: start,
+            direction: PlaybackDirection::default(),
+            playing: false,
+        }
+    }
+
+    /// Set the loop mode.
+    pub fn with_loop_mode(mut self, loop_mode: LoopMode) -> Self {
+        self.loop_mode = loop_mode;
+        self
+    }
+
+    /// Set the playback speed multiplier.
+    pub fn with_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Apply a [`AnimationCommand::SetCurrentTime`] or
+    /// [`AnimationCommand::SetWorkRange`]. Returns `false` for other commands.
+    pub fn apply_command(&mut self, command: &AnimationCommand) -> bool {
+        match command {
+            AnimationCommand::SetCurrentTime(time) => self.time = *time,
+            AnimationCommand::SetWorkRange { start, end } => self.range = (*start, *end),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Current time.
+    pub fn time(&self) -> TimeTick {
+        self.time
+    }
+
+    /// Set the current time, e.g. after the user scrubbed the playhead.
+    pub fn set_time(&mut self, time: impl Into<TimeTick>) {
+        self.time = time.into();
+    }
+
+    /// Current direction.
+    pub fn direction(&self) -> PlaybackDirection {
+        self.direction
+    }
+
+    /// Set the direction.
+    pub fn set_direction(&mut self, direction: PlaybackDirection) {
+        self.direction = direction;
+    }
+
+    /// Whether playback is running.
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Start playback.
+    ///
+    /// If the current time lies outside the work area, or at the boundary
+    /// playback would immediately finish at, it restarts from the opposite
+    /// boundary.
+    pub fn play(&mut self) {
+        let (start, end) = self.ordered_range();
+        let at_end = match self.direction {
+            PlaybackDirection::Forward => self.time >= end,
+            PlaybackDirection::Backward => self.time <= start,
+        };
+        if self.time < start || self.time > end || at_end {
+            self.time = match self.direction {
+                PlaybackDirection::Forward => start,
+                PlaybackDirection::Backward => end,
+            };
+        }
+        self.playing = true;
+    }
+
+    /// Pause playback at the current time.
+    pub fn pause(&mut self) {
+        self.playing = false;
+    }
+
+    /// Stop playback and return to the boundary playback starts from.
+    pub fn stop(&mut self) {
+        self.playing = false;
+        let (start, end) = self.ordered_range();
+        self.time = match self.direction {
+            PlaybackDirection::Forward => start,
+            PlaybackDirection::Backward => end,
+        };
+    }
+
+    /// Toggle between playing and paused.
+    pub fn toggle(&mut self) {
+        if self.playing {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Advance playback by `dt` time units.
+    ///
+    /// Returns the boundary events that occurred, in order. Does nothing
+    /// while paused.
+    pub fn advance(&mut self, dt: f64) -> Vec<PlaybackEvent> {
+        let mut events = Vec::new();
+        if !self.playing {
+            return events;
+        }
+
+        let (start, end) = self.ordered_range();
+        let (start, end) = (start.value(), end.value());
+        let length = end - start;
+        if length <= 0.0 {
+            self.time = TimeTick::new(start);
+            return events;
+        }
+
+        let mut t = self.time.value() + dt * self.direction.sign();
+
+        match self.loop_mode {
+            LoopMode::Once => {
+                if t >= end || t <= start {
+                    let at = if t >= end { end } else { start };
+                    t = at;
+                    self.playing = false;
+                    events.push(PlaybackEvent::Finished {
+                        at: TimeTick::new(at),
+                    });
+                }
+            }
+            LoopMode::Loop => {
+                if t > end || t < start {
+                    let (from, to) = if t > end { (end, start) } else { (start, end) };
+                    t = start + (t - start).rem_euclid(length);
+                    events.push(PlaybackEvent::Wrapped {
+                        from: TimeTick::new(from),
+                        to: TimeTick::new(to),
+                    });
+                }
+            }
+            LoopMode::PingPong => {
+                while t > end || t < start {
+                    let at = if t > end { end } else { start };
+                    t = 2.0 * at - t;
+                    self.direction = self.direction.reversed();
+                    events.push(PlaybackEvent::Bounced {
+                        at: TimeTick::new(at),
+                    });
+                }
+            }
+        }
+
+        self.time = TimeTick::new(t);
+        events
+    }
+
+    /// Advance playback by egui's stable frame time scaled by
+    /// [`speed`](Self::speed).
+    ///
+    /// While playing this requests a repaint and emits
+    /// [`AnimationCommand::SetCurrentTime`] with the new time.
+    pub fn update(&mut self, ctx: &egui::Context) -> PlaybackResponse {
+        if !self.playing {
+            return PlaybackResponse::default();
+        }
+
+        let dt = ctx.input(|i| i.stable_dt) as f64 * self.speed.max(0.0);
+        let events = self.advance(dt);
+        if self.playing {
+            ctx.request_repaint();
+        }
+
+        PlaybackResponse {
+            events,
+            commands: vec![AnimationCommand::SetCurrentTime(self.time)],
+        }
+    }
+
+    /// Start and end of the range in ascending order.
+    pub fn ordered_range(&self) -> (TimeTick, TimeTick) {
+        let (a, b) = self.range;
+        (a.min(b), a.max(b))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_wraps() {
+        let mut playback = PlaybackController::new(1.0, 3.0);
+        playback.play();
+        assert!(playback.advance(1.5).is_empty());
+
+        let events = playback.advance(1.0);
+        assert_eq!(
+            events,
+            vec![PlaybackEvent::Wrapped {
+                from: TimeTick::new(3.0),
+                to: TimeTick::new(1.0)
+            }]
+        );
+        assert!((playback.time().value() - 1.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn ping_pong_bounces() {
+        let mut playback = PlaybackController::new(0.0, 2.0).with_loop_mode(LoopMode::PingPong);
+        playback.play();
+
+        let events = playback.advance(2.5);
+        assert_eq!(
+            events,
+            vec![PlaybackEvent::Bounced {
+                at: TimeTick::new(2.0)
+            }]
+        );
+        assert_eq!(playback.direction(), PlaybackDirection::Backward);
+        assert!((playback.time().value() - 1.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn once_stops() {
+        let mut playback = PlaybackController::new(0.0, 1.0).with_loop_mode(LoopMode::Once);
+        playback.play();
+
+        let events = playback.advance(2.0);
+        assert_eq!(
+            events,
+            vec![PlaybackEvent::Finished {
+                at: TimeTick::new(1.0)
+            }]
+        );
+        assert!(!playback.is_playing());
+        assert!(playback.advance(1.0).is_empty());
+
+        // Playing again restarts from the beginning.
+        playback.play();
+        assert_eq!(playback.time(), TimeTick::new(0.0));
+    }
+
+    #[test]
+    fn stop_returns_to_start() {
+        let mut playback = PlaybackController::new(1.0, 3.0);
+        playback.play();
+        playback.advance(0.5);
+        playback.stop();
+        assert!(!playback.is_playing());
+        assert_eq!(playback.time(), TimeTick::new(1.0));
+
+        playback.set_direction(PlaybackDirection::Backward);
+        playback.stop();
+        assert_eq!(playback.time(), TimeTick::new(3.0));
+    }
+
+    #[test]
+    fn work_range_command_confines_playback() {
+        let mut playback = PlaybackController::new(0.0, 10.0);
+        assert!(playback.apply_command(&AnimationCommand::SetWorkRange {
+            start: TimeTick::new(2.0),
+            end: TimeTick::new(4.0),
+        }));
+        assert!(!playback.apply_command(&AnimationCommand::ToggleRowCollapse("a".into())));
+
+        playback.play();
+        assert_eq!(playback.time(), TimeTick::new(2.0));
+        playback.advance(2.5);
+        assert!((playback.time().value() - 2.5).abs() < 1e-10);
+    }
+
+    #[test]
+    fn play_outside_range_starts_at_boundary() {
+        let mut playback = PlaybackController::new(2.0, 4.0);
+        playback.set_time(10.0);
+        playback.play();
+        assert_eq!(playback.time(), TimeTick::new(2.0));
+    }
+}
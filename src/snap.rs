@@ -0,0 +1,200 @@
+//! Snapping of dragged times to frames, keyframes, markers and the grid.
+//!
+//! [`SnapConfig`] is shared by the [`CurveEditor`](crate::CurveEditor) and the
+//! [`DopeSheet`](crate::DopeSheet). Snapping is skipped while
+//! [`SnapConfig::disable_modifier`] is held.
+
+use crate::TimeTick;
+use crate::widgets::time_ruler::grid_interval;
+use egui::Modifiers;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// What a time was snapped to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SnapTarget {
+    /// A marker.
+    Marker,
+    /// Another keyframe.
+    Keyframe,
+    /// A major grid line.
+    Grid,
+    /// A frame boundary.
+    Frame,
+}
+
+/// Snapping configuration.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SnapConfig {
+    /// Master switch.
+    pub enabled: bool,
+    /// Frame rate used for frame snapping; `None` disables it.
+    pub fps: Option<f64>,
+    /// Snap to frame boundaries.
+    pub to_frames: bool,
+    /// Snap to other keyframes on visible tracks.
+    pub to_keyframes: bool,
+    /// Snap to markers.
+    pub to_markers: bool,
+    /// Snap to major grid lines.
+    pub to_grid: bool,
+    /// Distance in pixels within which keyframes, markers and grid lines
+    /// attract.
+    pub threshold: f32,
+    /// Modifier that temporarily disables snapping while held.
+    pub disable_modifier: Modifiers,
+}
+
+impl Default for SnapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            fps: None,
+            to_frames: true,
+            to_keyframes: true,
+            to_markers: true,
+            to_grid: false,
+            threshold: 8.0,
+            disable_modifier: Modifiers::CTRL,
+        }
+    }
+}
+
+impl SnapConfig {
+    /// Set the frame rate used for frame snapping.
+    pub fn with_fps(mut self, fps: f64) -> Self {
+        self.fps = Some(fps);
+        self
+    }
+
+    /// Whether snapping applies with the given modifiers held.
+    pub fn is_active(&self, modifiers: &Modifiers) -> bool {
+        self.enabled
+            && (self.disable_modifier.is_none() || !modifiers.contains(self.disable_modifier))
+    }
+
+    /// Snap `time` to the nearest target.
+    ///
+    /// Markers, keyframes and grid lines within [`threshold`](Self::threshold)
+    /// pixels win, with earlier kinds preferred at equal distance. Otherwise
+    /// the time is rounded to the nearest frame if frame snapping is on.
+    /// Returns `None` if nothing applies.
+    pub fn snap(
+        &self,
+        time: TimeTick,
+        pixels_per_unit: f64,
+        keyframes: &[TimeTick],
+        markers: &[TimeTick],
+    ) -> Option<(TimeTick, SnapTarget)> {
+        let threshold = self.threshold as f64 / pixels_per_unit;
+        let nearest = |times: &[TimeTick]| {
+            times.iter().copied().min_by(|a, b| {
+                let (da, db) = ((*a - time).value().abs(), (*b - time).value().abs());
+                da.partial_cmp(&db).unwrap_or(std::cmp::Ordering::Equal)
+            })
+        };
+
+        let grid = self.to_grid.then(|| {
+            let interval = grid_interval(pixels_per_unit);
+            TimeTick::new((time.value() / interval).round() * interval)
+        });
+
+        let candidates = [
+            (
+                self.to_markers.then(|| nearest(markers)).flatten(),
+                SnapTarget::Marker,
+            ),
+            (
+                self.to_keyframes.then(|| nearest(keyframes)).flatten(),
+                SnapTarget::Keyframe,
+            ),
+            (grid, SnapTarget::Grid),
+        ];
+
+        let mut best: Option<(TimeTick, SnapTarget, f64)> = None;
+        for (candidate, target) in candidates {
+            let Some(candidate) = candidate else {
+                continue;
+            };
+            let distance = (candidate - time).value().abs();
+            if distance <= threshold && best.is_none_or(|(_, _, d)| distance < d) {
+                best = Some((candidate, target, distance));
+            }
+        }
+        if let Some((candidate, target, _)) = best {
+            return Some((candidate, target));
+        }
+
+        self.fps
+            .filter(|fps| self.to_frames && *fps > 0.0)
+            .map(|fps| {
+                (
+                    TimeTick::new((time.value() * fps).round() / fps),
+                    SnapTarget::Frame,
+                )
+            })
+    }
+
+    /// Snap `time` if snapping is active with `modifiers` held, otherwise
+    /// return it unchanged.
+    pub fn apply(
+        &self,
+        time: TimeTick,
+        modifiers: &Modifiers,
+        pixels_per_unit: f64,
+        keyframes: &[TimeTick],
+        markers: &[TimeTick],
+    ) -> TimeTick {
+        if !self.is_active(modifiers) {
+            return time;
+        }
+        self.snap(time, pixels_per_unit, keyframes, markers)
+            .map_or(time, |(snapped, _)| snapped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snap_prefers_targets_within_threshold() {
+        let config = SnapConfig::default().with_fps(24.0);
+        let keyframes = [TimeTick::new(1.03)];
+        let markers = [TimeTick::new(2.0)];
+
+        // 100 px per unit: threshold is 0.08 units.
+        assert_eq!(
+            config.snap(TimeTick::new(1.0), 100.0, &keyframes, &markers),
+            Some((TimeTick::new(1.03), SnapTarget::Keyframe))
+        );
+        assert_eq!(
+            config.snap(TimeTick::new(1.95), 100.0, &keyframes, &markers),
+            Some((TimeTick::new(2.0), SnapTarget::Marker))
+        );
+
+        let (time, target) = config
+            .snap(TimeTick::new(1.5), 100.0, &keyframes, &markers)
+            .unwrap();
+        assert_eq!(target, SnapTarget::Frame);
+        assert!((time.value() - 36.0 / 24.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn disable_modifier_skips_snapping() {
+        let config = SnapConfig::default();
+        let keyframes = [TimeTick::new(1.0)];
+        let time = TimeTick::new(1.05);
+
+        assert_eq!(
+            config.apply(time, &Modifiers::NONE, 100.0, &keyframes, &[]),
+            TimeTick::new(1.0)
+        );
+        assert_eq!(
+            config.apply(time, &Modifiers::CTRL, 100.0, &keyframes, &[]),
+            time
+        );
+    }
+}
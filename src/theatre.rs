@@ -0,0 +1,347 @@
+//! Import of Theatre.js project state.
+//!
+//! [`load_theatre`] reads the JSON a Theatre.js studio exports
+//! (`studio.createContentOfSaveFile`) and converts the keyframed tracks of
+//! all sheets into `Track<f32>`s plus a [`PropertyRow`] tree of sheets,
+//! objects and property paths:
+//!
+//! ```json
+//! {
+//!   "sheetsById": {
+//!     "Scene": {
+//!       "sequence": {
+//!         "tracksByObject": {
+//!           "Box": {
+//!             "trackData": {
+//!               "aZ3": {
+//!                 "type": "BasicKeyframedTrack",
+//!                 "keyframes": [
+//!                   { "id": "k1", "position": 0, "value": 0,
+//!                     "handles": [0.5, 1, 0.5, 0], "connectedRight": true }
+//!                 ]
+//!               }
+//!             },
+//!             "trackIdByPropPath": { "[\"position\",\"x\"]": "aZ3" }
+//!           }
+//!         }
+//!       }
+//!     }
+//!   }
+//! }
+//! ```
+//!
+//! Positions are in seconds and `handles` use the same
+//! `[leftX, leftY, rightX, rightY]` convention as [`BezierHandles`], so they
+//! carry over unchanged. Numeric and boolean values are imported; keyframes
+//! of other props, e.g. strings and colors, are skipped.
+
+use crate::core::keyframe::{BezierHandles, Keyframe, KeyframeType};
+use crate::core::track::{Track, TrackId};
+use crate::io::IoError;
+use crate::model::SimpleAnimationModel;
+use crate::traits::PropertyRow;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// A keyframed Theatre.js prop.
+#[derive(Debug, Clone)]
+pub struct TheatreTrack {
+    /// Sheet ID, e.g. `Scene`.
+    pub sheet: String,
+    /// Object key within the sheet, e.g. `Box`.
+    pub object: String,
+    /// Prop path within the object, e.g. `["position", "x"]`.
+    pub path: Vec<String>,
+    /// The keyframes.
+    pub track: Track<f32>,
+}
+
+/// Tracks and their row tree read by [`load_theatre`].
+#[derive(Debug, Clone, Default)]
+pub struct TheatreProject {
+    /// Tracks sorted by sheet, object and prop path.
+    pub tracks: Vec<TheatreTrack>,
+    /// Sheets and objects at depths 0 and 1, then one row per prop path
+    /// segment; the last segment's row refers to the track.
+    pub rows: Vec<PropertyRow>,
+}
+
+impl TheatreProject {
+    /// Get a track by ID.
+    pub fn track(&self, id: TrackId) -> Option<&TheatreTrack> {
+        self.tracks.iter().find(|track| track.track.id == id)
+    }
+
+    /// Move the tracks into a [`SimpleAnimationModel`] with one group per
+    /// sheet object, labeled `sheet / object`, and tracks labeled with their
+    /// dotted prop path.
+    ///
+    /// The model has only two levels, so use [`Self::rows`] with a custom
+    /// provider to keep the full tree.
+    pub fn into_model(self) -> SimpleAnimationModel {
+        let mut model = SimpleAnimationModel::new();
+        let mut current: Option<((String, String), String)> = None;
+        for track in self.tracks {
+            let key = (track.sheet, track.object);
+            let group = match current.take() {
+                Some((current_key, group)) if current_key == key => group,
+                _ => model.add_group(format!("{} / {}", key.0, key.1)),
+            };
+            model.add_track(&group, track.path.join("."), track.track);
+            current = Some((key, group));
+        }
+        model
+    }
+}
+
+/// Convert a Theatre.js project state JSON document into tracks and rows.
+pub fn load_theatre(json: &str) -> Result<TheatreProject, IoError> {
+    let state: ProjectState = serde_json::from_str(json)?;
+
+    let mut tracks = Vec::new();
+    for (sheet, sheet_state) in state.sheets_by_id {
+        let Some(sequence) = sheet_state.sequence else {
+            continue;
+        };
+        for (object, object_tracks) in sequence.tracks_by_object {
+            let mut object_props: Vec<TheatreTrack> = object_tracks
+                .track_id_by_prop_path
+                .iter()
+                .filter_map(|(path, track_id)| {
+                    let data = object_tracks.track_data.get(track_id)?;
+                    let track = data.to_track();
+                    (!track.is_empty()).then(|| TheatreTrack {
+                        sheet: sheet.clone(),
+                        object: object.clone(),
+                        path: parse_prop_path(path),
+                        track,
+                    })
+                })
+                .collect();
+            object_props.sort_by(|a, b| a.path.cmp(&b.path));
+            tracks.extend(object_props);
+        }
+    }
+
+    let rows = property_rows(&tracks);
+    Ok(TheatreProject { tracks, rows })
+}
+
+/// One row per distinct prefix of `sheet / object / path`, in track order.
+fn property_rows(tracks: &[TheatreTrack]) -> Vec<PropertyRow> {
+    let mut rows = Vec::new();
+    let mut previous: Vec<String> = Vec::new();
+    for track in tracks {
+        let segments: Vec<String> = [track.sheet.clone(), track.object.clone()]
+            .into_iter()
+            .chain(track.path.iter().cloned())
+            .collect();
+        let shared = segments
+            .iter()
+            .zip(&previous)
+            .take_while(|(a, b)| a == b)
+            .count();
+        for depth in shared..segments.len() {
+            let is_leaf = depth + 1 == segments.len();
+            rows.push(PropertyRow {
+                id: segments[..=depth].join("/"),
+                label: segments[depth].clone(),
+                depth,
+                can_collapse: !is_leaf,
+                is_collapsed: false,
+                track_id: is_leaf.then_some(track.track.id),
+                color: None,
+                locked: false,
+                driven: false,
+                muted: false,
+                solo: false,
+            });
+        }
+        previous = segments;
+    }
+    rows
+}
+
+/// Segments of a JSON encoded prop path such as `["position","x"]`.
+fn parse_prop_path(path: &str) -> Vec<String> {
+    match serde_json::from_str::<Vec<serde_json::Value>>(path) {
+        Ok(segments) => segments
+            .into_iter()
+            .map(|segment| match segment {
+                serde_json::Value::String(name) => name,
+                other => other.to_string(),
+            })
+            .collect(),
+        Err(_) => vec![path.to_string()],
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProjectState {
+    #[serde(default)]
+    sheets_by_id: BTreeMap<String, SheetState>,
+}
+
+#[derive(Deserialize)]
+struct SheetState {
+    #[serde(default)]
+    sequence: Option<SequenceState>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SequenceState {
+    #[serde(default)]
+    tracks_by_object: BTreeMap<String, ObjectTracks>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ObjectTracks {
+    #[serde(default)]
+    track_data: BTreeMap<String, TrackData>,
+    #[serde(default)]
+    track_id_by_prop_path: BTreeMap<String, String>,
+}
+
+#[derive(Deserialize)]
+struct TrackData {
+    #[serde(default)]
+    keyframes: Vec<TheatreKeyframe>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TheatreKeyframe {
+    position: f64,
+    value: serde_json::Value,
+    #[serde(default = "linear_handles")]
+    handles: [f32; 4],
+    #[serde(default = "default_connected")]
+    connected_right: bool,
+    #[serde(default, rename = "type")]
+    keyframe_type: Option<String>,
+}
+
+fn linear_handles() -> [f32; 4] {
+    BezierHandles::linear().to_array()
+}
+
+fn default_connected() -> bool {
+    true
+}
+
+impl TrackData {
+    fn to_track(&self) -> Track<f32> {
+        let mut track = Track::new();
+        for kf in &self.keyframes {
+            let value = match &kf.value {
+                serde_json::Value::Number(number) => number.as_f64().map(|value| value as f32),
+                serde_json::Value::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+                _ => None,
+            };
+            let Some(value) = value else {
+                continue;
+            };
+            let keyframe_type = match kf.keyframe_type.as_deref() {
+                Some("hold") => KeyframeType::Hold,
+                _ => KeyframeType::Bezier,
+            };
+            let mut keyframe = Keyframe::new(kf.position, value)
+                .with_handles(BezierHandles::from_array(kf.handles))
+                .with_type(keyframe_type);
+            keyframe.connected_right = kf.connected_right;
+            track.add_keyframe(keyframe);
+        }
+        track
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::traits::AnimationDataProvider;
+
+    const PROJECT: &str = r#"{
+        "definitionVersion": "0.4.0",
+        "sheetsById": {
+            "Scene": {
+                "staticOverrides": { "byObject": {} },
+                "sequence": {
+                    "subUnitsPerUnit": 30,
+                    "length": 10,
+                    "type": "PositionalSequence",
+                    "tracksByObject": {
+                        "Box": {
+                            "trackData": {
+                                "t1": {
+                                    "type": "BasicKeyframedTrack",
+                                    "keyframes": [
+                                        { "id": "a", "position": 0, "value": 0, "handles": [0.5, 1, 0.5, 0], "connectedRight": true },
+                                        { "id": "b", "position": 2, "value": 10, "handles": [0.5, 1, 0.5, 0], "connectedRight": true }
+                                    ]
+                                },
+                                "t2": {
+                                    "type": "BasicKeyframedTrack",
+                                    "keyframes": [
+                                        { "id": "c", "position": 1, "value": 3, "type": "hold" }
+                                    ]
+                                },
+                                "t3": {
+                                    "type": "BasicKeyframedTrack",
+                                    "keyframes": [
+                                        { "id": "d", "position": 1, "value": "hello" }
+                                    ]
+                                }
+                            },
+                            "trackIdByPropPath": {
+                                "[\"position\",\"y\"]": "t2",
+                                "[\"position\",\"x\"]": "t1",
+                                "[\"label\"]": "t3"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }"#;
+
+    #[test]
+    fn imports_tracks_and_row_tree() {
+        let project = load_theatre(PROJECT).unwrap();
+
+        let paths: Vec<_> = project.tracks.iter().map(|t| t.path.join(".")).collect();
+        assert_eq!(paths, ["position.x", "position.y"]);
+        let x = &project.tracks[0].track;
+        let first = x.keyframes_sorted()[0];
+        assert_eq!(first.handles.to_array(), [0.5, 1.0, 0.5, 0.0]);
+        assert_eq!(x.evaluate(2.0), Some(10.0));
+        assert_eq!(
+            project.tracks[1].track.keyframes_sorted()[0].keyframe_type,
+            KeyframeType::Hold
+        );
+
+        let rows: Vec<_> = project
+            .rows
+            .iter()
+            .map(|row| (row.id.as_str(), row.depth, row.track_id.is_some()))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("Scene", 0, false),
+                ("Scene/Box", 1, false),
+                ("Scene/Box/position", 2, false),
+                ("Scene/Box/position/x", 3, true),
+                ("Scene/Box/position/y", 3, true),
+            ]
+        );
+        let leaf = project.rows[3].track_id.unwrap();
+        assert_eq!(project.track(leaf).unwrap().path, ["position", "x"]);
+
+        let model = project.into_model();
+        let labels: Vec<_> = model.property_rows().into_iter().map(|r| r.label).collect();
+        assert_eq!(labels, ["Scene / Box", "position.x", "position.y"]);
+    }
+}
@@ -3,9 +3,18 @@
 //! These traits allow the widgets to work with any data source without
 //! coupling to a specific implementation.
 
-use crate::core::keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType};
+use crate::core::color::Rgba;
+use crate::core::extrapolation::Extrapolation;
+use crate::core::group::{TrackGroupId, TrackGroups};
+use crate::core::history::KeyframeSnapshot;
+use crate::core::keyframe::{
+    BezierHandles, Keyframe, KeyframeId, KeyframeTag, KeyframeType, Spring, WeightedHandles,
+};
+use crate::core::layer::BlendMode;
+use crate::core::marker::{Marker, MarkerId};
 use crate::core::time::TimeTick;
 use crate::core::track::{Track, TrackId};
+use crate::intervals::IntervalContext;
 
 /// Trait for types that can be animated (interpolated).
 pub trait Animatable: Clone + Send + Sync + 'static {
@@ -111,7 +120,7 @@ impl Animatable for [f32; 4] {
 }
 
 /// A row in the property tree (for DopeSheet).
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct PropertyRow {
     /// Unique ID for this row.
     pub id: String,
@@ -127,6 +136,83 @@ pub struct PropertyRow {
     pub track_id: Option<TrackId>,
     /// Optional color for this row's keyframes.
     pub color: Option<egui::Color32>,
+    /// Whether the row is locked against editing.
+    pub locked: bool,
+    /// Whether the row's track follows another through a
+    /// [`Driver`](crate::Driver); the property tree shows a link icon.
+    pub driven: bool,
+    /// Whether the row's track is muted; muted tracks don't play and their
+    /// keyframes are grayed out.
+    pub muted: bool,
+    /// Whether the row's track is soloed; while any track is, only soloed
+    /// tracks play, see [`audible_track_ids`](crate::transport::audible_track_ids).
+    pub solo: bool,
+}
+
+/// Where a row lands in the property tree, see
+/// [`AnimationCommand::ReorderRow`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct RowTarget {
+    /// New parent row, or `None` for the root level.
+    pub parent: Option<String>,
+    /// Position among the children of `parent` after the move.
+    pub index: usize,
+}
+
+/// How the DopeSheet draws the keyframes of a track row.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum TrackLane {
+    /// A keyframe dot per key.
+    #[default]
+    Keyframes,
+    /// Stepped on/off values, e.g. of a `Track<bool>` for visibility: a bar
+    /// wherever the value is non-zero.
+    Toggle,
+    /// Stepped states, e.g. of a `Track<u32>`: one segment per key, labeled
+    /// with `labels[value]`, or the value if it has no label.
+    Enum { labels: Vec<String> },
+    /// Colors, e.g. of a `Track<Rgba>`: a gradient strip sampled from
+    /// [`AnimationDataProvider::track_color`] between the first and last
+    /// key.
+    Color,
+}
+
+/// Unit of a track's values.
+///
+/// Value grids and rulers step in intervals that suit the unit, e.g. 15°,
+/// 45° and 90° for angles, and label values with its suffix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValueUnit {
+    /// Plain numbers.
+    #[default]
+    Generic,
+    /// Angles in degrees.
+    Degrees,
+    /// Percentages, `100` being whole.
+    Percent,
+    /// Fractions in `0..=1`, e.g. opacity or a blend weight.
+    Normalized,
+}
+
+impl ValueUnit {
+    /// Intervals that count as nice for the unit.
+    pub fn interval_context(self) -> IntervalContext {
+        match self {
+            Self::Generic => IntervalContext::Decimal,
+            Self::Degrees => IntervalContext::Degrees,
+            Self::Percent => IntervalContext::Percent,
+            Self::Normalized => IntervalContext::Normalized,
+        }
+    }
+
+    /// Format `value` with `decimals` decimals and the unit's suffix.
+    pub fn format(self, value: f64, decimals: usize) -> String {
+        match self {
+            Self::Degrees => format!("{value:.decimals$}°"),
+            Self::Percent => format!("{value:.decimals$}%"),
+            Self::Generic | Self::Normalized => format!("{value:.decimals$}"),
+        }
+    }
 }
 
 /// Trait for providing animation data to widgets (read-only).
@@ -148,6 +234,138 @@ pub trait AnimationDataProvider {
     fn keyframe_handles(&self, track_id: TrackId, keyframe_id: KeyframeId)
     -> Option<BezierHandles>;
 
+    /// Get the weighted handles of a keyframe, if it has any.
+    ///
+    /// The default returns `None`.
+    fn keyframe_weighted_handles(
+        &self,
+        _track_id: TrackId,
+        _keyframe_id: KeyframeId,
+    ) -> Option<WeightedHandles> {
+        None
+    }
+
+    /// Get the interpolation type of a keyframe.
+    ///
+    /// The default returns `None`, which makes type changes non-undoable in
+    /// [`CommandHistory`](crate::core::history::CommandHistory).
+    fn keyframe_type(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> Option<KeyframeType> {
+        None
+    }
+
+    /// Get the spring parameters of a keyframe, see [`Spring`].
+    ///
+    /// The default returns `None`, which makes spring edits non-undoable.
+    fn keyframe_spring(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> Option<Spring> {
+        None
+    }
+
+    /// Whether a keyframe is pinned against batch edits such as offsetting,
+    /// scaling and pasting over it.
+    ///
+    /// The default is `false`.
+    fn keyframe_pinned(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> bool {
+        false
+    }
+
+    /// Whether a keyframe is locked against edits, see [`Keyframe::locked`].
+    /// Locked rows are reported by [`PropertyRow::locked`] instead.
+    ///
+    /// The default is `false`.
+    fn keyframe_locked(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> bool {
+        false
+    }
+
+    /// Whether a keyframe is a breakdown, see [`Keyframe::breakdown`].
+    ///
+    /// The default is `false`.
+    fn keyframe_breakdown(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> bool {
+        false
+    }
+
+    /// Get the hover label of a keyframe, see [`Keyframe::label`].
+    ///
+    /// The default returns `None`.
+    fn keyframe_label(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> Option<String> {
+        None
+    }
+
+    /// Get the color tag of a keyframe, see [`Keyframe::tag`].
+    ///
+    /// The default returns `None`.
+    fn keyframe_tag(&self, _track_id: TrackId, _keyframe_id: KeyframeId) -> Option<KeyframeTag> {
+        None
+    }
+
+    /// Get the global time at which a track's local time `0` plays.
+    ///
+    /// Keyframe positions are in track-local time; the DopeSheet displays
+    /// them shifted by this offset. The default is zero.
+    fn track_time_offset(&self, _track_id: TrackId) -> TimeTick {
+        TimeTick::zero()
+    }
+
+    /// Get the playback speed of a track's keyframes.
+    ///
+    /// The default is `1.0`.
+    fn track_time_scale(&self, _track_id: TrackId) -> f64 {
+        1.0
+    }
+
+    /// Get the range a track's values must stay within, as `(min, max)`.
+    ///
+    /// [`AnimationCommand::validate`] rejects edits that leave it. The
+    /// default is `None`, i.e. no limits.
+    fn track_value_limits(&self, _track_id: TrackId) -> Option<(f64, f64)> {
+        None
+    }
+
+    /// Get how the DopeSheet draws a track's row.
+    ///
+    /// The default is [`TrackLane::Keyframes`].
+    fn track_lane(&self, _track_id: TrackId) -> TrackLane {
+        TrackLane::Keyframes
+    }
+
+    /// Get the unit of a track's values, which sets the value grid steps of
+    /// the curve editor.
+    ///
+    /// The default is [`ValueUnit::Generic`].
+    fn track_unit(&self, _track_id: TrackId) -> ValueUnit {
+        ValueUnit::Generic
+    }
+
+    /// Get the blend mode of a track that is an
+    /// [`AnimationLayer`](crate::AnimationLayer) of a property.
+    ///
+    /// The DopeSheet draws layer rows as stacked lanes labeled with their
+    /// mode. The default is `None`, i.e. not a layer.
+    fn track_blend(&self, _track_id: TrackId) -> Option<BlendMode> {
+        None
+    }
+
+    /// Get the color of a [`TrackLane::Color`] track at global `time`.
+    ///
+    /// The default is `None`, which leaves the lane empty.
+    fn track_color(&self, _track_id: TrackId, _time: TimeTick) -> Option<Rgba> {
+        None
+    }
+
+    /// Find the track and position of a keyframe.
+    ///
+    /// The default scans the keyframe positions of all track rows.
+    fn find_keyframe(&self, keyframe_id: KeyframeId) -> Option<(TrackId, TimeTick)> {
+        self.property_rows()
+            .iter()
+            .filter_map(|row| row.track_id)
+            .find_map(|track_id| {
+                self.keyframe_positions(track_id)?
+                    .into_iter()
+                    .find(|(id, _)| *id == keyframe_id)
+                    .map(|(_, position)| (track_id, position))
+            })
+    }
+
     /// Get the current time position.
     fn current_time(&self) -> TimeTick;
 
@@ -156,11 +374,31 @@ pub trait AnimationDataProvider {
 
     /// Get the value range for a track (for curve editor scaling).
     fn value_range(&self, track_id: TrackId) -> Option<(f32, f32)>;
+
+    /// Get the track groups.
+    ///
+    /// Widgets hide the tracks of hidden groups and color member rows that
+    /// have no color of their own. The default returns no groups.
+    fn track_groups(&self) -> TrackGroups {
+        TrackGroups::new()
+    }
+
+    /// Get a timeline marker.
+    ///
+    /// Needed to undo marker edits. The default has no markers.
+    fn marker(&self, _marker_id: MarkerId) -> Option<Marker> {
+        None
+    }
 }
 
 /// Commands for mutating animation data.
 ///
 /// The host application receives these commands and applies them to the data model.
+///
+/// Widgets emit commands in a deterministic order that does not depend on
+/// hash iteration: the same input on the same data yields the same command
+/// stream, so hosts can record and replay it. Each response documents the
+/// order of its `commands`.
 #[derive(Debug, Clone)]
 pub enum AnimationCommand {
     /// Add a keyframe to a track.
@@ -169,8 +407,46 @@ pub enum AnimationCommand {
         position: TimeTick,
         value: f64,
     },
+    /// Insert a fully specified keyframe with a known ID.
+    ///
+    /// Used to restore removed keyframes on undo so IDs stay stable.
+    InsertKeyframe {
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+        position: TimeTick,
+        value: f64,
+        handles: BezierHandles,
+        keyframe_type: KeyframeType,
+    },
+    /// Insert a keyframe on the curve at `position` without changing its
+    /// shape, see [`Track::insert_keyframe_on_curve`].
+    ///
+    /// Also updates the handles of the neighboring keyframes. Outside the
+    /// keyframe range the keyframe takes the extrapolated value.
+    InsertKeyframePreserving {
+        track_id: TrackId,
+        keyframe_id: KeyframeId,
+        position: TimeTick,
+    },
+    /// Insert a breakdown keyframe at `fraction` of the way from keyframe
+    /// `from` to keyframe `to` of the same track, see
+    /// [`Track::insert_breakdown`].
+    InsertBreakdown {
+        from: KeyframeId,
+        to: KeyframeId,
+        keyframe_id: KeyframeId,
+        fraction: f64,
+    },
+    /// Insert pasted keyframes, e.g. from
+    /// [`Clipboard::paste`](crate::core::clipboard::Clipboard::paste).
+    ///
+    /// Each keyframe carries its target track and a new, unique ID.
+    PasteKeyframes { keyframes: Vec<KeyframeSnapshot> },
     /// Remove keyframes.
     RemoveKeyframes { keyframe_ids: Vec<KeyframeId> },
+    /// Remove a keyframe and refit its neighbors' handles so the curve keeps
+    /// its shape, see [`Track::remove_keyframe_preserving`].
+    RemoveKeyframePreserving { keyframe_id: KeyframeId },
     /// Move a keyframe to a new position.
     MoveKeyframe {
         keyframe_id: KeyframeId,
@@ -183,8 +459,59 @@ pub enum AnimationCommand {
         keyframe_id: KeyframeId,
         handles: BezierHandles,
     },
+    /// Pin or unpin a keyframe, see [`Keyframe::pinned`].
+    SetKeyframePinned {
+        keyframe_id: KeyframeId,
+        pinned: bool,
+    },
+    /// Lock or unlock keyframes, see [`Keyframe::locked`].
+    SetKeyframesLocked {
+        keyframe_ids: Vec<KeyframeId>,
+        locked: bool,
+    },
+    /// Mark or unmark a keyframe as a breakdown, see
+    /// [`Keyframe::breakdown`].
+    SetKeyframeBreakdown {
+        keyframe_id: KeyframeId,
+        breakdown: bool,
+    },
+    /// Set or clear the hover label of a keyframe.
+    SetKeyframeLabel {
+        keyframe_id: KeyframeId,
+        label: Option<String>,
+    },
+    /// Set or clear the color tag of a keyframe.
+    SetKeyframeTag {
+        keyframe_id: KeyframeId,
+        tag: Option<KeyframeTag>,
+    },
+    /// Set or clear weighted handles. `None` returns to the normalized
+    /// `handles`.
+    SetKeyframeWeightedHandles {
+        keyframe_id: KeyframeId,
+        handles: Option<WeightedHandles>,
+    },
+    /// Set the spring parameters of a keyframe, used by
+    /// [`KeyframeType::Spring`] segments.
+    SetKeyframeSpring {
+        keyframe_id: KeyframeId,
+        spring: Spring,
+    },
     /// Set the current time.
     SetCurrentTime(TimeTick),
+    /// Set the in/out work range that playback and baking are confined to.
+    SetWorkRange {
+        /// First time of the range.
+        start: TimeTick,
+        /// Last time of the range.
+        end: TimeTick,
+    },
+    /// Add a timeline marker.
+    AddMarker(Marker),
+    /// Move a marker to `time`.
+    MoveMarker { marker_id: MarkerId, time: TimeTick },
+    /// Remove a marker.
+    RemoveMarker { marker_id: MarkerId },
     /// Toggle row collapse state.
     ToggleRowCollapse(String),
 
@@ -204,11 +531,112 @@ pub enum AnimationCommand {
         value_scale: f64,
     },
 
+    /// Mirror keyframes in time around `anchor_time`, reversing their
+    /// motion, see [`Track::flip_time`].
+    FlipKeyframesTime {
+        keyframe_ids: Vec<KeyframeId>,
+        anchor_time: TimeTick,
+    },
+
+    /// Mirror keyframe values around `anchor_value`, turning rises into
+    /// falls.
+    FlipKeyframesValue {
+        keyframe_ids: Vec<KeyframeId>,
+        anchor_value: f64,
+    },
+
+    /// Reverse the order of keyframes within the time span they cover on
+    /// each track, see [`Track::reverse`].
+    ReverseKeyframes { keyframe_ids: Vec<KeyframeId> },
+
     /// Set the interpolation type for a keyframe.
     SetKeyframeType {
         keyframe_id: KeyframeId,
         keyframe_type: KeyframeType,
     },
+
+    /// Create a new track row labeled `label`.
+    ///
+    /// `parent_row` is the group row to create it under, or `None` for the
+    /// root level.
+    AddTrack {
+        parent_row: Option<String>,
+        label: String,
+    },
+
+    /// Remove a track row, or a group row together with all rows under it.
+    RemoveTrack { row_id: String },
+
+    /// Create a new, empty group row labeled `label`.
+    ///
+    /// `parent_row` is the group row to create it under, or `None` for the
+    /// root level.
+    AddGroup {
+        parent_row: Option<String>,
+        label: String,
+    },
+
+    /// Change the display label of a row.
+    RenameRow { row_id: String, label: String },
+
+    /// Move a row (and its children) under another parent.
+    ///
+    /// `index` is the row's position among the children of `new_parent`
+    /// after the move. `None` moves it to the root level.
+    MoveRow {
+        row_id: String,
+        new_parent: Option<String>,
+        index: usize,
+    },
+
+    /// Reorder a row (and its children) dragged in the property tree.
+    ///
+    /// `row` is the ID of the dragged row; it moves to `target`.
+    ReorderRow { row: String, target: RowTarget },
+
+    /// Set or clear the color of a row.
+    SetRowColor {
+        row_id: String,
+        color: Option<egui::Color32>,
+    },
+
+    /// Mute or unmute a track, see [`PropertyRow::muted`]. Like solo, this
+    /// is auditioning state and not undoable.
+    SetTrackMuted { track_id: TrackId, muted: bool },
+
+    /// Solo or unsolo a track, see [`PropertyRow::solo`].
+    SetTrackSolo { track_id: TrackId, solo: bool },
+
+    /// Lock or unlock a row; a track row's lock is [`Track::locked`].
+    SetRowLocked { row_id: String, locked: bool },
+
+    /// Show only this row and its descendants, or all rows if `None`.
+    IsolateRow(Option<String>),
+
+    /// Show or hide all tracks of a [track group](crate::core::group::TrackGroup).
+    SetTrackGroupVisible {
+        group_id: TrackGroupId,
+        visible: bool,
+    },
+
+    /// Remove all keyframes of a row's tracks (including those of
+    /// descendant rows) and reset their values to the default.
+    ResetRow { row_id: String },
+
+    /// Set a track's time offset and speed.
+    SetTrackRetime {
+        track_id: TrackId,
+        time_offset: TimeTick,
+        time_scale: f64,
+    },
+
+    /// Replace a track's keyframes with linear keyframes at every frame of
+    /// `fps` within `range`, see [`Track::bake_in_place`].
+    BakeTrack {
+        track_id: TrackId,
+        fps: f64,
+        range: (TimeTick, TimeTick),
+    },
 }
 
 /// Trait for mutating animation data.
@@ -251,6 +679,20 @@ pub struct KeyframeView {
     pub connected_right: bool,
     /// Interpolation type.
     pub keyframe_type: KeyframeType,
+    /// Weighted handles; when set they replace `handles`.
+    pub weighted: Option<WeightedHandles>,
+    /// Whether the keyframe is pinned against batch edits.
+    pub pinned: bool,
+    /// Whether the keyframe or its track is locked against edits.
+    pub locked: bool,
+    /// Whether the keyframe is a breakdown.
+    pub breakdown: bool,
+    /// Text shown when hovering the keyframe.
+    pub label: Option<String>,
+    /// Color tag.
+    pub tag: Option<KeyframeTag>,
+    /// Spring parameters, used by [`KeyframeType::Spring`].
+    pub spring: Spring,
 }
 
 impl KeyframeView {
@@ -270,8 +712,75 @@ impl KeyframeView {
             handles,
             connected_right,
             keyframe_type,
+            weighted: None,
+            pinned: false,
+            locked: false,
+            breakdown: false,
+            label: None,
+            tag: None,
+            spring: Spring::default(),
         }
     }
+
+    /// Set weighted handles.
+    pub fn with_weighted(mut self, weighted: Option<WeightedHandles>) -> Self {
+        self.weighted = weighted;
+        self
+    }
+
+    /// Set whether the keyframe is pinned.
+    pub fn with_pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Set whether the keyframe is locked.
+    pub fn with_locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Set whether the keyframe is a breakdown.
+    pub fn with_breakdown(mut self, breakdown: bool) -> Self {
+        self.breakdown = breakdown;
+        self
+    }
+
+    /// Set the hover label.
+    pub fn with_label(mut self, label: Option<String>) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// Set the color tag.
+    pub fn with_tag(mut self, tag: Option<KeyframeTag>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Set the spring parameters.
+    pub fn with_spring(mut self, spring: Spring) -> Self {
+        self.spring = spring;
+        self
+    }
+}
+
+impl KeyframeView {
+    /// Convert back to a [`Keyframe`] for evaluation.
+    pub fn to_keyframe(&self) -> Keyframe<f32> {
+        let mut keyframe = Keyframe::with_id(self.id, self.position, self.value)
+            .with_handles(self.handles)
+            .with_type(self.keyframe_type)
+            .with_connected(self.connected_right)
+            .with_weighted(self.weighted)
+            .with_pinned(self.pinned)
+            .with_locked(self.locked)
+            .with_breakdown(self.breakdown)
+            .with_tag(self.tag)
+            .with_spring(self.spring);
+        keyframe.label = self.label.clone();
+        keyframe
+    }
 }
 
 impl From<&Keyframe<f32>> for KeyframeView {
@@ -283,6 +792,13 @@ impl From<&Keyframe<f32>> for KeyframeView {
             handles: kf.handles,
             connected_right: kf.connected_right,
             keyframe_type: kf.keyframe_type,
+            weighted: kf.weighted,
+            pinned: kf.pinned,
+            locked: kf.locked,
+            breakdown: kf.breakdown,
+            label: kf.label.clone(),
+            tag: kf.tag,
+            spring: kf.spring,
         }
     }
 }
@@ -311,6 +827,18 @@ pub trait KeyframeSource {
     fn is_empty(&self) -> bool {
         self.len() == 0
     }
+
+    /// Get the extrapolation modes `(pre, post)` outside the keyframe range.
+    fn extrapolation(&self) -> (Extrapolation, Extrapolation) {
+        (Extrapolation::Constant, Extrapolation::Constant)
+    }
+
+    /// Check if the whole track is locked, which locks all its keyframes.
+    ///
+    /// The default is `false`.
+    fn is_locked(&self) -> bool {
+        false
+    }
 }
 
 /// Blanket implementation of KeyframeSource for Track<f32>.
@@ -318,10 +846,17 @@ impl KeyframeSource for Track<f32> {
     fn keyframes_sorted(&self) -> Vec<KeyframeView> {
         self.keyframes_sorted()
             .into_iter()
-            .map(KeyframeView::from)
+            .map(|kf| {
+                let locked = kf.locked || self.locked;
+                KeyframeView::from(kf).with_locked(locked)
+            })
             .collect()
     }
 
+    fn is_locked(&self) -> bool {
+        self.locked
+    }
+
     fn value_range(&self) -> Option<(f32, f32)> {
         Track::value_range(self)
     }
@@ -329,4 +864,85 @@ impl KeyframeSource for Track<f32> {
     fn len(&self) -> usize {
         Track::len(self)
     }
+
+    fn extrapolation(&self) -> (Extrapolation, Extrapolation) {
+        (self.extrapolation_pre, self.extrapolation_post)
+    }
+}
+
+/// One track of an [`AnimationDataProvider`] as a [`KeyframeSource`], to
+/// show it in a [`CurveEditor`](crate::CurveEditor).
+///
+/// Keyframes are at track-local time, without the track's time offset.
+pub struct ProviderTrack<'a, P: AnimationDataProvider> {
+    provider: &'a P,
+    track_id: TrackId,
+}
+
+impl<'a, P: AnimationDataProvider> ProviderTrack<'a, P> {
+    /// Create a source for `track_id` of `provider`.
+    pub fn new(provider: &'a P, track_id: TrackId) -> Self {
+        Self { provider, track_id }
+    }
+}
+
+impl<P: AnimationDataProvider> KeyframeSource for ProviderTrack<'_, P> {
+    fn keyframes_sorted(&self) -> Vec<KeyframeView> {
+        let track_locked = self.is_locked();
+        let mut keyframes: Vec<KeyframeView> = self
+            .provider
+            .keyframe_positions(self.track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, position)| {
+                KeyframeView::new(
+                    id,
+                    position,
+                    self.provider
+                        .keyframe_value(self.track_id, id)
+                        .unwrap_or_default() as f32,
+                    self.provider
+                        .keyframe_handles(self.track_id, id)
+                        .unwrap_or_default(),
+                    true,
+                    self.provider
+                        .keyframe_type(self.track_id, id)
+                        .unwrap_or_default(),
+                )
+                .with_weighted(self.provider.keyframe_weighted_handles(self.track_id, id))
+                .with_locked(track_locked || self.provider.keyframe_locked(self.track_id, id))
+                .with_breakdown(self.provider.keyframe_breakdown(self.track_id, id))
+                .with_label(self.provider.keyframe_label(self.track_id, id))
+                .with_tag(self.provider.keyframe_tag(self.track_id, id))
+                .with_spring(
+                    self.provider
+                        .keyframe_spring(self.track_id, id)
+                        .unwrap_or_default(),
+                )
+            })
+            .collect();
+        keyframes.sort_by(|a, b| {
+            a.position
+                .partial_cmp(&b.position)
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+        keyframes
+    }
+
+    fn value_range(&self) -> Option<(f32, f32)> {
+        self.provider.value_range(self.track_id)
+    }
+
+    fn is_locked(&self) -> bool {
+        self.provider
+            .property_rows()
+            .iter()
+            .any(|row| row.track_id == Some(self.track_id) && row.locked)
+    }
+
+    fn len(&self) -> usize {
+        self.provider
+            .keyframe_positions(self.track_id)
+            .map_or(0, |positions| positions.len())
+    }
 }
@@ -0,0 +1,349 @@
+//! Playhead navigation: frame stepping and jumping between keyframes and
+//! markers.
+//!
+//! [`Transport`] resolves a [`TransportAction`] to a new time. The host applies
+//! it, e.g. by executing [`AnimationCommand::SetCurrentTime`].
+//!
+//! [`AnimationCommand::SetCurrentTime`]: crate::traits::AnimationCommand::SetCurrentTime
+
+use crate::HashSet;
+use crate::TimeTick;
+use crate::core::keyframe::KeyframeId;
+use crate::core::track::{TrackId, retime_to_global};
+use crate::traits::{AnimationDataProvider, PropertyRow};
+use egui::{InputState, Key, Modifiers};
+
+/// Tolerance below which two times are considered equal when searching for
+/// the next/previous keyframe or marker.
+const TIME_EPSILON: f64 = 1e-9;
+
+/// A playhead navigation action.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TransportAction {
+    /// Step one frame forward.
+    NextFrame,
+    /// Step one frame backward.
+    PrevFrame,
+    /// Jump to the next keyframe on the selected rows.
+    NextKeyframe,
+    /// Jump to the previous keyframe on the selected rows.
+    PrevKeyframe,
+    /// Jump to the next marker.
+    NextMarker,
+    /// Jump to the previous marker.
+    PrevMarker,
+}
+
+impl TransportAction {
+    /// Get all actions.
+    pub fn all() -> &'static [Self] {
+        &[
+            Self::NextFrame,
+            Self::PrevFrame,
+            Self::NextKeyframe,
+            Self::PrevKeyframe,
+            Self::NextMarker,
+            Self::PrevMarker,
+        ]
+    }
+
+    /// Default shortcut for this action.
+    ///
+    /// - `→`/`←`: next/previous frame
+    /// - `↑`/`↓`: next/previous keyframe
+    /// - `Shift+↑`/`Shift+↓`: next/previous marker
+    pub fn default_shortcut(&self) -> (Modifiers, Key) {
+        match self {
+            Self::NextFrame => (Modifiers::NONE, Key::ArrowRight),
+            Self::PrevFrame => (Modifiers::NONE, Key::ArrowLeft),
+            Self::NextKeyframe => (Modifiers::NONE, Key::ArrowUp),
+            Self::PrevKeyframe => (Modifiers::NONE, Key::ArrowDown),
+            Self::NextMarker => (Modifiers::SHIFT, Key::ArrowUp),
+            Self::PrevMarker => (Modifiers::SHIFT, Key::ArrowDown),
+        }
+    }
+
+    /// Detect an action pressed this frame using the default shortcuts.
+    pub fn from_input(input: &InputState) -> Option<Self> {
+        Self::all().iter().copied().find(|action| {
+            let (modifiers, key) = action.default_shortcut();
+            input.modifiers.matches_exact(modifiers) && input.key_pressed(key)
+        })
+    }
+}
+
+/// Step `frames` frames from `time` at the given frame rate.
+///
+/// The result is snapped to the frame grid, so stepping from a fractional
+/// frame lands on a whole frame.
+pub fn step_frames(time: TimeTick, fps: f64, frames: i64) -> TimeTick {
+    let frame = (time.value() * fps + TIME_EPSILON).floor() as i64;
+    let on_grid = ((time.value() * fps) - frame as f64).abs() < TIME_EPSILON * fps.max(1.0);
+    let target = if frames < 0 && !on_grid {
+        // Stepping back from a fractional frame lands on the frame below.
+        frame + frames + 1
+    } else {
+        frame + frames
+    };
+    TimeTick::new(target as f64 / fps)
+}
+
+/// Earliest time strictly after `time`.
+pub fn next_time_after(
+    times: impl IntoIterator<Item = TimeTick>,
+    time: TimeTick,
+) -> Option<TimeTick> {
+    times
+        .into_iter()
+        .filter(|t| t.value() > time.value() + TIME_EPSILON)
+        .min_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// Latest time strictly before `time`.
+pub fn prev_time_before(
+    times: impl IntoIterator<Item = TimeTick>,
+    time: TimeTick,
+) -> Option<TimeTick> {
+    times
+        .into_iter()
+        .filter(|t| t.value() < time.value() - TIME_EPSILON)
+        .max_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal))
+}
+
+/// Keyframes of `tracks` with their track and global time.
+fn global_keyframes<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    tracks: impl IntoIterator<Item = TrackId>,
+) -> impl Iterator<Item = (TrackId, KeyframeId, TimeTick)> {
+    tracks.into_iter().flat_map(move |track_id| {
+        let offset = provider.track_time_offset(track_id);
+        let scale = provider.track_time_scale(track_id);
+        provider
+            .keyframe_positions(track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(move |(id, position)| (track_id, id, retime_to_global(position, offset, scale)))
+    })
+}
+
+/// Earliest keyframe strictly after the global `time` on any of `tracks`.
+///
+/// Returns the keyframe with its track and global time. Of keyframes at the
+/// same time, the first in `tracks` order wins.
+pub fn next_keyframe_after<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    time: TimeTick,
+    tracks: impl IntoIterator<Item = TrackId>,
+) -> Option<(TrackId, KeyframeId, TimeTick)> {
+    global_keyframes(provider, tracks)
+        .filter(|(_, _, t)| t.value() > time.value() + TIME_EPSILON)
+        .reduce(|best, key| if key.2 < best.2 { key } else { best })
+}
+
+/// Latest keyframe strictly before the global `time` on any of `tracks`.
+///
+/// Returns the keyframe with its track and global time. Of keyframes at the
+/// same time, the first in `tracks` order wins.
+pub fn prev_keyframe_before<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    time: TimeTick,
+    tracks: impl IntoIterator<Item = TrackId>,
+) -> Option<(TrackId, KeyframeId, TimeTick)> {
+    global_keyframes(provider, tracks)
+        .filter(|(_, _, t)| t.value() < time.value() - TIME_EPSILON)
+        .reduce(|best, key| if key.2 > best.2 { key } else { best })
+}
+
+/// Track IDs of the given rows and all of their descendants.
+///
+/// `rows` must be in tree order (parents directly followed by children).
+pub fn subtree_track_ids(rows: &[PropertyRow], row_ids: &HashSet<String>) -> Vec<TrackId> {
+    let mut selected_depth: Option<usize> = None;
+    rows.iter()
+        .filter_map(|row| {
+            if selected_depth.is_some_and(|depth| row.depth <= depth) {
+                selected_depth = None;
+            }
+            if selected_depth.is_none() && row_ids.contains(&row.id) {
+                selected_depth = Some(row.depth);
+            }
+            selected_depth.and(row.track_id)
+        })
+        .collect()
+}
+
+/// Track IDs of the rows that play: not muted and, while any row is
+/// soloed, soloed.
+pub fn audible_track_ids(rows: &[PropertyRow]) -> HashSet<TrackId> {
+    let any_solo = rows.iter().any(|row| row.solo);
+    rows.iter()
+        .filter(|row| !row.muted && (row.solo || !any_solo))
+        .filter_map(|row| row.track_id)
+        .collect()
+}
+
+/// Resolves [`TransportAction`]s against animation data.
+pub struct Transport<'a, P: AnimationDataProvider> {
+    provider: &'a P,
+    fps: f64,
+    selected_rows: Option<&'a HashSet<String>>,
+    markers: &'a [TimeTick],
+}
+
+impl<'a, P: AnimationDataProvider> Transport<'a, P> {
+    /// Create a transport for the given provider and frame rate.
+    pub fn new(provider: &'a P, fps: f64) -> Self {
+        Self {
+            provider,
+            fps,
+            selected_rows: None,
+            markers: &[],
+        }
+    }
+
+    /// Restrict keyframe jumps to these rows and their descendants.
+    ///
+    /// An empty selection considers all rows.
+    pub fn selected_rows(mut self, rows: &'a HashSet<String>) -> Self {
+        self.selected_rows = Some(rows);
+        self
+    }
+
+    /// Set the marker times used by marker jumps.
+    pub fn markers(mut self, markers: &'a [TimeTick]) -> Self {
+        self.markers = markers;
+        self
+    }
+
+    /// Tracks considered for keyframe jumps.
+    pub fn track_ids(&self) -> Vec<TrackId> {
+        let rows = self.provider.property_rows();
+        match self.selected_rows {
+            Some(selected) if !selected.is_empty() => subtree_track_ids(&rows, selected),
+            _ => rows.iter().filter_map(|row| row.track_id).collect(),
+        }
+    }
+
+    /// Global times of all keyframes considered for keyframe jumps.
+    pub fn keyframe_times(&self) -> Vec<TimeTick> {
+        global_keyframes(self.provider, self.track_ids())
+            .map(|(_, _, time)| time)
+            .collect()
+    }
+
+    /// Resolve an action to a new time, starting at the provider's current
+    /// time.
+    ///
+    /// Returns `None` if there is no target (e.g. no next keyframe).
+    pub fn apply(&self, action: TransportAction) -> Option<TimeTick> {
+        let time = self.provider.current_time();
+        match action {
+            TransportAction::NextFrame => Some(step_frames(time, self.fps, 1)),
+            TransportAction::PrevFrame => Some(step_frames(time, self.fps, -1)),
+            TransportAction::NextKeyframe => {
+                next_keyframe_after(self.provider, time, self.track_ids()).map(|(.., t)| t)
+            }
+            TransportAction::PrevKeyframe => {
+                prev_keyframe_before(self.provider, time, self.track_ids()).map(|(.., t)| t)
+            }
+            TransportAction::NextMarker => next_time_after(self.markers.iter().copied(), time),
+            TransportAction::PrevMarker => prev_time_before(self.markers.iter().copied(), time),
+        }
+    }
+
+    /// Handle the default shortcuts and return the new time, if any.
+    ///
+    /// Ignored while a text field has keyboard focus.
+    pub fn handle_input(&self, ctx: &egui::Context) -> Option<TimeTick> {
+        if ctx.wants_keyboard_input() {
+            return None;
+        }
+        ctx.input(TransportAction::from_input)
+            .and_then(|action| self.apply(action))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn step_frames_snaps_to_grid() {
+        let fps = 24.0;
+        let t = TimeTick::from_frames(10.0, fps);
+        assert!((step_frames(t, fps, 1).to_frames(fps) - 11.0).abs() < 1e-9);
+        assert!((step_frames(t, fps, -1).to_frames(fps) - 9.0).abs() < 1e-9);
+
+        let fractional = TimeTick::from_frames(10.5, fps);
+        assert!((step_frames(fractional, fps, 1).to_frames(fps) - 11.0).abs() < 1e-9);
+        assert!((step_frames(fractional, fps, -1).to_frames(fps) - 10.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn next_and_prev_time() {
+        let times = [1.0, 2.0, 3.0].map(TimeTick::new);
+        let now = TimeTick::new(2.0);
+        assert_eq!(next_time_after(times, now), Some(TimeTick::new(3.0)));
+        assert_eq!(prev_time_before(times, now), Some(TimeTick::new(1.0)));
+        assert_eq!(next_time_after(times, TimeTick::new(3.0)), None);
+    }
+
+    #[test]
+    fn next_and_prev_keyframe_across_tracks() {
+        use crate::core::keyframe::Keyframe;
+        use crate::core::track::Track;
+        use crate::model::SimpleAnimationModel;
+
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Cube");
+        let mut a = Track::new();
+        a.add_keyframe(Keyframe::new(1.0, 0.0));
+        let a3 = a.add_keyframe(Keyframe::new(3.0, 0.0));
+        // Local time 1 plays at global time 2.
+        let mut b = Track::new().with_retime(1.0, 1.0);
+        let b2 = b.add_keyframe(Keyframe::new(1.0, 0.0));
+        let a = model.add_track(&group, "A", a).unwrap();
+        let b = model.add_track(&group, "B", b).unwrap();
+
+        let now = TimeTick::new(1.0);
+        assert_eq!(
+            next_keyframe_after(&model, now, [a, b]),
+            Some((b, b2, TimeTick::new(2.0)))
+        );
+        assert_eq!(
+            next_keyframe_after(&model, now, [a]),
+            Some((a, a3, TimeTick::new(3.0)))
+        );
+        assert_eq!(prev_keyframe_before(&model, now, [a, b]), None);
+        assert_eq!(
+            prev_keyframe_before(&model, TimeTick::new(3.0), [a, b]).map(|(.., t)| t),
+            Some(TimeTick::new(2.0))
+        );
+    }
+
+    #[test]
+    fn subtree_tracks() {
+        let row = |id: &str, depth: usize, track: Option<TrackId>| PropertyRow {
+            id: id.into(),
+            label: id.into(),
+            depth,
+            can_collapse: track.is_none(),
+            track_id: track,
+            ..Default::default()
+        };
+        let (x, y, z) = (TrackId::new(), TrackId::new(), TrackId::new());
+        let rows = [
+            row("a", 0, None),
+            row("a.x", 1, Some(x)),
+            row("a.y", 1, Some(y)),
+            row("b", 0, None),
+            row("b.z", 1, Some(z)),
+        ];
+
+        let selected: HashSet<String> = ["a".to_string()].into_iter().collect();
+        assert_eq!(subtree_track_ids(&rows, &selected), vec![x, y]);
+
+        let selected: HashSet<String> = ["b.z".to_string()].into_iter().collect();
+        assert_eq!(subtree_track_ids(&rows, &selected), vec![z]);
+    }
+}
@@ -0,0 +1,772 @@
+//! Checks for keyframe data that edits or exports may trip over.
+//!
+//! [`validate`] scans tracks for keys off frame boundaries, non-finite times
+//! and values, keys sharing a time and handles that point the wrong way in
+//! time. The [`ValidationReport`] lists one [`ValidationIssue`] per finding
+//! for the host to show; [`conform_to_frames`] fixes off-frame keys.
+//!
+//! [`AnimationCommand::validate`] checks a single command against a provider
+//! before it is executed, so hosts can reject malformed edits with a
+//! [`CommandError`] instead of applying them partially or not at all.
+
+use crate::TimeTick;
+use crate::core::keyframe::{BezierHandles, KeyframeId, KeyframeType};
+use crate::core::marker::MarkerId;
+use crate::core::track::{TrackId, retime_to_global, retime_to_local};
+use crate::traits::{AnimationCommand, AnimationDataProvider};
+
+/// Distance in frames within which a key counts as on a frame boundary.
+const FRAME_TOLERANCE: f64 = 1e-6;
+
+/// Distance in seconds within which two keys share a time.
+const TIME_TOLERANCE: f64 = 1e-9;
+
+/// What is wrong with a keyframe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IssueKind {
+    /// The key is not on a frame boundary; `frame` is its fractional frame.
+    OffFrame { frame: f64 },
+    /// The key's time is NaN or infinite.
+    NonFiniteTime,
+    /// The key's value is NaN or infinite.
+    NonFiniteValue,
+    /// The key has the same time as `other` on the same track.
+    DuplicateTime { other: KeyframeId },
+    /// A handle points backward in time: the left one after its key or the
+    /// right one before it.
+    ReversedHandles,
+}
+
+impl std::fmt::Display for IssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::OffFrame { frame } => write!(f, "not on a frame boundary (frame {frame:.3})"),
+            Self::NonFiniteTime => write!(f, "time is not a finite number"),
+            Self::NonFiniteValue => write!(f, "value is not a finite number"),
+            Self::DuplicateTime { .. } => write!(f, "another keyframe has the same time"),
+            Self::ReversedHandles => write!(f, "handle points backward in time"),
+        }
+    }
+}
+
+/// A problem found by [`validate`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ValidationIssue {
+    /// Track of the keyframe.
+    pub track_id: TrackId,
+    /// The keyframe.
+    pub keyframe_id: KeyframeId,
+    /// What is wrong.
+    pub kind: IssueKind,
+}
+
+/// Issues found by [`validate`], grouped by track in the order given.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ValidationReport {
+    /// All issues.
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+    /// Check if no issues were found.
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty()
+    }
+
+    /// Number of issues.
+    pub fn len(&self) -> usize {
+        self.issues.len()
+    }
+
+    /// Iterate over issues.
+    pub fn iter(&self) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues.iter()
+    }
+
+    /// Issues of `keyframe_id`.
+    pub fn for_keyframe(&self, keyframe_id: KeyframeId) -> impl Iterator<Item = &ValidationIssue> {
+        self.issues
+            .iter()
+            .filter(move |issue| issue.keyframe_id == keyframe_id)
+    }
+
+    /// Check if any keys are off frame boundaries, i.e. if
+    /// [`conform_to_frames`] would change anything.
+    pub fn has_off_frame_keys(&self) -> bool {
+        self.issues
+            .iter()
+            .any(|issue| matches!(issue.kind, IssueKind::OffFrame { .. }))
+    }
+}
+
+/// Check the keyframes of `tracks`.
+///
+/// Frame boundaries are checked against global time at `fps`; pass `None`
+/// to skip that check.
+pub fn validate<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    tracks: impl IntoIterator<Item = TrackId>,
+    fps: Option<f64>,
+) -> ValidationReport {
+    let mut issues = Vec::new();
+    for track_id in tracks {
+        let offset = provider.track_time_offset(track_id);
+        let scale = provider.track_time_scale(track_id);
+        let mut keys: Vec<(KeyframeId, TimeTick)> = provider
+            .keyframe_positions(track_id)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(id, position)| (id, retime_to_global(position, offset, scale)))
+            .collect();
+        keys.sort_by(|a, b| a.1.value().total_cmp(&b.1.value()));
+
+        let mut issue = |keyframe_id, kind| {
+            issues.push(ValidationIssue {
+                track_id,
+                keyframe_id,
+                kind,
+            })
+        };
+        for (index, &(keyframe_id, time)) in keys.iter().enumerate() {
+            if !time.value().is_finite() {
+                issue(keyframe_id, IssueKind::NonFiniteTime);
+            } else if let Some(fps) = fps {
+                let frame = time.to_frames(fps);
+                if (frame - frame.round()).abs() > FRAME_TOLERANCE {
+                    issue(keyframe_id, IssueKind::OffFrame { frame });
+                }
+            }
+            if provider
+                .keyframe_value(track_id, keyframe_id)
+                .is_some_and(|value| !value.is_finite())
+            {
+                issue(keyframe_id, IssueKind::NonFiniteValue);
+            }
+            if let Some(&(other, previous)) = index.checked_sub(1).map(|i| &keys[i])
+                && (time - previous).value().abs() <= TIME_TOLERANCE
+            {
+                issue(keyframe_id, IssueKind::DuplicateTime { other });
+            }
+            if has_reversed_handles(provider, track_id, keyframe_id) {
+                issue(keyframe_id, IssueKind::ReversedHandles);
+            }
+        }
+    }
+    ValidationReport { issues }
+}
+
+/// Commands moving the keys of `tracks` to the nearest frame at `fps` in
+/// global time.
+///
+/// Pinned keys are left alone. Keys rounding to the same frame end up with
+/// the same time; [`validate`] reports them afterwards.
+pub fn conform_to_frames<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    tracks: impl IntoIterator<Item = TrackId>,
+    fps: f64,
+) -> Vec<AnimationCommand> {
+    if fps <= 0.0 {
+        return Vec::new();
+    }
+    tracks
+        .into_iter()
+        .flat_map(|track_id| {
+            let offset = provider.track_time_offset(track_id);
+            let scale = provider.track_time_scale(track_id);
+            provider
+                .keyframe_positions(track_id)
+                .unwrap_or_default()
+                .into_iter()
+                .filter(move |(keyframe_id, _)| !provider.keyframe_pinned(track_id, *keyframe_id))
+                .filter_map(move |(keyframe_id, position)| {
+                    let frame = retime_to_global(position, offset, scale).to_frames(fps);
+                    if !frame.is_finite() || (frame - frame.round()).abs() <= FRAME_TOLERANCE {
+                        return None;
+                    }
+                    let snapped = TimeTick::from_frames(frame.round(), fps);
+                    Some(AnimationCommand::MoveKeyframe {
+                        keyframe_id,
+                        new_position: retime_to_local(snapped, offset, scale),
+                    })
+                })
+        })
+        .collect()
+}
+
+/// Why [`AnimationCommand::validate`] rejected a command.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CommandError {
+    /// The provider has no track with this ID.
+    TrackNotFound(TrackId),
+    /// No track of the provider has a keyframe with this ID.
+    KeyframeNotFound(KeyframeId),
+    /// A keyframe to insert has the ID of an existing keyframe.
+    KeyframeExists(KeyframeId),
+    /// The provider lists no row with this ID.
+    RowNotFound(String),
+    /// The provider has no marker with this ID.
+    MarkerNotFound(MarkerId),
+    /// The named argument, e.g. `position`, is NaN or infinite.
+    NonFinite(&'static str),
+    /// A value would leave the limits of its track.
+    OutOfLimits {
+        /// The track.
+        track_id: TrackId,
+        /// The value the command would set.
+        value: f64,
+        /// The track's `(min, max)` limits.
+        limits: (f64, f64),
+    },
+    /// The named argument is out of its valid range.
+    InvalidArgument {
+        /// Argument name, e.g. `fps`.
+        name: &'static str,
+        /// What is wrong with it.
+        reason: &'static str,
+    },
+}
+
+impl std::fmt::Display for CommandError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::TrackNotFound(id) => write!(f, "track {} not found", id.0),
+            Self::KeyframeNotFound(id) => write!(f, "keyframe {} not found", id.0),
+            Self::KeyframeExists(id) => write!(f, "keyframe {} already exists", id.0),
+            Self::RowNotFound(id) => write!(f, "row `{id}` not found"),
+            Self::MarkerNotFound(id) => write!(f, "marker {} not found", id.0),
+            Self::NonFinite(name) => write!(f, "`{name}` is not a finite number"),
+            Self::OutOfLimits {
+                track_id,
+                value,
+                limits: (min, max),
+            } => write!(
+                f,
+                "value {value} is outside the limits {min}..={max} of track {}",
+                track_id.0
+            ),
+            Self::InvalidArgument { name, reason } => write!(f, "`{name}` {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+impl AnimationCommand {
+    /// Check that this command can be executed against `provider`.
+    ///
+    /// Referenced tracks, keyframes, rows and markers must exist, keyframes to insert
+    /// must not, times and values must be finite and resulting values must
+    /// be within the [track limits](AnimationDataProvider::track_value_limits).
+    /// Rows and tracks count as existing if the provider lists them, so rows
+    /// hidden by [isolation](Self::IsolateRow) are reported as missing.
+    ///
+    /// Nothing is changed; pinned keyframes are skipped like
+    /// [`SimpleAnimationModel`](crate::SimpleAnimationModel) skips them.
+    pub fn validate<P: AnimationDataProvider + ?Sized>(
+        &self,
+        provider: &P,
+    ) -> Result<(), CommandError> {
+        match self {
+            Self::AddKeyframe {
+                track_id,
+                position,
+                value,
+            } => {
+                require_track(provider, *track_id)?;
+                require_finite("position", position.value())?;
+                require_value(provider, *track_id, *value)
+            }
+            Self::InsertKeyframe {
+                track_id,
+                keyframe_id,
+                position,
+                value,
+                handles,
+                ..
+            } => {
+                require_track(provider, *track_id)?;
+                require_new_keyframe(provider, *keyframe_id)?;
+                require_finite("position", position.value())?;
+                require_handles(handles)?;
+                require_value(provider, *track_id, *value)
+            }
+            Self::InsertKeyframePreserving {
+                track_id,
+                keyframe_id,
+                position,
+            } => {
+                require_track(provider, *track_id)?;
+                require_new_keyframe(provider, *keyframe_id)?;
+                require_finite("position", position.value())
+            }
+            Self::InsertBreakdown {
+                from,
+                to,
+                keyframe_id,
+                fraction,
+            } => {
+                if require_keyframe(provider, *from)? != require_keyframe(provider, *to)? {
+                    return Err(CommandError::InvalidArgument {
+                        name: "to",
+                        reason: "is not on the track of `from`",
+                    });
+                }
+                require_new_keyframe(provider, *keyframe_id)?;
+                if *fraction > 0.0 && *fraction < 1.0 {
+                    Ok(())
+                } else {
+                    Err(CommandError::InvalidArgument {
+                        name: "fraction",
+                        reason: "must be between 0 and 1",
+                    })
+                }
+            }
+            Self::PasteKeyframes { keyframes } => keyframes.iter().try_for_each(|snapshot| {
+                require_track(provider, snapshot.track_id)?;
+                require_new_keyframe(provider, snapshot.keyframe_id)?;
+                require_finite("position", snapshot.position.value())?;
+                require_handles(&snapshot.handles)?;
+                require_value(provider, snapshot.track_id, snapshot.value)
+            }),
+            Self::RemoveKeyframes { keyframe_ids }
+            | Self::ReverseKeyframes { keyframe_ids }
+            | Self::SetKeyframesLocked { keyframe_ids, .. } => keyframe_ids
+                .iter()
+                .try_for_each(|id| require_keyframe(provider, *id).map(|_| ())),
+            Self::RemoveKeyframePreserving { keyframe_id }
+            | Self::SetKeyframePinned { keyframe_id, .. }
+            | Self::SetKeyframeBreakdown { keyframe_id, .. }
+            | Self::SetKeyframeLabel { keyframe_id, .. }
+            | Self::SetKeyframeTag { keyframe_id, .. }
+            | Self::SetKeyframeType { keyframe_id, .. } => {
+                require_keyframe(provider, *keyframe_id).map(|_| ())
+            }
+            Self::MoveKeyframe {
+                keyframe_id,
+                new_position,
+            } => {
+                require_keyframe(provider, *keyframe_id)?;
+                require_finite("new_position", new_position.value())
+            }
+            Self::SetKeyframeValue { keyframe_id, value } => {
+                let track_id = require_keyframe(provider, *keyframe_id)?;
+                require_value(provider, track_id, *value)
+            }
+            Self::SetKeyframeHandles {
+                keyframe_id,
+                handles,
+            } => {
+                require_keyframe(provider, *keyframe_id)?;
+                require_handles(handles)
+            }
+            Self::SetKeyframeWeightedHandles {
+                keyframe_id,
+                handles,
+            } => {
+                require_keyframe(provider, *keyframe_id)?;
+                handles.map_or(Ok(()), |handles| {
+                    [
+                        handles.left_time,
+                        handles.left_value,
+                        handles.right_time,
+                        handles.right_value,
+                    ]
+                    .into_iter()
+                    .try_for_each(|component| require_finite("handles", component))
+                })
+            }
+            Self::SetKeyframeSpring {
+                keyframe_id,
+                spring,
+            } => {
+                require_keyframe(provider, *keyframe_id)?;
+                require_finite("response", spring.response as f64)?;
+                if !(spring.frequency > 0.0 && spring.frequency.is_finite()) {
+                    return Err(CommandError::InvalidArgument {
+                        name: "frequency",
+                        reason: "is not positive",
+                    });
+                }
+                if !(spring.damping >= 0.0 && spring.damping.is_finite()) {
+                    return Err(CommandError::InvalidArgument {
+                        name: "damping",
+                        reason: "is negative",
+                    });
+                }
+                Ok(())
+            }
+            Self::SetCurrentTime(time) => require_finite("time", time.value()),
+            Self::SetWorkRange { start, end } => {
+                require_finite("start", start.value())?;
+                require_finite("end", end.value())?;
+                if start > end {
+                    return Err(CommandError::InvalidArgument {
+                        name: "start",
+                        reason: "is after `end`",
+                    });
+                }
+                Ok(())
+            }
+            Self::AddMarker(marker) => require_finite("time", marker.time.value()),
+            Self::MoveMarker { marker_id, time } => {
+                require_marker(provider, *marker_id)?;
+                require_finite("time", time.value())
+            }
+            Self::RemoveMarker { marker_id } => require_marker(provider, *marker_id),
+            Self::OffsetKeyframes {
+                keyframe_ids,
+                delta_time,
+                delta_value,
+            } => {
+                require_finite("delta_time", delta_time.value())?;
+                require_finite("delta_value", *delta_value)?;
+                require_moved_values(provider, keyframe_ids, |value| value + delta_value)
+            }
+            Self::ScaleKeyframes {
+                keyframe_ids,
+                anchor_time,
+                anchor_value,
+                time_scale,
+                value_scale,
+            } => {
+                require_finite("anchor_time", anchor_time.value())?;
+                require_finite("anchor_value", *anchor_value)?;
+                require_finite("time_scale", *time_scale)?;
+                require_finite("value_scale", *value_scale)?;
+                require_moved_values(provider, keyframe_ids, |value| {
+                    anchor_value + (value - anchor_value) * value_scale
+                })
+            }
+            Self::FlipKeyframesTime { anchor_time, .. } => {
+                require_finite("anchor_time", anchor_time.value())
+            }
+            Self::FlipKeyframesValue {
+                keyframe_ids,
+                anchor_value,
+            } => {
+                require_finite("anchor_value", *anchor_value)?;
+                require_moved_values(provider, keyframe_ids, |value| 2.0 * anchor_value - value)
+            }
+            Self::AddTrack { parent_row, .. } | Self::AddGroup { parent_row, .. } => parent_row
+                .as_deref()
+                .map_or(Ok(()), |row_id| require_row(provider, row_id)),
+            Self::RemoveTrack { row_id }
+            | Self::RenameRow { row_id, .. }
+            | Self::SetRowColor { row_id, .. }
+            | Self::SetRowLocked { row_id, .. }
+            | Self::ResetRow { row_id }
+            | Self::ToggleRowCollapse(row_id) => require_row(provider, row_id),
+            Self::MoveRow {
+                row_id, new_parent, ..
+            } => {
+                require_row(provider, row_id)?;
+                new_parent
+                    .as_deref()
+                    .map_or(Ok(()), |row_id| require_row(provider, row_id))
+            }
+            Self::ReorderRow { row, target } => {
+                require_row(provider, row)?;
+                target
+                    .parent
+                    .as_deref()
+                    .map_or(Ok(()), |row_id| require_row(provider, row_id))
+            }
+            // Isolating a row that is currently hidden by another isolation
+            // is valid.
+            Self::IsolateRow(_) => Ok(()),
+            Self::SetTrackGroupVisible { group_id, .. } => {
+                if provider.track_groups().get(*group_id).is_none() {
+                    return Err(CommandError::InvalidArgument {
+                        name: "group_id",
+                        reason: "is not a track group of the provider",
+                    });
+                }
+                Ok(())
+            }
+            Self::SetTrackMuted { track_id, .. } | Self::SetTrackSolo { track_id, .. } => {
+                require_track(provider, *track_id)
+            }
+            Self::SetTrackRetime {
+                track_id,
+                time_offset,
+                time_scale,
+            } => {
+                require_track(provider, *track_id)?;
+                require_finite("time_offset", time_offset.value())?;
+                require_finite("time_scale", *time_scale)
+            }
+            Self::BakeTrack {
+                track_id,
+                fps,
+                range: (start, end),
+            } => {
+                require_track(provider, *track_id)?;
+                require_finite("fps", *fps)?;
+                if *fps <= 0.0 {
+                    return Err(CommandError::InvalidArgument {
+                        name: "fps",
+                        reason: "is not positive",
+                    });
+                }
+                require_finite("range", start.value())?;
+                require_finite("range", end.value())?;
+                if start > end {
+                    return Err(CommandError::InvalidArgument {
+                        name: "range",
+                        reason: "ends before it starts",
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn require_track<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    track_id: TrackId,
+) -> Result<(), CommandError> {
+    match provider.keyframe_positions(track_id) {
+        Some(_) => Ok(()),
+        None => Err(CommandError::TrackNotFound(track_id)),
+    }
+}
+
+/// The track of `keyframe_id`.
+fn require_keyframe<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    keyframe_id: KeyframeId,
+) -> Result<TrackId, CommandError> {
+    provider
+        .find_keyframe(keyframe_id)
+        .map(|(track_id, _)| track_id)
+        .ok_or(CommandError::KeyframeNotFound(keyframe_id))
+}
+
+fn require_new_keyframe<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    keyframe_id: KeyframeId,
+) -> Result<(), CommandError> {
+    match provider.find_keyframe(keyframe_id) {
+        Some(_) => Err(CommandError::KeyframeExists(keyframe_id)),
+        None => Ok(()),
+    }
+}
+
+fn require_row<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    row_id: &str,
+) -> Result<(), CommandError> {
+    if provider.property_rows().iter().any(|row| row.id == row_id) {
+        Ok(())
+    } else {
+        Err(CommandError::RowNotFound(row_id.to_string()))
+    }
+}
+
+fn require_marker<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    marker_id: MarkerId,
+) -> Result<(), CommandError> {
+    match provider.marker(marker_id) {
+        Some(_) => Ok(()),
+        None => Err(CommandError::MarkerNotFound(marker_id)),
+    }
+}
+
+fn require_finite(name: &'static str, value: f64) -> Result<(), CommandError> {
+    if value.is_finite() {
+        Ok(())
+    } else {
+        Err(CommandError::NonFinite(name))
+    }
+}
+
+fn require_handles(handles: &BezierHandles) -> Result<(), CommandError> {
+    handles
+        .to_array()
+        .into_iter()
+        .try_for_each(|component| require_finite("handles", component as f64))
+}
+
+/// Check that `value` is finite and within the limits of `track_id`.
+fn require_value<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    track_id: TrackId,
+    value: f64,
+) -> Result<(), CommandError> {
+    require_finite("value", value)?;
+    match provider.track_value_limits(track_id) {
+        Some(limits @ (min, max)) if value < min || value > max => Err(CommandError::OutOfLimits {
+            track_id,
+            value,
+            limits,
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Check that the unpinned keyframes of `keyframe_ids` exist and stay within
+/// their track limits when their values are mapped by `new_value`.
+fn require_moved_values<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    keyframe_ids: &[KeyframeId],
+    new_value: impl Fn(f64) -> f64,
+) -> Result<(), CommandError> {
+    keyframe_ids.iter().try_for_each(|&keyframe_id| {
+        let track_id = require_keyframe(provider, keyframe_id)?;
+        if provider.keyframe_pinned(track_id, keyframe_id) {
+            return Ok(());
+        }
+        match provider.keyframe_value(track_id, keyframe_id) {
+            Some(value) => require_value(provider, track_id, new_value(value)),
+            None => Ok(()),
+        }
+    })
+}
+
+/// Whether a bezier keyframe's handles point backward in time.
+fn has_reversed_handles<P: AnimationDataProvider + ?Sized>(
+    provider: &P,
+    track_id: TrackId,
+    keyframe_id: KeyframeId,
+) -> bool {
+    // Only bezier handles are user-editable; other types ignore or compute
+    // them.
+    if provider
+        .keyframe_type(track_id, keyframe_id)
+        .is_some_and(|kind| kind != KeyframeType::Bezier)
+    {
+        return false;
+    }
+    if let Some(weighted) = provider.keyframe_weighted_handles(track_id, keyframe_id) {
+        return weighted.left_time > 0.0 || weighted.right_time < 0.0;
+    }
+    provider
+        .keyframe_handles(track_id, keyframe_id)
+        .is_some_and(|handles| handles.left_x > 1.0 || handles.right_x < 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::{BezierHandles, Keyframe};
+    use crate::core::track::Track;
+    use crate::model::SimpleAnimationModel;
+    use crate::traits::AnimationDataMutator;
+
+    #[test]
+    fn report_and_conform() {
+        let mut track = Track::<f32>::new();
+        let on_frame = track.add_keyframe(Keyframe::new(0.5, 0.0));
+        let off_frame = track.add_keyframe(Keyframe::new(1.01, 1.0));
+        let duplicate = track.add_keyframe(Keyframe::new(0.5, f32::NAN));
+        let reversed = track.add_keyframe(
+            Keyframe::new(2.0, 0.0).with_handles(BezierHandles::from_array([0.0, 0.0, -0.5, 0.0])),
+        );
+        let pinned = track.add_keyframe(Keyframe::new(3.01, 0.0).with_pinned(true));
+
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Object");
+        let track_id = model.add_track(&group, "X", track).unwrap();
+
+        let report = validate(&model, [track_id], Some(24.0));
+        let kinds = |id| -> Vec<IssueKind> { report.for_keyframe(id).map(|i| i.kind).collect() };
+        assert!(kinds(on_frame).is_empty());
+        assert!(matches!(kinds(off_frame)[..], [IssueKind::OffFrame { .. }]));
+        let duplicate_kinds = kinds(duplicate);
+        assert!(duplicate_kinds.contains(&IssueKind::NonFiniteValue));
+        assert!(duplicate_kinds.iter().any(|kind| matches!(
+            kind,
+            IssueKind::DuplicateTime { other } if *other == on_frame
+        )));
+        assert_eq!(kinds(reversed), [IssueKind::ReversedHandles]);
+        assert!(report.has_off_frame_keys());
+
+        let commands = conform_to_frames(&model, [track_id], 24.0);
+        assert!(matches!(
+            commands[..],
+            [AnimationCommand::MoveKeyframe { keyframe_id, new_position }]
+                if keyframe_id == off_frame && new_position == TimeTick::from_frames(24.0, 24.0)
+        ));
+        for command in commands {
+            model.execute(command);
+        }
+        let report = validate(&model, [track_id], Some(24.0));
+        assert_eq!(report.for_keyframe(off_frame).count(), 0);
+        assert_eq!(report.for_keyframe(pinned).count(), 1);
+    }
+
+    #[test]
+    fn validates_commands() {
+        let mut track = Track::<f32>::new();
+        let key = track.add_keyframe(Keyframe::new(0.0, 0.5));
+        let pinned = track.add_keyframe(Keyframe::new(1.0, 1.0).with_pinned(true));
+
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Object");
+        let track_id = model.add_track(&group, "Opacity", track).unwrap();
+        assert!(model.set_value_limits(track_id, Some((0.0, 1.0))));
+
+        let set = |value| AnimationCommand::SetKeyframeValue {
+            keyframe_id: key,
+            value,
+        };
+        assert_eq!(set(0.75).validate(&model), Ok(()));
+        assert_eq!(
+            set(1.5).validate(&model),
+            Err(CommandError::OutOfLimits {
+                track_id,
+                value: 1.5,
+                limits: (0.0, 1.0)
+            })
+        );
+        assert_eq!(
+            set(f64::NAN).validate(&model),
+            Err(CommandError::NonFinite("value"))
+        );
+
+        // The pinned key at the upper limit does not move.
+        let offset = AnimationCommand::OffsetKeyframes {
+            keyframe_ids: vec![key, pinned],
+            delta_time: TimeTick::zero(),
+            delta_value: 0.5,
+        };
+        assert_eq!(offset.validate(&model), Ok(()));
+
+        let missing = KeyframeId::new();
+        let remove = AnimationCommand::RemoveKeyframes {
+            keyframe_ids: vec![key, missing],
+        };
+        assert_eq!(
+            remove.validate(&model),
+            Err(CommandError::KeyframeNotFound(missing))
+        );
+        let insert = AnimationCommand::InsertKeyframePreserving {
+            track_id,
+            keyframe_id: key,
+            position: TimeTick::new(0.5),
+        };
+        assert_eq!(
+            insert.validate(&model),
+            Err(CommandError::KeyframeExists(key))
+        );
+        let rename = AnimationCommand::RenameRow {
+            row_id: "nope".to_string(),
+            label: "X".to_string(),
+        };
+        assert_eq!(
+            rename.validate(&model).unwrap_err().to_string(),
+            "row `nope` not found"
+        );
+        let bake = AnimationCommand::BakeTrack {
+            track_id: TrackId::new(),
+            fps: 24.0,
+            range: (TimeTick::zero(), TimeTick::new(1.0)),
+        };
+        assert!(matches!(
+            bake.validate(&model),
+            Err(CommandError::TrackNotFound(_))
+        ));
+    }
+}
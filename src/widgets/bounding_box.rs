@@ -1,8 +1,11 @@
 //! Bounding box widget for multi-keyframe selection transforms.
 //!
 //! Provides a visual bounding box around selected keyframes with handles
-//! for offset (translate) and scale operations.
+//! for offset (translate) and scale operations. [`WorldBoundingBox`] keeps
+//! the bounds in time/value space so they survive zooming and panning.
 
+use crate::TimeTick;
+use crate::widgets::reduced_motion::reduced_motion;
 use egui::{Color32, Painter, Pos2, Rect, Stroke, Vec2};
 
 #[cfg(feature = "serde")]
@@ -93,6 +96,8 @@ pub struct BoundingBoxConfig {
     pub anchor_color: Color32,
     /// Size of resize handles in pixels.
     pub handle_size: f32,
+    /// Side of the square in which the pointer grabs a handle.
+    pub hit_size: f32,
     /// Border stroke width.
     pub border_width: f32,
 }
@@ -104,6 +109,7 @@ impl Default for BoundingBoxConfig {
             handle_color: Color32::from_rgb(255, 255, 255),
             anchor_color: Color32::from_rgb(255, 200, 100),
             handle_size: 6.0,
+            hit_size: 6.0,
             border_width: 1.0,
         }
     }
@@ -157,9 +163,9 @@ impl BoundingBox {
         self
     }
 
-    /// Get the handle rectangles for hit testing.
+    /// Get the handle hit rectangles.
     fn handle_rects(&self) -> [(BoundingBoxHandle, Rect); 8] {
-        let hs = self.config.handle_size;
+        let hs = self.config.hit_size;
         let b = self.bounds;
 
         [
@@ -230,9 +236,13 @@ impl BoundingBox {
         self.draw_anchor(painter, self.anchor_pos);
     }
 
-    /// Draw a dashed rectangle.
+    /// Draw a dashed rectangle, or a solid one with reduced motion.
     fn draw_dashed_rect(&self, painter: &Painter, rect: Rect) {
         let stroke = Stroke::new(self.config.border_width, self.config.border_color);
+        if reduced_motion(painter.ctx()) {
+            painter.rect_stroke(rect, 0.0, stroke, egui::StrokeKind::Middle);
+            return;
+        }
         let dash_length = 4.0;
         let gap_length = 4.0;
 
@@ -311,7 +321,7 @@ impl BoundingBox {
 
     /// Draw a resize handle.
     fn draw_handle(&self, painter: &Painter, center: Pos2, hovered: bool) {
-        let size = if hovered {
+        let size = if hovered && !reduced_motion(painter.ctx()) {
             self.config.handle_size + 2.0
         } else {
             self.config.handle_size
@@ -353,6 +363,154 @@ impl BoundingBox {
     }
 }
 
+/// Bounding box of a selection in time/value space.
+///
+/// [`BoundingBox`] is fixed in screen space, so a view change during a
+/// transform leaves it behind. This variant keeps its bounds and anchor in
+/// animation units and re-derives the screen box from the current view with
+/// [`Self::to_screen`] every frame. [`Self::scale_factors`] likewise works on
+/// a pointer position in time/value space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WorldBoundingBox {
+    /// Earliest time.
+    pub min_time: TimeTick,
+    /// Latest time.
+    pub max_time: TimeTick,
+    /// Smallest value.
+    pub min_value: f32,
+    /// Largest value.
+    pub max_value: f32,
+    /// Pivot `(time, value)` for scale operations.
+    pub anchor: (TimeTick, f32),
+}
+
+impl WorldBoundingBox {
+    /// Bounds of `points`, anchored at their center. Returns `None` if there
+    /// are no points.
+    pub fn from_points(points: impl IntoIterator<Item = (TimeTick, f32)>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let (time, value) = points.next()?;
+        let mut bbox = Self {
+            min_time: time,
+            max_time: time,
+            min_value: value,
+            max_value: value,
+            anchor: (time, value),
+        };
+        for (time, value) in points {
+            bbox.min_time = bbox.min_time.min(time);
+            bbox.max_time = bbox.max_time.max(time);
+            bbox.min_value = bbox.min_value.min(value);
+            bbox.max_value = bbox.max_value.max(value);
+        }
+        Some(bbox.anchor_mode(AnchorMode::Center, TimeTick::default()))
+    }
+
+    /// Place the anchor according to `mode`; `playhead` is used by
+    /// [`AnchorMode::Playhead`].
+    pub fn anchor_mode(mut self, mode: AnchorMode, playhead: TimeTick) -> Self {
+        let center_value = (self.min_value + self.max_value) / 2.0;
+        self.anchor = match mode {
+            AnchorMode::Start => (self.min_time, self.min_value),
+            AnchorMode::End => (self.max_time, self.max_value),
+            AnchorMode::Center => (self.min_time.lerp(self.max_time, 0.5), center_value),
+            AnchorMode::Playhead => (playhead, center_value),
+        };
+        self
+    }
+
+    /// Duration covered by the box.
+    pub fn time_span(&self) -> f64 {
+        (self.max_time - self.min_time).value()
+    }
+
+    /// Value span covered by the box.
+    pub fn value_span(&self) -> f32 {
+        self.max_value - self.min_value
+    }
+
+    /// Screen-space box for the current view; `to_screen` maps
+    /// `(time, value)` to screen space.
+    pub fn to_screen(&self, to_screen: impl Fn(TimeTick, f32) -> Pos2) -> BoundingBox {
+        let corners = [
+            to_screen(self.min_time, self.min_value),
+            to_screen(self.max_time, self.max_value),
+        ];
+        // SAFETY: `corners` is non-empty.
+        let bounds = calculate_bounds(&corners).unwrap();
+        BoundingBox::new(bounds).anchor(to_screen(self.anchor.0, self.anchor.1))
+    }
+
+    /// `(time_scale, value_scale)` about the anchor that moves the edges
+    /// dragged by `handle` to `target`.
+    ///
+    /// An edge that coincides with the anchor cannot be moved by scaling;
+    /// dragging it grows the box by the distance it was dragged instead.
+    /// Factors are clamped to stay positive.
+    pub fn scale_factors(&self, handle: BoundingBoxHandle, target: (TimeTick, f32)) -> (f64, f64) {
+        let mut time_scale = 1.0;
+        let mut value_scale = 1.0;
+
+        if handle.scales_x() && self.time_span() > 1e-6 {
+            let moves_left = matches!(
+                handle,
+                BoundingBoxHandle::Left
+                    | BoundingBoxHandle::TopLeft
+                    | BoundingBoxHandle::BottomLeft
+            );
+            let edge = if moves_left {
+                self.min_time
+            } else {
+                self.max_time
+            };
+            time_scale = edge_scale(
+                edge.value(),
+                target.0.value(),
+                self.anchor.0.value(),
+                self.time_span(),
+                moves_left,
+            );
+        }
+
+        if handle.scales_y() && self.value_span() > 1e-6 {
+            let moves_bottom = matches!(
+                handle,
+                BoundingBoxHandle::Bottom
+                    | BoundingBoxHandle::BottomLeft
+                    | BoundingBoxHandle::BottomRight
+            );
+            let edge = if moves_bottom {
+                self.min_value
+            } else {
+                self.max_value
+            };
+            value_scale = edge_scale(
+                edge as f64,
+                target.1 as f64,
+                self.anchor.1 as f64,
+                self.value_span() as f64,
+                moves_bottom,
+            );
+        }
+
+        (time_scale, value_scale)
+    }
+}
+
+/// Scale factor about `anchor` that moves `edge` to `target`, falling back to
+/// growing a box of size `span` by the dragged distance when the edge sits on
+/// the anchor. `lower` marks the edge at the small end of the axis.
+fn edge_scale(edge: f64, target: f64, anchor: f64, span: f64, lower: bool) -> f64 {
+    let offset = edge - anchor;
+    let scale = if offset.abs() > 1e-6 {
+        (target - anchor) / offset
+    } else {
+        let expansion = if lower { edge - target } else { target - edge };
+        1.0 + expansion / span
+    };
+    scale.max(0.01)
+}
+
 /// Calculate the bounding rectangle for a set of screen positions.
 pub fn calculate_bounds(positions: &[Pos2]) -> Option<Rect> {
     if positions.is_empty() {
@@ -392,6 +550,27 @@ pub fn calculate_bounds(positions: &[Pos2]) -> Option<Rect> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn world_box_scales_edges_to_target() {
+        let bbox =
+            WorldBoundingBox::from_points([(TimeTick::new(1.0), 0.0), (TimeTick::new(3.0), 10.0)])
+                .unwrap();
+        assert_eq!(bbox.anchor, (TimeTick::new(2.0), 5.0));
+
+        // The right edge moves from 3 to 4 about the center at 2.
+        let (time_scale, value_scale) =
+            bbox.scale_factors(BoundingBoxHandle::Right, (TimeTick::new(4.0), 0.0));
+        assert!((time_scale - 2.0).abs() < 1e-9);
+        assert_eq!(value_scale, 1.0);
+
+        // Anchored at the start, the bottom edge sits on the anchor and grows
+        // the box by the dragged distance.
+        let bbox = bbox.anchor_mode(AnchorMode::Start, TimeTick::default());
+        let (_, value_scale) =
+            bbox.scale_factors(BoundingBoxHandle::Bottom, (TimeTick::new(2.0), -5.0));
+        assert!((value_scale - 1.5).abs() < 1e-9);
+    }
+
     #[test]
     fn handle_properties() {
         assert!(BoundingBoxHandle::TopLeft.scales_x());
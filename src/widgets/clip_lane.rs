@@ -0,0 +1,361 @@
+//! Lane of clip instances for non-linear animation.
+
+use crate::core::clip::{ClipEdge, ClipInstance, ClipInstanceId, ClipSequence};
+use crate::{SpaceTransform, TimeTick};
+use egui::{Color32, CursorIcon, Pos2, Rect, Response, Sense, Stroke, Ui};
+
+/// Configuration for the clip lane.
+#[derive(Debug, Clone)]
+pub struct ClipLaneConfig {
+    /// Height of the lane in pixels.
+    pub height: f32,
+    /// Background color.
+    pub background: Color32,
+    /// Label color.
+    pub text_color: Color32,
+    /// Horizontal distance in pixels within which a drag grabs an edge.
+    pub edge_grab_distance: f32,
+}
+
+impl Default for ClipLaneConfig {
+    fn default() -> Self {
+        Self {
+            height: 24.0,
+            background: Color32::from_gray(28),
+            text_color: Color32::from_gray(230),
+            edge_grab_distance: 4.0,
+        }
+    }
+}
+
+/// What a drag on the lane changes, kept in egui memory while dragging.
+#[derive(Debug, Clone, Copy)]
+enum ClipDrag {
+    /// Moving an instance grabbed `grab` after its start.
+    Move { id: ClipInstanceId, grab: TimeTick },
+    /// Trimming an edge of an instance.
+    Trim { id: ClipInstanceId, edge: ClipEdge },
+}
+
+/// Response from the [`ClipLane`].
+pub struct ClipLaneResponse {
+    /// The egui response for the lane.
+    pub response: Response,
+    /// Instance that was clicked.
+    pub clicked: Option<ClipInstanceId>,
+    /// Instance dragged to start at a new time.
+    pub moved: Option<(ClipInstanceId, TimeTick)>,
+    /// Instance whose edge was dragged to a new time.
+    pub trimmed: Option<(ClipInstanceId, ClipEdge, TimeTick)>,
+    /// Instance to split at a time.
+    pub split: Option<(ClipInstanceId, TimeTick)>,
+    /// Instance whose loop count was changed.
+    pub loop_count: Option<(ClipInstanceId, u32)>,
+    /// Instance the user asked to remove.
+    pub removed: Option<ClipInstanceId>,
+}
+
+impl ClipLaneResponse {
+    /// Apply the requested edits to `sequence`.
+    pub fn apply<T: Clone>(&self, sequence: &mut ClipSequence<T>) {
+        if let Some((id, start)) = self.moved {
+            sequence.move_instance(id, start);
+        }
+        if let Some((id, edge, time)) = self.trimmed {
+            sequence.trim(id, edge, time);
+        }
+        if let Some((id, time)) = self.split {
+            sequence.split(id, time);
+        }
+        if let Some((id, loop_count)) = self.loop_count {
+            sequence.set_loop_count(id, loop_count);
+        }
+        if let Some(id) = self.removed {
+            sequence.remove_instance(id);
+        }
+    }
+}
+
+/// Lane of [`ClipInstance`]s, the sequencer of a non-linear animation
+/// workflow.
+///
+/// Drag an instance to move it, drag its edges to trim it and right-click
+/// to split it where clicked, change how often it loops or remove it. Loop
+/// boundaries are drawn as seams. Edits are reported in the
+/// [`ClipLaneResponse`]; apply them with [`ClipLaneResponse::apply`].
+pub struct ClipLane<'a> {
+    instances: &'a [ClipInstance],
+    space: &'a SpaceTransform,
+    config: ClipLaneConfig,
+}
+
+impl<'a> ClipLane<'a> {
+    /// Create a new clip lane.
+    pub fn new(instances: &'a [ClipInstance], space: &'a SpaceTransform) -> Self {
+        Self {
+            instances,
+            space,
+            config: ClipLaneConfig::default(),
+        }
+    }
+
+    /// Set the configuration.
+    pub fn config(mut self, config: ClipLaneConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Show the lane in `rect`.
+    pub fn show(self, ui: &mut Ui, rect: Rect) -> ClipLaneResponse {
+        let response = ui.allocate_rect(rect, Sense::click_and_drag());
+        let mut result = ClipLaneResponse {
+            response: response.clone(),
+            clicked: None,
+            moved: None,
+            trimmed: None,
+            split: None,
+            loop_count: None,
+            removed: None,
+        };
+
+        if ui.is_rect_visible(rect) {
+            self.paint(&ui.painter_at(rect), rect);
+        }
+
+        let drag_id = response.id.with("drag");
+        let context_id = response.id.with("context");
+
+        let hovered_edge = response.hover_pos().and_then(|pos| self.edge_at(pos.x));
+        if hovered_edge.is_some() {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        }
+        if response.drag_started()
+            && let Some(origin) = ui.input(|i| i.pointer.press_origin())
+        {
+            let drag = match self.edge_at(origin.x) {
+                Some((id, edge)) => Some(ClipDrag::Trim { id, edge }),
+                None => self.instance_at(origin.x).map(|instance| ClipDrag::Move {
+                    id: instance.id,
+                    grab: self.space.clipped_to_unit(origin.x) - instance.start,
+                }),
+            };
+            if let Some(drag) = drag {
+                ui.data_mut(|data| data.insert_temp(drag_id, drag));
+            }
+        }
+        if let Some(drag) = ui.data(|data| data.get_temp::<ClipDrag>(drag_id)) {
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                let time = self.space.clipped_to_unit(pos.x);
+                match drag {
+                    ClipDrag::Move { id, grab } => result.moved = Some((id, time - grab)),
+                    ClipDrag::Trim { id, edge } => {
+                        ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+                        result.trimmed = Some((id, edge, time));
+                    }
+                }
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<ClipDrag>(drag_id));
+            }
+        }
+
+        let pointer_x = response.interact_pointer_pos().map(|pos| pos.x);
+        if response.clicked() {
+            result.clicked = pointer_x
+                .and_then(|x| self.instance_at(x))
+                .map(|instance| instance.id);
+        }
+        if response.secondary_clicked()
+            && let Some(x) = pointer_x
+            && let Some(instance) = self.instance_at(x)
+        {
+            let time = self.space.clipped_to_unit(x);
+            ui.data_mut(|data| data.insert_temp(context_id, (instance.id, time)));
+        }
+
+        // Context menu.
+        let context: Option<(ClipInstanceId, TimeTick)> = ui.data(|data| data.get_temp(context_id));
+        response.context_menu(|ui| {
+            let Some(instance) = context.and_then(|(id, _)| self.instance(id)) else {
+                ui.close();
+                return;
+            };
+            let time = context.map_or(instance.start, |(_, time)| time);
+            if ui
+                .add_enabled(instance.loop_count == 1, egui::Button::new("Split here"))
+                .clicked()
+            {
+                result.split = Some((instance.id, time));
+                ui.close();
+            }
+            if ui.button("Add loop").clicked() {
+                result.loop_count = Some((instance.id, instance.loop_count + 1));
+                ui.close();
+            }
+            if ui
+                .add_enabled(instance.loop_count > 1, egui::Button::new("Remove loop"))
+                .clicked()
+            {
+                result.loop_count = Some((instance.id, instance.loop_count - 1));
+                ui.close();
+            }
+            ui.separator();
+            if ui.button("Remove").clicked() {
+                result.removed = Some(instance.id);
+                ui.close();
+            }
+        });
+
+        result
+    }
+
+    /// Paint the instances, later ones on top.
+    pub fn paint(&self, painter: &egui::Painter, rect: Rect) {
+        painter.rect_filled(rect, 0.0, self.config.background);
+
+        for instance in self.instances {
+            let Some(instance_rect) = self.instance_rect(rect, instance) else {
+                continue;
+            };
+            painter.rect_filled(instance_rect, 3.0, instance.color.linear_multiply(0.6));
+
+            // Seams between loops.
+            for index in 1..instance.loop_count {
+                let time = instance.start + instance.loop_duration() * index as f64;
+                let x = self.space.unit_to_clipped(time);
+                painter.line_segment(
+                    [
+                        Pos2::new(x, instance_rect.top() + 3.0),
+                        Pos2::new(x, instance_rect.bottom() - 3.0),
+                    ],
+                    Stroke::new(1.0, instance.color),
+                );
+            }
+
+            painter.rect_stroke(
+                instance_rect,
+                3.0,
+                Stroke::new(1.0, instance.color),
+                egui::StrokeKind::Inside,
+            );
+            let label = if instance.loop_count > 1 {
+                format!("{} ×{}", instance.name, instance.loop_count)
+            } else {
+                instance.name.clone()
+            };
+            painter.with_clip_rect(instance_rect.intersect(rect)).text(
+                Pos2::new(
+                    instance_rect.left().max(rect.left()) + 4.0,
+                    instance_rect.center().y,
+                ),
+                egui::Align2::LEFT_CENTER,
+                label,
+                egui::FontId::proportional(11.0),
+                self.config.text_color,
+            );
+        }
+    }
+
+    fn instance(&self, id: ClipInstanceId) -> Option<&ClipInstance> {
+        self.instances.iter().find(|instance| instance.id == id)
+    }
+
+    /// Screen rect of an instance, or `None` if it is off screen.
+    fn instance_rect(&self, rect: Rect, instance: &ClipInstance) -> Option<Rect> {
+        let left = self.space.unit_to_clipped(instance.start);
+        let right = self.space.unit_to_clipped(instance.end());
+        (right >= rect.left() && left <= rect.right()).then(|| {
+            Rect::from_min_max(
+                Pos2::new(left, rect.top() + 2.0),
+                Pos2::new(right, rect.bottom() - 2.0),
+            )
+        })
+    }
+
+    /// Topmost instance at screen x.
+    fn instance_at(&self, x: f32) -> Option<&ClipInstance> {
+        let time = self.space.clipped_to_unit(x);
+        self.instances
+            .iter()
+            .rev()
+            .find(|instance| instance.contains(time))
+    }
+
+    /// Edge of the topmost instance within grab distance of screen x.
+    fn edge_at(&self, x: f32) -> Option<(ClipInstanceId, ClipEdge)> {
+        self.instances.iter().rev().find_map(|instance| {
+            let near = |time: TimeTick| {
+                (self.space.unit_to_clipped(time) - x).abs() <= self.config.edge_grab_distance
+            };
+            if near(instance.end()) {
+                Some((instance.id, ClipEdge::End))
+            } else if near(instance.start) {
+                Some((instance.id, ClipEdge::Start))
+            } else {
+                None
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::clip::Clip;
+    use crate::core::keyframe::Keyframe;
+    use crate::core::track::Track;
+    use crate::widgets::offscreen::run_input_frame;
+    use egui::{Context, Event, PointerButton, RawInput, Vec2};
+
+    #[test]
+    fn drags_move_and_trim_instances() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0));
+        track.add_keyframe(Keyframe::new(2.0, 1.0));
+        let mut sequence = ClipSequence::new();
+        let clip = sequence.add_clip(Clip::new("Walk").with_track("x", track));
+        let id = sequence.add_instance(clip, 1.0).unwrap();
+
+        // At 100 pixels per second the instance spans x 100..300.
+        let space = SpaceTransform::default();
+        let size = Vec2::new(400.0, 24.0);
+        let drag = |from: f32, to: f32| {
+            let pointer = |event: Event| RawInput {
+                events: vec![event],
+                ..Default::default()
+            };
+            let button = |x: f32, pressed: bool| Event::PointerButton {
+                pos: Pos2::new(x, 12.0),
+                button: PointerButton::Primary,
+                pressed,
+                modifiers: Default::default(),
+            };
+            let frames = [
+                pointer(Event::PointerMoved(Pos2::new(from, 12.0))),
+                pointer(button(from, true)),
+                pointer(Event::PointerMoved(Pos2::new((from + to) / 2.0, 12.0))),
+                pointer(Event::PointerMoved(Pos2::new(to, 12.0))),
+                pointer(button(to, false)),
+            ];
+            let ctx = Context::default();
+            let (mut moved, mut trimmed) = (None, None);
+            for input in frames {
+                run_input_frame(&ctx, size, 1.0, input, |ui| {
+                    let response = ClipLane::new(&sequence.instances, &space)
+                        .show(ui, Rect::from_min_size(Pos2::ZERO, size));
+                    moved = response.moved.or(moved);
+                    trimmed = response.trimmed.or(trimmed);
+                });
+            }
+            (moved, trimmed)
+        };
+
+        assert_eq!(drag(200.0, 250.0), (Some((id, TimeTick::new(1.5))), None));
+        assert_eq!(
+            drag(299.0, 350.0),
+            (None, Some((id, ClipEdge::End, TimeTick::new(3.5))))
+        );
+    }
+}
@@ -1,12 +1,28 @@
 //! Bezier curve editor widget for animation curves.
 
 use crate::HashSet;
-use crate::core::keyframe::{KeyframeId, KeyframeType};
-use crate::traits::{KeyframeSource, KeyframeView};
-use crate::widgets::bounding_box::{AnchorMode, BoundingBox, BoundingBoxHandle, calculate_bounds};
-use crate::widgets::keyframe_dot::KeyframeDot;
+use crate::core::easing::{
+    Easing, EasingLibrary, EasingPreset, ease_segment_commands, match_preset, segment_easing,
+};
+use crate::core::extrapolation::Extrapolation;
+use crate::core::interpolation::{interpolate_with_extrapolation, segment_control_points};
+use crate::core::keyframe::{BezierHandles, Keyframe, KeyframeId, KeyframeType, WeightedHandles};
+use crate::core::marker::Markers;
+use crate::core::modifier::ModifierStack;
+use crate::dopesheet::SelectionMode;
+use crate::key_bindings::KeyBindings;
+use crate::snap::SnapConfig;
+use crate::traits::{AnimationCommand, KeyframeSource, KeyframeView, ValueUnit};
+use crate::widgets::bounding_box::{AnchorMode, BoundingBox, BoundingBoxHandle, WorldBoundingBox};
+use crate::widgets::interaction::InteractionSizes;
+use crate::widgets::keyframe_dot::{
+    KeyframeDot, KeyframeDotTextures, KeyframeVisual, KeyframeVisualState, keyframe_tooltip,
+};
+use crate::widgets::time_ruler::{draw_markers, draw_work_range};
+use crate::widgets::value_ruler::{interval_decimals, value_grid_intervals};
+use crate::widgets::{offscreen, svg};
 use crate::{SpaceTransform, TimeTick};
-use egui::{Color32, Pos2, Rect, Response, Sense, Shape, Stroke, Ui, Vec2};
+use egui::{Color32, ColorImage, Pos2, Rect, Response, Sense, Shape, Stroke, Ui, Vec2};
 
 /// Configuration for the curve editor.
 #[derive(Debug, Clone)]
@@ -15,6 +31,9 @@ pub struct CurveEditorConfig {
     pub height: f32,
     /// Color for the curve line.
     pub curve_color: Color32,
+    /// Color for the curve with its modifiers applied, see
+    /// [`CurveEditor::modifiers`].
+    pub modifier_color: Color32,
     /// Color for keyframe dots.
     pub keyframe_color: Color32,
     /// Color for selected keyframes.
@@ -37,8 +56,81 @@ pub struct CurveEditorConfig {
     pub bounding_box_color: Color32,
     /// Color for the bounding box anchor indicator.
     pub anchor_color: Color32,
+    /// Color for the box selection rectangle.
+    pub box_select_color: Color32,
     /// Size of bounding box handles.
     pub bbox_handle_size: f32,
+    /// Pointer hit sizes, resolved for UI zoom and touch input when shown.
+    pub interaction: InteractionSizes,
+    /// Images drawn instead of the keyframe diamonds.
+    pub keyframe_textures: Option<KeyframeDotTextures>,
+    /// Label horizontal grid lines inside the editor. Turn off when a
+    /// [`ValueRuler`](super::ValueRuler) is shown next to it.
+    pub value_labels: bool,
+    /// How the editor height is chosen; `height` is the preferred height.
+    pub height_mode: HeightMode,
+    /// Smallest size the editor allocates, even if less space is available.
+    pub min_size: Vec2,
+    /// Keep the rect of the previous frame when the layout around the editor
+    /// changes and switch to the new size one frame later.
+    ///
+    /// The rect is then the same for every frame a
+    /// [`SpaceTransform`] is computed from, see [`CurveEditor::committed_rect`].
+    pub stable_rect: bool,
+    /// Show a toolbar in the top right corner with buttons for the view
+    /// requests that otherwise need keyboard shortcuts.
+    pub toolbar: bool,
+    /// Draw the curve as it was before a keyframe, handle or bounding box
+    /// drag faintly under the live curve until the drag ends.
+    pub ghost_curve: bool,
+    /// Mark the loop seams after the work range and repeat the curve inside
+    /// it faintly in the following loop iterations, as looped playback
+    /// shows it, to check that the curve joins up at the seam.
+    ///
+    /// Needs a [`CurveEditor::work_range`].
+    pub loop_preview: bool,
+    /// Draw the diagonal where value equals time as a reference, for
+    /// editing a [`TimeWarp`](crate::TimeWarp) curve: where the curve is
+    /// steeper than the diagonal, time runs faster.
+    pub time_warp: bool,
+    /// Order in which the layers are painted, bottom first.
+    pub draw_order: [CurveLayer; 4],
+    /// Keyboard shortcuts and the modifiers of pan, zoom, axis constraint
+    /// and selection.
+    pub key_bindings: KeyBindings,
+}
+
+/// A layer of a [`CurveEditor`], see [`CurveEditorConfig::draw_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CurveLayer {
+    /// The curve with its extrapolation, ghost and preview marker.
+    Curves,
+    /// Bezier handles of selected keyframes.
+    Handles,
+    /// Keyframe dots.
+    Keyframes,
+    /// Bounding box of a multi-keyframe selection.
+    BoundingBox,
+}
+
+impl CurveLayer {
+    /// Curves, handles, keyframes, bounding box.
+    pub const DEFAULT_ORDER: [Self; 4] = [
+        Self::Curves,
+        Self::Handles,
+        Self::Keyframes,
+        Self::BoundingBox,
+    ];
+}
+
+/// How a [`CurveEditor`] chooses its height.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HeightMode {
+    /// Use [`CurveEditorConfig::height`].
+    #[default]
+    Preferred,
+    /// Fill the available height.
+    Fill,
 }
 
 impl Default for CurveEditorConfig {
@@ -46,6 +138,7 @@ impl Default for CurveEditorConfig {
         Self {
             height: 200.0,
             curve_color: Color32::from_rgb(100, 180, 255),
+            modifier_color: Color32::from_rgb(150, 220, 120),
             keyframe_color: Color32::from_rgb(100, 180, 255),
             selected_color: Color32::from_rgb(255, 200, 100),
             handle_color: Color32::from_rgb(255, 150, 100),
@@ -57,33 +150,163 @@ impl Default for CurveEditorConfig {
             curve_segments: 32,
             bounding_box_color: Color32::from_rgb(100, 150, 255),
             anchor_color: Color32::from_rgb(255, 200, 100),
+            box_select_color: Color32::from_rgb(100, 150, 255),
             bbox_handle_size: 6.0,
+            interaction: InteractionSizes::default(),
+            keyframe_textures: None,
+            value_labels: true,
+            height_mode: HeightMode::default(),
+            min_size: Vec2::ZERO,
+            stable_rect: false,
+            toolbar: false,
+            ghost_curve: true,
+            loop_preview: false,
+            time_warp: false,
+            draw_order: CurveLayer::DEFAULT_ORDER,
+            key_bindings: KeyBindings::default(),
         }
     }
 }
 
+impl CurveEditorConfig {
+    /// Use `color` for the curve and its keyframes, e.g. a row color from
+    /// [`PropertyRow::color`](crate::PropertyRow::color).
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.curve_color = color;
+        self.keyframe_color = color;
+        self
+    }
+}
+
 /// Which handle is being dragged.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum HandleSide {
     Left,
     Right,
 }
 
+impl HandleSide {
+    /// The handle on the other side of the keyframe.
+    pub fn opposite(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+/// Screen radius of a handle circle.
+pub(crate) const HANDLE_RADIUS: f32 = 4.0;
+
+/// Keyframes moved by the drag in progress, read from the drag state the
+/// interactions of earlier frames stored.
+struct DraggedKeyframes {
+    /// A rigid or bounding box drag of the selection.
+    selection: bool,
+    /// A single keyframe drag.
+    single: Option<KeyframeId>,
+}
+
+impl DraggedKeyframes {
+    fn of(ui: &Ui, id: egui::Id) -> Self {
+        ui.data(|data| Self {
+            selection: data
+                .get_temp::<(KeyframeId, TimeTick, f32)>(id.with("rigid_drag"))
+                .is_some()
+                || data
+                    .get_temp::<(BoundingBoxHandle, f64, f32)>(id.with("bbox_drag"))
+                    .is_some(),
+            single: data
+                .get_temp::<KeyframeMove>(id.with("keyframe_move"))
+                .map(|last_move| last_move.keyframe_id),
+        })
+    }
+
+    fn contains(&self, kf: &KeyframeView, selected: bool) -> bool {
+        self.single == Some(kf.id) || (self.selection && selected && !kf.pinned && !kf.locked)
+    }
+}
+
+/// Screen-space geometry of a bezier handle.
+///
+/// Handles are stored normalized to the segment they belong to, so the
+/// handle position is `origin + span * (x, y)`.
+#[derive(Debug, Clone, Copy)]
+struct HandleGeometry {
+    position: Pos2,
+    origin: Pos2,
+    span: Vec2,
+}
+
+impl HandleGeometry {
+    /// Normalize a screen position into this handle's segment.
+    ///
+    /// Axes along which the segment is degenerate keep `fallback`.
+    fn normalize(&self, pos: Pos2, fallback: (f32, f32)) -> (f32, f32) {
+        let x = if self.span.x.abs() > f32::EPSILON {
+            ((pos.x - self.origin.x) / self.span.x).clamp(0.0, 1.0)
+        } else {
+            fallback.0
+        };
+        let y = if self.span.y.abs() > f32::EPSILON {
+            (pos.y - self.origin.y) / self.span.y
+        } else {
+            fallback.1
+        };
+        (x, y)
+    }
+}
+
 /// Information about a handle drag.
+///
+/// Emitted with [`DragPhase::Preview`] every frame while dragging, then once
+/// with [`DragPhase::Commit`] when the drag is released, together with the
+/// handle command in [`CurveEditorResponse::commands`].
 #[derive(Debug, Clone)]
 pub struct HandleDrag {
     pub keyframe_id: KeyframeId,
     pub side: HandleSide,
     pub new_x: f32,
     pub new_y: f32,
+    /// Whether the drag is ongoing or was just released.
+    pub phase: DragPhase,
+}
+
+impl HandleDrag {
+    /// Whether this is the final update of a drag.
+    pub fn is_commit(&self) -> bool {
+        self.phase == DragPhase::Commit
+    }
+}
+
+/// Phase of a continuous edit such as a [`KeyframeMove`] or a [`HandleDrag`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DragPhase {
+    /// The drag is ongoing; the value may change again next frame.
+    Preview,
+    /// The drag was released; this is the final value.
+    Commit,
 }
 
 /// Information about a keyframe move.
+///
+/// Emitted with [`DragPhase::Preview`] every frame while dragging, then once
+/// with [`DragPhase::Commit`] and the final values when the drag is released.
+/// Hosts with expensive downstream work can defer it to the commit.
 #[derive(Debug, Clone)]
 pub struct KeyframeMove {
     pub keyframe_id: KeyframeId,
     pub new_position: TimeTick,
     pub new_value: f32,
+    /// Whether the drag is ongoing or was just released.
+    pub phase: DragPhase,
+}
+
+impl KeyframeMove {
+    /// Whether this is the final move of a drag.
+    pub fn is_commit(&self) -> bool {
+        self.phase == DragPhase::Commit
+    }
 }
 
 /// Response from the curve editor.
@@ -93,6 +316,14 @@ pub struct CurveEditorResponse {
     pub response: Option<Response>,
     /// Keyframe that was clicked.
     pub clicked_keyframe: Option<KeyframeId>,
+    /// Keyframes at both ends of the curve segment that was clicked away
+    /// from its keyframes, in time order.
+    pub clicked_segment: Option<(KeyframeId, KeyframeId)>,
+    /// Request to insert a keyframe on the curve at this time without
+    /// changing its shape (Ctrl/Cmd+click on the curve, or `K` at the
+    /// current time), e.g. with
+    /// [`AnimationCommand::InsertKeyframePreserving`].
+    pub insert_keyframe_at: Option<TimeTick>,
     /// Handle that was dragged.
     pub handle_drag: Option<HandleDrag>,
     /// Keyframe that was moved.
@@ -101,11 +332,13 @@ pub struct CurveEditorResponse {
     pub add_keyframe_at: Option<(TimeTick, f32)>,
     /// Keyframe to delete.
     pub delete_keyframe: Option<KeyframeId>,
-    /// Batch offset for selected keyframes (delta_time, delta_value).
+    /// Batch offset for selected, unpinned keyframes (delta_time,
+    /// delta_value).
     pub offset_keyframes: Option<(TimeTick, f32)>,
-    /// Batch scale for selected keyframes (anchor_time, anchor_value, time_scale, value_scale).
+    /// Batch scale for selected, unpinned keyframes (anchor_time,
+    /// anchor_value, time_scale, value_scale).
     pub scale_keyframes: Option<(TimeTick, f32, f64, f64)>,
-    /// Whether a bounding box transform drag ended (for undo grouping).
+    /// Whether a bounding box or handle drag ended (for undo grouping).
     pub transform_ended: bool,
     /// Request to select all keyframes (Cmd+A).
     pub select_all: bool,
@@ -123,6 +356,58 @@ pub struct CurveEditorResponse {
     pub set_interpolation: Option<(KeyframeId, KeyframeType)>,
     /// Request to fit view to all keyframes (press F).
     pub fit_view: bool,
+    /// Request to toggle the normalized view (press N).
+    pub toggle_normalized: bool,
+    /// Request to fit the view to the selected keyframes (toolbar).
+    pub fit_selection: bool,
+    /// Request to toggle snapping (toolbar).
+    pub toggle_snap: bool,
+    /// Request to switch between showing time in frames and seconds
+    /// (toolbar).
+    pub toggle_frames: bool,
+    /// Keyframes enclosed by a finished box selection, and how to combine
+    /// them with the current selection.
+    pub box_selected: Option<(Vec<KeyframeId>, SelectionMode)>,
+    /// Position and state of every keyframe dot, in time order.
+    pub keyframes: Vec<KeyframeVisual>,
+    /// Animation commands to execute (from user interactions), in the order
+    /// they must run. Commands for several keyframes list them in time
+    /// order.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// The point of a curve segment closest to a screen position, see
+/// [`CurveEditor::distance_to_curve`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CurvePoint {
+    /// Keyframes at both ends of the segment, in time order.
+    pub segment: (KeyframeId, KeyframeId),
+    /// Time of the point.
+    pub time: TimeTick,
+    /// Value of the curve at `time`.
+    pub value: f32,
+    /// Screen distance from the position to the point.
+    pub distance: f32,
+}
+
+/// What lies under a screen position in a [`CurveEditor`].
+///
+/// Returned by [`CurveEditor::hit_test`] so hosts can build custom gestures.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CurveEditorHit {
+    /// A bezier handle of a selected keyframe.
+    Handle {
+        keyframe_id: KeyframeId,
+        side: HandleSide,
+    },
+    /// A keyframe.
+    Keyframe { keyframe_id: KeyframeId },
+    /// The bounding box of a multi-keyframe selection.
+    BoundingBox(BoundingBoxHandle),
+    /// A curve segment, at its point closest to the pointer.
+    Curve(CurvePoint),
+    /// Empty background at a time and value.
+    Background { time: TimeTick, value: f32 },
 }
 
 /// Curve editor widget for editing bezier animation curves.
@@ -138,6 +423,16 @@ pub struct CurveEditor<'a, S: KeyframeSource> {
     id_source: Option<egui::Id>,
     anchor_mode: AnchorMode,
     current_time: TimeTick,
+    snap: Option<SnapConfig>,
+    markers: Option<&'a Markers>,
+    work_range: Option<(TimeTick, TimeTick)>,
+    normalized: bool,
+    preview_time: Option<TimeTick>,
+    frames: bool,
+    unit: ValueUnit,
+    modifiers: Option<&'a ModifierStack>,
+    easing_library: Option<&'a EasingLibrary>,
+    read_only_curve: Option<&'a dyn Fn(TimeTick) -> Option<f32>>,
 }
 
 impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
@@ -157,6 +452,16 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             id_source: None,
             anchor_mode: AnchorMode::default(),
             current_time: TimeTick::default(),
+            snap: None,
+            markers: None,
+            work_range: None,
+            normalized: false,
+            preview_time: None,
+            frames: false,
+            unit: ValueUnit::Generic,
+            modifiers: None,
+            easing_library: None,
+            read_only_curve: None,
         }
     }
 
@@ -172,7 +477,7 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
         self
     }
 
-    /// Set the anchor mode for scale operations.
+    /// Set the anchor mode for scaling and flipping the selection.
     pub fn anchor_mode(mut self, mode: AnchorMode) -> Self {
         self.anchor_mode = mode;
         self
@@ -184,16 +489,96 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
         self
     }
 
+    /// Snap dragged keyframes to frames, other keyframes, markers or the
+    /// grid.
+    pub fn snap(mut self, snap: SnapConfig) -> Self {
+        self.snap = Some(snap);
+        self
+    }
+
+    /// Show markers as vertical lines and use them as snap targets.
+    pub fn markers(mut self, markers: &'a Markers) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    /// Shade times outside the `(start, end)` work range.
+    pub fn work_range(mut self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        self.work_range = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Remap the curve's own value range to the full height, labeled `0`
+    /// to `1`, instead of using the value range passed to [`Self::new`].
+    ///
+    /// Useful to compare the shape of curves with very different magnitudes.
+    /// Edits still produce real values.
+    pub fn normalized(mut self, normalized: bool) -> Self {
+        self.normalized = normalized;
+        if normalized {
+            self.value_range = display_range(self.source.value_range());
+        }
+        self
+    }
+
+    /// Mark the curve's value at `time`, e.g. the
+    /// [`TimeRulerResponse::hover_time`](super::TimeRulerResponse::hover_time)
+    /// while hovering the ruler, without moving the playhead.
+    pub fn preview_time(mut self, time: Option<TimeTick>) -> Self {
+        self.preview_time = time;
+        self
+    }
+
+    /// Draw the curve with the modifiers of `stack` applied over the keyed
+    /// curve. Editing still changes the keys.
+    pub fn modifiers(mut self, stack: &'a ModifierStack) -> Self {
+        self.modifiers = Some(stack);
+        self
+    }
+
+    /// Offer the user-defined presets of `library` in the easing section of
+    /// the keyframe context menu, after the built-ins.
+    pub fn easing_library(mut self, library: &'a EasingLibrary) -> Self {
+        self.easing_library = Some(library);
+        self
+    }
+
+    /// Draw the curve of `curve`, e.g. an expression, which has no keys to
+    /// edit.
+    ///
+    /// Pair it with an empty source to show a track whose values are
+    /// computed rather than keyed.
+    pub fn read_only_curve(mut self, curve: &'a dyn Fn(TimeTick) -> Option<f32>) -> Self {
+        self.read_only_curve = Some(curve);
+        self
+    }
+
+    /// Whether the host shows time in frames rather than seconds.
+    ///
+    /// Only sets the state of the toolbar's frames button, see
+    /// [`CurveEditorConfig::toolbar`].
+    pub fn frames(mut self, frames: bool) -> Self {
+        self.frames = frames;
+        self
+    }
+
+    /// Set the unit of the curve's values, which picks the value grid steps
+    /// and label suffix, e.g. 15°, 45° and 90° for [`ValueUnit::Degrees`].
+    ///
+    /// The normalized view always uses [`ValueUnit::Generic`].
+    pub fn unit(mut self, unit: ValueUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
     /// Show the curve editor widget.
-    pub fn show(self, ui: &mut Ui) -> CurveEditorResponse {
+    pub fn show(mut self, ui: &mut Ui) -> CurveEditorResponse {
+        self.config.interaction = self.config.interaction.for_context(ui.ctx());
         let id = self
             .id_source
             .unwrap_or_else(|| ui.make_persistent_id("curve_editor"));
 
-        let (rect, response) = ui.allocate_exact_size(
-            Vec2::new(ui.available_width(), self.config.height),
-            Sense::click_and_drag(),
-        );
+        let (rect, response) = allocate_editor(ui, id, &self.config);
 
         let mut result = CurveEditorResponse {
             response: Some(response.clone()),
@@ -211,86 +596,155 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
 
         // Draw grid
         self.draw_grid(&painter, rect);
+        if let Some(range) = self.work_range {
+            draw_work_range(&painter, rect, self.space, range);
+        }
+        if let Some(markers) = self.markers {
+            draw_markers(&painter, rect, self.space, markers, false);
+        }
 
-        // Draw curves between keyframes
-        let keyframes = self.source.keyframes_sorted();
-        let keyframe_refs: Vec<&KeyframeView> = keyframes.iter().collect();
-        for window in keyframes.windows(2) {
-            let left = &window[0];
-            let right = &window[1];
-            if left.connected_right {
-                self.draw_curve_segment(&painter, rect, left, right);
-            }
+        let mut keyframes = self.source.keyframes_sorted();
+        // The handles being dragged follow the pointer before their command
+        // is committed on release.
+        if let Some((_, command)) =
+            ui.data(|data| data.get_temp::<(HandleDrag, AnimationCommand)>(id.with("handle_edit")))
+        {
+            preview_handle_edit(&mut keyframes, command);
         }
+        let keyframe_refs: Vec<&KeyframeView> = keyframes.iter().collect();
+        let evaluated: Vec<Keyframe<f32>> =
+            keyframes.iter().map(KeyframeView::to_keyframe).collect();
+        let evaluated_refs: Vec<&Keyframe<f32>> = evaluated.iter().collect();
+        let ghost_id = id.with("ghost");
 
-        // Collect selected keyframe positions for bounding box
-        let mut selected_positions: Vec<Pos2> = Vec::new();
+        // Collect selected keyframe data for bounding box transforms
         let mut selected_keyframe_data: Vec<(KeyframeId, TimeTick, f32)> = Vec::new();
 
-        // Draw keyframes and handles
         let pointer_pos = response.hover_pos();
         let mut hovered_keyframe = None;
-
+        let hovered_handle = pointer_pos.and_then(|p| self.hit_handle(rect, &keyframe_refs, p));
+        let drag = DraggedKeyframes::of(ui, id);
         for kf in &keyframes {
-            let is_selected = self.selected.contains(&kf.id);
-            let screen_pos = self.keyframe_to_screen(rect, kf);
+            let selected = self.selected.contains(&kf.id);
+            let pos = self.keyframe_to_screen(rect, kf);
 
-            if is_selected {
-                selected_positions.push(screen_pos);
+            // Pinned and locked keyframes stay out of batch offsets and
+            // scales.
+            if selected && !kf.pinned && !kf.locked {
                 selected_keyframe_data.push((kf.id, kf.position, kf.value));
             }
 
-            // Check if hovered
-            let is_hovered = pointer_pos
-                .map(|p| {
-                    let dx = (p.x - screen_pos.x).abs();
-                    let dy = (p.y - screen_pos.y).abs();
-                    dx + dy < 12.0
-                })
-                .unwrap_or(false);
-
-            if is_hovered {
+            let hovered = pointer_pos
+                .is_some_and(|p| hits_keyframe(pos, p, self.config.interaction.keyframe_hit));
+            if hovered {
                 hovered_keyframe = Some(kf.id);
             }
 
-            // Draw handles for selected keyframes
-            if is_selected {
-                self.draw_handles(&painter, rect, kf, &keyframe_refs);
-            }
-
-            // Draw keyframe dot
-            KeyframeDot::new(screen_pos)
-                .color(self.config.keyframe_color)
-                .selected(is_selected)
-                .hovered(is_hovered)
-                .paint(&painter);
+            result.keyframes.push(KeyframeVisual {
+                keyframe_id: kf.id,
+                pos,
+                state: KeyframeVisualState {
+                    selected,
+                    hovered,
+                    locked: kf.locked,
+                    muted: false,
+                    pinned: kf.pinned,
+                    breakdown: kf.breakdown,
+                    tag: kf.tag,
+                    dragged: drag.contains(kf, selected),
+                },
+            });
         }
 
-        // Draw bounding box if multiple keyframes selected
-        let mut hovered_bbox_handle = None;
-        if selected_positions.len() > 1
-            && let Some(bounds) = calculate_bounds(&selected_positions)
-        {
-            let anchor_pos = self.calculate_anchor_screen_pos(rect, &selected_keyframe_data);
-
-            let bbox_config = crate::widgets::bounding_box::BoundingBoxConfig {
-                border_color: self.config.bounding_box_color,
-                handle_color: Color32::WHITE,
-                anchor_color: self.config.anchor_color,
-                handle_size: self.config.bbox_handle_size,
-                border_width: 1.0,
-            };
-
-            let bbox = BoundingBox::new(bounds)
-                .anchor(anchor_pos)
-                .config(bbox_config);
-
-            // Hit test for hover state
-            if let Some(pos) = pointer_pos {
-                hovered_bbox_handle = bbox.hit_test(pos);
+        let bbox = self.selection_bounding_box(rect, &keyframes);
+        let hovered_bbox_handle = bbox
+            .as_ref()
+            .zip(pointer_pos)
+            .and_then(|(bbox, pos)| bbox.hit_test(pos));
+        let hovered_curve = pointer_pos
+            .filter(|_| {
+                hovered_keyframe.is_none()
+                    && hovered_handle.is_none()
+                    && hovered_bbox_handle.is_none()
+            })
+            .and_then(|pos| self.nearest_curve_point(rect, &evaluated_refs, pos))
+            .filter(|point| point.distance <= self.config.interaction.curve_hit);
+
+        for layer in self.config.draw_order {
+            match layer {
+                CurveLayer::Curves => {
+                    if let Some(ghost) =
+                        ui.data(|data| data.get_temp::<Vec<Keyframe<f32>>>(ghost_id))
+                    {
+                        self.draw_ghost(&painter, rect, &ghost);
+                    }
+                    if self.config.time_warp {
+                        self.draw_identity(&painter, rect);
+                    }
+                    for (index, window) in keyframes.windows(2).enumerate() {
+                        if window[0].connected_right {
+                            let hovered =
+                                hovered_curve.is_some_and(|point| point.segment.0 == window[0].id);
+                            self.draw_curve_segment(
+                                &painter,
+                                rect,
+                                &evaluated_refs,
+                                index,
+                                hovered,
+                            );
+                        }
+                    }
+                    self.draw_extrapolation(&painter, rect, &keyframes);
+                    if let Some(curve) = self.read_only_curve {
+                        painter.add(Shape::line(
+                            self.sample_curve(rect, curve),
+                            Stroke::new(self.config.curve_width, self.config.curve_color),
+                        ));
+                    }
+                    if let Some(stack) = self.modifiers.filter(|stack| !stack.is_empty()) {
+                        self.draw_modified(&painter, rect, &evaluated_refs, stack);
+                    }
+                    if let Some(range) = self.work_range.filter(|_| self.config.loop_preview) {
+                        self.draw_loop_iterations(&painter, rect, &evaluated_refs, range);
+                    }
+                    if let Some(time) = self.preview_time {
+                        self.draw_preview(&painter, rect, &evaluated_refs, time);
+                    }
+                }
+                CurveLayer::Handles => {
+                    for kf in keyframes.iter().filter(|kf| self.selected.contains(&kf.id)) {
+                        self.draw_handles(&painter, rect, kf, &keyframe_refs, hovered_handle);
+                    }
+                }
+                CurveLayer::Keyframes => {
+                    for visual in &result.keyframes {
+                        KeyframeDot::new(visual.pos)
+                            .color(self.config.keyframe_color)
+                            .state(visual.state)
+                            .textures(self.config.keyframe_textures)
+                            .paint(&painter);
+                    }
+                }
+                CurveLayer::BoundingBox => {
+                    if let Some(bbox) = &bbox {
+                        bbox.paint(&painter, hovered_bbox_handle);
+                    }
+                }
             }
+        }
 
-            bbox.paint(&painter, hovered_bbox_handle);
+        // Keep the curve from the start of an edit drag for the ghost. It is
+        // drawn from the next frame on, when the live curve starts to differ.
+        if self.config.ghost_curve
+            && response.drag_started_by(egui::PointerButton::Primary)
+            && !ui.input(|i| KeyBindings::held(&i.modifiers, self.config.key_bindings.pan))
+            && (hovered_keyframe.is_some()
+                || hovered_handle.is_some()
+                || hovered_bbox_handle.is_some())
+        {
+            ui.data_mut(|data| data.insert_temp(ghost_id, evaluated.clone()));
+        } else if !response.dragged() {
+            ui.data_mut(|data| data.remove::<Vec<Keyframe<f32>>>(ghost_id));
         }
 
         // Handle interactions
@@ -301,142 +755,328 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             &response,
             &keyframe_refs,
             hovered_keyframe,
+            hovered_handle,
             hovered_bbox_handle,
+            hovered_curve,
             &selected_keyframe_data,
             &mut result,
         );
 
+        // Label and tag of the hovered keyframe.
+        if !response.dragged()
+            && let Some(text) = hovered_keyframe
+                .and_then(|id| keyframes.iter().find(|kf| kf.id == id))
+                .and_then(|kf| keyframe_tooltip(kf.label.as_deref(), kf.tag))
+        {
+            response.clone().on_hover_text_at_pointer(text);
+        }
+
+        if self.config.toolbar {
+            self.show_toolbar(ui, rect, &mut result);
+        }
+
         result
     }
 
-    /// Calculate the anchor position in screen coordinates.
-    fn calculate_anchor_screen_pos(
-        &self,
-        rect: Rect,
-        selected_data: &[(KeyframeId, TimeTick, f32)],
-    ) -> Pos2 {
-        if selected_data.is_empty() {
-            return rect.center();
-        }
+    /// Buttons in the top right corner of `rect` that set the view requests
+    /// of `result`.
+    fn show_toolbar(&self, ui: &mut Ui, rect: Rect, result: &mut CurveEditorResponse) {
+        let layout = egui::Layout::right_to_left(egui::Align::Min);
+        let builder = egui::UiBuilder::new()
+            .max_rect(rect.shrink(4.0))
+            .layout(layout);
+        ui.scope_builder(builder, |ui| {
+            egui::Frame::popup(ui.style())
+                .inner_margin(2.0)
+                .show(ui, |ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    let snapping = self.snap.as_ref().is_some_and(|snap| snap.enabled);
+                    let time_unit = if self.frames { "f" } else { "s" };
+                    result.toggle_frames |= ui
+                        .selectable_label(self.frames, time_unit)
+                        .on_hover_text("Show time in frames or seconds")
+                        .clicked();
+                    result.toggle_snap |= ui
+                        .selectable_label(snapping, "Snap")
+                        .on_hover_text("Toggle snapping")
+                        .clicked();
+                    result.toggle_normalized |= ui
+                        .selectable_label(self.normalized, "Norm")
+                        .on_hover_text("Toggle the normalized view (N)")
+                        .clicked();
+                    result.fit_selection |= ui
+                        .add_enabled(!self.selected.is_empty(), egui::Button::new("Sel"))
+                        .on_hover_text("Fit view to selection")
+                        .clicked();
+                    result.fit_view |= ui
+                        .button("Fit")
+                        .on_hover_text("Fit view to all keyframes (F)")
+                        .clicked();
+                });
+        });
+    }
 
-        match self.anchor_mode {
-            AnchorMode::Start => {
-                // First keyframe (earliest time).
-                // SAFETY: selected_data is confirmed non-empty at function start.
-                let first = selected_data
-                    .iter()
-                    .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-                    .unwrap();
-                Pos2::new(
-                    self.space.unit_to_clipped(first.1),
-                    self.value_to_y(rect, first.2),
-                )
-            }
-            AnchorMode::End => {
-                // Last keyframe (latest time).
-                // SAFETY: selected_data is confirmed non-empty at function start.
-                let last = selected_data
-                    .iter()
-                    .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
-                    .unwrap();
-                Pos2::new(
-                    self.space.unit_to_clipped(last.1),
-                    self.value_to_y(rect, last.2),
-                )
-            }
-            AnchorMode::Center => {
-                // Center of bounds.
-                // SAFETY: selected_data is confirmed non-empty at function start.
-                let min_t = selected_data
-                    .iter()
-                    .map(|d| d.1)
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-                let max_t = selected_data
-                    .iter()
-                    .map(|d| d.1)
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-                let min_v = selected_data
-                    .iter()
-                    .map(|d| d.2)
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-                let max_v = selected_data
-                    .iter()
-                    .map(|d| d.2)
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
+    /// Render a standalone SVG of `source` with the time axis of `space`:
+    /// grid, curve and keyframes over `size` points, e.g. for documentation,
+    /// bug reports or design reviews.
+    ///
+    /// The value axis is fitted to the keyframes.
+    pub fn render_svg(source: &S, space: &SpaceTransform, size: Vec2) -> String {
+        let selected = HashSet::default();
+        CurveEditor::new(
+            source,
+            &selected,
+            space,
+            display_range(source.value_range()),
+        )
+        .into_svg(size)
+    }
 
-                let center_t = min_t.lerp(max_t, 0.5);
-                let center_v = (min_v + max_v) / 2.0;
+    /// Render this editor with its configuration, view and selection as a
+    /// standalone SVG of `size` points, like [`Self::render_svg`].
+    pub fn into_svg(self, size: Vec2) -> String {
+        let mut space = *self.space;
+        space.left_padding = 0.0;
+        space.visible_width = size.x;
+        let mut editor = CurveEditor {
+            space: &space,
+            ..self
+        };
+        editor.config.height = size.y;
+        svg::render_svg(size, |ui| {
+            editor.show(ui);
+        })
+    }
 
-                Pos2::new(
-                    self.space.unit_to_clipped(center_t),
-                    self.value_to_y(rect, center_v),
-                )
-            }
-            AnchorMode::Playhead => {
-                // Playhead position with interpolated value.
-                let playhead_x = self.space.unit_to_clipped(self.current_time);
+    /// Rasterize `source` with the time axis of `space` into an image of
+    /// `size` points at `pixels_per_point`, e.g. for clip thumbnails.
+    ///
+    /// The value axis is fitted to the keyframes, like [`Self::render_svg`].
+    pub fn render_image(
+        source: &S,
+        space: &SpaceTransform,
+        size: Vec2,
+        pixels_per_point: f32,
+    ) -> ColorImage {
+        let selected = HashSet::default();
+        CurveEditor::new(
+            source,
+            &selected,
+            space,
+            display_range(source.value_range()),
+        )
+        .into_image(size, pixels_per_point)
+    }
 
-                // Find approximate value at playhead by interpolation.
-                // SAFETY: selected_data is confirmed non-empty at function start.
-                let min_v = selected_data
-                    .iter()
-                    .map(|d| d.2)
-                    .min_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-                let max_v = selected_data
-                    .iter()
-                    .map(|d| d.2)
-                    .max_by(|a, b| a.partial_cmp(b).unwrap())
-                    .unwrap();
-                let center_v = (min_v + max_v) / 2.0;
+    /// Rasterize this editor with its configuration, view and selection into
+    /// an image, like [`Self::render_image`].
+    pub fn into_image(self, size: Vec2, pixels_per_point: f32) -> ColorImage {
+        let mut space = *self.space;
+        space.left_padding = 0.0;
+        space.visible_width = size.x;
+        let mut editor = CurveEditor {
+            space: &space,
+            ..self
+        };
+        editor.config.height = size.y;
+        offscreen::render_image(size, pixels_per_point, |ui| {
+            editor.show(ui);
+        })
+    }
 
-                Pos2::new(playhead_x, self.value_to_y(rect, center_v))
-            }
+    /// Rect the editor with `id` allocated last frame when
+    /// [`CurveEditorConfig::stable_rect`] is set.
+    ///
+    /// Use it to compute the [`SpaceTransform`] before showing the editor.
+    pub fn committed_rect(ctx: &egui::Context, id: egui::Id) -> Option<Rect> {
+        ctx.data(|data| data.get_temp(committed_rect_id(id)))
+    }
+
+    /// Hit-test a screen position against the layout the editor renders into
+    /// `rect` (the rect of [`CurveEditorResponse::response`]).
+    ///
+    /// Handles take precedence over keyframes, which take precedence over the
+    /// selection bounding box and then the curve.
+    pub fn hit_test(&self, rect: Rect, pos: Pos2) -> Option<CurveEditorHit> {
+        if !rect.contains(pos) {
+            return None;
+        }
+
+        let keyframes = self.source.keyframes_sorted();
+        let keyframe_refs: Vec<&KeyframeView> = keyframes.iter().collect();
+
+        if let Some((keyframe_id, side)) = self.hit_handle(rect, &keyframe_refs, pos) {
+            return Some(CurveEditorHit::Handle { keyframe_id, side });
+        }
+        if let Some(kf) = keyframes.iter().find(|kf| {
+            hits_keyframe(
+                self.keyframe_to_screen(rect, kf),
+                pos,
+                self.config.interaction.keyframe_hit,
+            )
+        }) {
+            return Some(CurveEditorHit::Keyframe { keyframe_id: kf.id });
+        }
+        if let Some(handle) = self
+            .selection_bounding_box(rect, &keyframes)
+            .and_then(|bbox| bbox.hit_test(pos))
+        {
+            return Some(CurveEditorHit::BoundingBox(handle));
         }
+        if let Some(point) = self
+            .distance_to_curve(rect, pos)
+            .filter(|point| point.distance <= self.config.interaction.curve_hit)
+        {
+            return Some(CurveEditorHit::Curve(point));
+        }
+
+        Some(CurveEditorHit::Background {
+            time: self.space.clipped_to_unit(pos.x),
+            value: self.y_to_value(rect, pos.y),
+        })
     }
 
-    fn draw_grid(&self, painter: &egui::Painter, rect: Rect) {
-        // Horizontal grid lines for values
-        let (min_val, max_val) = self.value_range;
-        let value_range = max_val - min_val;
-
-        // Determine nice value intervals
-        let target_lines = 5;
-        let ideal_interval = value_range / target_lines as f32;
-        let nice_intervals = [0.1, 0.2, 0.5, 1.0, 2.0, 5.0, 10.0, 20.0, 50.0, 100.0];
-
-        let mut interval = 1.0;
-        for &ni in &nice_intervals {
-            if ni >= ideal_interval {
-                interval = ni;
-                break;
+    /// The point of the visible curve segments closest to `pos`, with `rect`
+    /// as for [`Self::hit_test`].
+    ///
+    /// Segments are measured as the polylines they are drawn with, so the
+    /// distance matches what the user sees, including hold steps. Returns
+    /// `None` if no segment is visible.
+    pub fn distance_to_curve(&self, rect: Rect, pos: Pos2) -> Option<CurvePoint> {
+        let keyframes: Vec<Keyframe<f32>> = self
+            .source
+            .keyframes_sorted()
+            .iter()
+            .map(KeyframeView::to_keyframe)
+            .collect();
+        let refs: Vec<&Keyframe<f32>> = keyframes.iter().collect();
+        self.nearest_curve_point(rect, &refs, pos)
+    }
+
+    fn nearest_curve_point(
+        &self,
+        rect: Rect,
+        keyframes: &[&Keyframe<f32>],
+        pos: Pos2,
+    ) -> Option<CurvePoint> {
+        let to_screen = |time: TimeTick, value: f32| {
+            Pos2::new(
+                self.space.unit_to_clipped(time),
+                self.value_to_y(rect, value),
+            )
+        };
+        let mut nearest: Option<(f32, Pos2, usize)> = None;
+        for index in 0..keyframes.len().saturating_sub(1) {
+            let (left, right) = (keyframes[index], keyframes[index + 1]);
+            let (left_x, right_x) = (
+                self.space.unit_to_clipped(left.position),
+                self.space.unit_to_clipped(right.position),
+            );
+            if !left.connected_right || right_x < rect.left() || left_x > rect.right() {
+                continue;
+            }
+            let points = segment_polyline(keyframes, index, to_screen, self.config.curve_segments);
+            let (distance, point) = closest_on_polyline(&points, pos);
+            if nearest.is_none_or(|(nearest, _, _)| distance < nearest) {
+                nearest = Some((distance, point, index));
             }
         }
 
-        let first_line = (min_val / interval).ceil() * interval;
-        let mut v = first_line;
-        while v <= max_val {
-            let y = self.value_to_y(rect, v);
-            painter.line_segment(
-                [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
-                Stroke::new(1.0, self.config.grid_color),
-            );
+        let (distance, point, index) = nearest?;
+        let time = self.space.clipped_to_unit(point.x);
+        let (pre, post) = self.source.extrapolation();
+        let value = interpolate_with_extrapolation(keyframes, time, pre, post)
+            .map_or_else(|| self.y_to_value(rect, point.y), |triple| triple.lerp());
+        Some(CurvePoint {
+            segment: (keyframes[index].id, keyframes[index + 1].id),
+            time,
+            value,
+            distance,
+        })
+    }
 
-            // Value label
-            painter.text(
-                Pos2::new(rect.left() + 4.0, y - 2.0),
-                egui::Align2::LEFT_BOTTOM,
-                format!("{:.1}", v),
-                egui::FontId::proportional(9.0),
-                Color32::from_gray(100),
-            );
+    /// Handle of a selected keyframe within grab distance of `pos`.
+    fn hit_handle(
+        &self,
+        rect: Rect,
+        keyframes: &[&KeyframeView],
+        pos: Pos2,
+    ) -> Option<(KeyframeId, HandleSide)> {
+        keyframes
+            .iter()
+            .filter(|kf| self.selected.contains(&kf.id) && !kf.locked)
+            .flat_map(|kf| [(kf, HandleSide::Left), (kf, HandleSide::Right)])
+            .find(|(kf, side)| {
+                self.handle_geometry(rect, kf, keyframes, *side)
+                    .is_some_and(|g| g.position.distance(pos) < self.config.interaction.handle_hit)
+            })
+            .map(|(kf, side)| (kf.id, side))
+    }
+
+    /// Bounding box around the selected keyframes, if more than one is
+    /// selected.
+    fn selection_bounding_box(
+        &self,
+        rect: Rect,
+        keyframes: &[KeyframeView],
+    ) -> Option<BoundingBox> {
+        let world =
+            self.selection_world_box(keyframes.iter().map(|kf| (kf.id, kf.position, kf.value)))?;
+
+        let bbox_config = crate::widgets::bounding_box::BoundingBoxConfig {
+            border_color: self.config.bounding_box_color,
+            handle_color: Color32::WHITE,
+            anchor_color: self.config.anchor_color,
+            handle_size: self.config.bbox_handle_size,
+            hit_size: self.config.interaction.bbox_handle_hit,
+            border_width: 1.0,
+        };
+
+        Some(
+            world
+                .to_screen(|time, value| {
+                    Pos2::new(
+                        self.space.unit_to_clipped(time),
+                        self.value_to_y(rect, value),
+                    )
+                })
+                .config(bbox_config),
+        )
+    }
 
-            v += interval;
+    /// Time/value bounds of the selected keyframes among `keyframes`,
+    /// anchored per the anchor mode, if at least two are selected.
+    fn selection_world_box(
+        &self,
+        keyframes: impl IntoIterator<Item = (KeyframeId, TimeTick, f32)>,
+    ) -> Option<WorldBoundingBox> {
+        let selected: Vec<(TimeTick, f32)> = keyframes
+            .into_iter()
+            .filter(|(id, ..)| self.selected.contains(id))
+            .map(|(_, time, value)| (time, value))
+            .collect();
+        if selected.len() < 2 {
+            return None;
         }
+        WorldBoundingBox::from_points(selected)
+            .map(|bbox| bbox.anchor_mode(self.anchor_mode, self.current_time))
+    }
+
+    fn draw_grid(&self, painter: &egui::Painter, rect: Rect) {
+        let (labeled_range, unit) = if self.normalized {
+            ((0.0, 1.0), ValueUnit::Generic)
+        } else {
+            (self.value_range, self.unit)
+        };
+        draw_value_grid(
+            painter,
+            rect,
+            labeled_range,
+            unit,
+            self.config.vertical_padding,
+            self.config.grid_color,
+            self.config.value_labels,
+        );
 
         // Vertical grid lines for time
         crate::widgets::time_ruler::draw_time_grid(
@@ -448,137 +1088,420 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
         );
     }
 
+    /// Draw the segment from `keyframes[index]` to the next keyframe.
     fn draw_curve_segment(
         &self,
         painter: &egui::Painter,
         rect: Rect,
-        left: &KeyframeView,
-        right: &KeyframeView,
+        keyframes: &[&Keyframe<f32>],
+        index: usize,
+        hovered: bool,
     ) {
-        let left_pos = self.keyframe_to_screen(rect, left);
-        let right_pos = self.keyframe_to_screen(rect, right);
-
-        match left.keyframe_type {
-            KeyframeType::Hold => {
-                // Step function: horizontal then vertical
-                let mid = Pos2::new(right_pos.x, left_pos.y);
-                painter.line_segment(
-                    [left_pos, mid],
-                    Stroke::new(self.config.curve_width, self.config.curve_color),
-                );
-                painter.line_segment(
-                    [mid, right_pos],
-                    Stroke::new(
-                        self.config.curve_width,
-                        self.config.curve_color.linear_multiply(0.5),
-                    ),
-                );
-            }
-            KeyframeType::Linear => {
-                // Straight line
-                painter.line_segment(
-                    [left_pos, right_pos],
-                    Stroke::new(self.config.curve_width, self.config.curve_color),
-                );
-            }
-            KeyframeType::Bezier => {
-                // Bezier curve - use egui's built-in cubic bezier
-                let dx = right_pos.x - left_pos.x;
-                let dy = right_pos.y - left_pos.y;
+        let width = if hovered {
+            self.config.curve_width + 1.5
+        } else {
+            self.config.curve_width
+        };
+        paint_curve_segment(
+            painter,
+            keyframes,
+            index,
+            |time, value| {
+                Pos2::new(
+                    self.space.unit_to_clipped(time),
+                    self.value_to_y(rect, value),
+                )
+            },
+            Stroke::new(width, self.config.curve_color),
+        );
+    }
 
-                let cp1 = Pos2::new(
-                    left_pos.x + dx * left.handles.right_x,
-                    left_pos.y + dy * left.handles.right_y,
-                );
-                let cp2 = Pos2::new(
-                    left_pos.x + dx * right.handles.left_x,
-                    left_pos.y + dy * right.handles.left_y,
-                );
+    /// Draw the curve through `keyframes` dimmed, as the ghost of an edit
+    /// drag.
+    /// Mark the curve's value at `time`, see [`Self::preview_time`].
+    fn draw_preview(
+        &self,
+        painter: &egui::Painter,
+        rect: Rect,
+        keyframes: &[&Keyframe<f32>],
+        time: TimeTick,
+    ) {
+        let x = self.space.unit_to_clipped(time);
+        let (pre, post) = self.source.extrapolation();
+        if rect.x_range().contains(x)
+            && let Some(triple) = interpolate_with_extrapolation(keyframes, time, pre, post)
+        {
+            let value = triple.lerp();
+            paint_preview_line(painter, rect, x);
+            paint_value_preview(
+                painter,
+                Pos2::new(x, self.value_to_y(rect, value)),
+                value,
+                self.config.curve_color,
+            );
+        }
+    }
 
-                painter.add(Shape::CubicBezier(egui::epaint::CubicBezierShape {
-                    points: [left_pos, cp1, cp2, right_pos],
-                    closed: false,
-                    fill: Color32::TRANSPARENT,
-                    stroke: Stroke::new(self.config.curve_width, self.config.curve_color).into(),
-                }));
+    fn draw_ghost(&self, painter: &egui::Painter, rect: Rect, keyframes: &[Keyframe<f32>]) {
+        let refs: Vec<&Keyframe<f32>> = keyframes.iter().collect();
+        let stroke = Stroke::new(
+            self.config.curve_width,
+            self.config.curve_color.linear_multiply(0.25),
+        );
+        for (index, window) in keyframes.windows(2).enumerate() {
+            if window[0].connected_right {
+                paint_curve_segment(
+                    painter,
+                    &refs,
+                    index,
+                    |time, value| {
+                        Pos2::new(
+                            self.space.unit_to_clipped(time),
+                            self.value_to_y(rect, value),
+                        )
+                    },
+                    stroke,
+                );
             }
         }
     }
 
-    fn draw_handles(
+    /// Draw the curve through `keyframes` with the modifiers of `stack`
+    /// applied.
+    fn draw_modified(
         &self,
         painter: &egui::Painter,
         rect: Rect,
-        kf: &KeyframeView,
-        all_keyframes: &[&KeyframeView],
+        keyframes: &[&Keyframe<f32>],
+        stack: &ModifierStack,
     ) {
-        let kf_pos = self.keyframe_to_screen(rect, kf);
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return;
+        };
+        let (pre, post) = self.source.extrapolation();
+        let base = |time: TimeTick| {
+            interpolate_with_extrapolation(keyframes, time, pre, post).map(|triple| triple.lerp())
+        };
+        let range = Some((first.position, last.position));
+        painter.add(Shape::line(
+            self.sample_curve(rect, &|time| stack.evaluate_with(range, &base, time)),
+            Stroke::new(self.config.curve_width, self.config.modifier_color),
+        ));
+    }
 
-        // Find adjacent keyframes
-        let mut prev_kf: Option<&KeyframeView> = None;
-        let mut next_kf: Option<&KeyframeView> = None;
+    /// Screen points of `curve` every few pixels across `rect`.
+    fn sample_curve(&self, rect: Rect, curve: &dyn Fn(TimeTick) -> Option<f32>) -> Vec<Pos2> {
+        let steps = (rect.width() / 3.0).ceil().max(1.0) as usize;
+        (0..=steps)
+            .filter_map(|i| {
+                let x = rect.left() + rect.width() * i as f32 / steps as f32;
+                curve(self.space.clipped_to_unit(x))
+                    .map(|value| Pos2::new(x, self.value_to_y(rect, value)))
+            })
+            .collect()
+    }
 
-        for (i, other) in all_keyframes.iter().enumerate() {
-            if other.id == kf.id {
-                if i > 0 {
-                    prev_kf = Some(all_keyframes[i - 1]);
-                }
-                if i + 1 < all_keyframes.len() {
-                    next_kf = Some(all_keyframes[i + 1]);
-                }
+    /// Draw the dashed diagonal where value equals time.
+    fn draw_identity(&self, painter: &egui::Painter, rect: Rect) {
+        let point = |x: f32| {
+            let time = self.space.clipped_to_unit(x).value() as f32;
+            Pos2::new(x, self.value_to_y(rect, time))
+        };
+        painter.add(Shape::dashed_line(
+            &[point(rect.left()), point(rect.right())],
+            Stroke::new(1.0, self.config.grid_color.gamma_multiply(2.0)),
+            6.0,
+            4.0,
+        ));
+    }
+
+    /// Draw the curve inside the `(start, end)` loop range faintly in the
+    /// loop iterations after it, with a dashed line at every seam.
+    fn draw_loop_iterations(
+        &self,
+        painter: &egui::Painter,
+        rect: Rect,
+        keyframes: &[&Keyframe<f32>],
+        (start, end): (TimeTick, TimeTick),
+    ) {
+        let length = end.value() - start.value();
+        let end_x = self.space.unit_to_clipped(end).max(rect.left());
+        if length <= 0.0 || end_x >= rect.right() || keyframes.is_empty() {
+            return;
+        }
+
+        let seam_stroke = Stroke::new(1.0, self.config.curve_color.linear_multiply(0.3));
+        let first_seam = ((self.space.clipped_to_unit(end_x).value() - end.value()) / length)
+            .floor()
+            .max(0.0);
+        for iteration in first_seam as usize.. {
+            let x = self
+                .space
+                .unit_to_clipped(TimeTick::new(end.value() + iteration as f64 * length));
+            if x > rect.right() {
                 break;
             }
+            painter.add(Shape::dashed_line(
+                &[Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+                seam_stroke,
+                4.0,
+                4.0,
+            ));
         }
 
-        // Draw left handle (if there's a previous keyframe)
-        if let Some(prev) = prev_kf
-            && prev.connected_right
-        {
-            let prev_pos = self.keyframe_to_screen(rect, prev);
-            let dx = kf_pos.x - prev_pos.x;
-            let dy = kf_pos.y - prev_pos.y;
+        let (pre, post) = self.source.extrapolation();
+        let steps = ((rect.right() - end_x) / 3.0).ceil() as usize;
+        let points: Vec<Pos2> = (0..=steps)
+            .filter_map(|i| {
+                let x = end_x + (rect.right() - end_x) * i as f32 / steps as f32;
+                let looped = start.value()
+                    + (self.space.clipped_to_unit(x).value() - start.value()).rem_euclid(length);
+                interpolate_with_extrapolation(keyframes, TimeTick::new(looped), pre, post)
+                    .map(|triple| Pos2::new(x, self.value_to_y(rect, triple.lerp())))
+            })
+            .collect();
+        painter.add(Shape::line(
+            points,
+            Stroke::new(
+                self.config.curve_width,
+                self.config.curve_color.linear_multiply(0.25),
+            ),
+        ));
+    }
 
-            let handle_pos = Pos2::new(
-                prev_pos.x + dx * kf.handles.left_x,
-                prev_pos.y + dy * kf.handles.left_y,
-            );
+    /// Draw the extrapolated regions before the first and after the last
+    /// keyframe as a dimmed curve.
+    fn draw_extrapolation(&self, painter: &egui::Painter, rect: Rect, keyframes: &[KeyframeView]) {
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return;
+        };
 
-            // Handle line
-            painter.line_segment(
-                [kf_pos, handle_pos],
-                Stroke::new(1.0, self.config.handle_line_color),
-            );
+        let (pre, post) = self.source.extrapolation();
+        let owned: Vec<Keyframe<f32>> = keyframes.iter().map(KeyframeView::to_keyframe).collect();
+        let refs: Vec<&Keyframe<f32>> = owned.iter().collect();
+        let stroke = Stroke::new(
+            self.config.curve_width,
+            self.config.curve_color.linear_multiply(0.4),
+        );
 
-            // Handle circle
-            painter.circle_filled(handle_pos, 4.0, self.config.handle_color);
-            painter.circle_stroke(handle_pos, 4.0, Stroke::new(1.0, Color32::WHITE));
+        let first_x = self.space.unit_to_clipped(first.position).max(rect.left());
+        let last_x = self.space.unit_to_clipped(last.position).min(rect.right());
+
+        for (from_x, to_x) in [(rect.left(), first_x), (last_x, rect.right())] {
+            if to_x <= from_x {
+                continue;
+            }
+
+            // Constant regions are flat, so two points suffice.
+            let is_constant = if to_x == first_x {
+                pre == Extrapolation::Constant
+            } else {
+                post == Extrapolation::Constant
+            };
+            let steps = if is_constant {
+                1
+            } else {
+                ((to_x - from_x) / 3.0).ceil() as usize
+            };
+
+            let points: Vec<Pos2> = (0..=steps)
+                .filter_map(|i| {
+                    let x = from_x + (to_x - from_x) * i as f32 / steps as f32;
+                    let time = self.space.clipped_to_unit(x);
+                    interpolate_with_extrapolation(&refs, time, pre, post)
+                        .map(|triple| Pos2::new(x, self.value_to_y(rect, triple.lerp())))
+                })
+                .collect();
+
+            painter.add(Shape::line(points, stroke));
         }
+    }
 
-        // Draw right handle (if connected to next keyframe)
-        if let Some(next) = next_kf
-            && kf.connected_right
-        {
-            let next_pos = self.keyframe_to_screen(rect, next);
-            let dx = next_pos.x - kf_pos.x;
-            let dy = next_pos.y - kf_pos.y;
+    /// Screen-space geometry of a keyframe's handle on `side`.
+    ///
+    /// Returns `None` if the handle's segment does not exist or is not
+    /// connected, or if the keyframe's handles are computed
+    /// ([`KeyframeType::Auto`]).
+    fn handle_geometry(
+        &self,
+        rect: Rect,
+        kf: &KeyframeView,
+        all_keyframes: &[&KeyframeView],
+        side: HandleSide,
+    ) -> Option<HandleGeometry> {
+        if kf.keyframe_type == KeyframeType::Auto {
+            return None;
+        }
+        let index = all_keyframes.iter().position(|other| other.id == kf.id)?;
+        let kf_pos = self.keyframe_to_screen(rect, kf);
 
-            let handle_pos = Pos2::new(
-                kf_pos.x + dx * kf.handles.right_x,
-                kf_pos.y + dy * kf.handles.right_y,
-            );
+        match side {
+            HandleSide::Left => {
+                let prev = all_keyframes.get(index.checked_sub(1)?)?;
+                if !prev.connected_right {
+                    return None;
+                }
+                let origin = self.keyframe_to_screen(rect, prev);
+                let span = kf_pos - origin;
+                let position = match kf.weighted {
+                    Some(weighted) => {
+                        self.offset_to_screen(rect, kf, weighted.left_time, weighted.left_value)
+                    }
+                    None => origin + span * Vec2::new(kf.handles.left_x, kf.handles.left_y),
+                };
+                Some(HandleGeometry {
+                    position,
+                    origin,
+                    span,
+                })
+            }
+            HandleSide::Right => {
+                let next = all_keyframes.get(index + 1)?;
+                if !kf.connected_right {
+                    return None;
+                }
+                let span = self.keyframe_to_screen(rect, next) - kf_pos;
+                let position = match kf.weighted {
+                    Some(weighted) => {
+                        self.offset_to_screen(rect, kf, weighted.right_time, weighted.right_value)
+                    }
+                    None => kf_pos + span * Vec2::new(kf.handles.right_x, kf.handles.right_y),
+                };
+                Some(HandleGeometry {
+                    position,
+                    origin: kf_pos,
+                    span,
+                })
+            }
+        }
+    }
+
+    fn draw_handles(
+        &self,
+        painter: &egui::Painter,
+        rect: Rect,
+        kf: &KeyframeView,
+        all_keyframes: &[&KeyframeView],
+        hovered_handle: Option<(KeyframeId, HandleSide)>,
+    ) {
+        let kf_pos = self.keyframe_to_screen(rect, kf);
+
+        for side in [HandleSide::Left, HandleSide::Right] {
+            let Some(geometry) = self.handle_geometry(rect, kf, all_keyframes, side) else {
+                continue;
+            };
 
             // Handle line
             painter.line_segment(
-                [kf_pos, handle_pos],
+                [kf_pos, geometry.position],
                 Stroke::new(1.0, self.config.handle_line_color),
             );
 
             // Handle circle
-            painter.circle_filled(handle_pos, 4.0, self.config.handle_color);
-            painter.circle_stroke(handle_pos, 4.0, Stroke::new(1.0, Color32::WHITE));
+            let radius = if hovered_handle == Some((kf.id, side)) {
+                HANDLE_RADIUS + 1.5
+            } else {
+                HANDLE_RADIUS
+            };
+            painter.circle_filled(geometry.position, radius, self.config.handle_color);
+            painter.circle_stroke(geometry.position, radius, Stroke::new(1.0, Color32::WHITE));
         }
     }
 
+    /// Compute a keyframe's handles after dragging its `side` handle to
+    /// `pointer`.
+    ///
+    /// Unless `break_tangents` is set, the opposite handle is rotated to stay
+    /// collinear, keeping its screen length. Returns the dragged handle's new
+    /// normalized `(x, y)` and the full new handles.
+    fn dragged_handles(
+        &self,
+        rect: Rect,
+        keyframes: &[&KeyframeView],
+        keyframe_id: KeyframeId,
+        side: HandleSide,
+        pointer: Pos2,
+        break_tangents: bool,
+    ) -> Option<((f32, f32), BezierHandles)> {
+        let kf = keyframes.iter().find(|kf| kf.id == keyframe_id)?;
+        let geometry = self.handle_geometry(rect, kf, keyframes, side)?;
+        let mut handles = kf.handles;
+
+        let (x, y) = geometry.normalize(pointer, handle_xy(&handles, side));
+        set_handle_xy(&mut handles, side, (x, y));
+
+        if !break_tangents
+            && let Some(opposite) = self.handle_geometry(rect, kf, keyframes, side.opposite())
+        {
+            let kf_pos = self.keyframe_to_screen(rect, kf);
+            let direction = (geometry.origin + geometry.span * Vec2::new(x, y)) - kf_pos;
+            if direction.length() > f32::EPSILON {
+                // Shorten the handle if it would otherwise leave its segment
+                // horizontally, which clamping would bend off the tangent.
+                let direction = direction.normalized();
+                let mut length = (opposite.position - kf_pos).length();
+                if direction.x.abs() > f32::EPSILON {
+                    length = length.min(opposite.span.x.abs() / direction.x.abs());
+                }
+                let target = kf_pos - direction * length;
+                let xy = opposite.normalize(target, handle_xy(&handles, side.opposite()));
+                set_handle_xy(&mut handles, side.opposite(), xy);
+            }
+        }
+
+        Some(((x, y), handles))
+    }
+
+    /// Compute a keyframe's weighted handles after dragging its `side`
+    /// handle to `pointer`.
+    ///
+    /// Unlike [`dragged_handles`](Self::dragged_handles) the handle is not
+    /// confined to its segment. Unless `break_tangents` is set, the opposite
+    /// handle is rotated to stay collinear, keeping its screen length.
+    fn dragged_weighted_handles(
+        &self,
+        rect: Rect,
+        keyframes: &[&KeyframeView],
+        keyframe_id: KeyframeId,
+        side: HandleSide,
+        pointer: Pos2,
+        break_tangents: bool,
+    ) -> Option<WeightedHandles> {
+        let kf = keyframes.iter().find(|kf| kf.id == keyframe_id)?;
+        let mut weighted = kf.weighted?;
+        let kf_pos = self.keyframe_to_screen(rect, kf);
+
+        let screen_to_offset = |pos: Pos2| {
+            (
+                (self.space.clipped_to_unit(pos.x) - kf.position).value(),
+                (self.y_to_value(rect, pos.y) - kf.value) as f64,
+            )
+        };
+        set_weighted_offset(&mut weighted, side, screen_to_offset(pointer));
+
+        if !break_tangents
+            && let Some(opposite) = self.handle_geometry(rect, kf, keyframes, side.opposite())
+        {
+            let direction = pointer - kf_pos;
+            if direction.length() > f32::EPSILON {
+                let length = (opposite.position - kf_pos).length();
+                let target = kf_pos - direction.normalized() * length;
+                set_weighted_offset(&mut weighted, side.opposite(), screen_to_offset(target));
+            }
+        }
+
+        Some(weighted)
+    }
+
+    /// Screen position of a point offset from a keyframe in time and value.
+    fn offset_to_screen(&self, rect: Rect, kf: &KeyframeView, time: f64, value: f64) -> Pos2 {
+        Pos2::new(
+            self.space
+                .unit_to_clipped(kf.position + TimeTick::new(time)),
+            self.value_to_y(rect, kf.value + value as f32),
+        )
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn handle_interactions(
         &self,
@@ -588,39 +1511,78 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
         response: &Response,
         keyframes: &[&KeyframeView],
         hovered_keyframe: Option<KeyframeId>,
+        hovered_handle: Option<(KeyframeId, HandleSide)>,
         hovered_bbox_handle: Option<BoundingBoxHandle>,
+        hovered_curve: Option<CurvePoint>,
         selected_keyframe_data: &[(KeyframeId, TimeTick, f32)],
         result: &mut CurveEditorResponse,
     ) {
+        let bindings = self.config.key_bindings;
+
         // Keyboard shortcuts
         if response.has_focus() || response.hovered() {
-            // Cmd+A to select all
-            if ui.input(|i| i.modifiers.command && i.key_pressed(egui::Key::A)) {
+            let pressed = |shortcut| ui.input(|i| KeyBindings::pressed(i, shortcut));
+
+            if pressed(bindings.select_all) {
                 result.select_all = true;
             }
 
-            // Escape to deselect all
-            if ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+            if pressed(bindings.deselect_all) {
                 result.deselect_all = true;
             }
 
-            // Delete key
-            if ui.input(|i| i.key_pressed(egui::Key::Delete))
-                && let Some(kf_id) = self.selected.iter().next().copied()
-            {
+            // Deleting while preserving keeps the curve shape. Keyframes go
+            // in time order, not the selection's hash order. Locked
+            // keyframes are kept.
+            let (delete, smooth) = (
+                pressed(bindings.delete),
+                pressed(bindings.delete_preserving),
+            );
+            let mut selected_in_order = keyframes
+                .iter()
+                .filter(|kf| self.selected.contains(&kf.id) && !kf.locked)
+                .map(|kf| kf.id);
+            if smooth {
+                result.commands.extend(
+                    selected_in_order.map(|keyframe_id| {
+                        AnimationCommand::RemoveKeyframePreserving { keyframe_id }
+                    }),
+                );
+            } else if delete && let Some(kf_id) = selected_in_order.next() {
                 result.delete_keyframe = Some(kf_id);
             }
 
-            // F key to fit view to all keyframes
-            if ui.input(|i| i.key_pressed(egui::Key::F)) {
+            if pressed(bindings.fit_view) {
                 result.fit_view = true;
             }
+
+            if pressed(bindings.toggle_normalized) {
+                result.toggle_normalized = true;
+            }
+
+            if pressed(bindings.insert_key_at_playhead) {
+                result.insert_keyframe_at = Some(self.current_time);
+            }
         }
 
         // Handle zoom and pan (matching timeline behavior)
         if response.hovered() {
-            // Ctrl+scroll or pinch gesture for zoom
-            let zoom_delta = ui.input(|i| i.zoom_delta_2d());
+            // Pinch gestures and scrolling with the zoom modifier held zoom.
+            // egui already turns Cmd/Ctrl+scroll into a zoom delta.
+            let (mut zoom_delta, mut scroll_delta, scroll_zooms) = ui.input(|i| {
+                (
+                    i.zoom_delta_2d(),
+                    i.smooth_scroll_delta,
+                    KeyBindings::held(&i.modifiers, bindings.zoom),
+                )
+            });
+            if bindings.zoom.is_none() {
+                zoom_delta = Vec2::splat(1.0);
+            } else if scroll_zooms && scroll_delta != Vec2::ZERO {
+                // egui's rate for Cmd/Ctrl+scroll.
+                zoom_delta = Vec2::splat(((scroll_delta.x + scroll_delta.y) / 200.0).exp());
+                scroll_delta = Vec2::ZERO;
+            }
             if zoom_delta.x != 1.0
                 && let Some(pos) = response.hover_pos()
             {
@@ -632,7 +1594,6 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             }
 
             // Smooth scroll for panning
-            let scroll_delta = ui.input(|i| i.smooth_scroll_delta);
             if scroll_delta != Vec2::ZERO {
                 result.pan_delta = Some(scroll_delta);
             }
@@ -661,11 +1622,12 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             }
         }
 
-        // Middle-mouse drag or Alt+LMB drag for panning.
+        // Middle-mouse drag or LMB drag with the pan modifier for panning.
         let is_middle_drag = ui.input(|i| i.pointer.middle_down());
-        let is_alt_drag = ui.input(|i| i.modifiers.alt) && response.dragged();
+        let is_pan_drag =
+            ui.input(|i| KeyBindings::held(&i.modifiers, bindings.pan)) && response.dragged();
 
-        if (is_middle_drag || is_alt_drag) && response.hovered() {
+        if (is_middle_drag || is_pan_drag) && response.hovered() {
             let drag_delta = ui.input(|i| i.pointer.delta());
             if drag_delta != Vec2::ZERO {
                 result.pan_delta = Some(drag_delta);
@@ -687,10 +1649,11 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             ui.memory(|mem| mem.data.get_temp(id.with("context_kf")));
         if let Some(kf_id) = context_kf {
             // Find the keyframe to get its current type
-            let current_type = keyframes
-                .iter()
-                .find(|kf| kf.id == kf_id)
-                .map(|kf| kf.keyframe_type);
+            let context_index = keyframes.iter().position(|kf| kf.id == kf_id);
+            let context_keyframe = context_index.map(|index| keyframes[index]);
+            let current_type = context_keyframe.map(|kf| kf.keyframe_type);
+            // Segment leaving the keyframe, if it has one.
+            let easing_segment = context_index.filter(|index| index + 1 < keyframes.len());
 
             let mut close_menu = false;
             egui::Area::new(id.with("interp_menu"))
@@ -706,6 +1669,8 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
                             (KeyframeType::Hold, "Hold (Step)"),
                             (KeyframeType::Linear, "Linear"),
                             (KeyframeType::Bezier, "Bezier"),
+                            (KeyframeType::Auto, "Auto"),
+                            (KeyframeType::Spring, "Spring"),
                         ];
 
                         for (kf_type, label) in types {
@@ -724,6 +1689,152 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
                             }
                         }
 
+                        if let Some(kf) =
+                            context_keyframe.filter(|kf| kf.keyframe_type == KeyframeType::Spring)
+                        {
+                            ui.separator();
+                            let mut spring = kf.spring;
+                            let changed = egui::Grid::new(id.with("spring"))
+                                .show(ui, |ui| {
+                                    ui.label("Frequency");
+                                    let frequency = ui.add(
+                                        egui::DragValue::new(&mut spring.frequency)
+                                            .speed(0.05)
+                                            .range(0.05..=50.0),
+                                    );
+                                    ui.end_row();
+                                    ui.label("Damping");
+                                    let damping = ui.add(
+                                        egui::DragValue::new(&mut spring.damping)
+                                            .speed(0.01)
+                                            .range(0.0..=5.0),
+                                    );
+                                    ui.end_row();
+                                    ui.label("Response");
+                                    let response = ui.add(
+                                        egui::DragValue::new(&mut spring.response)
+                                            .speed(0.01)
+                                            .range(-5.0..=5.0),
+                                    );
+                                    ui.end_row();
+                                    frequency.changed() || damping.changed() || response.changed()
+                                })
+                                .inner;
+                            if changed {
+                                result.commands.push(AnimationCommand::SetKeyframeSpring {
+                                    keyframe_id: kf_id,
+                                    spring,
+                                });
+                            }
+                        }
+
+                        // Flip the selection if the keyframe is part of it.
+                        let in_selection = self.selected.contains(&kf_id);
+                        let flipped: Vec<&KeyframeView> = keyframes
+                            .iter()
+                            .copied()
+                            .filter(|kf| {
+                                (kf.id == kf_id || (in_selection && self.selected.contains(&kf.id)))
+                                    && !kf.locked
+                            })
+                            .collect();
+                        if let Some(bbox) = WorldBoundingBox::from_points(
+                            flipped.iter().map(|kf| (kf.position, kf.value)),
+                        ) {
+                            let (anchor_time, anchor_value) =
+                                bbox.anchor_mode(self.anchor_mode, self.current_time).anchor;
+                            let keyframe_ids: Vec<KeyframeId> =
+                                flipped.iter().map(|kf| kf.id).collect();
+                            ui.separator();
+                            if ui.selectable_label(false, "   Flip time").clicked() {
+                                result.commands.push(AnimationCommand::FlipKeyframesTime {
+                                    keyframe_ids: keyframe_ids.clone(),
+                                    anchor_time,
+                                });
+                                close_menu = true;
+                            }
+                            if ui.selectable_label(false, "   Flip value").clicked() {
+                                result.commands.push(AnimationCommand::FlipKeyframesValue {
+                                    keyframe_ids: keyframe_ids.clone(),
+                                    anchor_value: anchor_value as f64,
+                                });
+                                close_menu = true;
+                            }
+                            if keyframe_ids.len() > 1
+                                && ui.selectable_label(false, "   Reverse").clicked()
+                            {
+                                result
+                                    .commands
+                                    .push(AnimationCommand::ReverseKeyframes { keyframe_ids });
+                                close_menu = true;
+                            }
+                        }
+
+                        // Lock or unlock the selection if the keyframe is
+                        // part of it. Keyframes of a locked track only show
+                        // the track's lock.
+                        if let Some(kf) = context_keyframe {
+                            ui.separator();
+                            let track_locked = self.source.is_locked();
+                            let text = if kf.locked { "✓ Locked" } else { "   Locked" };
+                            let response = ui
+                                .add_enabled(
+                                    !track_locked,
+                                    egui::Button::selectable(kf.locked, text),
+                                )
+                                .on_disabled_hover_text("The track is locked");
+                            if response.clicked() {
+                                let keyframe_ids = keyframes
+                                    .iter()
+                                    .map(|other| other.id)
+                                    .filter(|id| {
+                                        *id == kf_id || (in_selection && self.selected.contains(id))
+                                    })
+                                    .collect();
+                                result.commands.push(AnimationCommand::SetKeyframesLocked {
+                                    keyframe_ids,
+                                    locked: !kf.locked,
+                                });
+                                close_menu = true;
+                            }
+                        }
+
+                        if let Some(index) = easing_segment {
+                            ui.separator();
+                            ui.label("Easing");
+                            let owned: Vec<Keyframe<f32>> =
+                                keyframes.iter().map(|kf| kf.to_keyframe()).collect();
+                            let refs: Vec<&Keyframe<f32>> = owned.iter().collect();
+                            let current = segment_easing(&refs, index);
+                            let current = match self.easing_library {
+                                Some(library) => library.match_preset(&current, 0.01),
+                                None => match_preset(&current, 0.01).map(Easing::Preset),
+                            };
+                            let custom = self.easing_library.into_iter().flat_map(|library| {
+                                library
+                                    .iter()
+                                    .map(|(name, handles)| (Easing::Custom(name.into()), handles))
+                            });
+                            let easings = EasingPreset::common()
+                                .iter()
+                                .map(|preset| (Easing::Preset(*preset), preset.handles()))
+                                .chain(custom);
+                            for (easing, handles) in easings {
+                                let is_current = current.as_ref() == Some(&easing);
+                                let text = if is_current {
+                                    format!("✓ {}", easing.name())
+                                } else {
+                                    format!("   {}", easing.name())
+                                };
+                                if ui.selectable_label(is_current, text).clicked() {
+                                    result
+                                        .commands
+                                        .extend(ease_segment_commands(&refs, index, handles));
+                                    close_menu = true;
+                                }
+                            }
+                        }
+
                         // Close on click outside or Escape
                         if ui.input(|i| i.key_pressed(egui::Key::Escape))
                             || (ui.input(|i| i.pointer.any_click()) && !ui.ui_contains_pointer())
@@ -755,22 +1866,271 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             result.clicked_keyframe = Some(kf_id);
         }
 
+        // Click on the curve selects its segment; Ctrl/Cmd+click inserts a
+        // keyframe there.
+        if response.clicked()
+            && let Some(point) = hovered_curve
+        {
+            if ui.input(|i| KeyBindings::held(&i.modifiers, bindings.insert_keyframe)) {
+                result.insert_keyframe_at = Some(point.time);
+            } else {
+                result.clicked_segment = Some(point.segment);
+            }
+        }
+
+        // Bezier handle drag. Handles take precedence over keyframes and the
+        // bounding box. The break tangents modifier breaks the tangent. The
+        // last previewed edit is committed as one command on release.
+        let handle_drag_id = id.with("handle_drag");
+        let handle_edit_id = id.with("handle_edit");
+        if response.drag_started()
+            && let Some(handle) = hovered_handle
+        {
+            ui.data_mut(|data| data.insert_temp(handle_drag_id, handle));
+        }
+        if let Some((keyframe_id, side)) =
+            ui.data(|data| data.get_temp::<(KeyframeId, HandleSide)>(handle_drag_id))
+        {
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                let break_tangents =
+                    ui.input(|i| KeyBindings::held(&i.modifiers, bindings.break_tangents));
+                let edit = if let Some(weighted) = self.dragged_weighted_handles(
+                    rect,
+                    keyframes,
+                    keyframe_id,
+                    side,
+                    pos,
+                    break_tangents,
+                ) {
+                    keyframes
+                        .iter()
+                        .find(|kf| kf.id == keyframe_id)
+                        .and_then(|kf| self.handle_geometry(rect, kf, keyframes, side))
+                        .map(|geometry| {
+                            (
+                                geometry.normalize(pos, (0.0, 0.0)),
+                                AnimationCommand::SetKeyframeWeightedHandles {
+                                    keyframe_id,
+                                    handles: Some(weighted),
+                                },
+                            )
+                        })
+                } else {
+                    self.dragged_handles(rect, keyframes, keyframe_id, side, pos, break_tangents)
+                        .map(|(new, handles)| {
+                            (
+                                new,
+                                AnimationCommand::SetKeyframeHandles {
+                                    keyframe_id,
+                                    handles,
+                                },
+                            )
+                        })
+                };
+                if let Some(((new_x, new_y), command)) = edit {
+                    let handle_drag = HandleDrag {
+                        keyframe_id,
+                        side,
+                        new_x,
+                        new_y,
+                        phase: DragPhase::Preview,
+                    };
+                    ui.data_mut(|data| {
+                        data.insert_temp(handle_edit_id, (handle_drag.clone(), command));
+                    });
+                    result.handle_drag = Some(handle_drag);
+                }
+            }
+            if response.drag_stopped() {
+                let edit = ui.data_mut(|data| {
+                    data.remove::<(KeyframeId, HandleSide)>(handle_drag_id);
+                    let edit = data.get_temp::<(HandleDrag, AnimationCommand)>(handle_edit_id);
+                    data.remove::<(HandleDrag, AnimationCommand)>(handle_edit_id);
+                    edit
+                });
+                if let Some((mut handle_drag, command)) = edit {
+                    handle_drag.phase = DragPhase::Commit;
+                    result.handle_drag = Some(handle_drag);
+                    result.commands.push(command);
+                }
+                result.transform_ended = true;
+            }
+            return;
+        }
+
+        // Box selection on empty background. Shift adds, Ctrl/Cmd toggles.
+        let box_select_id = id.with("box_select");
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && hovered_keyframe.is_none()
+            && hovered_handle.is_none()
+            && hovered_bbox_handle.is_none()
+            && !ui.input(|i| KeyBindings::held(&i.modifiers, bindings.pan))
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            ui.data_mut(|data| data.insert_temp(box_select_id, pos));
+        }
+        if let Some(start) = ui.data(|data| data.get_temp::<Pos2>(box_select_id)) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let selection_rect = Rect::from_two_pos(start, pos);
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(
+                    selection_rect,
+                    0.0,
+                    self.config.box_select_color.linear_multiply(0.1),
+                );
+                painter.rect_stroke(
+                    selection_rect,
+                    0.0,
+                    Stroke::new(1.0, self.config.box_select_color),
+                    egui::StrokeKind::Inside,
+                );
+
+                if response.drag_stopped() {
+                    let enclosed = keyframes
+                        .iter()
+                        .filter(|kf| selection_rect.contains(self.keyframe_to_screen(rect, kf)))
+                        .map(|kf| kf.id)
+                        .collect();
+                    let mode = ui.input(|i| bindings.selection_mode(&i.modifiers));
+                    result.box_selected = Some((enclosed, mode));
+                }
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<Pos2>(box_select_id));
+            }
+            return;
+        }
+
+        // Dragging one of several selected keyframes moves the whole selection
+        // rigidly. It takes precedence over the bounding box interior and
+        // emits a single `OffsetKeyframes` on release.
+        let rigid_drag_id = id.with("rigid_drag");
+        if response.drag_started()
+            && selected_keyframe_data.len() > 1
+            && matches!(
+                hovered_bbox_handle,
+                None | Some(BoundingBoxHandle::Interior)
+            )
+            && let Some(kf) = hovered_keyframe
+                .filter(|kf_id| self.selected.contains(kf_id))
+                .and_then(|kf_id| keyframes.iter().find(|kf| kf.id == kf_id))
+                .filter(|kf| !kf.locked)
+        {
+            ui.data_mut(|data| data.insert_temp(rigid_drag_id, (kf.id, kf.position, kf.value)));
+        }
+        if let Some(grabbed) =
+            ui.data(|data| data.get_temp::<(KeyframeId, TimeTick, f32)>(rigid_drag_id))
+        {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let (delta_time, delta_value) =
+                    self.rigid_drag_delta(ui, rect, keyframes, grabbed, pos);
+
+                // Preview the moved selection.
+                let painter = ui.painter_at(rect);
+                for kf in keyframes
+                    .iter()
+                    .filter(|kf| self.selected.contains(&kf.id) && !kf.pinned)
+                {
+                    let moved = Pos2::new(
+                        self.space.unit_to_clipped(kf.position + delta_time),
+                        self.value_to_y(rect, kf.value + delta_value),
+                    );
+                    KeyframeDot::new(moved)
+                        .color(self.config.keyframe_color.linear_multiply(0.6))
+                        .selected(true)
+                        .textures(self.config.keyframe_textures)
+                        .paint(&painter);
+                }
+
+                if response.drag_stopped()
+                    && (delta_time != TimeTick::default() || delta_value != 0.0)
+                {
+                    result.commands.push(AnimationCommand::OffsetKeyframes {
+                        keyframe_ids: selected_keyframe_data.iter().map(|(id, ..)| *id).collect(),
+                        delta_time,
+                        delta_value: delta_value as f64,
+                    });
+                    result.transform_ended = true;
+                }
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<(KeyframeId, TimeTick, f32)>(rigid_drag_id));
+            }
+            return;
+        }
+
+        // Commit the last previewed single keyframe move on release.
+        let keyframe_move_id = id.with("keyframe_move");
+        if response.drag_stopped()
+            && let Some(mut last_move) =
+                ui.data(|data| data.get_temp::<KeyframeMove>(keyframe_move_id))
+        {
+            ui.data_mut(|data| data.remove::<KeyframeMove>(keyframe_move_id));
+            last_move.phase = DragPhase::Commit;
+            result.keyframe_move = Some(last_move);
+            return;
+        }
+
+        // Latch the bounding box handle a drag started on, with the
+        // selection's time and value spans at that moment, so the readout
+        // can report the overall scale factor. An interior drag also keeps
+        // the grabbed point in time/value space, so the selection follows
+        // the pointer even if the view changes mid-drag.
+        let bbox_drag_id = id.with("bbox_drag");
+        let bbox_grab_id = id.with("bbox_grab");
+        if response.drag_started()
+            && selected_keyframe_data.len() > 1
+            && let Some(handle) = hovered_bbox_handle
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let (time_span, value_span) = selection_spans(selected_keyframe_data);
+            let grab = (
+                self.space.clipped_to_unit(pos.x),
+                self.y_to_value(rect, pos.y),
+            );
+            ui.data_mut(|data| {
+                data.insert_temp(bbox_drag_id, (handle, time_span, value_span));
+                data.insert_temp(bbox_grab_id, grab);
+            });
+        }
+        let bbox_drag =
+            ui.data(|data| data.get_temp::<(BoundingBoxHandle, f64, f32)>(bbox_drag_id));
+        if response.drag_stopped() && bbox_drag.is_some() {
+            ui.data_mut(|data| {
+                data.remove::<(BoundingBoxHandle, f64, f32)>(bbox_drag_id);
+                data.remove::<(TimeTick, f32)>(bbox_grab_id);
+            });
+            result.transform_ended = true;
+        }
+
         // Drag interactions
         if response.dragged() {
-            let drag_delta = response.drag_delta();
-
             // Bounding box drag handling (for multiple selected keyframes)
             if selected_keyframe_data.len() > 1
-                && let Some(handle) = hovered_bbox_handle
+                && let Some((handle, ..)) = bbox_drag
+                && let Some(pointer) = response.interact_pointer_pos()
             {
+                let target = (
+                    self.space.clipped_to_unit(pointer.x),
+                    self.y_to_value(rect, pointer.y),
+                );
                 match handle {
                     BoundingBoxHandle::Interior => {
-                        // Offset all selected keyframes
-                        let delta_time = self.screen_delta_to_time(drag_delta.x);
-                        let delta_value = self.screen_delta_to_value(rect, drag_delta.y);
-
-                        // Constrain to axis if shift is held
-                        let (final_time, final_value) = if ui.input(|i| i.modifiers.shift) {
+                        // Offset all selected keyframes by the pointer's
+                        // movement since the last frame in time/value space.
+                        let grab = ui
+                            .data(|data| data.get_temp::<(TimeTick, f32)>(bbox_grab_id))
+                            .unwrap_or(target);
+                        let delta_time = target.0 - grab.0;
+                        let delta_value = target.1 - grab.1;
+
+                        // Constrain to the dominant axis.
+                        let (final_time, final_value) = if ui
+                            .input(|i| KeyBindings::held(&i.modifiers, bindings.constrain_axis))
+                        {
+                            let drag_delta = response.drag_delta();
                             if drag_delta.x.abs() > drag_delta.y.abs() {
                                 (delta_time, 0.0)
                             } else {
@@ -780,16 +2140,44 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
                             (delta_time, delta_value)
                         };
 
+                        let grab = (grab.0 + final_time, grab.1 + final_value);
+                        ui.data_mut(|data| data.insert_temp(bbox_grab_id, grab));
                         result.offset_keyframes = Some((final_time, final_value));
                     }
                     _ => {
-                        // Scale operation for edge/corner handles
-                        if let Some(scale) = self.calculate_scale_from_drag(
-                            rect,
-                            handle,
-                            drag_delta,
-                            selected_keyframe_data,
-                        ) {
+                        // Scale operation for edge/corner handles. With
+                        // snapping, the moving time edge follows the pointer
+                        // and snaps to frames, unselected keyframes and
+                        // markers.
+                        let mut target = target;
+                        if let Some(snap) = self.snap.as_ref().filter(|_| handle.scales_x()) {
+                            let others: Vec<TimeTick> = keyframes
+                                .iter()
+                                .filter(|kf| !self.selected.contains(&kf.id))
+                                .map(|kf| kf.position)
+                                .collect();
+                            target.0 = snap.apply(
+                                target.0,
+                                &ui.input(|i| i.modifiers),
+                                self.space.pixels_per_unit,
+                                &others,
+                                &self.markers.map(Markers::times).unwrap_or_default(),
+                            );
+                        }
+                        if let Some(scale) =
+                            self.calculate_scale_from_drag(handle, target, selected_keyframe_data)
+                        {
+                            if let Some((_, time_span, value_span)) = bbox_drag {
+                                self.draw_scale_readout(
+                                    ui,
+                                    rect,
+                                    pointer,
+                                    handle,
+                                    selected_keyframe_data,
+                                    scale,
+                                    (time_span, value_span),
+                                );
+                            }
                             result.scale_keyframes = Some(scale);
                         }
                     }
@@ -800,148 +2188,1116 @@ impl<'a, S: KeyframeSource> CurveEditor<'a, S> {
             // Single keyframe drag
             if let Some(kf_id) = hovered_keyframe
                 && self.selected.contains(&kf_id)
+                && keyframes.iter().any(|kf| kf.id == kf_id && !kf.locked)
                 && let Some(pos) = response.interact_pointer_pos()
             {
-                let time = self.space.clipped_to_unit(pos.x);
+                let mut time = self.space.clipped_to_unit(pos.x);
+                if let Some(snap) = &self.snap {
+                    let others: Vec<TimeTick> = keyframes
+                        .iter()
+                        .filter(|kf| kf.id != kf_id)
+                        .map(|kf| kf.position)
+                        .collect();
+                    time = snap.apply(
+                        time,
+                        &ui.input(|i| i.modifiers),
+                        self.space.pixels_per_unit,
+                        &others,
+                        &self.markers.map(Markers::times).unwrap_or_default(),
+                    );
+                }
                 let value = self.y_to_value(rect, pos.y);
-                result.keyframe_move = Some(KeyframeMove {
+                let keyframe_move = KeyframeMove {
                     keyframe_id: kf_id,
                     new_position: time,
                     new_value: value,
-                });
+                    phase: DragPhase::Preview,
+                };
+                ui.data_mut(|data| data.insert_temp(keyframe_move_id, keyframe_move.clone()));
+                result.keyframe_move = Some(keyframe_move);
             }
         }
+    }
 
-        // Drag ended - signal for undo grouping
-        if response.drag_stopped()
-            && selected_keyframe_data.len() > 1
-            && hovered_bbox_handle.is_some()
-        {
-            result.transform_ended = true;
+    /// Offset `(time, value)` of a rigid selection drag that grabbed the
+    /// keyframe `(id, time, value)` and is now at `pointer`.
+    ///
+    /// The grabbed keyframe snaps to unselected keyframes and markers. Shift
+    /// constrains the move to the dominant axis.
+    fn rigid_drag_delta(
+        &self,
+        ui: &Ui,
+        rect: Rect,
+        keyframes: &[&KeyframeView],
+        (_, start_time, start_value): (KeyframeId, TimeTick, f32),
+        pointer: Pos2,
+    ) -> (TimeTick, f32) {
+        let mut time = self.space.clipped_to_unit(pointer.x);
+        if let Some(snap) = &self.snap {
+            let others: Vec<TimeTick> = keyframes
+                .iter()
+                .filter(|kf| !self.selected.contains(&kf.id))
+                .map(|kf| kf.position)
+                .collect();
+            time = snap.apply(
+                time,
+                &ui.input(|i| i.modifiers),
+                self.space.pixels_per_unit,
+                &others,
+                &self.markers.map(Markers::times).unwrap_or_default(),
+            );
         }
-    }
+        let delta_time = time - start_time;
+        let delta_value = self.y_to_value(rect, pointer.y) - start_value;
 
-    /// Convert screen X delta to time delta.
-    fn screen_delta_to_time(&self, delta_x: f32) -> TimeTick {
-        TimeTick::new(delta_x as f64 / self.space.pixels_per_unit)
+        if ui.input(|i| KeyBindings::held(&i.modifiers, self.config.key_bindings.constrain_axis)) {
+            let start = Pos2::new(
+                self.space.unit_to_clipped(start_time),
+                self.value_to_y(rect, start_value),
+            );
+            if (pointer.x - start.x).abs() > (pointer.y - start.y).abs() {
+                (delta_time, 0.0)
+            } else {
+                (TimeTick::default(), delta_value)
+            }
+        } else {
+            (delta_time, delta_value)
+        }
     }
 
-    /// Convert screen Y delta to value delta.
-    fn screen_delta_to_value(&self, rect: Rect, delta_y: f32) -> f32 {
-        let (min_val, max_val) = self.value_range;
-        let value_range = max_val - min_val;
-        let usable_height = rect.height() - 2.0 * self.config.vertical_padding;
-        // Y is inverted (screen Y goes down, value goes up)
-        -delta_y * value_range / usable_height
+    /// Calculate scale factors that move the edges dragged by `handle` to
+    /// `target` in time/value space.
+    fn calculate_scale_from_drag(
+        &self,
+        handle: BoundingBoxHandle,
+        target: (TimeTick, f32),
+        selected_data: &[(KeyframeId, TimeTick, f32)],
+    ) -> Option<(TimeTick, f32, f64, f64)> {
+        let bbox = self.selection_world_box(selected_data.iter().copied())?;
+        let (time_scale, value_scale) = bbox.scale_factors(handle, target);
+        Some((bbox.anchor.0, bbox.anchor.1, time_scale, value_scale))
     }
 
-    /// Calculate scale factors from a bounding box handle drag.
-    fn calculate_scale_from_drag(
+    /// Show the selection's duration and value span after applying `scale`,
+    /// and the factors relative to `start_spans`, next to the pointer.
+    #[allow(clippy::too_many_arguments)]
+    fn draw_scale_readout(
         &self,
+        ui: &Ui,
         rect: Rect,
+        pointer: Pos2,
         handle: BoundingBoxHandle,
-        drag_delta: Vec2,
         selected_data: &[(KeyframeId, TimeTick, f32)],
-    ) -> Option<(TimeTick, f32, f64, f64)> {
-        if selected_data.is_empty() {
-            return None;
+        (_, _, time_scale, value_scale): (TimeTick, f32, f64, f64),
+        (start_time_span, start_value_span): (f64, f32),
+    ) {
+        let (time_span, value_span) = selection_spans(selected_data);
+        let mut lines = Vec::new();
+        if handle.scales_x() {
+            let duration = time_span * time_scale;
+            lines.push(format!(
+                "Duration {duration:.3} (×{:.2})",
+                ratio(duration, start_time_span)
+            ));
+        }
+        if handle.scales_y() {
+            let span = value_span as f64 * value_scale;
+            lines.push(format!(
+                "Value span {span:.3} (×{:.2})",
+                ratio(span, start_value_span as f64)
+            ));
         }
 
-        // Calculate bounds
-        let min_t = selected_data
-            .iter()
-            .map(|d| d.1)
-            .min_by(|a, b| a.partial_cmp(b).unwrap())?;
-        let max_t = selected_data
-            .iter()
-            .map(|d| d.1)
-            .max_by(|a, b| a.partial_cmp(b).unwrap())?;
-        let min_v = selected_data
-            .iter()
-            .map(|d| d.2)
-            .min_by(|a, b| a.partial_cmp(b).unwrap())?;
-        let max_v = selected_data
-            .iter()
-            .map(|d| d.2)
-            .max_by(|a, b| a.partial_cmp(b).unwrap())?;
+        let painter = ui.painter_at(rect);
+        let galley = painter.layout_no_wrap(
+            lines.join("\n"),
+            egui::FontId::monospace(11.0),
+            Color32::from_gray(230),
+        );
+        let text_rect =
+            Rect::from_min_size(pointer + Vec2::new(14.0, 14.0), galley.size()).expand(3.0);
+        painter.rect_filled(text_rect, 3.0, Color32::from_black_alpha(200));
+        painter.galley(text_rect.min + Vec2::splat(3.0), galley, Color32::WHITE);
+    }
+
+    fn keyframe_to_screen(&self, rect: Rect, kf: &KeyframeView) -> Pos2 {
+        let x = self.space.unit_to_clipped(kf.position);
+        let y = self.value_to_y(rect, kf.value);
+        Pos2::new(x, y)
+    }
+
+    fn value_to_y(&self, rect: Rect, value: f32) -> f32 {
+        value_to_y(rect, self.config.vertical_padding, self.value_range, value)
+    }
+
+    fn y_to_value(&self, rect: Rect, y: f32) -> f32 {
+        y_to_value(rect, self.config.vertical_padding, self.value_range, y)
+    }
+}
+
+/// Allocate the rect of an editor with `id` according to the sizing of
+/// `config`.
+pub(crate) fn allocate_editor(
+    ui: &mut Ui,
+    id: egui::Id,
+    config: &CurveEditorConfig,
+) -> (Rect, Response) {
+    let available = ui.available_size();
+    let height = match config.height_mode {
+        HeightMode::Preferred => config.height,
+        HeightMode::Fill => available.y,
+    };
+    let mut size = Vec2::new(available.x, height).max(config.min_size);
+
+    if config.stable_rect {
+        let key = committed_rect_id(id);
+        let committed = ui.data(|data| data.get_temp::<Rect>(key));
+        if let Some(committed) = committed
+            && committed.size() != size
+        {
+            // Settle on the new size next frame.
+            ui.data_mut(|data| data.insert_temp(key, Rect::from_min_size(committed.min, size)));
+            ui.ctx().request_repaint();
+            size = committed.size();
+        } else {
+            let rect = Rect::from_min_size(ui.cursor().min, size);
+            ui.data_mut(|data| data.insert_temp(key, rect));
+        }
+    }
+
+    ui.allocate_exact_size(size, Sense::click_and_drag())
+}
+
+fn committed_rect_id(id: egui::Id) -> egui::Id {
+    id.with("committed_rect")
+}
+
+/// Vertical line at `x` marking a preview time.
+pub(crate) fn paint_preview_line(painter: &egui::Painter, rect: Rect, x: f32) {
+    painter.line_segment(
+        [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+        Stroke::new(1.0, Color32::from_white_alpha(60)),
+    );
+}
 
-        let time_range = (max_t - min_t).value();
-        let value_range = max_v - min_v;
+/// Dot at `pos` on a curve of `color`, labeled with its `value`.
+pub(crate) fn paint_value_preview(painter: &egui::Painter, pos: Pos2, value: f32, color: Color32) {
+    painter.circle(pos, 4.0, Color32::from_gray(25), Stroke::new(2.0, color));
+    let galley = painter.layout_no_wrap(
+        format!("{value:.3}"),
+        egui::FontId::monospace(10.0),
+        Color32::from_gray(230),
+    );
+    let text_rect =
+        Rect::from_min_size(pos + Vec2::new(8.0, -galley.size().y - 6.0), galley.size())
+            .expand(2.0);
+    painter.rect_filled(text_rect, 2.0, Color32::from_black_alpha(180));
+    painter.galley(text_rect.min + Vec2::splat(2.0), galley, Color32::WHITE);
+}
+
+/// Draw horizontal grid lines at intervals of `value_range` that are nice
+/// for `unit`, optionally labeled.
+pub(crate) fn draw_value_grid(
+    painter: &egui::Painter,
+    rect: Rect,
+    (min_val, max_val): (f32, f32),
+    unit: ValueUnit,
+    vertical_padding: f32,
+    color: Color32,
+    labels: bool,
+) {
+    let usable_height = rect.height() - 2.0 * vertical_padding;
+    if max_val - min_val <= 0.0 || usable_height <= 0.0 {
+        return;
+    }
+    let interval = value_grid_intervals(max_val - min_val, usable_height, unit).major as f32;
+    let decimals = interval_decimals(interval);
+
+    let mut index = (min_val / interval).ceil() as i64;
+    loop {
+        let v = index as f32 * interval;
+        if v > max_val {
+            break;
+        }
+        let y = value_to_y(rect, vertical_padding, (min_val, max_val), v);
+        painter.line_segment(
+            [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+            Stroke::new(1.0, color),
+        );
 
-        // Get anchor position
-        let (anchor_time, anchor_value) = match self.anchor_mode {
-            AnchorMode::Start => (min_t, min_v),
-            AnchorMode::End => (max_t, max_v),
-            AnchorMode::Center => (min_t.lerp(max_t, 0.5), (min_v + max_v) / 2.0),
-            AnchorMode::Playhead => {
-                let center_v = (min_v + max_v) / 2.0;
-                (self.current_time, center_v)
+        // Value label
+        if labels {
+            painter.text(
+                Pos2::new(rect.left() + 4.0, y - 2.0),
+                egui::Align2::LEFT_BOTTOM,
+                unit.format(v as f64, decimals),
+                egui::FontId::proportional(9.0),
+                Color32::from_gray(100),
+            );
+        }
+
+        index += 1;
+    }
+}
+
+/// `range` widened so a flat curve ends up centered, or `(0, 1)` without
+/// keyframes.
+pub(crate) fn display_range(range: Option<(f32, f32)>) -> (f32, f32) {
+    match range {
+        Some((min, max)) if max - min < 1e-6 => (min - 1.0, max + 1.0),
+        Some(range) => range,
+        None => (0.0, 1.0),
+    }
+}
+
+/// Screen Y of `value` in `rect` for `value_range`, leaving
+/// `vertical_padding` at the top and bottom.
+pub(crate) fn value_to_y(
+    rect: Rect,
+    vertical_padding: f32,
+    (min_val, max_val): (f32, f32),
+    value: f32,
+) -> f32 {
+    let value_range = max_val - min_val;
+    if value_range.abs() < 1e-6 {
+        return rect.center().y;
+    }
+
+    let normalized = (value - min_val) / value_range;
+    let usable_height = rect.height() - 2.0 * vertical_padding;
+    rect.bottom() - vertical_padding - normalized * usable_height
+}
+
+/// Inverse of [`value_to_y`].
+pub(crate) fn y_to_value(
+    rect: Rect,
+    vertical_padding: f32,
+    (min_val, max_val): (f32, f32),
+    y: f32,
+) -> f32 {
+    let usable_height = rect.height() - 2.0 * vertical_padding;
+    let normalized = (rect.bottom() - vertical_padding - y) / usable_height;
+    min_val + normalized * (max_val - min_val)
+}
+
+/// Screen polyline of the segment from `keyframes[index]` to the next
+/// keyframe as it is drawn, with bezier segments split into `steps` lines.
+pub(crate) fn segment_polyline(
+    keyframes: &[&Keyframe<f32>],
+    index: usize,
+    to_screen: impl Fn(TimeTick, f32) -> Pos2,
+    steps: usize,
+) -> Vec<Pos2> {
+    let (left, right) = (keyframes[index], keyframes[index + 1]);
+    let left_pos = to_screen(left.position, left.value);
+    let right_pos = to_screen(right.position, right.value);
+
+    match left.keyframe_type {
+        KeyframeType::Hold => vec![left_pos, Pos2::new(right_pos.x, left_pos.y), right_pos],
+        KeyframeType::Linear => vec![left_pos, right_pos],
+        KeyframeType::Spring => spring_polyline(left, right, to_screen, steps),
+        KeyframeType::Bezier | KeyframeType::Auto => {
+            let delta = right_pos - left_pos;
+            let [x1, y1, x2, y2] = segment_control_points(keyframes, index);
+            let bezier = egui::epaint::CubicBezierShape {
+                points: [
+                    left_pos,
+                    left_pos + Vec2::new(delta.x * x1, delta.y * y1),
+                    left_pos + Vec2::new(delta.x * x2, delta.y * y2),
+                    right_pos,
+                ],
+                closed: false,
+                fill: Color32::TRANSPARENT,
+                stroke: Stroke::NONE.into(),
+            };
+            let steps = steps.max(1);
+            (0..=steps)
+                .map(|step| bezier.sample(step as f32 / steps as f32))
+                .collect()
+        }
+    }
+}
+
+/// Screen polyline of the spring segment from `left` to `right` in `steps`
+/// lines, ending with the jump onto `right` if the spring has not settled.
+fn spring_polyline(
+    left: &Keyframe<f32>,
+    right: &Keyframe<f32>,
+    to_screen: impl Fn(TimeTick, f32) -> Pos2,
+    steps: usize,
+) -> Vec<Pos2> {
+    let duration = (right.position - left.position).value();
+    let steps = steps.max(1);
+    (0..=steps)
+        .map(|step| {
+            let elapsed = duration * step as f64 / steps as f64;
+            let progress = left.spring.progress(elapsed) as f32;
+            to_screen(
+                left.position + TimeTick::new(elapsed),
+                left.value + (right.value - left.value) * progress,
+            )
+        })
+        .chain(std::iter::once(to_screen(right.position, right.value)))
+        .collect()
+}
+
+/// Apply the handles of `command`, a handle edit of a drag in progress, to
+/// the matching keyframe of `keyframes`.
+fn preview_handle_edit(keyframes: &mut [KeyframeView], command: AnimationCommand) {
+    match command {
+        AnimationCommand::SetKeyframeHandles {
+            keyframe_id,
+            handles,
+        } => {
+            if let Some(kf) = keyframes.iter_mut().find(|kf| kf.id == keyframe_id) {
+                kf.handles = handles;
             }
+        }
+        AnimationCommand::SetKeyframeWeightedHandles {
+            keyframe_id,
+            handles,
+        } => {
+            if let Some(kf) = keyframes.iter_mut().find(|kf| kf.id == keyframe_id) {
+                kf.weighted = handles;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Distance from `pos` to the closest point of the polyline `points`, and
+/// that point.
+pub(crate) fn closest_on_polyline(points: &[Pos2], pos: Pos2) -> (f32, Pos2) {
+    let closest = |a: Pos2, b: Pos2| {
+        let ab = b - a;
+        let t = if ab.length_sq() > 0.0 {
+            ((pos - a).dot(ab) / ab.length_sq()).clamp(0.0, 1.0)
+        } else {
+            0.0
         };
+        a + ab * t
+    };
+    points
+        .windows(2)
+        .map(|line| closest(line[0], line[1]))
+        .chain(points.first().copied())
+        .map(|point| (point.distance(pos), point))
+        .min_by(|a, b| a.0.total_cmp(&b.0))
+        .unwrap_or((f32::INFINITY, pos))
+}
 
-        // Convert drag delta to time/value space.
-        let delta_time = self.screen_delta_to_time(drag_delta.x).value();
-        let delta_value = self.screen_delta_to_value(rect, drag_delta.y);
-
-        // Calculate scale based on handle.
-        let mut time_scale = 1.0;
-        let mut value_scale = 1.0;
-
-        if handle.scales_x() && time_range.abs() > 1e-6 {
-            // Scale factor based on how much the drag expanded/contracted the bounds.
-            let expansion = match handle {
-                // Moving left edge left expands.
-                BoundingBoxHandle::Left
-                | BoundingBoxHandle::TopLeft
-                | BoundingBoxHandle::BottomLeft => -delta_time,
-                // Moving right edge right expands.
-                _ => delta_time,
-            };
-            time_scale = 1.0 + expansion / time_range;
-            // Prevent negative/zero scale.
-            time_scale = time_scale.max(0.01);
-        }
-
-        if handle.scales_y() && value_range.abs() > 1e-6 {
-            let expansion = match handle {
-                // Moving top edge up expands (delta_value is already negated for screen Y).
-                BoundingBoxHandle::Top
-                | BoundingBoxHandle::TopLeft
-                | BoundingBoxHandle::TopRight => delta_value,
-                // Moving bottom edge down expands.
-                _ => -delta_value,
+/// Paint the segment from `keyframes[index]` to the next keyframe.
+///
+/// `to_screen` maps `(time, value)` to screen space.
+pub(crate) fn paint_curve_segment(
+    painter: &egui::Painter,
+    keyframes: &[&Keyframe<f32>],
+    index: usize,
+    to_screen: impl Fn(TimeTick, f32) -> Pos2,
+    stroke: Stroke,
+) {
+    let (left, right) = (keyframes[index], keyframes[index + 1]);
+    let left_pos = to_screen(left.position, left.value);
+    let right_pos = to_screen(right.position, right.value);
+
+    match left.keyframe_type {
+        KeyframeType::Hold => {
+            // Step function: horizontal then vertical
+            let mid = Pos2::new(right_pos.x, left_pos.y);
+            painter.line_segment([left_pos, mid], stroke);
+            painter.line_segment(
+                [mid, right_pos],
+                Stroke::new(stroke.width, stroke.color.linear_multiply(0.5)),
+            );
+        }
+        KeyframeType::Linear => {
+            // Straight line
+            painter.line_segment([left_pos, right_pos], stroke);
+        }
+        KeyframeType::Spring => {
+            // Sampled, as a spring is no bezier.
+            let steps = ((right_pos.x - left_pos.x).abs() / 2.0).clamp(8.0, 512.0) as usize;
+            painter.add(Shape::line(
+                spring_polyline(left, right, to_screen, steps),
+                stroke,
+            ));
+        }
+        KeyframeType::Bezier | KeyframeType::Auto => {
+            // Bezier curve - use egui's built-in cubic bezier
+            let dx = right_pos.x - left_pos.x;
+            let dy = right_pos.y - left_pos.y;
+            let [x1, y1, x2, y2] = segment_control_points(keyframes, index);
+
+            let cp1 = Pos2::new(left_pos.x + dx * x1, left_pos.y + dy * y1);
+            let cp2 = Pos2::new(left_pos.x + dx * x2, left_pos.y + dy * y2);
+
+            painter.add(Shape::CubicBezier(egui::epaint::CubicBezierShape {
+                points: [left_pos, cp1, cp2, right_pos],
+                closed: false,
+                fill: Color32::TRANSPARENT,
+                stroke: stroke.into(),
+            }));
+        }
+    }
+}
+
+/// Time and value spans of the selected keyframes.
+fn selection_spans(selected_data: &[(KeyframeId, TimeTick, f32)]) -> (f64, f32) {
+    let (mut min_t, mut max_t) = (f64::INFINITY, f64::NEG_INFINITY);
+    let (mut min_v, mut max_v) = (f32::INFINITY, f32::NEG_INFINITY);
+    for (_, time, value) in selected_data {
+        min_t = min_t.min(time.value());
+        max_t = max_t.max(time.value());
+        min_v = min_v.min(*value);
+        max_v = max_v.max(*value);
+    }
+    if selected_data.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (max_t - min_t, max_v - min_v)
+    }
+}
+
+/// `value / reference`, or `1.0` for an empty reference.
+fn ratio(value: f64, reference: f64) -> f64 {
+    if reference.abs() < 1e-9 {
+        1.0
+    } else {
+        value / reference
+    }
+}
+
+/// Whether `pos` is within Manhattan `distance` of a keyframe at `screen_pos`.
+pub(crate) fn hits_keyframe(screen_pos: Pos2, pos: Pos2, distance: f32) -> bool {
+    (pos.x - screen_pos.x).abs() + (pos.y - screen_pos.y).abs() < distance
+}
+
+/// Normalized `(x, y)` of the handle on `side`.
+fn handle_xy(handles: &BezierHandles, side: HandleSide) -> (f32, f32) {
+    match side {
+        HandleSide::Left => (handles.left_x, handles.left_y),
+        HandleSide::Right => (handles.right_x, handles.right_y),
+    }
+}
+
+/// Set the `(time, value)` offset of the weighted handle on `side`.
+fn set_weighted_offset(handles: &mut WeightedHandles, side: HandleSide, (time, value): (f64, f64)) {
+    match side {
+        HandleSide::Left => {
+            handles.left_time = time;
+            handles.left_value = value;
+        }
+        HandleSide::Right => {
+            handles.right_time = time;
+            handles.right_value = value;
+        }
+    }
+}
+
+/// Set the normalized `(x, y)` of the handle on `side`.
+fn set_handle_xy(handles: &mut BezierHandles, side: HandleSide, (x, y): (f32, f32)) {
+    match side {
+        HandleSide::Left => {
+            handles.left_x = x;
+            handles.left_y = y;
+        }
+        HandleSide::Right => {
+            handles.right_x = x;
+            handles.right_y = y;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::track::Track;
+
+    #[test]
+    fn weighted_handle_drag_reaches_past_neighbor() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0));
+        let weighted = WeightedHandles {
+            left_time: -0.25,
+            left_value: 0.0,
+            right_time: 0.25,
+            right_value: 0.0,
+        };
+        let middle = track.add_keyframe(Keyframe::new(1.0, 5.0).with_weighted(Some(weighted)));
+        track.add_keyframe(Keyframe::new(2.0, 0.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 300.0);
+        let editor = CurveEditor::new(&track, &selected, &space, (0.0, 10.0));
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(300.0, 200.0));
+
+        let keyframes = KeyframeSource::keyframes_sorted(&track);
+        let refs: Vec<&KeyframeView> = keyframes.iter().collect();
+        let kf_pos = editor.keyframe_to_screen(rect, refs[1]);
+
+        let dragged = editor
+            .dragged_weighted_handles(
+                rect,
+                &refs,
+                middle,
+                HandleSide::Right,
+                kf_pos + Vec2::new(150.0, 0.0),
+                false,
+            )
+            .unwrap();
+        assert!((dragged.right_time - 1.5).abs() < 1e-4);
+        assert!(dragged.right_value.abs() < 1e-4);
+        // The opposite handle keeps its length and stays collinear.
+        assert!((dragged.left_time + 0.25).abs() < 1e-4);
+        assert!(dragged.left_value.abs() < 1e-4);
+    }
+
+    #[test]
+    fn normalized_view_fills_height() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 200.0));
+        track.add_keyframe(Keyframe::new(1.0, 1000.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 200.0));
+        let editor = CurveEditor::new(&track, &selected, &space, (0.0, 1.0)).normalized(true);
+        let padding = editor.config.vertical_padding;
+
+        assert_eq!(editor.value_to_y(rect, 200.0), rect.bottom() - padding);
+        assert_eq!(editor.value_to_y(rect, 1000.0), rect.top() + padding);
+        assert_eq!(editor.y_to_value(rect, rect.center().y), 600.0);
+    }
+
+    #[test]
+    fn reports_keyframe_states_in_draw_order() {
+        let mut track = Track::<f32>::new();
+        let first = track.add_keyframe(Keyframe::new(0.5, 0.0));
+        let pinned = track.add_keyframe(Keyframe::new(1.5, 10.0).with_pinned(true));
+
+        let selected: HashSet<KeyframeId> = [first, pinned].into_iter().collect();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let mut keyframes = Vec::new();
+        offscreen::run_frame(Vec2::new(200.0, 100.0), 1.0, |ui| {
+            keyframes = CurveEditor::new(&track, &selected, &space, (0.0, 10.0))
+                .show(ui)
+                .keyframes;
+        });
+        let states: Vec<_> = keyframes.iter().map(|k| (k.keyframe_id, k.state)).collect();
+        assert_eq!(
+            states,
+            [
+                (
+                    first,
+                    KeyframeVisualState {
+                        selected: true,
+                        ..Default::default()
+                    }
+                ),
+                (
+                    pinned,
+                    KeyframeVisualState {
+                        selected: true,
+                        pinned: true,
+                        ..Default::default()
+                    }
+                ),
+            ]
+        );
+
+        // Keyframes under the curve: the diamonds come first in the SVG.
+        let svg = |draw_order| {
+            let config = CurveEditorConfig {
+                draw_order,
+                ..Default::default()
             };
-            value_scale = 1.0 + (expansion / value_range) as f64;
-            value_scale = value_scale.max(0.01);
+            CurveEditor::new(&track, &HashSet::default(), &space, (0.0, 10.0))
+                .config(config)
+                .into_svg(Vec2::new(200.0, 100.0))
+        };
+        let below = svg([
+            CurveLayer::Keyframes,
+            CurveLayer::Curves,
+            CurveLayer::Handles,
+            CurveLayer::BoundingBox,
+        ]);
+        assert!(below.find("<polygon") < below.find("<path d=\"M"));
+        let above = svg(CurveLayer::DEFAULT_ORDER);
+        assert!(above.find("<polygon") > above.find("<path d=\"M"));
+    }
+
+    #[test]
+    fn delete_emits_commands_in_time_order() {
+        let mut track = Track::<f32>::new();
+        // Added in reverse so insertion order differs from time order.
+        let ids: Vec<KeyframeId> = (0..8)
+            .rev()
+            .map(|i| track.add_keyframe(Keyframe::new(i as f64 + 0.5, i as f32)))
+            .collect();
+        let selected: HashSet<KeyframeId> = ids.iter().copied().collect();
+        let space = SpaceTransform::new(20.0, 0.0, 200.0);
+        let size = Vec2::new(200.0, 100.0);
+
+        let ctx = egui::Context::default();
+        let hover = egui::RawInput {
+            events: vec![egui::Event::PointerMoved(Pos2::new(100.0, 50.0))],
+            ..Default::default()
+        };
+        let mut delete = hover.clone();
+        delete.modifiers = egui::Modifiers::SHIFT;
+        delete.events.push(egui::Event::Key {
+            key: egui::Key::Delete,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::SHIFT,
+        });
+        let mut commands = Vec::new();
+        for input in [hover.clone(), hover, delete] {
+            offscreen::run_input_frame(&ctx, size, 1.0, input, |ui| {
+                commands = CurveEditor::new(&track, &selected, &space, (0.0, 10.0))
+                    .show(ui)
+                    .commands;
+            });
         }
 
-        Some((anchor_time, anchor_value, time_scale, value_scale))
+        let removed: Vec<KeyframeId> = commands
+            .iter()
+            .map(|command| match command {
+                AnimationCommand::RemoveKeyframePreserving { keyframe_id } => *keyframe_id,
+                other => panic!("unexpected {other:?}"),
+            })
+            .collect();
+        let in_time_order: Vec<KeyframeId> = ids.iter().rev().copied().collect();
+        assert_eq!(removed, in_time_order);
     }
 
-    fn keyframe_to_screen(&self, rect: Rect, kf: &KeyframeView) -> Pos2 {
-        let x = self.space.unit_to_clipped(kf.position);
-        let y = self.value_to_y(rect, kf.value);
-        Pos2::new(x, y)
+    #[test]
+    fn locked_keyframes_are_not_deleted() {
+        let mut track = Track::<f32>::new();
+        let ids: Vec<KeyframeId> = (0..3)
+            .map(|i| track.add_keyframe(Keyframe::new(i as f64 + 0.5, i as f32)))
+            .collect();
+        track.get_keyframe_mut(ids[1]).unwrap().locked = true;
+        let selected: HashSet<KeyframeId> = ids.iter().copied().collect();
+        let space = SpaceTransform::new(20.0, 0.0, 200.0);
+        let size = Vec2::new(200.0, 100.0);
+
+        let ctx = egui::Context::default();
+        let hover = egui::RawInput {
+            events: vec![egui::Event::PointerMoved(Pos2::new(100.0, 50.0))],
+            ..Default::default()
+        };
+        let mut delete = hover.clone();
+        delete.modifiers = egui::Modifiers::SHIFT;
+        delete.events.push(egui::Event::Key {
+            key: egui::Key::Delete,
+            physical_key: None,
+            pressed: true,
+            repeat: false,
+            modifiers: egui::Modifiers::SHIFT,
+        });
+        let removed = |track: &Track<f32>| {
+            let mut commands = Vec::new();
+            for input in [hover.clone(), hover.clone(), delete.clone()] {
+                offscreen::run_input_frame(&ctx, size, 1.0, input, |ui| {
+                    commands = CurveEditor::new(track, &selected, &space, (0.0, 10.0))
+                        .show(ui)
+                        .commands;
+                });
+            }
+            commands.len()
+        };
+
+        assert_eq!(removed(&track), 2);
+        track.locked = true;
+        assert_eq!(removed(&track), 0);
     }
 
-    fn value_to_y(&self, rect: Rect, value: f32) -> f32 {
-        let (min_val, max_val) = self.value_range;
-        let value_range = max_val - min_val;
-        if value_range.abs() < 1e-6 {
-            return rect.center().y;
+    #[test]
+    fn svg_contains_curve_and_keyframes() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.5, 0.0));
+        track.add_keyframe(Keyframe::new(1.5, 10.0));
+
+        let space = SpaceTransform::new(100.0, 0.0, 200.0).with_left_padding(300.0);
+        let svg = CurveEditor::render_svg(&track, &space, Vec2::new(200.0, 100.0));
+
+        assert!(svg.contains("viewBox=\"0 0 200 100\""));
+        // One bezier segment between the keyframes, starting at the first.
+        assert_eq!(svg.matches("<path d=\"M50,80 C").count(), 1);
+        // Two keyframe diamonds.
+        assert_eq!(svg.matches("<polygon").count(), 2);
+    }
+
+    #[test]
+    fn preview_labels_value_at_time() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.5, 0.0));
+        track.add_keyframe(Keyframe::new(1.5, 10.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let svg = CurveEditor::new(&track, &selected, &space, (0.0, 10.0))
+            .preview_time(Some(TimeTick::new(1.0)))
+            .into_svg(Vec2::new(200.0, 100.0));
+
+        assert!(svg.contains(">5.000</text>"));
+        assert!(svg.contains("<circle cx=\"100\" cy=\"50\""));
+    }
+
+    #[test]
+    fn loop_preview_repeats_curve_after_work_range() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(1.0, 10.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 300.0);
+        let render = |loop_preview| {
+            CurveEditor::new(&track, &selected, &space, (0.0, 10.0))
+                .config(CurveEditorConfig {
+                    loop_preview,
+                    ..Default::default()
+                })
+                .work_range(0.0, 1.0)
+                .into_svg(Vec2::new(300.0, 100.0))
+        };
+
+        // After the seam at the work range end the curve starts over at 0.
+        let svg = render(true);
+        assert!(svg.contains("<polyline points=\"100,80 "));
+        // Dashed seams at the end of the first and second iteration.
+        assert!(svg.contains("<line x1=\"200\" y1=\"0\" x2=\"200\" y2=\"4\""));
+        assert!(!render(false).contains("<polyline points=\"100,80 "));
+    }
+
+    #[test]
+    fn draws_modified_curve_overlay() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Linear));
+        track.add_keyframe(Keyframe::new(1.0, 5.0));
+        let mut stack = ModifierStack::new();
+        stack.push(crate::core::modifier::Cycle { offset: true });
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 300.0);
+        let svg = CurveEditor::new(&track, &selected, &space, (0.0, 10.0))
+            .modifiers(&stack)
+            .into_svg(Vec2::new(300.0, 100.0));
+
+        // The cycled curve keeps rising past the last key, to 7.5 at 1.5.
+        assert!(svg.contains("stroke=\"#96dc78\""));
+        assert!(svg.contains(" 150,35 "));
+    }
+
+    #[test]
+    fn time_warp_mode_draws_identity_diagonal() {
+        let track = Track::<f32>::new();
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 300.0);
+        let render = |time_warp| {
+            CurveEditor::new(&track, &selected, &space, (0.0, 3.0))
+                .config(CurveEditorConfig {
+                    time_warp,
+                    ..Default::default()
+                })
+                .into_svg(Vec2::new(300.0, 100.0))
+        };
+
+        // The first dash of the diagonal rises from time 0, value 0 in the
+        // bottom left.
+        let dash = "<line x1=\"0\" y1=\"80\" x2=\"5.88";
+        assert!(render(true).contains(dash));
+        assert!(!render(false).contains(dash));
+    }
+
+    #[test]
+    fn stable_rect_lags_layout_by_one_frame() {
+        let track = Track::<f32>::new();
+        let selected = HashSet::default();
+        let space = SpaceTransform::default();
+        let config = CurveEditorConfig {
+            height_mode: HeightMode::Fill,
+            min_size: Vec2::new(0.0, 50.0),
+            stable_rect: true,
+            ..Default::default()
+        };
+
+        let ctx = egui::Context::default();
+        let frame = |size: Vec2| {
+            let mut rect = Rect::NOTHING;
+            let input = egui::RawInput {
+                screen_rect: Some(Rect::from_min_size(Pos2::ZERO, size)),
+                ..Default::default()
+            };
+            let _ = ctx.run(input, |ctx| {
+                egui::CentralPanel::default()
+                    .frame(egui::Frame::NONE)
+                    .show(ctx, |ui| {
+                        let response = CurveEditor::new(&track, &selected, &space, (0.0, 1.0))
+                            .config(config.clone())
+                            .id_source("editor")
+                            .show(ui);
+                        rect = response.response.unwrap().rect;
+                    });
+            });
+            rect.size()
+        };
+
+        assert_eq!(frame(Vec2::new(200.0, 100.0)), Vec2::new(200.0, 100.0));
+        assert_eq!(frame(Vec2::new(300.0, 30.0)), Vec2::new(200.0, 100.0));
+        assert_eq!(frame(Vec2::new(300.0, 30.0)), Vec2::new(300.0, 50.0));
+    }
+
+    #[test]
+    fn image_is_filled_with_background() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.5, 0.0));
+        track.add_keyframe(Keyframe::new(1.5, 10.0));
+
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let image = CurveEditor::render_image(&track, &space, Vec2::new(200.0, 100.0), 2.0);
+
+        assert_eq!(image.size, [400, 200]);
+        assert!(image.pixels.iter().all(|pixel| pixel.a() == 255));
+        // Most pixels lie between grid lines.
+        let background = CurveEditorConfig::default().background;
+        let filled = image.pixels.iter().filter(|p| **p == background).count();
+        assert!(filled > image.pixels.len() / 2);
+    }
+
+    #[test]
+    fn scale_edge_lands_on_target() {
+        let track = Track::<f32>::new();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let data = [
+            (KeyframeId::new(), TimeTick::new(1.0), 0.0),
+            (KeyframeId::new(), TimeTick::new(3.0), 1.0),
+        ];
+        let selected: HashSet<KeyframeId> = data.iter().map(|(id, ..)| *id).collect();
+
+        // Anchored at the start, the right edge moves from 3 to 4.
+        let editor =
+            CurveEditor::new(&track, &selected, &space, (0.0, 1.0)).anchor_mode(AnchorMode::Start);
+        let (_, _, time_scale, _) = editor
+            .calculate_scale_from_drag(BoundingBoxHandle::Right, (TimeTick::new(4.0), 0.5), &data)
+            .unwrap();
+        assert!((time_scale - 1.5).abs() < 1e-9);
+
+        // Anchored at the center (2), the left edge moves from 1 to 0.5.
+        let editor = editor.anchor_mode(AnchorMode::Center);
+        let (_, _, time_scale, _) = editor
+            .calculate_scale_from_drag(BoundingBoxHandle::Left, (TimeTick::new(0.5), 0.5), &data)
+            .unwrap();
+        assert!((time_scale - 1.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn handle_drag_keeps_tangent_aligned() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0));
+        let middle = track.add_keyframe(Keyframe::new(1.0, 10.0));
+        track.add_keyframe(Keyframe::new(2.0, 0.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let editor = CurveEditor::new(&track, &selected, &space, (0.0, 10.0));
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 200.0));
+
+        let keyframes = KeyframeSource::keyframes_sorted(&track);
+        let original: Vec<&KeyframeView> = keyframes.iter().collect();
+        let kf_pos = editor.keyframe_to_screen(rect, original[1]);
+        let pointer = kf_pos + Vec2::new(30.0, 20.0);
+
+        let (_, handles) = editor
+            .dragged_handles(rect, &original, middle, HandleSide::Right, pointer, false)
+            .unwrap();
+        let mut view = original[1].clone();
+        view.handles = handles;
+        let refs = [original[0], &view, original[2]];
+        let left = editor
+            .handle_geometry(rect, &view, &refs, HandleSide::Left)
+            .unwrap();
+        let right = editor
+            .handle_geometry(rect, &view, &refs, HandleSide::Right)
+            .unwrap();
+
+        let (a, b) = (left.position - kf_pos, right.position - kf_pos);
+        assert!((a.x * b.y - a.y * b.x).abs() < 1e-2);
+        assert!(a.dot(b) < 0.0);
+        assert!((right.position - pointer).length() < 1e-3);
+
+        // Shift leaves the opposite handle alone.
+        let (_, broken) = editor
+            .dragged_handles(rect, &original, middle, HandleSide::Right, pointer, true)
+            .unwrap();
+        assert_eq!(
+            broken.left_x,
+            track.get_keyframe(middle).unwrap().handles.left_x
+        );
+        assert_eq!(
+            broken.left_y,
+            track.get_keyframe(middle).unwrap().handles.left_y
+        );
+    }
+
+    #[test]
+    fn handle_drag_commits_once_on_release() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, 0.0));
+        let middle = track.add_keyframe(Keyframe::new(1.0, 10.0));
+        track.add_keyframe(Keyframe::new(2.0, 0.0));
+
+        let selected: HashSet<KeyframeId> = [middle].into_iter().collect();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let size = Vec2::new(200.0, 200.0);
+        let editor = || CurveEditor::new(&track, &selected, &space, (0.0, 10.0));
+
+        let ctx = egui::Context::default();
+        let mut rect = Rect::NOTHING;
+        offscreen::run_input_frame(&ctx, size, 1.0, Default::default(), |ui| {
+            rect = editor().show(ui).response.unwrap().rect;
+        });
+        let keyframes = KeyframeSource::keyframes_sorted(&track);
+        let refs: Vec<&KeyframeView> = keyframes.iter().collect();
+        let start = editor()
+            .handle_geometry(rect, refs[1], &refs, HandleSide::Right)
+            .unwrap()
+            .position;
+
+        let input = |event: egui::Event| egui::RawInput {
+            events: vec![event],
+            ..Default::default()
+        };
+        let button = |pos: Pos2, pressed: bool| {
+            input(egui::Event::PointerButton {
+                pos,
+                button: egui::PointerButton::Primary,
+                pressed,
+                modifiers: Default::default(),
+            })
+        };
+        let end = start + Vec2::new(10.0, 20.0);
+        let frames = [
+            input(egui::Event::PointerMoved(start)),
+            button(start, true),
+            // Past the drag threshold, within grab distance of the handle.
+            input(egui::Event::PointerMoved(start + Vec2::new(0.0, 6.5))),
+            input(egui::Event::PointerMoved(end)),
+            button(end, false),
+        ];
+        let mut drags = Vec::new();
+        let mut commands = Vec::new();
+        for input in frames {
+            offscreen::run_input_frame(&ctx, size, 1.0, input, |ui| {
+                let response = editor().show(ui);
+                drags.extend(response.handle_drag.map(|drag| drag.phase));
+                commands.extend(response.commands);
+            });
         }
 
-        let normalized = (value - min_val) / value_range;
-        let usable_height = rect.height() - 2.0 * self.config.vertical_padding;
-        rect.bottom() - self.config.vertical_padding - normalized * usable_height
+        assert_eq!(
+            drags,
+            [DragPhase::Preview, DragPhase::Preview, DragPhase::Commit]
+        );
+        let [AnimationCommand::SetKeyframeHandles { keyframe_id, .. }] = commands.as_slice() else {
+            panic!("expected one handle command, got {commands:?}");
+        };
+        assert_eq!(*keyframe_id, middle);
     }
 
-    fn y_to_value(&self, rect: Rect, y: f32) -> f32 {
-        let (min_val, max_val) = self.value_range;
-        let value_range = max_val - min_val;
+    #[test]
+    fn hit_test_priorities() {
+        let mut track = Track::<f32>::new();
+        let first = track.add_keyframe(Keyframe::new(0.0, 0.0));
+        let second = track.add_keyframe(Keyframe::new(1.0, 10.0));
+
+        let selected: HashSet<KeyframeId> = [first].into_iter().collect();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let editor = CurveEditor::new(&track, &selected, &space, (0.0, 10.0));
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 200.0));
+
+        let keyframes = KeyframeSource::keyframes_sorted(&track);
+        let kf_pos = editor.keyframe_to_screen(rect, &keyframes[0]);
+        assert_eq!(
+            editor.hit_test(rect, kf_pos + Vec2::new(2.0, -2.0)),
+            Some(CurveEditorHit::Keyframe { keyframe_id: first })
+        );
+        // Just off the middle of the line from (0, 180) to (100, 20).
+        let Some(CurveEditorHit::Curve(point)) = editor.hit_test(rect, Pos2::new(53.0, 100.0))
+        else {
+            panic!("expected a curve hit");
+        };
+        assert_eq!(point.segment, (first, second));
+        assert!(point.distance < 3.0);
+        assert!((point.value - 5.0).abs() < 0.5);
+        assert!(matches!(
+            editor.hit_test(rect, Pos2::new(150.0, 20.0)),
+            Some(CurveEditorHit::Background { .. })
+        ));
+        assert_eq!(editor.hit_test(rect, Pos2::new(-5.0, 20.0)), None);
+    }
+
+    #[test]
+    fn closest_on_polyline_projects_onto_nearest_line() {
+        let points = [
+            Pos2::new(0.0, 0.0),
+            Pos2::new(10.0, 0.0),
+            Pos2::new(10.0, 10.0),
+        ];
+        assert_eq!(
+            closest_on_polyline(&points, Pos2::new(4.0, 3.0)),
+            (3.0, Pos2::new(4.0, 0.0))
+        );
+        assert_eq!(
+            closest_on_polyline(&points, Pos2::new(13.0, 6.0)),
+            (3.0, Pos2::new(10.0, 6.0))
+        );
+        // Past the ends the closest point is clamped to the end point.
+        assert_eq!(
+            closest_on_polyline(&points, Pos2::new(-3.0, -4.0)),
+            (5.0, Pos2::new(0.0, 0.0))
+        );
+        assert_eq!(
+            closest_on_polyline(&points[..1], Pos2::new(0.0, 2.0)),
+            (2.0, Pos2::ZERO)
+        );
+        assert_eq!(
+            closest_on_polyline(&[], Pos2::new(1.0, 1.0)).0,
+            f32::INFINITY
+        );
+    }
 
-        let usable_height = rect.height() - 2.0 * self.config.vertical_padding;
-        let normalized = (rect.bottom() - self.config.vertical_padding - y) / usable_height;
-        min_val + normalized * value_range
+    #[test]
+    fn distance_to_curve_follows_hold_steps() {
+        let mut track = Track::<f32>::new();
+        let first = track.add_keyframe(Keyframe::new(0.0, 0.0).with_type(KeyframeType::Hold));
+        let second = track.add_keyframe(Keyframe::new(1.0, 10.0));
+        let third = track.add_keyframe(Keyframe::new(2.0, 0.0));
+
+        let selected = HashSet::default();
+        let space = SpaceTransform::new(100.0, 0.0, 200.0);
+        let editor = CurveEditor::new(&track, &selected, &space, (0.0, 10.0));
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(200.0, 200.0));
+        let bottom = editor.value_to_y(rect, 0.0);
+
+        // The hold stays at 0 until the step at the second key.
+        let point = editor
+            .distance_to_curve(rect, Pos2::new(50.0, bottom - 4.0))
+            .unwrap();
+        assert_eq!(point.segment, (first, second));
+        assert!((point.distance - 4.0).abs() < 1e-3);
+        assert!(point.value.abs() < 1e-3);
+
+        let point = editor
+            .distance_to_curve(rect, Pos2::new(190.0, bottom - 2.0))
+            .unwrap();
+        assert_eq!(point.segment, (second, third));
+
+        let mut single = Track::<f32>::new();
+        single.add_keyframe(Keyframe::new(0.0, 0.0));
+        let editor = CurveEditor::new(&single, &selected, &space, (0.0, 10.0));
+        assert_eq!(editor.distance_to_curve(rect, Pos2::ZERO), None);
     }
 }
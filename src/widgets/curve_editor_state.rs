@@ -0,0 +1,235 @@
+//! View state for a [`CurveEditor`] that applies its own pan and zoom.
+
+use crate::HashSet;
+use crate::core::keyframe::KeyframeId;
+use crate::traits::KeyframeSource;
+use crate::widgets::curve_editor::{CurveEditor, CurveEditorResponse, display_range};
+use crate::widgets::value_ruler::zoom_range;
+use crate::{SpaceTransform, TimeTick};
+use egui::{Context, Id, Ui};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Fraction of the fitted range added as margin on each side.
+const FIT_PADDING: f64 = 0.1;
+
+/// Time and value view of a [`CurveEditor`].
+///
+/// [`CurveEditor`] reports pan, zoom and fit-view requests and leaves applying
+/// them to the host. This state applies them itself:
+///
+/// ```ignore
+/// let mut state = CurveEditorState::load(ui.ctx(), id);
+/// let response = state.show(ui, &track, &selected);
+/// state.store(ui.ctx(), id);
+/// ```
+///
+/// The view is fitted to the keyframes the first time it is shown.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct CurveEditorState {
+    /// Time axis.
+    pub space: SpaceTransform,
+    /// Value range `(min, max)` shown over the editor height.
+    pub value_range: (f32, f32),
+    fitted: bool,
+}
+
+impl Default for CurveEditorState {
+    fn default() -> Self {
+        Self {
+            space: SpaceTransform::default(),
+            value_range: (0.0, 1.0),
+            fitted: false,
+        }
+    }
+}
+
+impl CurveEditorState {
+    /// Create a state showing `value_range` with the time axis of `space`.
+    pub fn new(space: SpaceTransform, value_range: (f32, f32)) -> Self {
+        Self {
+            space,
+            value_range,
+            fitted: true,
+        }
+    }
+
+    /// Load the state stored under `id` in egui memory, or a new state that
+    /// fits its first curve.
+    pub fn load(ctx: &Context, id: Id) -> Self {
+        ctx.data(|data| data.get_temp(id)).unwrap_or_default()
+    }
+
+    /// Store the state under `id` in egui memory.
+    pub fn store(&self, ctx: &Context, id: Id) {
+        ctx.data_mut(|data| data.insert_temp(id, self.clone()));
+    }
+
+    /// Show a [`CurveEditor`] for `source` with this view and apply the
+    /// view changes it requests.
+    pub fn show<S: KeyframeSource>(
+        &mut self,
+        ui: &mut Ui,
+        source: &S,
+        selected: &HashSet<KeyframeId>,
+    ) -> CurveEditorResponse {
+        self.show_with(ui, source, selected, |editor| editor)
+    }
+
+    /// Like [`Self::show`], with `configure` applied to the editor, e.g. to set
+    /// its config or snapping.
+    pub fn show_with<S: KeyframeSource>(
+        &mut self,
+        ui: &mut Ui,
+        source: &S,
+        selected: &HashSet<KeyframeId>,
+        configure: impl for<'e> FnOnce(CurveEditor<'e, S>) -> CurveEditor<'e, S>,
+    ) -> CurveEditorResponse {
+        // The editor spans the available width from the cursor.
+        self.space.left_padding = ui.cursor().left();
+        self.space.visible_width = ui.available_width();
+        if !self.fitted {
+            self.fit(source);
+        }
+
+        let view = self.clone();
+        let response = configure(CurveEditor::new(
+            source,
+            selected,
+            &view.space,
+            view.value_range,
+        ))
+        .show(ui);
+        if response.fit_selection {
+            self.fit_selection(source, selected);
+        } else {
+            self.apply(&response, source);
+        }
+        response
+    }
+
+    /// Apply the pan, zoom and fit-view requests in `response`.
+    ///
+    /// [`CurveEditorResponse::fit_selection`] needs the selection; handle it
+    /// with [`Self::fit_selection`].
+    pub fn apply<S: KeyframeSource>(&mut self, response: &CurveEditorResponse, source: &S) {
+        if response.fit_view {
+            self.fit(source);
+            return;
+        }
+
+        if let Some((factor, center)) = response.zoom_horizontal {
+            self.space = self
+                .space
+                .zoom_at(self.space.unit_to_clipped(center), factor as f64);
+        }
+        if let Some(factor) = response.zoom_vertical {
+            let (min, max) = self.value_range;
+            self.value_range = zoom_range(self.value_range, (min + max) / 2.0, factor);
+        }
+        if let Some(delta) = response.pan_delta {
+            self.space = self.space.pan(delta.x);
+            if let Some(height) = response.response.as_ref().map(|r| r.rect.height()) {
+                let (min, max) = self.value_range;
+                let value_per_pixel = (max - min) / height.max(1.0);
+                let shift = delta.y * value_per_pixel;
+                self.value_range = (min + shift, max + shift);
+            }
+        }
+    }
+
+    /// Fit the view to all keyframes of `source` with some margin.
+    pub fn fit<S: KeyframeSource>(&mut self, source: &S) {
+        self.fitted = true;
+        let keyframes = source.keyframes_sorted();
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return;
+        };
+        self.fit_ranges((first.position, last.position), source.value_range());
+    }
+
+    /// Fit the view to the keyframes of `source` in `selected` with some
+    /// margin. Does nothing if none of them are in `source`.
+    pub fn fit_selection<S: KeyframeSource>(&mut self, source: &S, selected: &HashSet<KeyframeId>) {
+        let keyframes: Vec<_> = source
+            .keyframes_sorted()
+            .into_iter()
+            .filter(|kf| selected.contains(&kf.id))
+            .collect();
+        let (Some(first), Some(last)) = (keyframes.first(), keyframes.last()) else {
+            return;
+        };
+        let values = keyframes
+            .iter()
+            .fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), kf| {
+                (min.min(kf.value), max.max(kf.value))
+            });
+        self.fit_ranges((first.position, last.position), Some(values));
+    }
+
+    /// Show `(first, last)` in time and `values` with padding, widening empty
+    /// ranges.
+    fn fit_ranges(&mut self, (first, last): (TimeTick, TimeTick), values: Option<(f32, f32)>) {
+        let (start, end) = if last > first {
+            (first, last)
+        } else {
+            (first - TimeTick::new(1.0), first + TimeTick::new(1.0))
+        };
+        self.space = self.space.fit_range(start, end, FIT_PADDING);
+
+        let (min, max) = display_range(values);
+        let margin = (max - min) * FIT_PADDING as f32;
+        self.value_range = (min - margin, max + margin);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::keyframe::Keyframe;
+    use crate::core::track::Track;
+
+    #[test]
+    fn fit_and_zoom() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(1.0, 0.0));
+        track.add_keyframe(Keyframe::new(3.0, 100.0));
+
+        let mut state = CurveEditorState::default();
+        state.space.visible_width = 240.0;
+        state.fit(&track);
+        assert_eq!(state.value_range, (-10.0, 110.0));
+        let (start, end) = state.space.visible_range();
+        assert!((start.value() - 0.8).abs() < 1e-9);
+        assert!((end.value() - 3.2).abs() < 1e-9);
+
+        let response = CurveEditorResponse {
+            zoom_vertical: Some(2.0),
+            ..Default::default()
+        };
+        state.apply(&response, &track);
+        assert_eq!(state.value_range, (20.0, 80.0));
+    }
+
+    #[test]
+    fn fit_selection_frames_selected_keyframes() {
+        let mut track = Track::<f32>::new();
+        track.add_keyframe(Keyframe::new(0.0, -50.0));
+        let second = track.add_keyframe(Keyframe::new(1.0, 0.0));
+        let third = track.add_keyframe(Keyframe::new(3.0, 10.0));
+
+        let mut state = CurveEditorState::default();
+        state.space.visible_width = 240.0;
+        state.fit_selection(&track, &HashSet::from_iter([second, third]));
+        assert_eq!(state.value_range, (-1.0, 11.0));
+        let (start, end) = state.space.visible_range();
+        assert!((start.value() - 0.8).abs() < 1e-9);
+        assert!((end.value() - 3.2).abs() < 1e-9);
+
+        let before = state.clone();
+        state.fit_selection(&track, &HashSet::default());
+        assert_eq!(state, before);
+    }
+}
@@ -0,0 +1,181 @@
+//! Dialog for generating a curve from a [`Generator`].
+//!
+//! The host opens the dialog, shows it every frame and inserts the track it
+//! returns:
+//!
+//! ```ignore
+//! let id = egui::Id::new("lfo");
+//! if ui.button("Generate…").clicked() {
+//!     GeneratorDialog::open(ui.ctx(), id);
+//! }
+//! if let Some(track) = GeneratorDialog::new(id, 0.0, 4.0).show(ui.ctx()) {
+//!     model.add_track(&group, "LFO", track);
+//! }
+//! ```
+
+use crate::core::generators::{Generator, Waveform};
+use crate::core::time::TimeTick;
+use crate::core::track::Track;
+use egui::{Color32, Pos2, Rect, Sense, Shape, Stroke, Vec2};
+
+/// Size of the preview plot.
+const PREVIEW_SIZE: Vec2 = Vec2::new(260.0, 80.0);
+
+/// Window editing a [`Generator`] with a preview of the track it produces.
+pub struct GeneratorDialog {
+    id: egui::Id,
+    range: (TimeTick, TimeTick),
+}
+
+impl GeneratorDialog {
+    /// Create the dialog `id` generating keys from `start` to `end`.
+    pub fn new(id: egui::Id, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        Self {
+            id,
+            range: (start.into(), end.into()),
+        }
+    }
+
+    /// Open dialog `id` with a default sine.
+    pub fn open(ctx: &egui::Context, id: egui::Id) {
+        ctx.data_mut(|data| data.insert_temp(id, Generator::default()));
+    }
+
+    /// Check if dialog `id` is open.
+    pub fn is_open(ctx: &egui::Context, id: egui::Id) -> bool {
+        ctx.data(|data| data.get_temp::<Generator>(id).is_some())
+    }
+
+    /// Show the dialog if it is open; returns the generated track when the
+    /// user clicks "Insert".
+    pub fn show(self, ctx: &egui::Context) -> Option<Track<f32>> {
+        let mut generator = ctx.data(|data| data.get_temp::<Generator>(self.id))?;
+
+        let mut open = true;
+        let mut inserted = false;
+        let mut cancelled = false;
+        egui::Window::new("Generate curve")
+            .id(self.id)
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                egui::Grid::new(self.id.with("grid")).show(ui, |ui| {
+                    ui.label("Waveform");
+                    egui::ComboBox::from_id_salt(self.id.with("waveform"))
+                        .selected_text(generator.waveform.label())
+                        .show_ui(ui, |ui| {
+                            for waveform in Waveform::ALL {
+                                ui.selectable_value(
+                                    &mut generator.waveform,
+                                    waveform,
+                                    waveform.label(),
+                                );
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Frequency");
+                    ui.add(
+                        egui::DragValue::new(&mut generator.frequency)
+                            .speed(0.01)
+                            .range(0.01..=100.0),
+                    );
+                    ui.end_row();
+
+                    ui.label("Amplitude");
+                    ui.add(egui::DragValue::new(&mut generator.amplitude).speed(0.01));
+                    ui.end_row();
+
+                    ui.label("Phase");
+                    ui.add(
+                        egui::DragValue::new(&mut generator.phase)
+                            .speed(0.01)
+                            .range(0.0..=1.0),
+                    );
+                    ui.end_row();
+
+                    ui.label("Offset");
+                    ui.add(egui::DragValue::new(&mut generator.offset).speed(0.01));
+                    ui.end_row();
+
+                    if generator.waveform == Waveform::Noise {
+                        ui.label("Seed");
+                        ui.add(egui::DragValue::new(&mut generator.seed));
+                        ui.end_row();
+                    }
+                });
+
+                let track = generator.track(self.range.0, self.range.1);
+                let (rect, _) = ui.allocate_exact_size(PREVIEW_SIZE, Sense::hover());
+                paint_preview(ui.painter(), rect, &track, self.range);
+
+                ui.horizontal(|ui| {
+                    inserted = ui.button("Insert").clicked();
+                    cancelled = ui.button("Cancel").clicked();
+                });
+            });
+
+        if inserted || cancelled || !open {
+            ctx.data_mut(|data| data.remove::<Generator>(self.id));
+        } else {
+            ctx.data_mut(|data| data.insert_temp(self.id, generator));
+        }
+
+        inserted.then(|| generator.track(self.range.0, self.range.1))
+    }
+}
+
+/// Plot `track` over `range` in `rect` with a dot per keyframe.
+fn paint_preview(
+    painter: &egui::Painter,
+    rect: Rect,
+    track: &Track<f32>,
+    range: (TimeTick, TimeTick),
+) {
+    painter.rect_filled(rect, 2.0, Color32::from_gray(25));
+
+    let duration = (range.1 - range.0).value();
+    let Some((low, high)) = track.value_range() else {
+        return;
+    };
+    let span = if high > low { high - low } else { 1.0 };
+    let to_screen = |time: TimeTick, value: f32| {
+        let x = if duration > 0.0 {
+            ((time - range.0).value() / duration) as f32
+        } else {
+            0.0
+        };
+        let y = (value - low) / span;
+        Pos2::new(
+            rect.left() + 4.0 + x * (rect.width() - 8.0),
+            rect.bottom() - 4.0 - y * (rect.height() - 8.0),
+        )
+    };
+
+    let mid_y = to_screen(range.0, (low + high) / 2.0).y;
+    painter.hline(
+        rect.x_range(),
+        mid_y,
+        Stroke::new(1.0, Color32::from_gray(50)),
+    );
+
+    let steps = rect.width().max(1.0) as usize;
+    let points: Vec<Pos2> = (0..=steps)
+        .filter_map(|i| {
+            let time = range.0 + TimeTick::new(duration * i as f64 / steps as f64);
+            track.evaluate(time).map(|value| to_screen(time, value))
+        })
+        .collect();
+    painter.add(Shape::line(
+        points,
+        Stroke::new(1.5, Color32::from_rgb(120, 180, 255)),
+    ));
+    for keyframe in track.iter() {
+        painter.circle_filled(
+            to_screen(keyframe.position, keyframe.value),
+            2.0,
+            Color32::WHITE,
+        );
+    }
+}
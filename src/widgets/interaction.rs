@@ -0,0 +1,116 @@
+//! Pointer hit and grab sizes shared by the widgets.
+
+use egui::{Context, Event, Id};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Sizes of the areas in which the pointer hits or grabs widget elements, in
+/// points at the app's native scale.
+///
+/// Widgets resolve them with [`Self::for_context`] each frame, which keeps
+/// them the same physical size when the app zooms its UI and enlarges them
+/// while the user works with touch input.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct InteractionSizes {
+    /// Manhattan distance within which the pointer hits a keyframe.
+    pub keyframe_hit: f32,
+    /// Distance within which the pointer grabs a bezier handle.
+    pub handle_hit: f32,
+    /// Distance within which the pointer hits a curve segment.
+    #[cfg_attr(feature = "serde", serde(default = "default_curve_hit"))]
+    pub curve_hit: f32,
+    /// Side of the square in which the pointer grabs a bounding box handle.
+    pub bbox_handle_hit: f32,
+    /// Horizontal distance within which the pointer grabs the playhead,
+    /// markers and work range brackets.
+    pub edge_grab: f32,
+    /// Factor applied to all sizes while the user works with touch input.
+    pub touch_scale: f32,
+}
+
+impl Default for InteractionSizes {
+    fn default() -> Self {
+        Self {
+            keyframe_hit: 12.0,
+            handle_hit: 7.0,
+            curve_hit: default_curve_hit(),
+            bbox_handle_hit: 6.0,
+            edge_grab: 5.0,
+            touch_scale: 2.0,
+        }
+    }
+}
+
+impl InteractionSizes {
+    /// All sizes multiplied by `factor`.
+    pub fn scaled(&self, factor: f32) -> Self {
+        Self {
+            keyframe_hit: self.keyframe_hit * factor,
+            handle_hit: self.handle_hit * factor,
+            curve_hit: self.curve_hit * factor,
+            bbox_handle_hit: self.bbox_handle_hit * factor,
+            edge_grab: self.edge_grab * factor,
+            touch_scale: self.touch_scale,
+        }
+    }
+
+    /// Sizes in points for the current frame of `ctx`.
+    ///
+    /// Divides by egui's zoom factor, so zooming the UI out does not shrink
+    /// the hit areas on screen, and applies [`Self::touch_scale`] while
+    /// [`touch_input`] is true.
+    pub fn for_context(&self, ctx: &Context) -> Self {
+        let mut factor = 1.0 / ctx.zoom_factor().max(0.1);
+        if touch_input(ctx) {
+            factor *= self.touch_scale;
+        }
+        self.scaled(factor)
+    }
+}
+
+fn default_curve_hit() -> f32 {
+    5.0
+}
+
+/// Whether the most recent pointer input came from a touch screen.
+///
+/// Updated from the events of each frame: touches switch it on, mouse
+/// movement without touches switches it off.
+pub fn touch_input(ctx: &Context) -> bool {
+    let id = Id::new("egui_keyframe_touch_input");
+    let (touched, moved) = ctx.input(|i| {
+        (
+            i.events.iter().any(|e| matches!(e, Event::Touch { .. })),
+            i.events.iter().any(|e| matches!(e, Event::PointerMoved(_))),
+        )
+    });
+    let mut touch = ctx.data(|data| data.get_temp::<bool>(id)).unwrap_or(false);
+    if touched {
+        touch = true;
+    } else if moved {
+        touch = false;
+    }
+    ctx.data_mut(|data| data.insert_temp(id, touch));
+    touch
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sizes_follow_ui_zoom() {
+        let ctx = Context::default();
+        let sizes = InteractionSizes::default();
+        assert_eq!(sizes.for_context(&ctx), sizes);
+
+        ctx.set_zoom_factor(0.5);
+        // The zoom factor applies from the next frame.
+        let _ = ctx.run(Default::default(), |_| {});
+        let zoomed = sizes.for_context(&ctx);
+        assert_eq!(zoomed.keyframe_hit, 24.0);
+        assert_eq!(zoomed.touch_scale, sizes.touch_scale);
+    }
+}
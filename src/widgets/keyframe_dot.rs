@@ -1,6 +1,129 @@
 //! Keyframe dot/diamond marker.
 
-use egui::{Color32, Painter, Pos2, Stroke};
+use crate::core::keyframe::{KeyframeId, KeyframeTag};
+use crate::widgets::reduced_motion::reduced_motion;
+use egui::{Color32, Painter, Pos2, Rect, Stroke, TextureId, Vec2};
+
+/// Color unselected breakdown keyframes are tinted towards.
+pub const BREAKDOWN_TINT: Color32 = Color32::from_rgb(120, 220, 160);
+
+/// How a keyframe is drawn this frame.
+///
+/// Widgets compute it for every visible keyframe and report it in their
+/// responses, so hosts can paint overlays that agree with the dots.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct KeyframeVisualState {
+    /// The keyframe is selected.
+    pub selected: bool,
+    /// The pointer is over the keyframe.
+    pub hovered: bool,
+    /// The keyframe or its row is locked against edits; drawn desaturated.
+    pub locked: bool,
+    /// The keyframe's track is muted or left out by a solo; grayed out.
+    pub muted: bool,
+    /// The keyframe is pinned against batch edits.
+    pub pinned: bool,
+    /// The keyframe is a breakdown; drawn smaller and tinted.
+    pub breakdown: bool,
+    /// Color tag of the keyframe; drawn in the tag's color.
+    pub tag: Option<KeyframeTag>,
+    /// An ongoing drag moves the keyframe.
+    pub dragged: bool,
+}
+
+/// A keyframe dot drawn by a widget, see [`KeyframeVisualState`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyframeVisual {
+    /// The keyframe.
+    pub keyframe_id: KeyframeId,
+    /// Center of the dot in screen coordinates.
+    pub pos: Pos2,
+    /// How the dot is drawn.
+    pub state: KeyframeVisualState,
+}
+
+/// Images that replace the painted diamond, as UV rects into one texture
+/// atlas, one per state.
+///
+/// States take precedence in the order locked, selected, hovered, normal.
+/// Hovered dots are drawn enlarged regardless.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KeyframeDotTextures {
+    /// The atlas texture.
+    pub texture: TextureId,
+    /// UV rect of the normal state.
+    pub normal: Rect,
+    /// UV rect of the selected state.
+    pub selected: Rect,
+    /// UV rect of the hovered state.
+    pub hovered: Rect,
+    /// UV rect of the locked state.
+    pub locked: Rect,
+    /// Multiply the image by the dot color, e.g. for white icons that should
+    /// pick up row colors.
+    pub tint: bool,
+}
+
+impl KeyframeDotTextures {
+    /// Use the `normal` UV rect of `texture` for every state.
+    pub fn new(texture: TextureId, normal: Rect) -> Self {
+        Self {
+            texture,
+            normal,
+            selected: normal,
+            hovered: normal,
+            locked: normal,
+            tint: false,
+        }
+    }
+
+    /// Set the UV rect of the selected state.
+    pub fn selected(mut self, uv: Rect) -> Self {
+        self.selected = uv;
+        self
+    }
+
+    /// Set the UV rect of the hovered state.
+    pub fn hovered(mut self, uv: Rect) -> Self {
+        self.hovered = uv;
+        self
+    }
+
+    /// Set the UV rect of the locked state.
+    pub fn locked(mut self, uv: Rect) -> Self {
+        self.locked = uv;
+        self
+    }
+
+    /// Set whether the image is tinted with the dot color.
+    pub fn tint(mut self, tint: bool) -> Self {
+        self.tint = tint;
+        self
+    }
+
+    /// UV rect for a dot in the given state.
+    pub fn uv(&self, selected: bool, hovered: bool, locked: bool) -> Rect {
+        if locked {
+            self.locked
+        } else if selected {
+            self.selected
+        } else if hovered {
+            self.hovered
+        } else {
+            self.normal
+        }
+    }
+}
+
+/// Hover text of a keyframe with `label` and `tag`, if it has either.
+pub(crate) fn keyframe_tooltip(label: Option<&str>, tag: Option<KeyframeTag>) -> Option<String> {
+    match (label, tag) {
+        (Some(label), Some(tag)) => Some(format!("{label} ({})", tag.label())),
+        (Some(label), None) => Some(label.to_string()),
+        (None, Some(tag)) => Some(tag.label().to_string()),
+        (None, None) => None,
+    }
+}
 
 /// Renders a keyframe marker (diamond shape).
 pub struct KeyframeDot {
@@ -14,6 +137,21 @@ pub struct KeyframeDot {
     pub selected: bool,
     /// Whether this keyframe is hovered.
     pub hovered: bool,
+    /// Whether this keyframe is locked against edits; drawn desaturated.
+    pub locked: bool,
+    /// Whether this keyframe's track is silenced by a mute or solo; grayed
+    /// out.
+    pub muted: bool,
+    /// Whether this keyframe is pinned against batch edits; adds a lock
+    /// glyph.
+    pub pinned: bool,
+    /// Whether this keyframe is a breakdown; drawn smaller and tinted with
+    /// [`BREAKDOWN_TINT`].
+    pub breakdown: bool,
+    /// Color tag; replaces `color` when set.
+    pub tag: Option<KeyframeTag>,
+    /// Images drawn instead of the diamond.
+    pub textures: Option<KeyframeDotTextures>,
 }
 
 impl KeyframeDot {
@@ -25,6 +163,12 @@ impl KeyframeDot {
             color: Color32::from_rgb(100, 150, 255),
             selected: false,
             hovered: false,
+            locked: false,
+            muted: false,
+            pinned: false,
+            breakdown: false,
+            tag: None,
+            textures: None,
         }
     }
 
@@ -52,22 +196,102 @@ impl KeyframeDot {
         self
     }
 
+    /// Set locked state.
+    pub fn locked(mut self, locked: bool) -> Self {
+        self.locked = locked;
+        self
+    }
+
+    /// Set muted state.
+    pub fn muted(mut self, muted: bool) -> Self {
+        self.muted = muted;
+        self
+    }
+
+    /// Set pinned state.
+    pub fn pinned(mut self, pinned: bool) -> Self {
+        self.pinned = pinned;
+        self
+    }
+
+    /// Set breakdown state.
+    pub fn breakdown(mut self, breakdown: bool) -> Self {
+        self.breakdown = breakdown;
+        self
+    }
+
+    /// Set the color tag.
+    pub fn tag(mut self, tag: Option<KeyframeTag>) -> Self {
+        self.tag = tag;
+        self
+    }
+
+    /// Set the selected, hovered, locked, muted, pinned and breakdown states
+    /// and the tag at once.
+    pub fn state(mut self, state: KeyframeVisualState) -> Self {
+        self.selected = state.selected;
+        self.hovered = state.hovered;
+        self.locked = state.locked;
+        self.muted = state.muted;
+        self.pinned = state.pinned;
+        self.breakdown = state.breakdown;
+        self.tag = state.tag;
+        self
+    }
+
+    /// Draw images from a texture atlas instead of the diamond.
+    pub fn textures(mut self, textures: Option<KeyframeDotTextures>) -> Self {
+        self.textures = textures;
+        self
+    }
+
     /// Paint the keyframe dot.
+    ///
+    /// Hovered dots grow unless [`reduced_motion`] is on, in which case they
+    /// get a brighter outline instead.
     pub fn paint(&self, painter: &Painter) {
-        let size = if self.hovered {
+        let reduced = reduced_motion(painter.ctx());
+        let mut size = if self.hovered && !reduced {
             self.size * 1.3
         } else {
             self.size
         };
+        if self.breakdown {
+            size *= 0.7;
+        }
 
-        let color = if self.selected {
+        let base = self.tag.map_or(self.color, KeyframeTag::color);
+        let mut color = if self.selected {
             Color32::from_rgb(255, 200, 100)
+        } else if self.breakdown {
+            base.lerp_to_gamma(BREAKDOWN_TINT, 0.6)
         } else {
-            self.color
+            base
         };
+        if self.locked {
+            color = desaturated(color);
+        }
+        if self.muted {
+            color = muted(color);
+        }
 
-        let stroke = if self.selected {
+        if let Some(textures) = &self.textures {
+            let uv = textures.uv(self.selected, self.hovered, self.locked);
+            let tint = if textures.tint { color } else { Color32::WHITE };
+            painter.image(
+                textures.texture,
+                Rect::from_center_size(self.pos, Vec2::splat(2.0 * size)),
+                uv,
+                tint,
+            );
+            self.paint_pin(painter, size);
+            return;
+        }
+
+        let stroke = if self.selected || (self.hovered && reduced) {
             Stroke::new(2.0, Color32::WHITE)
+        } else if self.locked {
+            Stroke::new(1.0, Color32::from_gray(110))
         } else {
             Stroke::new(1.0, Color32::from_gray(200))
         };
@@ -81,6 +305,19 @@ impl KeyframeDot {
         ];
 
         painter.add(egui::Shape::convex_polygon(points, color, stroke));
+        self.paint_pin(painter, size);
+    }
+
+    /// Small padlock at the top right of a pinned dot of half size `size`.
+    fn paint_pin(&self, painter: &Painter, size: f32) {
+        if !self.pinned {
+            return;
+        }
+        let body = Rect::from_min_size(
+            self.pos + Vec2::new(0.6 * size, -1.4 * size),
+            Vec2::new(0.9 * size, 0.7 * size),
+        );
+        paint_padlock(painter, body, Color32::from_gray(230), true);
     }
 
     /// Check if a point is within the hit area.
@@ -94,6 +331,34 @@ impl KeyframeDot {
     }
 }
 
+/// Gray, dimmed version of `color` for locked keyframes.
+pub(crate) fn desaturated(color: Color32) -> Color32 {
+    let [r, g, b, a] = color.to_array();
+    let luma = (0.3 * r as f32 + 0.59 * g as f32 + 0.11 * b as f32) as u8;
+    Color32::from_rgba_premultiplied(luma, luma, luma, a).gamma_multiply(0.6)
+}
+
+/// Faint gray version of `color` for keyframes of silenced tracks.
+pub(crate) fn muted(color: Color32) -> Color32 {
+    desaturated(color).gamma_multiply(0.5)
+}
+
+/// Padlock with its body in `body`; an open one has its shackle raised.
+pub(crate) fn paint_padlock(painter: &Painter, body: Rect, color: Color32, closed: bool) {
+    let lift = if closed { 0.0 } else { 0.3 * body.height() };
+    let shackle = Rect::from_center_size(
+        Pos2::new(body.center().x, body.top() - lift),
+        Vec2::new(0.6 * body.width(), 1.15 * body.height()),
+    );
+    painter.rect_stroke(
+        shackle,
+        shackle.width() / 2.0,
+        Stroke::new(1.0, color),
+        egui::StrokeKind::Middle,
+    );
+    painter.rect_filled(body, 1.0, color);
+}
+
 /// Renders an aggregate keyframe marker (multiple keyframes at same time).
 pub struct AggregateKeyframeDot {
     /// Position in screen coordinates.
@@ -125,7 +390,7 @@ impl AggregateKeyframeDot {
 
     /// Paint the aggregate dot.
     pub fn paint(&self, painter: &Painter) {
-        let size = if self.hovered {
+        let size = if self.hovered && !reduced_motion(painter.ctx()) {
             self.size * 1.3
         } else {
             self.size
@@ -167,3 +432,22 @@ impl AggregateKeyframeDot {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn texture_state_precedence() {
+        let uv = |x: f32| Rect::from_min_size(Pos2::new(x, 0.0), Vec2::splat(0.25));
+        let textures = KeyframeDotTextures::new(TextureId::default(), uv(0.0))
+            .selected(uv(0.25))
+            .hovered(uv(0.5))
+            .locked(uv(0.75));
+
+        assert_eq!(textures.uv(false, false, false), uv(0.0));
+        assert_eq!(textures.uv(false, true, false), uv(0.5));
+        assert_eq!(textures.uv(true, true, false), uv(0.25));
+        assert_eq!(textures.uv(true, true, true), uv(0.75));
+    }
+}
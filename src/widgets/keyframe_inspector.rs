@@ -0,0 +1,401 @@
+//! Numeric fields for the selected keyframes.
+
+use crate::HashSet;
+use crate::TimeTick;
+use crate::core::keyframe::{BezierHandles, KeyframeId, KeyframeTag, KeyframeType};
+use crate::core::track::TrackId;
+use crate::editor_settings::EditorSettings;
+use crate::traits::{AnimationCommand, AnimationDataProvider};
+use egui::{Response, Ui};
+
+/// Interpolation types in menu order, with their labels.
+const TYPES: [(KeyframeType, &str); 5] = [
+    (KeyframeType::Hold, "Hold (Step)"),
+    (KeyframeType::Linear, "Linear"),
+    (KeyframeType::Bezier, "Bezier"),
+    (KeyframeType::Auto, "Auto"),
+    (KeyframeType::Spring, "Spring"),
+];
+
+/// Response from a [`KeyframeInspector`].
+pub struct KeyframeInspectorResponse {
+    /// The egui response for the inspector.
+    pub response: Response,
+    /// Animation commands to execute, for the selected keyframes in row
+    /// order and then time order.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Panel with editable fields for the time, value, interpolation type,
+/// bezier handles, label and tag of the selected keyframes.
+///
+/// With several keyframes selected, fields show the first keyframe's value
+/// and are marked "mixed" where the keyframes differ. Editing the time moves
+/// all selected keyframes by the same amount; editing any other field sets
+/// it on all of them. With [`EditorSettings::auto_key`] on, value edits are
+/// keyed at the playhead on the tracks of the selection instead.
+pub struct KeyframeInspector<'a, P: AnimationDataProvider> {
+    provider: &'a P,
+    selected: &'a HashSet<KeyframeId>,
+    id_salt: egui::Id,
+    settings: EditorSettings,
+}
+
+/// A selected keyframe as shown by the inspector.
+#[derive(Debug, Clone)]
+struct Inspected {
+    id: KeyframeId,
+    track_id: TrackId,
+    position: TimeTick,
+    value: f64,
+    handles: BezierHandles,
+    keyframe_type: KeyframeType,
+    label: Option<String>,
+    tag: Option<KeyframeTag>,
+}
+
+/// A field edited in the inspector.
+#[derive(Debug, Clone)]
+enum Edit {
+    Time(TimeTick),
+    Value(f64),
+    Type(KeyframeType),
+    /// Index into `[left_x, left_y, right_x, right_y]`.
+    Handle(usize, f32),
+    /// Empty to remove the label.
+    Label(String),
+    Tag(Option<KeyframeTag>),
+}
+
+impl<'a, P: AnimationDataProvider> KeyframeInspector<'a, P> {
+    /// Create an inspector for the `selected` keyframes of `provider`.
+    pub fn new(provider: &'a P, selected: &'a HashSet<KeyframeId>) -> Self {
+        Self {
+            provider,
+            selected,
+            id_salt: egui::Id::new("keyframe_inspector"),
+            settings: EditorSettings::default(),
+        }
+    }
+
+    /// Set the editor settings, e.g. to auto-key value edits.
+    pub fn settings(mut self, settings: EditorSettings) -> Self {
+        self.settings = settings;
+        self
+    }
+
+    /// Set the ID salt, to show several inspectors.
+    pub fn id_salt(mut self, id_salt: impl std::hash::Hash) -> Self {
+        self.id_salt = egui::Id::new(id_salt);
+        self
+    }
+
+    /// Show the inspector.
+    pub fn show(self, ui: &mut Ui) -> KeyframeInspectorResponse {
+        let keyframes = self.inspected();
+        let mut edit = None;
+
+        let response = ui
+            .vertical(|ui| {
+                let Some(first) = keyframes.first().cloned() else {
+                    ui.weak("No keyframe selected");
+                    return;
+                };
+                if keyframes.len() > 1 {
+                    ui.label(format!("{} keyframes", keyframes.len()));
+                }
+
+                let mixed = |differs: &dyn Fn(&Inspected) -> bool| keyframes.iter().any(differs);
+                egui::Grid::new(self.id_salt).num_columns(3).show(ui, |ui| {
+                    ui.label("Time");
+                    let mut time = first.position.value();
+                    let field = ui.add(egui::DragValue::new(&mut time).speed(0.01));
+                    if field.changed() {
+                        edit = Some(Edit::Time(TimeTick::new(time)));
+                    }
+                    if keyframes.len() > 1 {
+                        field.on_hover_text("Moves all selected keyframes by the same amount");
+                    }
+                    ui.end_row();
+
+                    ui.label("Value");
+                    let mut value = first.value;
+                    if ui
+                        .add(egui::DragValue::new(&mut value).speed(0.01))
+                        .changed()
+                    {
+                        edit = Some(Edit::Value(value));
+                    }
+                    if mixed(&|kf| kf.value != first.value) {
+                        ui.weak("mixed");
+                    }
+                    ui.end_row();
+
+                    ui.label("Interpolation");
+                    let common_type = (!mixed(&|kf| kf.keyframe_type != first.keyframe_type))
+                        .then_some(first.keyframe_type);
+                    let selected_text = TYPES
+                        .iter()
+                        .find(|(kf_type, _)| Some(*kf_type) == common_type)
+                        .map_or("Mixed", |(_, label)| label);
+                    egui::ComboBox::from_id_salt(self.id_salt.with("type"))
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (kf_type, label) in TYPES {
+                                if ui
+                                    .selectable_label(common_type == Some(kf_type), label)
+                                    .clicked()
+                                {
+                                    edit = Some(Edit::Type(kf_type));
+                                }
+                            }
+                        });
+                    ui.end_row();
+
+                    // Handles of auto keyframes are computed.
+                    if common_type == Some(KeyframeType::Bezier) {
+                        let first_handles = handle_fields(first.handles);
+                        let mut handles = first_handles;
+                        for (side, indices) in [("In", [0, 1]), ("Out", [2, 3])] {
+                            ui.label(side);
+                            ui.horizontal(|ui| {
+                                for index in indices {
+                                    if ui
+                                        .add(egui::DragValue::new(&mut handles[index]).speed(0.01))
+                                        .changed()
+                                    {
+                                        edit = Some(Edit::Handle(index, handles[index]));
+                                    }
+                                }
+                            });
+                            if mixed(&|kf| {
+                                let fields = handle_fields(kf.handles);
+                                indices.iter().any(|&i| fields[i] != first_handles[i])
+                            }) {
+                                ui.weak("mixed");
+                            }
+                            ui.end_row();
+                        }
+                    }
+
+                    ui.label("Label");
+                    let mut label = first.label.clone().unwrap_or_default();
+                    if ui.text_edit_singleline(&mut label).changed() {
+                        edit = Some(Edit::Label(label));
+                    }
+                    if mixed(&|kf| kf.label != first.label) {
+                        ui.weak("mixed");
+                    }
+                    ui.end_row();
+
+                    ui.label("Tag");
+                    let common_tag = (!mixed(&|kf| kf.tag != first.tag)).then_some(first.tag);
+                    let selected_text = match common_tag {
+                        Some(Some(tag)) => tag.label(),
+                        Some(None) => "None",
+                        None => "Mixed",
+                    };
+                    egui::ComboBox::from_id_salt(self.id_salt.with("tag"))
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for tag in std::iter::once(None).chain(KeyframeTag::ALL.map(Some)) {
+                                let text = tag.map_or("None", KeyframeTag::label);
+                                if ui.selectable_label(common_tag == Some(tag), text).clicked() {
+                                    edit = Some(Edit::Tag(tag));
+                                }
+                            }
+                        });
+                    ui.end_row();
+                });
+            })
+            .response;
+
+        let commands = match edit {
+            Some(Edit::Value(value)) if self.settings.auto_key => {
+                self.auto_key_commands(&keyframes, value)
+            }
+            Some(edit) => edit_commands(&keyframes, edit),
+            None => Vec::new(),
+        };
+        KeyframeInspectorResponse { response, commands }
+    }
+
+    /// Commands keying `value` at the playhead on each track of
+    /// `keyframes`, in row order.
+    fn auto_key_commands(&self, keyframes: &[Inspected], value: f64) -> Vec<AnimationCommand> {
+        let mut tracks: Vec<TrackId> = keyframes.iter().map(|kf| kf.track_id).collect();
+        tracks.dedup();
+        tracks
+            .into_iter()
+            .flat_map(|track_id| {
+                self.settings
+                    .value_edit_commands(self.provider, track_id, value)
+            })
+            .collect()
+    }
+
+    /// The selected keyframes in row order and then time order.
+    fn inspected(&self) -> Vec<Inspected> {
+        if self.selected.is_empty() {
+            return Vec::new();
+        }
+        let provider = self.provider;
+        provider
+            .property_rows()
+            .into_iter()
+            .filter_map(|row| row.track_id)
+            .flat_map(|track_id| {
+                let mut positions = provider.keyframe_positions(track_id).unwrap_or_default();
+                positions.retain(|(id, _)| self.selected.contains(id));
+                positions.sort_by(|a, b| a.1.value().total_cmp(&b.1.value()));
+                positions.into_iter().filter_map(move |(id, position)| {
+                    Some(Inspected {
+                        id,
+                        track_id,
+                        position,
+                        value: provider.keyframe_value(track_id, id)?,
+                        handles: provider.keyframe_handles(track_id, id).unwrap_or_default(),
+                        keyframe_type: provider.keyframe_type(track_id, id).unwrap_or_default(),
+                        label: provider.keyframe_label(track_id, id),
+                        tag: provider.keyframe_tag(track_id, id),
+                    })
+                })
+            })
+            .collect()
+    }
+}
+
+/// Handle coordinates as `[left_x, left_y, right_x, right_y]`.
+fn handle_fields(handles: BezierHandles) -> [f32; 4] {
+    [
+        handles.left_x,
+        handles.left_y,
+        handles.right_x,
+        handles.right_y,
+    ]
+}
+
+/// Commands applying `edit` to `keyframes`.
+fn edit_commands(keyframes: &[Inspected], edit: Edit) -> Vec<AnimationCommand> {
+    match edit {
+        Edit::Time(time) => match keyframes {
+            [keyframe] => vec![AnimationCommand::MoveKeyframe {
+                keyframe_id: keyframe.id,
+                new_position: time,
+            }],
+            [first, ..] => vec![AnimationCommand::OffsetKeyframes {
+                keyframe_ids: keyframes.iter().map(|kf| kf.id).collect(),
+                delta_time: time - first.position,
+                delta_value: 0.0,
+            }],
+            [] => Vec::new(),
+        },
+        Edit::Value(value) => keyframes
+            .iter()
+            .map(|kf| AnimationCommand::SetKeyframeValue {
+                keyframe_id: kf.id,
+                value,
+            })
+            .collect(),
+        Edit::Type(keyframe_type) => keyframes
+            .iter()
+            .filter(|kf| kf.keyframe_type != keyframe_type)
+            .map(|kf| AnimationCommand::SetKeyframeType {
+                keyframe_id: kf.id,
+                keyframe_type,
+            })
+            .collect(),
+        Edit::Handle(index, coordinate) => keyframes
+            .iter()
+            .map(|kf| {
+                let mut handles = kf.handles;
+                match index {
+                    0 => handles.left_x = coordinate,
+                    1 => handles.left_y = coordinate,
+                    2 => handles.right_x = coordinate,
+                    _ => handles.right_y = coordinate,
+                }
+                AnimationCommand::SetKeyframeHandles {
+                    keyframe_id: kf.id,
+                    handles,
+                }
+            })
+            .collect(),
+        Edit::Label(label) => {
+            let label = (!label.is_empty()).then_some(label);
+            keyframes
+                .iter()
+                .map(|kf| AnimationCommand::SetKeyframeLabel {
+                    keyframe_id: kf.id,
+                    label: label.clone(),
+                })
+                .collect()
+        }
+        Edit::Tag(tag) => keyframes
+            .iter()
+            .filter(|kf| kf.tag != tag)
+            .map(|kf| AnimationCommand::SetKeyframeTag {
+                keyframe_id: kf.id,
+                tag,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SimpleAnimationModel;
+    use crate::core::keyframe::Keyframe;
+    use crate::core::track::Track;
+    use crate::traits::AnimationDataMutator;
+
+    #[test]
+    fn edits_apply_to_the_whole_selection() {
+        let mut model = SimpleAnimationModel::new();
+        let group = model.add_group("Ball");
+        let mut track = Track::new();
+        let a = track.add_keyframe(Keyframe::new(0.0, 1.0_f32));
+        let b = track.add_keyframe(Keyframe::new(1.0, 2.0_f32));
+        track.add_keyframe(Keyframe::new(2.0, 3.0_f32));
+        let track_id = model.add_track(&group, "Y", track).unwrap();
+        let selected: HashSet<KeyframeId> = [b, a].into_iter().collect();
+
+        let inspector = KeyframeInspector::new(&model, &selected);
+        let keyframes = inspector.inspected();
+        assert_eq!(
+            keyframes.iter().map(|kf| kf.id).collect::<Vec<_>>(),
+            vec![a, b]
+        );
+
+        let mut commands = edit_commands(&keyframes, Edit::Time(TimeTick::new(0.5)));
+        commands.extend(edit_commands(&keyframes, Edit::Value(4.0)));
+        commands.extend(edit_commands(&keyframes, Edit::Handle(2, 0.25)));
+        commands.extend(edit_commands(&keyframes, Edit::Type(KeyframeType::Linear)));
+        for command in commands {
+            model.execute(command);
+        }
+
+        let track = model.track(track_id).unwrap();
+        let moved = track.get_keyframe(a).unwrap();
+        assert_eq!(moved.position, TimeTick::new(0.5));
+        assert_eq!(moved.value, 4.0);
+        assert_eq!(moved.handles.right_x, 0.25);
+        assert_eq!(moved.keyframe_type, KeyframeType::Linear);
+        assert_eq!(track.get_keyframe(b).unwrap().position, TimeTick::new(1.5));
+        assert_eq!(track.evaluate(2.0), Some(3.0));
+
+        // Auto-key keys the value at the playhead instead.
+        model.execute(AnimationCommand::SetCurrentTime(TimeTick::new(1.0)));
+        let inspector = KeyframeInspector::new(&model, &selected)
+            .settings(EditorSettings::default().auto_key(true));
+        let keyframes = inspector.inspected();
+        for command in inspector.auto_key_commands(&keyframes, 6.0) {
+            model.execute(command);
+        }
+        let track = model.track(track_id).unwrap();
+        assert_eq!(track.len(), 4);
+        assert_eq!(track.evaluate(1.0), Some(6.0));
+        assert_eq!(track.get_keyframe(a).unwrap().value, 4.0);
+    }
+}
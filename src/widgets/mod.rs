@@ -1,13 +1,46 @@
 //! egui widgets for keyframe editing.
 
 pub mod bounding_box;
+pub mod clip_lane;
 mod curve_editor;
+mod curve_editor_state;
+pub mod generator_dialog;
+pub mod interaction;
 pub mod keyframe_dot;
+mod keyframe_inspector;
+mod motion_path_editor;
+mod multi_curve_editor;
+pub(crate) mod offscreen;
+pub mod playhead;
+pub mod reduced_motion;
+pub mod sections_lane;
+mod svg;
 pub mod time_ruler;
+mod timeline_panel;
+pub mod transport_bar;
+pub mod value_ruler;
 
-pub use bounding_box::{AnchorMode, BoundingBox, BoundingBoxConfig, BoundingBoxHandle};
+pub use bounding_box::{
+    AnchorMode, BoundingBox, BoundingBoxConfig, BoundingBoxHandle, WorldBoundingBox,
+};
+pub use clip_lane::{ClipLane, ClipLaneConfig, ClipLaneResponse};
 pub use curve_editor::{
-    CurveEditor, CurveEditorConfig, CurveEditorResponse, HandleDrag, HandleSide, KeyframeMove,
+    CurveEditor, CurveEditorConfig, CurveEditorHit, CurveEditorResponse, CurveLayer, CurvePoint,
+    DragPhase, HandleDrag, HandleSide, HeightMode, KeyframeMove,
+};
+pub use curve_editor_state::CurveEditorState;
+pub use generator_dialog::GeneratorDialog;
+pub use interaction::InteractionSizes;
+pub use keyframe_dot::{KeyframeDot, KeyframeDotTextures, KeyframeVisual, KeyframeVisualState};
+pub use keyframe_inspector::{KeyframeInspector, KeyframeInspectorResponse};
+pub use motion_path_editor::{MotionPathEditor, MotionPathEditorResponse};
+pub use multi_curve_editor::{
+    CurveEntry, MultiCurveEditor, MultiCurveEditorResponse, ValueNormalization,
 };
-pub use keyframe_dot::KeyframeDot;
-pub use time_ruler::TimeRuler;
+pub use playhead::{PlayheadLabel, PlayheadStyle};
+pub use reduced_motion::{reduced_motion, set_reduced_motion};
+pub use sections_lane::{SectionEdge, SectionsLane, SectionsLaneConfig, SectionsLaneResponse};
+pub use time_ruler::{ScrubCallback, TimeRuler, TimeRulerResponse};
+pub use timeline_panel::{TimelinePanel, TimelinePanelConfig, TimelinePanelResponse};
+pub use transport_bar::{TransportBar, TransportBarResponse};
+pub use value_ruler::{ValueRuler, ValueRulerConfig, ValueRulerResponse};
@@ -0,0 +1,784 @@
+//! 2D editor for the path traced by a pair of X/Y keyframe sources.
+
+use crate::HashSet;
+use crate::TimeTick;
+use crate::core::extrapolation::Extrapolation;
+use crate::core::interpolation::{interpolate_with_extrapolation, resolved_handles};
+use crate::core::keyframe::{Keyframe, KeyframeId, KeyframeType, WeightedHandles};
+use crate::dopesheet::SelectionMode;
+use crate::intervals::{IntervalContext, VALUE_TICK_SPACING, nice_intervals};
+use crate::traits::{AnimationCommand, KeyframeSource, KeyframeView};
+use crate::widgets::curve_editor::{
+    CurveEditorConfig, HANDLE_RADIUS, HandleSide, allocate_editor, hits_keyframe,
+};
+use crate::widgets::interaction::InteractionSizes;
+use crate::widgets::keyframe_dot::KeyframeDot;
+use egui::{Color32, Pos2, Rect, Response, Stroke, Ui, Vec2};
+
+/// Response from the [`MotionPathEditor`].
+#[derive(Default)]
+pub struct MotionPathEditorResponse {
+    /// The egui response.
+    pub response: Option<Response>,
+    /// X and Y keyframes of the path point that was clicked, and how to
+    /// combine them with the current selection.
+    pub clicked_keyframes: Option<(Vec<KeyframeId>, SelectionMode)>,
+    /// X and Y keyframes of the path points enclosed by a finished box
+    /// selection, and how to combine them with the current selection.
+    pub box_selected: Option<(Vec<KeyframeId>, SelectionMode)>,
+    /// Whether a point or handle drag ended (for undo grouping).
+    pub transform_ended: bool,
+    /// Animation commands to execute (from user interactions), X track
+    /// first, each listing keyframes in time order.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Editor for spatial motion: the path of a pair of X/Y tracks drawn as
+/// value against value.
+///
+/// Every time either track has a keyframe is a point on the path. Points
+/// select the keyframes of both tracks at that time, so selection passed to
+/// and from a [`DopeSheet`](crate::DopeSheet) stays in sync. Dragging a point
+/// moves the selected points, or only the grabbed one if it is unselected,
+/// and emits one [`AnimationCommand::OffsetKeyframes`] per track on release.
+///
+/// Selected points keyed on both tracks with bezier interpolation show their
+/// tangent handles. Dragging a handle sets
+/// [weighted handles](crate::WeightedHandles) on both keyframes; the
+/// opposite handle stays aligned unless Shift is held.
+///
+/// Y points up. Both axes share one scale, fitted to the path, so the shape
+/// is not distorted.
+pub struct MotionPathEditor<'a> {
+    x: &'a dyn KeyframeSource,
+    y: &'a dyn KeyframeSource,
+    selected: &'a HashSet<KeyframeId>,
+    config: CurveEditorConfig,
+    id_source: Option<egui::Id>,
+    current_time: Option<TimeTick>,
+}
+
+/// A point of the path where either track has a keyframe.
+#[derive(Debug, Clone)]
+struct PathKey {
+    time: TimeTick,
+    /// Index of the keyframe at `time` in the X and Y keyframes.
+    keys: [Option<usize>; 2],
+    /// Values of both tracks at `time`.
+    value: Vec2,
+}
+
+/// Keyframes and extrapolation of one track of the path.
+struct Axis {
+    views: Vec<KeyframeView>,
+    keyframes: Vec<Keyframe<f32>>,
+    extrapolation: (Extrapolation, Extrapolation),
+}
+
+impl Axis {
+    fn new(source: &dyn KeyframeSource) -> Self {
+        let views = source.keyframes_sorted();
+        let keyframes = views.iter().map(KeyframeView::to_keyframe).collect();
+        Self {
+            views,
+            keyframes,
+            extrapolation: source.extrapolation(),
+        }
+    }
+
+    fn evaluate(&self, time: TimeTick) -> f32 {
+        let refs: Vec<&Keyframe<f32>> = self.keyframes.iter().collect();
+        let (pre, post) = self.extrapolation;
+        interpolate_with_extrapolation(&refs, time, pre, post).map_or(0.0, |triple| triple.lerp())
+    }
+
+    /// Weighted handles of keyframe `index`, converted from its normalized
+    /// handles unless it has weighted ones.
+    fn weighted(&self, index: usize) -> WeightedHandles {
+        if let Some(weighted) = self.keyframes[index].weighted {
+            return weighted;
+        }
+        let refs: Vec<&Keyframe<f32>> = self.keyframes.iter().collect();
+        let span = |from: usize, to: usize| {
+            let (a, b) = (&self.keyframes[from], &self.keyframes[to]);
+            (
+                (b.position - a.position).value(),
+                (b.value - a.value) as f64,
+            )
+        };
+        WeightedHandles::from_normalized(
+            &resolved_handles(&refs, index),
+            index.checked_sub(1).map(|before| span(before, index)),
+            (index + 1 < self.keyframes.len()).then(|| span(index, index + 1)),
+        )
+    }
+}
+
+/// Mapping between path values and screen positions with one scale for both
+/// axes; Y points up.
+#[derive(Debug, Clone, Copy)]
+struct PathView {
+    center: Vec2,
+    scale: f32,
+    screen_center: Pos2,
+}
+
+impl PathView {
+    /// Fit the bounds of `points` into `rect` minus `padding`.
+    fn fit(rect: Rect, padding: f32, points: impl Iterator<Item = Vec2>) -> Self {
+        let (min, max) = points.fold(
+            (Vec2::splat(f32::INFINITY), Vec2::splat(f32::NEG_INFINITY)),
+            |(min, max), point| (min.min(point), max.max(point)),
+        );
+        let (min, max) = if min.x <= max.x {
+            (min, max)
+        } else {
+            (Vec2::ZERO, Vec2::ZERO)
+        };
+        let inner = (rect.size() - Vec2::splat(2.0 * padding)).max(Vec2::splat(1.0));
+        // Flat paths still get a unit span on their flat axis.
+        let span = (max - min).max(Vec2::splat(1e-6));
+        let scale = if max.x - min.x < 1e-6 && max.y - min.y < 1e-6 {
+            inner.min_elem()
+        } else {
+            (inner.x / span.x).min(inner.y / span.y)
+        };
+        Self {
+            center: (min + max) / 2.0,
+            scale,
+            screen_center: rect.center(),
+        }
+    }
+
+    fn to_screen(self, value: Vec2) -> Pos2 {
+        let offset = (value - self.center) * self.scale;
+        self.screen_center + Vec2::new(offset.x, -offset.y)
+    }
+
+    fn to_value(self, pos: Pos2) -> Vec2 {
+        let offset = pos - self.screen_center;
+        self.center + Vec2::new(offset.x, -offset.y) / self.scale
+    }
+}
+
+/// Index of the point and the point and side of the handle under the
+/// pointer.
+type PathHit = (Option<usize>, Option<(usize, HandleSide)>);
+
+/// What is being dragged, kept in temp memory between frames.
+#[derive(Debug, Clone, Copy)]
+enum PathDrag {
+    /// Points, starting at the pointer position; `selection` moves all
+    /// selected points instead of only the one at `time`.
+    Points {
+        start: Pos2,
+        time: TimeTick,
+        selection: bool,
+    },
+    /// A tangent handle of the point at `time`.
+    Handle { time: TimeTick, side: HandleSide },
+}
+
+impl<'a> MotionPathEditor<'a> {
+    /// Create a motion path editor for the `x` and `y` tracks.
+    pub fn new(
+        x: &'a dyn KeyframeSource,
+        y: &'a dyn KeyframeSource,
+        selected: &'a HashSet<KeyframeId>,
+    ) -> Self {
+        Self {
+            x,
+            y,
+            selected,
+            config: CurveEditorConfig::default(),
+            id_source: None,
+            current_time: None,
+        }
+    }
+
+    /// Set the configuration; the path uses the curve colors and
+    /// `vertical_padding` on all sides.
+    pub fn config(mut self, config: CurveEditorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set a custom ID source.
+    pub fn id_source(mut self, id: impl std::hash::Hash) -> Self {
+        self.id_source = Some(egui::Id::new(id));
+        self
+    }
+
+    /// Mark the position on the path at `time`, e.g. the playhead.
+    pub fn current_time(mut self, time: Option<TimeTick>) -> Self {
+        self.current_time = time;
+        self
+    }
+
+    /// Show the editor.
+    pub fn show(self, ui: &mut Ui) -> MotionPathEditorResponse {
+        let id = self
+            .id_source
+            .unwrap_or_else(|| ui.make_persistent_id("motion_path_editor"));
+        let (rect, response) = allocate_editor(ui, id, &self.config);
+
+        let mut result = MotionPathEditorResponse {
+            response: Some(response.clone()),
+            ..Default::default()
+        };
+        if !ui.is_rect_visible(rect) {
+            return result;
+        }
+
+        let axes = [Axis::new(self.x), Axis::new(self.y)];
+        let keys = path_keys(&axes);
+        let path = self.sample_path(&axes, &keys);
+        let view = PathView::fit(
+            rect,
+            self.config.vertical_padding,
+            path.iter().copied().chain(keys.iter().map(|key| key.value)),
+        );
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, self.config.background);
+        self.draw_grid(&painter, rect, &view);
+        painter.add(egui::Shape::line(
+            path.iter().map(|value| view.to_screen(*value)).collect(),
+            Stroke::new(self.config.curve_width, self.config.curve_color),
+        ));
+
+        let sizes = self.config.interaction.for_context(ui.ctx());
+        let pointer_pos = response.hover_pos();
+        let handles: Vec<(usize, HandleSide, Pos2)> = keys
+            .iter()
+            .enumerate()
+            .filter(|(_, key)| self.shows_handles(&axes, key))
+            .flat_map(|(index, key)| {
+                let axes = &axes;
+                [HandleSide::Left, HandleSide::Right]
+                    .into_iter()
+                    .filter(move |side| has_segment(axes, key, *side))
+                    .map(move |side| (index, side, view.to_screen(handle_value(axes, key, side))))
+            })
+            .collect();
+        let hit = |pos: Option<Pos2>| {
+            pos.map_or((None, None), |pos| {
+                hit_test(&view, &keys, &handles, &sizes, pos)
+            })
+        };
+        let (hovered_key, hovered_handle) = hit(pointer_pos);
+        // Drags start where the button went down, not where the pointer
+        // passed the drag threshold.
+        let pressed = hit(ui.input(|i| i.pointer.press_origin()));
+
+        for (index, side, pos) in &handles {
+            let key_pos = view.to_screen(keys[*index].value);
+            painter.line_segment(
+                [key_pos, *pos],
+                Stroke::new(1.0, self.config.handle_line_color),
+            );
+            let radius = if hovered_handle == Some((*index, *side)) {
+                HANDLE_RADIUS + 1.5
+            } else {
+                HANDLE_RADIUS
+            };
+            painter.circle_filled(*pos, radius, self.config.handle_color);
+            painter.circle_stroke(*pos, radius, Stroke::new(1.0, Color32::WHITE));
+        }
+        for (index, key) in keys.iter().enumerate() {
+            KeyframeDot::new(view.to_screen(key.value))
+                .color(self.config.keyframe_color)
+                .selected(self.is_selected(&axes, key))
+                .hovered(hovered_key == Some(index))
+                .locked(is_locked(&axes, key))
+                .textures(self.config.keyframe_textures)
+                .paint(&painter);
+        }
+        if let Some(time) = self.current_time {
+            let value = Vec2::new(axes[0].evaluate(time), axes[1].evaluate(time));
+            painter.circle_stroke(
+                view.to_screen(value),
+                6.0,
+                Stroke::new(2.0, Color32::from_white_alpha(200)),
+            );
+        }
+
+        self.handle_interactions(
+            ui,
+            id,
+            rect,
+            &response,
+            &view,
+            &axes,
+            &keys,
+            hovered_key,
+            pressed,
+            &mut result,
+        );
+        result
+    }
+
+    /// Path values at the keys and `curve_segments` steps between them.
+    fn sample_path(&self, axes: &[Axis; 2], keys: &[PathKey]) -> Vec<Vec2> {
+        let steps = self.config.curve_segments.max(1);
+        let mut path: Vec<Vec2> = keys.first().map(|key| key.value).into_iter().collect();
+        for window in keys.windows(2) {
+            let (from, to) = (window[0].time, window[1].time);
+            path.extend((1..=steps).map(|step| {
+                let time = from.lerp(to, step as f64 / steps as f64);
+                Vec2::new(axes[0].evaluate(time), axes[1].evaluate(time))
+            }));
+        }
+        path
+    }
+
+    fn draw_grid(&self, painter: &egui::Painter, rect: Rect, view: &PathView) {
+        let interval = nice_intervals(
+            VALUE_TICK_SPACING / view.scale as f64,
+            IntervalContext::Decimal,
+        )
+        .major as f32;
+        let stroke = Stroke::new(1.0, self.config.grid_color);
+        let (min, max) = (
+            view.to_value(rect.left_bottom()),
+            view.to_value(rect.right_top()),
+        );
+        for axis in 0..2 {
+            let mut index = (min[axis] / interval).ceil() as i64;
+            while (index as f32 * interval) <= max[axis] {
+                let value = index as f32 * interval;
+                let points = if axis == 0 {
+                    let x = view.to_screen(Vec2::new(value, 0.0)).x;
+                    [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())]
+                } else {
+                    let y = view.to_screen(Vec2::new(0.0, value)).y;
+                    [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)]
+                };
+                painter.line_segment(points, stroke);
+                index += 1;
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn handle_interactions(
+        &self,
+        ui: &mut Ui,
+        id: egui::Id,
+        rect: Rect,
+        response: &Response,
+        view: &PathView,
+        axes: &[Axis; 2],
+        keys: &[PathKey],
+        hovered_key: Option<usize>,
+        (pressed_key, pressed_handle): PathHit,
+        result: &mut MotionPathEditorResponse,
+    ) {
+        let mode = ui.input(|i| SelectionMode::from_modifiers(&i.modifiers));
+        if response.clicked() {
+            if let Some(index) = hovered_key {
+                result.clicked_keyframes = Some((keyframe_ids(axes, &keys[index]).collect(), mode));
+            }
+            return;
+        }
+
+        let painter = ui.painter_at(rect);
+        let drag_id = id.with("path_drag");
+        let box_select_id = id.with("box_select");
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && let Some(pos) = ui
+                .input(|i| i.pointer.press_origin())
+                .or(response.interact_pointer_pos())
+        {
+            if let Some((index, side)) = pressed_handle
+                && !is_locked(axes, &keys[index])
+            {
+                let drag = PathDrag::Handle {
+                    time: keys[index].time,
+                    side,
+                };
+                ui.data_mut(|data| data.insert_temp(drag_id, drag));
+            } else if let Some(index) = pressed_key {
+                let drag = PathDrag::Points {
+                    start: pos,
+                    time: keys[index].time,
+                    selection: self.is_selected(axes, &keys[index]),
+                };
+                ui.data_mut(|data| data.insert_temp(drag_id, drag));
+            } else {
+                ui.data_mut(|data| data.insert_temp(box_select_id, pos));
+            }
+        }
+
+        // Box selection on empty background. Shift adds, Ctrl/Cmd toggles.
+        if let Some(start) = ui.data(|data| data.get_temp::<Pos2>(box_select_id)) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let selection_rect = Rect::from_two_pos(start, pos);
+                painter.rect_filled(
+                    selection_rect,
+                    0.0,
+                    self.config.box_select_color.linear_multiply(0.1),
+                );
+                painter.rect_stroke(
+                    selection_rect,
+                    0.0,
+                    Stroke::new(1.0, self.config.box_select_color),
+                    egui::StrokeKind::Inside,
+                );
+                if response.drag_stopped() {
+                    let enclosed = keys
+                        .iter()
+                        .filter(|key| selection_rect.contains(view.to_screen(key.value)))
+                        .flat_map(|key| keyframe_ids(axes, key))
+                        .collect();
+                    result.box_selected = Some((enclosed, mode));
+                }
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<Pos2>(box_select_id));
+            }
+            return;
+        }
+
+        let Some(drag) = ui.data(|data| data.get_temp::<PathDrag>(drag_id)) else {
+            return;
+        };
+        if let Some(pos) = response.interact_pointer_pos() {
+            match drag {
+                PathDrag::Points {
+                    start,
+                    time,
+                    selection,
+                } => {
+                    let delta = view.to_value(pos) - view.to_value(start);
+                    let moved: Vec<&PathKey> = keys
+                        .iter()
+                        .filter(|key| {
+                            let grabbed = if selection {
+                                self.is_selected(axes, key)
+                            } else {
+                                key.time == time
+                            };
+                            grabbed && !is_locked(axes, key)
+                        })
+                        .collect();
+                    for key in &moved {
+                        KeyframeDot::new(view.to_screen(key.value + delta))
+                            .color(self.config.keyframe_color.linear_multiply(0.6))
+                            .selected(true)
+                            .textures(self.config.keyframe_textures)
+                            .paint(&painter);
+                    }
+                    if response.drag_stopped() {
+                        self.offset_points(axes, &moved, delta, result);
+                    }
+                }
+                PathDrag::Handle { time, side } => {
+                    if let Some(key) = keys.iter().find(|key| key.time == time) {
+                        let break_tangents = ui.input(|i| i.modifiers.shift);
+                        let handles =
+                            dragged_handles(axes, key, side, view.to_value(pos), break_tangents);
+                        if let Some(handles) = handles {
+                            for side in [HandleSide::Left, HandleSide::Right] {
+                                let offset = match side {
+                                    HandleSide::Left => Vec2::new(
+                                        handles[0].left_value as f32,
+                                        handles[1].left_value as f32,
+                                    ),
+                                    HandleSide::Right => Vec2::new(
+                                        handles[0].right_value as f32,
+                                        handles[1].right_value as f32,
+                                    ),
+                                };
+                                painter.line_segment(
+                                    [
+                                        view.to_screen(key.value),
+                                        view.to_screen(key.value + offset),
+                                    ],
+                                    Stroke::new(1.0, self.config.handle_color),
+                                );
+                            }
+                            if response.drag_stopped() {
+                                for (axis, handles) in handles.into_iter().enumerate() {
+                                    if let Some(index) = key.keys[axis] {
+                                        result.commands.push(
+                                            AnimationCommand::SetKeyframeWeightedHandles {
+                                                keyframe_id: axes[axis].views[index].id,
+                                                handles: Some(handles),
+                                            },
+                                        );
+                                    }
+                                }
+                                result.transform_ended = true;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        if response.drag_stopped() {
+            ui.data_mut(|data| data.remove::<PathDrag>(drag_id));
+        }
+    }
+
+    /// Emit one offset per track for the keyframes of `moved` points.
+    fn offset_points(
+        &self,
+        axes: &[Axis; 2],
+        moved: &[&PathKey],
+        delta: Vec2,
+        result: &mut MotionPathEditorResponse,
+    ) {
+        for (axis_index, axis) in axes.iter().enumerate() {
+            let delta_value = delta[axis_index];
+            let ids: Vec<KeyframeId> = moved
+                .iter()
+                .filter_map(|key| key.keys[axis_index])
+                .map(|index| &axis.views[index])
+                .filter(|view| !view.pinned && !view.locked)
+                .map(|view| view.id)
+                .collect();
+            if !ids.is_empty() && delta_value != 0.0 {
+                result.commands.push(AnimationCommand::OffsetKeyframes {
+                    keyframe_ids: ids,
+                    delta_time: TimeTick::default(),
+                    delta_value: delta_value as f64,
+                });
+                result.transform_ended = true;
+            }
+        }
+    }
+
+    fn is_selected(&self, axes: &[Axis; 2], key: &PathKey) -> bool {
+        keyframe_ids(axes, key).any(|id| self.selected.contains(&id))
+    }
+
+    /// Whether `key` is a selected bezier point keyed on both tracks.
+    fn shows_handles(&self, axes: &[Axis; 2], key: &PathKey) -> bool {
+        self.is_selected(axes, key)
+            && axes.iter().zip(key.keys).all(|(axis, index)| {
+                index.is_some_and(|index| axis.views[index].keyframe_type == KeyframeType::Bezier)
+            })
+    }
+}
+
+/// Points at every time either axis has a keyframe, in time order.
+fn path_keys(axes: &[Axis; 2]) -> Vec<PathKey> {
+    let mut times: Vec<TimeTick> = axes
+        .iter()
+        .flat_map(|axis| axis.views.iter().map(|view| view.position))
+        .collect();
+    times.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    times.dedup();
+
+    times
+        .into_iter()
+        .map(|time| {
+            let keys = [0, 1].map(|axis| {
+                axes[axis]
+                    .views
+                    .iter()
+                    .position(|view| view.position == time)
+            });
+            let value = [0, 1].map(|axis| match keys[axis] {
+                Some(index) => axes[axis].views[index].value,
+                None => axes[axis].evaluate(time),
+            });
+            PathKey {
+                time,
+                keys,
+                value: Vec2::new(value[0], value[1]),
+            }
+        })
+        .collect()
+}
+
+/// IDs of the X and Y keyframes of `key`.
+fn keyframe_ids<'k>(
+    axes: &'k [Axis; 2],
+    key: &'k PathKey,
+) -> impl Iterator<Item = KeyframeId> + 'k {
+    axes.iter()
+        .zip(key.keys)
+        .filter_map(|(axis, index)| index.map(|index| axis.views[index].id))
+}
+
+/// Whether a keyframe of `key` on either track is locked.
+fn is_locked(axes: &[Axis; 2], key: &PathKey) -> bool {
+    axes.iter()
+        .zip(key.keys)
+        .any(|(axis, index)| index.is_some_and(|index| axis.views[index].locked))
+}
+
+/// Point and handle at `pos`; handles win over points and later points
+/// over earlier ones, as they are drawn on top.
+fn hit_test(
+    view: &PathView,
+    keys: &[PathKey],
+    handles: &[(usize, HandleSide, Pos2)],
+    sizes: &InteractionSizes,
+    pos: Pos2,
+) -> PathHit {
+    let handle = handles
+        .iter()
+        .find(|(_, _, handle)| handle.distance(pos) <= sizes.handle_hit)
+        .map(|(index, side, _)| (*index, *side));
+    let key = handle.is_none().then(|| {
+        keys.iter()
+            .rposition(|key| hits_keyframe(view.to_screen(key.value), pos, sizes.keyframe_hit))
+    });
+    (key.flatten(), handle)
+}
+
+/// Whether either track of `key` has a segment on `side` for the handle
+/// to shape.
+fn has_segment(axes: &[Axis; 2], key: &PathKey, side: HandleSide) -> bool {
+    axes.iter().zip(key.keys).any(|(axis, index)| {
+        index.is_some_and(|index| match side {
+            HandleSide::Left => index > 0,
+            HandleSide::Right => index + 1 < axis.views.len(),
+        })
+    })
+}
+
+/// Path value of the `side` tangent handle of `key`, which must be keyed on
+/// both axes.
+fn handle_value(axes: &[Axis; 2], key: &PathKey, side: HandleSide) -> Vec2 {
+    let offset = |axis: usize| {
+        key.keys[axis].map_or(0.0, |index| {
+            let weighted = axes[axis].weighted(index);
+            match side {
+                HandleSide::Left => weighted.left_value,
+                HandleSide::Right => weighted.right_value,
+            }
+        }) as f32
+    };
+    key.value + Vec2::new(offset(0), offset(1))
+}
+
+/// Weighted handles of the X and Y keyframes of `key` after dragging its
+/// `side` handle to the path value `target`.
+///
+/// The opposite handle is rotated to stay aligned, keeping its length,
+/// unless `break_tangents` is set. Returns `None` if `key` is not keyed on
+/// both axes.
+fn dragged_handles(
+    axes: &[Axis; 2],
+    key: &PathKey,
+    side: HandleSide,
+    target: Vec2,
+    break_tangents: bool,
+) -> Option<[WeightedHandles; 2]> {
+    let [x, y] = [0, 1].map(|axis| key.keys[axis].map(|index| axes[axis].weighted(index)));
+    let (mut x, mut y) = (x?, y?);
+    let offset = target - key.value;
+    let opposite = match side {
+        HandleSide::Left => Vec2::new(x.right_value as f32, y.right_value as f32),
+        HandleSide::Right => Vec2::new(x.left_value as f32, y.left_value as f32),
+    };
+    let aligned = -offset.normalized() * opposite.length();
+    match side {
+        HandleSide::Left => {
+            (x.left_value, y.left_value) = (offset.x as f64, offset.y as f64);
+            if !break_tangents && offset.length() > 0.0 {
+                (x.right_value, y.right_value) = (aligned.x as f64, aligned.y as f64);
+            }
+        }
+        HandleSide::Right => {
+            (x.right_value, y.right_value) = (offset.x as f64, offset.y as f64);
+            if !break_tangents && offset.length() > 0.0 {
+                (x.left_value, y.left_value) = (aligned.x as f64, aligned.y as f64);
+            }
+        }
+    }
+    Some([x, y])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Track;
+    use crate::widgets::offscreen::run_input_frame;
+    use egui::{Context, Event, PointerButton, RawInput};
+
+    #[test]
+    fn drags_points_on_both_tracks() {
+        let mut x = Track::<f32>::new();
+        let mut y = Track::<f32>::new();
+        let x0 = x.add_keyframe(Keyframe::new(0.0, 0.0));
+        let x1 = x.add_keyframe(Keyframe::new(1.0, 10.0));
+        let y0 = y.add_keyframe(Keyframe::new(0.0, 0.0));
+        let y1 = y.add_keyframe(Keyframe::new(1.0, 10.0));
+        // Only X is keyed here, so the point takes Y from its curve.
+        x.add_keyframe(Keyframe::new(0.5, 0.0));
+
+        let axes = [Axis::new(&x), Axis::new(&y)];
+        let keys = path_keys(&axes);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys[1].keys[1], None);
+        assert_eq!(keys[2].value, Vec2::new(10.0, 10.0));
+
+        // A 100 x 100 editor with 10 padding maps 0..10 onto 10..90, with Y
+        // up.
+        let selected: HashSet<KeyframeId> = [x1, y1].into_iter().collect();
+        let config = CurveEditorConfig {
+            height: 100.0,
+            vertical_padding: 10.0,
+            ..Default::default()
+        };
+        let ctx = Context::default();
+        let mut output = MotionPathEditorResponse::default();
+        let pointer = |event: Event| RawInput {
+            events: vec![event],
+            ..Default::default()
+        };
+        let frames = [
+            pointer(Event::PointerMoved(Pos2::new(90.0, 10.0))),
+            pointer(Event::PointerMoved(Pos2::new(90.0, 10.0))),
+            pointer(Event::PointerButton {
+                pos: Pos2::new(90.0, 10.0),
+                button: PointerButton::Primary,
+                pressed: true,
+                modifiers: Default::default(),
+            }),
+            pointer(Event::PointerMoved(Pos2::new(82.0, 18.0))),
+            pointer(Event::PointerMoved(Pos2::new(74.0, 26.0))),
+            pointer(Event::PointerButton {
+                pos: Pos2::new(74.0, 26.0),
+                button: PointerButton::Primary,
+                pressed: false,
+                modifiers: Default::default(),
+            }),
+        ];
+        for input in frames {
+            run_input_frame(&ctx, Vec2::splat(100.0), 1.0, input, |ui| {
+                let response = MotionPathEditor::new(&x, &y, &selected)
+                    .config(config.clone())
+                    .show(ui);
+                if !response.commands.is_empty() {
+                    output = response;
+                }
+            });
+        }
+
+        assert!(output.transform_ended);
+        let offsets: Vec<(Vec<KeyframeId>, f64)> = output
+            .commands
+            .iter()
+            .filter_map(|command| match command {
+                AnimationCommand::OffsetKeyframes {
+                    keyframe_ids,
+                    delta_value,
+                    ..
+                } => Some((keyframe_ids.clone(), *delta_value)),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(offsets, [(vec![x1], -2.0), (vec![y1], -2.0)]);
+        assert!(
+            !offsets
+                .iter()
+                .any(|(ids, _)| ids.contains(&x0) || ids.contains(&y0))
+        );
+    }
+}
@@ -0,0 +1,690 @@
+//! Curve editor overlaying several keyframe sources, e.g. the X/Y/Z channels
+//! of a position.
+
+use crate::HashSet;
+use crate::core::group::TrackGroups;
+use crate::core::interpolation::interpolate_with_extrapolation;
+use crate::core::keyframe::{Keyframe, KeyframeId};
+use crate::core::track::TrackId;
+use crate::dopesheet::SelectionMode;
+use crate::key_bindings::KeyBindings;
+use crate::traits::{AnimationCommand, KeyframeSource, KeyframeView, ValueUnit};
+use crate::widgets::curve_editor::{
+    CurveEditorConfig, allocate_editor, display_range, draw_value_grid, hits_keyframe,
+    paint_curve_segment, paint_preview_line, paint_value_preview, value_to_y, y_to_value,
+};
+use crate::widgets::keyframe_dot::KeyframeDot;
+use crate::widgets::time_ruler::draw_time_grid;
+use crate::{SpaceTransform, TimeTick};
+use egui::{Color32, Pos2, Rect, Response, Stroke, Ui, Vec2};
+
+/// How the value axis is shared between the curves of a [`MultiCurveEditor`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ValueNormalization {
+    /// All curves use one value range, so their values compare directly.
+    #[default]
+    Shared,
+    /// Each curve is scaled to fill the editor height.
+    PerCurve,
+}
+
+/// A curve shown in a [`MultiCurveEditor`].
+pub struct CurveEntry<'a> {
+    /// Keyframes of the curve.
+    pub source: &'a dyn KeyframeSource,
+    /// Label shown in the legend.
+    pub label: String,
+    /// Color of the curve and its keyframes.
+    pub color: Color32,
+    /// Whether the curve is drawn and editable.
+    pub visible: bool,
+    /// Track of the curve, for hiding it with its
+    /// [track group](crate::core::group::TrackGroup).
+    pub track_id: Option<TrackId>,
+}
+
+impl<'a> CurveEntry<'a> {
+    /// Create a visible curve entry.
+    pub fn new(source: &'a dyn KeyframeSource, label: impl Into<String>) -> Self {
+        Self {
+            source,
+            label: label.into(),
+            color: CurveEditorConfig::default().curve_color,
+            visible: true,
+            track_id: None,
+        }
+    }
+
+    /// Set the color.
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set whether the curve is visible.
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Set the track of the curve.
+    pub fn track(mut self, track_id: TrackId) -> Self {
+        self.track_id = Some(track_id);
+        self
+    }
+}
+
+/// Response from the [`MultiCurveEditor`].
+#[derive(Default)]
+pub struct MultiCurveEditorResponse {
+    /// The egui response.
+    pub response: Option<Response>,
+    /// Keyframe that was clicked, with the index of its curve.
+    pub clicked_keyframe: Option<(usize, KeyframeId)>,
+    /// Keyframes of all visible curves enclosed by a finished box selection,
+    /// and how to combine them with the current selection.
+    pub box_selected: Option<(Vec<KeyframeId>, SelectionMode)>,
+    /// Index of the curve whose legend entry was clicked to toggle its
+    /// visibility. The click leaves the keyframe selection unchanged.
+    pub toggle_visibility: Option<usize>,
+    /// Track of the toggled curve and its requested visibility, for curves
+    /// with a [track](CurveEntry::track).
+    pub toggled_track: Option<(TrackId, bool)>,
+    /// Request to switch between [`ValueNormalization::Shared`] and
+    /// [`ValueNormalization::PerCurve`] (press N).
+    pub toggle_normalization: bool,
+    /// Whether a selection drag ended (for undo grouping).
+    pub transform_ended: bool,
+    /// Animation commands to execute (from user interactions), one per
+    /// affected curve in curve order, each listing keyframes in time order.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Height of a legend entry in pixels.
+const LEGEND_ROW_HEIGHT: f32 = 16.0;
+
+/// Curve editor showing several keyframe sources in one view.
+///
+/// Curves are drawn in their own colors. With more than one curve, a legend
+/// in the top-left corner lists them with an eye toggle and color swatch. Selection spans all curves: click or box-select
+/// keyframes on any visible curve, then drag one of them to move the whole
+/// selection. The drag emits one [`AnimationCommand::OffsetKeyframes`] per
+/// affected curve on release; with [`ValueNormalization::PerCurve`] the value
+/// offset is scaled to each curve's range.
+pub struct MultiCurveEditor<'a> {
+    curves: Vec<CurveEntry<'a>>,
+    selected: &'a HashSet<KeyframeId>,
+    space: &'a SpaceTransform,
+    normalization: ValueNormalization,
+    value_range: Option<(f32, f32)>,
+    config: CurveEditorConfig,
+    id_source: Option<egui::Id>,
+    preview_time: Option<TimeTick>,
+    groups: Option<&'a TrackGroups>,
+}
+
+impl<'a> MultiCurveEditor<'a> {
+    /// Create a new multi-curve editor without curves.
+    pub fn new(selected: &'a HashSet<KeyframeId>, space: &'a SpaceTransform) -> Self {
+        Self {
+            curves: Vec::new(),
+            selected,
+            space,
+            normalization: ValueNormalization::default(),
+            value_range: None,
+            config: CurveEditorConfig::default(),
+            id_source: None,
+            preview_time: None,
+            groups: None,
+        }
+    }
+
+    /// Add a curve.
+    pub fn curve(mut self, curve: CurveEntry<'a>) -> Self {
+        self.curves.push(curve);
+        self
+    }
+
+    /// Set how the value axis is shared between curves.
+    pub fn normalization(mut self, normalization: ValueNormalization) -> Self {
+        self.normalization = normalization;
+        self
+    }
+
+    /// Set the shared value range instead of fitting it to the visible curves.
+    ///
+    /// Ignored with [`ValueNormalization::PerCurve`].
+    pub fn value_range(mut self, range: (f32, f32)) -> Self {
+        self.value_range = Some(range);
+        self
+    }
+
+    /// Set the configuration.
+    pub fn config(mut self, config: CurveEditorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set a custom ID source.
+    pub fn id_source(mut self, id: impl std::hash::Hash) -> Self {
+        self.id_source = Some(egui::Id::new(id));
+        self
+    }
+
+    /// Mark the value of each visible curve at `time`, like
+    /// [`CurveEditor::preview_time`](super::CurveEditor::preview_time).
+    pub fn preview_time(mut self, time: Option<TimeTick>) -> Self {
+        self.preview_time = time;
+        self
+    }
+
+    /// Hide the curves whose [track](CurveEntry::track) is in a hidden
+    /// group.
+    pub fn groups(mut self, groups: &'a TrackGroups) -> Self {
+        self.groups = Some(groups);
+        self
+    }
+
+    /// Show the editor.
+    pub fn show(mut self, ui: &mut Ui) -> MultiCurveEditorResponse {
+        if let Some(groups) = self.groups {
+            for curve in &mut self.curves {
+                if curve
+                    .track_id
+                    .is_some_and(|id| !groups.is_track_visible(id))
+                {
+                    curve.visible = false;
+                }
+            }
+        }
+
+        let hit_distance = self.config.interaction.for_context(ui.ctx()).keyframe_hit;
+        let id = self
+            .id_source
+            .unwrap_or_else(|| ui.make_persistent_id("multi_curve_editor"));
+
+        let (rect, response) = allocate_editor(ui, id, &self.config);
+
+        let mut result = MultiCurveEditorResponse {
+            response: Some(response.clone()),
+            ..Default::default()
+        };
+
+        if !ui.is_rect_visible(rect) {
+            return result;
+        }
+
+        let keyframes: Vec<Vec<KeyframeView>> = self
+            .curves
+            .iter()
+            .map(|curve| curve.source.keyframes_sorted())
+            .collect();
+        let ranges = curve_value_ranges(
+            &self
+                .curves
+                .iter()
+                .map(|curve| curve.visible.then(|| curve.source.value_range()).flatten())
+                .collect::<Vec<_>>(),
+            self.normalization,
+            self.value_range,
+        );
+
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, self.config.background);
+        self.draw_grid(&painter, rect, &ranges);
+
+        let pointer_pos = response.hover_pos();
+        let legend_hit = pointer_pos.and_then(|pos| legend_entry_at(rect, self.curves.len(), pos));
+
+        let preview_x = self
+            .preview_time
+            .map(|time| self.space.unit_to_clipped(time))
+            .filter(|x| rect.x_range().contains(*x));
+        if let Some(x) = preview_x {
+            paint_preview_line(&painter, rect, x);
+        }
+
+        // Curves and keyframes. Later curves draw on top, so they win hovers.
+        let mut hovered = None;
+        for (index, curve) in self.curves.iter().enumerate() {
+            if !curve.visible {
+                continue;
+            }
+            let range = ranges[index];
+            let to_screen = |time: TimeTick, value: f32| {
+                Pos2::new(
+                    self.space.unit_to_clipped(time),
+                    value_to_y(rect, self.config.vertical_padding, range, value),
+                )
+            };
+
+            let evaluated: Vec<Keyframe<f32>> = keyframes[index]
+                .iter()
+                .map(KeyframeView::to_keyframe)
+                .collect();
+            let evaluated_refs: Vec<&Keyframe<f32>> = evaluated.iter().collect();
+            let stroke = Stroke::new(self.config.curve_width, curve.color);
+            for (segment, window) in keyframes[index].windows(2).enumerate() {
+                if window[0].connected_right {
+                    paint_curve_segment(&painter, &evaluated_refs, segment, to_screen, stroke);
+                }
+            }
+            if let (Some(time), Some(_)) = (self.preview_time, preview_x) {
+                let (pre, post) = curve.source.extrapolation();
+                if let Some(triple) =
+                    interpolate_with_extrapolation(&evaluated_refs, time, pre, post)
+                {
+                    let value = triple.lerp();
+                    paint_value_preview(&painter, to_screen(time, value), value, curve.color);
+                }
+            }
+
+            for kf in &keyframes[index] {
+                let screen_pos = to_screen(kf.position, kf.value);
+                if legend_hit.is_none()
+                    && pointer_pos.is_some_and(|p| hits_keyframe(screen_pos, p, hit_distance))
+                {
+                    hovered = Some((index, kf.id));
+                }
+                KeyframeDot::new(screen_pos)
+                    .color(curve.color)
+                    .selected(self.selected.contains(&kf.id))
+                    .locked(kf.locked)
+                    .pinned(kf.pinned)
+                    .breakdown(kf.breakdown)
+                    .tag(kf.tag)
+                    .textures(self.config.keyframe_textures)
+                    .paint(&painter);
+            }
+        }
+        if let Some((index, kf_id)) = hovered
+            && let Some(kf) = keyframes[index].iter().find(|kf| kf.id == kf_id)
+        {
+            KeyframeDot::new(Pos2::new(
+                self.space.unit_to_clipped(kf.position),
+                value_to_y(rect, self.config.vertical_padding, ranges[index], kf.value),
+            ))
+            .color(self.curves[index].color)
+            .selected(self.selected.contains(&kf_id))
+            .hovered(true)
+            .locked(kf.locked)
+            .pinned(kf.pinned)
+            .breakdown(kf.breakdown)
+            .tag(kf.tag)
+            .textures(self.config.keyframe_textures)
+            .paint(&painter);
+        }
+
+        if self.curves.len() > 1 {
+            self.draw_legend(&painter, rect, legend_hit);
+        }
+
+        self.handle_interactions(
+            ui,
+            id,
+            rect,
+            &response,
+            &keyframes,
+            &ranges,
+            hovered,
+            legend_hit,
+            &mut result,
+        );
+
+        result
+    }
+
+    fn draw_grid(&self, painter: &egui::Painter, rect: Rect, ranges: &[(f32, f32)]) {
+        match self.normalization {
+            ValueNormalization::Shared => {
+                let range = ranges.first().copied().unwrap_or((0.0, 1.0));
+                draw_value_grid(
+                    painter,
+                    rect,
+                    range,
+                    ValueUnit::Generic,
+                    self.config.vertical_padding,
+                    self.config.grid_color,
+                    self.config.value_labels,
+                );
+            }
+            ValueNormalization::PerCurve => {
+                // Values differ per curve; mark quarters of the range instead.
+                for quarter in 0..=4 {
+                    let y = value_to_y(
+                        rect,
+                        self.config.vertical_padding,
+                        (0.0, 1.0),
+                        quarter as f32 / 4.0,
+                    );
+                    painter.line_segment(
+                        [Pos2::new(rect.left(), y), Pos2::new(rect.right(), y)],
+                        Stroke::new(1.0, self.config.grid_color),
+                    );
+                }
+            }
+        }
+        draw_time_grid(painter, rect, self.space, self.config.grid_color, None);
+    }
+
+    fn draw_legend(&self, painter: &egui::Painter, rect: Rect, hovered: Option<usize>) {
+        for (index, curve) in self.curves.iter().enumerate() {
+            let entry = legend_rect(rect, index);
+            if hovered == Some(index) {
+                painter.rect_filled(entry, 2.0, Color32::from_white_alpha(20));
+            }
+            let (color, text_color) = if curve.visible {
+                (curve.color, Color32::from_gray(220))
+            } else {
+                (Color32::TRANSPARENT, Color32::from_gray(110))
+            };
+            let eye = Pos2::new(entry.left() + 8.0, entry.center().y);
+            paint_eye(painter, eye, curve.visible, text_color);
+            let swatch =
+                Rect::from_center_size(Pos2::new(eye.x + 14.0, entry.center().y), Vec2::splat(8.0));
+            painter.rect_filled(swatch, 1.0, color);
+            painter.rect_stroke(
+                swatch,
+                1.0,
+                Stroke::new(1.0, curve.color),
+                egui::StrokeKind::Inside,
+            );
+            painter.text(
+                Pos2::new(swatch.right() + 6.0, entry.center().y),
+                egui::Align2::LEFT_CENTER,
+                &curve.label,
+                egui::FontId::proportional(11.0),
+                text_color,
+            );
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn handle_interactions(
+        &self,
+        ui: &mut Ui,
+        id: egui::Id,
+        rect: Rect,
+        response: &Response,
+        keyframes: &[Vec<KeyframeView>],
+        ranges: &[(f32, f32)],
+        hovered: Option<(usize, KeyframeId)>,
+        legend_hit: Option<usize>,
+        result: &mut MultiCurveEditorResponse,
+    ) {
+        let bindings = self.config.key_bindings;
+        if (response.has_focus() || response.hovered())
+            && ui.input(|i| KeyBindings::pressed(i, bindings.toggle_normalized))
+        {
+            result.toggle_normalization = true;
+        }
+
+        if response.clicked() {
+            if let Some(index) = legend_hit {
+                result.toggle_visibility = Some(index);
+                let curve = &self.curves[index];
+                result.toggled_track = curve.track_id.map(|track_id| (track_id, !curve.visible));
+            } else {
+                result.clicked_keyframe = hovered;
+            }
+            return;
+        }
+
+        let padding = self.config.vertical_padding;
+
+        // Box selection on empty background. Shift adds, Ctrl/Cmd toggles.
+        let box_select_id = id.with("box_select");
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && hovered.is_none()
+            && legend_hit.is_none()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            ui.data_mut(|data| data.insert_temp(box_select_id, pos));
+        }
+        if let Some(start) = ui.data(|data| data.get_temp::<Pos2>(box_select_id)) {
+            if let Some(pos) = response.interact_pointer_pos() {
+                let selection_rect = Rect::from_two_pos(start, pos);
+                let painter = ui.painter_at(rect);
+                painter.rect_filled(
+                    selection_rect,
+                    0.0,
+                    self.config.box_select_color.linear_multiply(0.1),
+                );
+                painter.rect_stroke(
+                    selection_rect,
+                    0.0,
+                    Stroke::new(1.0, self.config.box_select_color),
+                    egui::StrokeKind::Inside,
+                );
+
+                if response.drag_stopped() {
+                    let enclosed = self
+                        .visible_curves()
+                        .flat_map(|index| {
+                            keyframes[index].iter().filter_map(move |kf| {
+                                let screen_pos = Pos2::new(
+                                    self.space.unit_to_clipped(kf.position),
+                                    value_to_y(rect, padding, ranges[index], kf.value),
+                                );
+                                selection_rect.contains(screen_pos).then_some(kf.id)
+                            })
+                        })
+                        .collect();
+                    let mode = ui.input(|i| bindings.selection_mode(&i.modifiers));
+                    result.box_selected = Some((enclosed, mode));
+                }
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<Pos2>(box_select_id));
+            }
+            return;
+        }
+
+        // Dragging a selected keyframe moves the selection on all curves.
+        // The grabbed curve, keyframe time and value are kept while dragging.
+        let drag_id = id.with("selection_drag");
+        if response.drag_started_by(egui::PointerButton::Primary)
+            && let Some((index, kf_id)) = hovered.filter(|(_, kf_id)| self.selected.contains(kf_id))
+            && let Some(kf) = keyframes[index]
+                .iter()
+                .find(|kf| kf.id == kf_id && !kf.locked)
+        {
+            ui.data_mut(|data| data.insert_temp(drag_id, (index, kf.position, kf.value)));
+        }
+        let Some((grabbed, start_time, start_value)) =
+            ui.data(|data| data.get_temp::<(usize, TimeTick, f32)>(drag_id))
+        else {
+            return;
+        };
+
+        if let Some(pos) = response.interact_pointer_pos() {
+            let delta_time = self.space.clipped_to_unit(pos.x) - start_time;
+            let grabbed_range = ranges[grabbed];
+            let delta_value = y_to_value(rect, padding, grabbed_range, pos.y) - start_value;
+            let (delta_time, delta_value) = if ui
+                .input(|i| KeyBindings::held(&i.modifiers, bindings.constrain_axis))
+            {
+                let start_y = value_to_y(rect, padding, grabbed_range, start_value);
+                if (pos.x - self.space.unit_to_clipped(start_time)).abs() > (pos.y - start_y).abs()
+                {
+                    (delta_time, 0.0)
+                } else {
+                    (TimeTick::default(), delta_value)
+                }
+            } else {
+                (delta_time, delta_value)
+            };
+
+            let painter = ui.painter_at(rect);
+            for index in self.visible_curves() {
+                let curve_delta = scaled_value_delta(delta_value, grabbed_range, ranges[index]);
+                let ids: Vec<KeyframeId> = keyframes[index]
+                    .iter()
+                    .filter(|kf| self.selected.contains(&kf.id) && !kf.pinned && !kf.locked)
+                    .map(|kf| {
+                        // Preview the moved keyframe.
+                        let moved = Pos2::new(
+                            self.space.unit_to_clipped(kf.position + delta_time),
+                            value_to_y(rect, padding, ranges[index], kf.value + curve_delta),
+                        );
+                        KeyframeDot::new(moved)
+                            .color(self.curves[index].color.linear_multiply(0.6))
+                            .selected(true)
+                            .textures(self.config.keyframe_textures)
+                            .paint(&painter);
+                        kf.id
+                    })
+                    .collect();
+
+                if response.drag_stopped()
+                    && !ids.is_empty()
+                    && (delta_time != TimeTick::default() || curve_delta != 0.0)
+                {
+                    result.commands.push(AnimationCommand::OffsetKeyframes {
+                        keyframe_ids: ids,
+                        delta_time,
+                        delta_value: curve_delta as f64,
+                    });
+                    result.transform_ended = true;
+                }
+            }
+        }
+        if response.drag_stopped() {
+            ui.data_mut(|data| data.remove::<(usize, TimeTick, f32)>(drag_id));
+        }
+    }
+
+    fn visible_curves(&self) -> impl Iterator<Item = usize> + '_ {
+        self.curves
+            .iter()
+            .enumerate()
+            .filter(|(_, curve)| curve.visible)
+            .map(|(index, _)| index)
+    }
+}
+
+/// Value range of each curve.
+///
+/// `ranges` holds each curve's own range, `None` for hidden or empty curves.
+/// With [`ValueNormalization::Shared`] all curves get `shared`, or the union
+/// of `ranges` if it is `None`. Degenerate ranges are widened so flat curves
+/// end up centered.
+fn curve_value_ranges(
+    ranges: &[Option<(f32, f32)>],
+    normalization: ValueNormalization,
+    shared: Option<(f32, f32)>,
+) -> Vec<(f32, f32)> {
+    match normalization {
+        ValueNormalization::Shared => {
+            let range =
+                shared.unwrap_or_else(|| {
+                    display_range(ranges.iter().flatten().copied().reduce(
+                        |(a_min, a_max), (b_min, b_max)| (a_min.min(b_min), a_max.max(b_max)),
+                    ))
+                });
+            vec![range; ranges.len()]
+        }
+        ValueNormalization::PerCurve => ranges.iter().map(|range| display_range(*range)).collect(),
+    }
+}
+
+/// Convert a value offset on a curve spanning `from` into the offset that
+/// moves a curve spanning `to` by the same on-screen distance.
+fn scaled_value_delta(delta: f32, from: (f32, f32), to: (f32, f32)) -> f32 {
+    let from_span = from.1 - from.0;
+    if from_span.abs() < 1e-6 {
+        return 0.0;
+    }
+    delta * (to.1 - to.0) / from_span
+}
+
+/// Screen rect of the legend entry of curve `index`.
+fn legend_rect(rect: Rect, index: usize) -> Rect {
+    Rect::from_min_size(
+        Pos2::new(
+            rect.left() + 4.0,
+            rect.top() + 4.0 + index as f32 * LEGEND_ROW_HEIGHT,
+        ),
+        Vec2::new(110.0, LEGEND_ROW_HEIGHT),
+    )
+}
+
+/// Index of the legend entry at `pos`. There is no legend for fewer than two
+/// curves.
+fn legend_entry_at(rect: Rect, count: usize, pos: Pos2) -> Option<usize> {
+    if count < 2 {
+        return None;
+    }
+    (0..count).find(|&index| legend_rect(rect, index).contains(pos))
+}
+
+/// Paint an eye glyph centered at `center`, struck through when closed.
+fn paint_eye(painter: &egui::Painter, center: Pos2, open: bool, color: Color32) {
+    let stroke = Stroke::new(1.0, color);
+    let (half_width, half_height) = (5.0, 3.0);
+    let lid = |sign: f32| -> Vec<Pos2> {
+        (0..=8)
+            .map(|step| {
+                let t = step as f32 / 8.0 * 2.0 - 1.0;
+                Pos2::new(
+                    center.x + t * half_width,
+                    center.y + sign * half_height * (1.0 - t * t),
+                )
+            })
+            .collect()
+    };
+    painter.add(egui::Shape::line(lid(-1.0), stroke));
+    painter.add(egui::Shape::line(lid(1.0), stroke));
+    if open {
+        painter.circle_filled(center, 1.5, color);
+    } else {
+        painter.line_segment(
+            [
+                center + Vec2::new(-half_width, half_height),
+                center + Vec2::new(half_width, -half_height),
+            ],
+            stroke,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_range_spans_all_curves() {
+        let ranges = [Some((0.0, 1.0)), None, Some((-2.0, 0.5))];
+        assert_eq!(
+            curve_value_ranges(&ranges, ValueNormalization::Shared, None),
+            vec![(-2.0, 1.0); 3]
+        );
+        assert_eq!(
+            curve_value_ranges(&ranges, ValueNormalization::Shared, Some((0.0, 10.0))),
+            vec![(0.0, 10.0); 3]
+        );
+    }
+
+    #[test]
+    fn per_curve_ranges_scale_drag_offsets() {
+        let ranges = [Some((0.0, 1.0)), Some((0.0, 100.0)), Some((5.0, 5.0))];
+        let resolved = curve_value_ranges(&ranges, ValueNormalization::PerCurve, None);
+        assert_eq!(resolved, vec![(0.0, 1.0), (0.0, 100.0), (4.0, 6.0)]);
+
+        // A quarter of the grabbed curve's height is a quarter of the other's.
+        assert_eq!(scaled_value_delta(0.25, resolved[0], resolved[1]), 25.0);
+        assert_eq!(scaled_value_delta(0.25, resolved[0], resolved[2]), 0.5);
+    }
+
+    #[test]
+    fn legend_only_lists_multiple_curves() {
+        let rect = Rect::from_min_size(Pos2::ZERO, Vec2::new(400.0, 200.0));
+        let second = legend_rect(rect, 1).center();
+        assert_eq!(legend_entry_at(rect, 3, second), Some(1));
+        assert_eq!(legend_entry_at(rect, 3, Pos2::new(300.0, 100.0)), None);
+        assert_eq!(
+            legend_entry_at(rect, 1, legend_rect(rect, 0).center()),
+            None
+        );
+    }
+}
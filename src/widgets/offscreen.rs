@@ -0,0 +1,262 @@
+//! Offscreen rendering of widgets into images.
+
+use crate::HashMap;
+use egui::epaint::{ClippedPrimitive, ImageData, Mesh, Primitive, TextureId, Vertex};
+use egui::{
+    CentralPanel, Color32, ColorImage, Context, Frame, FullOutput, Pos2, RawInput, Rect, Ui, Vec2,
+    ViewportId,
+};
+
+/// Run `add_contents` for one frame in a UI of `size` points rendered at
+/// `pixels_per_point` and return the context with its output.
+pub(crate) fn run_frame(
+    size: Vec2,
+    pixels_per_point: f32,
+    add_contents: impl FnOnce(&mut Ui),
+) -> (Context, FullOutput) {
+    let ctx = Context::default();
+    let output = run_input_frame(
+        &ctx,
+        size,
+        pixels_per_point,
+        RawInput::default(),
+        add_contents,
+    );
+    (ctx, output)
+}
+
+/// Run `add_contents` for one more frame of `ctx` with the events and
+/// modifiers of `input`, e.g. to simulate the pointer and keyboard over
+/// several frames.
+pub(crate) fn run_input_frame(
+    ctx: &Context,
+    size: Vec2,
+    pixels_per_point: f32,
+    mut input: RawInput,
+    add_contents: impl FnOnce(&mut Ui),
+) -> FullOutput {
+    input.screen_rect = Some(Rect::from_min_size(Pos2::ZERO, size));
+    input
+        .viewports
+        .entry(ViewportId::ROOT)
+        .or_default()
+        .native_pixels_per_point = Some(pixels_per_point);
+
+    let mut add_contents = Some(add_contents);
+    ctx.run(input, |ctx| {
+        CentralPanel::default().frame(Frame::NONE).show(ctx, |ui| {
+            if let Some(add_contents) = add_contents.take() {
+                add_contents(ui);
+            }
+        });
+    })
+}
+
+/// Run `add_contents` for one frame in a UI of `size` points and rasterize
+/// it into an image of `size * pixels_per_point` pixels.
+///
+/// Unpainted pixels are transparent. Paint callbacks are skipped.
+pub(crate) fn render_image(
+    size: Vec2,
+    pixels_per_point: f32,
+    add_contents: impl FnOnce(&mut Ui),
+) -> ColorImage {
+    let (ctx, output) = run_frame(size, pixels_per_point, add_contents);
+
+    let mut textures: HashMap<TextureId, ColorImage> = HashMap::default();
+    for (id, delta) in output.textures_delta.set {
+        let ImageData::Color(image) = delta.image;
+        match delta.pos {
+            Some([x, y]) => {
+                if let Some(texture) = textures.get_mut(&id) {
+                    patch(texture, &image, x, y);
+                }
+            }
+            None => {
+                textures.insert(id, (*image).clone());
+            }
+        }
+    }
+
+    let primitives = ctx.tessellate(output.shapes, pixels_per_point);
+    let pixel_size = [
+        (size.x * pixels_per_point).round().max(0.0) as usize,
+        (size.y * pixels_per_point).round().max(0.0) as usize,
+    ];
+    let mut canvas = Canvas::new(pixel_size);
+    for ClippedPrimitive {
+        clip_rect,
+        primitive,
+    } in &primitives
+    {
+        if let Primitive::Mesh(mesh) = primitive {
+            let clip = Rect::from_min_max(
+                (clip_rect.min.to_vec2() * pixels_per_point).to_pos2(),
+                (clip_rect.max.to_vec2() * pixels_per_point).to_pos2(),
+            );
+            canvas.draw_mesh(mesh, textures.get(&mesh.texture_id), clip, pixels_per_point);
+        }
+    }
+    canvas.into_image()
+}
+
+/// Copy `image` into `texture` with its top left corner at `(x, y)`.
+fn patch(texture: &mut ColorImage, image: &ColorImage, x: usize, y: usize) {
+    let [width, height] = texture.size;
+    for (row, pixels) in image.pixels.chunks(image.size[0].max(1)).enumerate() {
+        if y + row >= height {
+            break;
+        }
+        let start = (y + row) * width + x;
+        let count = pixels.len().min(width.saturating_sub(x));
+        texture.pixels[start..start + count].copy_from_slice(&pixels[..count]);
+    }
+}
+
+/// Premultiplied RGBA accumulation buffer.
+struct Canvas {
+    size: [usize; 2],
+    pixels: Vec<[f32; 4]>,
+}
+
+impl Canvas {
+    fn new(size: [usize; 2]) -> Self {
+        Self {
+            size,
+            pixels: vec![[0.0; 4]; size[0] * size[1]],
+        }
+    }
+
+    /// Rasterize the triangles of `mesh`, given in points, inside `clip`,
+    /// given in pixels.
+    fn draw_mesh(
+        &mut self,
+        mesh: &Mesh,
+        texture: Option<&ColorImage>,
+        clip: Rect,
+        pixels_per_point: f32,
+    ) {
+        for triangle in mesh.indices.chunks_exact(3) {
+            let vertices = [0, 1, 2].map(|i| {
+                let vertex = mesh.vertices[triangle[i] as usize];
+                Vertex {
+                    pos: (vertex.pos.to_vec2() * pixels_per_point).to_pos2(),
+                    ..vertex
+                }
+            });
+            self.draw_triangle(&vertices, texture, clip);
+        }
+    }
+
+    fn draw_triangle(&mut self, [a, b, c]: &[Vertex; 3], texture: Option<&ColorImage>, clip: Rect) {
+        let area = edge(a.pos, b.pos, c.pos);
+        if area.abs() < f32::EPSILON {
+            return;
+        }
+
+        let bounds = Rect::from_points(&[a.pos, b.pos, c.pos])
+            .intersect(clip)
+            .intersect(Rect::from_min_size(
+                Pos2::ZERO,
+                Vec2::new(self.size[0] as f32, self.size[1] as f32),
+            ));
+        if !bounds.is_positive() {
+            return;
+        }
+
+        let (x0, x1) = (bounds.min.x.floor() as usize, bounds.max.x.ceil() as usize);
+        let (y0, y1) = (bounds.min.y.floor() as usize, bounds.max.y.ceil() as usize);
+        for y in y0..y1.min(self.size[1]) {
+            for x in x0..x1.min(self.size[0]) {
+                let p = Pos2::new(x as f32 + 0.5, y as f32 + 0.5);
+                if !clip.contains(p) {
+                    continue;
+                }
+                // Barycentric weights; all non-negative inside either winding.
+                let weights = [
+                    edge(b.pos, c.pos, p) / area,
+                    edge(c.pos, a.pos, p) / area,
+                    edge(a.pos, b.pos, p) / area,
+                ];
+                if weights.iter().any(|w| *w < 0.0) {
+                    continue;
+                }
+
+                let mut color = [0.0; 4];
+                let mut uv = Vec2::ZERO;
+                for (vertex, weight) in [a, b, c].into_iter().zip(weights) {
+                    for (channel, value) in color.iter_mut().zip(vertex.color.to_array()) {
+                        *channel += weight * value as f32 / 255.0;
+                    }
+                    uv += weight * vertex.uv.to_vec2();
+                }
+                if let Some(texture) = texture {
+                    let texel = sample(texture, uv).to_array();
+                    for (channel, value) in color.iter_mut().zip(texel) {
+                        *channel *= value as f32 / 255.0;
+                    }
+                }
+                self.blend(y * self.size[0] + x, color);
+            }
+        }
+    }
+
+    /// Premultiplied "over" blending of `color` onto pixel `index`.
+    fn blend(&mut self, index: usize, color: [f32; 4]) {
+        let pixel = &mut self.pixels[index];
+        let keep = 1.0 - color[3];
+        for (dst, src) in pixel.iter_mut().zip(color) {
+            *dst = src + *dst * keep;
+        }
+    }
+
+    fn into_image(self) -> ColorImage {
+        let to_byte = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let pixels = self
+            .pixels
+            .into_iter()
+            .map(|[r, g, b, a]| {
+                Color32::from_rgba_premultiplied(to_byte(r), to_byte(g), to_byte(b), to_byte(a))
+            })
+            .collect();
+        ColorImage::new(self.size, pixels)
+    }
+}
+
+/// Twice the signed area of the triangle `a`, `b`, `p`.
+fn edge(a: Pos2, b: Pos2, p: Pos2) -> f32 {
+    (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Nearest texel of `texture` at normalized `uv`.
+fn sample(texture: &ColorImage, uv: Vec2) -> Color32 {
+    let [width, height] = texture.size;
+    if width == 0 || height == 0 {
+        return Color32::WHITE;
+    }
+    let x = ((uv.x * width as f32) as usize).min(width - 1);
+    let y = ((uv.y * height as f32) as usize).min(height - 1);
+    texture.pixels[y * width + x]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rect_is_rasterized() {
+        let image = render_image(Vec2::new(10.0, 8.0), 2.0, |ui| {
+            ui.painter().rect_filled(
+                Rect::from_min_size(Pos2::new(2.0, 2.0), Vec2::new(4.0, 4.0)),
+                0.0,
+                Color32::RED,
+            );
+        });
+
+        assert_eq!(image.size, [20, 16]);
+        let pixel = |x: usize, y: usize| image.pixels[y * 20 + x];
+        assert_eq!(pixel(8, 8), Color32::RED);
+        assert_eq!(pixel(1, 1), Color32::TRANSPARENT);
+        assert_eq!(pixel(15, 8), Color32::TRANSPARENT);
+    }
+}
@@ -0,0 +1,173 @@
+//! Playhead line, head and time label.
+
+use crate::TimeTick;
+use egui::{Align2, Color32, CornerRadius, FontId, Painter, Pos2, Rect, Shape, Stroke, Vec2};
+
+/// Text shown in the label bubble at the playhead's head.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PlayheadLabel {
+    /// Time in seconds, e.g. `1.25s`.
+    Seconds,
+    /// Frame number at the given frame rate, e.g. `30`.
+    Frames(f64),
+}
+
+impl PlayheadLabel {
+    /// Format `time` for the label.
+    pub fn format(&self, time: TimeTick) -> String {
+        match self {
+            Self::Seconds => format!("{:.2}s", time.value()),
+            Self::Frames(fps) => format!("{}", time.to_frames(*fps).round() as i64),
+        }
+    }
+}
+
+/// Appearance and grab zone of a playhead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlayheadStyle {
+    /// Whether the playhead is drawn. Disable it when the host draws one
+    /// playhead across several widgets; dragging it still scrubs.
+    pub visible: bool,
+    /// Color of the line, head and label bubble.
+    pub color: Color32,
+    /// Width of the line.
+    pub line_width: f32,
+    /// Dash and gap length of the line; `None` draws it solid.
+    pub dash: Option<(f32, f32)>,
+    /// Size of the triangular head above the line.
+    pub head_size: f32,
+    /// Label bubble attached to the head; `None` hides it.
+    pub label: Option<PlayheadLabel>,
+    /// Half width of the invisible zone in which the pointer grabs the
+    /// playhead; `None` uses [`InteractionSizes::edge_grab`].
+    ///
+    /// [`InteractionSizes::edge_grab`]: super::InteractionSizes::edge_grab
+    pub grab_radius: Option<f32>,
+}
+
+impl Default for PlayheadStyle {
+    fn default() -> Self {
+        Self {
+            visible: true,
+            color: Color32::from_rgb(255, 100, 100),
+            line_width: 2.0,
+            dash: None,
+            head_size: 6.0,
+            label: None,
+            grab_radius: None,
+        }
+    }
+}
+
+impl PlayheadStyle {
+    /// Set the color.
+    pub fn color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Set whether the playhead is drawn.
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.visible = visible;
+        self
+    }
+
+    /// Draw the line dashed.
+    pub fn dashed(mut self, dash: f32, gap: f32) -> Self {
+        self.dash = Some((dash, gap));
+        self
+    }
+
+    /// Show a label bubble at the head.
+    pub fn label(mut self, label: PlayheadLabel) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Half width of the grab zone, falling back to `default` if unset.
+    pub fn grab_radius_or(&self, default: f32) -> f32 {
+        self.grab_radius.unwrap_or(default)
+    }
+
+    /// Paint the playhead at `x` for `time` spanning `rect` vertically.
+    ///
+    /// The head is drawn just above `rect` and the label bubble just inside
+    /// its top edge, kept within `rect` horizontally.
+    pub fn paint(&self, painter: &Painter, rect: Rect, x: f32, time: TimeTick) {
+        if !self.visible || x < rect.left() || x > rect.right() {
+            return;
+        }
+
+        let stroke = Stroke::new(self.line_width, self.color);
+        let (top, bottom) = (Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom()));
+        match self.dash {
+            Some((dash, gap)) if dash > 0.0 => {
+                painter.extend(Shape::dashed_line(
+                    &[top, bottom],
+                    stroke,
+                    dash,
+                    gap.max(0.0),
+                ));
+            }
+            _ => {
+                painter.line_segment([top, bottom], stroke);
+            }
+        }
+
+        if self.head_size > 0.0 {
+            painter.add(Shape::convex_polygon(
+                vec![
+                    top,
+                    Pos2::new(x - self.head_size, rect.top() - self.head_size),
+                    Pos2::new(x + self.head_size, rect.top() - self.head_size),
+                ],
+                self.color,
+                Stroke::NONE,
+            ));
+        }
+
+        if let Some(label) = self.label {
+            let galley = painter.layout_no_wrap(
+                label.format(time),
+                FontId::monospace(10.0),
+                contrast_text(self.color),
+            );
+            let size = galley.size() + Vec2::new(6.0, 2.0);
+            let left =
+                (x - size.x / 2.0).clamp(rect.left(), (rect.right() - size.x).max(rect.left()));
+            let bubble = Rect::from_min_size(Pos2::new(left, rect.top()), size);
+            painter.rect_filled(bubble, CornerRadius::same(3), self.color);
+            painter.galley(
+                Align2::CENTER_CENTER
+                    .anchor_size(bubble.center(), galley.size())
+                    .min,
+                galley,
+                Color32::PLACEHOLDER,
+            );
+        }
+    }
+}
+
+/// Black or white, whichever reads better on `background`.
+fn contrast_text(background: Color32) -> Color32 {
+    let [r, g, b, _] = background.to_array();
+    let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+    if luma > 140.0 {
+        Color32::BLACK
+    } else {
+        Color32::WHITE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_formats_time_and_frames() {
+        let time = TimeTick::new(1.25);
+        assert_eq!(PlayheadLabel::Seconds.format(time), "1.25s");
+        assert_eq!(PlayheadLabel::Frames(24.0).format(time), "30");
+        assert_eq!(PlayheadStyle::default().grab_radius_or(8.0), 8.0);
+    }
+}
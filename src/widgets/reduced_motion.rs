@@ -0,0 +1,45 @@
+//! Global reduced-motion and performance mode.
+//!
+//! When enabled, the widgets of this crate skip cosmetic effects: hovered
+//! keyframes and handles no longer grow, and selection borders are drawn
+//! solid instead of dashed. The setting lives in the egui context, so one
+//! call covers every widget in the app.
+
+use egui::{Context, Id};
+
+fn setting_id() -> Id {
+    Id::new("egui_keyframe_reduced_motion")
+}
+
+/// Enable or disable reduced motion for all widgets drawn with `ctx`.
+pub fn set_reduced_motion(ctx: &Context, reduced: bool) {
+    ctx.data_mut(|data| data.insert_temp(setting_id(), reduced));
+}
+
+/// Whether cosmetic effects are disabled.
+///
+/// True if enabled with [`set_reduced_motion`] or if the app disabled egui's
+/// own animations by setting `animation_time` to zero.
+pub fn reduced_motion(ctx: &Context) -> bool {
+    ctx.data(|data| data.get_temp::<bool>(setting_id()))
+        .unwrap_or(false)
+        || ctx.style().animation_time <= 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn follows_setting_and_animation_time() {
+        let ctx = Context::default();
+        assert!(!reduced_motion(&ctx));
+
+        set_reduced_motion(&ctx, true);
+        assert!(reduced_motion(&ctx));
+        set_reduced_motion(&ctx, false);
+
+        ctx.style_mut(|style| style.animation_time = 0.0);
+        assert!(reduced_motion(&ctx));
+    }
+}
@@ -0,0 +1,288 @@
+//! Lane showing named time ranges above a timeline.
+
+use crate::core::section::{SectionId, Sections};
+use crate::{SpaceTransform, TimeTick};
+use egui::{Color32, CursorIcon, Pos2, Rect, Response, Sense, Stroke, Ui};
+
+/// Configuration for the sections lane.
+#[derive(Debug, Clone)]
+pub struct SectionsLaneConfig {
+    /// Height of the lane in pixels.
+    pub height: f32,
+    /// Background color.
+    pub background: Color32,
+    /// Label color.
+    pub text_color: Color32,
+    /// Horizontal distance in pixels within which a drag grabs a boundary.
+    pub edge_grab_distance: f32,
+}
+
+impl Default for SectionsLaneConfig {
+    fn default() -> Self {
+        Self {
+            height: 20.0,
+            background: Color32::from_gray(28),
+            text_color: Color32::from_gray(230),
+            edge_grab_distance: 4.0,
+        }
+    }
+}
+
+/// Which boundary of a section is dragged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SectionEdge {
+    Start,
+    End,
+}
+
+/// Response from the [`SectionsLane`].
+pub struct SectionsLaneResponse {
+    /// The egui response for the lane.
+    pub response: Response,
+    /// Section that was clicked.
+    pub clicked: Option<SectionId>,
+    /// Section whose boundaries were dragged to `(start, end)`.
+    pub resized: Option<(SectionId, TimeTick, TimeTick)>,
+    /// Section that was renamed.
+    pub renamed: Option<(SectionId, String)>,
+    /// Section moved to a new index in the sequence.
+    pub reordered: Option<(SectionId, usize)>,
+    /// Section the user asked to remove.
+    pub removed: Option<SectionId>,
+}
+
+impl SectionsLaneResponse {
+    /// Apply the requested edits to `sections`.
+    pub fn apply(&self, sections: &mut Sections) {
+        if let Some((id, start, end)) = self.resized {
+            sections.set_bounds(id, start, end);
+        }
+        if let Some((id, name)) = &self.renamed {
+            sections.rename(*id, name.clone());
+        }
+        if let Some((id, index)) = self.reordered {
+            sections.reorder(id, index);
+        }
+        if let Some(id) = self.removed {
+            sections.remove(id);
+        }
+    }
+}
+
+/// Lane of labeled, colored time ranges.
+///
+/// Drag a boundary to resize a section, double-click to rename it and
+/// right-click to reorder or remove it. Edits are reported in the
+/// [`SectionsLaneResponse`]; apply them with [`SectionsLaneResponse::apply`].
+pub struct SectionsLane<'a> {
+    sections: &'a Sections,
+    space: &'a SpaceTransform,
+    config: SectionsLaneConfig,
+}
+
+impl<'a> SectionsLane<'a> {
+    /// Create a new sections lane.
+    pub fn new(sections: &'a Sections, space: &'a SpaceTransform) -> Self {
+        Self {
+            sections,
+            space,
+            config: SectionsLaneConfig::default(),
+        }
+    }
+
+    /// Set the configuration.
+    pub fn config(mut self, config: SectionsLaneConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Show the lane in `rect`.
+    pub fn show(self, ui: &mut Ui, rect: Rect) -> SectionsLaneResponse {
+        let response = ui.allocate_rect(rect, Sense::click_and_drag());
+        let mut result = SectionsLaneResponse {
+            response: response.clone(),
+            clicked: None,
+            resized: None,
+            renamed: None,
+            reordered: None,
+            removed: None,
+        };
+
+        if ui.is_rect_visible(rect) {
+            self.paint(&ui.painter_at(rect), rect);
+        }
+
+        let edge_id = response.id.with("edge");
+        let rename_id = response.id.with("rename");
+        let context_id = response.id.with("context");
+
+        // Boundary dragging.
+        let hovered_edge = response.hover_pos().and_then(|pos| self.edge_at(pos.x));
+        if hovered_edge.is_some() {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+        }
+        if response.drag_started()
+            && let Some(edge) = hovered_edge
+        {
+            ui.data_mut(|data| data.insert_temp(edge_id, edge));
+        }
+        if let Some((id, edge)) = ui.data(|data| data.get_temp::<(SectionId, SectionEdge)>(edge_id))
+        {
+            ui.ctx().set_cursor_icon(CursorIcon::ResizeHorizontal);
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+                && let Some(section) = self.sections.get(id)
+            {
+                let time = self.space.clipped_to_unit(pos.x);
+                result.resized = Some(match edge {
+                    SectionEdge::Start => (id, time, section.end),
+                    SectionEdge::End => (id, section.start, time),
+                });
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<(SectionId, SectionEdge)>(edge_id));
+            }
+        }
+
+        let section_under_pointer = response
+            .interact_pointer_pos()
+            .and_then(|pos| self.sections.section_at(self.space.clipped_to_unit(pos.x)))
+            .map(|section| section.id);
+
+        if response.clicked() {
+            result.clicked = section_under_pointer;
+        }
+        if response.double_clicked()
+            && let Some(id) = section_under_pointer
+            && let Some(section) = self.sections.get(id)
+        {
+            ui.data_mut(|data| data.insert_temp(rename_id, (id, section.name.clone())));
+        }
+        if response.secondary_clicked()
+            && let Some(id) = section_under_pointer
+        {
+            ui.data_mut(|data| data.insert_temp(context_id, id));
+        }
+
+        // Inline rename.
+        if let Some((id, mut name)) =
+            ui.data(|data| data.get_temp::<(SectionId, String)>(rename_id))
+        {
+            match self.section_rect(rect, id) {
+                Some(section_rect) => {
+                    let edit = ui.put(
+                        section_rect.shrink(1.0),
+                        egui::TextEdit::singleline(&mut name).margin(egui::vec2(2.0, 0.0)),
+                    );
+                    edit.request_focus();
+                    if edit.lost_focus() {
+                        ui.data_mut(|data| data.remove::<(SectionId, String)>(rename_id));
+                        if !ui.input(|i| i.key_pressed(egui::Key::Escape)) {
+                            result.renamed = Some((id, name));
+                        }
+                    } else {
+                        ui.data_mut(|data| data.insert_temp(rename_id, (id, name)));
+                    }
+                }
+                None => ui.data_mut(|data| data.remove::<(SectionId, String)>(rename_id)),
+            }
+        }
+
+        // Context menu.
+        let context_section: Option<SectionId> = ui.data(|data| data.get_temp(context_id));
+        response.context_menu(|ui| {
+            let Some(id) = context_section else {
+                ui.close();
+                return;
+            };
+            let index = self.sections.index_of(id).unwrap_or_default();
+            if ui.button("Rename").clicked() {
+                if let Some(section) = self.sections.get(id) {
+                    ui.data_mut(|data| data.insert_temp(rename_id, (id, section.name.clone())));
+                }
+                ui.close();
+            }
+            if ui
+                .add_enabled(index > 0, egui::Button::new("Move earlier"))
+                .clicked()
+            {
+                result.reordered = Some((id, index - 1));
+                ui.close();
+            }
+            if ui
+                .add_enabled(
+                    index + 1 < self.sections.len(),
+                    egui::Button::new("Move later"),
+                )
+                .clicked()
+            {
+                result.reordered = Some((id, index + 1));
+                ui.close();
+            }
+            ui.separator();
+            if ui.button("Remove").clicked() {
+                result.removed = Some(id);
+                ui.close();
+            }
+        });
+
+        result
+    }
+
+    /// Paint the sections.
+    pub fn paint(&self, painter: &egui::Painter, rect: Rect) {
+        painter.rect_filled(rect, 0.0, self.config.background);
+
+        for section in self.sections.iter() {
+            let Some(section_rect) = self.section_rect(rect, section.id) else {
+                continue;
+            };
+            painter.rect_filled(section_rect, 2.0, section.color.linear_multiply(0.6));
+            painter.rect_stroke(
+                section_rect,
+                2.0,
+                Stroke::new(1.0, section.color),
+                egui::StrokeKind::Inside,
+            );
+            painter.with_clip_rect(section_rect.intersect(rect)).text(
+                Pos2::new(
+                    section_rect.left().max(rect.left()) + 4.0,
+                    section_rect.center().y,
+                ),
+                egui::Align2::LEFT_CENTER,
+                &section.name,
+                egui::FontId::proportional(11.0),
+                self.config.text_color,
+            );
+        }
+    }
+
+    /// Screen rect of a section, or `None` if it is off screen.
+    fn section_rect(&self, rect: Rect, id: SectionId) -> Option<Rect> {
+        let section = self.sections.get(id)?;
+        let left = self.space.unit_to_clipped(section.start);
+        let right = self.space.unit_to_clipped(section.end);
+        (right >= rect.left() && left <= rect.right()).then(|| {
+            Rect::from_min_max(
+                Pos2::new(left, rect.top() + 1.0),
+                Pos2::new(right, rect.bottom() - 1.0),
+            )
+        })
+    }
+
+    /// Section boundary within grab distance of screen x.
+    fn edge_at(&self, x: f32) -> Option<(SectionId, SectionEdge)> {
+        self.sections.iter().find_map(|section| {
+            let near = |time: TimeTick| {
+                (self.space.unit_to_clipped(time) - x).abs() <= self.config.edge_grab_distance
+            };
+            if near(section.end) {
+                Some((section.id, SectionEdge::End))
+            } else if near(section.start) {
+                Some((section.id, SectionEdge::Start))
+            } else {
+                None
+            }
+        })
+    }
+}
@@ -0,0 +1,255 @@
+//! Rendering of widgets to standalone SVG.
+
+use super::offscreen::run_frame;
+use egui::epaint::{ClippedShape, ColorMode, PathStroke, TextShape};
+use egui::{Color32, Pos2, Rect, Shape, Stroke, Ui, Vec2};
+use std::fmt::Write;
+
+/// Run `add_contents` for one frame in a UI of `size` points and return the
+/// painted shapes as an SVG document.
+///
+/// Meshes, e.g. textured keyframe dots, are not exported.
+pub(crate) fn render_svg(size: Vec2, add_contents: impl FnOnce(&mut Ui)) -> String {
+    let (_, output) = run_frame(size, 1.0, add_contents);
+    shapes_to_svg(&output.shapes, size)
+}
+
+/// SVG document of `size` points containing `shapes`.
+pub(crate) fn shapes_to_svg(shapes: &[ClippedShape], size: Vec2) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\">\n",
+        w = size.x,
+        h = size.y,
+    );
+    for clipped in shapes {
+        write_shape(&mut svg, &clipped.shape);
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn write_shape(svg: &mut String, shape: &Shape) {
+    // Writing to a `String` cannot fail.
+    let _ = match shape {
+        Shape::Vec(shapes) => {
+            shapes.iter().for_each(|shape| write_shape(svg, shape));
+            Ok(())
+        }
+        Shape::Circle(circle) => writeln!(
+            svg,
+            "<circle cx=\"{}\" cy=\"{}\" r=\"{}\"{}{}/>",
+            circle.center.x,
+            circle.center.y,
+            circle.radius,
+            fill(circle.fill),
+            stroke(circle.stroke),
+        ),
+        Shape::Ellipse(ellipse) => writeln!(
+            svg,
+            "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"{}{}/>",
+            ellipse.center.x,
+            ellipse.center.y,
+            ellipse.radius.x,
+            ellipse.radius.y,
+            fill(ellipse.fill),
+            stroke(ellipse.stroke),
+        ),
+        Shape::LineSegment { points, stroke: s } => writeln!(
+            svg,
+            "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{}/>",
+            points[0].x,
+            points[0].y,
+            points[1].x,
+            points[1].y,
+            stroke(*s),
+        ),
+        Shape::Path(path) => {
+            let element = if path.closed { "polygon" } else { "polyline" };
+            let points: Vec<String> = path
+                .points
+                .iter()
+                .map(|p| format!("{},{}", p.x, p.y))
+                .collect();
+            let fill = if path.closed {
+                fill(path.fill)
+            } else {
+                fill(Color32::TRANSPARENT)
+            };
+            writeln!(
+                svg,
+                "<{element} points=\"{}\"{fill}{}/>",
+                points.join(" "),
+                path_stroke(&path.stroke),
+            )
+        }
+        Shape::Rect(rect) => writeln!(
+            svg,
+            "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"{}\"{}{}/>",
+            rect.rect.left(),
+            rect.rect.top(),
+            rect.rect.width(),
+            rect.rect.height(),
+            rect.corner_radius.nw,
+            fill(rect.fill),
+            stroke(rect.stroke),
+        ),
+        Shape::Text(text) => write_text(svg, text),
+        Shape::QuadraticBezier(bezier) => {
+            let [p0, p1, p2] = bezier.points;
+            writeln!(
+                svg,
+                "<path d=\"M{},{} Q{},{} {},{}{}\"{}{}/>",
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                p2.x,
+                p2.y,
+                if bezier.closed { " Z" } else { "" },
+                fill(bezier.fill),
+                path_stroke(&bezier.stroke),
+            )
+        }
+        Shape::CubicBezier(bezier) => {
+            let [p0, p1, p2, p3] = bezier.points;
+            writeln!(
+                svg,
+                "<path d=\"M{},{} C{},{} {},{} {},{}{}\"{}{}/>",
+                p0.x,
+                p0.y,
+                p1.x,
+                p1.y,
+                p2.x,
+                p2.y,
+                p3.x,
+                p3.y,
+                if bezier.closed { " Z" } else { "" },
+                fill(bezier.fill),
+                path_stroke(&bezier.stroke),
+            )
+        }
+        Shape::Noop | Shape::Mesh(_) | Shape::Callback(_) => Ok(()),
+    };
+}
+
+fn write_text(svg: &mut String, text: &TextShape) -> std::fmt::Result {
+    let format = text
+        .galley
+        .job
+        .sections
+        .first()
+        .map(|section| &section.format);
+    let color = text
+        .override_text_color
+        .or(format
+            .map(|format| format.color)
+            .filter(|color| *color != Color32::PLACEHOLDER))
+        .unwrap_or(text.fallback_color)
+        .gamma_multiply(text.opacity_factor);
+    let size = format.map_or(12.0, |format| format.font_id.size);
+
+    for row in &text.galley.rows {
+        let Some(first) = row.row.glyphs.first() else {
+            continue;
+        };
+        let pos = text.pos + row.pos.to_vec2() + first.pos.to_vec2();
+        writeln!(
+            svg,
+            "<text x=\"{}\" y=\"{}\" font-family=\"sans-serif\" font-size=\"{size}\"{}>{}</text>",
+            pos.x,
+            pos.y,
+            fill(color),
+            escape(&row.row.text()),
+        )?;
+    }
+    Ok(())
+}
+
+/// `fill` attribute, `none` for transparent colors.
+fn fill(color: Color32) -> String {
+    if color.a() == 0 {
+        " fill=\"none\"".to_owned()
+    } else {
+        format!(" fill=\"{}\"{}", hex(color), opacity("fill-opacity", color))
+    }
+}
+
+/// `stroke` attributes, or nothing for invisible strokes.
+fn stroke(stroke: Stroke) -> String {
+    if stroke.is_empty() {
+        String::new()
+    } else {
+        format!(
+            " stroke=\"{}\" stroke-width=\"{}\"{}",
+            hex(stroke.color),
+            stroke.width,
+            opacity("stroke-opacity", stroke.color),
+        )
+    }
+}
+
+/// Like [`stroke`]; gradient strokes are exported in their mid color.
+fn path_stroke(path_stroke: &PathStroke) -> String {
+    let color = match &path_stroke.color {
+        ColorMode::Solid(color) => *color,
+        ColorMode::UV(color_at) => color_at(
+            Rect::from_min_size(Pos2::ZERO, Vec2::splat(1.0)),
+            Pos2::new(0.5, 0.5),
+        ),
+    };
+    stroke(Stroke::new(path_stroke.width, color))
+}
+
+fn hex(color: Color32) -> String {
+    let [r, g, b, _] = color.to_srgba_unmultiplied();
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+/// Opacity attribute `name` for translucent colors.
+fn opacity(name: &str, color: Color32) -> String {
+    if color.a() == 255 {
+        String::new()
+    } else {
+        format!(" {name}=\"{:.3}\"", color.a() as f32 / 255.0)
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shapes_become_elements() {
+        let shapes = [
+            Shape::line_segment(
+                [Pos2::new(0.0, 1.0), Pos2::new(2.0, 3.0)],
+                Stroke::new(1.0, Color32::RED),
+            ),
+            Shape::rect_filled(
+                Rect::from_min_size(Pos2::ZERO, Vec2::splat(4.0)),
+                0.0,
+                Color32::from_black_alpha(128),
+            ),
+        ]
+        .map(|shape| ClippedShape {
+            clip_rect: Rect::EVERYTHING,
+            shape,
+        });
+        let svg = shapes_to_svg(&shapes, Vec2::new(10.0, 5.0));
+
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains("viewBox=\"0 0 10 5\""));
+        assert!(svg.contains(
+            "<line x1=\"0\" y1=\"1\" x2=\"2\" y2=\"3\" stroke=\"#ff0000\" stroke-width=\"1\"/>"
+        ));
+        assert!(svg.contains("fill=\"#000000\" fill-opacity=\"0.502\""));
+        assert!(svg.ends_with("</svg>\n"));
+    }
+}
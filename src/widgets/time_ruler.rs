@@ -1,7 +1,55 @@
 //! Time ruler widget for timeline displays.
 
+use crate::core::marker::{Marker, MarkerId, Markers};
+use crate::intervals::{IntervalContext, NiceIntervals, TIME_TICK_SPACING, nice_intervals};
+use crate::traits::AnimationCommand;
+use crate::widgets::interaction::InteractionSizes;
 use crate::{SpaceTransform, TimeTick};
-use egui::{Color32, Painter, Pos2, Rect, Stroke};
+use egui::{Color32, Painter, Pos2, Rect, Response, Sense, Stroke, Ui};
+
+/// Size of the marker flag drawn in the ruler.
+const MARKER_FLAG_SIZE: f32 = 7.0;
+
+/// Width of the work range brackets drawn in the ruler.
+const BRACKET_WIDTH: f32 = 5.0;
+
+/// Shade drawn over times outside the work range.
+const OUTSIDE_WORK_RANGE: Color32 = Color32::from_black_alpha(90);
+
+/// Callback invoked with `(old_time, new_time)` whenever the playhead is
+/// scrubbed through a widget.
+///
+/// Consecutive calls during one drag are continuous: `old_time` of a call is
+/// `new_time` of the previous one. This lets hosts drive audio scrubbing.
+pub type ScrubCallback<'a> = Box<dyn FnMut(TimeTick, TimeTick) + 'a>;
+
+/// Report a scrub to `callback`, keeping continuity across frames.
+///
+/// `current_time` is used as the old time when a scrub starts. While
+/// `ongoing` is true the last reported time is kept in egui memory under `id`.
+pub(crate) fn notify_scrub(
+    ui: &Ui,
+    id: egui::Id,
+    current_time: TimeTick,
+    new_time: TimeTick,
+    ongoing: bool,
+    callback: &mut Option<ScrubCallback<'_>>,
+) {
+    let last: Option<TimeTick> = ui.data(|data| data.get_temp(id));
+    let old_time = last.unwrap_or(current_time);
+
+    if ongoing {
+        ui.data_mut(|data| data.insert_temp(id, new_time));
+    } else {
+        ui.data_mut(|data| data.remove::<TimeTick>(id));
+    }
+
+    if old_time != new_time
+        && let Some(callback) = callback
+    {
+        callback(old_time, new_time);
+    }
+}
 
 /// Configuration for the time ruler.
 #[derive(Debug, Clone)]
@@ -18,6 +66,9 @@ pub struct TimeRulerConfig {
     pub tick_color: Color32,
     /// Background color.
     pub background: Color32,
+    /// Pointer grab sizes for markers and work range brackets, resolved for
+    /// UI zoom and touch input when shown.
+    pub interaction: InteractionSizes,
 }
 
 impl Default for TimeRulerConfig {
@@ -29,6 +80,33 @@ impl Default for TimeRulerConfig {
             text_color: Color32::from_gray(180),
             tick_color: Color32::from_gray(100),
             background: Color32::from_gray(30),
+            interaction: InteractionSizes::default(),
+        }
+    }
+}
+
+/// Response from an interactive [`TimeRuler`].
+pub struct TimeRulerResponse {
+    /// The egui response for the ruler.
+    pub response: Response,
+    /// Time the playhead was scrubbed to this frame.
+    pub scrubbed_time: Option<TimeTick>,
+    /// Animation commands to execute (from user interactions), including
+    /// marker edits.
+    pub commands: Vec<AnimationCommand>,
+    /// Work range after dragging one of its brackets.
+    pub work_range: Option<(TimeTick, TimeTick)>,
+    /// Time under the pointer while hovering or scrubbing the ruler, e.g.
+    /// for [`CurveEditor::preview_time`](crate::CurveEditor::preview_time).
+    pub hover_time: Option<TimeTick>,
+}
+
+impl TimeRulerResponse {
+    /// Apply the marker edits in [`commands`](Self::commands) to `markers`,
+    /// for hosts that keep markers outside their undo history.
+    pub fn apply_markers(&self, markers: &mut Markers) {
+        for command in &self.commands {
+            markers.apply(command);
         }
     }
 }
@@ -38,6 +116,10 @@ pub struct TimeRuler<'a> {
     space: &'a SpaceTransform,
     config: TimeRulerConfig,
     fps: Option<f32>,
+    current_time: TimeTick,
+    markers: Option<&'a Markers>,
+    work_range: Option<(TimeTick, TimeTick)>,
+    on_scrub: Option<ScrubCallback<'a>>,
 }
 
 impl<'a> TimeRuler<'a> {
@@ -47,6 +129,10 @@ impl<'a> TimeRuler<'a> {
             space,
             config: TimeRulerConfig::default(),
             fps: None,
+            current_time: TimeTick::default(),
+            markers: None,
+            work_range: None,
+            on_scrub: None,
         }
     }
 
@@ -62,8 +148,268 @@ impl<'a> TimeRuler<'a> {
         self
     }
 
+    /// Set the current time (start point for scrub continuity).
+    pub fn current_time(mut self, time: impl Into<TimeTick>) -> Self {
+        self.current_time = time.into();
+        self
+    }
+
+    /// Show markers as flags.
+    ///
+    /// Drag a flag to move it; right-click to add or delete markers. Edits
+    /// are emitted as [`AnimationCommand::AddMarker`],
+    /// [`AnimationCommand::MoveMarker`] on release and
+    /// [`AnimationCommand::RemoveMarker`]. New markers are labeled with
+    /// [`Markers::next_label`].
+    pub fn markers(mut self, markers: &'a Markers) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    /// Show the in/out work range as draggable brackets.
+    ///
+    /// Dragging a bracket emits [`AnimationCommand::SetWorkRange`].
+    pub fn work_range(mut self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        self.work_range = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Set a callback invoked with `(old_time, new_time)` on every scrub.
+    pub fn on_scrub(mut self, callback: impl FnMut(TimeTick, TimeTick) + 'a) -> Self {
+        self.on_scrub = Some(Box::new(callback));
+        self
+    }
+
+    /// Paint the ruler and handle click/drag scrubbing.
+    ///
+    /// Scrubs emit [`AnimationCommand::SetCurrentTime`] and invoke the scrub
+    /// callback.
+    pub fn show(mut self, ui: &mut Ui, rect: Rect) -> TimeRulerResponse {
+        self.config.interaction = self.config.interaction.for_context(ui.ctx());
+        let response = ui.allocate_rect(rect, Sense::click_and_drag());
+
+        // The dragged marker follows the pointer until the move is
+        // committed on release.
+        let marker_drag_id = response.id.with("marker_drag");
+        let marker_drag = ui.data(|data| data.get_temp::<(MarkerId, TimeTick)>(marker_drag_id));
+        if ui.is_rect_visible(rect) {
+            let preview = self.markers.zip(marker_drag).map(|(markers, (id, time))| {
+                let mut markers = markers.clone();
+                markers.set_time(id, time);
+                markers
+            });
+            self.paint_markers(
+                &ui.painter_at(rect),
+                rect,
+                preview.as_ref().or(self.markers),
+            );
+        }
+
+        let mut result = TimeRulerResponse {
+            response: response.clone(),
+            scrubbed_time: None,
+            commands: Vec::new(),
+            work_range: None,
+            hover_time: response
+                .hover_pos()
+                .or_else(|| response.interact_pointer_pos())
+                .map(|pos| self.space.clipped_to_unit(pos.x)),
+        };
+
+        // Marker dragging takes precedence over scrubbing.
+        let hovered_marker = response.hover_pos().and_then(|pos| self.marker_at(pos.x));
+        if hovered_marker.is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeHorizontal);
+        }
+        if response.drag_started()
+            && let Some(id) = hovered_marker
+            && let Some(marker) = self.markers.and_then(|markers| markers.get(id))
+        {
+            ui.data_mut(|data| data.insert_temp(marker_drag_id, (id, marker.time)));
+        }
+        if let Some((id, time)) =
+            ui.data(|data| data.get_temp::<(MarkerId, TimeTick)>(marker_drag_id))
+        {
+            if response.dragged()
+                && let Some(pos) = response.interact_pointer_pos()
+            {
+                let time = self.space.clipped_to_unit(pos.x);
+                ui.data_mut(|data| data.insert_temp(marker_drag_id, (id, time)));
+            }
+            if response.drag_stopped() {
+                ui.data_mut(|data| data.remove::<(MarkerId, TimeTick)>(marker_drag_id));
+                if self
+                    .markers
+                    .and_then(|markers| markers.get(id))
+                    .is_some_and(|marker| marker.time != time)
+                {
+                    result.commands.push(AnimationCommand::MoveMarker {
+                        marker_id: id,
+                        time,
+                    });
+                }
+            }
+            return result;
+        }
+
+        if self.drag_work_range(ui, &response, hovered_marker.is_none(), &mut result) {
+            return result;
+        }
+
+        if self.markers.is_some() {
+            self.marker_context_menu(ui, &response, hovered_marker, &mut result);
+        }
+
+        let scrubbing = response.clicked() || response.dragged();
+        let scrubbed_time = response
+            .interact_pointer_pos()
+            .filter(|_| scrubbing)
+            .map(|pos| self.space.clipped_to_unit(pos.x));
+
+        if let Some(time) = scrubbed_time {
+            notify_scrub(
+                ui,
+                response.id,
+                self.current_time,
+                time,
+                response.dragged(),
+                &mut self.on_scrub,
+            );
+        } else if response.drag_stopped() {
+            ui.data_mut(|data| data.remove::<TimeTick>(response.id));
+        }
+
+        result.scrubbed_time = scrubbed_time;
+        result
+            .commands
+            .extend(scrubbed_time.map(AnimationCommand::SetCurrentTime));
+        result
+    }
+
+    /// Drag the work range bracket under the pointer.
+    ///
+    /// Returns `true` while a bracket is dragged, which suppresses scrubbing.
+    fn drag_work_range(
+        &self,
+        ui: &Ui,
+        response: &Response,
+        can_grab: bool,
+        result: &mut TimeRulerResponse,
+    ) -> bool {
+        let Some((start, end)) = self.work_range else {
+            return false;
+        };
+        let drag_id = response.id.with("work_range_drag");
+
+        // `true` for the start bracket, `false` for the end bracket.
+        let hovered_edge = response.hover_pos().filter(|_| can_grab).and_then(|pos| {
+            let start_distance = (self.space.unit_to_clipped(start) - pos.x).abs();
+            let end_distance = (self.space.unit_to_clipped(end) - pos.x).abs();
+            if start_distance.min(end_distance) > self.config.interaction.edge_grab {
+                None
+            } else {
+                Some(start_distance <= end_distance)
+            }
+        });
+        if hovered_edge.is_some() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeColumn);
+        }
+        if response.drag_started()
+            && let Some(edge) = hovered_edge
+        {
+            ui.data_mut(|data| data.insert_temp(drag_id, edge));
+        }
+
+        let Some(is_start) = ui.data(|data| data.get_temp::<bool>(drag_id)) else {
+            return false;
+        };
+        if response.dragged()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let time = self.space.clipped_to_unit(pos.x);
+            let (start, end) = if is_start {
+                (time.min(end), end)
+            } else {
+                (start, time.max(start))
+            };
+            result.work_range = Some((start, end));
+            result
+                .commands
+                .push(AnimationCommand::SetWorkRange { start, end });
+        }
+        if response.drag_stopped() {
+            ui.data_mut(|data| data.remove::<bool>(drag_id));
+        }
+        true
+    }
+
+    /// Context menu to add a marker, or delete the right-clicked one.
+    fn marker_context_menu(
+        &self,
+        ui: &mut Ui,
+        response: &Response,
+        hovered_marker: Option<MarkerId>,
+        result: &mut TimeRulerResponse,
+    ) {
+        let context_id = response.id.with("marker_context");
+        if response.secondary_clicked()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            let target = (hovered_marker, self.space.clipped_to_unit(pos.x));
+            ui.data_mut(|data| data.insert_temp(context_id, target));
+        }
+
+        let target: Option<(Option<MarkerId>, TimeTick)> =
+            ui.data(|data| data.get_temp(context_id));
+        response.context_menu(|ui| {
+            let Some((marker, time)) = target else {
+                ui.close();
+                return;
+            };
+            match marker {
+                Some(id) => {
+                    if ui.button("Delete marker").clicked() {
+                        result
+                            .commands
+                            .push(AnimationCommand::RemoveMarker { marker_id: id });
+                        ui.close();
+                    }
+                }
+                None => {
+                    if ui.button("Add marker").clicked()
+                        && let Some(markers) = self.markers
+                    {
+                        let marker = Marker::new(time, markers.next_label());
+                        result.commands.push(AnimationCommand::AddMarker(marker));
+                        ui.close();
+                    }
+                }
+            }
+        });
+    }
+
+    /// Marker within grab distance of screen x.
+    fn marker_at(&self, x: f32) -> Option<MarkerId> {
+        self.markers?
+            .iter()
+            .map(|marker| {
+                (
+                    marker.id,
+                    (self.space.unit_to_clipped(marker.time) - x).abs(),
+                )
+            })
+            .filter(|(_, distance)| *distance <= self.config.interaction.edge_grab)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
+    }
+
     /// Paint the time ruler.
     pub fn paint(&self, painter: &Painter, rect: Rect) {
+        self.paint_markers(painter, rect, self.markers);
+    }
+
+    /// Paint the time ruler with `markers` in place of the shown markers.
+    fn paint_markers(&self, painter: &Painter, rect: Rect, markers: Option<&Markers>) {
         // Background
         painter.rect_filled(rect, 0.0, self.config.background);
 
@@ -114,44 +460,21 @@ impl<'a> TimeRuler<'a> {
             }
             t += major_interval;
         }
-    }
 
-    /// Calculate tick intervals based on zoom level.
-    fn calculate_intervals(&self) -> (f64, usize) {
-        let ppu = self.space.pixels_per_unit;
-
-        // Target ~80-150 pixels between major ticks
-        let target_pixels = 100.0;
-        let ideal_interval = target_pixels / ppu;
-
-        // Snap to nice intervals
-        let nice_intervals = [
-            0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0,
-            30.0, 60.0, 120.0, 300.0, 600.0,
-        ];
-
-        let mut major_interval = 1.0;
-        for &interval in &nice_intervals {
-            if interval >= ideal_interval {
-                major_interval = interval;
-                break;
-            }
+        if let Some(range) = self.work_range {
+            draw_work_range(painter, rect, self.space, range);
+            draw_work_range_brackets(painter, rect, self.space, range, self.config.text_color);
         }
 
-        // Minor tick count
-        let minor_count = if major_interval >= 1.0 {
-            if major_interval == 1.0 || major_interval == 2.0 {
-                4
-            } else if major_interval == 5.0 || major_interval == 10.0 {
-                5
-            } else {
-                4
-            }
-        } else {
-            5
-        };
+        if let Some(markers) = markers {
+            draw_markers(painter, rect, self.space, markers, true);
+        }
+    }
 
-        (major_interval, minor_count)
+    /// Calculate tick intervals based on zoom level.
+    fn calculate_intervals(&self) -> (f64, usize) {
+        let intervals = time_intervals(self.space.pixels_per_unit);
+        (intervals.major, intervals.minor_count)
     }
 
     /// Format time for display.
@@ -188,6 +511,121 @@ impl<'a> TimeRuler<'a> {
     }
 }
 
+/// Draw markers as vertical lines, with labeled flags at the top if `flags`.
+pub fn draw_markers(
+    painter: &Painter,
+    rect: Rect,
+    space: &SpaceTransform,
+    markers: &Markers,
+    flags: bool,
+) {
+    for marker in markers.iter() {
+        let x = space.unit_to_clipped(marker.time);
+        if x < rect.left() || x > rect.right() {
+            continue;
+        }
+
+        painter.line_segment(
+            [Pos2::new(x, rect.top()), Pos2::new(x, rect.bottom())],
+            Stroke::new(
+                1.0,
+                marker.color.linear_multiply(if flags { 1.0 } else { 0.5 }),
+            ),
+        );
+        if flags {
+            painter.add(egui::Shape::convex_polygon(
+                vec![
+                    Pos2::new(x, rect.top()),
+                    Pos2::new(x + MARKER_FLAG_SIZE, rect.top() + MARKER_FLAG_SIZE * 0.5),
+                    Pos2::new(x, rect.top() + MARKER_FLAG_SIZE),
+                ],
+                marker.color,
+                Stroke::NONE,
+            ));
+            painter.text(
+                Pos2::new(x + MARKER_FLAG_SIZE + 2.0, rect.top()),
+                egui::Align2::LEFT_TOP,
+                &marker.label,
+                egui::FontId::proportional(10.0),
+                marker.color,
+            );
+        }
+    }
+}
+
+/// Shade the parts of `rect` outside the `(start, end)` work range.
+pub fn draw_work_range(
+    painter: &Painter,
+    rect: Rect,
+    space: &SpaceTransform,
+    (start, end): (TimeTick, TimeTick),
+) {
+    let start_x = space
+        .unit_to_clipped(start)
+        .clamp(rect.left(), rect.right());
+    let end_x = space.unit_to_clipped(end).clamp(rect.left(), rect.right());
+
+    if start_x > rect.left() {
+        painter.rect_filled(
+            Rect::from_x_y_ranges(rect.left()..=start_x, rect.y_range()),
+            0.0,
+            OUTSIDE_WORK_RANGE,
+        );
+    }
+    if end_x < rect.right() {
+        painter.rect_filled(
+            Rect::from_x_y_ranges(end_x..=rect.right(), rect.y_range()),
+            0.0,
+            OUTSIDE_WORK_RANGE,
+        );
+    }
+}
+
+/// Draw `[` and `]` brackets at the work range boundaries.
+fn draw_work_range_brackets(
+    painter: &Painter,
+    rect: Rect,
+    space: &SpaceTransform,
+    (start, end): (TimeTick, TimeTick),
+    color: Color32,
+) {
+    let stroke = Stroke::new(2.0, color);
+    let top = rect.top() + 2.0;
+    let bottom = rect.bottom() - 1.0;
+    for (time, direction) in [(start, 1.0), (end, -1.0)] {
+        let x = space.unit_to_clipped(time);
+        if x < rect.left() || x > rect.right() {
+            continue;
+        }
+        let tip = x + direction * BRACKET_WIDTH;
+        painter.line(
+            vec![
+                Pos2::new(tip, top),
+                Pos2::new(x, top),
+                Pos2::new(x, bottom),
+                Pos2::new(tip, bottom),
+            ],
+            stroke,
+        );
+    }
+}
+
+/// Interval between major grid lines at the given zoom level.
+///
+/// The smallest nice interval in seconds at least [`TIME_TICK_SPACING`]
+/// pixels wide, see [`nice_intervals`].
+pub fn grid_interval(pixels_per_unit: f64) -> f64 {
+    time_intervals(pixels_per_unit).major
+}
+
+/// Major and minor time intervals at the given zoom level.
+fn time_intervals(pixels_per_unit: f64) -> NiceIntervals {
+    nice_intervals(
+        TIME_TICK_SPACING / pixels_per_unit,
+        IntervalContext::Seconds,
+    )
+}
+
 /// Draw vertical grid lines in the track area.
 pub fn draw_time_grid(
     painter: &Painter,
@@ -196,22 +634,7 @@ pub fn draw_time_grid(
     color: Color32,
     fps: Option<f32>,
 ) {
-    let ppu = space.pixels_per_unit;
-    let target_pixels = 100.0;
-    let ideal_interval = target_pixels / ppu;
-
-    let nice_intervals = [
-        0.001, 0.002, 0.005, 0.01, 0.02, 0.05, 0.1, 0.2, 0.25, 0.5, 1.0, 2.0, 5.0, 10.0, 15.0,
-        30.0, 60.0, 120.0, 300.0, 600.0,
-    ];
-
-    let mut major_interval = 1.0;
-    for &interval in &nice_intervals {
-        if interval >= ideal_interval {
-            major_interval = interval;
-            break;
-        }
-    }
+    let major_interval = grid_interval(space.pixels_per_unit);
 
     let (start, end) = space.visible_range();
     let start_val = start.value();
@@ -0,0 +1,409 @@
+//! Composite timeline panel: time ruler, DopeSheet and curve editor.
+
+use crate::HashSet;
+use crate::core::keyframe::KeyframeId;
+use crate::core::marker::Markers;
+use crate::core::track::TrackId;
+use crate::dopesheet::{DopeSheet, DopeSheetConfig, DopeSheetResponse};
+use crate::traits::{AnimationCommand, AnimationDataProvider, KeyframeSource, ProviderTrack};
+use crate::widgets::curve_editor::{CurveEditorConfig, CurveEditorResponse, display_range};
+use crate::widgets::curve_editor_state::CurveEditorState;
+use crate::widgets::time_ruler::{TimeRuler, TimeRulerConfig, TimeRulerResponse};
+use crate::widgets::value_ruler::{ValueRuler, ValueRulerConfig};
+use crate::{SpaceTransform, TimeTick};
+use egui::{Color32, Id, Pos2, Rect, Sense, Ui, UiBuilder};
+
+/// Configuration for the timeline panel.
+#[derive(Debug, Clone)]
+pub struct TimelinePanelConfig {
+    /// Configuration of the time ruler at the top.
+    pub ruler: TimeRulerConfig,
+    /// Configuration of the DopeSheet. Its `tree_width` is also the width of
+    /// the value ruler left of the curve editor.
+    pub dope_sheet: DopeSheetConfig,
+    /// Configuration of the curve editor. Its height follows the splitter.
+    pub curve_editor: CurveEditorConfig,
+    /// Configuration of the value ruler left of the curve editor.
+    pub value_ruler: ValueRulerConfig,
+    /// Initial fraction of the height below the ruler given to the curve
+    /// editor.
+    pub curve_fraction: f32,
+    /// Height of the splitter between DopeSheet and curve editor.
+    pub splitter_height: f32,
+    /// Minimum height of the DopeSheet and the curve editor.
+    pub min_pane_height: f32,
+    /// Color of the splitter.
+    pub splitter_color: Color32,
+}
+
+impl Default for TimelinePanelConfig {
+    fn default() -> Self {
+        Self {
+            ruler: TimeRulerConfig::default(),
+            dope_sheet: DopeSheetConfig::default(),
+            curve_editor: CurveEditorConfig {
+                value_labels: false,
+                ..Default::default()
+            },
+            value_ruler: ValueRulerConfig::default(),
+            curve_fraction: 0.4,
+            splitter_height: 6.0,
+            min_pane_height: 40.0,
+            splitter_color: Color32::from_gray(60),
+        }
+    }
+}
+
+/// Response from a [`TimelinePanel`].
+pub struct TimelinePanelResponse {
+    /// Response of the time ruler.
+    pub ruler: TimeRulerResponse,
+    /// Response of the DopeSheet.
+    pub dope_sheet: DopeSheetResponse,
+    /// Response of the curve editor, when a track is shown in it.
+    pub curve_editor: Option<CurveEditorResponse>,
+    /// Track shown in the curve editor.
+    pub curve_track: Option<TrackId>,
+    /// Time axis after panning or zooming this frame. Store it and pass it
+    /// back next frame.
+    pub space: Option<SpaceTransform>,
+    /// Commands of the ruler, the DopeSheet and the curve editor, in that
+    /// order, with an [`AnimationCommand::InsertKeyframePreserving`] on the
+    /// curve track last for a Ctrl/Cmd+click on the curve or the insert key
+    /// at playhead shortcut.
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Timeline panel widget.
+///
+/// The layout animation tools end up building by hand: a [`TimeRuler`] on
+/// top, a [`DopeSheet`] below it and a
+/// [`CurveEditor`](crate::CurveEditor) for the selected track at the bottom,
+/// separated by a draggable splitter. All parts share one time axis, so
+/// panning or zooming any of them scrolls the others along.
+///
+/// ```text
+/// +--------------+------------------------------+
+/// |              | 0    1    2    3    4         |  <- TimeRuler
+/// +--------------+------------------------------+
+/// | ▼ Object A   | [◆]-----[◆]                  |  <- DopeSheet
+/// |   Position   | [◆]-----[◆]                  |
+/// +==============+==============================+  <- splitter
+/// |          10  |      ___                     |  <- ValueRuler and
+/// |           0  | ____/   \____                |     CurveEditor
+/// +--------------+------------------------------+
+/// ```
+///
+/// The curve editor shows the first selected track row, or else the first
+/// track with a selected keyframe, at track-local time. Its value view is
+/// kept per track in egui memory.
+pub struct TimelinePanel<'a, P: AnimationDataProvider> {
+    provider: &'a P,
+    space: &'a SpaceTransform,
+    selected_keyframes: &'a HashSet<KeyframeId>,
+    selected_rows: &'a HashSet<String>,
+    config: TimelinePanelConfig,
+    fps: Option<f32>,
+    markers: Option<&'a Markers>,
+    work_range: Option<(TimeTick, TimeTick)>,
+    id_source: Option<Id>,
+}
+
+impl<'a, P: AnimationDataProvider> TimelinePanel<'a, P> {
+    /// Create a new timeline panel.
+    pub fn new(
+        provider: &'a P,
+        space: &'a SpaceTransform,
+        selected_keyframes: &'a HashSet<KeyframeId>,
+        selected_rows: &'a HashSet<String>,
+    ) -> Self {
+        Self {
+            provider,
+            space,
+            selected_keyframes,
+            selected_rows,
+            config: TimelinePanelConfig::default(),
+            fps: None,
+            markers: None,
+            work_range: None,
+            id_source: None,
+        }
+    }
+
+    /// Set the configuration.
+    pub fn config(mut self, config: TimelinePanelConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set FPS for frame ticks in the ruler.
+    pub fn fps(mut self, fps: f32) -> Self {
+        self.fps = Some(fps);
+        self
+    }
+
+    /// Show markers in the ruler and the DopeSheet.
+    pub fn markers(mut self, markers: &'a Markers) -> Self {
+        self.markers = Some(markers);
+        self
+    }
+
+    /// Shade times outside the `(start, end)` work range.
+    pub fn work_range(mut self, start: impl Into<TimeTick>, end: impl Into<TimeTick>) -> Self {
+        self.work_range = Some((start.into(), end.into()));
+        self
+    }
+
+    /// Set a custom ID source, to tell several panels apart.
+    pub fn id_source(mut self, id: impl std::hash::Hash) -> Self {
+        self.id_source = Some(Id::new(id));
+        self
+    }
+
+    /// Show the panel in the available space.
+    pub fn show(self, ui: &mut Ui) -> TimelinePanelResponse {
+        let id = self
+            .id_source
+            .unwrap_or_else(|| ui.make_persistent_id("timeline_panel"));
+        let (rect, _) = ui.allocate_exact_size(ui.available_size(), Sense::hover());
+
+        // All parts map time through one transform spanning the track column.
+        let track_left = rect.left() + self.config.dope_sheet.tree_width;
+        let mut space = *self.space;
+        space.left_padding = track_left;
+        space.visible_width = (rect.right() - track_left).max(1.0);
+
+        let ruler_rect = Rect::from_min_max(
+            Pos2::new(track_left, rect.top()),
+            Pos2::new(rect.right(), rect.top() + self.config.ruler.height),
+        );
+        ui.painter().rect_filled(
+            Rect::from_min_max(rect.left_top(), Pos2::new(track_left, ruler_rect.bottom())),
+            0.0,
+            self.config.ruler.background,
+        );
+        let ruler = self.show_ruler(ui, &space, ruler_rect);
+
+        let body = Rect::from_min_max(Pos2::new(rect.left(), ruler_rect.bottom()), rect.max);
+        let (dope_rect, curve_rect) = self.show_splitter(ui, id, body);
+
+        let mut dope_ui = ui.new_child(UiBuilder::new().max_rect(dope_rect));
+        let mut dope_sheet = DopeSheet::new(
+            self.provider,
+            &space,
+            self.selected_keyframes,
+            self.selected_rows,
+        )
+        .config(self.config.dope_sheet.clone())
+        .id_source(id.with("dope_sheet"));
+        if let Some(markers) = self.markers {
+            dope_sheet = dope_sheet.markers(markers);
+        }
+        if let Some((start, end)) = self.work_range {
+            dope_sheet = dope_sheet.work_range(start, end);
+        }
+        let dope_sheet = dope_sheet.show(&mut dope_ui);
+
+        let curve_track = self.curve_track();
+        let (curve_editor, curve_space) = match curve_track {
+            Some(track_id) => {
+                let (response, space) =
+                    self.show_curve_editor(ui, id, &space, curve_rect, track_id, ruler.hover_time);
+                (Some(response), space)
+            }
+            None => {
+                self.paint_empty_curve_area(ui, curve_rect);
+                (None, space)
+            }
+        };
+
+        // Horizontal scrolling and zooming over the ruler or the DopeSheet.
+        let mut new_space = curve_space;
+        let upper_rect =
+            Rect::from_min_max(ruler_rect.min, Pos2::new(rect.right(), dope_rect.bottom()));
+        if let Some(pos) = ui.ctx().pointer_hover_pos()
+            && upper_rect.contains(pos)
+        {
+            let (zoom, scroll) = ui.input(|i| (i.zoom_delta(), i.smooth_scroll_delta.x));
+            if zoom != 1.0 {
+                new_space = new_space.zoom_at(pos.x, zoom as f64);
+            }
+            if scroll != 0.0 {
+                new_space = new_space.pan(scroll);
+            }
+        }
+
+        let mut commands = ruler.commands.clone();
+        commands.extend(dope_sheet.commands.iter().cloned());
+        if let Some(response) = &curve_editor {
+            commands.extend(response.commands.iter().cloned());
+            if let (Some(position), Some(track_id)) = (response.insert_keyframe_at, curve_track) {
+                commands.push(AnimationCommand::InsertKeyframePreserving {
+                    track_id,
+                    keyframe_id: KeyframeId::new(),
+                    position,
+                });
+            }
+        }
+
+        TimelinePanelResponse {
+            ruler,
+            dope_sheet,
+            curve_editor,
+            curve_track,
+            space: (new_space != space).then_some(new_space),
+            commands,
+        }
+    }
+
+    fn show_ruler(&self, ui: &mut Ui, space: &SpaceTransform, rect: Rect) -> TimeRulerResponse {
+        let mut ruler = TimeRuler::new(space)
+            .config(self.config.ruler.clone())
+            .current_time(self.provider.current_time());
+        if let Some(fps) = self.fps {
+            ruler = ruler.fps(fps);
+        }
+        if let Some(markers) = self.markers {
+            ruler = ruler.markers(markers);
+        }
+        if let Some((start, end)) = self.work_range {
+            ruler = ruler.work_range(start, end);
+        }
+        ruler.show(ui, rect)
+    }
+
+    /// Handle the splitter in `body` and return the DopeSheet and curve
+    /// editor rects.
+    fn show_splitter(&self, ui: &mut Ui, id: Id, body: Rect) -> (Rect, Rect) {
+        let split_id = id.with("curve_fraction");
+        let mut fraction = ui
+            .data(|data| data.get_temp::<f32>(split_id))
+            .unwrap_or(self.config.curve_fraction);
+
+        let splitter_rect = |fraction: f32| {
+            let half = self.config.splitter_height / 2.0;
+            let min = self.config.min_pane_height;
+            let y = (body.bottom() - fraction * body.height()).clamp(
+                body.top() + min + half,
+                (body.bottom() - min - half).max(body.top()),
+            );
+            Rect::from_min_max(
+                Pos2::new(body.left(), y - half),
+                Pos2::new(body.right(), y + half),
+            )
+        };
+
+        let response = ui.interact(splitter_rect(fraction), id.with("splitter"), Sense::drag());
+        if response.hovered() || response.dragged() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeVertical);
+        }
+        if response.dragged()
+            && let Some(pos) = response.interact_pointer_pos()
+        {
+            fraction = ((body.bottom() - pos.y) / body.height().max(1.0)).clamp(0.0, 1.0);
+            ui.data_mut(|data| data.insert_temp(split_id, fraction));
+        }
+
+        let splitter = splitter_rect(fraction);
+        let color = if response.hovered() || response.dragged() {
+            self.config.splitter_color.gamma_multiply(1.5)
+        } else {
+            self.config.splitter_color
+        };
+        ui.painter().rect_filled(splitter, 0.0, color);
+
+        (
+            Rect::from_min_max(body.min, Pos2::new(body.right(), splitter.top())),
+            Rect::from_min_max(Pos2::new(body.left(), splitter.bottom()), body.max),
+        )
+    }
+
+    /// Track shown in the curve editor: the first selected track row, or
+    /// else the first track with a selected keyframe.
+    fn curve_track(&self) -> Option<TrackId> {
+        let rows = self.provider.property_rows();
+        rows.iter()
+            .filter(|row| self.selected_rows.contains(&row.id))
+            .find_map(|row| row.track_id)
+            .or_else(|| {
+                rows.iter().filter_map(|row| row.track_id).find(|track_id| {
+                    self.provider
+                        .keyframe_positions(*track_id)
+                        .is_some_and(|positions| {
+                            positions
+                                .iter()
+                                .any(|(id, _)| self.selected_keyframes.contains(id))
+                        })
+                })
+            })
+    }
+
+    /// Show the value ruler and curve editor for `track_id` in `rect`, with
+    /// the value at `preview_time` marked, and return the time axis after the
+    /// editor's pan and zoom.
+    fn show_curve_editor(
+        &self,
+        ui: &mut Ui,
+        id: Id,
+        space: &SpaceTransform,
+        rect: Rect,
+        track_id: TrackId,
+        preview_time: Option<TimeTick>,
+    ) -> (CurveEditorResponse, SpaceTransform) {
+        let source = ProviderTrack::new(self.provider, track_id);
+        let state_id = id.with(("curve_view", track_id));
+        let mut state = ui
+            .data(|data| data.get_temp::<CurveEditorState>(state_id))
+            .unwrap_or_else(|| CurveEditorState::new(*space, display_range(source.value_range())));
+        state.space = *space;
+
+        let ruler_rect = Rect::from_min_max(rect.min, Pos2::new(space.left_padding, rect.bottom()));
+        let editor_rect = Rect::from_min_max(Pos2::new(space.left_padding, rect.top()), rect.max);
+
+        let mut config = self.config.curve_editor.clone();
+        config.height = editor_rect.height();
+        let current_time = self.provider.current_time();
+        let work_range = self.work_range;
+        let unit = self.provider.track_unit(track_id);
+        let mut editor_ui = ui.new_child(UiBuilder::new().max_rect(editor_rect));
+        let response =
+            state.show_with(&mut editor_ui, &source, self.selected_keyframes, |editor| {
+                let editor = editor
+                    .config(config)
+                    .id_source(id.with("curve_editor"))
+                    .current_time(current_time)
+                    .preview_time(preview_time)
+                    .unit(unit);
+                match work_range {
+                    Some((start, end)) => editor.work_range(start, end),
+                    None => editor,
+                }
+            });
+
+        let value_ruler = ValueRuler::new(state.value_range)
+            .config(ValueRulerConfig {
+                vertical_padding: self.config.curve_editor.vertical_padding,
+                ..self.config.value_ruler.clone()
+            })
+            .unit(unit)
+            .show(ui, ruler_rect);
+        if let Some(range) = value_ruler.value_range {
+            state.value_range = range;
+        }
+
+        ui.data_mut(|data| data.insert_temp(state_id, state.clone()));
+        (response, state.space)
+    }
+
+    fn paint_empty_curve_area(&self, ui: &Ui, rect: Rect) {
+        let painter = ui.painter_at(rect);
+        painter.rect_filled(rect, 0.0, self.config.curve_editor.background);
+        painter.text(
+            rect.center(),
+            egui::Align2::CENTER_CENTER,
+            "Select a track to edit its curve",
+            egui::FontId::proportional(12.0),
+            Color32::from_gray(120),
+        );
+    }
+}
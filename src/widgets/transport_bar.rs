@@ -0,0 +1,166 @@
+//! Transport bar with playback buttons and a current-time readout.
+
+use crate::TimeTick;
+use crate::playback::{LoopMode, PlaybackController, PlaybackDirection};
+use crate::traits::AnimationCommand;
+use crate::transport::step_frames;
+use egui::{Response, Ui};
+
+/// Response from the [`TransportBar`].
+pub struct TransportBarResponse {
+    /// The egui response for the bar.
+    pub response: Response,
+    /// Animation commands to execute (a
+    /// [`AnimationCommand::SetCurrentTime`] when a button moved the time).
+    pub commands: Vec<AnimationCommand>,
+}
+
+/// Row of transport buttons driving a [`PlaybackController`].
+///
+/// Buttons, left to right: go to start, step back, play backward, play
+/// forward/pause, stop, step forward, go to end and the loop mode toggle,
+/// followed by the current time.
+pub struct TransportBar<'a> {
+    playback: &'a mut PlaybackController,
+    fps: f64,
+    show_frames: bool,
+}
+
+impl<'a> TransportBar<'a> {
+    /// Create a transport bar for the given controller and frame rate.
+    pub fn new(playback: &'a mut PlaybackController, fps: f64) -> Self {
+        Self {
+            playback,
+            fps,
+            show_frames: true,
+        }
+    }
+
+    /// Display the current time in frames (default) or seconds.
+    pub fn show_frames(mut self, show_frames: bool) -> Self {
+        self.show_frames = show_frames;
+        self
+    }
+
+    /// Show the transport bar.
+    pub fn show(self, ui: &mut Ui) -> TransportBarResponse {
+        let playback = self.playback;
+        let before = playback.time();
+        let (start, end) = playback.ordered_range();
+
+        let inner = ui.horizontal(|ui| {
+            if ui.button("⏮").on_hover_text("Go to start").clicked() {
+                playback.pause();
+                playback.set_time(start);
+            }
+            if ui.button("⏪").on_hover_text("Step back").clicked() {
+                playback.pause();
+                playback.set_time(step_frames(playback.time(), self.fps, -1).max(start));
+            }
+
+            let playing_backward =
+                playback.is_playing() && playback.direction() == PlaybackDirection::Backward;
+            let playing_forward =
+                playback.is_playing() && playback.direction() == PlaybackDirection::Forward;
+
+            if ui
+                .selectable_label(playing_backward, "◀")
+                .on_hover_text("Play backward")
+                .clicked()
+            {
+                play_or_pause(playback, PlaybackDirection::Backward, playing_backward);
+            }
+            let (label, hover) = if playing_forward {
+                ("⏸", "Pause")
+            } else {
+                ("▶", "Play")
+            };
+            if ui
+                .selectable_label(playing_forward, label)
+                .on_hover_text(hover)
+                .clicked()
+            {
+                play_or_pause(playback, PlaybackDirection::Forward, playing_forward);
+            }
+            if ui.button("⏹").on_hover_text("Stop").clicked() {
+                playback.stop();
+            }
+
+            if ui.button("⏩").on_hover_text("Step forward").clicked() {
+                playback.pause();
+                playback.set_time(step_frames(playback.time(), self.fps, 1).min(end));
+            }
+            if ui.button("⏭").on_hover_text("Go to end").clicked() {
+                playback.pause();
+                playback.set_time(end);
+            }
+
+            ui.separator();
+
+            let loop_icon = match playback.loop_mode {
+                LoopMode::Once => "→",
+                LoopMode::Loop => "🔁",
+                LoopMode::PingPong => "⇄",
+            };
+            if ui
+                .button(loop_icon)
+                .on_hover_text(playback.loop_mode.name())
+                .clicked()
+            {
+                playback.loop_mode = next_loop_mode(playback.loop_mode);
+            }
+
+            ui.separator();
+
+            ui.monospace(format_time(playback.time(), self.fps, self.show_frames));
+        });
+
+        let mut commands = Vec::new();
+        if playback.time() != before {
+            commands.push(AnimationCommand::SetCurrentTime(playback.time()));
+        }
+
+        TransportBarResponse {
+            response: inner.response,
+            commands,
+        }
+    }
+}
+
+/// Pause if already playing in `direction`, otherwise play in it.
+fn play_or_pause(playback: &mut PlaybackController, direction: PlaybackDirection, active: bool) {
+    if active {
+        playback.pause();
+    } else {
+        playback.set_direction(direction);
+        playback.play();
+    }
+}
+
+/// The loop mode following `mode` in [`LoopMode::all`].
+fn next_loop_mode(mode: LoopMode) -> LoopMode {
+    let all = LoopMode::all();
+    let index = all.iter().position(|m| *m == mode).unwrap_or_default();
+    all[(index + 1) % all.len()]
+}
+
+/// Format a time as whole frames or seconds with two decimals.
+fn format_time(time: TimeTick, fps: f64, show_frames: bool) -> String {
+    if show_frames {
+        format!("{:>5}", time.to_frames(fps).round() as i64)
+    } else {
+        format!("{:>7.2}s", time.value())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loop_mode_cycles() {
+        assert_eq!(next_loop_mode(LoopMode::Once), LoopMode::Loop);
+        assert_eq!(next_loop_mode(LoopMode::Loop), LoopMode::PingPong);
+        assert_eq!(next_loop_mode(LoopMode::PingPong), LoopMode::Once);
+    }
+}
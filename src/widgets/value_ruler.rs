@@ -0,0 +1,258 @@
+//! Value ruler widget for the vertical axis of curve editors.
+
+use crate::intervals::{IntervalContext, NiceIntervals, VALUE_TICK_SPACING, nice_intervals};
+use crate::traits::ValueUnit;
+use egui::{Color32, Painter, Pos2, Rect, Response, Sense, Stroke, Ui};
+
+/// Configuration for the value ruler.
+#[derive(Debug, Clone)]
+pub struct ValueRulerConfig {
+    /// Width of the ruler in pixels.
+    pub width: f32,
+    /// Major tick length.
+    pub major_tick_length: f32,
+    /// Minor tick length.
+    pub minor_tick_length: f32,
+    /// Text color.
+    pub text_color: Color32,
+    /// Tick color.
+    pub tick_color: Color32,
+    /// Background color.
+    pub background: Color32,
+    /// Padding at top/bottom; must match the curve editor's.
+    pub vertical_padding: f32,
+}
+
+impl Default for ValueRulerConfig {
+    fn default() -> Self {
+        Self {
+            width: 48.0,
+            major_tick_length: 8.0,
+            minor_tick_length: 4.0,
+            text_color: Color32::from_gray(180),
+            tick_color: Color32::from_gray(100),
+            background: Color32::from_gray(30),
+            vertical_padding: 20.0,
+        }
+    }
+}
+
+/// Response from a [`ValueRuler`].
+pub struct ValueRulerResponse {
+    /// The egui response for the ruler.
+    pub response: Response,
+    /// Value range after panning or zooming this frame.
+    pub value_range: Option<(f32, f32)>,
+}
+
+/// Value ruler widget.
+///
+/// Strip left of a [`CurveEditor`](crate::CurveEditor) labeling its value
+/// axis, analogous to the [`TimeRuler`](super::TimeRuler) for time. Drag it
+/// to pan the value range and scroll over it to zoom around the pointer. The
+/// new range is reported in [`ValueRulerResponse::value_range`].
+pub struct ValueRuler {
+    value_range: (f32, f32),
+    config: ValueRulerConfig,
+    unit: ValueUnit,
+}
+
+impl ValueRuler {
+    /// Create a new value ruler for the `(min, max)` range shown next to it.
+    pub fn new(value_range: (f32, f32)) -> Self {
+        Self {
+            value_range,
+            config: ValueRulerConfig::default(),
+            unit: ValueUnit::Generic,
+        }
+    }
+
+    /// Set the configuration.
+    pub fn config(mut self, config: ValueRulerConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the unit of the values, which picks the tick steps and label
+    /// suffix.
+    pub fn unit(mut self, unit: ValueUnit) -> Self {
+        self.unit = unit;
+        self
+    }
+
+    /// Paint the ruler in `rect` and handle panning and zooming.
+    pub fn show(self, ui: &mut Ui, rect: Rect) -> ValueRulerResponse {
+        let response = ui.allocate_rect(rect, Sense::drag());
+
+        if ui.is_rect_visible(rect) {
+            self.paint(&ui.painter_at(rect), rect);
+        }
+
+        let mut result = ValueRulerResponse {
+            response: response.clone(),
+            value_range: None,
+        };
+
+        let (min, max) = self.value_range;
+        let usable_height = rect.height() - 2.0 * self.config.vertical_padding;
+        if usable_height <= 0.0 {
+            return result;
+        }
+        let value_per_pixel = (max - min) / usable_height;
+
+        if response.hovered() {
+            ui.ctx().set_cursor_icon(egui::CursorIcon::ResizeVertical);
+        }
+
+        if response.dragged() {
+            // Dragging down moves the view to higher values under the pointer.
+            let delta = response.drag_delta().y * value_per_pixel;
+            result.value_range = Some((min + delta, max + delta));
+        } else if response.hovered()
+            && let Some(pos) = response.hover_pos()
+        {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                let factor = (-scroll * 0.005).exp();
+                let center = self.y_to_value(rect, pos.y);
+                result.value_range = Some(zoom_range(self.value_range, center, factor));
+            }
+        }
+
+        result
+    }
+
+    fn paint(&self, painter: &Painter, rect: Rect) {
+        painter.rect_filled(rect, 0.0, self.config.background);
+
+        let (min, max) = self.value_range;
+        let usable_height = rect.height() - 2.0 * self.config.vertical_padding;
+        if max - min <= 0.0 || usable_height <= 0.0 {
+            return;
+        }
+
+        let intervals = value_grid_intervals(max - min, usable_height, self.unit);
+        let interval = intervals.major as f32;
+        let minor = intervals.minor() as f32;
+        let decimals = interval_decimals(interval);
+        let tick_stroke = Stroke::new(1.0, self.config.tick_color);
+
+        let mut index = (min / minor).ceil() as i64;
+        loop {
+            let value = index as f32 * minor;
+            if value > max {
+                break;
+            }
+            let y = self.value_to_y(rect, value);
+            let major = index % intervals.minor_count.max(1) as i64 == 0;
+            let length = if major {
+                self.config.major_tick_length
+            } else {
+                self.config.minor_tick_length
+            };
+            painter.line_segment(
+                [
+                    Pos2::new(rect.right() - length, y),
+                    Pos2::new(rect.right(), y),
+                ],
+                tick_stroke,
+            );
+            if major {
+                painter.text(
+                    Pos2::new(rect.right() - length - 3.0, y),
+                    egui::Align2::RIGHT_CENTER,
+                    self.unit.format(value as f64, decimals),
+                    egui::FontId::proportional(10.0),
+                    self.config.text_color,
+                );
+            }
+            index += 1;
+        }
+
+        painter.line_segment(
+            [rect.right_top(), rect.right_bottom()],
+            Stroke::new(1.0, self.config.tick_color),
+        );
+    }
+
+    fn value_to_y(&self, rect: Rect, value: f32) -> f32 {
+        super::curve_editor::value_to_y(rect, self.config.vertical_padding, self.value_range, value)
+    }
+
+    fn y_to_value(&self, rect: Rect, y: f32) -> f32 {
+        super::curve_editor::y_to_value(rect, self.config.vertical_padding, self.value_range, y)
+    }
+}
+
+/// Interval between major value grid lines for a range of `span` values
+/// drawn over `pixels`.
+///
+/// The smallest 1, 2 or 5 times a power of ten that keeps lines at least
+/// [`VALUE_TICK_SPACING`] pixels apart.
+pub fn value_grid_interval(span: f32, pixels: f32) -> f32 {
+    value_grid_intervals(span, pixels, ValueUnit::Generic).major as f32
+}
+
+/// Major and minor value grid intervals for a range of `span` values in
+/// `unit` drawn over `pixels`.
+///
+/// The smallest interval nice for the unit, see
+/// [`ValueUnit::interval_context`], that keeps lines at least
+/// [`VALUE_TICK_SPACING`] pixels apart.
+pub fn value_grid_intervals(span: f32, pixels: f32, unit: ValueUnit) -> NiceIntervals {
+    if span <= 0.0 || pixels <= 0.0 {
+        return nice_intervals(1.0, IntervalContext::Decimal);
+    }
+    let ideal = span as f64 * VALUE_TICK_SPACING / pixels as f64;
+    nice_intervals(ideal, unit.interval_context())
+}
+
+/// Number of decimals needed to label multiples of `interval`.
+pub(crate) fn interval_decimals(interval: f32) -> usize {
+    // Fewest decimals that represent the interval, e.g. two for `0.25`; the
+    // tolerance absorbs `f32` rounding.
+    (0..6)
+        .find(|decimals| {
+            let scaled = interval as f64 * 10f64.powi(*decimals);
+            (scaled - scaled.round()).abs() < 1e-3 * scaled.max(1.0)
+        })
+        .unwrap_or(6) as usize
+}
+
+/// `range` zoomed by `factor` around `center`; a factor above one zooms in.
+pub(crate) fn zoom_range((min, max): (f32, f32), center: f32, factor: f32) -> (f32, f32) {
+    (
+        center - (center - min) / factor,
+        center + (max - center) / factor,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grid_interval_is_nice() {
+        // 0..1000 over 400 px: at least 100 per 40 px.
+        assert_eq!(value_grid_interval(1000.0, 400.0), 100.0);
+        // 0..1 over 400 px: at least 0.1.
+        assert!((value_grid_interval(1.0, 400.0) - 0.1).abs() < 1e-6);
+        // 0..30 over 400 px: at least 3, so 5.
+        assert_eq!(value_grid_interval(30.0, 400.0), 5.0);
+        // 0..360 degrees over 400 px: at least 36, so 45 rather than 50.
+        let degrees = value_grid_intervals(360.0, 400.0, ValueUnit::Degrees);
+        assert_eq!((degrees.major, degrees.minor_count), (45.0, 3));
+        assert_eq!(ValueUnit::Degrees.format(45.0, 0), "45°");
+
+        assert_eq!(interval_decimals(100.0), 0);
+        assert_eq!(interval_decimals(0.5), 1);
+        assert_eq!(interval_decimals(0.02), 2);
+        assert_eq!(interval_decimals(0.25), 2);
+    }
+
+    #[test]
+    fn zoom_keeps_center() {
+        let (min, max) = zoom_range((0.0, 10.0), 2.0, 2.0);
+        assert_eq!((min, max), (1.0, 6.0));
+    }
+}